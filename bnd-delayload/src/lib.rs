@@ -0,0 +1,249 @@
+//! Lazy loading for bnd-generated FFI bindings of optional libraries.
+//!
+//! Functions declared with [`link!`] resolve their library with
+//! `dlopen` (`LoadLibraryA` on Windows) and their symbol with `dlsym`
+//! (`GetProcAddress`) on first call, instead of at link time. A binary
+//! using them starts even when the library (libnuma, liburing, ...) is
+//! not installed; check [`Library::is_available`] on the library's
+//! static before calling.
+
+use std::ffi::{CStr, CString, c_void};
+use std::sync::OnceLock;
+
+/// A shared library opened on first use.
+pub struct Library {
+    name: &'static str,
+    handle: OnceLock<Result<usize, String>>,
+}
+
+impl Library {
+    /// A library named as in the metadata: a file name (`libnuma.so.1`)
+    /// or a bare link name (`numa`), which is also tried as
+    /// `libnuma.so` / `libnuma.dylib` / `numa.dll`.
+    pub const fn new(name: &'static str) -> Self {
+        Self {
+            name,
+            handle: OnceLock::new(),
+        }
+    }
+
+    /// The name this library was declared with.
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    /// Open the library if it isn't yet; `true` if that succeeded.
+    pub fn is_available(&self) -> bool {
+        self.handle().is_ok()
+    }
+
+    /// Address of `symbol`, opening the library first if needed.
+    pub fn symbol(&self, symbol: &CStr) -> Result<*const c_void, String> {
+        let handle = self.handle()?;
+        let address = unsafe { sys::symbol(handle as *mut c_void, symbol.as_ptr()) };
+        if address.is_null() {
+            Err(format!(
+                "{}: symbol {} not found",
+                self.name,
+                symbol.to_string_lossy()
+            ))
+        } else {
+            Ok(address)
+        }
+    }
+
+    fn handle(&self) -> Result<usize, String> {
+        self.handle
+            .get_or_init(|| {
+                let mut errors = Vec::new();
+                for candidate in candidates(self.name) {
+                    let file = CString::new(candidate).map_err(|e| e.to_string())?;
+                    let handle = unsafe { sys::open(file.as_ptr()) };
+                    if !handle.is_null() {
+                        return Ok(handle as usize);
+                    }
+                    errors.push(sys::error());
+                }
+                Err(format!(
+                    "{} could not be loaded: {}",
+                    self.name,
+                    errors.join("; ")
+                ))
+            })
+            .clone()
+    }
+}
+
+/// File names to try for `name`: itself, then the platform's spelling of
+/// a bare link name.
+fn candidates(name: &str) -> Vec<String> {
+    let mut names = vec![name.to_string()];
+    if !name.contains(['.', '/', '\\']) {
+        names.push(if cfg!(windows) {
+            format!("{name}.dll")
+        } else if cfg!(target_os = "macos") {
+            format!("lib{name}.dylib")
+        } else {
+            format!("lib{name}.so")
+        });
+    }
+    names
+}
+
+/// A function resolved from a [`Library`] on first use.
+pub struct Symbol {
+    library: &'static Library,
+    /// NUL-terminated symbol name.
+    name: &'static str,
+    address: OnceLock<usize>,
+}
+
+impl Symbol {
+    /// `name` must end with a NUL byte (`"numa_available\0"`).
+    pub const fn new(library: &'static Library, name: &'static str) -> Self {
+        Self {
+            library,
+            name,
+            address: OnceLock::new(),
+        }
+    }
+
+    /// The symbol's address.
+    ///
+    /// # Panics
+    ///
+    /// If the library or the symbol can't be loaded.
+    pub fn get(&self) -> *const c_void {
+        *self.address.get_or_init(|| {
+            let name = CStr::from_bytes_with_nul(self.name.as_bytes())
+                .expect("symbol name must end with a NUL byte");
+            match self.library.symbol(name) {
+                Ok(address) => address as usize,
+                Err(err) => panic!("bnd-delayload: {err}"),
+            }
+        }) as *const c_void
+    }
+}
+
+/// Declares an external function loaded on first call from a library,
+/// named either by a literal, with the same syntax as `bnd_macros::link!`
+/// so generated code can use it through `extern crate bnd_delayload as
+/// windows_link;`, or by the path of a [`Library`] static, whose
+/// [`is_available`](Library::is_available) then tells whether the
+/// function can be called.
+///
+/// # Example
+///
+/// ```
+/// bnd_delayload::link!("libm.so.6" "C" fn cos(x: f64) -> f64);
+/// # #[cfg(target_os = "linux")]
+/// assert_eq!(unsafe { cos(0.0) }, 1.0);
+///
+/// pub static LIBM: bnd_delayload::Library = bnd_delayload::Library::new("libm.so.6");
+/// bnd_delayload::link!(LIBM "C" fn sqrt(x: f64) -> f64);
+/// # #[cfg(target_os = "linux")]
+/// if LIBM.is_available() {
+///     assert_eq!(unsafe { sqrt(4.0) }, 2.0);
+/// }
+/// ```
+#[macro_export]
+macro_rules! link {
+    ($library:literal $abi:literal $($link_name:literal)? fn $name:ident($($arg:ident : $ty:ty),* $(,)?) $(-> $ret:ty)?) => (
+        $crate::__delayload_fn!(
+            {
+                static LIBRARY: $crate::Library = $crate::Library::new($library);
+                &LIBRARY
+            }
+            $abi $($link_name)? fn $name($($arg: $ty),*) $(-> $ret)?
+        );
+    );
+    ($($library:ident)::+ $abi:literal $($link_name:literal)? fn $name:ident($($arg:ident : $ty:ty),* $(,)?) $(-> $ret:ty)?) => (
+        $crate::__delayload_fn!(
+            { &$($library)::+ }
+            $abi $($link_name)? fn $name($($arg: $ty),*) $(-> $ret)?
+        );
+    );
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __delayload_fn {
+    ($library:block $abi:literal $($link_name:literal)? fn $name:ident($($arg:ident : $ty:ty),*) $(-> $ret:ty)?) => (
+        #[allow(clippy::missing_safety_doc)]
+        pub unsafe fn $name($($arg: $ty),*) $(-> $ret)? {
+            static SYMBOL: $crate::Symbol = $crate::Symbol::new(
+                $library,
+                $crate::__symbol_name!($name $($link_name)?),
+            );
+            let function: unsafe extern $abi fn($($ty),*) $(-> $ret)? =
+                unsafe { ::core::mem::transmute(SYMBOL.get()) };
+            unsafe { function($($arg),*) }
+        }
+    )
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __symbol_name {
+    ($name:ident) => {
+        concat!(stringify!($name), "\0")
+    };
+    ($name:ident $link_name:literal) => {
+        concat!($link_name, "\0")
+    };
+}
+
+#[cfg(unix)]
+mod sys {
+    use std::ffi::{CStr, c_char, c_int, c_void};
+
+    const RTLD_NOW: c_int = 2;
+
+    unsafe extern "C" {
+        fn dlopen(filename: *const c_char, flags: c_int) -> *mut c_void;
+        fn dlsym(handle: *mut c_void, symbol: *const c_char) -> *mut c_void;
+        fn dlerror() -> *mut c_char;
+    }
+
+    pub unsafe fn open(file: *const c_char) -> *mut c_void {
+        unsafe { dlopen(file, RTLD_NOW) }
+    }
+
+    pub unsafe fn symbol(handle: *mut c_void, name: *const c_char) -> *const c_void {
+        unsafe { dlsym(handle, name) }
+    }
+
+    pub fn error() -> String {
+        let message = unsafe { dlerror() };
+        if message.is_null() {
+            "unknown error".to_string()
+        } else {
+            unsafe { CStr::from_ptr(message) }
+                .to_string_lossy()
+                .into_owned()
+        }
+    }
+}
+
+#[cfg(windows)]
+mod sys {
+    use std::ffi::{c_char, c_void};
+
+    unsafe extern "system" {
+        fn LoadLibraryA(file: *const c_char) -> *mut c_void;
+        fn GetProcAddress(module: *mut c_void, name: *const c_char) -> *const c_void;
+        fn GetLastError() -> u32;
+    }
+
+    pub unsafe fn open(file: *const c_char) -> *mut c_void {
+        unsafe { LoadLibraryA(file) }
+    }
+
+    pub unsafe fn symbol(handle: *mut c_void, name: *const c_char) -> *const c_void {
+        unsafe { GetProcAddress(handle, name) }
+    }
+
+    pub fn error() -> String {
+        format!("error {}", unsafe { GetLastError() })
+    }
+}
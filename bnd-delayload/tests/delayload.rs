@@ -0,0 +1,58 @@
+//! Resolve libm functions through `link!` at first call.
+
+#![cfg(target_os = "linux")]
+
+use std::ffi::CStr;
+
+mod libm {
+    bnd_delayload::link!("libm.so.6" "C" fn cos(x: f64) -> f64);
+    bnd_delayload::link!("libm.so.6" "C" "pow" fn power(base: f64, exponent: f64) -> f64);
+
+    pub static LIBM: bnd_delayload::Library = bnd_delayload::Library::new("libm.so.6");
+    bnd_delayload::link!(LIBM "C" fn sqrt(x: f64) -> f64);
+}
+
+mod missing {
+    pub static MISSING: bnd_delayload::Library = bnd_delayload::Library::new("bnd_no_such_library");
+    bnd_delayload::link!(self::MISSING "C" fn absent() -> i32);
+}
+
+#[test]
+fn functions_resolve_on_first_call() {
+    assert_eq!(unsafe { libm::cos(0.0) }, 1.0);
+    // Link name different from the Rust name.
+    assert_eq!(unsafe { libm::power(2.0, 10.0) }, 1024.0);
+}
+
+#[test]
+fn functions_share_a_library_static() {
+    assert!(libm::LIBM.is_available());
+    assert_eq!(unsafe { libm::sqrt(4.0) }, 2.0);
+    assert!(!missing::MISSING.is_available());
+    let panic = std::panic::catch_unwind(|| unsafe { missing::absent() }).unwrap_err();
+    let message = panic.downcast_ref::<String>().expect("panic message");
+    assert!(
+        message.contains("bnd_no_such_library could not be loaded"),
+        "{message}"
+    );
+}
+
+#[test]
+fn missing_library_is_reported() {
+    static MISSING: bnd_delayload::Library = bnd_delayload::Library::new("bnd_no_such_library");
+    assert!(!MISSING.is_available());
+    let err = MISSING.symbol(c"anything").unwrap_err();
+    assert!(
+        err.contains("bnd_no_such_library could not be loaded"),
+        "{err}"
+    );
+}
+
+#[test]
+fn missing_symbol_is_reported() {
+    static LIBM: bnd_delayload::Library = bnd_delayload::Library::new("libm.so.6");
+    assert!(LIBM.is_available());
+    let name: &CStr = c"bnd_no_such_symbol";
+    let err = LIBM.symbol(name).unwrap_err();
+    assert!(err.contains("symbol bnd_no_such_symbol not found"), "{err}");
+}
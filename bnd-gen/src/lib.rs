@@ -0,0 +1,204 @@
+//! bnd-gen — run generator crates in dependency order.
+//!
+//! A generator whose config imports another generator's winmd (e.g.
+//! bnd-openssl referencing bnd-linux) must run after it. [`Workspace`]
+//! sorts the registered [`Project`]s topologically and regenerates only
+//! those whose winmd is missing or older than its inputs or dependencies:
+//!
+//! ```no_run
+//! use bnd_gen::{Project, Workspace};
+//!
+//! let mut ws = Workspace::new();
+//! ws.add_project(
+//!     Project::new("bnd-linux", "bnd-linux/winmd/bnd-linux.winmd", || Ok(()))
+//!         .input("bnd-linux-gen/bnd-linux.toml"),
+//! );
+//! ws.add_project(
+//!     Project::new("bnd-openssl", "bnd-openssl/winmd/bnd-openssl.winmd", || Ok(()))
+//!         .input("bnd-openssl-gen/openssl.toml")
+//!         .depends_on("bnd-linux"),
+//! );
+//! ws.generate_all().unwrap();
+//! ```
+
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use anyhow::Result;
+use tracing::{debug, info};
+
+type GenerateFn = Box<dyn Fn() -> Result<()>>;
+
+/// One generator: the winmd it produces and how to produce it.
+pub struct Project {
+    name: String,
+    winmd: PathBuf,
+    inputs: Vec<PathBuf>,
+    depends_on: Vec<String>,
+    always: bool,
+    generate: GenerateFn,
+}
+
+impl Project {
+    /// A project named `name` whose `generate` writes `winmd`.
+    pub fn new(
+        name: impl Into<String>,
+        winmd: impl Into<PathBuf>,
+        generate: impl Fn() -> Result<()> + 'static,
+    ) -> Self {
+        Self {
+            name: name.into(),
+            winmd: winmd.into(),
+            inputs: Vec::new(),
+            depends_on: Vec::new(),
+            always: false,
+            generate: Box::new(generate),
+        }
+    }
+
+    /// A file (config, header) whose change makes the winmd stale.
+    pub fn input(mut self, path: impl Into<PathBuf>) -> Self {
+        self.inputs.push(path.into());
+        self
+    }
+
+    /// Another project that must be generated first.
+    pub fn depends_on(mut self, name: impl Into<String>) -> Self {
+        self.depends_on.push(name.into());
+        self
+    }
+
+    /// Regenerate even when the winmd is up to date — for generators whose
+    /// inputs (system headers) are not tracked.
+    pub fn always(mut self, always: bool) -> Self {
+        self.always = always;
+        self
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn winmd(&self) -> &Path {
+        &self.winmd
+    }
+}
+
+/// A set of projects generated in dependency order.
+#[derive(Default)]
+pub struct Workspace {
+    projects: Vec<Project>,
+}
+
+impl Workspace {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add_project(&mut self, project: Project) -> &mut Self {
+        self.projects.push(project);
+        self
+    }
+
+    /// Project names in generation order: dependencies first, otherwise in
+    /// the order they were added. Fails on duplicate names, unknown
+    /// dependencies and cycles.
+    pub fn order(&self) -> Result<Vec<&str>> {
+        let mut names = HashSet::new();
+        for p in &self.projects {
+            if !names.insert(p.name.as_str()) {
+                anyhow::bail!("project `{}` added twice", p.name);
+            }
+        }
+        for p in &self.projects {
+            for dep in &p.depends_on {
+                if !names.contains(dep.as_str()) {
+                    anyhow::bail!("project `{}` depends on unknown project `{dep}`", p.name);
+                }
+            }
+        }
+
+        let mut order = Vec::with_capacity(self.projects.len());
+        let mut done = HashSet::new();
+        while order.len() < self.projects.len() {
+            let next = self.projects.iter().find(|p| {
+                !done.contains(p.name.as_str())
+                    && p.depends_on.iter().all(|d| done.contains(d.as_str()))
+            });
+            let Some(next) = next else {
+                let cycle: Vec<&str> = self
+                    .projects
+                    .iter()
+                    .map(|p| p.name.as_str())
+                    .filter(|n| !done.contains(n))
+                    .collect();
+                anyhow::bail!("dependency cycle among projects: {}", cycle.join(", "));
+            };
+            done.insert(next.name.as_str());
+            order.push(next.name.as_str());
+        }
+        Ok(order)
+    }
+
+    /// Generate every stale project in dependency order. A project is
+    /// stale if it is marked [`always`](Project::always), if its winmd is
+    /// missing or older than one of its inputs or dependencies' winmds, or
+    /// if a dependency was regenerated in this run.
+    ///
+    /// Returns the names of the projects that were regenerated.
+    pub fn generate_all(&self) -> Result<Vec<String>> {
+        let mut regenerated: Vec<String> = Vec::new();
+        for name in self.order()? {
+            let project = self.project(name);
+            let reason = if project.always {
+                Some("always".to_string())
+            } else if regenerated.iter().any(|r| project.depends_on.contains(r)) {
+                Some("dependency regenerated".to_string())
+            } else {
+                self.stale_reason(project)
+            };
+            let Some(reason) = reason else {
+                debug!(project = name, "winmd up to date");
+                continue;
+            };
+            info!(project = name, reason = %reason, "generating");
+            (project.generate)().map_err(|e| e.context(format!("generating project `{name}`")))?;
+            anyhow::ensure!(
+                project.winmd.exists(),
+                "project `{name}` did not produce {}",
+                project.winmd.display()
+            );
+            regenerated.push(name.to_string());
+        }
+        Ok(regenerated)
+    }
+
+    fn project(&self, name: &str) -> &Project {
+        self.projects.iter().find(|p| p.name == name).unwrap()
+    }
+
+    /// Why `project` needs regenerating, or `None` if its winmd is newer
+    /// than everything it is built from.
+    fn stale_reason(&self, project: &Project) -> Option<String> {
+        let Some(built) = modified(&project.winmd) else {
+            return Some(format!("{} missing", project.winmd.display()));
+        };
+        let deps = project
+            .depends_on
+            .iter()
+            .map(|d| self.project(d).winmd.as_path());
+        for path in project.inputs.iter().map(PathBuf::as_path).chain(deps) {
+            match modified(path) {
+                Some(t) if t <= built => {}
+                Some(_) => return Some(format!("{} changed", path.display())),
+                None => return Some(format!("{} missing", path.display())),
+            }
+        }
+        None
+    }
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path).and_then(|m| m.modified()).ok()
+}
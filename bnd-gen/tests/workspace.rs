@@ -0,0 +1,85 @@
+//! Ordering and staleness of `Workspace::generate_all`, using generators
+//! that just write their winmd path.
+
+use std::cell::RefCell;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+use std::time::{Duration, SystemTime};
+
+use bnd_gen::{Project, Workspace};
+
+/// A project that writes a dummy winmd under `dir` and logs its name.
+fn project(dir: &Path, name: &'static str, log: &Rc<RefCell<Vec<&'static str>>>) -> Project {
+    let winmd = dir.join(format!("{name}.winmd"));
+    let out = winmd.clone();
+    let log = Rc::clone(log);
+    Project::new(name, winmd, move || {
+        log.borrow_mut().push(name);
+        std::fs::write(&out, name)?;
+        Ok(())
+    })
+}
+
+fn set_mtime(path: &Path, time: SystemTime) {
+    std::fs::File::options()
+        .write(true)
+        .open(path)
+        .unwrap()
+        .set_modified(time)
+        .unwrap();
+}
+
+#[test]
+fn dependencies_run_first() {
+    let dir = tempfile::tempdir().unwrap();
+    let log = Rc::new(RefCell::new(Vec::new()));
+    let mut ws = Workspace::new();
+    ws.add_project(project(dir.path(), "openssl", &log).depends_on("linux"))
+        .add_project(project(dir.path(), "linux", &log))
+        .add_project(project(dir.path(), "zlib", &log));
+
+    assert_eq!(ws.order().unwrap(), ["linux", "openssl", "zlib"]);
+    assert_eq!(ws.generate_all().unwrap(), ["linux", "openssl", "zlib"]);
+    assert_eq!(*log.borrow(), ["linux", "openssl", "zlib"]);
+
+    // Everything is fresh now.
+    assert!(ws.generate_all().unwrap().is_empty());
+}
+
+#[test]
+fn stale_input_regenerates_dependents() {
+    let dir = tempfile::tempdir().unwrap();
+    let config: PathBuf = dir.path().join("linux.toml");
+    std::fs::write(&config, "").unwrap();
+    let log = Rc::new(RefCell::new(Vec::new()));
+    let mut ws = Workspace::new();
+    ws.add_project(project(dir.path(), "linux", &log).input(&config))
+        .add_project(project(dir.path(), "openssl", &log).depends_on("linux"))
+        .add_project(project(dir.path(), "zlib", &log));
+    ws.generate_all().unwrap();
+
+    let later = SystemTime::now() + Duration::from_secs(60);
+    set_mtime(&config, later);
+    assert_eq!(ws.generate_all().unwrap(), ["linux", "openssl"]);
+}
+
+#[test]
+fn cycles_and_unknown_dependencies_are_errors() {
+    let dir = tempfile::tempdir().unwrap();
+    let log = Rc::new(RefCell::new(Vec::new()));
+
+    let mut ws = Workspace::new();
+    ws.add_project(project(dir.path(), "a", &log).depends_on("b"))
+        .add_project(project(dir.path(), "b", &log).depends_on("a"));
+    let err = ws.order().unwrap_err().to_string();
+    assert!(
+        err.contains("dependency cycle among projects: a, b"),
+        "{err}"
+    );
+
+    let mut ws = Workspace::new();
+    ws.add_project(project(dir.path(), "a", &log).depends_on("missing"));
+    let err = ws.generate_all().unwrap_err().to_string();
+    assert!(err.contains("unknown project `missing`"), "{err}");
+    assert!(log.borrow().is_empty());
+}
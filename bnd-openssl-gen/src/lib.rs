@@ -1,24 +1,21 @@
 use std::path::Path;
 
+use bnd_winmd::pipeline::{Builder, Layout};
+
 /// Generate the bnd-openssl source tree at `output_dir`.
 ///
 /// 1. Runs bnd-winmd on `openssl.toml` to produce a `.winmd`.
 /// 2. Runs `windows-bindgen --package` to emit `src/openssl/*/mod.rs`.
-///    Passes both the openssl and bnd-linux winmds so that cross-winmd type
-///    references resolve correctly.  `--reference` suppresses codegen for
+///    The config's `[[reference]]` passes the bnd-linux winmd too, so that
+///    cross-winmd type references resolve, and suppresses codegen for
 ///    `libc.*` types; the generated code uses `bnd_linux::libc::…` paths.
 /// 3. Saves the `.winmd` under `output_dir/winmd/`.
 pub fn generate(output_dir: &Path) {
     let gen_dir = Path::new(env!("CARGO_MANIFEST_DIR"));
 
-    // Step 1: Generate .winmd
-    let winmd_dir = output_dir.join("winmd");
-    std::fs::create_dir_all(&winmd_dir).expect("failed to create winmd directory");
-    let openssl_winmd = winmd_dir.join("bnd-openssl.winmd");
-    bnd_winmd::run(&gen_dir.join("openssl.toml"), Some(&openssl_winmd))
-        .expect("bnd-winmd failed to generate winmd");
+    let openssl_winmd = output_dir.join("winmd/bnd-openssl.winmd");
 
-    // Step 2: Locate bnd-linux winmd (produced by bnd-linux-gen)
+    // Locate bnd-linux winmd (produced by bnd-linux-gen)
     let linux_winmd = gen_dir.join("../bnd-linux/winmd/bnd-linux.winmd");
     assert!(
         linux_winmd.exists(),
@@ -27,21 +24,14 @@ pub fn generate(output_dir: &Path) {
         linux_winmd.display()
     );
 
-    // Step 3: Generate crate source tree via windows-bindgen package mode
-    windows_bindgen::bindgen([
-        "--in",
-        openssl_winmd.to_str().unwrap(),
-        "--in",
-        linux_winmd.to_str().unwrap(),
-        "--out",
-        output_dir.to_str().unwrap(),
-        "--filter",
-        "openssl",
-        "--reference",
-        "bnd_linux,full,libc",
-        "--sys",
-        "--package",
-        "--no-toml",
-    ])
-    .unwrap();
+    // Generate the .winmd, then the crate source tree via windows-bindgen
+    // package mode. The `[[reference]]` in openssl.toml adds the bnd-linux
+    // winmd as an input and `--reference bnd_linux,full,libc`.
+    Builder::new(gen_dir.join("openssl.toml"), output_dir)
+        .winmd(openssl_winmd)
+        .filter("openssl")
+        .layout(Layout::Package)
+        .no_toml(true)
+        .run()
+        .expect("failed to generate bnd-openssl");
 }
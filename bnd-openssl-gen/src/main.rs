@@ -1,14 +1,45 @@
 use std::path::PathBuf;
 
+use bnd_gen::{Project, Workspace};
+
 fn main() {
     tracing_subscriber::fmt()
         .with_env_filter(tracing_subscriber::EnvFilter::from_default_env())
         .init();
 
     let workspace_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("..");
+    let bnd_linux_dir = workspace_dir.join("bnd-linux");
     let bnd_openssl_dir = workspace_dir.join("bnd-openssl");
 
-    bnd_openssl_gen::generate(&bnd_openssl_dir);
+    // bnd-openssl references the bnd-linux winmd; regenerate it first if
+    // it is missing or stale.
+    let mut ws = Workspace::new();
+    let linux_dir = bnd_linux_dir.clone();
+    ws.add_project(
+        Project::new(
+            "bnd-linux",
+            bnd_linux_dir.join("winmd/bnd-linux.winmd"),
+            move || {
+                bnd_linux_gen::generate(&linux_dir);
+                Ok(())
+            },
+        )
+        .input(workspace_dir.join("bnd-linux-gen/bnd-linux.toml")),
+    );
+    let openssl_dir = bnd_openssl_dir.clone();
+    ws.add_project(
+        Project::new(
+            "bnd-openssl",
+            bnd_openssl_dir.join("winmd/bnd-openssl.winmd"),
+            move || {
+                bnd_openssl_gen::generate(&openssl_dir);
+                Ok(())
+            },
+        )
+        .depends_on("bnd-linux")
+        .always(true),
+    );
+    ws.generate_all().expect("generation failed");
 
     println!(
         "Generated bnd-openssl crate at {}",
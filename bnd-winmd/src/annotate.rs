@@ -0,0 +1,217 @@
+//! Function annotations from config: `[[param_association]]`,
+//! `[[param_attributes]]`, `[strings]` and `[return_conventions]`.
+
+use std::collections::{BTreeMap, HashSet};
+
+use anyhow::Result;
+use tracing::{debug, warn};
+
+use crate::config::{Config, ParamAssociationConfig, ParamAttributesConfig};
+use crate::diagnostics::{Diagnostic, Diagnostics};
+use crate::model::*;
+
+/// Record an enum/constant association on a function parameter.
+/// A missing function is warned about (it may be out of scope); a missing
+/// parameter or an ambiguous entry is a config error.
+pub(crate) fn apply_param_association(
+    partitions: &mut [Partition],
+    assoc: &ParamAssociationConfig,
+    diagnostics: &mut Diagnostics,
+) -> Result<()> {
+    match (&assoc.enum_name, &assoc.constant) {
+        (Some(_), Some(_)) => anyhow::bail!(
+            "param_association: `{}({})` sets both `enum` and `constant`",
+            assoc.function,
+            assoc.param
+        ),
+        (None, None) => anyhow::bail!(
+            "param_association: `{}({})` requires `enum` or `constant`",
+            assoc.function,
+            assoc.param
+        ),
+        _ => {}
+    }
+
+    if let Some(en) = &assoc.enum_name
+        && !partitions
+            .iter()
+            .any(|p| p.enums.iter().any(|e| &e.name == en))
+    {
+        warn!(enum_name = %en, "param_association: enum not extracted in any partition");
+        diagnostics.push(
+            Diagnostic::new(
+                "param_association_unresolved",
+                "param_association: enum not extracted in any partition",
+            )
+            .name(en),
+        );
+    }
+
+    let func = partitions
+        .iter_mut()
+        .flat_map(|p| p.functions.iter_mut())
+        .find(|f| f.name == assoc.function);
+    let Some(func) = func else {
+        warn!(function = %assoc.function, "param_association: function not found, skipping");
+        diagnostics.push(
+            Diagnostic::new(
+                "param_association_unresolved",
+                "param_association: function not found",
+            )
+            .name(&assoc.function),
+        );
+        return Ok(());
+    };
+    let param = func
+        .params
+        .iter_mut()
+        .find(|p| p.name == assoc.param)
+        .ok_or_else(|| {
+            anyhow::anyhow!(
+                "param_association: function `{}` has no parameter `{}`",
+                assoc.function,
+                assoc.param
+            )
+        })?;
+    param.associated_enum = assoc.enum_name.clone();
+    param.associated_constant = assoc.constant.clone();
+    debug!(function = %assoc.function, param = %assoc.param, "associated parameter");
+    Ok(())
+}
+
+/// Override one parameter's annotations from `[[param_attributes]]`.
+pub(crate) fn apply_param_attributes(
+    partitions: &mut [Partition],
+    attrs: &ParamAttributesConfig,
+    diagnostics: &mut Diagnostics,
+) -> Result<()> {
+    let func = partitions
+        .iter_mut()
+        .flat_map(|p| p.functions.iter_mut())
+        .find(|f| f.name == attrs.function);
+    let Some(func) = func else {
+        warn!(function = %attrs.function, "param_attributes: function not found, skipping");
+        diagnostics.push(
+            Diagnostic::new(
+                "param_attributes_unresolved",
+                "param_attributes: function not found",
+            )
+            .name(&attrs.function),
+        );
+        return Ok(());
+    };
+    let index = |name: &str| {
+        func.params
+            .iter()
+            .position(|p| p.name == name)
+            .ok_or_else(|| {
+                anyhow::anyhow!(
+                    "param_attributes: function `{}` has no parameter `{name}`",
+                    attrs.function
+                )
+            })
+    };
+    let param = index(&attrs.param)?;
+    let memory_size = attrs.memory_size.as_deref().map(index).transpose()?;
+    let array_count = attrs.count.as_deref().map(index).transpose()?;
+    anyhow::ensure!(
+        (memory_size.is_none() && array_count.is_none())
+            || matches!(func.params[param].ty, CType::Ptr { .. }),
+        "param_attributes: `{}({})` needs a pointer type for `memory_size` or `count`",
+        attrs.function,
+        attrs.param
+    );
+
+    let annotations = &mut func.params[param].annotations;
+    if let Some(direction) = attrs.direction {
+        annotations.direction = Some(direction);
+    }
+    if let Some(optional) = attrs.optional {
+        annotations.optional = optional;
+    }
+    if memory_size.is_some() {
+        annotations.memory_size = memory_size;
+    }
+    if array_count.is_some() {
+        annotations.array_count = array_count;
+    }
+    if let Some(not_null_terminated) = attrs.not_null_terminated {
+        annotations.not_null_terminated = not_null_terminated;
+    }
+    Ok(())
+}
+
+/// Mark the `char *` parameters and returns of `string_params` /
+/// `[strings]` functions as C strings. Unknown allowlist entries are
+/// warned about (they may be out of scope).
+pub(crate) fn apply_strings(
+    partitions: &mut [Partition],
+    cfg: &Config,
+    diagnostics: &mut Diagnostics,
+) {
+    let mut found = HashSet::new();
+    for func in partitions.iter_mut().flat_map(|p| p.functions.iter_mut()) {
+        let listed = cfg.strings.functions.contains(&func.name);
+        if !(cfg.string_params || listed) {
+            continue;
+        }
+        if listed {
+            found.insert(func.name.clone());
+        }
+        func.returns_string = func.return_type.is_char_ptr();
+        for param in &mut func.params {
+            param.is_string = param.ty.is_char_ptr();
+        }
+    }
+    for name in &cfg.strings.functions {
+        if !found.contains(name) {
+            warn!(function = %name, "strings: function not found, skipping");
+            diagnostics.push(
+                Diagnostic::new("strings_unmatched", "strings: function not found").name(name),
+            );
+        }
+    }
+}
+
+/// Record `[return_conventions]` on their functions. A missing function is
+/// warned about; a convention that doesn't fit the return type is a config
+/// error.
+pub(crate) fn apply_return_conventions(
+    partitions: &mut [Partition],
+    conventions: &BTreeMap<String, ReturnConvention>,
+    diagnostics: &mut Diagnostics,
+) -> Result<()> {
+    for (name, &convention) in conventions {
+        let mut found = false;
+        for func in partitions
+            .iter_mut()
+            .flat_map(|p| p.functions.iter_mut())
+            .filter(|f| &f.name == name)
+        {
+            found = true;
+            let (fits, expected) = match convention {
+                ReturnConvention::Null => {
+                    (matches!(func.return_type, CType::Ptr { .. }), "a pointer")
+                }
+                _ => (func.return_type.is_integer(), "an integer"),
+            };
+            anyhow::ensure!(
+                fits,
+                "return_conventions: `{name}` = \"{}\" needs {expected} return type",
+                convention.as_str()
+            );
+            func.return_convention = Some(convention);
+        }
+        if !found {
+            warn!(function = %name, "return_conventions: function not found, skipping");
+            diagnostics.push(
+                Diagnostic::new(
+                    "return_convention_unmatched",
+                    "return_conventions: function not found",
+                )
+                .name(name),
+            );
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,208 @@
+//! Assembly version and descriptive metadata from `[output]`.
+//!
+//! The windows-metadata writer always writes version `255.255.255.255`
+//! into the `Assembly` row and has no way to change it, so the emitted
+//! bytes are patched in place: the metadata tables are walked (see
+//! [`crate::tables`]) to the single `Assembly` row, whose four version
+//! fields have a fixed offset. The writer has no assembly-level attribute parent
+//! either, so `company` and `copyright` become attributes of the stamp
+//! type (see [`crate::stamp`]).
+//!
+//! The writer also names every `AssemblyRef` after a root namespace, so
+//! [`write_assembly_refs`] re-points the `TypeRef`s of imported types at
+//! rows named after the winmds that define them.
+
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fmt;
+use std::str::FromStr;
+
+use anyhow::{Context, Result, ensure};
+use serde::{Deserialize, Serialize};
+use windows_metadata::Value;
+use windows_metadata::reader::HasAttributes;
+
+use crate::config::OutputConfig;
+use crate::tables::{assembly_ref_names, assembly_row, rewrite_assembly_refs, type_refs, u16};
+
+/// `AssemblyCompanyAttribute(company)` on the stamp type.
+pub(crate) const COMPANY_ATTRIBUTE: &str = "AssemblyCompanyAttribute";
+/// `AssemblyCopyrightAttribute(copyright)` on the stamp type.
+pub(crate) const COPYRIGHT_ATTRIBUTE: &str = "AssemblyCopyrightAttribute";
+
+/// A four-part assembly version, `major.minor.build.revision`.
+///
+/// Parsed from one to four dot-separated numbers; missing parts are 0, so
+/// `"0.3"` is `0.3.0.0`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct AssemblyVersion(pub [u16; 4]);
+
+impl FromStr for AssemblyVersion {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let parts: Vec<&str> = s.split('.').collect();
+        ensure!(
+            (1..=4).contains(&parts.len()),
+            "assembly version `{s}` must have one to four parts"
+        );
+        let mut version = [0; 4];
+        for (slot, part) in version.iter_mut().zip(parts) {
+            *slot = part.parse().with_context(|| {
+                format!("assembly version `{s}`: `{part}` is not a number 0-65535")
+            })?;
+        }
+        Ok(Self(version))
+    }
+}
+
+impl TryFrom<String> for AssemblyVersion {
+    type Error = anyhow::Error;
+
+    fn try_from(s: String) -> Result<Self> {
+        s.parse()
+    }
+}
+
+impl From<AssemblyVersion> for String {
+    fn from(v: AssemblyVersion) -> Self {
+        v.to_string()
+    }
+}
+
+impl fmt::Display for AssemblyVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let [major, minor, build, revision] = self.0;
+        write!(f, "{major}.{minor}.{build}.{revision}")
+    }
+}
+
+/// What `[output]` says about the assembly beyond its name.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AssemblyMetadata {
+    /// `None` keeps the writer's `255.255.255.255`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<AssemblyVersion>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub company: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub copyright: Option<String>,
+}
+
+impl AssemblyMetadata {
+    pub(crate) fn of(output: &OutputConfig) -> Self {
+        Self {
+            version: output.version,
+            company: output.company.clone(),
+            copyright: output.copyright.clone(),
+        }
+    }
+
+    /// Read the metadata back from winmd bytes.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        let version = read_version(bytes)?;
+        let type_defs = crate::tables::type_def_rows(bytes)?;
+        let index = crate::inspect::index_bytes(bytes.to_vec())?;
+        let stamp_string = |name: &str| {
+            let td = crate::inspect::stamp_type(&index, type_defs)?;
+            match td.find_attribute(name)?.value().as_slice() {
+                [(_, Value::Utf8(s))] => Some(s.clone()),
+                _ => None,
+            }
+        };
+        Ok(Self {
+            version: (version != AssemblyVersion([0xFF; 4])).then_some(version),
+            company: stamp_string(COMPANY_ATTRIBUTE),
+            copyright: stamp_string(COPYRIGHT_ATTRIBUTE),
+        })
+    }
+}
+
+/// Overwrite the version of the `Assembly` row in winmd `bytes`.
+pub(crate) fn write_version(bytes: &mut [u8], version: AssemblyVersion) -> Result<()> {
+    let offset = assembly_row(bytes)? + 4;
+    for (i, part) in version.0.iter().enumerate() {
+        bytes[offset + 2 * i..offset + 2 * i + 2].copy_from_slice(&part.to_le_bytes());
+    }
+    Ok(())
+}
+
+/// The version of the `Assembly` row in winmd `bytes`.
+fn read_version(bytes: &[u8]) -> Result<AssemblyVersion> {
+    let offset = assembly_row(bytes)? + 4;
+    let mut version = [0; 4];
+    for (i, part) in version.iter_mut().enumerate() {
+        *part = u16(bytes, offset + 2 * i)?;
+    }
+    Ok(AssemblyVersion(version))
+}
+
+/// The `AssemblyRef` name each `TypeRef` in winmd `bytes` resolves
+/// through, keyed by `namespace.name`.
+pub fn type_ref_assemblies(bytes: &[u8]) -> Result<BTreeMap<String, String>> {
+    let names = assembly_ref_names(bytes)?;
+    Ok(type_refs(bytes)?
+        .into_iter()
+        .filter_map(|t| {
+            let row = t.assembly_ref?;
+            let assembly = names.get(row as usize - 1)?.clone();
+            Some((format!("{}.{}", t.namespace, t.name), assembly))
+        })
+        .collect())
+}
+
+/// Resolve every `TypeRef` that `assembly_of` maps to an assembly name
+/// (an imported type) through an `AssemblyRef` row of that name, instead
+/// of the writer's row named after the type's root namespace. Writer rows
+/// no `TypeRef` uses any more are dropped, except `mscorlib`. Leaves
+/// `bytes` untouched when nothing is imported.
+pub(crate) fn write_assembly_refs<'a>(
+    bytes: &mut Vec<u8>,
+    assembly_of: impl Fn(&str, &str) -> Option<&'a str>,
+) -> Result<()> {
+    let type_refs = type_refs(bytes)?;
+    let targets: Vec<Option<&str>> = type_refs
+        .iter()
+        .map(|t| assembly_of(&t.namespace, &t.name))
+        .collect();
+    if targets.iter().all(Option::is_none) {
+        return Ok(());
+    }
+    let names = assembly_ref_names(bytes)?;
+
+    // The writer's first row is `mscorlib`, which parsers expect.
+    let used: BTreeSet<usize> = type_refs
+        .iter()
+        .zip(&targets)
+        .filter(|(_, target)| target.is_none())
+        .filter_map(|(t, _)| t.assembly_ref)
+        .map(|row| row as usize - 1)
+        .collect();
+    let keep: Vec<usize> = (0..names.len())
+        .filter(|i| *i == 0 || used.contains(i))
+        .collect();
+    let kept_row: HashMap<usize, u32> = keep
+        .iter()
+        .enumerate()
+        .map(|(new, &old)| (old, new as u32 + 1))
+        .collect();
+    let mut row_of: HashMap<&str, u32> = keep
+        .iter()
+        .map(|&old| (names[old].as_str(), kept_row[&old]))
+        .collect();
+
+    let mut added = Vec::new();
+    let mut scopes = Vec::new();
+    for (t, target) in type_refs.iter().zip(&targets) {
+        let row = match (target, t.assembly_ref) {
+            (Some(assembly), _) => *row_of.entry(assembly).or_insert_with(|| {
+                added.push(assembly.to_string());
+                (keep.len() + added.len()) as u32
+            }),
+            (None, Some(old)) => kept_row[&(old as usize - 1)],
+            (None, None) => continue,
+        };
+        scopes.push((t.scope_at, row));
+    }
+    rewrite_assembly_refs(bytes, &keep, &added, &scopes)
+}
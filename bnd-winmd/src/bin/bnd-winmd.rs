@@ -1,14 +1,23 @@
 //! CLI entry point for bnd-winmd.
 
-use std::path::PathBuf;
+use std::ops::ControlFlow;
+use std::path::{Path, PathBuf};
 
-use anyhow::Result;
-use clap::Parser;
+use anyhow::{Context, Result};
+use clap::{Parser, Subcommand};
 
 /// bnd-winmd — generate WinMD metadata from C headers.
 #[derive(Parser, Debug)]
-#[command(name = "bnd-winmd", version, about)]
+#[command(
+    name = "bnd-winmd",
+    version,
+    about,
+    args_conflicts_with_subcommands = true
+)]
 struct Cli {
+    #[command(subcommand)]
+    command: Option<Command>,
+
     /// Path to the bnd-winmd.toml configuration file.
     #[arg(default_value = "bnd-winmd.toml")]
     config: PathBuf,
@@ -17,9 +26,87 @@ struct Cli {
     #[arg(short, long)]
     output: Option<PathBuf>,
 
-    /// Validate config and print stats without writing output.
+    /// Apply the config's `[profile.<NAME>]`.
+    #[arg(long, value_name = "NAME")]
+    profile: Option<String>,
+
+    /// Extract and validate, then list what would be emitted without
+    /// writing output.
     #[arg(long)]
     dry_run: bool,
+
+    /// Keep running: regenerate whenever the config or a header changes
+    /// and print what changed in the API.
+    #[arg(long, conflicts_with_all = ["dry_run", "profile"])]
+    watch: bool,
+
+    /// Also write a JSON summary of the extracted API surface to this path.
+    #[arg(long, value_name = "PATH")]
+    report: Option<PathBuf>,
+
+    /// Also write a JSON coverage report (declared vs emitted, with the
+    /// reason for every omission) to this path, and print its summary.
+    #[arg(long, value_name = "PATH")]
+    coverage: Option<PathBuf>,
+
+    /// Also write the extracted model (IR) as JSON to this path.
+    #[arg(long, value_name = "PATH")]
+    emit_ir: Option<PathBuf>,
+
+    /// Also write Rust `-sys` bindings, generated directly from the model,
+    /// to this path.
+    #[arg(long, value_name = "PATH")]
+    rust_sys: Option<PathBuf>,
+
+    /// Also write C# P/Invoke source, one file per namespace, into this
+    /// directory.
+    #[arg(long, value_name = "DIR")]
+    csharp: Option<PathBuf>,
+
+    /// Also write a MIDL3-like IDL listing, one file per namespace, into
+    /// this directory.
+    #[arg(long, value_name = "DIR")]
+    emit_idl: Option<PathBuf>,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Compare two winmd files; exits non-zero on breaking changes.
+    Diff {
+        /// Baseline winmd.
+        old: PathBuf,
+        /// Regenerated winmd.
+        new: PathBuf,
+        /// Print the report as JSON.
+        #[arg(long)]
+        json: bool,
+    },
+    /// Print a human-readable listing of a winmd file.
+    Dump {
+        /// Winmd file to dump.
+        winmd: PathBuf,
+    },
+    /// Emit a winmd from an IR file written by `--emit-ir`, without
+    /// parsing any headers.
+    Emit {
+        /// IR JSON file.
+        ir: PathBuf,
+        /// Output file path.
+        #[arg(short, long)]
+        output: PathBuf,
+    },
+    /// Merge several winmd files into a single assembly (first type wins; `Apis` functions are combined).
+    Merge {
+        /// Input winmd files, in priority order.
+        #[arg(required = true)]
+        inputs: Vec<PathBuf>,
+        /// Assembly name of the merged winmd.
+        #[arg(long)]
+        name: String,
+        /// Output file path.
+        #[arg(short, long)]
+        output: PathBuf,
+    },
 }
 
 fn main() -> Result<()> {
@@ -31,10 +118,129 @@ fn main() -> Result<()> {
         .init();
 
     let cli = Cli::parse();
+    match &cli.command {
+        Some(Command::Diff { old, new, json }) => {
+            let report = bnd_winmd::diff(old, new)?;
+            if *json {
+                println!("{}", serde_json::to_string_pretty(&report)?);
+            } else {
+                print!("{report}");
+            }
+            if report.is_breaking() {
+                std::process::exit(1);
+            }
+            return Ok(());
+        }
+        Some(Command::Dump { winmd }) => {
+            print!("{}", bnd_winmd::dump(winmd)?);
+            return Ok(());
+        }
+        Some(Command::Emit { ir, output }) => {
+            std::fs::write(output, bnd_winmd::emit_from_ir(ir)?)?;
+            return Ok(());
+        }
+        Some(Command::Merge {
+            inputs,
+            name,
+            output,
+        }) => {
+            let bytes = bnd_winmd::emit::merge_winmds(inputs, name)?;
+            std::fs::write(output, bytes)?;
+            return Ok(());
+        }
+        None => {}
+    }
+
+    if cli.watch {
+        bnd_winmd::watch(&cli.config, |event| {
+            write_regenerated(&cli, event);
+            ControlFlow::Continue(())
+        })?;
+        return Ok(());
+    }
+
+    let cfg = bnd_winmd::config::load_config_profile(&cli.config, cli.profile.as_deref())
+        .with_context(|| format!("loading config from {}", cli.config.display()))?;
+    let base_dir = cli.config.parent().unwrap_or_else(|| Path::new("."));
+    if let Some(report_path) = &cli.report {
+        bnd_winmd::report_from_config(&cfg, base_dir)?.write_json(report_path)?;
+    }
+    if let Some(coverage_path) = &cli.coverage {
+        let coverage = bnd_winmd::coverage_from_config(&cfg, base_dir)?;
+        coverage.write_json(coverage_path)?;
+        print!("{coverage}");
+    }
+    if let Some(ir_path) = &cli.emit_ir {
+        bnd_winmd::ir_from_config(&cfg, base_dir)?.write_json(ir_path)?;
+    }
+    if let Some(rust_path) = &cli.rust_sys {
+        std::fs::write(
+            rust_path,
+            bnd_winmd::rust_sys_from_config(&cfg, base_dir)?.to_single_file(),
+        )?;
+    }
+    if let Some(dir) = &cli.csharp {
+        std::fs::create_dir_all(dir)?;
+        for (file, source) in bnd_winmd::csharp_from_config(&cfg, base_dir)? {
+            std::fs::write(dir.join(file), source)?;
+        }
+    }
+    if let Some(dir) = &cli.emit_idl {
+        std::fs::create_dir_all(dir)?;
+        for (file, text) in bnd_winmd::idl_from_config(&cfg, base_dir)? {
+            std::fs::write(dir.join(file), text)?;
+        }
+    }
     if cli.dry_run {
-        bnd_winmd::validate(&cli.config)?;
+        print!("{}", bnd_winmd::plan_from_config(&cfg, base_dir)?);
     } else {
-        bnd_winmd::run(&cli.config, cli.output.as_deref())?;
+        bnd_winmd::run(
+            &cli.config,
+            cli.profile.as_deref().map(bnd_winmd::Profile),
+            cli.output.as_deref(),
+        )?;
     }
     Ok(())
 }
+
+/// Write one watch-mode result and summarize it; errors are printed and
+/// the watch goes on.
+fn write_regenerated(cli: &Cli, event: &bnd_winmd::watch::Regenerated) {
+    for path in &event.changed {
+        println!("changed: {}", path.display());
+    }
+    let written = event
+        .output
+        .as_ref()
+        .map_err(|e| anyhow::anyhow!("{e:#}"))
+        .and_then(|out| {
+            let base_dir = cli.config.parent().unwrap_or_else(|| Path::new("."));
+            let path = match &cli.output {
+                Some(path) => path.clone(),
+                None => {
+                    let cfg = bnd_winmd::config::load_config(&cli.config)?;
+                    base_dir.join(&cfg.output.file)
+                }
+            };
+            std::fs::write(&path, &out.bytes)?;
+            for assembly in &out.assemblies {
+                std::fs::write(
+                    assembly.path(base_dir, cli.output.as_deref()),
+                    &assembly.bytes,
+                )?;
+            }
+            Ok(path)
+        });
+    match (written, &event.diff) {
+        (Ok(path), Some(Ok(diff))) if diff.is_empty() => {
+            println!("wrote {} (no API changes)", path.display())
+        }
+        (Ok(path), Some(Ok(diff))) => print!("wrote {}\n{diff}", path.display()),
+        (Ok(path), Some(Err(e))) => {
+            println!("wrote {}", path.display());
+            eprintln!("error: diffing against the previous winmd: {e:#}");
+        }
+        (Ok(path), None) => println!("wrote {}", path.display()),
+        (Err(e), _) => eprintln!("error: {e:#}"),
+    }
+}
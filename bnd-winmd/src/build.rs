@@ -0,0 +1,80 @@
+//! Generation from a `build.rs` script.
+//!
+//! [`generate`] follows Cargo's conventions: the config is found next to
+//! the crate's manifest, everything is written to `OUT_DIR`, and Cargo is
+//! told which files to watch:
+//!
+//! ```no_run
+//! // in build.rs `main`
+//! let winmd = bnd_winmd::build::generate().unwrap();
+//! // e.g. hand `winmd` to windows-bindgen
+//! ```
+
+use std::path::{Path, PathBuf};
+
+use crate::{Error, Result};
+
+/// Environment variable naming the config, relative to
+/// `CARGO_MANIFEST_DIR`.
+pub const CONFIG_ENV: &str = "BND_WINMD_CONFIG";
+
+/// Environment variable naming a `[profile.<name>]` to apply.
+pub const PROFILE_ENV: &str = "BND_WINMD_PROFILE";
+
+/// Config used when [`CONFIG_ENV`] is unset.
+pub const DEFAULT_CONFIG: &str = "bnd-winmd.toml";
+
+/// Generate from the config named by [`CONFIG_ENV`] (default
+/// [`DEFAULT_CONFIG`]) into `OUT_DIR`, and print `cargo:rerun-if-changed`
+/// for it and every file it reads.
+///
+/// The winmd keeps the file name of `[output] file`; `[[assembly]]`
+/// winmds, the shim source and layout tests land next to it. Returns the
+/// winmd's path.
+pub fn generate() -> Result<PathBuf> {
+    println!("cargo:rerun-if-env-changed={CONFIG_ENV}");
+    println!("cargo:rerun-if-env-changed={PROFILE_ENV}");
+    let manifest_dir = env_path("CARGO_MANIFEST_DIR")?;
+    let config = std::env::var_os(CONFIG_ENV)
+        .map(PathBuf::from)
+        .unwrap_or_else(|| PathBuf::from(DEFAULT_CONFIG));
+    let profile = std::env::var(PROFILE_ENV).ok();
+    generate_config(&manifest_dir.join(config), profile.as_deref())
+}
+
+/// [`generate`] for the config at `config_path`, with `profile` applied.
+pub fn generate_config(config_path: &Path, profile: Option<&str>) -> Result<PathBuf> {
+    let out_dir = env_path("OUT_DIR")?;
+    let cfg = crate::load_config_profile(config_path, profile)?;
+    let base_dir = config_path.parent().unwrap_or_else(|| Path::new("."));
+    let file_name = cfg
+        .output
+        .file
+        .file_name()
+        .unwrap_or(cfg.output.file.as_os_str());
+    let winmd = out_dir.join(file_name);
+    let mut written = crate::write_winmd(&cfg, base_dir, Some(&winmd))?;
+    // A failed build script reruns regardless, so the files a successful
+    // generation read are all Cargo needs.
+    for directive in rerun_directives(&written.inputs) {
+        println!("{directive}");
+    }
+    Ok(written.winmds.remove(0))
+}
+
+/// `cargo:rerun-if-changed` for each of `inputs`, e.g. a generation's
+/// [`GenerateOutput::inputs`](crate::GenerateOutput::inputs).
+pub fn rerun_directives(inputs: &[PathBuf]) -> Vec<String> {
+    inputs
+        .iter()
+        .map(|path| format!("cargo:rerun-if-changed={}", path.display()))
+        .collect()
+}
+
+fn env_path(name: &str) -> Result<PathBuf> {
+    std::env::var_os(name).map(PathBuf::from).ok_or_else(|| {
+        Error::Config(anyhow::anyhow!(
+            "{name} is not set; bnd_winmd::build runs from a build script"
+        ))
+    })
+}
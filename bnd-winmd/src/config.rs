@@ -1,69 +1,1834 @@
 //! Configuration types for `bnd-winmd.toml`.
 
+use anyhow::Context;
 use serde::Deserialize;
-use std::collections::HashMap;
+use std::collections::BTreeMap;
 use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use crate::assembly::AssemblyVersion;
+use crate::model::{ApisSplit, CallConv, ParamDirection, ReturnConvention};
+use crate::progress::ProgressSink;
+use crate::type_map::TypeMapHook;
 
 /// Root configuration.
 #[derive(Debug, Deserialize)]
 pub struct Config {
     pub output: OutputConfig,
+    /// Further winmds generated in the same pass, each from the partitions
+    /// it lists. `[output]` gets every partition no `[[assembly]]` claims.
+    #[serde(default)]
+    pub assembly: Vec<AssemblyConfig>,
     /// Additional directories to search when resolving header and traverse
     /// paths.  Each entry is tried in order after `base_dir` (the TOML
     /// file's parent directory).  Also injected as `-I` flags for clang.
     #[serde(default)]
     pub include_paths: Vec<PathBuf>,
+    /// Directories searched for partition `frameworks`, before
+    /// `/System/Library/Frameworks` and `/Library/Frameworks`. Absolute
+    /// entries (and the defaults) are re-rooted under `sysroot`.
+    #[serde(default)]
+    pub framework_paths: Vec<PathBuf>,
+    /// Directories the linker searches for partition libraries, printed
+    /// as `cargo:rustc-link-search` by [`Config::build_script`].
+    /// `[discover]` appends the package's `-L` directories; absolute
+    /// entries are re-rooted under `sysroot`.
+    #[serde(default)]
+    pub library_paths: Vec<PathBuf>,
     /// Extra clang arguments applied to **all** partitions (e.g. `-DFOO`,
     /// `-Wno-pragma-once-outside-header`).  Per-partition `clang_args` are
     /// appended after these.
     #[serde(default)]
     pub clang_args: Vec<String>,
+    /// Preprocessor defines for **all** partitions, passed to clang as
+    /// `-D` flags after `clang_args` (see [`DefineValue`]).
+    ///
+    /// ```toml
+    /// [defines]
+    /// _GNU_SOURCE = true
+    /// OPENSSL_API_COMPAT = 30000
+    /// ```
+    #[serde(default)]
+    pub defines: BTreeMap<String, DefineValue>,
+    /// Parse the headers of every partition with the same language and
+    /// `clang_args` as one translation unit, and slice it into partitions
+    /// by their `traverse` lists. Partitions with identical `headers`
+    /// share a parse either way.
+    #[serde(default)]
+    pub shared_translation_unit: bool,
+    /// Headers to precompile once (per set of clang arguments) and load
+    /// into every partition's parse with `-include-pch`, e.g. the system
+    /// headers all partitions drag in. Resolved like `headers`.
+    #[serde(default)]
+    pub pch: Vec<PathBuf>,
+    /// Directory for the files generated to feed clang (wrapper `.c`
+    /// files, `extra_source` headers, precompiled headers). Names carry a
+    /// hash of their content, so builds sharing the directory don't
+    /// clash. Defaults to `$OUT_DIR/bnd-winmd` in a build script, else
+    /// `<temp>/bnd_winmd_wrappers`.
+    #[serde(default)]
+    pub scratch_dir: Option<PathBuf>,
+    /// Target sysroot for cross-compilation (Android NDK, Yocto SDK, ...).
+    /// Passed to clang as `--sysroot`, and absolute `include_paths`,
+    /// `headers` and `traverse` entries are re-rooted under it, so
+    /// `/usr/include` means `<sysroot>/usr/include`.
+    #[serde(default)]
+    pub sysroot: Option<PathBuf>,
+    /// Platform (a Rust `target_os`: `linux`, `macos`, `windows`, ...)
+    /// whose name is used from `library` tables. Defaults to
+    /// `CARGO_CFG_TARGET_OS` when run from a build script, else the host.
+    #[serde(default)]
+    pub platform: Option<String>,
+    /// Compiler dialect the headers are written for (see [`Flavor`]).
+    #[serde(default)]
+    pub flavor: Flavor,
+    /// Header/library auto-discovery (pkg-config).
+    #[serde(default)]
+    pub discover: DiscoverConfig,
     #[serde(default)]
     pub partition: Vec<PartitionConfig>,
+    /// Named variants of the config (`[profile.<name>]`), applied on top
+    /// of it by [`load_config_profile`].
+    #[serde(default)]
+    pub profile: BTreeMap<String, ProfileConfig>,
     #[serde(default)]
-    pub namespace_overrides: HashMap<String, String>,
+    pub namespace_overrides: BTreeMap<String, String>,
     #[serde(default)]
     pub type_import: Vec<TypeImportConfig>,
+    /// Crates that already provide bindings for an external winmd. Seeds
+    /// the registry like `type_import` and becomes a windows-bindgen
+    /// `--reference` in [`pipeline::Builder`](crate::pipeline::Builder).
+    #[serde(default)]
+    pub reference: Vec<ReferenceConfig>,
     /// User-declared types that bypass clang extraction. Used for types
     /// that bnd-winmd cannot extract (bitfield enums, anonymous enums in
     /// structs, etc.). Merged into partitions before validation/emission.
     #[serde(default)]
     pub inject_type: Vec<InjectTypeConfig>,
+    /// Resource-management pairs. The handle type returned by `alloc` is
+    /// annotated with `RAIIFreeAttribute(free)` so downstream codegen can
+    /// generate drop guards.
+    #[serde(default)]
+    pub raii_free: Vec<RaiiFreeConfig>,
+    /// Links function parameters to the enum or constant group they accept
+    /// (`AssociatedEnumAttribute` / `AssociatedConstantAttribute`).
+    #[serde(default)]
+    pub param_association: Vec<ParamAssociationConfig>,
+    /// Per-parameter direction, nullability and buffer-size attributes,
+    /// overriding SAL annotations found in the headers.
+    #[serde(default)]
+    pub param_attributes: Vec<ParamAttributesConfig>,
+    /// Treat the `char *` / `const char *` parameters and returns of every
+    /// function as C strings (see [`strings`](Self::strings)).
+    #[serde(default)]
+    pub string_params: bool,
+    /// Functions whose `char *` / `const char *` parameters and returns are
+    /// C strings, emitted as `PSTR` / `PCSTR` instead of `*mut i8` /
+    /// `*const i8`.
+    #[serde(default)]
+    pub strings: StringsConfig,
+    /// How each listed function's return value reports failure (`bool`,
+    /// `errno`, `neg-errno`, `minus-one-errno` or `null`), recorded as a
+    /// `ReturnConventionAttribute`.
+    ///
+    /// ```toml
+    /// [return_conventions]
+    /// pthread_mutex_lock = "errno"
+    /// BN_set_word = "bool"
+    /// ```
+    #[serde(default)]
+    pub return_conventions: BTreeMap<String, ReturnConvention>,
+    /// Enums assembled from `#define` families sharing a prefix.
+    #[serde(default)]
+    pub synthesize_enum: Vec<SynthesizeEnumConfig>,
+    /// Underlying integer type (`i8` … `u64`) per enum name, replacing the
+    /// one derived from clang and the variant values.
+    ///
+    /// ```toml
+    /// [enum_type]
+    /// curl_sslbackend = "u8"
+    /// ```
+    #[serde(default)]
+    pub enum_type: BTreeMap<String, String>,
+    /// Integer type (`i8` … `u64`) per `#define` constant, replacing the
+    /// one inferred from the literal's suffix and radix.
+    ///
+    /// ```toml
+    /// [constant_type]
+    /// O_RDONLY = "u32"
+    /// ```
+    #[serde(default)]
+    pub constant_type: BTreeMap<String, String>,
+    /// Primitive (`bool`, `i8` … `u64`, `isize`, `usize`, `f32`, `f64`)
+    /// per type name. References to these typedefs map straight to the
+    /// primitive instead of going through the host's typedef chain.
+    ///
+    /// ```toml
+    /// [type_map]
+    /// size_t = "usize"
+    /// off_t = "i64"
+    /// ```
+    #[serde(default)]
+    pub type_map: BTreeMap<String, String>,
+    /// Rename rules for emitted symbols, applied in order (first match wins).
+    #[serde(default)]
+    pub rename: Vec<RenameConfig>,
+    /// Exported C wrappers for `static inline` functions.
+    #[serde(default)]
+    pub shim: Option<ShimConfig>,
+    /// Generated Rust tests asserting struct sizes.
+    #[serde(default)]
+    pub layout_tests: Option<LayoutTestsConfig>,
+    /// Vtable structs (every field a function pointer taking the object
+    /// pointer first) to emit as interfaces instead of structs.
+    #[serde(default)]
+    pub interface: Vec<String>,
+    /// Move functions whose first parameter is the same handle type
+    /// (`BIGNUM *`, `FILE *`, a `typedef struct x *x_t`) into a
+    /// `<namespace>.<Handle>` sub-namespace with its own `Apis` class.
+    #[serde(default)]
+    pub group_by_handle: bool,
+    /// Calling convention for functions clang reports as the platform
+    /// default, in partitions that don't set their own (see
+    /// [`PartitionConfig::calling_convention`]).
+    #[serde(default)]
+    pub calling_convention: Option<CallConv>,
+    /// How to report a type and a function sharing a name in one namespace.
+    #[serde(default)]
+    pub name_collision: Severity,
+    /// How to report emitted struct layouts whose field offsets differ
+    /// from clang's.
+    #[serde(default)]
+    pub layout_mismatch: Severity,
+    /// How to report errors clang finds in the headers (missing includes,
+    /// wrong `-D` flags). Defaults to `warn`, collecting them as
+    /// `clang_error` diagnostics as earlier versions tolerated them; set
+    /// `error` to fail with clang's messages. clang warnings are always
+    /// collected as diagnostics.
+    #[serde(default)]
+    pub clang_errors: Severity,
+    /// Keep one definition (the registry's) of a struct that partitions
+    /// define with different layouts, instead of failing.
+    #[serde(default)]
+    pub allow_duplicate_types: bool,
+    /// Fail on every kind of skipped declaration (see [`fail_on`](Self::fail_on)).
+    #[serde(default)]
+    pub strict: bool,
+    /// Kinds of skipped declarations that fail generation.
+    #[serde(default)]
+    pub fail_on: Vec<SkipKind>,
+    /// Programmatic override of the C type mapping; not settable from TOML.
+    #[serde(skip)]
+    pub type_map_hook: Option<Arc<dyn TypeMapHook>>,
+    /// Receives partition and emit progress; not settable from TOML.
+    #[serde(skip)]
+    pub progress: Option<Arc<dyn ProgressSink>>,
+    /// The config file and every file it `extends`, as loaded; empty for
+    /// a config built in code. Not settable from TOML.
+    #[serde(skip)]
+    pub config_files: Vec<PathBuf>,
+    /// The `[profile.<name>]` that [`load_config_profile`] applied, if
+    /// any. Not settable from TOML.
+    #[serde(skip)]
+    pub applied_profile: Option<String>,
+}
+
+/// A class of declaration that extraction skips. Out-of-scope
+/// declarations are never counted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SkipKind {
+    /// Variadic functions.
+    Variadic,
+    /// Declarations whose types or layout could not be mapped.
+    UnsupportedType,
+    /// Object-like `#define`s that are not numeric literals.
+    SkippedMacro,
+    /// `traverse` entries no declaration was found in, usually a typo.
+    UnmatchedTraverse,
+}
+
+/// How a validation finding is reported.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    /// Not checked.
+    Allow,
+    /// Logged with `warn!`.
+    #[default]
+    Warn,
+    /// Fails generation.
+    Error,
+}
+
+/// Output file settings.
+#[derive(Debug, Deserialize)]
+pub struct OutputConfig {
+    /// Assembly name written into the winmd.
+    pub name: String,
+    /// Output file path (e.g. `MyLib.winmd`).
+    #[serde(default = "default_output_file")]
+    pub file: PathBuf,
+    /// Assembly version (`"0.3.0.0"`) written into the `Assembly` row of
+    /// every winmd the config generates.
+    #[serde(default)]
+    pub version: Option<AssemblyVersion>,
+    /// Company recorded with the assembly.
+    #[serde(default)]
+    pub company: Option<String>,
+    /// Copyright notice recorded with the assembly.
+    #[serde(default)]
+    pub copyright: Option<String>,
+}
+
+fn default_output_file() -> PathBuf {
+    PathBuf::from("output.winmd")
+}
+
+/// A variant of the config, e.g. musl instead of glibc headers:
+///
+/// ```toml
+/// [profile.musl]
+/// include_paths = ["/usr/include/x86_64-linux-musl"]
+/// remove_include_paths = ["/usr/include/x86_64-linux-gnu"]
+/// defines = { _GNU_SOURCE = false }
+/// remove_partitions = ["posix.gnu"]
+/// ```
+///
+/// Removals are applied first, then additions. Removing something the
+/// config does not have is an error, so a renamed partition does not
+/// silently leave a profile stale.
+#[derive(Debug, Default, Deserialize)]
+pub struct ProfileConfig {
+    /// Partitions added after the config's own.
+    #[serde(default)]
+    pub partition: Vec<PartitionConfig>,
+    /// Namespaces of partitions to drop.
+    #[serde(default)]
+    pub remove_partitions: Vec<String>,
+    /// Top-level defines to add, replacing any of the same name.
+    #[serde(default)]
+    pub defines: BTreeMap<String, DefineValue>,
+    /// Top-level defines to drop.
+    #[serde(default)]
+    pub remove_defines: Vec<String>,
+    /// Include paths searched after the config's own.
+    #[serde(default)]
+    pub include_paths: Vec<PathBuf>,
+    /// Include paths to drop.
+    #[serde(default)]
+    pub remove_include_paths: Vec<PathBuf>,
+    /// Clang arguments appended to the top-level ones.
+    #[serde(default)]
+    pub clang_args: Vec<String>,
+}
+
+/// An additional winmd generated from the same config.
+///
+/// ```toml
+/// [[assembly]]
+/// name = "PosixExt"
+/// file = "posix-ext.winmd"
+/// partitions = ["posix.ext.aio", "posix.ext.mqueue"]
+/// ```
+///
+/// All assemblies share one extraction pass and one type registry, so a
+/// type referenced across assemblies resolves to the partition that
+/// defines it, as with `[[type_import]]`.
+#[derive(Debug, Deserialize)]
+pub struct AssemblyConfig {
+    /// Assembly name written into the winmd.
+    pub name: String,
+    /// Output file path, relative to the config directory.
+    pub file: PathBuf,
+    /// Namespaces of the `[[partition]]`s emitted into this winmd, along
+    /// with the namespaces derived from them (C++ namespaces,
+    /// `namespace_from_path`, `overrides`, `group_by_handle`).
+    pub partitions: Vec<String>,
+}
+
+impl AssemblyConfig {
+    pub fn new(name: impl Into<String>, file: impl Into<PathBuf>) -> Self {
+        Self {
+            name: name.into(),
+            file: file.into(),
+            partitions: Vec::new(),
+        }
+    }
+
+    /// Emit the partition with this namespace into this assembly.
+    pub fn partition(mut self, namespace: impl Into<String>) -> Self {
+        self.partitions.push(namespace.into());
+        self
+    }
+}
+
+impl Config {
+    /// Every header the partitions parse or traverse, resolved against
+    /// `base_dir` and the include paths, sorted and deduplicated.
+    pub fn input_headers(&self, base_dir: &Path) -> Vec<PathBuf> {
+        let mut paths: Vec<PathBuf> = self
+            .partition
+            .iter()
+            .flat_map(|p| {
+                let headers = p
+                    .expanded_headers(base_dir, &self.include_paths)
+                    .into_iter()
+                    .map(|h| resolve_header(&h, base_dir, &self.include_paths));
+                headers.chain(p.resolved_traverse(base_dir, &self.include_paths))
+            })
+            .chain(
+                self.pch
+                    .iter()
+                    .map(|h| resolve_header(h, base_dir, &self.include_paths)),
+            )
+            .collect();
+        paths.sort();
+        paths.dedup();
+        paths
+    }
+
+    /// Every file generation reads: the [`config_files`](Self::config_files),
+    /// the [`input_headers`](Self::input_headers) and the winmds of
+    /// `[[type_import]]` and `[[reference]]` entries.
+    pub fn input_files(&self, base_dir: &Path) -> Vec<PathBuf> {
+        let mut files = self.config_files.clone();
+        files.extend(self.input_headers(base_dir));
+        for (winmd, _) in self.winmd_import_filters() {
+            let path = resolve_header(winmd, base_dir, &self.include_paths);
+            if !files.contains(&path) {
+                files.push(path);
+            }
+        }
+        files
+    }
+
+    /// Start building a config in code instead of loading TOML.
+    ///
+    /// ```
+    /// use bnd_winmd::config::{Config, PartitionConfig};
+    ///
+    /// let cfg = Config::builder()
+    ///     .output("posix")
+    ///     .include_path("/usr/include/x86_64-linux-gnu")
+    ///     .partition(PartitionConfig::new("posix.time").header("time.h").library("c"))
+    ///     .build()
+    ///     .unwrap();
+    /// assert_eq!(cfg.partition[0].traverse_files().len(), 1);
+    /// ```
+    pub fn builder() -> ConfigBuilder {
+        ConfigBuilder::default()
+    }
+}
+
+/// Builder for [`Config`]; see [`Config::builder`].
+///
+/// Mirrors the TOML layout one-to-one. [`build`](Self::build) checks the
+/// fields that are mandatory in TOML (`output.name`, and `library` /
+/// `headers` on every partition) after applying `[discover]` and
+/// `frameworks`.
+#[derive(Debug, Default)]
+pub struct ConfigBuilder {
+    output_name: Option<String>,
+    output_file: Option<PathBuf>,
+    output_version: Option<AssemblyVersion>,
+    output_company: Option<String>,
+    output_copyright: Option<String>,
+    assembly: Vec<AssemblyConfig>,
+    include_paths: Vec<PathBuf>,
+    framework_paths: Vec<PathBuf>,
+    library_paths: Vec<PathBuf>,
+    clang_args: Vec<String>,
+    defines: BTreeMap<String, DefineValue>,
+    shared_translation_unit: bool,
+    pch: Vec<PathBuf>,
+    scratch_dir: Option<PathBuf>,
+    sysroot: Option<PathBuf>,
+    flavor: Flavor,
+    discover: DiscoverConfig,
+    partition: Vec<PartitionConfig>,
+    namespace_overrides: BTreeMap<String, String>,
+    type_import: Vec<TypeImportConfig>,
+    reference: Vec<ReferenceConfig>,
+    inject_type: Vec<InjectTypeConfig>,
+    raii_free: Vec<RaiiFreeConfig>,
+    param_association: Vec<ParamAssociationConfig>,
+    param_attributes: Vec<ParamAttributesConfig>,
+    string_params: bool,
+    strings: StringsConfig,
+    return_conventions: BTreeMap<String, ReturnConvention>,
+    synthesize_enum: Vec<SynthesizeEnumConfig>,
+    enum_type: BTreeMap<String, String>,
+    constant_type: BTreeMap<String, String>,
+    type_map: BTreeMap<String, String>,
+    rename: Vec<RenameConfig>,
+    shim: Option<ShimConfig>,
+    layout_tests: Option<LayoutTestsConfig>,
+    interface: Vec<String>,
+    group_by_handle: bool,
+    calling_convention: Option<CallConv>,
+    name_collision: Severity,
+    layout_mismatch: Severity,
+    clang_errors: Severity,
+    allow_duplicate_types: bool,
+    strict: bool,
+    fail_on: Vec<SkipKind>,
+    type_map_hook: Option<Arc<dyn TypeMapHook>>,
+    progress: Option<Arc<dyn ProgressSink>>,
+}
+
+impl ConfigBuilder {
+    /// Assembly name written into the winmd (`[output] name`).
+    pub fn output(mut self, name: impl Into<String>) -> Self {
+        self.output_name = Some(name.into());
+        self
+    }
+
+    /// Output file path (`[output] file`).
+    pub fn output_file(mut self, file: impl Into<PathBuf>) -> Self {
+        self.output_file = Some(file.into());
+        self
+    }
+
+    /// Assembly version (`[output] version`).
+    pub fn version(mut self, version: AssemblyVersion) -> Self {
+        self.output_version = Some(version);
+        self
+    }
+
+    /// Company recorded with the assembly (`[output] company`).
+    pub fn company(mut self, company: impl Into<String>) -> Self {
+        self.output_company = Some(company.into());
+        self
+    }
+
+    /// Copyright notice recorded with the assembly (`[output] copyright`).
+    pub fn copyright(mut self, copyright: impl Into<String>) -> Self {
+        self.output_copyright = Some(copyright.into());
+        self
+    }
+
+    /// Generate a further winmd (`[[assembly]]`).
+    pub fn assembly(mut self, assembly: AssemblyConfig) -> Self {
+        self.assembly.push(assembly);
+        self
+    }
+
+    pub fn include_path(mut self, path: impl Into<PathBuf>) -> Self {
+        self.include_paths.push(path.into());
+        self
+    }
+
+    /// Search `path` for partition frameworks (see [`Config::framework_paths`]).
+    pub fn framework_path(mut self, path: impl Into<PathBuf>) -> Self {
+        self.framework_paths.push(path.into());
+        self
+    }
+
+    /// Search `path` for partition libraries (see [`Config::library_paths`]).
+    pub fn library_path(mut self, path: impl Into<PathBuf>) -> Self {
+        self.library_paths.push(path.into());
+        self
+    }
+
+    pub fn clang_arg(mut self, arg: impl Into<String>) -> Self {
+        self.clang_args.push(arg.into());
+        self
+    }
+
+    /// Define a preprocessor macro for all partitions (`[defines]`).
+    pub fn define(mut self, name: impl Into<String>, value: impl Into<DefineValue>) -> Self {
+        self.defines.insert(name.into(), value.into());
+        self
+    }
+
+    /// Parse partitions sharing language and `clang_args` as one
+    /// translation unit.
+    pub fn shared_translation_unit(mut self, shared: bool) -> Self {
+        self.shared_translation_unit = shared;
+        self
+    }
+
+    /// Precompile a header for every partition's parse.
+    pub fn pch(mut self, header: impl Into<PathBuf>) -> Self {
+        self.pch.push(header.into());
+        self
+    }
+
+    pub fn partition(mut self, partition: PartitionConfig) -> Self {
+        self.partition.push(partition);
+        self
+    }
+
+    pub fn namespace_override(mut self, name: impl Into<String>, ns: impl Into<String>) -> Self {
+        self.namespace_overrides.insert(name.into(), ns.into());
+        self
+    }
+
+    pub fn type_import(mut self, import: TypeImportConfig) -> Self {
+        self.type_import.push(import);
+        self
+    }
+
+    pub fn reference(mut self, reference: ReferenceConfig) -> Self {
+        self.reference.push(reference);
+        self
+    }
+
+    pub fn inject_type(mut self, inject: InjectTypeConfig) -> Self {
+        self.inject_type.push(inject);
+        self
+    }
+
+    pub fn raii_free(mut self, alloc: impl Into<String>, free: impl Into<String>) -> Self {
+        self.raii_free.push(RaiiFreeConfig {
+            alloc: alloc.into(),
+            free: free.into(),
+        });
+        self
+    }
+
+    pub fn param_association(mut self, assoc: ParamAssociationConfig) -> Self {
+        self.param_association.push(assoc);
+        self
+    }
+
+    pub fn param_attributes(mut self, attrs: ParamAttributesConfig) -> Self {
+        self.param_attributes.push(attrs);
+        self
+    }
+
+    pub fn string_params(mut self, all: bool) -> Self {
+        self.string_params = all;
+        self
+    }
+
+    pub fn string_function(mut self, function: impl Into<String>) -> Self {
+        self.strings.functions.push(function.into());
+        self
+    }
+
+    pub fn return_convention(
+        mut self,
+        function: impl Into<String>,
+        convention: ReturnConvention,
+    ) -> Self {
+        self.return_conventions.insert(function.into(), convention);
+        self
+    }
+
+    pub fn synthesize_enum(mut self, synth: SynthesizeEnumConfig) -> Self {
+        self.synthesize_enum.push(synth);
+        self
+    }
+
+    pub fn enum_type(mut self, name: impl Into<String>, ty: impl Into<String>) -> Self {
+        self.enum_type.insert(name.into(), ty.into());
+        self
+    }
+
+    pub fn constant_type(mut self, name: impl Into<String>, ty: impl Into<String>) -> Self {
+        self.constant_type.insert(name.into(), ty.into());
+        self
+    }
+
+    pub fn type_map(mut self, name: impl Into<String>, ty: impl Into<String>) -> Self {
+        self.type_map.insert(name.into(), ty.into());
+        self
+    }
+
+    pub fn rename(mut self, rule: RenameConfig) -> Self {
+        self.rename.push(rule);
+        self
+    }
+
+    pub fn shim(mut self, shim: ShimConfig) -> Self {
+        self.shim = Some(shim);
+        self
+    }
+
+    pub fn layout_tests(mut self, layout_tests: LayoutTestsConfig) -> Self {
+        self.layout_tests = Some(layout_tests);
+        self
+    }
+
+    pub fn interface(mut self, name: impl Into<String>) -> Self {
+        self.interface.push(name.into());
+        self
+    }
+
+    /// Group functions by their first-parameter handle type
+    /// (see [`Config::group_by_handle`]).
+    pub fn group_by_handle(mut self, group: bool) -> Self {
+        self.group_by_handle = group;
+        self
+    }
+
+    /// Default calling convention for every partition
+    /// (see [`Config::calling_convention`]).
+    pub fn calling_convention(mut self, cc: CallConv) -> Self {
+        self.calling_convention = Some(cc);
+        self
+    }
+
+    pub fn name_collision(mut self, severity: Severity) -> Self {
+        self.name_collision = severity;
+        self
+    }
+
+    pub fn layout_mismatch(mut self, severity: Severity) -> Self {
+        self.layout_mismatch = severity;
+        self
+    }
+
+    /// How to report clang errors (see [`Config::clang_errors`]).
+    pub fn clang_errors(mut self, severity: Severity) -> Self {
+        self.clang_errors = severity;
+        self
+    }
+
+    pub fn allow_duplicate_types(mut self, allow: bool) -> Self {
+        self.allow_duplicate_types = allow;
+        self
+    }
+
+    pub fn strict(mut self, strict: bool) -> Self {
+        self.strict = strict;
+        self
+    }
+
+    pub fn fail_on(mut self, kind: SkipKind) -> Self {
+        self.fail_on.push(kind);
+        self
+    }
+
+    /// Override the C type mapping; see [`TypeMapHook`].
+    pub fn type_map_hook(mut self, hook: impl TypeMapHook + 'static) -> Self {
+        self.type_map_hook = Some(Arc::new(hook));
+        self
+    }
+
+    /// Report partition and emit progress; see [`ProgressSink`].
+    pub fn progress(mut self, sink: impl ProgressSink + 'static) -> Self {
+        self.progress = Some(Arc::new(sink));
+        self
+    }
+
+    /// Finish the config, failing if a field that TOML requires is missing.
+    pub fn build(self) -> anyhow::Result<Config> {
+        let Some(name) = self.output_name else {
+            anyhow::bail!("config builder: output name is required");
+        };
+        Config {
+            output: OutputConfig {
+                name,
+                file: self.output_file.unwrap_or_else(default_output_file),
+                version: self.output_version,
+                company: self.output_company,
+                copyright: self.output_copyright,
+            },
+            assembly: self.assembly,
+            include_paths: self.include_paths,
+            framework_paths: self.framework_paths,
+            library_paths: self.library_paths,
+            clang_args: self.clang_args,
+            defines: self.defines,
+            shared_translation_unit: self.shared_translation_unit,
+            pch: self.pch,
+            scratch_dir: self.scratch_dir,
+            sysroot: self.sysroot,
+            // `library` tables are TOML-only; builder partitions name
+            // their library directly.
+            platform: None,
+            flavor: self.flavor,
+            discover: self.discover,
+            partition: self.partition,
+            profile: BTreeMap::new(),
+            namespace_overrides: self.namespace_overrides,
+            type_import: self.type_import,
+            reference: self.reference,
+            inject_type: self.inject_type,
+            raii_free: self.raii_free,
+            param_association: self.param_association,
+            param_attributes: self.param_attributes,
+            string_params: self.string_params,
+            strings: self.strings,
+            return_conventions: self.return_conventions,
+            synthesize_enum: self.synthesize_enum,
+            enum_type: self.enum_type,
+            constant_type: self.constant_type,
+            type_map: self.type_map,
+            rename: self.rename,
+            shim: self.shim,
+            layout_tests: self.layout_tests,
+            interface: self.interface,
+            group_by_handle: self.group_by_handle,
+            calling_convention: self.calling_convention,
+            name_collision: self.name_collision,
+            layout_mismatch: self.layout_mismatch,
+            clang_errors: self.clang_errors,
+            allow_duplicate_types: self.allow_duplicate_types,
+            strict: self.strict,
+            fail_on: self.fail_on,
+            type_map_hook: self.type_map_hook,
+            progress: self.progress,
+            config_files: Vec::new(),
+            applied_profile: None,
+        }
+        .finish()
+    }
+
+    /// Write generated clang inputs to `dir` (see [`Config::scratch_dir`]).
+    pub fn scratch_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.scratch_dir = Some(dir.into());
+        self
+    }
+
+    /// Scrape headers from a target sysroot (see [`Config::sysroot`]).
+    pub fn sysroot(mut self, path: impl Into<PathBuf>) -> Self {
+        self.sysroot = Some(path.into());
+        self
+    }
+
+    /// Parse headers written for another compiler (see [`Flavor`]).
+    pub fn flavor(mut self, flavor: Flavor) -> Self {
+        self.flavor = flavor;
+        self
+    }
+
+    /// Query pkg-config for `package` (see [`DiscoverConfig`]).
+    pub fn pkg_config(mut self, package: impl Into<String>) -> Self {
+        self.discover.pkg_config = Some(package.into());
+        self
+    }
+}
+
+impl Config {
+    /// [`scratch_dir`](Self::scratch_dir), or its default.
+    pub fn resolved_scratch_dir(&self) -> PathBuf {
+        self.scratch_dir.clone().unwrap_or_else(default_scratch_dir)
+    }
+
+    /// The `cargo:` directives that link the partitions' libraries:
+    /// a `rustc-link-search` per [`library_paths`](Self::library_paths)
+    /// entry and framework directory, then a `rustc-link-lib` per distinct
+    /// library. `delay_load` partitions are loaded at run time and left
+    /// out.
+    pub fn link_directives(&self) -> Vec<String> {
+        let mut directives: Vec<String> = self
+            .library_paths
+            .iter()
+            .map(|dir| format!("cargo:rustc-link-search=native={}", dir.display()))
+            .collect();
+        let mut libraries = Vec::new();
+        for p in self.partition.iter().filter(|p| !p.delay_load) {
+            let lib = match p.library.split_once(".framework/") {
+                Some((name, binary)) if name == binary => {
+                    for dir in p.clang_args.iter().filter_map(|a| a.strip_prefix("-F")) {
+                        let search = format!("cargo:rustc-link-search=framework={dir}");
+                        if !directives.contains(&search) {
+                            directives.push(search);
+                        }
+                    }
+                    format!("cargo:rustc-link-lib=framework={name}")
+                }
+                _ => format!("cargo:rustc-link-lib={}", p.library),
+            };
+            if !libraries.contains(&lib) {
+                libraries.push(lib);
+            }
+        }
+        directives.extend(libraries);
+        directives
+    }
+
+    /// A `build.rs` for the generated crate that prints
+    /// [`link_directives`](Self::link_directives).
+    pub fn build_script(&self) -> String {
+        let mut out = format!(
+            "// Generated by bnd-winmd from the `{}` config. Do not edit.\n\nfn main() {{\n",
+            self.output.name
+        );
+        for directive in self.link_directives() {
+            out.push_str(&format!("    println!({directive:?});\n"));
+        }
+        out.push_str("}\n");
+        out
+    }
+
+    /// Whether a skipped declaration of `kind` fails generation.
+    pub fn fails_on(&self, kind: SkipKind) -> bool {
+        self.strict || self.fail_on.contains(&kind)
+    }
+
+    /// `(winmd, namespace)` of every external winmd whose types seed the
+    /// registry: `[[type_import]]` entries, then `[[reference]]` entries.
+    pub fn winmd_imports(&self) -> impl Iterator<Item = (&Path, &str)> {
+        self.winmd_import_filters()
+            .map(|(winmd, filter)| (winmd, filter.namespace))
+    }
+
+    /// Like [`winmd_imports`](Self::winmd_imports), with the `types` and
+    /// `exclude` filters of `[[type_import]]` entries.
+    pub fn winmd_import_filters(&self) -> impl Iterator<Item = (&Path, ImportFilter<'_>)> {
+        let imports = self.type_import.iter().map(|ti| {
+            let filter = ImportFilter {
+                namespace: &ti.namespace,
+                types: &ti.types,
+                exclude: &ti.exclude,
+            };
+            (ti.winmd.as_path(), filter)
+        });
+        let references = self.reference.iter().map(|r| {
+            let filter = ImportFilter {
+                namespace: &r.namespace,
+                types: &[],
+                exclude: &[],
+            };
+            (r.winmd.as_path(), filter)
+        });
+        imports.chain(references)
+    }
+
+    /// Assembly the `[[partition]]` with `namespace` is emitted into: an
+    /// `[[assembly]]` listing it, or `None` for `[output]`. Derived
+    /// namespaces follow the partition they came from.
+    pub fn assembly_of(&self, namespace: &str) -> Option<&AssemblyConfig> {
+        self.assembly
+            .iter()
+            .find(|a| a.partitions.iter().any(|ns| ns == namespace))
+    }
+
+    /// Every `[[assembly]]` partition must exist and belong to one
+    /// assembly only, and assembly names must be distinct.
+    fn check_assemblies(&self) -> anyhow::Result<()> {
+        let mut names = vec![self.output.name.as_str()];
+        let mut claimed: Vec<&str> = Vec::new();
+        for a in &self.assembly {
+            if names.contains(&a.name.as_str()) {
+                anyhow::bail!("assembly `{}` is declared twice", a.name);
+            }
+            names.push(&a.name);
+            for ns in &a.partitions {
+                if !self.partition.iter().any(|p| &p.namespace == ns) {
+                    anyhow::bail!("assembly `{}`: no partition `{ns}`", a.name);
+                }
+                if claimed.contains(&ns.as_str()) {
+                    anyhow::bail!("partition `{ns}` is listed by more than one assembly");
+                }
+                claimed.push(ns);
+            }
+        }
+        Ok(())
+    }
+
+    /// Apply `sysroot` and `[discover]` results and check that every
+    /// partition ended up with a library. Called by [`load_config`] and
+    /// [`ConfigBuilder::build`].
+    fn finish(mut self) -> anyhow::Result<Config> {
+        self.expand_env_vars()?;
+        if let Some(sysroot) = &self.sysroot {
+            for inc in self
+                .include_paths
+                .iter_mut()
+                .chain(self.framework_paths.iter_mut())
+                .chain(self.library_paths.iter_mut())
+            {
+                *inc = under_sysroot(sysroot, inc);
+            }
+            for p in &mut self.partition {
+                for path in p.headers.iter_mut().chain(p.traverse.iter_mut()) {
+                    *path = under_sysroot(sysroot, path);
+                }
+                for o in &mut p.overrides {
+                    o.file = under_sysroot(sysroot, &o.file);
+                }
+            }
+        }
+        if let Some(package) = &self.discover.pkg_config {
+            let found = pkg_config_probe(package, self.sysroot.as_deref())?;
+            tracing::info!(
+                package = %package,
+                include_paths = found.include_paths.len(),
+                library = ?found.library,
+                "discovered package via pkg-config"
+            );
+            for inc in found.include_paths {
+                if !self.include_paths.contains(&inc) {
+                    self.include_paths.push(inc);
+                }
+            }
+            for dir in found.library_paths {
+                if !self.library_paths.contains(&dir) {
+                    self.library_paths.push(dir);
+                }
+            }
+            if let Some(lib) = found.library {
+                for p in &mut self.partition {
+                    if p.library.is_empty() {
+                        p.library = lib.clone();
+                    }
+                }
+            }
+        }
+        if let Some(cc) = self.calling_convention {
+            for p in &mut self.partition {
+                p.calling_convention.get_or_insert(cc);
+            }
+        }
+        self.apply_frameworks()?;
+        for p in &self.partition {
+            if p.headers.is_empty() {
+                anyhow::bail!(
+                    "partition {} has no headers — set `headers` or `frameworks`",
+                    p.namespace
+                );
+            }
+            if p.library.is_empty() {
+                anyhow::bail!(
+                    "partition {} has no library — set `library` or `[discover] pkg_config`",
+                    p.namespace
+                );
+            }
+            if let Some(width) = p.wchar_width
+                && !matches!(width, 2 | 4)
+            {
+                anyhow::bail!(
+                    "partition {}: wchar_width must be 2 or 4, got {width}",
+                    p.namespace
+                );
+            }
+        }
+        self.check_assemblies()?;
+        self.check_defines()?;
+        Ok(self)
+    }
+
+    /// Apply `[profile.<name>]` (see [`ProfileConfig`]).
+    fn apply_profile(&mut self, name: &str) -> anyhow::Result<()> {
+        let Some(profile) = self.profile.remove(name) else {
+            anyhow::bail!(
+                "no profile `{name}` (defined: {})",
+                self.profile.keys().cloned().collect::<Vec<_>>().join(", ")
+            );
+        };
+        for ns in &profile.remove_partitions {
+            let before = self.partition.len();
+            self.partition.retain(|p| &p.namespace != ns);
+            if self.partition.len() == before {
+                anyhow::bail!("profile {name}: no partition `{ns}` to remove");
+            }
+        }
+        for define in &profile.remove_defines {
+            if self.defines.remove(define).is_none() {
+                anyhow::bail!("profile {name}: no define `{define}` to remove");
+            }
+        }
+        for path in &profile.remove_include_paths {
+            let before = self.include_paths.len();
+            self.include_paths.retain(|p| p != path);
+            if self.include_paths.len() == before {
+                anyhow::bail!(
+                    "profile {name}: no include path `{}` to remove",
+                    path.display()
+                );
+            }
+        }
+        self.partition.extend(profile.partition);
+        self.defines.extend(profile.defines);
+        self.include_paths.extend(profile.include_paths);
+        self.clang_args.extend(profile.clang_args);
+        Ok(())
+    }
+
+    /// Resolve partition `frameworks` to their `Headers` directories: add
+    /// `-F` for each directory they were found in, fill empty `headers`
+    /// with the umbrella headers and empty `traverse` with the `Headers`
+    /// directories, and default `library` to the first framework.
+    fn apply_frameworks(&mut self) -> anyhow::Result<()> {
+        if self.partition.iter().all(|p| p.frameworks.is_empty()) {
+            return Ok(());
+        }
+        let mut search = self.framework_paths.clone();
+        for dir in ["/System/Library/Frameworks", "/Library/Frameworks"] {
+            let dir = match &self.sysroot {
+                Some(sysroot) => under_sysroot(sysroot, Path::new(dir)),
+                None => PathBuf::from(dir),
+            };
+            if !search.contains(&dir) {
+                search.push(dir);
+            }
+        }
+        for p in &mut self.partition {
+            let fill_headers = p.headers.is_empty();
+            let fill_traverse = p.traverse.is_empty();
+            for name in &p.frameworks {
+                let bundle = format!("{name}.framework");
+                let Some(dir) = search
+                    .iter()
+                    .find(|dir| dir.join(&bundle).join("Headers").is_dir())
+                else {
+                    anyhow::bail!(
+                        "partition {}: framework {name} not found in {} — set `sysroot` to the \
+                         macOS SDK (`xcrun --show-sdk-path`) or add `framework_paths`",
+                        p.namespace,
+                        search
+                            .iter()
+                            .map(|d| d.display().to_string())
+                            .collect::<Vec<_>>()
+                            .join(", ")
+                    );
+                };
+                let headers = dir.join(&bundle).join("Headers");
+                let flag = format!("-F{}", dir.display());
+                if !p.clang_args.contains(&flag) {
+                    p.clang_args.push(flag);
+                }
+                let umbrella = headers.join(format!("{name}.h"));
+                if fill_headers && umbrella.is_file() {
+                    p.headers.push(umbrella);
+                }
+                if fill_traverse {
+                    p.traverse.push(headers);
+                }
+            }
+            if p.library.is_empty()
+                && let Some(first) = p.frameworks.first()
+            {
+                p.library = format!("{first}.framework/{first}");
+            }
+        }
+        Ok(())
+    }
+
+    /// Substitute environment variables in every path a config names
+    /// (see [`expand_env`]).
+    fn expand_env_vars(&mut self) -> anyhow::Result<()> {
+        self.output.file = expand_env(&self.output.file).context("output file")?;
+        for a in &mut self.assembly {
+            a.file = expand_env(&a.file).with_context(|| format!("assembly {} file", a.name))?;
+        }
+        for (key, paths) in [
+            ("include_paths", &mut self.include_paths),
+            ("framework_paths", &mut self.framework_paths),
+            ("library_paths", &mut self.library_paths),
+            ("pch", &mut self.pch),
+        ] {
+            for path in paths {
+                *path = expand_env(path).context(key)?;
+            }
+        }
+        for (key, dir) in [
+            ("scratch_dir", &mut self.scratch_dir),
+            ("sysroot", &mut self.sysroot),
+        ] {
+            if let Some(dir) = dir {
+                *dir = expand_env(dir).context(key)?;
+            }
+        }
+        if let Some(shim) = &mut self.shim {
+            shim.source = expand_env(&shim.source).context("shim source")?;
+        }
+        if let Some(tests) = &mut self.layout_tests {
+            tests.file = expand_env(&tests.file).context("layout_tests file")?;
+        }
+        for p in &mut self.partition {
+            let ns = &p.namespace;
+            for path in &mut p.headers {
+                *path = expand_env(path).with_context(|| format!("partition {ns} headers"))?;
+            }
+            for path in &mut p.traverse {
+                *path = expand_env(path).with_context(|| format!("partition {ns} traverse"))?;
+            }
+            for o in &mut p.overrides {
+                o.file =
+                    expand_env(&o.file).with_context(|| format!("partition {ns} overrides"))?;
+            }
+        }
+        for t in &mut self.type_import {
+            t.winmd = expand_env(&t.winmd).context("type_import winmd")?;
+        }
+        for r in &mut self.reference {
+            r.winmd = expand_env(&r.winmd).context("reference winmd")?;
+        }
+        Ok(())
+    }
+
+    /// Check `[defines]` against each other and against `-D` / `-U` flags
+    /// in `clang_args` (see [`effective_clang_args`](Self::effective_clang_args)
+    /// for where they end up). A macro set to different values in two places (top level
+    /// and a partition, or `[defines]` and `clang_args`) is an error; the
+    /// same value twice is only warned about.
+    fn check_defines(&self) -> anyhow::Result<()> {
+        for name in self
+            .defines
+            .keys()
+            .chain(self.partition.iter().flat_map(|p| p.defines.keys()))
+        {
+            let mut chars = name.chars();
+            if !chars
+                .next()
+                .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+                || !chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+            {
+                anyhow::bail!("defines: `{name}` is not a valid macro name");
+            }
+        }
+
+        // `(source, name, flag)` of every macro set for all partitions.
+        let mut global = Vec::new();
+        for (name, flag) in macro_flags(&self.clang_args) {
+            global.push(("clang_args".to_string(), name, flag));
+        }
+        for (name, value) in &self.defines {
+            global.push(("[defines]".to_string(), name.clone(), value.flag(name)));
+        }
+        check_macro_flags(&[], &global)?;
+        for p in &self.partition {
+            let mut local = Vec::new();
+            for (name, flag) in macro_flags(&p.clang_args) {
+                let source = format!("partition {} clang_args", p.namespace);
+                local.push((source, name, flag));
+            }
+            for (name, value) in &p.defines {
+                let source = format!("partition {} defines", p.namespace);
+                local.push((source, name.clone(), value.flag(name)));
+            }
+            check_macro_flags(&global, &local)?;
+        }
+
+        Ok(())
+    }
+
+    /// The clang arguments every partition is parsed with: the
+    /// [`flavor`](Self::flavor)'s, `--sysroot`, [`clang_args`](Self::clang_args),
+    /// then [`defines`](Self::defines) as `-D` / `-U` flags. Derived from
+    /// the config on each call; `clang_args` itself holds only what the
+    /// config spells out.
+    pub fn effective_clang_args(&self) -> Vec<String> {
+        let mut args = self.flavor.clang_args();
+        if let Some(sysroot) = &self.sysroot {
+            args.push(format!("--sysroot={}", sysroot.display()));
+        }
+        args.extend(self.clang_args.iter().cloned());
+        args.extend(define_flags(&self.defines));
+        args
+    }
+}
+
+/// `-D` / `-U` flags for `defines`, in name order.
+fn define_flags(defines: &BTreeMap<String, DefineValue>) -> impl Iterator<Item = String> + '_ {
+    defines.iter().map(|(name, value)| value.flag(name))
+}
+
+/// Compare every `(source, name, flag)` of `new` with those before it and
+/// with `earlier`.
+fn check_macro_flags(
+    earlier: &[(String, String, String)],
+    new: &[(String, String, String)],
+) -> anyhow::Result<()> {
+    for (i, (source, name, flag)) in new.iter().enumerate() {
+        for (other_source, other_name, other_flag) in earlier.iter().chain(&new[..i]) {
+            if name != other_name {
+                continue;
+            }
+            if flag != other_flag {
+                anyhow::bail!(
+                    "defines: `{name}` is set by both {other_source} (`{other_flag}`) \
+                     and {source} (`{flag}`) — keep one"
+                );
+            }
+            tracing::warn!(
+                name = %name,
+                first = %other_source,
+                again = %source,
+                "defines: macro set twice to the same value"
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Replace `${VAR}` and `$ENV{VAR}` in `path` with the value of the
+/// environment variable `VAR`, failing if it is unset. `$$` is a literal
+/// `$`. Paths that are not UTF-8 are returned unchanged.
+fn expand_env(path: &Path) -> anyhow::Result<PathBuf> {
+    let Some(text) = path.to_str() else {
+        return Ok(path.to_path_buf());
+    };
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(i) = rest.find('$') {
+        out.push_str(&rest[..i]);
+        rest = &rest[i + 1..];
+        let name_start = if rest.starts_with('{') {
+            1
+        } else if rest.starts_with("ENV{") {
+            4
+        } else if let Some(after) = rest.strip_prefix('$') {
+            out.push('$');
+            rest = after;
+            continue;
+        } else {
+            out.push('$');
+            continue;
+        };
+        let Some(len) = rest[name_start..].find('}') else {
+            anyhow::bail!("unterminated `${{` in `{text}`");
+        };
+        let name = &rest[name_start..name_start + len];
+        let value = std::env::var(name).with_context(|| {
+            format!("environment variable `{name}` used in `{text}` is not set")
+        })?;
+        out.push_str(&value);
+        rest = &rest[name_start + len + 1..];
+    }
+    out.push_str(rest);
+    Ok(PathBuf::from(out))
 }
 
-/// Output file settings.
-#[derive(Debug, Deserialize)]
-pub struct OutputConfig {
-    /// Assembly name written into the winmd.
-    pub name: String,
-    /// Output file path (e.g. `MyLib.winmd`).
-    #[serde(default = "default_output_file")]
-    pub file: PathBuf,
-}
+/// Re-root an absolute path under `sysroot`. Relative paths, and paths
+/// already inside the sysroot, are returned unchanged.
+fn under_sysroot(sysroot: &Path, path: &Path) -> PathBuf {
+    match path.strip_prefix("/") {
+        Ok(rel) if !path.starts_with(sysroot) => sysroot.join(rel),
+        _ => path.to_path_buf(),
+    }
+}
+
+/// Header auto-discovery.
+///
+/// ```toml
+/// [discover]
+/// pkg_config = "openssl"
+/// ```
+///
+/// Runs `pkg-config --cflags-only-I` / `--libs-only-L` / `--libs-only-l`
+/// for the package. Include directories are appended to `include_paths`,
+/// library directories to `library_paths`, and the first library becomes
+/// the default `library` for partitions that omit it.
+#[derive(Debug, Default, Deserialize)]
+pub struct DiscoverConfig {
+    /// pkg-config package name (e.g. `openssl`, `zlib`).
+    #[serde(default)]
+    pub pkg_config: Option<String>,
+}
+
+/// What pkg-config reported for a package.
+struct PkgConfigResult {
+    include_paths: Vec<PathBuf>,
+    library_paths: Vec<PathBuf>,
+    library: Option<String>,
+}
+
+/// With a `sysroot`, pkg-config is pointed at it via
+/// `PKG_CONFIG_SYSROOT_DIR` so the reported `-I` and `-L` paths are
+/// already re-rooted.
+fn pkg_config_probe(package: &str, sysroot: Option<&Path>) -> anyhow::Result<PkgConfigResult> {
+    let query = |flag: &str| -> anyhow::Result<String> {
+        let mut cmd = std::process::Command::new("pkg-config");
+        cmd.args([flag, package]);
+        if let Some(sysroot) = sysroot {
+            cmd.env("PKG_CONFIG_SYSROOT_DIR", sysroot);
+        }
+        let out = cmd
+            .output()
+            .map_err(|e| anyhow::anyhow!("failed to run pkg-config: {e}"))?;
+        if !out.status.success() {
+            anyhow::bail!(
+                "pkg-config {flag} {package} failed: {}",
+                String::from_utf8_lossy(&out.stderr).trim()
+            );
+        }
+        Ok(String::from_utf8_lossy(&out.stdout).into_owned())
+    };
+
+    let include_paths = query("--cflags-only-I")?
+        .split_whitespace()
+        .filter_map(|arg| arg.strip_prefix("-I"))
+        .map(PathBuf::from)
+        .collect();
+    let library_paths = query("--libs-only-L")?
+        .split_whitespace()
+        .filter_map(|arg| arg.strip_prefix("-L"))
+        .map(PathBuf::from)
+        .collect();
+    let library = query("--libs-only-l")?
+        .split_whitespace()
+        .find_map(|arg| arg.strip_prefix("-l"))
+        .map(str::to_string);
+
+    Ok(PkgConfigResult {
+        include_paths,
+        library_paths,
+        library,
+    })
+}
+
+/// A single partition — maps a set of headers to one namespace.
+#[derive(Debug, Deserialize)]
+pub struct PartitionConfig {
+    /// ECMA-335 namespace (e.g. `MyLib.Graphics`).
+    pub namespace: String,
+    /// Library name for P/Invoke `ImplMap` entries (e.g. `mylib.so`).
+    /// May be omitted when `[discover]` provides a default. In TOML it may
+    /// also be a table of names per platform, resolved on load (see
+    /// [`Config::platform`]):
+    ///
+    /// ```toml
+    /// library = { linux = "c", macos = "System", windows = "ucrtbase" }
+    /// ```
+    #[serde(default)]
+    pub library: String,
+    /// Versioned file name of `library` (`libssl.so.3`), for loaders
+    /// that `dlopen` it: the unversioned `libssl.so` link is only
+    /// installed with the development package. `library` stays the
+    /// `ImplMap` scope, which windows-bindgen turns into
+    /// `#[link(name = ...)]`; the soname is recorded on each `Apis` class
+    /// as a `LibrarySonameAttribute` and used by the C# backend's
+    /// `DllImport`.
+    #[serde(default)]
+    pub library_soname: Option<String>,
+    /// Resolve the partition's functions with `dlopen`/`dlsym` on first
+    /// call instead of linking `library`, so a binary still starts when
+    /// an optional library is missing. Recorded on each `Apis` class as a
+    /// `DelayLoadAttribute`; the Rust `-sys` backend declares the
+    /// functions with `bnd_delayload::link!`.
+    #[serde(default)]
+    pub delay_load: bool,
+    /// Headers to include (all are parsed for dependency resolution).
+    /// Entries may be directories (every `.h` file below them) or glob
+    /// patterns such as `openssl/*.h`. May be left empty when
+    /// `frameworks` supplies the umbrella headers.
+    #[serde(default)]
+    pub headers: Vec<PathBuf>,
+    /// Which files to actually emit declarations from.
+    /// If empty, uses `headers`. Entries may be directories (every file
+    /// below them) or glob patterns.
+    #[serde(default)]
+    pub traverse: Vec<PathBuf>,
+    /// Extra clang arguments (e.g. `-I/usr/include`).
+    #[serde(default)]
+    pub clang_args: Vec<String>,
+    /// Preprocessor defines for this partition, after the top-level ones.
+    #[serde(default)]
+    pub defines: BTreeMap<String, DefineValue>,
+    /// Source language of the headers.
+    #[serde(default)]
+    pub language: Language,
+    /// How bitfields are represented in struct fields.
+    #[serde(default)]
+    pub bitfields: BitfieldMode,
+    /// How `__int128` / `unsigned __int128` are represented.
+    #[serde(default)]
+    pub int128: Int128Mode,
+    /// How `long double` is represented.
+    #[serde(default)]
+    pub long_double: LongDoubleMode,
+    /// Width of `wchar_t` in bytes (2 or 4). Defaults to clang's
+    /// `sizeof(wchar_t)` for the target: 4 on Linux, 2 for Windows
+    /// targets or with `-fshort-wchar`.
+    #[serde(default)]
+    pub wchar_width: Option<usize>,
+    /// Derive sub-namespaces from the subdirectories of directory
+    /// `traverse` entries: with `traverse = ["linux"]`, declarations from
+    /// `linux/netlink/*.h` land in `<namespace>.netlink`. Files directly
+    /// inside the directory keep the partition's namespace.
+    #[serde(default)]
+    pub namespace_from_path: bool,
+    /// Traversed files whose declarations go to another namespace.
+    #[serde(default)]
+    pub overrides: Vec<FileNamespaceConfig>,
+    /// macOS frameworks (`CoreFoundation`) whose headers this partition
+    /// binds, looked up in [`Config::framework_paths`]. Empty `headers`
+    /// and `traverse` default to each framework's umbrella header and
+    /// `Headers` directory, and an empty `library` to
+    /// `<First>.framework/<First>`, which dyld finds in the standard
+    /// framework directories.
+    #[serde(default)]
+    pub frameworks: Vec<String>,
+    /// C source appended to the partition's translation unit after its
+    /// headers (see [`ExtraSource`]).
+    #[serde(default)]
+    pub extra_source: Vec<ExtraSource>,
+    /// How functions and constants are spread over `Apis` classes.
+    #[serde(default)]
+    pub apis_split: ApisSplit,
+    /// Most methods (and, separately, constants) per `Apis` class; larger
+    /// classes are sharded into the `Apis` classes of `<namespace>.apis1`,
+    /// `<namespace>.apis2`, ...
+    #[serde(default)]
+    pub max_methods: Option<usize>,
+    /// Calling convention for functions clang reports as the platform
+    /// default (`cdecl`), for libraries whose convention is set by the
+    /// build rather than the headers: a 32-bit stdcall DLL whose
+    /// `__stdcall` appears only in its `.def` file. Conventions clang does
+    /// see in the headers are kept; function pointer types are unchanged.
+    #[serde(default)]
+    pub calling_convention: Option<CallConv>,
+}
+
+/// Inline C appended to a partition's translation unit, for small
+/// supplements without a header on disk.
+///
+/// ```toml
+/// [[partition.extra_source]]
+/// code = "typedef unsigned int my_id_t;"
+/// ```
+///
+/// The snippets are written to a scratch header included after the
+/// partition's `headers`, so they see every declaration of those headers.
+/// Their declarations are always in scope for the partition.
+#[derive(Debug, Clone, Deserialize)]
+pub struct ExtraSource {
+    pub code: String,
+}
+
+/// Moves the declarations of one traversed header into their own
+/// namespace.
+///
+/// ```toml
+/// [[partition]]
+/// namespace = "posix.stat"
+/// overrides = [{ file = "bits/struct_stat.h", namespace = "posix.stat.types" }]
+/// ```
+///
+/// `file` is resolved like a `traverse` entry. Takes precedence over
+/// [`namespace_from_path`](PartitionConfig::namespace_from_path).
+#[derive(Debug, Clone, Deserialize)]
+pub struct FileNamespaceConfig {
+    pub file: PathBuf,
+    pub namespace: String,
+}
+
+/// Winmd representation of `__int128`, which has no metadata type (see
+/// `docs/design/features/Int128Limitation.md`).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Int128Mode {
+    /// Skip declarations that use it.
+    #[default]
+    Skip,
+    /// `[u64; 2]`, low word first. Right size, but 8- instead of 16-byte
+    /// aligned, signedness is lost and by-value parameters use the wrong ABI.
+    U64Pair,
+}
+
+/// Winmd representation of `long double`, which has no metadata type.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum LongDoubleMode {
+    /// Skip declarations that use it.
+    #[default]
+    Skip,
+    /// `[u8; sizeof(long double)]`: struct layouts stay right, values are
+    /// opaque.
+    Bytes,
+    /// `f64`: values are usable where the platform's `long double` is a
+    /// double, otherwise structs holding one change size and by-value
+    /// parameters use the wrong ABI.
+    F64,
+}
+
+/// Winmd representation of C bitfields.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BitfieldMode {
+    /// Each run of contiguous bitfields becomes the smallest unsigned
+    /// integer covering its bits.
+    #[default]
+    Flatten,
+    /// Bitfields sharing a C storage unit become one `_bitfieldN` field of
+    /// the unit's size, with a `NativeBitfieldAttribute` per logical field
+    /// (the win32metadata representation).
+    Storage,
+}
+
+/// Value of a `[defines]` entry.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(untagged)]
+pub enum DefineValue {
+    /// `true` defines the macro without a value (`-DNAME`), `false`
+    /// undefines it (`-UNAME`).
+    Flag(bool),
+    /// `-DNAME=<n>`.
+    Int(i64),
+    /// `-DNAME=<s>`, verbatim.
+    Str(String),
+}
+
+impl DefineValue {
+    /// The clang flag defining `name` to this value.
+    pub fn flag(&self, name: &str) -> String {
+        match self {
+            DefineValue::Flag(true) => format!("-D{name}"),
+            DefineValue::Flag(false) => format!("-U{name}"),
+            DefineValue::Int(n) => format!("-D{name}={n}"),
+            DefineValue::Str(s) => format!("-D{name}={s}"),
+        }
+    }
+}
+
+impl From<bool> for DefineValue {
+    fn from(flag: bool) -> Self {
+        DefineValue::Flag(flag)
+    }
+}
+
+impl From<i64> for DefineValue {
+    fn from(n: i64) -> Self {
+        DefineValue::Int(n)
+    }
+}
+
+impl From<&str> for DefineValue {
+    fn from(s: &str) -> Self {
+        DefineValue::Str(s.to_string())
+    }
+}
+
+impl From<String> for DefineValue {
+    fn from(s: String) -> Self {
+        DefineValue::Str(s)
+    }
+}
+
+/// `(name, flag)` of every `-D` / `-U` flag in `args`, in both the joined
+/// (`-DFOO=1`) and separate (`-D FOO=1`) forms.
+fn macro_flags(args: &[String]) -> Vec<(String, String)> {
+    let mut flags = Vec::new();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let (kind, rest) = match arg.as_str() {
+            "-D" | "-U" => match args.next() {
+                Some(next) => (&arg[..2], next.as_str()),
+                None => break,
+            },
+            a if a.starts_with("-D") || a.starts_with("-U") => (&a[..2], &a[2..]),
+            _ => continue,
+        };
+        let name = rest.split('=').next().unwrap_or_default();
+        flags.push((name.to_string(), format!("{kind}{rest}")));
+    }
+    flags
+}
+
+/// Compiler dialect of the headers. Sets the clang target and extensions
+/// they need; `--target` in `clang_args` (top-level or a partition's)
+/// still overrides the target.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Flavor {
+    /// GCC/clang headers for the host target.
+    #[default]
+    Gnu,
+    /// MSVC / Windows SDK style headers: `__declspec(dllimport)`,
+    /// `__stdcall` (`WINAPI`, `CALLBACK`), `__int64`. Parsed for
+    /// `x86_64-pc-windows-msvc` with `-fms-extensions` and
+    /// `-fms-compatibility`, so `long` is 32-bit and `wchar_t` 16-bit.
+    /// Conventions are those clang reports for the target: x64 has a
+    /// single one, an `i686-pc-windows-msvc` target keeps `__stdcall`.
+    Msvc,
+}
+
+impl Flavor {
+    /// Clang arguments placed before the top-level `clang_args`.
+    fn clang_args(self) -> Vec<String> {
+        match self {
+            Flavor::Gnu => Vec::new(),
+            Flavor::Msvc => [
+                "--target=x86_64-pc-windows-msvc",
+                "-fms-extensions",
+                "-fms-compatibility",
+                "-fdeclspec",
+            ]
+            .map(String::from)
+            .to_vec(),
+        }
+    }
+}
+
+/// Header language for a partition.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+pub enum Language {
+    #[default]
+    #[serde(rename = "c")]
+    C,
+    /// Parsed with `-x c++`. Declarations inside `extern "C"` blocks and
+    /// namespaces are extracted; namespaces become sub-namespaces.
+    #[serde(rename = "c++")]
+    Cxx,
+    /// Parsed with `-x objective-c`, for macOS framework headers that mix
+    /// C APIs with Objective-C classes. C declarations are extracted as
+    /// in `c`; `@interface`, `@protocol` and other Objective-C
+    /// declarations are skipped, and object, `id`, `Class`, `SEL` and
+    /// block pointers become `*mut c_void`.
+    #[serde(rename = "objective-c")]
+    ObjC,
+}
+
+impl PartitionConfig {
+    /// This partition's [`clang_args`](Self::clang_args) followed by its
+    /// [`defines`](Self::defines) as `-D` / `-U` flags, placed after
+    /// [`Config::effective_clang_args`].
+    pub fn effective_clang_args(&self) -> Vec<String> {
+        let mut args = self.clang_args.clone();
+        args.extend(define_flags(&self.defines));
+        args
+    }
+
+    /// Create an empty partition for `namespace`. Set at least
+    /// [`library`](Self::library) and one [`header`](Self::header).
+    pub fn new(namespace: impl Into<String>) -> Self {
+        Self {
+            namespace: namespace.into(),
+            library: String::new(),
+            library_soname: None,
+            delay_load: false,
+            headers: Vec::new(),
+            traverse: Vec::new(),
+            clang_args: Vec::new(),
+            defines: BTreeMap::new(),
+            language: Language::C,
+            bitfields: BitfieldMode::Flatten,
+            int128: Int128Mode::Skip,
+            long_double: LongDoubleMode::Skip,
+            wchar_width: None,
+            namespace_from_path: false,
+            overrides: Vec::new(),
+            frameworks: Vec::new(),
+            extra_source: Vec::new(),
+            apis_split: ApisSplit::None,
+            max_methods: None,
+            calling_convention: None,
+        }
+    }
+
+    pub fn library(mut self, library: impl Into<String>) -> Self {
+        self.library = library.into();
+        self
+    }
+
+    pub fn library_soname(mut self, soname: impl Into<String>) -> Self {
+        self.library_soname = Some(soname.into());
+        self
+    }
+
+    pub fn delay_load(mut self, delay_load: bool) -> Self {
+        self.delay_load = delay_load;
+        self
+    }
+
+    pub fn header(mut self, header: impl Into<PathBuf>) -> Self {
+        self.headers.push(header.into());
+        self
+    }
+
+    pub fn traverse(mut self, path: impl Into<PathBuf>) -> Self {
+        self.traverse.push(path.into());
+        self
+    }
+
+    pub fn clang_arg(mut self, arg: impl Into<String>) -> Self {
+        self.clang_args.push(arg.into());
+        self
+    }
+
+    /// Define a preprocessor macro for this partition.
+    pub fn define(mut self, name: impl Into<String>, value: impl Into<DefineValue>) -> Self {
+        self.defines.insert(name.into(), value.into());
+        self
+    }
+
+    pub fn language(mut self, language: Language) -> Self {
+        self.language = language;
+        self
+    }
+
+    pub fn bitfields(mut self, mode: BitfieldMode) -> Self {
+        self.bitfields = mode;
+        self
+    }
+
+    pub fn int128(mut self, mode: Int128Mode) -> Self {
+        self.int128 = mode;
+        self
+    }
+
+    pub fn long_double(mut self, mode: LongDoubleMode) -> Self {
+        self.long_double = mode;
+        self
+    }
+
+    pub fn wchar_width(mut self, width: usize) -> Self {
+        self.wchar_width = Some(width);
+        self
+    }
+
+    pub fn namespace_from_path(mut self, enabled: bool) -> Self {
+        self.namespace_from_path = enabled;
+        self
+    }
+
+    pub fn apis_split(mut self, split: ApisSplit) -> Self {
+        self.apis_split = split;
+        self
+    }
+
+    pub fn max_methods(mut self, max: usize) -> Self {
+        self.max_methods = Some(max);
+        self
+    }
+
+    pub fn calling_convention(mut self, cc: CallConv) -> Self {
+        self.calling_convention = Some(cc);
+        self
+    }
+
+    /// Bind a macOS framework (see [`frameworks`](Self::frameworks)).
+    pub fn framework(mut self, name: impl Into<String>) -> Self {
+        self.frameworks.push(name.into());
+        self
+    }
 
-fn default_output_file() -> PathBuf {
-    PathBuf::from("output.winmd")
-}
+    /// Append `code` to the translation unit (see [`ExtraSource`]).
+    pub fn extra_source(mut self, code: impl Into<String>) -> Self {
+        self.extra_source.push(ExtraSource { code: code.into() });
+        self
+    }
 
-/// A single partition — maps a set of headers to one namespace.
-#[derive(Debug, Deserialize)]
-pub struct PartitionConfig {
-    /// ECMA-335 namespace (e.g. `MyLib.Graphics`).
-    pub namespace: String,
-    /// Library name for P/Invoke `ImplMap` entries (e.g. `mylib.so`).
-    pub library: String,
-    /// Headers to include (all are parsed for dependency resolution).
-    pub headers: Vec<PathBuf>,
-    /// Which files to actually emit declarations from.
-    /// If empty, uses `headers`.
-    #[serde(default)]
-    pub traverse: Vec<PathBuf>,
-    /// Extra clang arguments (e.g. `-I/usr/include`).
-    #[serde(default)]
-    pub clang_args: Vec<String>,
-}
+    pub fn override_namespace(
+        mut self,
+        file: impl Into<PathBuf>,
+        namespace: impl Into<String>,
+    ) -> Self {
+        self.overrides.push(FileNamespaceConfig {
+            file: file.into(),
+            namespace: namespace.into(),
+        });
+        self
+    }
+
+    /// The `extra_source` snippets as one file, if any.
+    pub(crate) fn extra_source_content(&self) -> Option<String> {
+        if self.extra_source.is_empty() {
+            return None;
+        }
+        let mut content = String::new();
+        for extra in &self.extra_source {
+            content.push_str(&extra.code);
+            content.push('\n');
+        }
+        Some(content)
+    }
+
+    /// File name of the scratch header holding the `extra_source`
+    /// snippets, unique to this partition and content.
+    pub(crate) fn extra_source_file_name(&self) -> Option<String> {
+        self.extra_source_content()
+            .map(|content| wrapper_name(&self.namespace, content.as_bytes(), "_extra.h"))
+    }
 
-impl PartitionConfig {
     /// Returns the traverse list, falling back to `headers` if empty.
     pub fn traverse_files(&self) -> &[PathBuf] {
         if self.traverse.is_empty() {
@@ -76,33 +1841,216 @@ impl PartitionConfig {
     /// Returns the translation unit file to parse.
     ///
     /// If there's a single header/source file, returns it directly.
-    /// If there are multiple, generates a wrapper `.c` file in `out_dir`
-    /// that `#include`s all of them — mimicking the scraper `.c` files
-    /// that win32metadata uses.
-    pub fn wrapper_header(&self, base_dir: &Path, include_paths: &[PathBuf]) -> PathBuf {
-        if self.headers.len() == 1 {
-            resolve_header(&self.headers[0], base_dir, include_paths)
-        } else {
-            // Generate a wrapper .c file that #includes all headers.
-            let wrapper_dir = std::env::temp_dir().join("bnd_winmd_wrappers");
-            std::fs::create_dir_all(&wrapper_dir).expect("create wrapper dir");
+    /// If there are multiple, generates a wrapper `.c` file in
+    /// `scratch_dir` (see [`Config::scratch_dir`]) that `#include`s all of
+    /// them — mimicking the scraper `.c` files that win32metadata uses.
+    pub fn wrapper_header(
+        &self,
+        scratch_dir: &Path,
+        base_dir: &Path,
+        include_paths: &[PathBuf],
+    ) -> anyhow::Result<PathBuf> {
+        let headers = self.expanded_headers(base_dir, include_paths);
+        wrapper_for(
+            scratch_dir,
+            &self.namespace,
+            &headers,
+            base_dir,
+            include_paths,
+        )
+    }
+
+    /// Returns `headers` with directory entries and glob patterns expanded.
+    ///
+    /// Expanded entries stay relative to the search root they were found
+    /// under (`base_dir` or an `include_paths` entry), so they can still be
+    /// `#include`d via `-I`.
+    pub fn expanded_headers(&self, base_dir: &Path, include_paths: &[PathBuf]) -> Vec<PathBuf> {
+        self.headers
+            .iter()
+            .flat_map(|h| expand_entry(h, base_dir, include_paths, true))
+            .collect()
+    }
+
+    /// Returns the traverse list (see [`traverse_files`](Self::traverse_files))
+    /// expanded and resolved to the paths clang reports for each file.
+    pub fn resolved_traverse(&self, base_dir: &Path, include_paths: &[PathBuf]) -> Vec<PathBuf> {
+        self.resolved_traverse_entries(base_dir, include_paths)
+            .into_iter()
+            .flat_map(|(_, files)| files)
+            .collect()
+    }
+
+    /// [`resolved_traverse`](Self::resolved_traverse), grouped by the
+    /// traverse entry each file was expanded from.
+    pub fn resolved_traverse_entries(
+        &self,
+        base_dir: &Path,
+        include_paths: &[PathBuf],
+    ) -> Vec<(&Path, Vec<PathBuf>)> {
+        let headers_only = self.traverse.is_empty();
+        self.traverse_files()
+            .iter()
+            .map(|t| {
+                let files = expand_entry(t, base_dir, include_paths, headers_only)
+                    .into_iter()
+                    .map(|f| resolve_header(&f, base_dir, include_paths))
+                    .collect();
+                (t.as_path(), files)
+            })
+            .collect()
+    }
+
+    /// Traverse entries that name directories, resolved like
+    /// [`resolved_traverse`](Self::resolved_traverse).
+    pub fn traverse_dirs(&self, base_dir: &Path, include_paths: &[PathBuf]) -> Vec<PathBuf> {
+        self.traverse_files()
+            .iter()
+            .filter(|t| !is_glob_pattern(t))
+            .map(|t| resolve_header(t, base_dir, include_paths))
+            .filter(|t| t.is_dir())
+            .collect()
+    }
+}
+
+/// Returns `true` if a config path contains glob metacharacters.
+fn is_glob_pattern(path: &Path) -> bool {
+    path.to_string_lossy().contains(['*', '?', '['])
+}
 
-            // Use namespace as a stable filename
-            let safe_name = self.namespace.replace('.', "_");
-            let wrapper_path = wrapper_dir.join(format!("{safe_name}_wrapper.c"));
+/// Expand a single `headers`/`traverse` entry.
+///
+/// Glob patterns are matched under each search root in [`resolve_header`]
+/// order; the first root with any match wins. Directories are walked
+/// recursively. Plain file entries are returned unchanged. When
+/// `headers_only` is set, only `.h` files are collected from directories.
+fn expand_entry(
+    entry: &Path,
+    base_dir: &Path,
+    include_paths: &[PathBuf],
+    headers_only: bool,
+) -> Vec<PathBuf> {
+    let roots: Vec<&Path> = if entry.is_absolute() {
+        vec![Path::new("")]
+    } else {
+        std::iter::once(base_dir)
+            .chain(include_paths.iter().map(PathBuf::as_path))
+            .collect()
+    };
 
-            let mut content = String::new();
-            for h in &self.headers {
-                // Use angle-bracket includes so clang resolves headers
-                // via -I search paths, same as single-header partitions.
-                content.push_str(&format!("#include <{}>\n", h.display()));
+    if is_glob_pattern(entry) {
+        for root in roots {
+            let pattern = root.join(entry);
+            let Ok(paths) = glob::glob(&pattern.to_string_lossy()) else {
+                tracing::warn!(pattern = %entry.display(), "invalid glob pattern");
+                return Vec::new();
+            };
+            let mut matches: Vec<PathBuf> = paths
+                .filter_map(Result::ok)
+                .filter(|p| p.is_file())
+                .map(|p| p.strip_prefix(root).map(Path::to_path_buf).unwrap_or(p))
+                .collect();
+            if !matches.is_empty() {
+                matches.sort();
+                return matches;
             }
-            std::fs::write(&wrapper_path, &content).expect("write wrapper file");
-            wrapper_path
         }
+        tracing::warn!(pattern = %entry.display(), "glob pattern matched no files");
+        return Vec::new();
+    }
+
+    for root in roots {
+        let dir = root.join(entry);
+        if dir.is_dir() {
+            let mut files = Vec::new();
+            collect_dir_files(&dir, headers_only, &mut files);
+            files.sort();
+            return files
+                .into_iter()
+                .map(|p| p.strip_prefix(root).map(Path::to_path_buf).unwrap_or(p))
+                .collect();
+        }
+    }
+
+    vec![entry.to_path_buf()]
+}
+
+/// Recursively collect the files below `dir`.
+fn collect_dir_files(dir: &Path, headers_only: bool, out: &mut Vec<PathBuf>) {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        tracing::warn!(dir = %dir.display(), "failed to read directory");
+        return;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            collect_dir_files(&path, headers_only, out);
+        } else if !headers_only || path.extension().is_some_and(|ext| ext == "h") {
+            out.push(path);
+        }
+    }
+}
+
+/// Where generated clang inputs go when `scratch_dir` is unset:
+/// `$OUT_DIR/bnd-winmd` in a build script, else `<temp>/bnd_winmd_wrappers`.
+pub(crate) fn default_scratch_dir() -> PathBuf {
+    match std::env::var_os("OUT_DIR") {
+        Some(out_dir) => PathBuf::from(out_dir).join("bnd-winmd"),
+        None => std::env::temp_dir().join("bnd_winmd_wrappers"),
     }
 }
 
+/// File name for generated content keyed by `key`: `<name>_<hash><suffix>`,
+/// with dots in `name` replaced. Builds that generate different content
+/// for the same name never share a file.
+pub(crate) fn wrapper_name(name: &str, key: &[u8], suffix: &str) -> String {
+    let safe_name = name.replace('.', "_");
+    format!("{safe_name}_{}{suffix}", crate::stamp::hash(key))
+}
+
+/// Write `content` to `<dir>/<wrapper_name>` and return the path. The file
+/// is written under a temporary name and renamed into place, so a parse
+/// running concurrently with the same content never sees it half written.
+pub(crate) fn write_wrapper(
+    dir: &Path,
+    name: &str,
+    suffix: &str,
+    content: &str,
+) -> anyhow::Result<PathBuf> {
+    std::fs::create_dir_all(dir)
+        .with_context(|| format!("creating scratch dir {}", dir.display()))?;
+    let path = dir.join(wrapper_name(name, content.as_bytes(), suffix));
+    if std::fs::read(&path).is_ok_and(|existing| existing == content.as_bytes()) {
+        return Ok(path);
+    }
+    let tmp = path.with_extension(format!("tmp{}", std::process::id()));
+    std::fs::write(&tmp, content).with_context(|| format!("writing {}", tmp.display()))?;
+    std::fs::rename(&tmp, &path).with_context(|| format!("writing {}", path.display()))?;
+    Ok(path)
+}
+
+/// The file to parse for `headers`: the header itself when there is one,
+/// else a wrapper `.c` file in `scratch_dir`, named after `name` and its
+/// content, that `#include`s them all.
+pub(crate) fn wrapper_for(
+    scratch_dir: &Path,
+    name: &str,
+    headers: &[PathBuf],
+    base_dir: &Path,
+    include_paths: &[PathBuf],
+) -> anyhow::Result<PathBuf> {
+    if headers.len() == 1 {
+        return Ok(resolve_header(&headers[0], base_dir, include_paths));
+    }
+    let mut content = String::new();
+    for h in headers {
+        // Use angle-bracket includes so clang resolves headers
+        // via -I search paths, same as single-header partitions.
+        content.push_str(&format!("#include <{}>\n", h.display()));
+    }
+    write_wrapper(scratch_dir, name, "_wrapper.c", &content)
+}
+
 /// Resolve a header path by searching `base_dir` first, then each
 /// `include_paths` entry.  Absolute paths are returned as-is.  If the
 /// file is not found anywhere, falls back to `base_dir.join(path)` so
@@ -136,6 +2084,17 @@ pub fn resolve_header(path: &Path, base_dir: &Path, include_paths: &[PathBuf]) -
 /// winmd = "../bnd-posix/winmd/bnd-posix.winmd"
 /// namespace = "posix"
 /// ```
+///
+/// `types` narrows the import to the listed names, and `exclude` keeps
+/// names out of it, so a config can borrow a couple of shared types
+/// without shadowing its own:
+///
+/// ```toml
+/// [[type_import]]
+/// winmd = "../bnd-posix/winmd/bnd-posix.winmd"
+/// namespace = "posix"
+/// types = ["timespec", "itimerspec"]
+/// ```
 #[derive(Debug, Deserialize)]
 pub struct TypeImportConfig {
     /// Path to the external `.winmd` file (resolved relative to the TOML
@@ -144,6 +2103,135 @@ pub struct TypeImportConfig {
     /// Root namespace filter — only types under this namespace tree are
     /// imported into the registry.
     pub namespace: String,
+    /// Import only these type names (and the types they need); empty
+    /// imports the whole namespace tree.
+    #[serde(default)]
+    pub types: Vec<String>,
+    /// Type names never imported, even when another imported type needs
+    /// them.
+    #[serde(default)]
+    pub exclude: Vec<String>,
+}
+
+impl TypeImportConfig {
+    pub fn new(winmd: impl Into<PathBuf>, namespace: impl Into<String>) -> Self {
+        Self {
+            winmd: winmd.into(),
+            namespace: namespace.into(),
+            types: Vec::new(),
+            exclude: Vec::new(),
+        }
+    }
+
+    pub fn ty(mut self, name: impl Into<String>) -> Self {
+        self.types.push(name.into());
+        self
+    }
+
+    pub fn exclude(mut self, name: impl Into<String>) -> Self {
+        self.exclude.push(name.into());
+        self
+    }
+}
+
+/// Which types of an external winmd seed the registry.
+#[derive(Debug, Clone, Copy)]
+pub struct ImportFilter<'a> {
+    /// Root namespace the imported types live under.
+    pub namespace: &'a str,
+    /// Names to import; empty imports every type under `namespace`.
+    pub types: &'a [String],
+    /// Names never imported.
+    pub exclude: &'a [String],
+}
+
+impl ImportFilter<'_> {
+    /// Whether the type is imported in its own right, before following
+    /// what it references.
+    pub fn selects(&self, namespace: &str, name: &str) -> bool {
+        namespace.starts_with(self.namespace)
+            && (self.types.is_empty() || self.types.iter().any(|t| t == name))
+            && !self.excludes(name)
+    }
+
+    /// Whether the name is kept out of the import.
+    pub fn excludes(&self, name: &str) -> bool {
+        self.exclude.iter().any(|t| t == name)
+    }
+}
+
+/// A crate that already ships bindings for an external winmd.
+///
+/// ```toml
+/// [[reference]]
+/// crate = "bnd_linux"
+/// namespace = "libc"
+/// winmd = "../bnd-linux/winmd/bnd-linux.winmd"
+/// ```
+///
+/// The winmd is imported like a `[[type_import]]`, and generated code
+/// names its types through `crate` instead of generating them again.
+#[derive(Debug, Clone, Deserialize)]
+pub struct ReferenceConfig {
+    /// Rust crate name as used in paths (`bnd_linux`, not `bnd-linux`).
+    #[serde(rename = "crate")]
+    pub krate: String,
+    /// Root namespace the crate provides.
+    pub namespace: String,
+    /// Path to the crate's `.winmd` (resolved like `type_import`).
+    pub winmd: PathBuf,
+    /// How generated code spells paths into the crate.
+    #[serde(default)]
+    pub style: ReferenceStyle,
+}
+
+impl ReferenceConfig {
+    pub fn new(
+        krate: impl Into<String>,
+        namespace: impl Into<String>,
+        winmd: impl Into<PathBuf>,
+    ) -> Self {
+        Self {
+            krate: krate.into(),
+            namespace: namespace.into(),
+            winmd: winmd.into(),
+            style: ReferenceStyle::default(),
+        }
+    }
+
+    pub fn style(mut self, style: ReferenceStyle) -> Self {
+        self.style = style;
+        self
+    }
+
+    /// The windows-bindgen `--reference` value: `crate,style,namespace`.
+    pub fn bindgen_arg(&self) -> String {
+        format!("{},{},{}", self.krate, self.style.as_str(), self.namespace)
+    }
+}
+
+/// Path style of a windows-bindgen `--reference` — how generated code
+/// names the referenced crate's types.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ReferenceStyle {
+    /// `crate::namespace::Type`, keeping the namespace modules.
+    #[default]
+    Full,
+    /// `crate::Type`.
+    Flat,
+    /// Full path without the root namespace.
+    SkipRoot,
+}
+
+impl ReferenceStyle {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ReferenceStyle::Full => "full",
+            ReferenceStyle::Flat => "flat",
+            ReferenceStyle::SkipRoot => "skip-root",
+        }
+    }
 }
 
 /// User-declared type injection.
@@ -198,11 +2286,510 @@ pub struct InjectVariant {
     pub value: i64,
 }
 
-/// Load and parse a `bnd-winmd.toml` configuration file.
+/// An allocate/free function pair (e.g. `opendir`/`closedir`).
+///
+/// The handle type is taken from the return type of `alloc` (pointers are
+/// peeled, so `DIR *opendir(...)` annotates `DIR`). It must be a typedef
+/// extracted by one of the partitions.
+///
+/// ```toml
+/// [[raii_free]]
+/// alloc = "BN_new"
+/// free = "BN_free"
+/// ```
+#[derive(Debug, Deserialize)]
+pub struct RaiiFreeConfig {
+    /// Function that returns a new handle.
+    pub alloc: String,
+    /// Function that releases the handle.
+    pub free: String,
+}
+
+/// Associates a function parameter with an enum or a constant.
+///
+/// Exactly one of `enum` or `constant` must be set.
+///
+/// ```toml
+/// [[param_association]]
+/// function = "epoll_ctl"
+/// param = "op"
+/// enum = "EpollOp"
+/// ```
+#[derive(Debug, Deserialize)]
+pub struct ParamAssociationConfig {
+    /// Function name as extracted from C.
+    pub function: String,
+    /// Parameter name within `function`.
+    pub param: String,
+    /// Enum type whose variants are valid values for the parameter.
+    #[serde(default, rename = "enum")]
+    pub enum_name: Option<String>,
+    /// Constant that the parameter is associated with.
+    #[serde(default)]
+    pub constant: Option<String>,
+}
+
+/// Overrides the attributes of one function parameter. Unset fields keep
+/// what the header's SAL annotations said.
+///
+/// ```toml
+/// [[param_attributes]]
+/// function = "read"
+/// param = "buf"
+/// direction = "out"
+/// memory_size = "count"
+/// ```
+#[derive(Debug, Default, Deserialize)]
+pub struct ParamAttributesConfig {
+    /// Function name as extracted from C.
+    pub function: String,
+    /// Parameter name within `function`.
+    pub param: String,
+    /// `in`, `out` or `inout`.
+    #[serde(default)]
+    pub direction: Option<ParamDirection>,
+    /// The parameter may be null.
+    #[serde(default)]
+    pub optional: Option<bool>,
+    /// Name of the parameter holding the buffer's size in bytes.
+    #[serde(default)]
+    pub memory_size: Option<String>,
+    /// Name of the parameter holding the buffer's element count.
+    #[serde(default)]
+    pub count: Option<String>,
+    /// The parameter is text without a terminating NUL.
+    #[serde(default)]
+    pub not_null_terminated: Option<bool>,
+}
+
+/// C-string functions (`[strings]`).
+///
+/// ```toml
+/// [strings]
+/// functions = ["inet_pton", "dlopen"]
+/// ```
+#[derive(Debug, Default, Deserialize)]
+pub struct StringsConfig {
+    /// Function names as extracted from C.
+    #[serde(default)]
+    pub functions: Vec<String>,
+}
+
+/// Groups `#define` constants that share a prefix into an enum.
+///
+/// ```toml
+/// [[synthesize_enum]]
+/// name = "SignalNumber"
+/// prefix = "SIG"
+/// type = "i32"
+/// ```
+///
+/// Matching constants become the enum's variants (names unchanged) in each
+/// partition where they were extracted, and are removed from the loose
+/// constants unless `keep_constants` is set.
+#[derive(Debug, Deserialize)]
+pub struct SynthesizeEnumConfig {
+    /// Enum type name.
+    pub name: String,
+    /// Constant-name prefix selecting the variants.
+    pub prefix: String,
+    /// Underlying integer type (`i8` … `u64`). Defaults to `i32`.
+    #[serde(default, rename = "type")]
+    pub underlying: Option<String>,
+    /// Only synthesize in this partition namespace.
+    #[serde(default)]
+    pub namespace: Option<String>,
+    /// Also keep the matching constants on `Apis`.
+    #[serde(default)]
+    pub keep_constants: bool,
+}
+
+/// Renames emitted symbols, either one exact `name` or every name matching
+/// a regex `pattern` (with `$1`-style captures in `to`).
+///
+/// ```toml
+/// [[rename]]
+/// name = "stat"
+/// kind = "struct"
+/// to = "stat_t"
+///
+/// [[rename]]
+/// pattern = "^curl_easy_(.*)$"
+/// kind = "function"
+/// to = "$1"
+/// ```
+///
+/// References (field/parameter types, `raii_free`, parameter associations)
+/// follow the rename. Renamed functions keep their C symbol as the
+/// P/Invoke entry point. Config keyed by type name
+/// (`namespace_overrides`) uses the renamed names.
+#[derive(Debug, Clone, Deserialize)]
+pub struct RenameConfig {
+    /// Exact symbol name to rename.
+    #[serde(default)]
+    pub name: Option<String>,
+    /// Regex matched against symbol names.
+    #[serde(default)]
+    pub pattern: Option<String>,
+    /// New name (regex replacement string for `pattern` rules).
+    pub to: String,
+    /// Restrict the rule to one kind of symbol. Applies to all if omitted.
+    #[serde(default)]
+    pub kind: Option<RenameKind>,
+}
+
+/// Symbol kinds a [`RenameConfig`] can target.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RenameKind {
+    Struct,
+    Enum,
+    Typedef,
+    Function,
+    Constant,
+}
+
+/// Shim library for inline functions.
+///
+/// ```toml
+/// [shim]
+/// library = "mylib_shims"
+/// functions = ["CMSG_NXTHDR"]
+/// ```
+///
+/// Each listed `static inline` function is imported from `library` as
+/// `<prefix><name>`, and [`run`](crate::run) writes a C file defining
+/// those exported wrappers next to the winmd. Compile it into `library`
+/// (e.g. with the `cc` crate) so the functions become callable.
+#[derive(Debug, Deserialize)]
+pub struct ShimConfig {
+    /// Library the wrappers are compiled into.
+    pub library: String,
+    /// Inline functions to wrap (C names).
+    pub functions: Vec<String>,
+    /// Generated C file, relative to the output winmd's directory.
+    #[serde(default = "default_shim_source")]
+    pub source: PathBuf,
+    /// Prefix for the exported wrapper symbols.
+    #[serde(default = "default_shim_prefix")]
+    pub prefix: String,
+}
+
+impl ShimConfig {
+    /// Wrap `functions` into `library` with the default file name and prefix.
+    pub fn new(library: impl Into<String>, functions: &[&str]) -> Self {
+        ShimConfig {
+            library: library.into(),
+            functions: functions.iter().map(|f| f.to_string()).collect(),
+            source: default_shim_source(),
+            prefix: default_shim_prefix(),
+        }
+    }
+}
+
+fn default_shim_source() -> PathBuf {
+    PathBuf::from("shims.c")
+}
+
+fn default_shim_prefix() -> String {
+    "bnd_shim_".to_string()
+}
+
+/// Size and alignment assertions generated from clang's layouts.
+///
+/// ```toml
+/// [layout_tests]
+/// path = "crate::bindings"
+/// flat = true
+/// ```
+///
+/// [`run`](crate::run) writes a Rust file next to the winmd with one
+/// `#[test]` per struct asserting `size_of` and `align_of` equal what
+/// clang reported. `include!` it from the bindings crate's tests.
+#[derive(Debug, Deserialize)]
+pub struct LayoutTestsConfig {
+    /// Generated Rust file, relative to the output winmd's directory.
+    #[serde(default = "default_layout_tests_file")]
+    pub file: PathBuf,
+    /// Rust path of the generated bindings module.
+    #[serde(default = "default_layout_tests_path")]
+    pub path: String,
+    /// Bindings were generated with `--flat`: types sit directly under
+    /// `path` instead of in per-namespace modules.
+    #[serde(default)]
+    pub flat: bool,
+}
+
+impl Default for LayoutTestsConfig {
+    fn default() -> Self {
+        LayoutTestsConfig {
+            file: default_layout_tests_file(),
+            path: default_layout_tests_path(),
+            flat: false,
+        }
+    }
+}
+
+fn default_layout_tests_file() -> PathBuf {
+    PathBuf::from("layout_tests.rs")
+}
+
+fn default_layout_tests_path() -> String {
+    "crate".to_string()
+}
+
+/// Load and parse a `bnd-winmd.toml` configuration file. Its profiles
+/// are left unapplied; see [`load_config_profile`].
+///
+/// A config may name other configs to build on with `extends` (a path or
+/// a list of paths, relative to the config). They are loaded first, in
+/// order, and each later file is merged over them (see [`merge_toml`]).
 pub fn load_config(path: &Path) -> anyhow::Result<Config> {
+    load_config_profile(path, None)
+}
+
+/// [`load_config`], then apply `[profile.<profile>]` if a profile is
+/// given.
+pub fn load_config_profile(path: &Path, profile: Option<&str>) -> anyhow::Result<Config> {
     let content = std::fs::read_to_string(path)
         .map_err(|e| anyhow::anyhow!("failed to read config file {}: {}", path.display(), e))?;
-    let config: Config = toml::from_str(&content)
-        .map_err(|e| anyhow::anyhow!("failed to parse config file {}: {}", path.display(), e))?;
-    Ok(config)
+    let parse_error = |e: toml::de::Error| {
+        anyhow::anyhow!("failed to parse config file {}: {}", path.display(), e)
+    };
+    let mut table: toml::Table = toml::from_str(&content).map_err(parse_error)?;
+    let extends = table.contains_key("extends");
+    let mut config_files = vec![path.to_path_buf()];
+    if extends {
+        config_files.clear();
+        table = load_extended(path, &mut Vec::new(), &mut config_files)?;
+    }
+    let selected = select_platform_libraries(&mut table)?;
+    // Without `extends` or `library` tables, deserialize the text itself
+    // so errors keep their line numbers.
+    let mut config: Config = if extends || selected {
+        table.try_into().map_err(parse_error)?
+    } else {
+        toml::from_str(&content).map_err(parse_error)?
+    };
+    config.config_files = config_files;
+    if let Some(profile) = profile {
+        config
+            .apply_profile(profile)
+            .with_context(|| format!("applying profile {profile}"))?;
+        config.applied_profile = Some(profile.to_string());
+    }
+    config.finish()
+}
+
+/// Replace every partition's `library = { <platform> = "<name>", ... }`
+/// table, profiles' included, with the name for the platform being bound:
+/// `platform`, else `CARGO_CFG_TARGET_OS`, else the host OS. Returns
+/// whether any table was replaced.
+fn select_platform_libraries(table: &mut toml::Table) -> anyhow::Result<bool> {
+    let platform = match table.get("platform") {
+        Some(toml::Value::String(platform)) => platform.clone(),
+        Some(_) => anyhow::bail!("`platform` must be a string"),
+        None => std::env::var("CARGO_CFG_TARGET_OS")
+            .unwrap_or_else(|_| std::env::consts::OS.to_string()),
+    };
+    let mut partitions = Vec::new();
+    for (key, value) in table.iter_mut() {
+        match (key.as_str(), value) {
+            ("partition", toml::Value::Array(array)) => partitions.push(array),
+            ("profile", toml::Value::Table(profiles)) => {
+                let arrays = profiles
+                    .iter_mut()
+                    .filter_map(|(_, profile)| profile.get_mut("partition")?.as_array_mut());
+                partitions.extend(arrays);
+            }
+            _ => {}
+        }
+    }
+    let mut selected = false;
+    for p in partitions
+        .into_iter()
+        .flatten()
+        .filter_map(|p| p.as_table_mut())
+    {
+        let Some(toml::Value::Table(names)) = p.get("library") else {
+            continue;
+        };
+        let namespace = p.get("namespace").and_then(|v| v.as_str()).unwrap_or("?");
+        let Some(name) = names.get(&platform) else {
+            anyhow::bail!(
+                "partition {namespace}: `library` has no entry for platform `{platform}` \
+                 (has: {})",
+                names.keys().cloned().collect::<Vec<_>>().join(", ")
+            );
+        };
+        let name = name.clone();
+        p.insert("library".to_string(), name);
+        selected = true;
+    }
+    Ok(selected)
+}
+
+/// The TOML table of `path` with everything it `extends` merged in.
+/// `chain` holds the files being loaded, to reject cycles; every file
+/// read is added to `files`.
+fn load_extended(
+    path: &Path,
+    chain: &mut Vec<PathBuf>,
+    files: &mut Vec<PathBuf>,
+) -> anyhow::Result<toml::Table> {
+    let canonical = std::fs::canonicalize(path)
+        .with_context(|| format!("failed to read config file {}", path.display()))?;
+    if chain.contains(&canonical) {
+        anyhow::bail!("config {} extends itself", path.display());
+    }
+    let content = std::fs::read_to_string(path)
+        .with_context(|| format!("failed to read config file {}", path.display()))?;
+    if !files.iter().any(|f| f == path) {
+        files.push(path.to_path_buf());
+    }
+    let mut table: toml::Table = toml::from_str(&content)
+        .with_context(|| format!("failed to parse config file {}", path.display()))?;
+    let dir = path.parent().unwrap_or(Path::new("."));
+
+    let bases = match table.remove("extends") {
+        None => Vec::new(),
+        Some(toml::Value::String(base)) => vec![base],
+        Some(toml::Value::Array(bases)) => bases
+            .into_iter()
+            .map(|base| match base {
+                toml::Value::String(base) => Ok(base),
+                other => anyhow::bail!("{}: `extends` entry {other} is not a path", path.display()),
+            })
+            .collect::<anyhow::Result<_>>()?,
+        Some(other) => anyhow::bail!(
+            "{}: `extends` must be a path or a list of paths, not {other}",
+            path.display()
+        ),
+    };
+    if bases.is_empty() {
+        return Ok(table);
+    }
+
+    chain.push(canonical);
+    let mut merged = toml::Table::new();
+    for base in bases {
+        let base_path = dir.join(&base);
+        let mut base_table = load_extended(&base_path, chain, files)
+            .with_context(|| format!("{} extends {base}", path.display()))?;
+        rebase_paths(
+            &mut base_table,
+            Path::new(&base).parent().unwrap_or(Path::new("")),
+            base_path.parent().unwrap_or(Path::new(".")),
+        );
+        merge_toml(&mut merged, base_table);
+    }
+    chain.pop();
+    merge_toml(&mut merged, table);
+    Ok(merged)
+}
+
+/// Merge `over` into `base`: tables merge key by key, arrays (including
+/// `[[partition]]` and other arrays of tables) are concatenated with
+/// `base`'s entries first, and any other value in `over` replaces
+/// `base`'s.
+fn merge_toml(base: &mut toml::Table, over: toml::Table) {
+    for (key, value) in over {
+        match (base.get_mut(&key), value) {
+            (Some(toml::Value::Table(b)), toml::Value::Table(o)) => merge_toml(b, o),
+            (Some(toml::Value::Array(b)), toml::Value::Array(o)) => b.extend(o),
+            (_, value) => {
+                base.insert(key, value);
+            }
+        }
+    }
+}
+
+/// Prefix the relative paths of an extended config with `dir`, the
+/// extended config's directory relative to the extending one, so they
+/// keep pointing where they did: the directories (`include_paths`,
+/// `framework_paths`, `library_paths`, `scratch_dir`, `sysroot`, and a
+/// profile's `include_paths` and `remove_include_paths`), and the
+/// `winmd` of `type_import` / `reference`. `base_dir` is the extended
+/// config's actual directory: a `pch` entry, or a partition's `headers`,
+/// `traverse` or `overrides` `file`, is rebased when it names a file or
+/// directory there (or is a glob matching there), as it resolved against
+/// it, and otherwise looked up through the include paths like any other
+/// header name and left alone. Output
+/// files name what the loaded config writes and stay relative to it, as
+/// do paths starting with an environment variable.
+fn rebase_paths(table: &mut toml::Table, dir: &Path, base_dir: &Path) {
+    let rebase = |value: &mut toml::Value| {
+        if let toml::Value::String(path) = value
+            && !path.starts_with('$')
+            && Path::new(path.as_str()).is_relative()
+        {
+            *path = dir.join(path.as_str()).display().to_string();
+        }
+    };
+    let rebase_all = |table: &mut toml::Table, key: &str| {
+        if let Some(toml::Value::Array(paths)) = table.get_mut(key) {
+            paths.iter_mut().for_each(&rebase);
+        }
+    };
+    for key in ["include_paths", "framework_paths", "library_paths"] {
+        rebase_all(table, key);
+    }
+    for key in ["scratch_dir", "sysroot"] {
+        if let Some(path) = table.get_mut(key) {
+            rebase(path);
+        }
+    }
+    // Header names resolve against the config's directory first, then
+    // the include paths; only the former move with the config.
+    let rebase_local = |value: &mut toml::Value| {
+        let toml::Value::String(path) = value else {
+            return;
+        };
+        let local = base_dir.join(path.as_str());
+        let found = if is_glob_pattern(Path::new(path.as_str())) {
+            glob::glob(&local.to_string_lossy()).is_ok_and(|mut m| m.next().is_some())
+        } else {
+            local.exists()
+        };
+        if found {
+            rebase(value);
+        }
+    };
+    if let Some(toml::Value::Array(headers)) = table.get_mut("pch") {
+        headers.iter_mut().for_each(&rebase_local);
+    }
+    if let Some(toml::Value::Array(partitions)) = table.get_mut("partition") {
+        for partition in partitions {
+            let toml::Value::Table(partition) = partition else {
+                continue;
+            };
+            for key in ["headers", "traverse"] {
+                if let Some(toml::Value::Array(paths)) = partition.get_mut(key) {
+                    paths.iter_mut().for_each(&rebase_local);
+                }
+            }
+            if let Some(toml::Value::Array(overrides)) = partition.get_mut("overrides") {
+                for file in overrides.iter_mut().filter_map(|o| o.get_mut("file")) {
+                    rebase_local(file);
+                }
+            }
+        }
+    }
+    if let Some(toml::Value::Table(profiles)) = table.get_mut("profile") {
+        for (_, profile) in profiles.iter_mut() {
+            if let toml::Value::Table(profile) = profile {
+                rebase_all(profile, "include_paths");
+                rebase_all(profile, "remove_include_paths");
+            }
+        }
+    }
+    for key in ["type_import", "reference"] {
+        if let Some(toml::Value::Array(entries)) = table.get_mut(key) {
+            for entry in entries {
+                if let Some(winmd) = entry.get_mut("winmd") {
+                    rebase(winmd);
+                }
+            }
+        }
+    }
 }
@@ -0,0 +1,214 @@
+//! Coverage report — how much of the traversed headers made it into the
+//! winmd, and why the rest did not.
+//!
+//! Per partition and declaration kind, counts what extraction found in
+//! the partition's traverse scope against what is emitted, and lists every
+//! omission with its skip reason. A declaration counts as declared when it
+//! was either emitted or skipped in scope; out-of-scope declarations belong
+//! to another partition (or none) and are not counted. Duplicates dropped
+//! in favour of another partition's copy are emitted there and not counted
+//! here either. Checked in, the JSON tracks a binding's completeness from
+//! one header version to the next.
+
+use std::collections::HashSet;
+use std::fmt;
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use serde::Serialize;
+
+use crate::model::{Partition, SkipReason, SourceLocation};
+use crate::report::skip_reason;
+
+/// Coverage of a whole config.
+#[derive(Debug, Serialize)]
+pub struct CoverageReport {
+    /// Assembly name from `[output]`.
+    pub assembly: String,
+    pub partitions: Vec<PartitionCoverage>,
+}
+
+/// Coverage of one partition, per declaration kind.
+#[derive(Debug, Serialize)]
+pub struct PartitionCoverage {
+    pub namespace: String,
+    pub library: String,
+    pub functions: Coverage,
+    /// Structs and unions, including those emitted as interfaces.
+    pub structs: Coverage,
+    pub enums: Coverage,
+    pub typedefs: Coverage,
+    /// `#define` constants.
+    pub constants: Coverage,
+}
+
+/// Declared and emitted counts of one declaration kind.
+#[derive(Debug, Default, Serialize)]
+pub struct Coverage {
+    pub declared: usize,
+    pub emitted: usize,
+    pub omitted: Vec<Omission>,
+}
+
+/// A declaration in scope that is not in the winmd.
+#[derive(Debug, Serialize)]
+pub struct Omission {
+    pub name: String,
+    /// `variadic`, `unsupported` or `unparsed_macro`.
+    pub reason: String,
+    /// Error message, for unsupported declarations.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub location: Option<SourceLocation>,
+}
+
+impl CoverageReport {
+    /// Build a report from resolved partitions.
+    pub fn new(assembly: &str, partitions: &[Partition]) -> Self {
+        Self {
+            assembly: assembly.to_string(),
+            partitions: partitions.iter().map(PartitionCoverage::new).collect(),
+        }
+    }
+
+    /// `(declared, emitted)` over every partition and kind.
+    pub fn totals(&self) -> (usize, usize) {
+        self.partitions
+            .iter()
+            .flat_map(|p| p.kinds())
+            .fold((0, 0), |(d, e), (_, c)| (d + c.declared, e + c.emitted))
+    }
+
+    /// Serialize the report as pretty-printed JSON.
+    pub fn to_json(&self) -> Result<String> {
+        serde_json::to_string_pretty(self).context("serializing coverage report")
+    }
+
+    /// Write the report as pretty-printed JSON to `path`.
+    pub fn write_json(&self, path: &Path) -> Result<()> {
+        std::fs::write(path, self.to_json()?)
+            .with_context(|| format!("writing coverage report to {}", path.display()))
+    }
+}
+
+impl PartitionCoverage {
+    fn new(p: &Partition) -> Self {
+        let mut coverage = Self {
+            namespace: p.namespace.clone(),
+            library: p.library.clone(),
+            functions: Coverage::emitted(p.functions.len()),
+            structs: Coverage::emitted(p.structs.len() + p.interfaces.len()),
+            enums: Coverage::emitted(p.enums.len()),
+            typedefs: Coverage::emitted(p.typedefs.len()),
+            constants: Coverage::emitted(p.constants.len()),
+        };
+        // Injected types and shims can fill in a declaration extraction
+        // skipped; those count as emitted only.
+        let emitted: HashSet<&str> = p
+            .functions
+            .iter()
+            .map(|f| f.name.as_str())
+            .chain(p.structs.iter().map(|s| s.name.as_str()))
+            .chain(p.interfaces.iter().map(|i| i.name.as_str()))
+            .chain(p.enums.iter().map(|e| e.name.as_str()))
+            .chain(p.typedefs.iter().map(|t| t.name.as_str()))
+            .chain(p.constants.iter().map(|c| c.name.as_str()))
+            .collect();
+        for skipped in &p.skipped {
+            if matches!(skipped.reason, SkipReason::OutOfScope { .. })
+                || emitted.contains(skipped.name.as_str())
+            {
+                continue;
+            }
+            let kind = match skipped.kind.as_str() {
+                "function" => &mut coverage.functions,
+                "struct" | "union" => &mut coverage.structs,
+                "enum" => &mut coverage.enums,
+                "typedef" => &mut coverage.typedefs,
+                "macro" => &mut coverage.constants,
+                _ => continue,
+            };
+            let (reason, detail) = skip_reason(&skipped.reason);
+            kind.declared += 1;
+            kind.omitted.push(Omission {
+                name: skipped.name.clone(),
+                reason: reason.to_string(),
+                detail,
+                location: skipped.location.clone(),
+            });
+        }
+        coverage
+    }
+
+    /// Every kind with its label, in report order.
+    pub fn kinds(&self) -> [(&'static str, &Coverage); 5] {
+        [
+            ("functions", &self.functions),
+            ("structs", &self.structs),
+            ("enums", &self.enums),
+            ("typedefs", &self.typedefs),
+            ("constants", &self.constants),
+        ]
+    }
+}
+
+impl Coverage {
+    fn emitted(count: usize) -> Self {
+        Self {
+            declared: count,
+            emitted: count,
+            omitted: Vec::new(),
+        }
+    }
+
+    /// Emitted share of the declarations, in percent; 100 when nothing
+    /// was declared.
+    pub fn percent(&self) -> f64 {
+        percent(self.emitted, self.declared)
+    }
+}
+
+fn percent(emitted: usize, declared: usize) -> f64 {
+    if declared == 0 {
+        100.0
+    } else {
+        emitted as f64 * 100.0 / declared as f64
+    }
+}
+
+impl fmt::Display for CoverageReport {
+    /// One header line per partition, a line per kind that declared
+    /// anything, and its omissions indented below.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for p in &self.partitions {
+            writeln!(f, "{} ({})", p.namespace, p.library)?;
+            for (label, c) in p.kinds() {
+                if c.declared == 0 {
+                    continue;
+                }
+                writeln!(
+                    f,
+                    "  {label:<10} {:>5}/{:<5} {:>5.1}%",
+                    c.emitted,
+                    c.declared,
+                    c.percent()
+                )?;
+                for o in &c.omitted {
+                    write!(f, "    {}: {}", o.name, o.reason)?;
+                    if let Some(detail) = &o.detail {
+                        write!(f, " ({detail})")?;
+                    }
+                    writeln!(f)?;
+                }
+            }
+        }
+        let (declared, emitted) = self.totals();
+        writeln!(
+            f,
+            "{}: {emitted}/{declared} declarations emitted ({:.1}%)",
+            self.assembly,
+            percent(emitted, declared)
+        )
+    }
+}
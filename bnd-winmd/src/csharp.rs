@@ -0,0 +1,568 @@
+//! C# backend — model types → P/Invoke source, without CsWin32.
+//!
+//! One file per namespace, laid out the way CsWin32 projects a winmd:
+//! enums with their underlying type, blittable structs (explicit layout
+//! whenever clang reported every field offset), typedefs as single-field
+//! `Value` structs, vtables as structs of `delegate* unmanaged` slots, and
+//! an `Apis` class holding the `[DllImport]` functions and constants. The
+//! code is `unsafe` throughout: pointers stay pointers.
+
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::Write;
+use std::path::PathBuf;
+
+use tracing::debug;
+
+use crate::model::*;
+
+/// Render every partition as a C# file named after its namespace
+/// (`Foo.Bar.cs`).
+pub fn emit_csharp(partitions: &[Partition], registry: &TypeRegistry) -> Vec<(PathBuf, String)> {
+    let types = Types {
+        structs: partitions
+            .iter()
+            .flat_map(|p| &p.structs)
+            .map(|s| (s.name.as_str(), s.size))
+            .collect(),
+        enums: partitions
+            .iter()
+            .flat_map(|p| &p.enums)
+            .map(|e| (e.name.as_str(), &e.underlying_type))
+            .collect(),
+        typedefs: partitions
+            .iter()
+            .flat_map(|p| &p.typedefs)
+            .map(|td| (td.name.as_str(), &td.underlying_type))
+            .collect(),
+    };
+
+    // Partitions sharing a namespace share the file; `Apis` is partial.
+    let mut sources: BTreeMap<&str, String> = BTreeMap::new();
+    for p in partitions.iter().filter(|p| !p.is_empty()) {
+        let file = File {
+            namespace: &p.namespace,
+            registry,
+            types: &types,
+        };
+        let source = sources.entry(&p.namespace).or_insert_with(|| {
+            format!(
+                "// Generated by bnd-winmd. Do not edit.\n\n\
+                 using System;\n\
+                 using System.Runtime.InteropServices;\n\n\
+                 namespace {};\n",
+                p.namespace
+            )
+        });
+        file.render(source, p);
+    }
+    sources
+        .into_iter()
+        .map(|(namespace, source)| {
+            debug!(namespace, bytes = source.len(), "rendered C# file");
+            (PathBuf::from(format!("{namespace}.cs")), source)
+        })
+        .collect()
+}
+
+/// Every extracted named type, for sizing array elements.
+struct Types<'a> {
+    structs: HashMap<&'a str, usize>,
+    enums: HashMap<&'a str, &'a CType>,
+    typedefs: HashMap<&'a str, &'a CType>,
+}
+
+impl Types<'_> {
+    /// Size of `ty` in bytes; `None` for pointer-sized and unknown types,
+    /// whose size depends on the target.
+    fn size_of(&self, ty: &CType) -> Option<usize> {
+        match ty {
+            CType::Bool | CType::I8 | CType::U8 => Some(1),
+            CType::I16 | CType::U16 => Some(2),
+            CType::I32 | CType::U32 | CType::F32 => Some(4),
+            CType::I64 | CType::U64 | CType::F64 => Some(8),
+            CType::WChar { width } => Some(*width),
+            CType::Array { element, len } => Some(self.size_of(element)? * len),
+            CType::Named { name, resolved } => {
+                if let Some(size) = self.structs.get(name.as_str()) {
+                    Some(*size)
+                } else if let Some(ty) = self.enums.get(name.as_str()) {
+                    self.size_of(ty)
+                } else if let Some(ty) = self.typedefs.get(name.as_str()) {
+                    self.size_of(ty)
+                } else {
+                    self.size_of(resolved.as_deref()?)
+                }
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Renders the declarations of one namespace.
+struct File<'a> {
+    namespace: &'a str,
+    registry: &'a TypeRegistry,
+    types: &'a Types<'a>,
+}
+
+impl File<'_> {
+    fn render(&self, out: &mut String, p: &Partition) {
+        for en in &p.enums {
+            render_enum(out, en);
+        }
+        for s in &p.structs {
+            self.render_struct(out, s);
+        }
+        for td in &p.typedefs {
+            self.render_typedef(out, td);
+        }
+        for i in &p.interfaces {
+            self.render_interface(out, i);
+        }
+        if !p.functions.is_empty() || !p.constants.is_empty() {
+            out.push_str("\npublic static unsafe partial class Apis\n{\n");
+            for c in &p.constants {
+                render_constant(out, c);
+            }
+            for (i, f) in p.functions.iter().enumerate() {
+                if i > 0 || !p.constants.is_empty() {
+                    out.push('\n');
+                }
+                let library = p.library_soname.as_deref().unwrap_or(&p.library);
+                self.render_function(out, f, library);
+            }
+            out.push_str("}\n");
+        }
+    }
+
+    fn render_struct(&self, out: &mut String, s: &StructDef) {
+        // Explicit offsets reproduce clang's layout exactly, over-aligned
+        // members included; otherwise fall back to sequential packing.
+        // Arrays spread over numbered fields need each element's offset.
+        let explicit = s.is_union
+            || (!s.fields.is_empty()
+                && s.fields.iter().all(|f| {
+                    f.offset.is_some()
+                        && (self.array_element(&f.ty).is_none_or(|(element, _)| {
+                            fixed_buffer_type(element).is_some()
+                                || self.types.size_of(element).is_some()
+                        }))
+                }));
+        let layout = if explicit {
+            format!("LayoutKind.Explicit, Size = {}", s.size)
+        } else {
+            format!(
+                "LayoutKind.Sequential, Pack = {}, Size = {}",
+                s.packing.unwrap_or(s.align).max(1),
+                s.size
+            )
+        };
+        let _ = writeln!(
+            out,
+            "\n[StructLayout({layout})]\npublic unsafe struct {}\n{{",
+            ident(&s.name)
+        );
+        for field in &s.fields {
+            for bitfield in &field.native_bitfields {
+                let _ = writeln!(
+                    out,
+                    "    /// <summary>Bitfield <c>{}</c>: bits {}..{}.</summary>",
+                    bitfield.name,
+                    bitfield.offset,
+                    bitfield.offset + bitfield.width
+                );
+            }
+            let offset = if s.is_union { Some(0) } else { field.offset };
+            // A union member array of pointer-sized elements can't be
+            // spread over offsets; keep its bytes.
+            if s.is_union
+                && self.array_element(&field.ty).is_some_and(|(element, _)| {
+                    fixed_buffer_type(element).is_none() && self.types.size_of(element).is_none()
+                })
+            {
+                let _ = writeln!(
+                    out,
+                    "    [FieldOffset(0)]\n    public fixed byte {}[{}];",
+                    ident(&field.name),
+                    s.size
+                );
+                continue;
+            }
+            self.render_field(
+                out,
+                &field.name,
+                &field.ty,
+                explicit.then_some(offset).flatten(),
+            );
+        }
+        out.push_str("}\n");
+    }
+
+    /// A field, with arrays as `fixed` buffers when their element is a
+    /// primitive and as numbered fields otherwise.
+    fn render_field(&self, out: &mut String, name: &str, ty: &CType, offset: Option<usize>) {
+        let attr = |offset: Option<usize>| match offset {
+            Some(offset) => format!("    [FieldOffset({offset})]\n"),
+            None => String::new(),
+        };
+        let Some((element, len)) = self.array_element(ty) else {
+            let _ = writeln!(
+                out,
+                "{}    public {} {};",
+                attr(offset),
+                self.cs_type(ty),
+                ident(name)
+            );
+            return;
+        };
+
+        if let Some(primitive) = fixed_buffer_type(element) {
+            let _ = writeln!(
+                out,
+                "{}    public fixed {primitive} {}[{len}];",
+                attr(offset),
+                ident(name)
+            );
+            return;
+        }
+        let element_type = self.cs_type(element);
+        let element_size = self.types.size_of(element);
+        for i in 0..len {
+            let _ = writeln!(
+                out,
+                "{}    public {element_type} {}_{i};",
+                attr(offset.zip(element_size).map(|(o, size)| o + i * size)),
+                name
+            );
+        }
+    }
+
+    /// The innermost element type and total length of a (possibly
+    /// multi-dimensional) array; `None` for anything else.
+    fn array_element<'t>(&self, ty: &'t CType) -> Option<(&'t CType, usize)> {
+        let CType::Array { .. } = ty else {
+            return None;
+        };
+        let mut element = ty;
+        let mut len = 1;
+        while let CType::Array {
+            element: inner,
+            len: n,
+        } = element
+        {
+            element = inner;
+            len *= n;
+        }
+        Some((element, len))
+    }
+
+    /// Like CsWin32's `NativeTypedef` projection: a struct whose only field
+    /// is `Value`.
+    fn render_typedef(&self, out: &mut String, td: &TypedefDef) {
+        if let Some(free) = &td.raii_free {
+            let _ = writeln!(
+                out,
+                "\n/// <summary>Released with <see cref=\"Apis.{free}\"/>.</summary>"
+            );
+        } else {
+            out.push('\n');
+        }
+        let _ = write!(out, "public unsafe struct {}\n{{\n", ident(&td.name));
+        // An opaque typedef is only ever used behind a pointer.
+        if td.underlying_type != CType::Void {
+            self.render_field(out, "Value", &td.underlying_type, None);
+        }
+        out.push_str("}\n");
+    }
+
+    /// A vtable struct: one unmanaged function pointer per slot, taking the
+    /// object pointer first.
+    fn render_interface(&self, out: &mut String, i: &InterfaceDef) {
+        if let Some(guid) = &i.guid {
+            let _ = writeln!(out, "\n/// <summary>IID <c>{{{guid}}}</c>.</summary>");
+        } else {
+            out.push('\n');
+        }
+        let _ = write!(
+            out,
+            "[StructLayout(LayoutKind.Sequential)]\npublic unsafe struct {}\n{{\n",
+            ident(&i.name)
+        );
+        for m in &i.methods {
+            let mut types = vec!["void*".to_string()];
+            types.extend(m.params.iter().map(|p| self.cs_type(&p.ty)));
+            types.push(self.cs_type(&m.return_type));
+            let _ = writeln!(
+                out,
+                "    public delegate* unmanaged<{}> {};",
+                types.join(", "),
+                ident(&m.name)
+            );
+        }
+        out.push_str("}\n");
+    }
+
+    fn render_function(&self, out: &mut String, f: &FunctionDef, library: &str) {
+        let library = f.library.as_deref().unwrap_or(library);
+        let params: Vec<String> = f
+            .params
+            .iter()
+            .map(|p| format!("{} {}", self.cs_type(&p.ty), ident(&p.name)))
+            .collect();
+        let _ = writeln!(
+            out,
+            "    [DllImport(\"{library}\", EntryPoint = \"{}\", CallingConvention = CallingConvention.{}, ExactSpelling = true)]",
+            f.entry_point,
+            calling_convention(f.calling_convention)
+        );
+        let _ = writeln!(
+            out,
+            "    public static extern {} {}({});",
+            self.cs_type(&f.return_type),
+            ident(&f.name),
+            params.join(", ")
+        );
+    }
+
+    fn cs_type(&self, ty: &CType) -> String {
+        match ty {
+            CType::Void | CType::Forward { .. } => "void".to_string(),
+            // `bool` would marshal as a 4-byte BOOL; C's `_Bool` is one byte.
+            CType::Bool => "byte".to_string(),
+            CType::I8 => "sbyte".to_string(),
+            CType::U8 => "byte".to_string(),
+            CType::I16 => "short".to_string(),
+            CType::U16 => "ushort".to_string(),
+            CType::I32 => "int".to_string(),
+            CType::U32 => "uint".to_string(),
+            CType::I64 => "long".to_string(),
+            CType::U64 => "ulong".to_string(),
+            CType::F32 => "float".to_string(),
+            CType::F64 => "double".to_string(),
+            CType::WChar { width: 2 } => "char".to_string(),
+            CType::WChar { .. } => "uint".to_string(),
+            CType::ISize => "nint".to_string(),
+            CType::USize => "nuint".to_string(),
+            // A pointer to a function is the function pointer itself.
+            CType::Ptr { pointee, .. } if matches!(pointee.as_ref(), CType::FnPtr { .. }) => {
+                self.cs_type(pointee)
+            }
+            CType::Ptr { pointee, .. } => format!("{}*", self.cs_type(pointee)),
+            // Arrays outside struct fields and typedefs decay like in C.
+            CType::Array { element, .. } => format!("{}*", self.cs_type(element)),
+            CType::Named { name, resolved } => match resolved {
+                // System typedefs no partition extracted fall back to the
+                // type clang resolved.
+                Some(resolved) if !self.registry.contains(name) => self.cs_type(resolved),
+                _ => self.type_name(name),
+            },
+            CType::FnPtr {
+                return_type,
+                params,
+                calling_convention: cc,
+            } => {
+                let mut types: Vec<String> = params.iter().map(|p| self.cs_type(p)).collect();
+                types.push(self.cs_type(return_type));
+                format!(
+                    "delegate* unmanaged[{}]<{}>",
+                    calling_convention(*cc),
+                    types.join(", ")
+                )
+            }
+        }
+    }
+
+    /// A named type, qualified when it lives in another namespace.
+    fn type_name(&self, name: &str) -> String {
+        let namespace = self.registry.namespace_for(name, self.namespace);
+        if namespace == self.namespace {
+            ident(name)
+        } else {
+            format!("global::{namespace}.{}", ident(name))
+        }
+    }
+}
+
+fn render_enum(out: &mut String, en: &EnumDef) {
+    let underlying = match en.underlying_type {
+        CType::I8 => "sbyte",
+        CType::U8 => "byte",
+        CType::I16 => "short",
+        CType::U16 => "ushort",
+        CType::U32 => "uint",
+        CType::I64 => "long",
+        CType::U64 => "ulong",
+        _ => "int",
+    };
+    let _ = write!(
+        out,
+        "\npublic enum {} : {underlying}\n{{\n",
+        ident(&en.name)
+    );
+    for (i, variant) in en.variants.iter().enumerate() {
+        let value = match en.alias_of(i) {
+            Some(original) => ident(&original.name),
+            None => en.value(variant).to_string(),
+        };
+        let _ = writeln!(out, "    {} = {value},", ident(&variant.name));
+    }
+    out.push_str("}\n");
+}
+
+fn render_constant(out: &mut String, c: &ConstantDef) {
+    let name = ident(&c.name);
+    let _ = match &c.value {
+        ConstantValue::Signed(_) | ConstantValue::Unsigned(_) => {
+            let ty = match c.int_type().expect("integer constant") {
+                CType::I8 => "sbyte",
+                CType::U8 => "byte",
+                CType::I16 => "short",
+                CType::U16 => "ushort",
+                CType::I32 => "int",
+                CType::U32 => "uint",
+                CType::I64 => "long",
+                _ => "ulong",
+            };
+            let v = c.int_value().expect("integer constant");
+            writeln!(out, "    public const {ty} {name} = {v};")
+        }
+        ConstantValue::Float(v) if v.is_nan() => {
+            writeln!(out, "    public const double {name} = double.NaN;")
+        }
+        ConstantValue::Float(v) if v.is_infinite() => {
+            let sign = if *v > 0.0 { "Positive" } else { "Negative" };
+            writeln!(
+                out,
+                "    public const double {name} = double.{sign}Infinity;"
+            )
+        }
+        ConstantValue::Float(v) => writeln!(out, "    public const double {name} = {v:?};"),
+        ConstantValue::Guid(g) => {
+            let d = &g.data4;
+            writeln!(
+                out,
+                "    public static readonly Guid {name} = new Guid(0x{:08x}, 0x{:04x}, 0x{:04x}, \
+                 0x{:02x}, 0x{:02x}, 0x{:02x}, 0x{:02x}, 0x{:02x}, 0x{:02x}, 0x{:02x}, 0x{:02x});",
+                g.data1, g.data2, g.data3, d[0], d[1], d[2], d[3], d[4], d[5], d[6], d[7]
+            )
+        }
+    };
+}
+
+/// The C# type of a `fixed` buffer element, if `ty` can be one.
+fn fixed_buffer_type(ty: &CType) -> Option<&'static str> {
+    Some(match ty {
+        CType::Bool | CType::U8 => "byte",
+        CType::I8 => "sbyte",
+        CType::I16 => "short",
+        CType::U16 => "ushort",
+        CType::I32 => "int",
+        CType::U32 | CType::WChar { width: 4 } => "uint",
+        CType::I64 => "long",
+        CType::U64 => "ulong",
+        CType::F32 => "float",
+        CType::F64 => "double",
+        CType::WChar { .. } => "char",
+        CType::Named {
+            resolved: Some(resolved),
+            ..
+        } => return fixed_buffer_type(resolved),
+        _ => return None,
+    })
+}
+
+fn calling_convention(cc: CallConv) -> &'static str {
+    match cc {
+        CallConv::Cdecl => "Cdecl",
+        CallConv::Stdcall => "StdCall",
+        CallConv::Fastcall => "FastCall",
+    }
+}
+
+/// `name` as a C# identifier: keywords get the `@` prefix.
+fn ident(name: &str) -> String {
+    const KEYWORDS: &[&str] = &[
+        "abstract",
+        "as",
+        "base",
+        "bool",
+        "break",
+        "byte",
+        "case",
+        "catch",
+        "char",
+        "checked",
+        "class",
+        "const",
+        "continue",
+        "decimal",
+        "default",
+        "delegate",
+        "do",
+        "double",
+        "else",
+        "enum",
+        "event",
+        "explicit",
+        "extern",
+        "false",
+        "finally",
+        "fixed",
+        "float",
+        "for",
+        "foreach",
+        "goto",
+        "if",
+        "implicit",
+        "in",
+        "int",
+        "interface",
+        "internal",
+        "is",
+        "lock",
+        "long",
+        "namespace",
+        "new",
+        "null",
+        "object",
+        "operator",
+        "out",
+        "override",
+        "params",
+        "private",
+        "protected",
+        "public",
+        "readonly",
+        "ref",
+        "return",
+        "sbyte",
+        "sealed",
+        "short",
+        "sizeof",
+        "stackalloc",
+        "static",
+        "string",
+        "struct",
+        "switch",
+        "this",
+        "throw",
+        "true",
+        "try",
+        "typeof",
+        "uint",
+        "ulong",
+        "unchecked",
+        "unsafe",
+        "ushort",
+        "using",
+        "virtual",
+        "void",
+        "volatile",
+        "while",
+    ];
+    if KEYWORDS.contains(&name) {
+        format!("@{name}")
+    } else {
+        name.to_string()
+    }
+}
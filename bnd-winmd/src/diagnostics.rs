@@ -0,0 +1,106 @@
+//! Machine-readable diagnostics — the warnings a generation run logs,
+//! collected with their codes and source locations.
+//!
+//! Every recorded diagnostic is also logged via `tracing` at its site, so
+//! collecting them changes nothing for callers that only read logs.
+
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use serde::Serialize;
+
+use crate::model::SourceLocation;
+
+/// One warning from the pipeline.
+#[derive(Debug, Clone, Serialize)]
+pub struct Diagnostic {
+    /// Stable machine-readable identifier, e.g. `duplicate_dropped`.
+    pub code: &'static str,
+    pub message: String,
+    /// Declaration or config entry the diagnostic is about.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// Partition namespace, when the diagnostic belongs to one.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub namespace: Option<String>,
+    /// Header the declaration came from.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub file: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub line: Option<u32>,
+}
+
+impl Diagnostic {
+    pub fn new(code: &'static str, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+            name: None,
+            namespace: None,
+            file: None,
+            line: None,
+        }
+    }
+
+    pub fn name(mut self, name: impl Into<String>) -> Self {
+        self.name = Some(name.into());
+        self
+    }
+
+    pub fn namespace(mut self, namespace: impl Into<String>) -> Self {
+        self.namespace = Some(namespace.into());
+        self
+    }
+
+    pub fn location(mut self, location: Option<&SourceLocation>) -> Self {
+        if let Some(loc) = location {
+            self.file = Some(loc.file.clone());
+            self.line = Some(loc.line);
+        }
+        self
+    }
+}
+
+/// Diagnostics collected over one generation run, in the order they
+/// were raised.
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(transparent)]
+pub struct Diagnostics(Vec<Diagnostic>);
+
+impl Diagnostics {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn push(&mut self, diagnostic: Diagnostic) {
+        self.0.push(diagnostic);
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Diagnostic> {
+        self.0.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Diagnostics with the given `code`.
+    pub fn with_code<'a>(&'a self, code: &'a str) -> impl Iterator<Item = &'a Diagnostic> {
+        self.0.iter().filter(move |d| d.code == code)
+    }
+
+    /// Serialize as a pretty-printed JSON array.
+    pub fn to_json(&self) -> Result<String> {
+        serde_json::to_string_pretty(self).context("serializing diagnostics")
+    }
+
+    /// Write as a pretty-printed JSON array to `path`.
+    pub fn write_json(&self, path: &Path) -> Result<()> {
+        std::fs::write(path, self.to_json()?)
+            .with_context(|| format!("writing diagnostics to {}", path.display()))
+    }
+}
@@ -0,0 +1,349 @@
+//! WinMD diff — compare two winmd files at the metadata level and classify
+//! each change as breaking or additive.
+//!
+//! Used to gate regeneration of checked-in bindings: a header upgrade that
+//! only adds functions/constants is additive, while removed items, changed
+//! signatures, changed struct layouts, or changed constant values are
+//! breaking.
+
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use serde::Serialize;
+use windows_metadata::reader::{AsRow, TypeIndex};
+
+use crate::inspect;
+
+/// Whether a change can break existing consumers of the generated bindings.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Breaking,
+    Additive,
+}
+
+/// A single metadata difference.
+#[derive(Debug, Clone, Serialize)]
+pub struct Change {
+    pub severity: Severity,
+    /// Fully-qualified item, e.g. `zlib.Apis.deflate` or `zlib.z_stream`.
+    pub item: String,
+    pub description: String,
+}
+
+/// Result of [`diff_indexes`] — every difference between two winmds.
+#[derive(Debug, Default, Serialize)]
+pub struct DiffReport {
+    pub changes: Vec<Change>,
+}
+
+impl DiffReport {
+    /// Returns `true` if the two winmds describe the same API surface.
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+
+    /// Returns `true` if any change is breaking.
+    pub fn is_breaking(&self) -> bool {
+        self.breaking().next().is_some()
+    }
+
+    pub fn breaking(&self) -> impl Iterator<Item = &Change> {
+        self.changes
+            .iter()
+            .filter(|c| c.severity == Severity::Breaking)
+    }
+
+    pub fn additive(&self) -> impl Iterator<Item = &Change> {
+        self.changes
+            .iter()
+            .filter(|c| c.severity == Severity::Additive)
+    }
+
+    fn push(&mut self, severity: Severity, item: String, description: String) {
+        self.changes.push(Change {
+            severity,
+            item,
+            description,
+        });
+    }
+}
+
+impl fmt::Display for DiffReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for c in &self.changes {
+            let tag = match c.severity {
+                Severity::Breaking => "BREAKING",
+                Severity::Additive => "additive",
+            };
+            writeln!(f, "{tag:<8}  {}: {}", c.item, c.description)?;
+        }
+        Ok(())
+    }
+}
+
+/// Compare two winmd files on disk.
+pub fn diff_files(old: &Path, new: &Path) -> Result<DiffReport> {
+    let read = |path: &Path| {
+        let bytes =
+            std::fs::read(path).with_context(|| format!("reading winmd {}", path.display()))?;
+        surface(bytes).with_context(|| format!("parsing winmd {}", path.display()))
+    };
+    Ok(diff(read(old)?, read(new)?))
+}
+
+/// Compare two in-memory winmds, including the `FieldLayout` offsets of
+/// explicitly laid out fields.
+pub fn diff_bytes(old: Vec<u8>, new: Vec<u8>) -> Result<DiffReport> {
+    Ok(diff(surface(old)?, surface(new)?))
+}
+
+/// Compare two indexed winmds. The index does not expose `FieldLayout`,
+/// so field offsets are not compared; see [`diff_bytes`].
+pub fn diff_indexes(old: &TypeIndex, new: &TypeIndex) -> DiffReport {
+    let no_layouts = HashMap::new();
+    diff(
+        collect_types(old, &no_layouts),
+        collect_types(new, &no_layouts),
+    )
+}
+
+fn diff(old: BTreeMap<String, TypeSurface>, new: BTreeMap<String, TypeSurface>) -> DiffReport {
+    let mut report = DiffReport::default();
+
+    for (name, old_ty) in &old {
+        match new.get(name) {
+            None => report.push(
+                Severity::Breaking,
+                name.clone(),
+                format!("{} removed", old_ty.kind),
+            ),
+            Some(new_ty) => diff_type(&mut report, name, old_ty, new_ty),
+        }
+    }
+    for (name, new_ty) in &new {
+        if !old.contains_key(name) {
+            report.push(
+                Severity::Additive,
+                name.clone(),
+                format!("{} added", new_ty.kind),
+            );
+        }
+    }
+
+    report
+}
+
+// ---------------------------------------------------------------------------
+// Comparable snapshot of a winmd
+// ---------------------------------------------------------------------------
+
+struct TypeSurface {
+    kind: &'static str,
+    /// `(packing_size, class_size)` from ClassLayout.
+    layout: Option<(u16, u32)>,
+    /// Fields in declaration order: `(name, type, constant value)`.
+    fields: Vec<(String, String, Option<String>)>,
+    /// `FieldLayout` offsets of explicitly laid out fields, by name.
+    offsets: BTreeMap<String, u32>,
+    /// Methods by name: rendered signature including the import library.
+    methods: BTreeMap<String, String>,
+}
+
+/// Snapshot of an in-memory winmd, with its `FieldLayout` offsets.
+fn surface(bytes: Vec<u8>) -> Result<BTreeMap<String, TypeSurface>> {
+    let field_layouts = crate::tables::field_layouts(&bytes)?;
+    Ok(collect_types(&inspect::index_bytes(bytes)?, &field_layouts))
+}
+
+fn collect_types(
+    index: &TypeIndex,
+    field_layouts: &HashMap<usize, u32>,
+) -> BTreeMap<String, TypeSurface> {
+    let mut types = BTreeMap::new();
+    for td in index.types() {
+        if inspect::is_synthetic(&td) {
+            continue;
+        }
+        let fields = td
+            .fields()
+            .map(|f| {
+                (
+                    f.name().to_string(),
+                    inspect::type_name(&f.ty()),
+                    f.constant().map(|c| inspect::value_text(&c.value())),
+                )
+            })
+            .collect();
+        let offsets = td
+            .fields()
+            .filter_map(|f| Some((f.name().to_string(), *field_layouts.get(&f.pos())?)))
+            .collect();
+        let methods = td
+            .methods()
+            .map(|m| {
+                let sig = inspect::method_sig(&m);
+                let params: Vec<&str> = sig.params.iter().map(|(_, ty)| ty.as_str()).collect();
+                let mut rendered = format!("fn({}) -> {}", params.join(", "), sig.return_type);
+                if let Some(imp) = m.impl_map() {
+                    rendered.push_str(&format!(" [{}]", imp.import_scope().name()));
+                }
+                (m.name().to_string(), rendered)
+            })
+            .collect();
+        types.insert(
+            format!("{}.{}", td.namespace(), td.name()),
+            TypeSurface {
+                kind: inspect::type_kind(&td),
+                layout: td
+                    .class_layout()
+                    .map(|l| (l.packing_size(), l.class_size())),
+                fields,
+                offsets,
+                methods,
+            },
+        );
+    }
+    types
+}
+
+// ---------------------------------------------------------------------------
+// Per-type comparison
+// ---------------------------------------------------------------------------
+
+fn diff_type(report: &mut DiffReport, name: &str, old: &TypeSurface, new: &TypeSurface) {
+    if old.kind != new.kind {
+        report.push(
+            Severity::Breaking,
+            name.to_string(),
+            format!("kind changed from {} to {}", old.kind, new.kind),
+        );
+        return;
+    }
+
+    if old.layout != new.layout {
+        report.push(
+            Severity::Breaking,
+            name.to_string(),
+            format!(
+                "layout changed from {} to {}",
+                layout_text(old.layout),
+                layout_text(new.layout)
+            ),
+        );
+    }
+
+    match old.kind {
+        // Apis constants and enum variants are independent named values:
+        // additions are additive, removals and value changes are breaking.
+        "apis" | "enum" => diff_named_fields(report, name, &old.fields, &new.fields),
+        // Struct/union/typedef fields define the layout, so any change to
+        // the field list is breaking.
+        _ => {
+            if old.fields != new.fields {
+                report.push(
+                    Severity::Breaking,
+                    name.to_string(),
+                    format!(
+                        "fields changed from {{{}}} to {{{}}}",
+                        fields_text(&old.fields),
+                        fields_text(&new.fields)
+                    ),
+                );
+            }
+        }
+    }
+
+    // A field that moved breaks the layout even when the field list and
+    // size stay the same.
+    for (field, old_offset) in &old.offsets {
+        match new.offsets.get(field) {
+            Some(new_offset) if new_offset != old_offset => report.push(
+                Severity::Breaking,
+                format!("{name}.{field}"),
+                format!("layout changed: offset {old_offset} to {new_offset}"),
+            ),
+            _ => {}
+        }
+    }
+
+    for (method, old_sig) in &old.methods {
+        let item = format!("{name}.{method}");
+        match new.methods.get(method) {
+            None => report.push(Severity::Breaking, item, "removed".into()),
+            Some(new_sig) if new_sig != old_sig => report.push(
+                Severity::Breaking,
+                item,
+                format!("signature changed from `{old_sig}` to `{new_sig}`"),
+            ),
+            Some(_) => {}
+        }
+    }
+    for method in new.methods.keys() {
+        if !old.methods.contains_key(method) {
+            report.push(
+                Severity::Additive,
+                format!("{name}.{method}"),
+                "added".into(),
+            );
+        }
+    }
+}
+
+fn diff_named_fields(
+    report: &mut DiffReport,
+    name: &str,
+    old: &[(String, String, Option<String>)],
+    new: &[(String, String, Option<String>)],
+) {
+    let new_by_name: BTreeMap<&str, _> = new.iter().map(|f| (f.0.as_str(), f)).collect();
+    let old_by_name: BTreeMap<&str, _> = old.iter().map(|f| (f.0.as_str(), f)).collect();
+
+    for (field, old_f) in &old_by_name {
+        let item = format!("{name}.{field}");
+        match new_by_name.get(field) {
+            None => report.push(Severity::Breaking, item, "removed".into()),
+            Some(new_f) if new_f.1 != old_f.1 => report.push(
+                Severity::Breaking,
+                item,
+                format!("type changed from {} to {}", old_f.1, new_f.1),
+            ),
+            Some(new_f) if new_f.2 != old_f.2 => report.push(
+                Severity::Breaking,
+                item,
+                format!(
+                    "value changed from {} to {}",
+                    old_f.2.as_deref().unwrap_or("<none>"),
+                    new_f.2.as_deref().unwrap_or("<none>")
+                ),
+            ),
+            Some(_) => {}
+        }
+    }
+    for field in new_by_name.keys() {
+        if !old_by_name.contains_key(field) {
+            report.push(
+                Severity::Additive,
+                format!("{name}.{field}"),
+                "added".into(),
+            );
+        }
+    }
+}
+
+fn layout_text(layout: Option<(u16, u32)>) -> String {
+    match layout {
+        Some((pack, size)) => format!("size {size}, pack {pack}"),
+        None => "none".into(),
+    }
+}
+
+fn fields_text(fields: &[(String, String, Option<String>)]) -> String {
+    fields
+        .iter()
+        .map(|(name, ty, _)| format!("{name}: {ty}"))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
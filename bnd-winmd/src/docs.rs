@@ -0,0 +1,383 @@
+//! Markdown API reference rendered from the model.
+//!
+//! One page per namespace (`Foo.Bar.md`) listing its functions with their
+//! signatures, structs with size, alignment and field offsets, enums,
+//! typedefs, interfaces and constants, plus an `index.md` linking the
+//! pages. Types are spelled the way the generated Rust bindings spell
+//! them, and every reference to a documented type links to its entry, so
+//! the pages browse as-is on GitHub or any Markdown site generator.
+//!
+//! ```no_run
+//! use std::path::Path;
+//!
+//! let ir = bnd_winmd::ir(Path::new("bnd-winmd.toml")).unwrap();
+//! for (page, text) in bnd_winmd::docs::render(&ir.partitions) {
+//!     std::fs::write(Path::new("docs/api").join(page), text).unwrap();
+//! }
+//! ```
+
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::Write;
+use std::path::PathBuf;
+
+use crate::model::*;
+
+const GENERATED: &str = "<!-- Generated by bnd-winmd. Do not edit. -->\n\n";
+
+/// Render one Markdown page per namespace, plus `index.md`, as `(file
+/// name, text)` pairs. Partitions sharing a namespace share a page.
+pub fn render(partitions: &[Partition]) -> Vec<(PathBuf, String)> {
+    let mut namespaces: BTreeMap<&str, Vec<&Partition>> = BTreeMap::new();
+    for p in partitions.iter().filter(|p| !p.is_empty()) {
+        namespaces.entry(&p.namespace).or_default().push(p);
+    }
+    let mut types: HashMap<&str, &str> = HashMap::new();
+    for p in partitions {
+        let names = p.structs.iter().map(|s| &s.name);
+        let names = names.chain(p.enums.iter().map(|e| &e.name));
+        let names = names.chain(p.typedefs.iter().map(|t| &t.name));
+        let names = names.chain(p.interfaces.iter().map(|i| &i.name));
+        for name in names {
+            types.entry(name).or_insert(&p.namespace);
+        }
+    }
+
+    let mut index = format!("{GENERATED}# API reference\n\n");
+    for namespace in namespaces.keys() {
+        let _ = writeln!(index, "- [{namespace}]({namespace}.md)");
+    }
+    let mut pages = vec![(PathBuf::from("index.md"), index)];
+    for (namespace, parts) in &namespaces {
+        let page = Page {
+            namespace,
+            types: &types,
+        };
+        pages.push((PathBuf::from(format!("{namespace}.md")), page.render(parts)));
+    }
+    pages
+}
+
+/// Renders the page of one namespace.
+struct Page<'a> {
+    namespace: &'a str,
+    /// Namespace of every documented type, for links.
+    types: &'a HashMap<&'a str, &'a str>,
+}
+
+impl Page<'_> {
+    fn render(&self, parts: &[&Partition]) -> String {
+        let mut out = format!("{GENERATED}# {}\n", self.namespace);
+        let libraries: Vec<&str> = parts
+            .iter()
+            .filter(|p| !p.functions.is_empty())
+            .map(|p| p.library.as_str())
+            .collect();
+        if !libraries.is_empty() {
+            let _ = writeln!(out, "\nLibrary: `{}`", libraries.join("`, `"));
+        }
+
+        let functions: Vec<&FunctionDef> = parts.iter().flat_map(|p| &p.functions).collect();
+        if !functions.is_empty() {
+            out.push_str("\n## Functions\n");
+            for f in functions {
+                self.render_function(&mut out, f);
+            }
+        }
+        let structs: Vec<&StructDef> = parts.iter().flat_map(|p| &p.structs).collect();
+        if !structs.is_empty() {
+            out.push_str("\n## Structs\n");
+            for s in structs {
+                self.render_struct(&mut out, s);
+            }
+        }
+        let enums: Vec<&EnumDef> = parts.iter().flat_map(|p| &p.enums).collect();
+        if !enums.is_empty() {
+            out.push_str("\n## Enums\n");
+            for en in enums {
+                self.render_enum(&mut out, en);
+            }
+        }
+        let typedefs: Vec<&TypedefDef> = parts.iter().flat_map(|p| &p.typedefs).collect();
+        if !typedefs.is_empty() {
+            out.push_str("\n## Typedefs\n");
+            for td in typedefs {
+                self.render_typedef(&mut out, td);
+            }
+        }
+        let interfaces: Vec<&InterfaceDef> = parts.iter().flat_map(|p| &p.interfaces).collect();
+        if !interfaces.is_empty() {
+            out.push_str("\n## Interfaces\n");
+            for i in interfaces {
+                self.render_interface(&mut out, i);
+            }
+        }
+        let constants: Vec<&ConstantDef> = parts.iter().flat_map(|p| &p.constants).collect();
+        if !constants.is_empty() {
+            out.push_str("\n## Constants\n\n| Name | Type | Value |\n|---|---|---:|\n");
+            for c in constants {
+                let (ty, value) = constant(c);
+                let spelling = c
+                    .spelling
+                    .as_ref()
+                    .map(|s| format!(" (`{}`)", s.replace('|', "\\|")))
+                    .unwrap_or_default();
+                let _ = writeln!(out, "| `{}` | `{ty}` | `{value}`{spelling} |", c.name);
+            }
+        }
+        out
+    }
+
+    fn render_function(&self, out: &mut String, f: &FunctionDef) {
+        let _ = writeln!(
+            out,
+            "\n### `{}`\n\n```rust\nfn {}({}){}\n```",
+            f.name,
+            f.name,
+            self.params(&f.params).join(", "),
+            self.returns(&f.return_type)
+        );
+        if f.entry_point != f.name {
+            let _ = writeln!(out, "\nExported as `{}`.", f.entry_point);
+        }
+        if let Some(library) = &f.library {
+            let _ = writeln!(out, "\nImported from `{library}`.");
+        }
+        if let Some(convention) = f.return_convention {
+            let _ = writeln!(out, "\nReturn convention: `{}`.", convention.as_str());
+        }
+        if f.params.is_empty() {
+            return;
+        }
+        out.push_str("\n| Parameter | Type | Notes |\n|---|---|---|\n");
+        for p in &f.params {
+            let _ = writeln!(
+                out,
+                "| `{}` | {} | {} |",
+                p.name,
+                self.type_cell(&p.ty),
+                self.param_notes(p, &f.params)
+            );
+        }
+    }
+
+    fn render_struct(&self, out: &mut String, s: &StructDef) {
+        let keyword = if s.is_union { "Union" } else { "Struct" };
+        let _ = writeln!(
+            out,
+            "\n### `{}`\n\n{keyword}, {} bytes, aligned to {}.",
+            s.name, s.size, s.align
+        );
+        if let Some(packing) = s.packing {
+            let _ = writeln!(out, "Packed to {packing}.");
+        }
+        if s.fields.is_empty() {
+            out.push_str("\nOpaque: no fields are declared.\n");
+            return;
+        }
+        out.push_str("\n| Offset | Field | Type |\n|---:|---|---|\n");
+        for field in &s.fields {
+            let offset = field.offset.map_or("—".to_string(), |o| o.to_string());
+            let mut ty = self.type_cell(&field.ty);
+            for bitfield in &field.native_bitfields {
+                let _ = write!(
+                    ty,
+                    "<br>`{}`: bits {}..{}",
+                    bitfield.name,
+                    bitfield.offset,
+                    bitfield.offset + bitfield.width
+                );
+            }
+            let _ = writeln!(out, "| {offset} | `{}` | {ty} |", field.name);
+        }
+    }
+
+    fn render_enum(&self, out: &mut String, en: &EnumDef) {
+        let _ = writeln!(
+            out,
+            "\n### `{}`\n\nUnderlying type: `{}`.\n\n| Name | Value |\n|---|---:|",
+            en.name,
+            self.type_name(&en.underlying_type)
+        );
+        for (i, variant) in en.variants.iter().enumerate() {
+            let _ = write!(out, "| `{}` | `{}`", variant.name, en.value(variant));
+            if let Some(original) = en.alias_of(i) {
+                let _ = write!(out, " (alias of `{}`)", original.name);
+            }
+            out.push_str(" |\n");
+        }
+    }
+
+    fn render_typedef(&self, out: &mut String, td: &TypedefDef) {
+        let _ = writeln!(
+            out,
+            "\n### `{}`\n\nAlias of {}.",
+            td.name,
+            self.type_cell(&td.underlying_type)
+        );
+        if let Some(free) = &td.raii_free {
+            let _ = writeln!(out, "\nReleased with [`{free}`](#{}).", anchor(free));
+        }
+    }
+
+    fn render_interface(&self, out: &mut String, i: &InterfaceDef) {
+        let _ = writeln!(out, "\n### `{}`", i.name);
+        if let Some(guid) = &i.guid {
+            let _ = writeln!(out, "\nIID `{{{guid}}}`.");
+        }
+        out.push_str("\n```rust\n");
+        for m in &i.methods {
+            let _ = writeln!(
+                out,
+                "fn {}({}){};",
+                m.name,
+                self.params(&m.params).join(", "),
+                self.returns(&m.return_type)
+            );
+        }
+        out.push_str("```\n");
+    }
+
+    fn params(&self, params: &[ParamDef]) -> Vec<String> {
+        params
+            .iter()
+            .map(|p| format!("{}: {}", p.name, self.type_name(&p.ty)))
+            .collect()
+    }
+
+    fn returns(&self, ty: &CType) -> String {
+        match ty {
+            CType::Void => String::new(),
+            other => format!(" -> {}", self.type_name(other)),
+        }
+    }
+
+    /// A type as a table cell: a code span, linked to the entry of the
+    /// documented type it is built on, if any.
+    fn type_cell(&self, ty: &CType) -> String {
+        let spelled = format!("`{}`", self.type_name(ty));
+        let mut base = ty;
+        loop {
+            match base {
+                CType::Ptr { pointee, .. } => base = pointee,
+                CType::Array { element, .. } => base = element,
+                _ => break,
+            }
+        }
+        match base {
+            CType::Named { name, .. } => self.link(&spelled, name),
+            _ => spelled,
+        }
+    }
+
+    /// `text` linked to the entry of type `name`, if it is documented.
+    fn link(&self, text: &str, name: &str) -> String {
+        match self.types.get(name) {
+            Some(&namespace) if namespace == self.namespace => {
+                format!("[{text}](#{})", anchor(name))
+            }
+            Some(namespace) => format!("[{text}]({namespace}.md#{})", anchor(name)),
+            None => text.to_string(),
+        }
+    }
+
+    /// Annotations of a parameter, comma-separated; indices become names.
+    fn param_notes(&self, p: &ParamDef, params: &[ParamDef]) -> String {
+        let name_of = |index: usize| {
+            params
+                .get(index)
+                .map_or_else(|| index.to_string(), |p| format!("`{}`", p.name))
+        };
+        let a = &p.annotations;
+        let mut notes = Vec::new();
+        match a.direction {
+            Some(ParamDirection::In) => notes.push("in".to_string()),
+            Some(ParamDirection::Out) => notes.push("out".to_string()),
+            Some(ParamDirection::InOut) => notes.push("in/out".to_string()),
+            None => {}
+        }
+        if a.optional {
+            notes.push("optional".to_string());
+        }
+        if p.is_string {
+            notes.push("C string".to_string());
+        }
+        if let Some(index) = a.array_count {
+            notes.push(format!("{} elements", name_of(index)));
+        }
+        if let Some(index) = a.memory_size {
+            notes.push(format!("{} bytes", name_of(index)));
+        }
+        if let Some(e) = &p.associated_enum {
+            notes.push(format!("values of {}", self.link(&format!("`{e}`"), e)));
+        }
+        if let Some(c) = &p.associated_constant {
+            notes.push(format!("see `{c}`"));
+        }
+        notes.join(", ")
+    }
+
+    /// Rust spelling, as in the generated bindings.
+    fn type_name(&self, ty: &CType) -> String {
+        match ty {
+            CType::Void | CType::Forward { .. } => "c_void".to_string(),
+            CType::Bool => "bool".to_string(),
+            CType::I8 => "i8".to_string(),
+            CType::U8 => "u8".to_string(),
+            CType::I16 => "i16".to_string(),
+            CType::U16 => "u16".to_string(),
+            CType::I32 => "i32".to_string(),
+            CType::U32 => "u32".to_string(),
+            CType::I64 => "i64".to_string(),
+            CType::U64 => "u64".to_string(),
+            CType::F32 => "f32".to_string(),
+            CType::F64 => "f64".to_string(),
+            CType::WChar { width: 2 } => "u16".to_string(),
+            CType::WChar { .. } => "u32".to_string(),
+            CType::ISize => "isize".to_string(),
+            CType::USize => "usize".to_string(),
+            // A pointer to a function is the function pointer itself.
+            CType::Ptr { pointee, .. } if matches!(pointee.as_ref(), CType::FnPtr { .. }) => {
+                self.type_name(pointee)
+            }
+            CType::Ptr { pointee, is_const } => {
+                let mutability = if *is_const { "const" } else { "mut" };
+                format!("*{mutability} {}", self.type_name(pointee))
+            }
+            CType::Array { element, len } => format!("[{}; {len}]", self.type_name(element)),
+            CType::Named { name, resolved } => match resolved {
+                // System typedefs no partition extracted read as the type
+                // clang resolved.
+                Some(resolved) if !self.types.contains_key(name.as_str()) => {
+                    self.type_name(resolved)
+                }
+                _ => name.clone(),
+            },
+            CType::FnPtr {
+                return_type,
+                params,
+                ..
+            } => {
+                let params: Vec<String> = params.iter().map(|p| self.type_name(p)).collect();
+                format!("fn({}){}", params.join(", "), self.returns(return_type))
+            }
+        }
+    }
+}
+
+fn constant(c: &ConstantDef) -> (&'static str, String) {
+    match &c.value {
+        ConstantValue::Signed(_) | ConstantValue::Unsigned(_) => (
+            c.int_type()
+                .and_then(|t| t.int_name())
+                .expect("integer constant"),
+            c.int_value().expect("integer constant").to_string(),
+        ),
+        ConstantValue::Float(v) => ("f64", format!("{v:?}")),
+        ConstantValue::Guid(g) => ("GUID", format!("{{{g}}}")),
+    }
+}
+
+/// GitHub's anchor for a `` ### `name` `` heading.
+fn anchor(name: &str) -> String {
+    name.to_lowercase()
+}
@@ -0,0 +1,163 @@
+//! WinMD dump — human-readable listing of a winmd's contents.
+//!
+//! Renders every TypeDef with its layout, fields, constants, methods,
+//! P/Invoke imports and custom attributes, in file order. Intended for
+//! debugging emit problems without writing one-off reader code.
+
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use windows_metadata::{
+    FieldAttributes,
+    reader::{AsRow, HasAttributes, TypeIndex},
+};
+
+use crate::inspect;
+
+/// Render the winmd at `path` as text.
+pub fn dump_file(path: &Path) -> Result<String> {
+    let bytes = std::fs::read(path).with_context(|| format!("reading winmd {}", path.display()))?;
+    dump_bytes(bytes).with_context(|| format!("dumping winmd {}", path.display()))
+}
+
+/// Render an in-memory winmd as text, explicit-layout fields with their
+/// `FieldLayout` offsets.
+pub fn dump_bytes(bytes: Vec<u8>) -> Result<String> {
+    let field_layouts = crate::tables::field_layouts(&bytes)?;
+    Ok(render(&inspect::index_bytes(bytes)?, &field_layouts))
+}
+
+/// Render an indexed winmd as text. The index does not expose
+/// `FieldLayout`, so field offsets are left out; see [`dump_bytes`].
+pub fn dump_index(index: &TypeIndex) -> String {
+    render(index, &HashMap::new())
+}
+
+fn render(index: &TypeIndex, field_layouts: &HashMap<usize, u32>) -> String {
+    let mut out = String::new();
+    for td in index.types() {
+        if inspect::is_synthetic(&td) {
+            continue;
+        }
+
+        for attr in td.attributes() {
+            writeln!(
+                out,
+                "[{}]",
+                attribute_text(&inspect::attribute_name(&attr), &attr.value())
+            )
+            .unwrap();
+        }
+        write!(
+            out,
+            "{} {}.{}",
+            inspect::type_kind(&td),
+            td.namespace(),
+            td.name()
+        )
+        .unwrap();
+        if let Some(layout) = td.class_layout() {
+            write!(
+                out,
+                " (size {}, pack {})",
+                layout.class_size(),
+                layout.packing_size()
+            )
+            .unwrap();
+        }
+        out.push('\n');
+
+        for field in td.fields() {
+            for attr in field.attributes() {
+                writeln!(
+                    out,
+                    "    [{}]",
+                    attribute_text(&inspect::attribute_name(&attr), &attr.value())
+                )
+                .unwrap();
+            }
+            let ty = inspect::type_name(&field.ty());
+            match field.constant() {
+                Some(c) if field.flags().contains(FieldAttributes::Literal) => writeln!(
+                    out,
+                    "    const {}: {ty} = {}",
+                    field.name(),
+                    inspect::value_text(&c.value())
+                ),
+                _ => match field_layouts.get(&field.pos()) {
+                    Some(offset) => writeln!(out, "    {}: {ty} @ {offset}", field.name()),
+                    None => writeln!(out, "    {}: {ty}", field.name()),
+                },
+            }
+            .unwrap();
+        }
+
+        for method in td.methods() {
+            for attr in method.attributes() {
+                writeln!(
+                    out,
+                    "    [{}]",
+                    attribute_text(&inspect::attribute_name(&attr), &attr.value())
+                )
+                .unwrap();
+            }
+            let sig = inspect::method_sig(&method);
+            let params: Vec<String> = sig
+                .params
+                .iter()
+                .map(|(name, ty)| format!("{name}: {ty}"))
+                .collect();
+            write!(
+                out,
+                "    fn {}({}) -> {}",
+                method.name(),
+                params.join(", "),
+                sig.return_type
+            )
+            .unwrap();
+            if let Some(imp) = method.impl_map() {
+                write!(
+                    out,
+                    "  // import {}!{}",
+                    imp.import_scope().name(),
+                    imp.import_name()
+                )
+                .unwrap();
+            }
+            out.push('\n');
+
+            for param in method.params() {
+                for attr in param.attributes() {
+                    writeln!(
+                        out,
+                        "        {}: [{}]",
+                        param.name(),
+                        attribute_text(&inspect::attribute_name(&attr), &attr.value())
+                    )
+                    .unwrap();
+                }
+            }
+        }
+        out.push('\n');
+    }
+    out
+}
+
+fn attribute_text(name: &str, args: &[(String, windows_metadata::Value)]) -> String {
+    if args.is_empty() {
+        return name.to_string();
+    }
+    let args: Vec<String> = args
+        .iter()
+        .map(|(arg, value)| {
+            if arg.is_empty() {
+                inspect::value_text(value)
+            } else {
+                format!("{arg} = {}", inspect::value_text(value))
+            }
+        })
+        .collect();
+    format!("{name}({})", args.join(", "))
+}
@@ -1,32 +1,156 @@
 //! Emitter — model types → `windows-metadata` writer calls → winmd bytes.
+//!
+//! [`emit_rust_sys`] and [`emit_csharp`] are the alternative backends,
+//! rendering the same model as Rust `-sys` or C# P/Invoke source instead;
+//! [`emit_idl`] renders it as IDL text for review.
 
-use anyhow::Result;
-use tracing::debug;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use tracing::{debug, warn};
 use windows_metadata::{
     FieldAttributes, MethodAttributes, MethodCallAttributes, MethodImplAttributes,
     PInvokeAttributes, ParamAttributes, Signature, Type, TypeAttributes, Value,
-    writer::{File, HasConstant, MemberRefParent, TypeDefOrRef},
+    reader::{self, AsRow, HasAttributes},
+    writer::{AttributeType, File, HasAttribute, HasConstant, MemberRefParent, TypeDefOrRef},
 };
 
+use crate::assembly::{self, AssemblyMetadata, COMPANY_ATTRIBUTE, COPYRIGHT_ATTRIBUTE};
+pub use crate::csharp::emit_csharp;
+pub use crate::idl::emit_idl;
 use crate::model::*;
+pub use crate::rust_sys::{GeneratedTree, emit_rust_sys};
+use crate::stamp::{DEFINE_ATTRIBUTE, SOURCE_HASH_ATTRIBUTE, STAMP_ATTRIBUTE, STAMP_TYPE, Stamp};
+
+/// Namespace of bnd-winmd's own attribute types — those with no Win32
+/// metadata equivalent (the stamp, return conventions).
+const BND_METADATA: &str = "Bnd.Metadata";
 
 /// Emit all partitions into a single winmd byte stream.
+///
+/// Types are written in model order: per partition, enums, structs,
+/// typedefs and interfaces, then the `Apis` class(es). The generation pipeline
+/// sorts the model first (see [`Partition::sort`]) so the bytes are
+/// reproducible.
 pub fn emit_winmd(
     assembly_name: &str,
     partitions: &[Partition],
     registry: &TypeRegistry,
 ) -> Result<Vec<u8>> {
+    let partitions: Vec<&Partition> = partitions.iter().collect();
+    into_bytes(emit_file(assembly_name, &partitions, registry)?, registry)
+}
+
+/// [`emit_winmd`] plus the provenance [`Stamp`] and the assembly
+/// version, company and copyright.
+pub(crate) fn emit_winmd_stamped(
+    assembly_name: &str,
+    partitions: &[&Partition],
+    registry: &TypeRegistry,
+    stamp: &Stamp,
+    metadata: &AssemblyMetadata,
+) -> Result<Vec<u8>> {
+    let mut file = emit_file(assembly_name, partitions, registry)?;
+    emit_stamp(&mut file, stamp, metadata);
+    let mut bytes = into_bytes(file, registry)?;
+    if let Some(version) = metadata.version {
+        assembly::write_version(&mut bytes, version)?;
+    }
+    Ok(bytes)
+}
+
+fn emit_file(
+    assembly_name: &str,
+    partitions: &[&Partition],
+    registry: &TypeRegistry,
+) -> Result<File> {
     let mut file = File::new(assembly_name);
 
+    let mut apis_namespaces = HashMap::new();
     for partition in partitions {
-        emit_partition(&mut file, partition, registry)?;
+        emit_partition(&mut file, partition, registry, &mut apis_namespaces)?;
     }
 
-    Ok(file.into_stream())
+    Ok(file)
+}
+
+/// Serialize `file`, resolving imported types through an `AssemblyRef`
+/// named after the winmd they come from (see
+/// [`assembly::write_assembly_refs`]).
+fn into_bytes(file: File, registry: &TypeRegistry) -> Result<Vec<u8>> {
+    let mut bytes = file.into_stream();
+    assembly::write_assembly_refs(&mut bytes, |namespace, name| {
+        registry.assembly_for(namespace, name)
+    })?;
+    Ok(bytes)
+}
+
+/// The synthetic `<BndStamp>` type and its attributes (see [`crate::stamp`]),
+/// including the assembly's company and copyright.
+fn emit_stamp(file: &mut File, stamp: &Stamp, metadata: &AssemblyMetadata) {
+    let object_ref = file.TypeRef("System", "Object");
+    let td = file.TypeDef(
+        "",
+        STAMP_TYPE,
+        TypeDefOrRef::TypeRef(object_ref),
+        TypeAttributes::Abstract | TypeAttributes::Sealed,
+    );
+    emit_attribute(
+        file,
+        HasAttribute::TypeDef(td),
+        BND_METADATA,
+        STAMP_ATTRIBUTE,
+        vec![
+            Value::Utf8(stamp.version.clone()),
+            Value::Utf8(stamp.config_hash.clone()),
+        ],
+    );
+    for (path, hash) in &stamp.headers {
+        emit_attribute(
+            file,
+            HasAttribute::TypeDef(td),
+            BND_METADATA,
+            SOURCE_HASH_ATTRIBUTE,
+            vec![Value::Utf8(path.clone()), Value::Utf8(hash.clone())],
+        );
+    }
+    for (scope, flag) in &stamp.defines {
+        emit_attribute(
+            file,
+            HasAttribute::TypeDef(td),
+            BND_METADATA,
+            DEFINE_ATTRIBUTE,
+            vec![Value::Utf8(scope.clone()), Value::Utf8(flag.clone())],
+        );
+    }
+    for (name, value) in [
+        (COMPANY_ATTRIBUTE, &metadata.company),
+        (COPYRIGHT_ATTRIBUTE, &metadata.copyright),
+    ] {
+        if let Some(value) = value {
+            emit_attribute(
+                file,
+                HasAttribute::TypeDef(td),
+                BND_METADATA,
+                name,
+                vec![Value::Utf8(value.clone())],
+            );
+        }
+    }
 }
 
 /// Emit a single partition's declarations into the writer.
-fn emit_partition(file: &mut File, partition: &Partition, registry: &TypeRegistry) -> Result<()> {
+///
+/// `apis_namespaces` maps each namespace given an `Apis` class so far to
+/// whether it is a shard (see [`apis_classes`]), so that a shard landing
+/// on another class's namespace is an error rather than a duplicate type.
+fn emit_partition(
+    file: &mut File,
+    partition: &Partition,
+    registry: &TypeRegistry,
+    apis_namespaces: &mut HashMap<String, bool>,
+) -> Result<()> {
     let ns = &partition.namespace;
 
     // Emit enums
@@ -44,22 +168,64 @@ fn emit_partition(file: &mut File, partition: &Partition, registry: &TypeRegistr
         emit_typedef(file, ns, td, registry)?;
     }
 
-    // Emit functions (P/Invoke) — all go under a single "Apis" TypeDef
-    if !partition.functions.is_empty() || !partition.constants.is_empty() {
+    for i in &partition.interfaces {
+        emit_interface(file, ns, i, registry);
+    }
+
+    // Emit functions (P/Invoke) and #define constants (static literal
+    // fields) on the "Apis" class, or on the Apis classes of its shard
+    // namespaces (see `apis_split`)
+    for class in apis_classes(partition)? {
+        let shard = class.namespace != *ns;
+        if let Some(was_shard) = apis_namespaces.insert(class.namespace.clone(), shard) {
+            anyhow::ensure!(
+                !shard,
+                "partition {ns}: Apis shard namespace {} already has an Apis class",
+                class.namespace
+            );
+            anyhow::ensure!(
+                !was_shard,
+                "partition {ns}: namespace already has the Apis class of another \
+                 partition's apis_split / max_methods shard"
+            );
+        }
         let object_ref = file.TypeRef("System", "Object");
-        let _apis_td = file.TypeDef(
-            ns,
+        let apis_td = file.TypeDef(
+            &class.namespace,
             "Apis",
             TypeDefOrRef::TypeRef(object_ref),
             TypeAttributes::Public | TypeAttributes::Abstract | TypeAttributes::Sealed,
         );
+        if let Some(soname) = &partition.library_soname {
+            emit_attribute(
+                file,
+                HasAttribute::TypeDef(apis_td),
+                BND_METADATA,
+                "LibrarySonameAttribute",
+                vec![
+                    Value::Utf8(partition.library.clone()),
+                    Value::Utf8(soname.clone()),
+                ],
+            );
+        }
+        if partition.delay_load {
+            let library = partition
+                .library_soname
+                .as_ref()
+                .unwrap_or(&partition.library);
+            emit_attribute(
+                file,
+                HasAttribute::TypeDef(apis_td),
+                BND_METADATA,
+                "DelayLoadAttribute",
+                vec![Value::Utf8(library.clone())],
+            );
+        }
 
-        for f in &partition.functions {
+        for f in class.functions {
             emit_function(file, ns, f, &partition.library, registry)?;
         }
-
-        // Emit #define constants as static literal fields on the Apis class
-        for c in &partition.constants {
+        for c in class.constants {
             emit_constant(file, c)?;
         }
     }
@@ -67,6 +233,81 @@ fn emit_partition(file: &mut File, partition: &Partition, registry: &TypeRegistr
     Ok(())
 }
 
+/// One `Apis` class and the members emitted on it.
+struct ApisClass<'a> {
+    namespace: String,
+    functions: Vec<&'a FunctionDef>,
+    constants: Vec<&'a ConstantDef>,
+}
+
+/// Spread a partition's functions and constants over `Apis` classes in
+/// sub-namespaces, since windows-bindgen only reads members from classes
+/// named exactly `Apis`: bucketed by lowercased name prefix for
+/// [`ApisSplit::ByPrefix`] (`<namespace>.evp_md`), then each bucket sharded
+/// into `max_methods`-sized chunks (`<bucket>`, `<bucket>.apis1`, ...).
+/// Empty when the partition has neither. Model order is kept within a
+/// class.
+///
+/// Errors when `max_methods` is zero, which a hand-written or edited IR
+/// file can carry past the extract-time check, or when a shard lands on a
+/// prefix bucket's namespace (prefix `apis1` next to a sharded `Apis`).
+fn apis_classes(partition: &Partition) -> Result<Vec<ApisClass<'_>>> {
+    let ns = &partition.namespace;
+    anyhow::ensure!(
+        partition.max_methods != Some(0),
+        "partition {ns}: max_methods must be at least 1",
+    );
+    let mut buckets: BTreeMap<String, (Vec<&FunctionDef>, Vec<&ConstantDef>)> = BTreeMap::new();
+    let bucket_of = |name: &str| match partition.apis_split {
+        ApisSplit::None => ns.clone(),
+        ApisSplit::ByPrefix => match name.rfind('_') {
+            Some(i) if i > 0 => format!("{ns}.{}", name[..i].to_ascii_lowercase()),
+            _ => ns.clone(),
+        },
+    };
+    for f in &partition.functions {
+        buckets.entry(bucket_of(&f.name)).or_default().0.push(f);
+    }
+    for c in &partition.constants {
+        buckets.entry(bucket_of(&c.name)).or_default().1.push(c);
+    }
+
+    let Some(max) = partition.max_methods else {
+        return Ok(buckets
+            .into_iter()
+            .map(|(namespace, (functions, constants))| ApisClass {
+                namespace,
+                functions,
+                constants,
+            })
+            .collect());
+    };
+    let mut classes = Vec::new();
+    for (bucket, (functions, constants)) in &buckets {
+        let shards = functions.len().max(constants.len()).div_ceil(max);
+        for shard in 0..shards {
+            let namespace = if shard == 0 {
+                bucket.clone()
+            } else {
+                format!("{bucket}.apis{shard}")
+            };
+            anyhow::ensure!(
+                shard == 0 || !buckets.contains_key(&namespace),
+                "partition {ns}: Apis shard namespace {namespace} collides with the \
+                 namespace of prefix `{}`",
+                &namespace[ns.len() + 1..]
+            );
+            let chunk = |start: usize, len: usize| start.min(len)..(start + max).min(len);
+            classes.push(ApisClass {
+                namespace,
+                functions: functions[chunk(shard * max, functions.len())].to_vec(),
+                constants: constants[chunk(shard * max, constants.len())].to_vec(),
+            });
+        }
+    }
+    Ok(classes)
+}
+
 // ---------------------------------------------------------------------------
 // Enum emission
 // ---------------------------------------------------------------------------
@@ -142,18 +383,57 @@ fn emit_struct(
         TypeDefOrRef::TypeRef(valuetype_ref),
         TypeAttributes::Public | layout_attr,
     );
-    file.ClassLayout(td, s.align as u16, s.size as u32);
+    let (packing_size, class_size) = class_layout(s)?;
+    file.ClassLayout(td, packing_size, class_size);
 
     for field in &s.fields {
         let wintype = ctype_to_wintype(&field.ty, namespace, registry);
-        file.Field(&field.name, &wintype, FieldAttributes::Public);
-        // TODO: emit NativeBitfieldAttribute for bitfield fields
+        let field_id = file.Field(&field.name, &wintype, FieldAttributes::Public);
+        if s.is_union {
+            file.FieldLayout(field_id, 0);
+        }
+        emit_encoding(file, HasAttribute::Field(field_id), &field.ty);
+        for bitfield in &field.native_bitfields {
+            emit_metadata_attribute(
+                file,
+                HasAttribute::Field(field_id),
+                "NativeBitfieldAttribute",
+                vec![
+                    Value::Utf8(bitfield.name.clone()),
+                    Value::I64(bitfield.offset as i64),
+                    Value::I64(bitfield.width as i64),
+                ],
+            );
+        }
     }
 
     debug!(name = %s.name, fields = s.fields.len(), size = s.size, "emitted struct");
     Ok(())
 }
 
+/// Largest `ClassLayout.PackingSize` ECMA-335 allows.
+const MAX_PACKING_SIZE: usize = 128;
+
+/// `ClassLayout` (packing size, class size) for a struct. The packing size
+/// is the explicit `#pragma pack` value for packed structs and the struct
+/// alignment otherwise. Fails instead of truncating values that metadata
+/// cannot represent.
+fn class_layout(s: &StructDef) -> Result<(u16, u32)> {
+    let packing = s.packing.unwrap_or(s.align);
+    if packing > MAX_PACKING_SIZE || (packing != 0 && !packing.is_power_of_two()) {
+        anyhow::bail!(
+            "struct {}: alignment {} cannot be represented in winmd \
+             (ClassLayout packing must be a power of two up to {})",
+            s.name,
+            packing,
+            MAX_PACKING_SIZE
+        );
+    }
+    let size = u32::try_from(s.size)
+        .map_err(|_| anyhow::anyhow!("struct {}: size {} exceeds u32::MAX", s.name, s.size))?;
+    Ok((packing as u16, size))
+}
+
 // ---------------------------------------------------------------------------
 // Typedef emission
 // ---------------------------------------------------------------------------
@@ -171,26 +451,34 @@ fn emit_typedef(
         CType::FnPtr {
             return_type,
             params,
-            calling_convention: _,
-        } => Some((return_type.as_ref(), params.as_slice())),
+            calling_convention,
+        } => Some((return_type.as_ref(), params.as_slice(), *calling_convention)),
         CType::Ptr { pointee, .. } => match pointee.as_ref() {
             CType::FnPtr {
                 return_type,
                 params,
-                calling_convention: _,
-            } => Some((return_type.as_ref(), params.as_slice())),
+                calling_convention,
+            } => Some((return_type.as_ref(), params.as_slice(), *calling_convention)),
             _ => None,
         },
         _ => None,
     };
-    if let Some((return_type, params)) = fnptr {
-        emit_delegate(file, namespace, &td.name, return_type, params, registry)?;
+    if let Some((return_type, params, calling_convention)) = fnptr {
+        emit_delegate(
+            file,
+            namespace,
+            td,
+            return_type,
+            params,
+            calling_convention,
+            registry,
+        )?;
         return Ok(());
     }
 
     // Otherwise emit as a struct wrapper with NativeTypedefAttribute
     let valuetype_ref = file.TypeRef("System", "ValueType");
-    let _td = file.TypeDef(
+    let typedef = file.TypeDef(
         namespace,
         &td.name,
         TypeDefOrRef::TypeRef(valuetype_ref),
@@ -218,6 +506,15 @@ fn emit_typedef(
         MemberRefParent::TypeRef(attr_typeref),
     );
 
+    if let Some(free) = &td.raii_free {
+        emit_metadata_attribute(
+            file,
+            HasAttribute::TypeDef(typedef),
+            "RAIIFreeAttribute",
+            vec![Value::Utf8(free.clone())],
+        );
+    }
+
     debug!(name = %td.name, "emitted typedef");
     Ok(())
 }
@@ -229,19 +526,33 @@ fn emit_typedef(
 fn emit_delegate(
     file: &mut File,
     namespace: &str,
-    name: &str,
+    typedef: &TypedefDef,
     return_type: &CType,
     params: &[CType],
+    calling_convention: CallConv,
     registry: &TypeRegistry,
 ) -> Result<()> {
+    let name = typedef.name.as_str();
+    let param_names = &typedef.param_names;
     let delegate_ref = file.TypeRef("System", "MulticastDelegate");
-    let _td = file.TypeDef(
+    let td = file.TypeDef(
         namespace,
         name,
         TypeDefOrRef::TypeRef(delegate_ref),
         TypeAttributes::Public | TypeAttributes::Sealed,
     );
 
+    // The Invoke signature has no slot for an unmanaged calling convention,
+    // so record it the way Win32 metadata does:
+    // `[UnmanagedFunctionPointer(CallingConvention.X)]` on the delegate.
+    emit_attribute(
+        file,
+        HasAttribute::TypeDef(td),
+        "System.Runtime.InteropServices",
+        "UnmanagedFunctionPointerAttribute",
+        vec![Value::I32(interop_calling_convention(calling_convention))],
+    );
+
     // Build signature for the Invoke method
     let ret_wintype = ctype_to_wintype(return_type, namespace, registry);
     let param_wintypes: Vec<Type> = params
@@ -265,19 +576,93 @@ fn emit_delegate(
         MethodImplAttributes::default(),
     );
 
-    // Add params (unnamed, indexed from 1)
+    // Add params (indexed from 1). Use the prototype's names when clang
+    // reported one per parameter, falling back to `paramN` for blanks.
+    let names_usable = param_names.len() == params.len();
     for i in 0..params.len() {
-        file.Param(
-            &format!("param{}", i),
-            (i + 1) as u16,
-            ParamAttributes::default(),
-        );
+        let name = match param_names.get(i) {
+            Some(n) if names_usable && !n.is_empty() => n.clone(),
+            _ => format!("param{}", i),
+        };
+        file.Param(&name, (i + 1) as u16, ParamAttributes::default());
     }
 
-    debug!(name, params = params.len(), "emitted delegate");
+    debug!(
+        name,
+        params = params.len(),
+        ?calling_convention,
+        "emitted delegate"
+    );
     Ok(())
 }
 
+/// `System.Runtime.InteropServices.CallingConvention` value for `cc`.
+fn interop_calling_convention(cc: CallConv) -> i32 {
+    match cc {
+        CallConv::Cdecl => 2,
+        CallConv::Stdcall => 3,
+        CallConv::Fastcall => 5,
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Interface emission
+// ---------------------------------------------------------------------------
+
+/// Emit a vtable struct as an interface: one abstract instance method per
+/// slot, in slot order.
+fn emit_interface(file: &mut File, namespace: &str, i: &InterfaceDef, registry: &TypeRegistry) {
+    // The writer always records a base type; readers classify by the
+    // Interface flag.
+    let object_ref = file.TypeRef("System", "Object");
+    let td = file.TypeDef(
+        namespace,
+        &i.name,
+        TypeDefOrRef::TypeRef(object_ref),
+        TypeAttributes::Public | TypeAttributes::Interface | TypeAttributes::Abstract,
+    );
+    if let Some(guid) = &i.guid {
+        emit_metadata_attribute(
+            file,
+            HasAttribute::TypeDef(td),
+            "GuidAttribute",
+            guid_args(guid),
+        );
+    }
+
+    for m in &i.methods {
+        let sig = Signature {
+            flags: MethodCallAttributes::HASTHIS,
+            return_type: ctype_to_wintype(&m.return_type, namespace, registry),
+            types: m
+                .params
+                .iter()
+                .map(|p| ctype_to_wintype(&p.ty, namespace, registry))
+                .collect(),
+        };
+        file.MethodDef(
+            &m.name,
+            &sig,
+            MethodAttributes::Public
+                | MethodAttributes::Virtual
+                | MethodAttributes::Abstract
+                | MethodAttributes::HideBySig
+                | MethodAttributes::NewSlot,
+            MethodImplAttributes::default(),
+        );
+        for (n, param) in m.params.iter().enumerate() {
+            let attrs = if param.ty.is_outer_ptr_mut() {
+                ParamAttributes::Out
+            } else {
+                ParamAttributes::default()
+            };
+            file.Param(&param.name, (n + 1) as u16, attrs);
+        }
+    }
+
+    debug!(name = %i.name, methods = i.methods.len(), "emitted interface");
+}
+
 // ---------------------------------------------------------------------------
 // Function (P/Invoke) emission
 // ---------------------------------------------------------------------------
@@ -289,11 +674,21 @@ fn emit_function(
     library: &str,
     registry: &TypeRegistry,
 ) -> Result<()> {
-    let ret_wintype = ctype_to_wintype(&f.return_type, namespace, registry);
+    let ret_wintype = if f.returns_string {
+        string_wintype(&f.return_type)
+    } else {
+        ctype_to_wintype(&f.return_type, namespace, registry)
+    };
     let param_wintypes: Vec<Type> = f
         .params
         .iter()
-        .map(|p| ctype_to_wintype(&p.ty, namespace, registry))
+        .map(|p| {
+            if p.is_string {
+                string_wintype(&p.ty)
+            } else {
+                ctype_to_wintype(&p.ty, namespace, registry)
+            }
+        })
         .collect();
 
     let sig = Signature {
@@ -314,19 +709,87 @@ fn emit_function(
         MethodAttributes::Public | MethodAttributes::HideBySig,
         MethodImplAttributes::PreserveSig,
     );
-    file.ImplMap(method, pinvoke_flags, &f.name, library);
+    let library = f.library.as_deref().unwrap_or(library);
+    file.ImplMap(method, pinvoke_flags, &f.entry_point, library);
+    if let Some(convention) = f.return_convention {
+        emit_attribute(
+            file,
+            HasAttribute::MethodDef(method),
+            BND_METADATA,
+            "ReturnConventionAttribute",
+            vec![Value::Utf8(convention.as_str().to_string())],
+        );
+    }
+
+    if f.returns_string {
+        let ret_id = file.Param("", 0, ParamAttributes::default());
+        emit_string_encoding(file, HasAttribute::Param(ret_id));
+    }
 
     for (i, param) in f.params.iter().enumerate() {
-        // windows-bindgen treats non-Out parameters as input and applies
-        // to_const_ptr(), converting PtrMut → PtrConst → `*const`.
-        // Set ParamAttributes::Out on mutable pointer params so that
-        // windows-bindgen preserves `*mut` in the generated Rust.
-        let attrs = if param.ty.is_outer_ptr_mut() {
-            ParamAttributes::Out
-        } else {
-            ParamAttributes::default()
+        let annotations = &param.annotations;
+        let mut attrs = match annotations.direction {
+            Some(ParamDirection::In) => ParamAttributes::In,
+            Some(ParamDirection::Out) => ParamAttributes::Out,
+            Some(ParamDirection::InOut) => ParamAttributes::In | ParamAttributes::Out,
+            // windows-bindgen treats non-Out parameters as input and applies
+            // to_const_ptr(), converting PtrMut → PtrConst → `*const`.
+            // Set ParamAttributes::Out on mutable pointer params so that
+            // windows-bindgen preserves `*mut` in the generated Rust. String
+            // types carry their own mutability.
+            None if param.ty.is_outer_ptr_mut() && !param.is_string => ParamAttributes::Out,
+            None => ParamAttributes::default(),
         };
-        file.Param(&param.name, (i + 1) as u16, attrs);
+        if annotations.optional {
+            attrs |= ParamAttributes::Optional;
+        }
+        let param_id = file.Param(&param.name, (i + 1) as u16, attrs);
+        if let Some(size) = annotations.memory_size {
+            emit_named_attribute(
+                file,
+                HasAttribute::Param(param_id),
+                "Windows.Win32.Foundation.Metadata",
+                "MemorySizeAttribute",
+                &[("BytesParamIndex".to_string(), Value::I16(size as i16))],
+            );
+        }
+        if let Some(count) = annotations.array_count {
+            emit_named_attribute(
+                file,
+                HasAttribute::Param(param_id),
+                "Windows.Win32.Foundation.Metadata",
+                "NativeArrayInfoAttribute",
+                &[("CountParamIndex".to_string(), Value::I16(count as i16))],
+            );
+        }
+        if annotations.not_null_terminated {
+            emit_metadata_attribute(
+                file,
+                HasAttribute::Param(param_id),
+                "NotNullTerminatedAttribute",
+                Vec::new(),
+            );
+        }
+        if param.is_string {
+            emit_string_encoding(file, HasAttribute::Param(param_id));
+        }
+        emit_encoding(file, HasAttribute::Param(param_id), &param.ty);
+        if let Some(en) = &param.associated_enum {
+            emit_metadata_attribute(
+                file,
+                HasAttribute::Param(param_id),
+                "AssociatedEnumAttribute",
+                vec![Value::Utf8(en.clone())],
+            );
+        }
+        if let Some(c) = &param.associated_constant {
+            emit_metadata_attribute(
+                file,
+                HasAttribute::Param(param_id),
+                "AssociatedConstantAttribute",
+                vec![Value::Utf8(c.clone())],
+            );
+        }
     }
 
     debug!(name = %f.name, params = f.params.len(), "emitted function");
@@ -339,12 +802,22 @@ fn emit_function(
 
 fn emit_constant(file: &mut File, c: &ConstantDef) -> Result<()> {
     let (wintype, value) = match &c.value {
-        ConstantValue::Signed(v) => (Type::I32, Value::I32(*v as i32)),
-        ConstantValue::Unsigned(v) => {
-            if *v <= u32::MAX as u64 {
-                (Type::U32, Value::U32(*v as u32))
-            } else {
-                (Type::U64, Value::U64(*v))
+        ConstantValue::Guid(guid) => {
+            emit_guid_constant(file, &c.name, guid);
+            return Ok(());
+        }
+        ConstantValue::Signed(_) | ConstantValue::Unsigned(_) => {
+            let ty = c.int_type().expect("integer constant");
+            let v = c.int_value().expect("integer constant");
+            match ty {
+                CType::I8 => (Type::I8, Value::I8(v as i8)),
+                CType::U8 => (Type::U8, Value::U8(v as u8)),
+                CType::I16 => (Type::I16, Value::I16(v as i16)),
+                CType::U16 => (Type::U16, Value::U16(v as u16)),
+                CType::I32 => (Type::I32, Value::I32(v as i32)),
+                CType::U32 => (Type::U32, Value::U32(v as u32)),
+                CType::I64 => (Type::I64, Value::I64(v as i64)),
+                _ => (Type::U64, Value::U64(v as u64)),
             }
         }
         ConstantValue::Float(v) => (Type::F64, Value::F64(*v)),
@@ -364,13 +837,324 @@ fn emit_constant(file: &mut File, c: &ConstantDef) -> Result<()> {
     Ok(())
 }
 
+/// GUIDs have no blob constant form; like Win32 metadata, emit a static
+/// `System.Guid` field carrying the value in a `GuidAttribute`.
+fn emit_guid_constant(file: &mut File, name: &str, guid: &Guid) {
+    let field = file.Field(
+        name,
+        &Type::named("System", "Guid"),
+        FieldAttributes::Public | FieldAttributes::Static,
+    );
+    emit_metadata_attribute(
+        file,
+        HasAttribute::Field(field),
+        "GuidAttribute",
+        guid_args(guid),
+    );
+    debug!(name, %guid, "emitted GUID constant");
+}
+
+/// `GuidAttribute(u32, u16, u16, u8 × 8)` arguments.
+fn guid_args(guid: &Guid) -> Vec<Value> {
+    let mut args = vec![
+        Value::U32(guid.data1),
+        Value::U16(guid.data2),
+        Value::U16(guid.data3),
+    ];
+    args.extend(guid.data4.iter().map(|&b| Value::U8(b)));
+    args
+}
+
+// ---------------------------------------------------------------------------
+// Winmd merging
+// ---------------------------------------------------------------------------
+
+/// Re-read several winmd files and re-emit their types as a single assembly.
+///
+/// Types are copied in input order. When the same `namespace.name` appears
+/// in more than one input, the first occurrence wins and later copies are
+/// dropped with a warning. `Apis` classes are the exception: inputs sharing
+/// a partition namespace each contribute functions, so the methods of later
+/// `Apis` classes are appended to the first one (a method name already
+/// present is dropped with a warning; class attributes such as the soname
+/// stay the first input's). The stamp (see [`crate::stamp`]) and the
+/// assembly version also come from the first input that has them. Custom
+/// attributes keep the namespace of their constructor's type and their
+/// arguments, and explicit-layout fields keep their `FieldLayout` offsets.
+pub fn merge_winmds(inputs: &[PathBuf], assembly_name: &str) -> Result<Vec<u8>> {
+    struct Input<'a> {
+        path: &'a Path,
+        field_layouts: HashMap<usize, u32>,
+        type_defs: usize,
+        index: reader::TypeIndex,
+    }
+
+    let mut file = File::new(assembly_name);
+    let mut seen = HashSet::new();
+    let mut version = None;
+
+    let mut loaded = Vec::with_capacity(inputs.len());
+    for input in inputs {
+        let bytes =
+            std::fs::read(input).with_context(|| format!("reading winmd {}", input.display()))?;
+        let field_layouts = crate::tables::field_layouts(&bytes)
+            .with_context(|| format!("reading FieldLayout of {}", input.display()))?;
+        version = version.or(AssemblyMetadata::from_bytes(&bytes)?.version);
+        let type_defs = crate::tables::type_def_rows(&bytes)?;
+        let index = crate::inspect::index_bytes(bytes)
+            .with_context(|| format!("parsing winmd {}", input.display()))?;
+        loaded.push(Input {
+            path: input,
+            field_layouts,
+            type_defs,
+            index,
+        });
+    }
+
+    for (i, input) in loaded.iter().enumerate() {
+        if let Some(td) = crate::inspect::stamp_type(&input.index, input.type_defs)
+            && seen.insert((String::new(), STAMP_TYPE.to_string()))
+        {
+            copy_typedef(&mut file, &td, &input.field_layouts);
+        }
+        let mut copied = 0usize;
+        for td in input.index.types() {
+            if crate::inspect::is_synthetic(&td) {
+                continue;
+            }
+            if !seen.insert((td.namespace().to_string(), td.name().to_string())) {
+                if !crate::inspect::is_apis(&td) {
+                    warn!(
+                        namespace = td.namespace(),
+                        name = td.name(),
+                        input = %input.path.display(),
+                        "merge: dropping duplicate type (first input wins)"
+                    );
+                }
+                continue;
+            }
+            copy_typedef(&mut file, &td, &input.field_layouts);
+            if crate::inspect::is_apis(&td) {
+                // Methods belong to the TypeDef emitted last, so the later
+                // inputs' functions are appended right here.
+                let mut names: HashSet<String> =
+                    td.methods().map(|m| m.name().to_string()).collect();
+                for later in &loaded[i + 1..] {
+                    let Some(other) = later
+                        .index
+                        .types()
+                        .find(|t| t.namespace() == td.namespace() && t.name() == td.name())
+                    else {
+                        continue;
+                    };
+                    if !crate::inspect::is_apis(&other) {
+                        continue;
+                    }
+                    for method in other.methods() {
+                        if names.insert(method.name().to_string()) {
+                            copy_method(&mut file, &method);
+                        } else {
+                            warn!(
+                                namespace = td.namespace(),
+                                name = method.name(),
+                                input = %later.path.display(),
+                                "merge: dropping duplicate function (first input wins)"
+                            );
+                        }
+                    }
+                }
+            }
+            copied += 1;
+        }
+        debug!(input = %input.path.display(), types = copied, "merged winmd");
+    }
+
+    let mut bytes = file.into_stream();
+    if let Some(version) = version {
+        assembly::write_version(&mut bytes, version)?;
+    }
+    Ok(bytes)
+}
+
+/// Copy one TypeDef (with its layout, fields, methods, params, imports and
+/// custom attributes) from a reader into the writer. `field_layouts` are
+/// the input's `FieldLayout` offsets by field row.
+fn copy_typedef(file: &mut File, td: &reader::TypeDef, field_layouts: &HashMap<usize, u32>) {
+    let namespace = td.namespace();
+    let extends = match td.extends() {
+        Some(base) => file.TypeRef(base.namespace(), base.name()),
+        None => file.TypeRef("System", "Object"),
+    };
+    let new_td = file.TypeDef(
+        namespace,
+        td.name(),
+        TypeDefOrRef::TypeRef(extends),
+        td.flags(),
+    );
+    if let Some(layout) = td.class_layout() {
+        file.ClassLayout(new_td, layout.packing_size(), layout.class_size());
+    }
+    copy_attributes(file, HasAttribute::TypeDef(new_td), td.attributes());
+
+    for field in td.fields() {
+        let new_field = file.Field(field.name(), &field.ty(), field.flags());
+        if let Some(&offset) = field_layouts.get(&field.pos()) {
+            file.FieldLayout(new_field, offset);
+        }
+        if let Some(constant) = field.constant() {
+            file.Constant(HasConstant::Field(new_field), &constant.value());
+        }
+        copy_attributes(file, HasAttribute::Field(new_field), field.attributes());
+    }
+
+    for method in td.methods() {
+        copy_method(file, &method);
+    }
+}
+
+/// Copy one method (with its import, params and custom attributes) onto
+/// the TypeDef emitted last.
+fn copy_method(file: &mut File, method: &reader::MethodDef) {
+    let new_method = file.MethodDef(
+        method.name(),
+        &method.signature(&[]),
+        method.flags(),
+        method.impl_flags(),
+    );
+    if let Some(imp) = method.impl_map() {
+        file.ImplMap(
+            new_method,
+            imp.flags(),
+            imp.import_name(),
+            imp.import_scope().name(),
+        );
+    }
+    copy_attributes(
+        file,
+        HasAttribute::MethodDef(new_method),
+        method.attributes(),
+    );
+    for param in method.params() {
+        let new_param = file.Param(param.name(), param.sequence(), param.flags());
+        copy_attributes(file, HasAttribute::Param(new_param), param.attributes());
+    }
+}
+
+fn copy_attributes<'a>(
+    file: &mut File,
+    parent: HasAttribute,
+    attributes: impl Iterator<Item = reader::Attribute<'a>>,
+) {
+    for attr in attributes {
+        let ctor_type = attr.ctor().parent();
+        emit_named_attribute(
+            file,
+            parent,
+            ctor_type.namespace(),
+            ctor_type.name(),
+            &attr.value(),
+        );
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Custom attribute emission
+// ---------------------------------------------------------------------------
+
+/// Attach a `Windows.Win32.Foundation.Metadata.<name>` custom attribute with
+/// positional constructor arguments. The constructor signature is derived
+/// from the argument values.
+fn emit_metadata_attribute(file: &mut File, parent: HasAttribute, name: &str, args: Vec<Value>) {
+    emit_attribute(
+        file,
+        parent,
+        "Windows.Win32.Foundation.Metadata",
+        name,
+        args,
+    );
+}
+
+/// Tag a field or parameter holding 4-byte wide characters (directly, or
+/// through pointers and arrays) with `NativeEncodingAttribute("utf-32")`,
+/// since their `u32` type alone does not say they are text.
+fn emit_encoding(file: &mut File, parent: HasAttribute, ty: &CType) {
+    let mut inner = ty;
+    while let CType::Ptr { pointee: t, .. } | CType::Array { element: t, .. } = inner {
+        inner = t;
+    }
+    if let CType::WChar { width: 4 } = inner {
+        emit_metadata_attribute(
+            file,
+            parent,
+            "NativeEncodingAttribute",
+            vec![Value::Utf8("utf-32".to_string())],
+        );
+    }
+}
+
+/// `PSTR` / `PCSTR` for a C-string `char *`; windows-bindgen maps these to
+/// its string pointer types.
+fn string_wintype(ty: &CType) -> Type {
+    let name = match ty {
+        CType::Ptr { is_const: true, .. } => "PCSTR",
+        _ => "PSTR",
+    };
+    Type::named("Windows.Win32.Foundation", name)
+}
+
+/// Tag a C-string parameter or return with `NativeEncodingAttribute("ansi")`:
+/// narrow text in the platform's multibyte encoding.
+fn emit_string_encoding(file: &mut File, parent: HasAttribute) {
+    emit_metadata_attribute(
+        file,
+        parent,
+        "NativeEncodingAttribute",
+        vec![Value::Utf8("ansi".to_string())],
+    );
+}
+
+/// Attach a custom attribute `namespace.name(args…)` to `parent`.
+fn emit_attribute(
+    file: &mut File,
+    parent: HasAttribute,
+    namespace: &str,
+    name: &str,
+    args: Vec<Value>,
+) {
+    let args: Vec<(String, Value)> = args.into_iter().map(|v| (String::new(), v)).collect();
+    emit_named_attribute(file, parent, namespace, name, &args);
+}
+
+/// Like [`emit_attribute`], but arguments with a name set that field of
+/// the attribute instead of being passed to its constructor.
+fn emit_named_attribute(
+    file: &mut File,
+    parent: HasAttribute,
+    namespace: &str,
+    name: &str,
+    args: &[(String, Value)],
+) {
+    let attr_typeref = file.TypeRef(namespace, name);
+    let sig = Signature {
+        flags: MethodCallAttributes::HASTHIS,
+        return_type: Type::Void,
+        types: args
+            .iter()
+            .filter(|(name, _)| name.is_empty())
+            .map(|(_, value)| value.ty())
+            .collect(),
+    };
+    let ctor = file.MemberRef(".ctor", &sig, MemberRefParent::TypeRef(attr_typeref));
+    file.Attribute(parent, AttributeType::MemberRef(ctor), args);
+}
+
 // ---------------------------------------------------------------------------
 // CType → windows_metadata::Type mapping
 // ---------------------------------------------------------------------------
 
 fn ctype_to_wintype(ctype: &CType, default_namespace: &str, registry: &TypeRegistry) -> Type {
     match ctype {
-        CType::Void => Type::Void,
+        CType::Void | CType::Forward { .. } => Type::Void,
         CType::Bool => Type::Bool,
         CType::I8 => Type::I8,
         CType::U8 => Type::U8,
@@ -382,9 +1166,18 @@ fn ctype_to_wintype(ctype: &CType, default_namespace: &str, registry: &TypeRegis
         CType::U64 => Type::U64,
         CType::F32 => Type::F32,
         CType::F64 => Type::F64,
+        // A UTF-16 code unit is `Char`; wider characters are plain u32
+        // tagged with `NativeEncodingAttribute` (see `emit_encoding`).
+        CType::WChar { width: 2 } => Type::Char,
+        CType::WChar { .. } => Type::U32,
         CType::ISize => Type::ISize,
         CType::USize => Type::USize,
 
+        // Interfaces are reference types: `IFoo *` is just `IFoo`.
+        CType::Ptr { pointee, .. } if matches!(pointee.as_ref(), CType::Named { name, .. } if registry.is_interface(name)) => {
+            ctype_to_wintype(pointee, default_namespace, registry)
+        }
+
         CType::Ptr {
             pointee,
             is_const: _,
@@ -422,8 +1215,9 @@ fn ctype_to_wintype(ctype: &CType, default_namespace: &str, registry: &TypeRegis
         }
 
         CType::FnPtr { .. } => {
-            // Function pointers in field positions are emitted as IntPtr (nint)
-            // since the delegate TypeDef is separate.
+            // Bare function types have no delegate TypeDef to reference
+            // (struct fields get one synthesized during extraction), so
+            // emit them as IntPtr (nint).
             Type::ISize
         }
     }
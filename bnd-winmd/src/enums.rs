@@ -0,0 +1,218 @@
+//! Enum and constant types from config: `[[synthesize_enum]]`,
+//! `[enum_type]` and `[constant_type]`.
+
+use std::collections::{BTreeMap, HashSet};
+
+use anyhow::Result;
+use tracing::{info, warn};
+
+use crate::config::SynthesizeEnumConfig;
+use crate::diagnostics::{Diagnostic, Diagnostics};
+use crate::model::*;
+
+/// Turn the constants matching `synth.prefix` into an enum in every
+/// (selected) partition that extracted some. Float constants can't be enum
+/// values and are left alone.
+pub(crate) fn synthesize_enum(
+    partitions: &mut [Partition],
+    synth: &SynthesizeEnumConfig,
+    diagnostics: &mut Diagnostics,
+) -> Result<()> {
+    let underlying = parse_underlying(
+        Some(synth.underlying.as_deref().unwrap_or("i32")),
+        &synth.name,
+        "synthesize_enum",
+    )?;
+
+    let mut found = false;
+    for partition in partitions.iter_mut() {
+        if synth
+            .namespace
+            .as_ref()
+            .is_some_and(|ns| *ns != partition.namespace)
+        {
+            continue;
+        }
+        let variants: Vec<EnumVariant> = partition
+            .constants
+            .iter()
+            .filter(|c| c.name.starts_with(&synth.prefix))
+            .filter_map(|c| match c.value {
+                ConstantValue::Signed(v) => Some(EnumVariant {
+                    name: c.name.clone(),
+                    signed_value: v,
+                    unsigned_value: v as u64,
+                }),
+                ConstantValue::Unsigned(v) => Some(EnumVariant {
+                    name: c.name.clone(),
+                    signed_value: v as i64,
+                    unsigned_value: v,
+                }),
+                ConstantValue::Float(_) | ConstantValue::Guid(_) => {
+                    warn!(constant = %c.name, "synthesize_enum: non-integer constant cannot be a variant");
+                    diagnostics.push(
+                        Diagnostic::new(
+                            "synthesize_enum_non_integer",
+                            format!("synthesize_enum `{}`: non-integer constant cannot be a variant", synth.name),
+                        )
+                        .name(&c.name)
+                        .namespace(&partition.namespace),
+                    );
+                    None
+                }
+            })
+            .collect();
+        if variants.is_empty() {
+            continue;
+        }
+        if partition.enums.iter().any(|e| e.name == synth.name) {
+            anyhow::bail!(
+                "synthesize_enum: `{}` already exists in partition {}",
+                synth.name,
+                partition.namespace
+            );
+        }
+        found = true;
+
+        if !synth.keep_constants {
+            let names: HashSet<&str> = variants.iter().map(|v| v.name.as_str()).collect();
+            partition
+                .constants
+                .retain(|c| !names.contains(c.name.as_str()));
+        }
+        info!(
+            name = %synth.name,
+            variants = variants.len(),
+            "synthesized enum in partition {}",
+            partition.namespace
+        );
+        partition.enums.push(EnumDef {
+            name: synth.name.clone(),
+            underlying_type: underlying.clone(),
+            variants,
+        });
+    }
+
+    if !found {
+        warn!(
+            name = %synth.name,
+            prefix = %synth.prefix,
+            "synthesize_enum: no constants match the prefix"
+        );
+        diagnostics.push(
+            Diagnostic::new(
+                "synthesize_enum_unmatched",
+                format!(
+                    "synthesize_enum: no constants match the prefix `{}`",
+                    synth.prefix
+                ),
+            )
+            .name(&synth.name),
+        );
+    }
+    Ok(())
+}
+
+/// Replace the underlying type of the enums named in `[enum_type]`,
+/// failing if a variant's value does not fit the requested type.
+pub(crate) fn apply_enum_types(
+    partitions: &mut [Partition],
+    enum_types: &BTreeMap<String, String>,
+    diagnostics: &mut Diagnostics,
+) -> Result<()> {
+    for (name, underlying) in enum_types {
+        let ty = parse_underlying(Some(underlying), name, "enum_type")?;
+        let (min, max) = ty.int_range().expect("parse_underlying returns integers");
+        let mut found = false;
+        for en in partitions
+            .iter_mut()
+            .flat_map(|p| &mut p.enums)
+            .filter(|en| en.name == *name)
+        {
+            found = true;
+            if let Some(v) = en
+                .variants
+                .iter()
+                .find(|v| !(min..=max).contains(&en.value(v)))
+            {
+                anyhow::bail!(
+                    "enum_type: `{}` = {} does not fit `{}`",
+                    v.name,
+                    en.value(v),
+                    underlying
+                );
+            }
+            en.underlying_type = ty.clone();
+        }
+        if !found {
+            warn!(name = %name, "enum_type: enum not found, skipping");
+            diagnostics.push(
+                Diagnostic::new("enum_type_unmatched", "enum_type: enum not found").name(name),
+            );
+        }
+    }
+    Ok(())
+}
+
+/// `[constant_type]`: set the integer type of the named constants.
+pub(crate) fn apply_constant_types(
+    partitions: &mut [Partition],
+    constant_types: &BTreeMap<String, String>,
+    diagnostics: &mut Diagnostics,
+) -> Result<()> {
+    for (name, ty_name) in constant_types {
+        let ty = parse_underlying(Some(ty_name), name, "constant_type")?;
+        let (min, max) = ty.int_range().expect("parse_underlying returns integers");
+        let mut found = false;
+        for c in partitions
+            .iter_mut()
+            .flat_map(|p| &mut p.constants)
+            .filter(|c| c.name == *name)
+        {
+            let Some(value) = c.int_value() else {
+                anyhow::bail!("constant_type: `{name}` is not an integer constant");
+            };
+            if !(min..=max).contains(&value) {
+                anyhow::bail!("constant_type: `{name}` = {value} does not fit `{ty_name}`");
+            }
+            found = true;
+            c.ty = Some(ty.clone());
+        }
+        if !found {
+            warn!(name = %name, "constant_type: constant not found, skipping");
+            diagnostics.push(
+                Diagnostic::new(
+                    "constant_type_unmatched",
+                    "constant_type: constant not found",
+                )
+                .name(name),
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Parse an `underlying` string (e.g. `"u8"`, `"i32"`) into a `CType`.
+/// `section` names the config table for error messages.
+pub(crate) fn parse_underlying(
+    underlying: Option<&str>,
+    type_name: &str,
+    section: &str,
+) -> Result<CType> {
+    let s = underlying
+        .ok_or_else(|| anyhow::anyhow!("{section}: `{}` requires `underlying` field", type_name))?;
+    match s {
+        "i8" => Ok(CType::I8),
+        "u8" => Ok(CType::U8),
+        "i16" => Ok(CType::I16),
+        "u16" => Ok(CType::U16),
+        "i32" => Ok(CType::I32),
+        "u32" => Ok(CType::U32),
+        "i64" => Ok(CType::I64),
+        "u64" => Ok(CType::U64),
+        other => anyhow::bail!(
+            "{section}: unsupported underlying type `{other}` for `{type_name}` \
+             (expected i8, u8, i16, u16, i32, u32, i64, u64)"
+        ),
+    }
+}
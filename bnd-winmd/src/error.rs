@@ -0,0 +1,114 @@
+//! Error type for the top-level API, one variant per pipeline stage.
+//!
+//! Lower-level modules return `anyhow::Result`; the stage a failure
+//! happened in is decided where those results reach the pipeline, and the
+//! original context chain is kept inside the variant.
+
+use std::fmt;
+
+use crate::model;
+
+/// `Result` with [`Error`] as the default error type.
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+/// A failure of the bnd-winmd pipeline, classified by stage.
+///
+/// `Display` prints the stage followed by the full context chain, e.g.
+/// `config error: loading config from zstd.toml: ...`.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum Error {
+    /// The config could not be loaded or parsed, or one of its entries
+    /// (`inject_type`, `rename`, `interface`, ...) contradicts the headers.
+    Config(anyhow::Error),
+    /// libclang failed to initialize or to parse a partition's headers.
+    Parse(anyhow::Error),
+    /// `strict` / `fail_on` matched declarations skipped during extraction.
+    Skipped(SkippedDeclsError),
+    /// A signature or field references a type that no partition extracted
+    /// and no `type_import` provides.
+    UnresolvedType(anyhow::Error),
+    /// `name_collision = "error"` found types and functions sharing a name.
+    NameCollision(anyhow::Error),
+    /// Several partitions define a struct with different layouts (and
+    /// `allow_duplicate_types` is not set).
+    TypeConflict(anyhow::Error),
+    /// `layout_mismatch = "error"` found emitted struct layouts that differ
+    /// from clang's.
+    LayoutMismatch(anyhow::Error),
+    /// Building the winmd failed.
+    Emit(anyhow::Error),
+    /// An existing winmd (for `diff` / `dump`, or a `type_import` /
+    /// `reference` input) could not be read.
+    Winmd(anyhow::Error),
+    /// An IR file (for [`emit_from_ir`](crate::emit_from_ir)) could not be
+    /// read or parsed.
+    Ir(anyhow::Error),
+    /// Writing an output file failed.
+    Io(anyhow::Error),
+    /// windows-bindgen reported warnings (see [`crate::pipeline`]).
+    Bindgen(anyhow::Error),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Config(e) => write!(f, "config error: {e:#}"),
+            Error::Parse(e) => write!(f, "parse error: {e:#}"),
+            Error::Skipped(e) => write!(f, "{e}"),
+            Error::UnresolvedType(e) => write!(f, "{e:#}"),
+            Error::NameCollision(e) => write!(f, "{e:#}"),
+            Error::TypeConflict(e) => write!(f, "{e:#}"),
+            Error::LayoutMismatch(e) => write!(f, "{e:#}"),
+            Error::Emit(e) => write!(f, "emit error: {e:#}"),
+            Error::Winmd(e) => write!(f, "winmd error: {e:#}"),
+            Error::Ir(e) => write!(f, "IR error: {e:#}"),
+            Error::Io(e) => write!(f, "I/O error: {e:#}"),
+            Error::Bindgen(e) => write!(f, "windows-bindgen: {e:#}"),
+        }
+    }
+}
+
+// No `source()`: the anyhow context chain is already part of `Display`.
+impl std::error::Error for Error {}
+
+impl From<SkippedDeclsError> for Error {
+    fn from(e: SkippedDeclsError) -> Self {
+        Error::Skipped(e)
+    }
+}
+
+/// Declarations skipped during extraction that `strict` / `fail_on` turn
+/// into an error. Returned as [`Error::Skipped`].
+#[derive(Debug)]
+pub struct SkippedDeclsError {
+    /// `(namespace, declaration)` for every counted skip.
+    pub skipped: Vec<(String, model::SkippedDecl)>,
+}
+
+impl fmt::Display for SkippedDeclsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} declaration(s) skipped during extraction (strict mode):",
+            self.skipped.len()
+        )?;
+        for (namespace, decl) in &self.skipped {
+            let reason = match &decl.reason {
+                model::SkipReason::Variadic => "variadic".to_string(),
+                model::SkipReason::Unsupported(err) => format!("unsupported: {err}"),
+                model::SkipReason::UnparsedMacro => "macro is not a numeric literal".to_string(),
+                model::SkipReason::ObjC => "Objective-C declaration".to_string(),
+                model::SkipReason::OutOfScope { file } => format!("out of scope ({file})"),
+            };
+            write!(
+                f,
+                "\n  • {} `{}` in `{namespace}` — {reason}",
+                decl.kind, decl.name
+            )?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for SkippedDeclsError {}
@@ -1,127 +1,690 @@
 //! Extraction — clang `Entity`/`Type` → intermediate model types.
 
+use std::cell::RefCell;
 use std::path::{Path, PathBuf};
 
 use anyhow::{Context, Result};
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, HashMap, HashSet};
 
 use clang::{
-    CallingConvention, Entity, EntityKind, Index, Type as ClangType, TypeKind,
+    CallingConvention, Entity, EntityKind, EvaluationResult, Index, StorageClass, TranslationUnit,
+    Type as ClangType, TypeKind,
     sonar::{self, Declaration, DefinitionValue},
 };
 use tracing::{debug, trace, warn};
 
-use crate::config::{self, PartitionConfig};
+use crate::config::{
+    BitfieldMode, Config, Int128Mode, Language, LongDoubleMode, PartitionConfig, Severity,
+    default_scratch_dir, resolve_header, wrapper_for, wrapper_name, write_wrapper,
+};
+use crate::diagnostics::{Diagnostic, Diagnostics};
 use crate::model::*;
+use crate::type_map::TypeMapHook;
+
+/// Per-partition extraction settings, from [`PartitionConfig`], plus the
+/// config's [`TypeMapHook`].
+#[derive(Debug, Clone, Copy)]
+struct Options<'a> {
+    bitfields: BitfieldMode,
+    int128: Int128Mode,
+    long_double: LongDoubleMode,
+    wchar_width: Option<usize>,
+    calling_convention: Option<CallConv>,
+    type_map: Option<&'a dyn TypeMapHook>,
+}
+
+impl<'a> Options<'a> {
+    fn new(partition: &PartitionConfig, type_map: Option<&'a dyn TypeMapHook>) -> Self {
+        Self {
+            bitfields: partition.bitfields,
+            int128: partition.int128,
+            long_double: partition.long_double,
+            wchar_width: partition.wchar_width,
+            calling_convention: partition.calling_convention,
+            type_map,
+        }
+    }
+}
 
 /// Extract all declarations from a single partition into model types.
+///
+/// Returns one partition per namespace: the partition itself, plus one
+/// per named C++ namespace (`<namespace>.<cpp namespace>`) and one per
+/// `overrides` / `namespace_from_path` target. The partition itself comes
+/// first, followed by C++ namespaces in source order — unless the
+/// partition is split by file, which puts the whole list in namespace
+/// order. Structs that are only forward-declared stay
+/// [`CType::Forward`] references.
 pub fn extract_partition(
     index: &Index,
     partition: &PartitionConfig,
     base_dir: &Path,
     include_paths: &[PathBuf],
     global_clang_args: &[String],
-    namespace_overrides: &std::collections::HashMap<String, String>,
-) -> Result<Partition> {
-    let _ = namespace_overrides; // reserved for future per-API namespace overrides
-    let header_path = partition.wrapper_header(base_dir, include_paths);
-    debug!(header = %header_path.display(), namespace = %partition.namespace, "parsing partition");
+    type_map: Option<&dyn TypeMapHook>,
+    diagnostics: &mut Diagnostics,
+) -> Result<Vec<Partition>> {
+    let scratch_dir = default_scratch_dir();
+    let input = ParseInput::new(
+        partition,
+        &scratch_dir,
+        base_dir,
+        include_paths,
+        global_clang_args,
+    )?;
+    let tu = input.parse(
+        index,
+        &partition.namespace,
+        &scratch_dir,
+        base_dir,
+        include_paths,
+    )?;
+    extract_from_tu(
+        &tu,
+        partition,
+        base_dir,
+        include_paths,
+        type_map,
+        diagnostics,
+    )
+}
+
+/// What a translation unit is parsed from: the headers it includes and the
+/// clang arguments. Partitions with equal inputs can share one parse.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub(crate) struct ParseInput {
+    /// Expanded `headers`, then the `extra_source` file, in include order.
+    pub(crate) headers: Vec<PathBuf>,
+    pub(crate) args: Vec<String>,
+}
 
-    // Build clang arguments: global args + per-partition args + -I flags.
-    // Include base_dir so that wrapper files (in /tmp/) can find headers
-    // via angle-bracket includes relative to the TOML config directory.
-    let mut all_args: Vec<String> = global_clang_args.to_vec();
-    for arg in &partition.clang_args {
-        if !all_args.contains(arg) {
-            all_args.push(arg.clone());
+impl ParseInput {
+    /// Inputs for `partition`; its `extra_source` snippets are written to
+    /// `scratch_dir`.
+    pub(crate) fn new(
+        partition: &PartitionConfig,
+        scratch_dir: &Path,
+        base_dir: &Path,
+        include_paths: &[PathBuf],
+        global_clang_args: &[String],
+    ) -> Result<Self> {
+        // Build clang arguments: global args + per-partition args + -I flags.
+        // Include base_dir so that wrapper files (in the scratch dir) can find headers
+        // via angle-bracket includes relative to the TOML config directory.
+        let mut args: Vec<String> = global_clang_args.to_vec();
+        match partition.language {
+            Language::C => {}
+            Language::Cxx => args.extend(["-x".to_string(), "c++".to_string()]),
+            Language::ObjC => args.extend(["-x".to_string(), "objective-c".to_string()]),
         }
+        for arg in partition.effective_clang_args() {
+            if !args.contains(&arg) {
+                args.push(arg);
+            }
+        }
+        let base_flag = format!("-I{}", base_dir.display());
+        if !args.contains(&base_flag) {
+            args.push(base_flag);
+        }
+        for inc in include_paths {
+            let flag = format!("-I{}", inc.display());
+            if !args.contains(&flag) {
+                args.push(flag);
+            }
+        }
+        let mut headers = partition.expanded_headers(base_dir, include_paths);
+        if let Some(content) = partition.extra_source_content() {
+            headers.push(write_wrapper(
+                scratch_dir,
+                &partition.namespace,
+                "_extra.h",
+                &content,
+            )?);
+        }
+        Ok(Self { headers, args })
     }
-    let base_flag = format!("-I{}", base_dir.display());
-    if !all_args.contains(&base_flag) {
-        all_args.push(base_flag);
+
+    /// Parse the headers; `name` names the wrapper file in `scratch_dir`
+    /// when there are several.
+    pub(crate) fn parse<'i>(
+        &self,
+        index: &'i Index,
+        name: &str,
+        scratch_dir: &Path,
+        base_dir: &Path,
+        include_paths: &[PathBuf],
+    ) -> Result<TranslationUnit<'i>> {
+        let header_path = wrapper_for(scratch_dir, name, &self.headers, base_dir, include_paths)?;
+        debug!(header = %header_path.display(), namespace = %name, "parsing partition");
+        let path = header_path
+            .to_str()
+            .with_context(|| format!("non-UTF-8 path {}", header_path.display()))?;
+        index
+            .parser(path)
+            .arguments(&self.args.iter().map(|s| s.as_str()).collect::<Vec<_>>())
+            .detailed_preprocessing_record(true)
+            .parse()
+            .map_err(|e| anyhow::anyhow!("failed to parse {}: {:?}", header_path.display(), e))
     }
-    for inc in include_paths {
-        let flag = format!("-I{}", inc.display());
-        if !all_args.contains(&flag) {
-            all_args.push(flag);
+}
+
+/// Every file `tu` read: the file it was parsed from, each file an
+/// `#include` pulled in and each file declaring something.
+pub(crate) fn files_read(tu: &TranslationUnit) -> Vec<PathBuf> {
+    let root = tu.get_entity();
+    let mut files: Vec<PathBuf> = root.get_name().map(PathBuf::from).into_iter().collect();
+    for entity in root.get_children() {
+        let included = match entity.get_kind() {
+            EntityKind::InclusionDirective => entity.get_file(),
+            _ => None,
+        };
+        let declared = entity
+            .get_location()
+            .and_then(|loc| loc.get_file_location().file);
+        for file in included.into_iter().chain(declared) {
+            files.push(file.get_path());
         }
     }
+    files.sort();
+    files.dedup();
+    files
+}
 
+/// Precompile the config's `pch` headers for parses with `args` (see [`ParseInput`]) and
+/// return the `.pch` file, named after `name` and the inputs, to pass as
+/// `-include-pch`.
+pub(crate) fn build_pch(
+    index: &Index,
+    name: &str,
+    cfg: &Config,
+    args: &[String],
+    base_dir: &Path,
+    diagnostics: &mut Diagnostics,
+) -> Result<PathBuf> {
+    let scratch_dir = cfg.resolved_scratch_dir();
+    let header_path = wrapper_for(
+        &scratch_dir,
+        &format!("{name}_pch"),
+        &cfg.pch,
+        base_dir,
+        &cfg.include_paths,
+    )?;
+    let language = args
+        .windows(2)
+        .filter(|w| w[0] == "-x")
+        .map(|w| w[1].as_str())
+        .next_back()
+        .unwrap_or("c");
+    let mut args = args.to_vec();
+    args.extend(["-x".to_string(), format!("{language}-header")]);
+    debug!(header = %header_path.display(), "building precompiled header");
+    let path = header_path
+        .to_str()
+        .with_context(|| format!("non-UTF-8 path {}", header_path.display()))?;
     let tu = index
-        .parser(header_path.to_str().unwrap())
-        .arguments(&all_args.iter().map(|s| s.as_str()).collect::<Vec<_>>())
+        .parser(path)
+        .arguments(&args)
         .detailed_preprocessing_record(true)
+        .incomplete(true)
         .parse()
         .map_err(|e| anyhow::anyhow!("failed to parse {}: {:?}", header_path.display(), e))?;
+    check_clang_diagnostics(&tu, "pch", cfg.clang_errors, diagnostics)?;
+    std::fs::create_dir_all(&scratch_dir)
+        .with_context(|| format!("creating scratch dir {}", scratch_dir.display()))?;
+    let key = format!("{}\n{}", header_path.display(), args.join("\n"));
+    let pch = scratch_dir.join(wrapper_name(name, key.as_bytes(), ".pch"));
+    // Saved under a name of its own and renamed into place, so a process
+    // sharing the scratch dir never loads a half-written PCH.
+    let tmp = pch.with_extension(format!("tmp{}", std::process::id()));
+    tu.save(&tmp)
+        .map_err(|e| anyhow::anyhow!("failed to save {}: {:?}", tmp.display(), e))?;
+    std::fs::rename(&tmp, &pch).with_context(|| format!("writing {}", pch.display()))?;
+    Ok(pch)
+}
 
+/// Report what clang diagnosed while parsing for `name` (a partition
+/// namespace, or `pch`). Warnings become `clang_warning` diagnostics;
+/// errors fail the parse with clang's messages, or are reported as
+/// `clang_error` diagnostics when `clang_errors` is lowered to `warn`.
+/// Without this, a missing include or wrong `-D` flag only shows up later
+/// as unresolved or missing types.
+pub(crate) fn check_clang_diagnostics(
+    tu: &TranslationUnit,
+    name: &str,
+    clang_errors: Severity,
+    diagnostics: &mut Diagnostics,
+) -> Result<()> {
+    use clang::diagnostic::Severity as ClangSeverity;
+
+    let mut errors = Vec::new();
+    for diag in tu.get_diagnostics() {
+        let error = match diag.get_severity() {
+            ClangSeverity::Error | ClangSeverity::Fatal => true,
+            ClangSeverity::Warning => false,
+            ClangSeverity::Ignored | ClangSeverity::Note => continue,
+        };
+        let formatted = diag.formatter().format();
+        let location = diag.get_location().get_file_location();
+        let location = location.file.map(|file| SourceLocation {
+            file: file.get_path().display().to_string(),
+            line: location.line,
+        });
+        let code = match (error, clang_errors) {
+            (false, _) => {
+                debug!(namespace = %name, "clang: {formatted}");
+                "clang_warning"
+            }
+            (true, Severity::Error) => {
+                errors.push(formatted);
+                continue;
+            }
+            (true, Severity::Warn) => {
+                warn!(namespace = %name, "clang: {formatted}");
+                "clang_error"
+            }
+            (true, Severity::Allow) => continue,
+        };
+        diagnostics.push(
+            Diagnostic::new(code, diag.get_text())
+                .namespace(name)
+                .location(location.as_ref()),
+        );
+    }
+    if !errors.is_empty() {
+        anyhow::bail!(
+            "clang reported {} error(s) parsing `{name}`:\n  {}",
+            errors.len(),
+            errors.join("\n  ")
+        );
+    }
+    Ok(())
+}
+
+/// Extract a partition's declarations from an already-parsed translation
+/// unit, which may include more headers than the partition's own (see
+/// [`ParseInput`]); the traverse filter decides what is in scope.
+pub(crate) fn extract_from_tu(
+    tu: &TranslationUnit,
+    partition: &PartitionConfig,
+    base_dir: &Path,
+    include_paths: &[PathBuf],
+    type_map: Option<&dyn TypeMapHook>,
+    diagnostics: &mut Diagnostics,
+) -> Result<Vec<Partition>> {
     // Resolve traverse files through include_paths so relative names work
-    let resolved_traverse: Vec<PathBuf> = partition
-        .traverse_files()
-        .iter()
-        .map(|t| config::resolve_header(t, base_dir, include_paths))
-        .collect();
+    // (directory entries and glob patterns are expanded first).
+    let traverse = TraverseScope::new(partition.resolved_traverse_entries(base_dir, include_paths));
+    // The `extra_source` file is matched by name, which is unique to the
+    // partition and its snippets, wherever the scratch dir is.
+    let extra_source = partition.extra_source_file_name();
     let entities = tu.get_entity().get_children();
+    let pointer_width = tu.get_target().pointer_width / 8;
+
+    let in_scope = |e: &Entity| {
+        traverse.contains(e)
+            || extra_source.as_ref().is_some_and(|extra| {
+                e.get_location()
+                    .and_then(|loc| loc.get_file_location().file)
+                    .is_some_and(|file| file.get_path().file_name() == Some(extra.as_ref()))
+            })
+    };
+
+    let scopes = match partition.language {
+        Language::C | Language::ObjC => vec![Scope {
+            namespace: partition.namespace.clone(),
+            entities,
+            mangled: HashMap::new(),
+        }],
+        Language::Cxx => {
+            let mut scopes = Vec::new();
+            collect_cxx_scopes(&entities, &partition.namespace, false, &mut scopes);
+            scopes
+        }
+    };
 
-    let in_scope = |e: &Entity| should_emit(e, &resolved_traverse, base_dir);
-
-    let structs = collect_structs(&entities, &in_scope);
-    let (enums, anon_enum_constants) = collect_enums(&entities, &in_scope);
-    let functions = collect_functions(&entities, &in_scope);
-    let typedefs = collect_typedefs(&entities, &in_scope);
-    let mut constants = collect_constants(&entities, &in_scope);
-
-    // Merge in constants extracted from anonymous enums
-    constants.extend(anon_enum_constants);
-
-    tracing::info!(
-        namespace = %partition.namespace,
-        structs = structs.len(),
-        enums = enums.len(),
-        functions = functions.len(),
-        typedefs = typedefs.len(),
-        constants = constants.len(),
-        "partition extraction complete"
+    let mut files = FileNamespaces::new(partition, base_dir, include_paths);
+    let scopes = if files.is_empty() {
+        scopes
+    } else {
+        split_scopes(scopes, &mut files)
+    };
+    for (i, o) in partition.overrides.iter().enumerate() {
+        if !files.used.contains(&i) {
+            warn!(file = %o.file.display(), "overrides: no declarations from this file, skipping");
+            diagnostics.push(
+                Diagnostic::new(
+                    "namespace_override_unmatched",
+                    format!("overrides: no declarations from `{}`", o.file.display()),
+                )
+                .namespace(&o.namespace),
+            );
+        }
+    }
+
+    anyhow::ensure!(
+        matches!(partition.wchar_width, None | Some(2 | 4)),
+        "partition {}: wchar_width must be 2 or 4",
+        partition.namespace
+    );
+    anyhow::ensure!(
+        partition.max_methods != Some(0),
+        "partition {}: max_methods must be at least 1",
+        partition.namespace
     );
+    let opts = Options::new(partition, type_map);
+    let mut partitions = Vec::with_capacity(scopes.len());
+    for scope in scopes {
+        let entities = scope.entities;
+        let mut skipped = Vec::new();
+        let (mut structs, nested_enums) = collect_structs(&entities, &in_scope, opts, &mut skipped);
+        let (mut enums, anon_enum_constants) =
+            collect_enums(&entities, &in_scope, opts, &mut skipped);
+        enums.extend(nested_enums);
+        let mut functions = collect_functions(&entities, &in_scope, opts, &mut skipped);
+        let mut typedefs = collect_typedefs(&entities, &in_scope, opts, &mut skipped, diagnostics);
+        synthesize_inline_delegates(&mut structs, &mut functions, &mut typedefs);
+        let mut constants = collect_constants(&entities, &in_scope, &mut skipped);
+        skip_objc(&entities, &in_scope, &mut skipped);
+        let guids = collect_guids(&entities, &in_scope, &constants, diagnostics);
+        constants.extend(guids);
+        let const_vars = collect_const_vars(&entities, &in_scope, &constants, opts);
+        constants.extend(const_vars);
+
+        // Merge in constants extracted from anonymous enums
+        constants.extend(anon_enum_constants);
+
+        record_skipped(&scope.namespace, &skipped, diagnostics);
+
+        // Functions with C++ linkage are exported under their mangled name.
+        for f in &mut functions {
+            if f.entry_point == f.name
+                && let Some(mangled) = scope.mangled.get(&f.name)
+            {
+                f.entry_point = mangled.clone();
+            }
+        }
+
+        tracing::info!(
+            namespace = %scope.namespace,
+            structs = structs.len(),
+            enums = enums.len(),
+            functions = functions.len(),
+            typedefs = typedefs.len(),
+            constants = constants.len(),
+            "partition extraction complete"
+        );
+
+        partitions.push(Partition {
+            namespace: scope.namespace,
+            library: partition.library.clone(),
+            library_soname: partition.library_soname.clone(),
+            delay_load: partition.delay_load,
+            structs,
+            enums,
+            functions,
+            typedefs,
+            constants,
+            interfaces: Vec::new(),
+            skipped,
+            apis_split: partition.apis_split,
+            max_methods: partition.max_methods,
+            pointer_width: Some(pointer_width),
+        });
+    }
+
+    // A C++ header usually declares into only some of its namespaces;
+    // drop the empty ones unless nothing was extracted at all.
+    if partitions.iter().any(|p| !p.is_empty()) {
+        partitions.retain(|p| !p.is_empty());
+    }
+
+    for (entry, hits) in traverse.hits() {
+        debug!(
+            namespace = %partition.namespace,
+            traverse = %entry.display(),
+            hits,
+            "traverse entry matches"
+        );
+    }
+    for entry in traverse.unmatched() {
+        warn!(
+            namespace = %partition.namespace,
+            traverse = %entry.display(),
+            "traverse entry matched no declarations — check the path"
+        );
+        diagnostics.push(
+            Diagnostic::new(
+                "traverse_unmatched",
+                format!(
+                    "traverse entry `{}` matched no declarations",
+                    entry.display()
+                ),
+            )
+            .name(entry.display().to_string())
+            .namespace(&partition.namespace),
+        );
+    }
+    Ok(partitions)
+}
+
+/// Top-level entities sharing one winmd namespace.
+struct Scope<'tu> {
+    namespace: String,
+    entities: Vec<Entity<'tu>>,
+    /// Mangled symbols of functions declared outside `extern "C"`.
+    mangled: HashMap<String, String>,
+}
+
+/// Where the declarations of one header file go.
+#[derive(Clone)]
+enum Placement {
+    /// The namespace of the scope they were found in.
+    Scope,
+    /// `<scope namespace>.<sub>`, from `namespace_from_path`.
+    Sub(String),
+    /// A namespace from the partition's `overrides`.
+    Override(String),
+}
+
+/// Per-file namespace rules of a partition: `overrides` entries first,
+/// then the directory layout when `namespace_from_path` is set.
+struct FileNamespaces {
+    dirs: Vec<PathBuf>,
+    overrides: Vec<(PathBuf, String)>,
+    /// `overrides` entries that matched a file.
+    used: HashSet<usize>,
+    cache: HashMap<String, Placement>,
+}
+
+impl FileNamespaces {
+    fn new(partition: &PartitionConfig, base_dir: &Path, include_paths: &[PathBuf]) -> Self {
+        let dirs = if partition.namespace_from_path {
+            partition.traverse_dirs(base_dir, include_paths)
+        } else {
+            Vec::new()
+        };
+        let canonical = |p: PathBuf| p.canonicalize().unwrap_or(p);
+        FileNamespaces {
+            dirs: dirs.into_iter().map(canonical).collect(),
+            overrides: partition
+                .overrides
+                .iter()
+                .map(|o| {
+                    let file = resolve_header(&o.file, base_dir, include_paths);
+                    (canonical(file), o.namespace.clone())
+                })
+                .collect(),
+            used: HashSet::new(),
+            cache: HashMap::new(),
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.dirs.is_empty() && self.overrides.is_empty()
+    }
+
+    fn place(&mut self, file: &str) -> Placement {
+        if let Some(placement) = self.cache.get(file) {
+            return placement.clone();
+        }
+        let path = Path::new(file);
+        let path = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+        let placement = if let Some(i) = self.overrides.iter().position(|(f, _)| *f == path) {
+            self.used.insert(i);
+            Placement::Override(self.overrides[i].1.clone())
+        } else if let Some(sub) = path_namespace(&path, &self.dirs) {
+            Placement::Sub(sub)
+        } else {
+            Placement::Scope
+        };
+        self.cache.insert(file.to_string(), placement.clone());
+        placement
+    }
+}
 
-    Ok(Partition {
-        namespace: partition.namespace.clone(),
-        library: partition.library.clone(),
-        structs,
-        enums,
-        functions,
-        typedefs,
-        constants,
+/// Regroup entities by the namespace [`FileNamespaces`] assigns their
+/// file. Entities without a file keep their scope.
+fn split_scopes<'tu>(scopes: Vec<Scope<'tu>>, files: &mut FileNamespaces) -> Vec<Scope<'tu>> {
+    let mut split: BTreeMap<String, Scope<'tu>> = BTreeMap::new();
+    for scope in scopes {
+        let mut groups: BTreeMap<String, Vec<Entity<'tu>>> = BTreeMap::new();
+        for entity in scope.entities {
+            let placement =
+                source_location(&entity).map_or(Placement::Scope, |loc| files.place(&loc.file));
+            let namespace = match placement {
+                Placement::Scope => scope.namespace.clone(),
+                Placement::Sub(sub) => format!("{}.{sub}", scope.namespace),
+                Placement::Override(namespace) => namespace,
+            };
+            groups.entry(namespace).or_default().push(entity);
+        }
+        for (namespace, entities) in groups {
+            match split.get_mut(&namespace) {
+                Some(target) => {
+                    target.entities.extend(entities);
+                    target.mangled.extend(scope.mangled.clone());
+                }
+                None => {
+                    split.insert(
+                        namespace.clone(),
+                        Scope {
+                            namespace,
+                            entities,
+                            mangled: scope.mangled.clone(),
+                        },
+                    );
+                }
+            }
+        }
+    }
+    debug!(namespaces = split.len(), "split partition by header file");
+    split.into_values().collect()
+}
+
+/// Dotted sub-namespace for the canonical path `file` from its directory
+/// below the first of `dirs` containing it. Characters that can't appear in a namespace
+/// segment become `_`.
+fn path_namespace(file: &Path, dirs: &[PathBuf]) -> Option<String> {
+    dirs.iter().find_map(|dir| {
+        let parent = file.strip_prefix(dir).ok()?.parent()?;
+        let segments: Vec<String> = parent
+            .components()
+            .map(|c| {
+                c.as_os_str()
+                    .to_string_lossy()
+                    .chars()
+                    .map(|ch| if ch.is_ascii_alphanumeric() { ch } else { '_' })
+                    .collect()
+            })
+            .collect();
+        (!segments.is_empty()).then(|| segments.join("."))
     })
 }
 
+/// Flatten a C++ translation unit into per-namespace scopes. `extern "C"`
+/// blocks are transparent; named namespaces open `<namespace>.<name>`;
+/// anonymous namespaces have internal linkage and are skipped.
+fn collect_cxx_scopes<'tu>(
+    entities: &[Entity<'tu>],
+    namespace: &str,
+    extern_c: bool,
+    scopes: &mut Vec<Scope<'tu>>,
+) {
+    let index = match scopes.iter().position(|s| s.namespace == namespace) {
+        Some(i) => i,
+        None => {
+            scopes.push(Scope {
+                namespace: namespace.to_string(),
+                entities: Vec::new(),
+                mangled: HashMap::new(),
+            });
+            scopes.len() - 1
+        }
+    };
+    for e in entities {
+        match e.get_kind() {
+            EntityKind::LinkageSpec => {
+                collect_cxx_scopes(&e.get_children(), namespace, true, scopes);
+            }
+            EntityKind::Namespace => match e.get_name() {
+                Some(name) if !name.is_empty() => {
+                    let nested = format!("{namespace}.{name}");
+                    collect_cxx_scopes(&e.get_children(), &nested, extern_c, scopes);
+                }
+                _ => trace!("skipping anonymous namespace"),
+            },
+            kind => {
+                let scope = &mut scopes[index];
+                if kind == EntityKind::FunctionDecl
+                    && !extern_c
+                    && let (Some(name), Some(mangled)) = (e.get_name(), e.get_mangled_name())
+                {
+                    scope.mangled.entry(name).or_insert(mangled);
+                }
+                scope.entities.push(*e);
+            }
+        }
+    }
+}
+
 // ---------------------------------------------------------------------------
 // Collection helpers — one per declaration kind
 // ---------------------------------------------------------------------------
 
 /// Collect structs via sonar, then run a supplemental pass for StructDecl
 /// entities that sonar missed (e.g. structs that only have a pointer typedef).
-fn collect_structs(entities: &[Entity], in_scope: &impl Fn(&Entity) -> bool) -> Vec<StructDef> {
+fn collect_structs(
+    entities: &[Entity],
+    in_scope: &impl Fn(&Entity) -> bool,
+    opts: Options,
+    skipped: &mut Vec<SkippedDecl>,
+) -> (Vec<StructDef>, Vec<EnumDef>) {
     let mut structs = Vec::new();
+    let mut enums = Vec::new();
     let mut seen = HashSet::new();
 
     // Primary: sonar-discovered structs (via typedef patterns)
     for decl in sonar::find_structs(entities.to_vec()) {
         if !in_scope(&decl.entity) {
-            trace_out_of_scope(&decl.entity, "struct");
+            skip_out_of_scope(&decl.entity, "struct", skipped);
             continue;
         }
         seen.insert(decl.name.clone());
-        match extract_struct(&decl) {
+        match extract_struct(&decl, opts) {
             Ok((s, nested)) => {
                 debug!(name = %s.name, fields = s.fields.len(), size = s.size, "extracted struct");
-                for ns in nested {
+                for ns in nested.structs {
                     seen.insert(ns.name.clone());
                     debug!(name = %ns.name, fields = ns.fields.len(), "  nested anonymous type");
                     structs.push(ns);
                 }
+                enums.extend(nested.enums);
                 structs.push(s);
             }
-            Err(e) => warn!(name = %decl.name, err = %e, "skipping struct"),
+            Err(e) => {
+                warn!(name = %decl.name, err = %e, "skipping struct");
+                skip_unsupported(skipped, &decl.entity, &decl.name, "struct", &e);
+            }
         }
     }
 
@@ -135,7 +698,7 @@ fn collect_structs(entities: &[Entity], in_scope: &impl Fn(&Entity) -> bool) ->
             _ => continue,
         };
         if !in_scope(entity) {
-            trace_out_of_scope(entity, "struct");
+            skip_out_of_scope(entity, "struct", skipped);
             continue;
         }
         let name = match entity.get_name() {
@@ -146,22 +709,27 @@ fn collect_structs(entities: &[Entity], in_scope: &impl Fn(&Entity) -> bool) ->
             continue;
         }
         seen.insert(name.clone());
-        match extract_struct_from_entity(entity, &name, is_union) {
+        match extract_struct_from_entity(entity, &name, is_union, opts) {
             Ok((s, nested)) => {
                 let kind = if is_union { "union" } else { "struct" };
                 debug!(name = %s.name, fields = s.fields.len(), size = s.size, "extracted {kind} (supplemental)");
-                for ns in nested {
+                for ns in nested.structs {
                     seen.insert(ns.name.clone());
                     debug!(name = %ns.name, fields = ns.fields.len(), "  nested anonymous type");
                     structs.push(ns);
                 }
+                enums.extend(nested.enums);
                 structs.push(s);
             }
-            Err(e) => warn!(name = %name, err = %e, "skipping struct/union"),
+            Err(e) => {
+                warn!(name = %name, err = %e, "skipping struct/union");
+                let kind = if is_union { "union" } else { "struct" };
+                skip_unsupported(skipped, entity, &name, kind, &e);
+            }
         }
     }
 
-    structs
+    (structs, enums)
 }
 
 /// Collect enums via sonar, then run a supplemental pass for EnumDecl
@@ -170,6 +738,8 @@ fn collect_structs(entities: &[Entity], in_scope: &impl Fn(&Entity) -> bool) ->
 fn collect_enums(
     entities: &[Entity],
     in_scope: &impl Fn(&Entity) -> bool,
+    opts: Options,
+    skipped: &mut Vec<SkippedDecl>,
 ) -> (Vec<EnumDef>, Vec<ConstantDef>) {
     let mut enums = Vec::new();
     let mut anon_constants = Vec::new();
@@ -178,7 +748,7 @@ fn collect_enums(
     // Primary: sonar-discovered enums
     for decl in sonar::find_enums(entities.to_vec()) {
         if !in_scope(&decl.entity) {
-            trace_out_of_scope(&decl.entity, "enum");
+            skip_out_of_scope(&decl.entity, "enum", skipped);
             continue;
         }
         // Detect anonymous enums (e.g. `enum { DT_UNKNOWN = 0, ... }`).
@@ -186,7 +756,7 @@ fn collect_enums(
         // These are just collections of integer constants in C — emit their
         // variants as standalone ConstantDef entries instead of a named enum.
         if decl.entity.is_anonymous() || decl.name.contains("(unnamed") {
-            match extract_enum(&decl) {
+            match extract_enum(&decl, opts) {
                 Ok(en) => {
                     debug!(
                         name = %decl.name,
@@ -200,22 +770,28 @@ fn collect_enums(
                             ConstantValue::Unsigned(variant.unsigned_value)
                         };
                         anon_constants.push(ConstantDef {
-                            name: variant.name,
-                            value,
+                            ty: Some(en.underlying_type.clone()),
+                            ..ConstantDef::new(variant.name, value)
                         });
                     }
                 }
-                Err(e) => warn!(name = %decl.name, err = %e, "skipping anonymous enum"),
+                Err(e) => {
+                    warn!(name = %decl.name, err = %e, "skipping anonymous enum");
+                    skip_unsupported(skipped, &decl.entity, &decl.name, "enum", &e);
+                }
             }
             continue;
         }
         seen.insert(decl.name.clone());
-        match extract_enum(&decl) {
+        match extract_enum(&decl, opts) {
             Ok(en) => {
                 debug!(name = %en.name, variants = en.variants.len(), "extracted enum");
                 enums.push(en);
             }
-            Err(e) => warn!(name = %decl.name, err = %e, "skipping enum"),
+            Err(e) => {
+                warn!(name = %decl.name, err = %e, "skipping enum");
+                skip_unsupported(skipped, &decl.entity, &decl.name, "enum", &e);
+            }
         }
     }
 
@@ -230,7 +806,7 @@ fn collect_enums(
             if !entity.is_definition() {
                 // forward decl — silent, not interesting
             } else {
-                trace_out_of_scope(entity, "enum");
+                skip_out_of_scope(entity, "enum", skipped);
             }
             continue;
         }
@@ -242,12 +818,15 @@ fn collect_enums(
             continue;
         }
         seen.insert(name.clone());
-        match extract_enum_from_entity(entity, &name) {
+        match extract_enum_from_entity(entity, &name, opts) {
             Ok(en) => {
                 debug!(name = %en.name, variants = en.variants.len(), "extracted enum (supplemental)");
                 enums.push(en);
             }
-            Err(e) => warn!(name = %name, err = %e, "skipping enum"),
+            Err(e) => {
+                warn!(name = %name, err = %e, "skipping enum");
+                skip_unsupported(skipped, entity, &name, "enum", &e);
+            }
         }
     }
 
@@ -255,31 +834,61 @@ fn collect_enums(
 }
 
 /// Collect functions via sonar.
-fn collect_functions(entities: &[Entity], in_scope: &impl Fn(&Entity) -> bool) -> Vec<FunctionDef> {
-    let mut functions = Vec::new();
+fn collect_functions(
+    entities: &[Entity],
+    in_scope: &impl Fn(&Entity) -> bool,
+    opts: Options,
+    skipped: &mut Vec<SkippedDecl>,
+) -> Vec<FunctionDef> {
+    let mut functions: Vec<FunctionDef> = Vec::new();
     let mut seen = HashSet::new();
     for decl in sonar::find_functions(entities.to_vec()) {
         if !in_scope(&decl.entity) {
-            trace_out_of_scope(&decl.entity, "function");
+            skip_out_of_scope(&decl.entity, "function", skipped);
             continue;
         }
         // Skip variadic functions — P/Invoke metadata cannot represent `...`
         if decl.entity.is_variadic() {
             warn!(name = %decl.name, "skipping variadic function");
+            skipped.push(SkippedDecl {
+                name: decl.name.clone(),
+                kind: "function".into(),
+                reason: SkipReason::Variadic,
+                location: source_location(&decl.entity),
+            });
             continue;
         }
-        match extract_function(&decl) {
+        match extract_function(&decl, opts) {
             Ok(f) => {
                 // Deduplicate by name — glibc __REDIRECT macros can produce
                 // multiple declarations of the same function (e.g. lockf / lockf64).
+                // A redirected redeclaration carries the real symbol, so its
+                // entry point wins over a plain one, and an inline definition
+                // after its prototype keeps the definition's C spelling.
                 if !seen.insert(f.name.clone()) {
-                    trace!(name = %f.name, "skipping duplicate function");
+                    let prev = functions.iter_mut().find(|p| p.name == f.name);
+                    match prev {
+                        Some(prev) if f.entry_point != f.name && prev.entry_point == prev.name => {
+                            debug!(name = %f.name, entry_point = %f.entry_point, "redirected redeclaration");
+                            prev.entry_point = f.entry_point;
+                        }
+                        Some(prev)
+                            if f.inline_prototype.is_some() && prev.inline_prototype.is_none() =>
+                        {
+                            debug!(name = %f.name, "inline definition after prototype");
+                            prev.inline_prototype = f.inline_prototype;
+                        }
+                        _ => trace!(name = %f.name, "skipping duplicate function"),
+                    }
                     continue;
                 }
                 debug!(name = %f.name, params = f.params.len(), "extracted function");
                 functions.push(f);
             }
-            Err(e) => warn!(name = %decl.name, err = %e, "skipping function"),
+            Err(e) => {
+                warn!(name = %decl.name, err = %e, "skipping function");
+                skip_unsupported(skipped, &decl.entity, &decl.name, "function", &e);
+            }
         }
     }
     functions
@@ -287,7 +896,13 @@ fn collect_functions(entities: &[Entity], in_scope: &impl Fn(&Entity) -> bool) -
 
 /// Collect typedefs via custom discovery (not sonar, which drops typedef-to-
 /// typedef aliases like `typedef Byte Bytef`).
-fn collect_typedefs(entities: &[Entity], in_scope: &impl Fn(&Entity) -> bool) -> Vec<TypedefDef> {
+fn collect_typedefs(
+    entities: &[Entity],
+    in_scope: &impl Fn(&Entity) -> bool,
+    opts: Options,
+    skipped: &mut Vec<SkippedDecl>,
+    diagnostics: &mut Diagnostics,
+) -> Vec<TypedefDef> {
     let mut typedefs = Vec::new();
     let mut seen = HashSet::new();
     for entity in entities {
@@ -295,7 +910,7 @@ fn collect_typedefs(entities: &[Entity], in_scope: &impl Fn(&Entity) -> bool) ->
             continue;
         }
         if !in_scope(entity) {
-            trace_out_of_scope(entity, "typedef");
+            skip_out_of_scope(entity, "typedef", skipped);
             continue;
         }
         let name = match entity.get_name() {
@@ -319,21 +934,38 @@ fn collect_typedefs(entities: &[Entity], in_scope: &impl Fn(&Entity) -> bool) ->
         // recursive `pub type bool = bool;`).
         if is_primitive_name(&name) {
             trace!(name = %name, "skipping typedef that shadows a Rust primitive");
+            diagnostics.push(
+                Diagnostic::new(
+                    "typedef_shadows_primitive",
+                    "skipped typedef that shadows a Rust primitive",
+                )
+                .name(&name)
+                .location(source_location(entity).as_ref()),
+            );
             continue;
         }
-        match extract_typedef_from_entity(entity, &name) {
+        match extract_typedef_from_entity(entity, &name, opts) {
             Ok(td) => {
                 debug!(name = %td.name, "extracted typedef");
                 typedefs.push(td);
             }
-            Err(e) => warn!(name = %name, err = %e, "skipping typedef"),
+            Err(e) => {
+                warn!(name = %name, err = %e, "skipping typedef");
+                skip_unsupported(skipped, entity, &name, "typedef", &e);
+            }
         }
     }
     typedefs
 }
 
 /// Collect `#define` constants via sonar + supplemental hex parsing.
-fn collect_constants(entities: &[Entity], in_scope: &impl Fn(&Entity) -> bool) -> Vec<ConstantDef> {
+/// Object-like macros with a body that is not a number are recorded in
+/// `skipped`.
+fn collect_constants(
+    entities: &[Entity],
+    in_scope: &impl Fn(&Entity) -> bool,
+    skipped: &mut Vec<SkippedDecl>,
+) -> Vec<ConstantDef> {
     let mut constants = Vec::new();
     let mut seen = HashSet::new();
 
@@ -342,6 +974,13 @@ fn collect_constants(entities: &[Entity], in_scope: &impl Fn(&Entity) -> bool) -
         if !in_scope(&def.entity) {
             continue;
         }
+        let tokens = spellings(&def.entity);
+        let literal = macro_literal(&tokens);
+        // sonar reads `0400` as decimal 400; leave octal to the
+        // supplemental pass below.
+        if matches!(def.value, DefinitionValue::Integer(..)) && literal.non_decimal {
+            continue;
+        }
         let value = match def.value {
             DefinitionValue::Integer(negated, val) => {
                 if negated {
@@ -356,14 +995,17 @@ fn collect_constants(entities: &[Entity], in_scope: &impl Fn(&Entity) -> bool) -
         };
         debug!(name = %def.name, "extracted #define constant");
         seen.insert(def.name.clone());
+        let spelling = macro_spelling(&tokens, &value);
         constants.push(ConstantDef {
-            name: def.name,
-            value,
+            literal,
+            spelling,
+            ..ConstantDef::new(def.name, value)
         });
     }
 
-    // Supplemental: hex constants that sonar's u64::from_str misses.
-    // sonar only parses decimal; `#define PROT_READ 0x1` is silently skipped.
+    // Supplemental: constants that sonar's u64::from_str misses. sonar only
+    // parses decimal; `#define PROT_READ 0x1`, `#define SEP ':'` and
+    // `#define SIG_ERR ((__sighandler_t) -1)` are evaluated here.
     for entity in entities {
         if entity.get_kind() != EntityKind::MacroDefinition {
             continue;
@@ -385,24 +1027,35 @@ fn collect_constants(entities: &[Entity], in_scope: &impl Fn(&Entity) -> bool) -
             if tokens.last().is_some_and(|t| t == "#") {
                 tokens.pop();
             }
-            let (negated, number) = if tokens.len() == 2 {
-                (false, &tokens[1])
-            } else if tokens.len() == 3 && tokens[1] == "-" {
-                (true, &tokens[2])
+            let parsed = if entity.is_function_like_macro() {
+                None
             } else {
-                continue;
+                eval_macro(&tokens[1..])
             };
-            if let Some(val) = parse_hex_or_suffixed_int(number) {
-                let value = if negated {
-                    ConstantValue::Signed(-(val as i64))
-                } else if val <= i64::MAX as u64 {
-                    ConstantValue::Signed(val as i64)
-                } else {
-                    ConstantValue::Unsigned(val)
+            if let Some(parsed) = parsed {
+                let value = match u64::try_from(parsed.value) {
+                    Ok(v) if v > i64::MAX as u64 => ConstantValue::Unsigned(v),
+                    _ => ConstantValue::Signed(parsed.value as i64),
                 };
-                debug!(name = %name, "extracted #define hex constant");
+                debug!(name = %name, "extracted #define expression constant");
                 seen.insert(name.clone());
-                constants.push(ConstantDef { name, value });
+                let spelling = macro_spelling(&tokens, &value);
+                constants.push(ConstantDef {
+                    literal: parsed.literal,
+                    ty: parsed.ty,
+                    spelling,
+                    ..ConstantDef::new(name, value)
+                });
+            } else if tokens.len() > 1 && !entity.is_function_like_macro() {
+                // Include guards and flag macros have no body; anything
+                // else is an expression, alias or string we can't represent.
+                trace!(name = %name, "skipping unparsed #define");
+                skipped.push(SkippedDecl {
+                    name,
+                    kind: "macro".into(),
+                    reason: SkipReason::UnparsedMacro,
+                    location: source_location(entity),
+                });
             }
         }
     }
@@ -410,14 +1063,348 @@ fn collect_constants(entities: &[Entity], in_scope: &impl Fn(&Entity) -> bool) -
     constants
 }
 
-/// Parse a hex literal (`0x1F`) or a suffixed integer (`1U`, `0x10UL`, etc.)
-/// that `u64::from_str` can't handle. Returns None if not parseable.
+/// Collect GUID constants from `DEFINE_GUID(name, l, w1, w2, b1..b8)`
+/// invocations and `const GUID name = { ... }` definitions. `existing`
+/// constants win on a name clash.
+fn collect_guids(
+    entities: &[Entity],
+    in_scope: &impl Fn(&Entity) -> bool,
+    existing: &[ConstantDef],
+    diagnostics: &mut Diagnostics,
+) -> Vec<ConstantDef> {
+    let mut guids = Vec::new();
+    let mut seen: HashSet<String> = existing.iter().map(|c| c.name.clone()).collect();
+
+    for entity in entities {
+        let (name, values) = match entity.get_kind() {
+            EntityKind::MacroExpansion if entity.get_name().as_deref() == Some("DEFINE_GUID") => {
+                // DEFINE_GUID ( name , values... )
+                let tokens = spellings(entity);
+                match tokens.get(2) {
+                    Some(name) if tokens.get(1).is_some_and(|t| t == "(") => {
+                        (name.clone(), guid_values(&tokens[3..]))
+                    }
+                    _ => continue,
+                }
+            }
+            EntityKind::VarDecl if is_guid_type(entity) => {
+                let Some(name) = entity.get_name() else {
+                    continue;
+                };
+                let tokens = spellings(entity);
+                let Some(eq) = tokens.iter().position(|t| t == "=") else {
+                    // `extern const GUID IID_X;` — defined elsewhere.
+                    continue;
+                };
+                (name, guid_values(&tokens[eq + 1..]))
+            }
+            _ => continue,
+        };
+        if !in_scope(entity) || seen.contains(&name) {
+            continue;
+        }
+        let Some(guid) = values.as_deref().and_then(Guid::from_values) else {
+            warn!(name = %name, "skipping GUID with unrecognized initializer");
+            diagnostics.push(
+                Diagnostic::new("guid_unrecognized", "GUID initializer not recognized")
+                    .name(&name)
+                    .location(source_location(entity).as_ref()),
+            );
+            continue;
+        };
+        debug!(name = %name, %guid, "extracted GUID constant");
+        seen.insert(name.clone());
+        guids.push(ConstantDef::new(name, ConstantValue::Guid(guid)));
+    }
+    guids
+}
+
+/// Collect file-scope `const` variables with constant initializers
+/// (`static const int FOO = 3;`, `constexpr double BAR = 1.5;`) as
+/// constants of the variable's type. `existing` constants win on a name
+/// clash.
+fn collect_const_vars(
+    entities: &[Entity],
+    in_scope: &impl Fn(&Entity) -> bool,
+    existing: &[ConstantDef],
+    opts: Options,
+) -> Vec<ConstantDef> {
+    let mut vars = Vec::new();
+    let mut seen: HashSet<String> = existing.iter().map(|c| c.name.clone()).collect();
+
+    for entity in entities {
+        if entity.get_kind() != EntityKind::VarDecl || !in_scope(entity) {
+            continue;
+        }
+        let Some(name) = entity.get_name() else {
+            continue;
+        };
+        let Some(var_type) = entity.get_type() else {
+            continue;
+        };
+        if !var_type.is_const_qualified() || seen.contains(&name) {
+            continue;
+        }
+        // Through typedefs: `const uint32_t` is a `u32` constant.
+        let Ok(ty) = map_clang_type(&var_type.get_canonical_type(), opts) else {
+            continue;
+        };
+        let value = match entity.evaluate() {
+            Some(EvaluationResult::SignedInteger(v)) => ConstantValue::Signed(v),
+            Some(EvaluationResult::UnsignedInteger(v)) => ConstantValue::Unsigned(v),
+            Some(EvaluationResult::Float(v)) => ConstantValue::Float(v),
+            // Strings, pointers, aggregates, or no initializer in this
+            // translation unit (`extern const int X;`).
+            _ => continue,
+        };
+        let ty = match (&value, ty.int_range()) {
+            (ConstantValue::Float(_), _) => None,
+            (_, Some(_)) => Some(ty),
+            // `bool`, enums, pointers.
+            (_, None) => continue,
+        };
+        debug!(name = %name, "extracted const variable");
+        seen.insert(name.clone());
+        vars.push(ConstantDef {
+            ty,
+            ..ConstantDef::new(name, value)
+        });
+    }
+    vars
+}
+
+fn spellings(entity: &Entity) -> Vec<String> {
+    entity
+        .get_range()
+        .map(|r| r.tokenize().iter().map(|t| t.get_spelling()).collect())
+        .unwrap_or_default()
+}
+
+/// Integer literals of a GUID initializer, ignoring braces and commas.
+/// `None` if anything else (a cast, an identifier) appears.
+fn guid_values(tokens: &[String]) -> Option<Vec<u64>> {
+    tokens
+        .iter()
+        .filter(|t| !matches!(t.as_str(), "," | "{" | "}" | "(" | ")" | ";"))
+        .map(|t| parse_hex_or_suffixed_int(t))
+        .collect()
+}
+
+/// `true` if the variable's type is (a typedef of) `GUID` / `struct _GUID`.
+fn is_guid_type(entity: &Entity) -> bool {
+    entity
+        .get_type()
+        .and_then(|t| t.get_canonical_type().get_declaration())
+        .and_then(|d| d.get_name())
+        .is_some_and(|n| n == "GUID" || n == "_GUID")
+}
+
+/// A `#define` body evaluated by [`eval_macro`].
+struct MacroValue {
+    value: i128,
+    /// Spelling of the innermost literal.
+    literal: IntLiteral,
+    /// Integer type of the outermost cast, or of an unsigned operand of
+    /// `-`/`~`, if any.
+    ty: Option<CType>,
+}
+
+/// Evaluate a `#define` body made of one integer or character literal
+/// under parentheses, unary `-`, `+`, `~` and casts: `(-1)`, `':'`,
+/// `((unsigned char) ~0)`. A cast to an integer type wraps the value and
+/// fixes the constant's type, as does an unsigned literal under an
+/// operator (`~0U` is `u32::MAX`); other casts (pointers, typedef names
+/// such as `(__sighandler_t) -1`) keep the integer value. `None` for
+/// anything else.
+fn eval_macro(tokens: &[String]) -> Option<MacroValue> {
+    let mut pos = 0;
+    let value = eval_unary(tokens, &mut pos)?;
+    (pos == tokens.len()).then_some(value)
+}
+
+fn eval_unary(tokens: &[String], pos: &mut usize) -> Option<MacroValue> {
+    let token = tokens.get(*pos)?.as_str();
+    *pos += 1;
+    match token {
+        "-" | "+" | "~" => {
+            let mut operand = eval_unary(tokens, pos)?;
+            // Without a cast the operand has its literal's own type, which
+            // only matters when it is unsigned (`~0U`, `-1UL`).
+            if operand.ty.is_none() {
+                let ty = operand.literal.c_type(operand.value);
+                operand.ty = ty
+                    .int_range()
+                    .is_some_and(|(min, _)| min == 0)
+                    .then_some(ty);
+            }
+            operand.value = match token {
+                "-" => -operand.value,
+                "+" => operand.value,
+                _ => !operand.value,
+            };
+            // The operand of an operator is promoted: types narrower than
+            // `int` become `int`, wider ones wrap (`-(unsigned) 1`).
+            if let Some((min, max)) = operand.ty.as_ref().and_then(CType::int_range) {
+                if max < i128::from(i32::MAX) {
+                    operand.ty = None;
+                } else {
+                    operand.value = wrap_into(operand.value, min, max);
+                }
+            }
+            Some(operand)
+        }
+        "(" => {
+            if let Some(close) = cast_end(tokens, *pos) {
+                let ty = cast_type(&tokens[*pos..close]);
+                *pos = close + 1;
+                let mut operand = eval_unary(tokens, pos)?;
+                if let Some(ty) = ty {
+                    let (min, max) = ty.int_range().expect("cast_type returns integers");
+                    operand.value = wrap_into(operand.value, min, max);
+                    operand.ty = Some(ty);
+                }
+                return Some(operand);
+            }
+            let inner = eval_unary(tokens, pos)?;
+            if tokens.get(*pos)? != ")" {
+                return None;
+            }
+            *pos += 1;
+            Some(inner)
+        }
+        _ => {
+            if let Some(c) = parse_char_literal(token) {
+                return Some(MacroValue {
+                    value: c.into(),
+                    literal: IntLiteral::default(),
+                    ty: None,
+                });
+            }
+            Some(MacroValue {
+                value: parse_hex_or_suffixed_int(token)?.into(),
+                literal: IntLiteral::parse(token)?,
+                ty: None,
+            })
+        }
+    }
+}
+
+/// Index of the `)` closing a cast whose type starts at `start`: type
+/// keywords, identifiers and `*`, followed by the start of an operand.
+fn cast_end(tokens: &[String], start: usize) -> Option<usize> {
+    let close = start + tokens[start..].iter().position(|t| t == ")")?;
+    let is_type = close > start
+        && tokens[start..close]
+            .iter()
+            .all(|t| t == "*" || t.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_'));
+    let operand_follows = tokens.get(close + 1).is_some_and(|t| {
+        matches!(t.as_str(), "(" | "-" | "+" | "~")
+            || t.starts_with(|c: char| c.is_ascii_digit() || c == '\'')
+    });
+    (is_type && operand_follows).then_some(close)
+}
+
+/// The integer type a cast's type tokens name, for the common spellings
+/// (LP64). `None` for pointers and other types.
+fn cast_type(tokens: &[String]) -> Option<CType> {
+    let spelled: Vec<&str> = tokens
+        .iter()
+        .map(String::as_str)
+        .filter(|t| !matches!(*t, "const" | "volatile" | "int") || tokens.len() == 1)
+        .collect();
+    Some(match spelled.as_slice() {
+        ["char"] | ["signed", "char"] | ["int8_t"] => CType::I8,
+        ["unsigned", "char"] | ["uint8_t"] => CType::U8,
+        ["short"] | ["signed", "short"] | ["int16_t"] => CType::I16,
+        ["unsigned", "short"] | ["uint16_t"] => CType::U16,
+        ["int"] | ["signed"] | [] | ["int32_t"] => CType::I32,
+        ["unsigned"] | ["uint32_t"] => CType::U32,
+        ["long"] | ["long", "long"] | ["signed", "long"] | ["signed", "long", "long"] => CType::I64,
+        ["int64_t"] | ["ssize_t"] | ["intptr_t"] | ["ptrdiff_t"] => CType::I64,
+        ["unsigned", "long"] | ["unsigned", "long", "long"] => CType::U64,
+        ["uint64_t"] | ["size_t"] | ["uintptr_t"] => CType::U64,
+        _ => return None,
+    })
+}
+
+/// `value` converted to an integer type with range `min..=max`, wrapping
+/// like a C conversion.
+fn wrap_into(value: i128, min: i128, max: i128) -> i128 {
+    let span = max - min + 1;
+    (value - min).rem_euclid(span) + min
+}
+
+/// The value of a C character literal (`'a'`, `'\n'`, `'\x41'`,
+/// `'\101'`); `None` for anything else, including multi-char literals.
+fn parse_char_literal(token: &str) -> Option<u32> {
+    let body = token.strip_prefix('\'')?.strip_suffix('\'')?;
+    let Some(escape) = body.strip_prefix('\\') else {
+        let mut chars = body.chars();
+        return match (chars.next(), chars.next()) {
+            (Some(c), None) => Some(c as u32),
+            _ => None,
+        };
+    };
+    Some(match escape {
+        "n" => 0x0a,
+        "t" => 0x09,
+        "r" => 0x0d,
+        "a" => 0x07,
+        "b" => 0x08,
+        "f" => 0x0c,
+        "v" => 0x0b,
+        "\\" | "'" | "\"" | "?" => escape.chars().next()? as u32,
+        _ => match escape.strip_prefix('x') {
+            Some(hex) => u32::from_str_radix(hex, 16).ok()?,
+            None if escape.len() <= 3 => u32::from_str_radix(escape, 8).ok()?,
+            None => return None,
+        },
+    })
+}
+
+/// Suffix and radix of the integer literal in a `#define`'s tokens (the
+/// macro name first).
+fn macro_literal(tokens: &[String]) -> IntLiteral {
+    tokens
+        .iter()
+        .skip(1)
+        .rev()
+        .find_map(|t| IntLiteral::parse(t))
+        .unwrap_or_default()
+}
+
+/// A `#define` body's text (the macro name first in `tokens`), unless it
+/// just spells `value` in decimal.
+fn macro_spelling(tokens: &[String], value: &ConstantValue) -> Option<String> {
+    let mut text = String::new();
+    let word = |t: &str| t.starts_with(|c: char| c.is_ascii_alphanumeric() || c == '_');
+    for (i, token) in tokens.iter().enumerate().skip(1) {
+        // Space between words, and after a cast's `)`.
+        if i > 1 && word(token) && (word(&tokens[i - 1]) || tokens[i - 1] == ")") {
+            text.push(' ');
+        }
+        text.push_str(token);
+    }
+    let decimal = match value {
+        ConstantValue::Signed(v) => v.to_string(),
+        ConstantValue::Unsigned(v) => v.to_string(),
+        ConstantValue::Float(_) | ConstantValue::Guid(_) => return None,
+    };
+    (text != decimal).then_some(text)
+}
+
+/// Parse a hex (`0x1F`), octal (`0755`) or binary (`0b101`) literal or a
+/// suffixed integer (`1U`, `0x10UL`, etc.) that `u64::from_str` can't
+/// handle, ignoring `'` digit separators. Returns None if not parseable.
 fn parse_hex_or_suffixed_int(s: &str) -> Option<u64> {
     // Strip trailing integer suffixes: U, L, LL, UL, ULL (case-insensitive)
     let s = s.trim_end_matches(['u', 'U', 'l', 'L']);
+    let s = &s.replace('\'', "");
 
     if let Some(hex) = s.strip_prefix("0x").or_else(|| s.strip_prefix("0X")) {
         u64::from_str_radix(hex, 16).ok()
+    } else if let Some(binary) = s.strip_prefix("0b").or_else(|| s.strip_prefix("0B")) {
+        u64::from_str_radix(binary, 2).ok()
     } else if let Some(octal) = s.strip_prefix("0") {
         if octal.is_empty() {
             Some(0) // "0" with suffixes stripped
@@ -436,21 +1423,36 @@ fn parse_hex_or_suffixed_int(s: &str) -> Option<u64> {
 // Struct extraction
 // ---------------------------------------------------------------------------
 
-fn extract_struct(decl: &Declaration) -> Result<(StructDef, Vec<StructDef>)> {
-    extract_struct_from_entity(&decl.entity, &decl.name, false)
+/// Synthetic types extracted from a struct's anonymous members.
+#[derive(Default)]
+struct NestedTypes {
+    structs: Vec<StructDef>,
+    enums: Vec<EnumDef>,
+}
+
+impl NestedTypes {
+    fn append(&mut self, other: &mut NestedTypes) {
+        self.structs.append(&mut other.structs);
+        self.enums.append(&mut other.enums);
+    }
+}
+
+fn extract_struct(decl: &Declaration, opts: Options) -> Result<(StructDef, NestedTypes)> {
+    extract_struct_from_entity(&decl.entity, &decl.name, false, opts)
 }
 
 fn extract_struct_from_entity(
     entity: &Entity,
     name: &str,
     is_union: bool,
-) -> Result<(StructDef, Vec<StructDef>)> {
+    opts: Options,
+) -> Result<(StructDef, NestedTypes)> {
     let ty = entity.get_type().context("struct has no type")?;
     let size = ty.get_sizeof().unwrap_or(0);
     let align = ty.get_alignof().unwrap_or(0);
 
     let mut fields = Vec::new();
-    let mut nested_types = Vec::new();
+    let mut nested_types = NestedTypes::default();
     let mut anon_counter = 0u32;
     // Parallel vec: clang byte-offset for each field pushed into `fields`.
     // Used after bitfield flattening to insert inter-field alignment padding.
@@ -502,7 +1504,7 @@ fn extract_struct_from_entity(
                 let is_nested_union = child.get_kind() == EntityKind::UnionDecl;
                 let synthetic_name = format!("{name}__anon_{anon_counter}");
                 anon_counter += 1;
-                match extract_struct_from_entity(child, &synthetic_name, is_nested_union) {
+                match extract_struct_from_entity(child, &synthetic_name, is_nested_union, opts) {
                     Ok((nested, mut more)) => {
                         let kind = if is_nested_union { "union" } else { "struct" };
                         debug!(
@@ -519,12 +1521,15 @@ fn extract_struct_from_entity(
                             ty: ctype,
                             bitfield_width: None,
                             bitfield_offset: None,
+                            offset: None,
+                            size: Some(nested.size),
+                            native_bitfields: Vec::new(),
                         });
                         // Anonymous members don't have a FieldDecl with
                         // get_offset_of_field(); offset unknown.
                         field_offsets.push(None);
                         field_sizes.push(nested.size);
-                        nested_types.push(nested);
+                        nested_types.structs.push(nested);
                         nested_types.append(&mut more);
                     }
                     Err(e) => {
@@ -548,12 +1553,22 @@ fn extract_struct_from_entity(
         // Check for anonymous record type (unnamed struct/union used as a field type),
         // including the case where it appears as an array element type
         // (e.g. `struct { ... } pool_map[N]`).
-        let ctype =
-            match try_extract_anonymous_field(&field_type, name, &field_name, &mut nested_types) {
-                Some(ctype) => ctype,
-                None => map_clang_type(&field_type)
-                    .with_context(|| format!("unsupported type for field '{}'", field_name))?,
-            };
+        let anonymous =
+            try_extract_anonymous_field(&field_type, name, &field_name, opts, &mut nested_types)
+                .or_else(|| {
+                    try_extract_anonymous_enum(
+                        &field_type,
+                        name,
+                        &field_name,
+                        opts,
+                        &mut nested_types,
+                    )
+                });
+        let ctype = match anonymous {
+            Some(ctype) => ctype,
+            None => map_clang_type(&field_type, opts)
+                .with_context(|| format!("unsupported type for field '{}'", field_name))?,
+        };
 
         let bitfield_width = if child.is_bit_field() {
             child.get_bit_field_width()
@@ -581,15 +1596,24 @@ fn extract_struct_from_entity(
             ty: ctype,
             bitfield_width,
             bitfield_offset,
+            offset: clang_offset,
+            size: Some(clang_field_size),
+            native_bitfields: Vec::new(),
         });
     }
 
-    // Flatten bitfield fields: replace each bitfield group with a single
-    // integer field sized to cover the group's total bit span. Adjacent
-    // bitfields that pack into the same storage unit (determined by
-    // bitfield_offset continuity) are merged into one field.
+    // Replace bitfields with plain integer fields: either one per run of
+    // contiguous bitfields (flatten) or one per C storage unit carrying
+    // NativeBitfield descriptions (storage).
     if !is_union {
-        fields = flatten_bitfields(fields, name, &mut field_offsets, &mut field_sizes);
+        fields = match opts.bitfields {
+            BitfieldMode::Flatten => {
+                flatten_bitfields(fields, name, &mut field_offsets, &mut field_sizes)
+            }
+            BitfieldMode::Storage => {
+                storage_bitfields(fields, name, &mut field_offsets, &mut field_sizes)
+            }
+        };
     }
 
     // Insert inter-field and trailing padding based on clang's actual
@@ -602,11 +1626,31 @@ fn extract_struct_from_entity(
             insert_alignment_padding(fields, &field_offsets, &field_sizes, &children, size, name);
     }
 
+    // A struct aligned below its most-aligned member was packed
+    // (`#pragma pack(n)` / `__attribute__((packed))`); record the packing
+    // explicitly rather than inferring it from `align` at emit time.
+    let natural_align = children
+        .iter()
+        .filter(|c| match c.get_kind() {
+            EntityKind::FieldDecl => true,
+            // C11 anonymous members; named nested decls also have a FieldDecl.
+            EntityKind::StructDecl | EntityKind::UnionDecl => c.is_anonymous(),
+            _ => false,
+        })
+        .filter_map(|c| c.get_type()?.get_alignof().ok())
+        .max()
+        .unwrap_or(0);
+    let packing = (align < natural_align).then_some(align);
+    if let Some(pack) = packing {
+        debug!(name = %name, pack, natural_align, "packed struct");
+    }
+
     Ok((
         StructDef {
             name: name.to_string(),
             size,
             align,
+            packing,
             fields,
             is_union,
         },
@@ -658,9 +1702,35 @@ fn flatten_bitfields(
         let group_end = last.bitfield_offset.unwrap_or(0) + last.bitfield_width.unwrap_or(0);
         let total_bits = group_end - group_start;
 
-        let (name, ty) = if group.len() == 1 {
+        // The covering integer must start on its own alignment, or
+        // `repr(C)` would move it (`char c; int a : 12;` puts `a` at byte
+        // 1); such groups, and any wider than a u64, keep their bytes as a
+        // `[u8; N]` at clang's offset instead.
+        let start = group_start / 8;
+        let bytes = group_end.div_ceil(8) - start;
+        let int_ty = smallest_int_for_bits(bytes * 8);
+        let int_size = storage_align(&int_ty);
+        let (ty, merged_size) = if start % int_size == 0 && bytes <= int_size {
+            (int_ty, int_size)
+        } else {
+            debug!(
+                struct_name = %struct_name,
+                offset = start,
+                bytes,
+                "misaligned bitfield group, using byte array storage"
+            );
+            (
+                CType::Array {
+                    element: Box::new(CType::U8),
+                    len: bytes,
+                },
+                bytes,
+            )
+        };
+
+        let name = if group.len() == 1 {
             // Solo bitfield: keep original name, replace type.
-            (first.name.clone(), smallest_int_for_bits(total_bits))
+            first.name.clone()
         } else {
             // Merged group: synthetic name, covering type.
             let names: Vec<&str> = group.iter().map(|(_, f)| f.name.as_str()).collect();
@@ -670,17 +1740,7 @@ fn flatten_bitfields(
                 total_bits,
                 "merged adjacent bitfield group"
             );
-            (
-                format!("_bitfield_{}", *group_index),
-                smallest_int_for_bits(total_bits),
-            )
-        };
-        let merged_size = match &ty {
-            CType::U8 => 1,
-            CType::U16 => 2,
-            CType::U32 => 4,
-            CType::U64 => 8,
-            _ => 0,
+            format!("_bitfield_{}", *group_index)
         };
         *group_index += 1;
 
@@ -689,6 +1749,9 @@ fn flatten_bitfields(
             ty,
             bitfield_width: None,
             bitfield_offset: None,
+            offset: Some(start),
+            size: Some(merged_size),
+            native_bitfields: Vec::new(),
         });
         // Keep the first field's offset for the merged group.
         new_offsets.push(field_offsets.get(first_idx).copied().flatten());
@@ -723,29 +1786,199 @@ fn flatten_bitfields(
                 &mut result,
                 &mut new_offsets,
                 &mut new_sizes,
-                field_offsets,
-                &mut group_index,
-                struct_name,
+                field_offsets,
+                &mut group_index,
+                struct_name,
+            );
+            result.push(FieldDef {
+                name: field.name.clone(),
+                ty: field.ty.clone(),
+                bitfield_width: None,
+                bitfield_offset: None,
+                offset: field.offset,
+                size: field.size,
+                native_bitfields: Vec::new(),
+            });
+            new_offsets.push(field_offsets.get(i).copied().flatten());
+            new_sizes.push(field_sizes.get(i).copied().unwrap_or(0));
+        }
+    }
+    // Flush any trailing group.
+    flush_group(
+        &mut group,
+        &mut result,
+        &mut new_offsets,
+        &mut new_sizes,
+        field_offsets,
+        &mut group_index,
+        struct_name,
+    );
+
+    *field_offsets = new_offsets;
+    *field_sizes = new_sizes;
+    result
+}
+
+/// Return the smallest unsigned integer CType that can hold `bits` bits.
+fn smallest_int_for_bits(bits: usize) -> CType {
+    match bits {
+        0..=8 => CType::U8,
+        9..=16 => CType::U16,
+        17..=32 => CType::U32,
+        _ => CType::U64,
+    }
+}
+
+/// Alignment of the unsigned integers bitfields collapse into; 1 for
+/// anything else, including `[u8; N]` storage.
+fn storage_align(ty: &CType) -> usize {
+    match ty {
+        CType::U16 => 2,
+        CType::U32 => 4,
+        CType::U64 => 8,
+        _ => 1,
+    }
+}
+
+/// Collapse bitfields into storage fields the way win32metadata does.
+///
+/// Bitfields that share a C storage unit (the `sizeof(declared type)`-sized,
+/// naturally aligned block holding them) become one unsigned
+/// `_bitfieldN` field of that size at the unit's offset, with a
+/// [`NativeBitfield`] per logical field. When the unit overlaps the
+/// previous field — GCC packs bitfields into the tail of a preceding
+/// member — the storage covers just the bytes the bitfields occupy.
+/// Zero-width bitfields are dropped; their effect is already in clang's
+/// offsets.
+///
+/// `field_offsets` / `field_sizes` are rebuilt in parallel, with each
+/// storage field's exact byte offset so padding lands it where clang did.
+fn storage_bitfields(
+    fields: Vec<FieldDef>,
+    struct_name: &str,
+    field_offsets: &mut Vec<Option<usize>>,
+    field_sizes: &mut Vec<usize>,
+) -> Vec<FieldDef> {
+    if !fields.iter().any(|f| f.bitfield_width.is_some()) {
+        return fields;
+    }
+
+    /// A storage field being filled: byte offset, byte size, members.
+    struct Storage {
+        start: usize,
+        size: usize,
+        members: Vec<NativeBitfield>,
+    }
+
+    let mut result = Vec::new();
+    let mut new_offsets = Vec::new();
+    let mut new_sizes = Vec::new();
+    let mut storage: Option<Storage> = None;
+    let mut storage_index = 0u32;
+    // End (in bytes) of the last field placed, for overlap detection.
+    let mut prev_end = 0usize;
+
+    let mut flush = |storage: &mut Option<Storage>,
+                     result: &mut Vec<FieldDef>,
+                     new_offsets: &mut Vec<Option<usize>>,
+                     new_sizes: &mut Vec<usize>,
+                     prev_end: &mut usize| {
+        let Some(st) = storage.take() else {
+            return;
+        };
+        let ty = match st.size {
+            1 | 2 | 4 | 8 if st.start % st.size == 0 => smallest_int_for_bits(st.size * 8),
+            _ => CType::Array {
+                element: Box::new(CType::U8),
+                len: st.size,
+            },
+        };
+        debug!(
+            struct_name = %struct_name,
+            storage = storage_index,
+            offset = st.start,
+            size = st.size,
+            fields = ?st.members.iter().map(|m| m.name.as_str()).collect::<Vec<_>>(),
+            "bitfield storage unit"
+        );
+        result.push(FieldDef {
+            name: format!("_bitfield{storage_index}"),
+            ty,
+            bitfield_width: None,
+            bitfield_offset: None,
+            offset: Some(st.start),
+            size: Some(st.size),
+            native_bitfields: st.members,
+        });
+        new_offsets.push(Some(st.start));
+        new_sizes.push(st.size);
+        *prev_end = st.start + st.size;
+        storage_index += 1;
+    };
+
+    for (i, field) in fields.into_iter().enumerate() {
+        let declared_size = field_sizes.get(i).copied().unwrap_or(0);
+        let (Some(bit), Some(width)) = (field.bitfield_offset, field.bitfield_width) else {
+            flush(
+                &mut storage,
+                &mut result,
+                &mut new_offsets,
+                &mut new_sizes,
+                &mut prev_end,
             );
-            result.push(FieldDef {
-                name: field.name.clone(),
-                ty: field.ty.clone(),
-                bitfield_width: None,
-                bitfield_offset: None,
+            let offset = field_offsets.get(i).copied().flatten();
+            prev_end = offset.unwrap_or(prev_end) + declared_size;
+            new_offsets.push(offset);
+            new_sizes.push(declared_size);
+            result.push(field);
+            continue;
+        };
+        if width == 0 {
+            flush(
+                &mut storage,
+                &mut result,
+                &mut new_offsets,
+                &mut new_sizes,
+                &mut prev_end,
+            );
+            continue;
+        }
+        let end = bit + width;
+        let fits = |st: &Storage| bit >= st.start * 8 && end <= (st.start + st.size) * 8;
+        if !storage.as_ref().is_some_and(fits) {
+            flush(
+                &mut storage,
+                &mut result,
+                &mut new_offsets,
+                &mut new_sizes,
+                &mut prev_end,
+            );
+            let unit = declared_size.max(1);
+            let unit_start = bit / 8 / unit * unit;
+            let (start, size) = if unit_start >= prev_end && end <= (unit_start + unit) * 8 {
+                (unit_start, unit)
+            } else {
+                (bit / 8, end.div_ceil(8) - bit / 8)
+            };
+            storage = Some(Storage {
+                start,
+                size,
+                members: Vec::new(),
             });
-            new_offsets.push(field_offsets.get(i).copied().flatten());
-            new_sizes.push(field_sizes.get(i).copied().unwrap_or(0));
         }
+        let st = storage.as_mut().expect("storage unit just ensured");
+        st.members.push(NativeBitfield {
+            name: field.name,
+            offset: bit - st.start * 8,
+            width,
+        });
     }
-    // Flush any trailing group.
-    flush_group(
-        &mut group,
+    flush(
+        &mut storage,
         &mut result,
         &mut new_offsets,
         &mut new_sizes,
-        field_offsets,
-        &mut group_index,
-        struct_name,
+        &mut prev_end,
     );
 
     *field_offsets = new_offsets;
@@ -753,16 +1986,6 @@ fn flatten_bitfields(
     result
 }
 
-/// Return the smallest unsigned integer CType that can hold `bits` bits.
-fn smallest_int_for_bits(bits: usize) -> CType {
-    match bits {
-        0..=8 => CType::U8,
-        9..=16 => CType::U16,
-        17..=32 => CType::U32,
-        _ => CType::U64,
-    }
-}
-
 /// Insert inter-field and trailing padding so the generated struct matches
 /// clang's actual layout byte-for-byte.
 ///
@@ -790,8 +2013,10 @@ fn insert_alignment_padding(
     //   packed(N) which sets max alignment, not min alignment)
     let mut field_align_map: HashMap<String, usize> = HashMap::new();
     let mut field_rust_align_map: HashMap<String, usize> = HashMap::new();
+    // Bitfields are skipped: their declared type says nothing about the
+    // storage they collapsed into, which `storage_align` covers instead.
     for child in children {
-        if child.get_kind() != EntityKind::FieldDecl {
+        if child.get_kind() != EntityKind::FieldDecl || child.is_bit_field() {
             continue;
         }
         if let Some(name) = child.get_name() {
@@ -845,7 +2070,10 @@ fn insert_alignment_padding(
             // Use the Rust-side alignment (max field alignment of embedded struct)
             // rather than clang's type alignment, because windows-bindgen uses
             // packed(N) which doesn't enforce min alignment from alignment attributes.
-            let rust_align = field_rust_align_map.get(&field.name).copied().unwrap_or(1);
+            let rust_align = field_rust_align_map
+                .get(&field.name)
+                .copied()
+                .unwrap_or_else(|| storage_align(&field.ty));
             let natural_offset = if rust_align > 0 {
                 (cursor + rust_align - 1) & !(rust_align - 1)
             } else {
@@ -874,6 +2102,9 @@ fn insert_alignment_padding(
                     },
                     bitfield_width: None,
                     bitfield_offset: None,
+                    offset: Some(cursor),
+                    size: Some(gap),
+                    native_bitfields: Vec::new(),
                 });
                 pad_counter += 1;
             }
@@ -887,6 +2118,9 @@ fn insert_alignment_padding(
             ty: field.ty.clone(),
             bitfield_width: field.bitfield_width,
             bitfield_offset: field.bitfield_offset,
+            offset: field.offset,
+            size: field.size,
+            native_bitfields: field.native_bitfields.clone(),
         });
     }
 
@@ -894,18 +2128,16 @@ fn insert_alignment_padding(
     // Use the max Rust-side field alignment (not clang's, which includes
     // alignment attributes that packed(N) doesn't enforce).
     if struct_size > cursor {
-        let mut max_rust_field_align: usize = 1;
-        for child in children {
-            if child.get_kind() != EntityKind::FieldDecl {
-                continue;
-            }
-            if let Some(name) = child.get_name() {
-                let ra = field_rust_align_map.get(&name).copied().unwrap_or(1);
-                if ra > max_rust_field_align {
-                    max_rust_field_align = ra;
-                }
-            }
-        }
+        let max_rust_field_align = result
+            .iter()
+            .map(|f| {
+                field_rust_align_map
+                    .get(&f.name)
+                    .copied()
+                    .unwrap_or_else(|| storage_align(&f.ty))
+            })
+            .max()
+            .unwrap_or(1);
         let natural_size = if max_rust_field_align > 0 {
             (cursor + max_rust_field_align - 1) & !(max_rust_field_align - 1)
         } else {
@@ -929,6 +2161,9 @@ fn insert_alignment_padding(
                 },
                 bitfield_width: None,
                 bitfield_offset: None,
+                offset: Some(cursor),
+                size: Some(trailing),
+                native_bitfields: Vec::new(),
             });
         }
     }
@@ -936,6 +2171,90 @@ fn insert_alignment_padding(
     result
 }
 
+/// Give inline function-pointer fields and parameters a named delegate type.
+///
+/// A field like `void *(*zalloc)(void *, unsigned, unsigned)` or a parameter
+/// like `void (*destructor)(void *)` has no typedef to reference, so it would
+/// otherwise be emitted as a bare pointer-sized integer. Mirroring anonymous
+/// nested records, synthesize a typedef `Parent_member` (emitted as a
+/// delegate) and point the field or parameter at it, where the parent is the
+/// struct or function name. Arrays of function pointers keep their dimensions.
+fn synthesize_inline_delegates(
+    structs: &mut [StructDef],
+    functions: &mut [FunctionDef],
+    typedefs: &mut Vec<TypedefDef>,
+) {
+    let mut taken: HashSet<String> = structs
+        .iter()
+        .map(|s| s.name.clone())
+        .chain(typedefs.iter().map(|t| t.name.clone()))
+        .collect();
+
+    for s in structs.iter_mut() {
+        for field in &mut s.fields {
+            synthesize_delegate(&s.name, &field.name, &mut field.ty, &mut taken, typedefs);
+        }
+    }
+    for f in functions.iter_mut() {
+        for param in &mut f.params {
+            synthesize_delegate(&f.name, &param.name, &mut param.ty, &mut taken, typedefs);
+        }
+    }
+}
+
+/// Replace the inline function pointer in `ty` (if any) with a reference to
+/// a new `parent_member` delegate typedef.
+fn synthesize_delegate(
+    parent: &str,
+    member: &str,
+    ty: &mut CType,
+    taken: &mut HashSet<String>,
+    typedefs: &mut Vec<TypedefDef>,
+) {
+    let Some(fnptr) = inline_fnptr_mut(ty) else {
+        return;
+    };
+    let synthetic_name = format!("{parent}_{member}");
+    if !taken.insert(synthetic_name.clone()) {
+        warn!(
+            parent,
+            member,
+            synthetic = %synthetic_name,
+            "delegate name already taken, keeping untyped function pointer"
+        );
+        return;
+    }
+    let underlying = std::mem::replace(
+        fnptr,
+        CType::Named {
+            name: synthetic_name.clone(),
+            resolved: None,
+        },
+    );
+    debug!(
+        parent,
+        member,
+        synthetic = %synthetic_name,
+        "synthesized delegate for inline function pointer"
+    );
+    typedefs.push(TypedefDef {
+        name: synthetic_name,
+        underlying_type: underlying,
+        param_names: Vec::new(),
+        raii_free: None,
+    });
+}
+
+/// The `Ptr { FnPtr }` inside a field or parameter type (peeling array
+/// dimensions), if it is an inline — not typedef'd — function pointer.
+fn inline_fnptr_mut(ty: &mut CType) -> Option<&mut CType> {
+    match ty {
+        CType::Array { element, .. } => inline_fnptr_mut(element),
+        CType::Ptr { pointee, .. } if matches!(pointee.as_ref(), CType::FnPtr { .. }) => Some(ty),
+        _ => None,
+    }
+}
+
 /// Try to extract an anonymous record field type as a synthetic named type.
 ///
 /// When a struct/union contains a field whose type is an anonymous record
@@ -951,7 +2270,8 @@ fn try_extract_anonymous_field(
     field_type: &ClangType,
     parent_name: &str,
     field_name: &str,
-    nested_types: &mut Vec<StructDef>,
+    opts: Options,
+    nested_types: &mut NestedTypes,
 ) -> Option<CType> {
     // Peel all array levels, collecting dims outermost-first.
     let mut dims: Vec<usize> = Vec::new();
@@ -971,7 +2291,7 @@ fn try_extract_anonymous_field(
     let is_nested_union = decl.get_kind() == EntityKind::UnionDecl;
     let synthetic_name = format!("{}_{}", parent_name, field_name);
 
-    match extract_struct_from_entity(&decl, &synthetic_name, is_nested_union) {
+    match extract_struct_from_entity(&decl, &synthetic_name, is_nested_union, opts) {
         Ok((nested, mut more)) => {
             let kind = if is_nested_union { "union" } else { "struct" };
             debug!(
@@ -981,7 +2301,7 @@ fn try_extract_anonymous_field(
                 dims = ?dims,
                 "extracted anonymous {kind} as synthetic type"
             );
-            nested_types.push(nested);
+            nested_types.structs.push(nested);
             nested_types.append(&mut more);
             let named = CType::Named {
                 name: synthetic_name,
@@ -1006,20 +2326,83 @@ fn try_extract_anonymous_field(
     }
 }
 
+/// Try to extract an anonymous enum field type (`enum { A, B } mode;`) as
+/// a synthetic enum named `ParentName_FieldName`, the way
+/// [`try_extract_anonymous_field`] does for records. Array dimensions are
+/// kept.
+fn try_extract_anonymous_enum(
+    field_type: &ClangType,
+    parent_name: &str,
+    field_name: &str,
+    opts: Options,
+    nested_types: &mut NestedTypes,
+) -> Option<CType> {
+    let mut dims: Vec<usize> = Vec::new();
+    let mut inner = field_type.get_canonical_type();
+    while inner.get_kind() == TypeKind::ConstantArray {
+        dims.push(inner.get_size().unwrap_or(0));
+        inner = inner.get_element_type()?.get_canonical_type();
+    }
+
+    if inner.get_kind() != TypeKind::Enum {
+        return None;
+    }
+    let decl = inner.get_declaration()?;
+    if !decl.is_anonymous() && !decl.get_name().is_none_or(|n| n.contains("(unnamed")) {
+        return None;
+    }
+    let synthetic_name = format!("{}_{}", parent_name, field_name);
+
+    match extract_enum_from_entity(&decl, &synthetic_name, opts) {
+        Ok(en) => {
+            debug!(
+                parent = %parent_name,
+                field = %field_name,
+                synthetic = %synthetic_name,
+                variants = en.variants.len(),
+                "extracted anonymous enum as synthetic type"
+            );
+            nested_types.enums.push(en);
+            let named = CType::Named {
+                name: synthetic_name,
+                resolved: None,
+            };
+            let ctype = dims.iter().rev().fold(named, |acc, &len| CType::Array {
+                element: Box::new(acc),
+                len,
+            });
+            Some(ctype)
+        }
+        Err(e) => {
+            warn!(
+                parent = %parent_name,
+                field = %field_name,
+                err = %e,
+                "failed to extract anonymous nested enum"
+            );
+            None
+        }
+    }
+}
+
 // ---------------------------------------------------------------------------
 // Enum extraction
 // ---------------------------------------------------------------------------
 
-fn extract_enum(decl: &Declaration) -> Result<EnumDef> {
-    extract_enum_from_entity(&decl.entity, &decl.name)
+fn extract_enum(decl: &Declaration, opts: Options) -> Result<EnumDef> {
+    extract_enum_from_entity(&decl.entity, &decl.name, opts)
 }
 
 /// Extract an enum directly from a clang Entity (used by the supplemental pass).
-fn extract_enum_from_entity(entity: &Entity, name: &str) -> Result<EnumDef> {
+fn extract_enum_from_entity(entity: &Entity, name: &str, opts: Options) -> Result<EnumDef> {
     let underlying = entity
         .get_enum_underlying_type()
         .context("enum has no underlying type")?;
-    let underlying_ctype = map_clang_type(&underlying).unwrap_or(CType::I32);
+    // The canonical type, so `enum : uint8_t` maps to U8 rather than to a
+    // typedef reference.
+    let clang_ctype = map_clang_type(&underlying.get_canonical_type(), opts)
+        .ok()
+        .filter(|ty| ty.int_range().is_some());
 
     let mut variants = Vec::new();
     for child in entity.get_children() {
@@ -1035,32 +2418,71 @@ fn extract_enum_from_entity(entity: &Entity, name: &str) -> Result<EnumDef> {
         });
     }
 
+    let underlying_type = enum_underlying_type(name, clang_ctype, &variants);
     Ok(EnumDef {
         name: name.to_string(),
-        underlying_type: underlying_ctype,
+        underlying_type,
         variants,
     })
 }
 
+/// Clang's underlying type when it is an integer that holds every variant,
+/// otherwise the narrowest of `i32`, `u32`, `i64`, `u64` that does.
+fn enum_underlying_type(name: &str, clang: Option<CType>, variants: &[EnumVariant]) -> CType {
+    let signed = clang
+        .as_ref()
+        .and_then(CType::int_range)
+        .is_none_or(|(min, _)| min < 0);
+    let fits = |ty: &CType| {
+        let (min, max) = ty.int_range().expect("integer type");
+        variants
+            .iter()
+            .all(|v| (min..=max).contains(&v.value(signed)))
+    };
+    if let Some(ty) = clang.filter(|ty| fits(ty)) {
+        return ty;
+    }
+    let widened = [CType::I32, CType::U32, CType::I64, CType::U64]
+        .into_iter()
+        .find(|ty| fits(ty))
+        .unwrap_or(CType::I64);
+    debug!(name, underlying = ?widened, "enum underlying type derived from variant values");
+    widened
+}
+
 // ---------------------------------------------------------------------------
 // Function extraction
 // ---------------------------------------------------------------------------
 
-fn extract_function(decl: &Declaration) -> Result<FunctionDef> {
+fn extract_function(decl: &Declaration, opts: Options) -> Result<FunctionDef> {
     let fn_type = decl.entity.get_type().context("function has no type")?;
 
     let ret_type = fn_type
         .get_result_type()
         .context("function has no return type")?;
-    let return_ctype = map_clang_type(&ret_type).unwrap_or(CType::Void);
+    let arg_types = fn_type.get_argument_types().unwrap_or_default();
+    // A `[T; 2]` matches `T _Complex` in memory but not in registers, so
+    // by-value complex parameters and returns are skipped.
+    if std::iter::once(&ret_type)
+        .chain(&arg_types)
+        .any(|t| t.get_canonical_type().get_kind() == TypeKind::Complex)
+    {
+        anyhow::bail!("_Complex passed by value not supported (no WinMD complex type)");
+    }
+    let return_ctype = map_clang_type(&ret_type, opts).unwrap_or(CType::Void);
 
-    let calling_convention = fn_type
+    // The partition's `calling_convention` replaces the platform default
+    // only; an explicit `__stdcall` / `__fastcall` in the header wins.
+    let calling_convention = match fn_type
         .get_calling_convention()
         .map(map_calling_convention)
-        .unwrap_or(CallConv::Cdecl);
+        .unwrap_or(CallConv::Cdecl)
+    {
+        CallConv::Cdecl => opts.calling_convention.unwrap_or(CallConv::Cdecl),
+        cc => cc,
+    };
 
     let args = decl.entity.get_arguments().unwrap_or_default();
-    let arg_types = fn_type.get_argument_types().unwrap_or_default();
 
     let mut params = Vec::new();
     for (i, arg_entity) in args.iter().enumerate() {
@@ -1068,7 +2490,7 @@ fn extract_function(decl: &Declaration) -> Result<FunctionDef> {
             .get_name()
             .unwrap_or_else(|| format!("param{}", i));
         let ty = if i < arg_types.len() {
-            map_clang_type(&arg_types[i]).unwrap_or(CType::Void)
+            map_clang_type(&arg_types[i], opts).unwrap_or(CType::Void)
         } else {
             CType::Void
         };
@@ -1082,31 +2504,235 @@ fn extract_function(decl: &Declaration) -> Result<FunctionDef> {
             },
             other => other,
         };
-        params.push(ParamDef { name, ty });
+        params.push(ParamDef {
+            name,
+            ty,
+            associated_enum: None,
+            associated_constant: None,
+            is_string: false,
+            annotations: ParamAnnotations::default(),
+        });
+    }
+
+    declared_annotations(&decl.entity, &decl.name, &mut params);
+
+    let entry_point = asm_label(&decl.entity).unwrap_or_else(|| decl.name.clone());
+    if entry_point != decl.name {
+        debug!(name = %decl.name, entry_point = %entry_point, "function redirected via asm label");
     }
 
+    // Inline definitions have no exported symbol; keep the C spelling so a
+    // `[shim]` wrapper can be generated for them.
+    let is_inline = decl.entity.is_definition()
+        && (decl.entity.is_inline_function()
+            || decl.entity.get_storage_class() == Some(StorageClass::Static));
+    let inline_prototype = is_inline.then(|| CPrototype {
+        return_type: ret_type.get_display_name(),
+        param_types: arg_types.iter().map(|t| t.get_display_name()).collect(),
+    });
+
     Ok(FunctionDef {
         name: decl.name.clone(),
+        entry_point,
         return_type: return_ctype,
         params,
         calling_convention,
+        library: None,
+        inline_prototype,
+        returns_string: false,
+        return_convention: None,
     })
 }
 
+/// Parameter annotations spelled in the header: SAL macros (`_In_`,
+/// `_Out_opt_`, `_Out_writes_bytes_(len)`, legacy `__in_opt`), `_Nullable`
+/// and `__attribute__((nonnull(...)))`. The macros usually expand to
+/// nothing, so they are read from the declaration's tokens rather than the
+/// parsed AST. Parameter lists produced by macros are left alone.
+fn declared_annotations(entity: &Entity, name: &str, params: &mut [ParamDef]) {
+    let mut tokens = spellings(entity);
+    // Attributes after the declarator aren't always inside the declaration's
+    // range; their own ranges are.
+    for attr in entity.get_children() {
+        if attr.get_kind() == EntityKind::UnexposedAttr {
+            tokens.extend(spellings(&attr));
+        }
+    }
+
+    if let Some(start) = tokens.windows(2).position(|w| w[0] == name && w[1] == "(") {
+        let segments = param_segments(&tokens[start + 2..]);
+        if segments.len() == params.len() {
+            for (i, segment) in segments.iter().enumerate() {
+                sal_annotations(segment, i, params);
+            }
+        }
+    }
+
+    // `nonnull(1, 2)` names the pointers that must not be null; the others
+    // may be. Several `nonnull` attributes add up. A bare `nonnull` says
+    // nothing beyond the default.
+    let mut listed: Option<HashSet<usize>> = None;
+    for (i, window) in tokens.windows(2).enumerate() {
+        if !matches!(window[0].as_str(), "nonnull" | "__nonnull" | "__nonnull__")
+            || window[1] != "("
+        {
+            continue;
+        }
+        listed.get_or_insert_default().extend(
+            tokens[i + 2..]
+                .iter()
+                .take_while(|t| *t != ")")
+                .filter_map(|t| t.parse::<usize>().ok()),
+        );
+    }
+    if let Some(listed) = listed {
+        for (index, param) in params.iter_mut().enumerate() {
+            if matches!(param.ty, CType::Ptr { .. }) && !listed.contains(&(index + 1)) {
+                param.annotations.optional = true;
+            }
+        }
+    }
+}
+
+/// Split the tokens after a parameter list's `(` at top-level commas, up
+/// to the closing `)`.
+fn param_segments(tokens: &[String]) -> Vec<&[String]> {
+    let mut segments = Vec::new();
+    let mut depth = 0usize;
+    let mut start = 0;
+    for (i, token) in tokens.iter().enumerate() {
+        match token.as_str() {
+            "(" | "[" => depth += 1,
+            ")" if depth == 0 => {
+                segments.push(&tokens[start..i]);
+                break;
+            }
+            ")" | "]" => depth = depth.saturating_sub(1),
+            "," if depth == 0 => {
+                segments.push(&tokens[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    if let [.., [last]] = segments[..]
+        && last == "..."
+    {
+        segments.pop();
+    }
+    // `f()` and `f(void)` take no parameters.
+    if let [segment] = segments[..]
+        && segment.iter().all(|t| t == "void")
+    {
+        segments.clear();
+    }
+    segments
+}
+
+/// Apply the SAL annotations in one parameter's tokens to `params[index]`.
+/// SAL macros lead the parameter, before its type, so only that prefix is
+/// read; a parameter *named* `__in` or `__out` is not an annotation.
+/// `_Nullable` follows the `*` and is recognised anywhere.
+fn sal_annotations(segment: &[String], index: usize, params: &mut [ParamDef]) {
+    let mut prefix = true;
+    // Nesting inside an annotation's `(...)` arguments.
+    let mut depth = 0usize;
+    for (i, token) in segment.iter().enumerate() {
+        if matches!(token.as_str(), "_Nullable" | "__nullable") {
+            params[index].annotations.optional = true;
+            continue;
+        }
+        match token.as_str() {
+            "(" if prefix && i > 0 => {
+                depth += 1;
+                continue;
+            }
+            ")" if depth > 0 => {
+                depth -= 1;
+                continue;
+            }
+            _ if depth > 0 => continue,
+            _ => {}
+        }
+        if !prefix {
+            continue;
+        }
+        let parts: Vec<&str> = token.trim_matches('_').split('_').collect();
+        let direction = match parts[0].to_ascii_lowercase().as_str() {
+            _ if !token.starts_with('_') => None,
+            "in" => Some(ParamDirection::In),
+            "out" => Some(ParamDirection::Out),
+            "inout" => Some(ParamDirection::InOut),
+            _ => None,
+        };
+        let Some(direction) = direction else {
+            prefix = false;
+            continue;
+        };
+        let annotations = &mut params[index].annotations;
+        annotations.direction = Some(direction);
+        annotations.optional |= parts.contains(&"opt");
+        // `_Out_writes_bytes_(len)` / `__in_bcount(len)` give the size in
+        // bytes, `_In_reads_(n)` / `__out_ecount(n)` in elements. Only a
+        // plain parameter name is understood as the size.
+        let bytes = parts.iter().any(|p| matches!(*p, "bytes" | "bcount"));
+        let elements = parts
+            .iter()
+            .any(|p| matches!(*p, "reads" | "writes" | "updates" | "ecount"));
+        if (bytes || elements)
+            && let [open, size, close, ..] = &segment[i + 1..]
+            && open == "("
+            && close == ")"
+        {
+            let size = params.iter().position(|p| p.name == *size);
+            let annotations = &mut params[index].annotations;
+            if bytes {
+                annotations.memory_size = size;
+            } else {
+                annotations.array_count = size;
+            }
+        }
+    }
+}
+
+/// The symbol named by an `__asm__("name")` label on a declaration — how
+/// glibc's `__REDIRECT` maps e.g. `lockf` onto `lockf64`. A leading `\x01`
+/// ("don't add the user label prefix") is stripped.
+fn asm_label(entity: &Entity) -> Option<String> {
+    entity
+        .get_children()
+        .into_iter()
+        .find(|c| c.get_kind() == EntityKind::AsmLabelAttr)
+        .and_then(|attr| attr.get_name())
+        .map(|label| label.trim_start_matches('\u{1}').to_string())
+        .filter(|label| !label.is_empty())
+}
+
 // ---------------------------------------------------------------------------
 // Typedef extraction
 // ---------------------------------------------------------------------------
 
-fn extract_typedef_from_entity(entity: &Entity, name: &str) -> Result<TypedefDef> {
+fn extract_typedef_from_entity(entity: &Entity, name: &str, opts: Options) -> Result<TypedefDef> {
     let underlying = entity
         .get_typedef_underlying_type()
         .context("typedef has no underlying type")?;
-    let ctype = map_clang_type(&underlying)?;
+    let ctype = map_clang_type(&underlying, opts)?;
     trace!(name = %name, ty = ?ctype, "typedef underlying type");
 
+    // For function-pointer typedefs clang exposes the prototype's
+    // parameters as ParmDecl children; unnamed ones come back empty.
+    let param_names = entity
+        .get_children()
+        .iter()
+        .filter(|c| c.get_kind() == EntityKind::ParmDecl)
+        .map(|c| c.get_name().unwrap_or_default())
+        .collect();
+
     Ok(TypedefDef {
         name: name.to_string(),
         underlying_type: ctype,
+        param_names,
+        raii_free: None,
     })
 }
 
@@ -1114,7 +2740,34 @@ fn extract_typedef_from_entity(entity: &Entity, name: &str) -> Result<TypedefDef
 // Type mapping: clang TypeKind → CType
 // ---------------------------------------------------------------------------
 
-fn map_clang_type(ty: &ClangType) -> Result<CType> {
+/// `wchar_t` at the configured width, or clang's for the target.
+fn wchar(ty: &ClangType, opts: Options) -> Result<CType> {
+    let width = match opts.wchar_width {
+        Some(width) => width,
+        None => ty.get_sizeof().context("wchar_t has no size")?,
+    };
+    Ok(CType::WChar { width })
+}
+
+/// Map a clang type, then let the config's [`TypeMapHook`] replace the
+/// result.
+fn map_clang_type(ty: &ClangType, opts: Options) -> Result<CType> {
+    let mapped = map_type_kind(ty, opts)?;
+    let Some(hook) = opts.type_map else {
+        return Ok(mapped);
+    };
+    let name = ty.get_display_name();
+    let mut spelling = name.as_str();
+    while let Some(rest) = spelling
+        .strip_prefix("const ")
+        .or_else(|| spelling.strip_prefix("volatile "))
+    {
+        spelling = rest;
+    }
+    Ok(hook.map(spelling, mapped.clone()).unwrap_or(mapped))
+}
+
+fn map_type_kind(ty: &ClangType, opts: Options) -> Result<CType> {
     match ty.get_kind() {
         TypeKind::Void => Ok(CType::Void),
         TypeKind::Bool => Ok(CType::Bool),
@@ -1124,7 +2777,10 @@ fn map_clang_type(ty: &ClangType) -> Result<CType> {
         TypeKind::UShort => Ok(CType::U16),
         TypeKind::Int => Ok(CType::I32),
         TypeKind::UInt => Ok(CType::U32),
-        // C `long` is 64-bit on Linux x86-64 (LP64 ABI)
+        // C `long` is 64-bit on Linux x86-64 (LP64) but 32-bit on Windows
+        // (LLP64) and 32-bit targets.
+        TypeKind::Long if ty.get_sizeof() == Ok(4) => Ok(CType::I32),
+        TypeKind::ULong if ty.get_sizeof() == Ok(4) => Ok(CType::U32),
         TypeKind::Long => Ok(CType::I64),
         TypeKind::ULong => Ok(CType::U64),
         TypeKind::LongLong => Ok(CType::I64),
@@ -1132,9 +2788,34 @@ fn map_clang_type(ty: &ClangType) -> Result<CType> {
         TypeKind::Float => Ok(CType::F32),
         TypeKind::Double => Ok(CType::F64),
 
+        // Built-in in C++; typedefs in C (see the Typedef arm).
+        TypeKind::WChar => wchar(ty, opts),
+        TypeKind::Char16 => Ok(CType::WChar { width: 2 }),
+        TypeKind::Char32 => Ok(CType::WChar { width: 4 }),
+
+        // No WinMD type for `long double`; `long_double` picks a lossy
+        // stand-in or skips the declaration.
+        TypeKind::LongDouble => match opts.long_double {
+            LongDoubleMode::Skip => {
+                anyhow::bail!("long double not supported (set `long_double` to map it)")
+            }
+            LongDoubleMode::Bytes => Ok(CType::Array {
+                element: Box::new(CType::U8),
+                len: ty.get_sizeof().context("long double has no size")?,
+            }),
+            LongDoubleMode::F64 => Ok(CType::F64),
+        },
+
         // __int128 / unsigned __int128: no WinMD ELEMENT_TYPE for 128-bit
         // integers and windows-bindgen cannot emit i128/u128. Bail so the
-        // caller can skip the containing type with a warning.
+        // caller can skip the containing type with a warning, unless
+        // `int128 = "u64-pair"` accepts two u64 words instead.
+        TypeKind::Int128 | TypeKind::UInt128 if opts.int128 == Int128Mode::U64Pair => {
+            Ok(CType::Array {
+                element: Box::new(CType::U64),
+                len: 2,
+            })
+        }
         TypeKind::Int128 => {
             anyhow::bail!("__int128 not supported (no WinMD 128-bit integer type)")
         }
@@ -1142,12 +2823,35 @@ fn map_clang_type(ty: &ClangType) -> Result<CType> {
             anyhow::bail!("unsigned __int128 not supported (no WinMD 128-bit integer type)")
         }
 
+        // `T _Complex` is laid out as `T[2]` (real, imaginary). Right for
+        // fields and pointees only: `extract_function` rejects by-value
+        // parameters and returns, which are passed differently.
+        TypeKind::Complex => {
+            let elem = ty
+                .get_element_type()
+                .context("complex type has no element type")?;
+            Ok(CType::Array {
+                element: Box::new(map_clang_type(&elem, opts)?),
+                len: 2,
+            })
+        }
+
+        // Objective-C object pointers and blocks are opaque to C callers.
+        TypeKind::ObjCObjectPointer
+        | TypeKind::ObjCId
+        | TypeKind::ObjCClass
+        | TypeKind::ObjCSel
+        | TypeKind::BlockPointer => Ok(CType::Ptr {
+            pointee: Box::new(CType::Void),
+            is_const: false,
+        }),
+
         TypeKind::Pointer => {
             let pointee = ty
                 .get_pointee_type()
                 .context("pointer has no pointee type")?;
             let is_const = pointee.is_const_qualified();
-            let inner = map_clang_type(&pointee)?;
+            let inner = map_clang_type(&pointee, opts)?;
             Ok(CType::Ptr {
                 pointee: Box::new(inner),
                 is_const,
@@ -1157,7 +2861,7 @@ fn map_clang_type(ty: &ClangType) -> Result<CType> {
         TypeKind::ConstantArray => {
             let elem = ty.get_element_type().context("array has no element type")?;
             let len = ty.get_size().unwrap_or(0);
-            let inner = map_clang_type(&elem)?;
+            let inner = map_clang_type(&elem, opts)?;
             Ok(CType::Array {
                 element: Box::new(inner),
                 len,
@@ -1169,7 +2873,7 @@ fn map_clang_type(ty: &ClangType) -> Result<CType> {
             let elem = ty
                 .get_element_type()
                 .context("incomplete array has no element type")?;
-            let inner = map_clang_type(&elem)?;
+            let inner = map_clang_type(&elem, opts)?;
             Ok(CType::Ptr {
                 pointee: Box::new(inner),
                 is_const: false,
@@ -1180,7 +2884,7 @@ fn map_clang_type(ty: &ClangType) -> Result<CType> {
             let inner = ty
                 .get_elaborated_type()
                 .context("elaborated type has no inner type")?;
-            map_clang_type(&inner)
+            map_clang_type(&inner, opts)
         }
 
         TypeKind::Typedef => {
@@ -1188,6 +2892,12 @@ fn map_clang_type(ty: &ClangType) -> Result<CType> {
             if let Some(decl) = decl {
                 let name = decl.get_name().unwrap_or_default();
                 if !name.is_empty() {
+                    match name.as_str() {
+                        "wchar_t" => return wchar(ty, opts),
+                        "char16_t" => return Ok(CType::WChar { width: 2 }),
+                        "char32_t" => return Ok(CType::WChar { width: 4 }),
+                        _ => {}
+                    }
                     // va_list is a compiler built-in with no portable canonical type
                     if matches!(
                         name.as_str(),
@@ -1202,7 +2912,7 @@ fn map_clang_type(ty: &ClangType) -> Result<CType> {
                     // __int128), bail so any typedef chain referencing it is
                     // also skipped (e.g. `typedef __s128 s128`).
                     let canonical = ty.get_canonical_type();
-                    let resolved = map_clang_type(&canonical).map(Box::new)?;
+                    let resolved = map_clang_type(&canonical, opts).map(Box::new)?;
                     return Ok(CType::Named {
                         name,
                         resolved: Some(resolved),
@@ -1211,7 +2921,7 @@ fn map_clang_type(ty: &ClangType) -> Result<CType> {
             }
             // Unnamed or unresolvable typedef — resolve to canonical primitive
             let canonical = ty.get_canonical_type();
-            map_clang_type(&canonical)
+            map_clang_type(&canonical, opts)
         }
 
         TypeKind::Record => {
@@ -1228,15 +2938,16 @@ fn map_clang_type(ty: &ClangType) -> Result<CType> {
 
                 // Check if the type is complete (has a definition, not just forward-declared).
                 // Incomplete/opaque types (like `struct internal_state` in zlib) are
-                // mapped to Void so that pointers to them become `*mut c_void`.
+                // kept as forward references: they resolve to another partition's
+                // definition, or to Void so that pointers to them become `*mut c_void`.
                 if ty.get_sizeof().is_ok() {
                     return Ok(CType::Named {
                         name,
                         resolved: None,
                     });
                 } else {
-                    debug!(name = %name, "incomplete record type, mapping to Void");
-                    return Ok(CType::Void);
+                    debug!(name = %name, "incomplete record type, keeping forward reference");
+                    return Ok(CType::Forward { name });
                 }
             }
             anyhow::bail!("anonymous record type without name")
@@ -1259,11 +2970,11 @@ fn map_clang_type(ty: &ClangType) -> Result<CType> {
             let ret = ty
                 .get_result_type()
                 .context("function prototype has no return type")?;
-            let ret_ctype = map_clang_type(&ret)?;
+            let ret_ctype = map_clang_type(&ret, opts)?;
             let arg_types = ty.get_argument_types().unwrap_or_default();
             let mut params = Vec::new();
             for at in &arg_types {
-                params.push(map_clang_type(at)?);
+                params.push(map_clang_type(at, opts)?);
             }
             let cc = ty
                 .get_calling_convention()
@@ -1349,39 +3060,206 @@ fn is_primitive_name(name: &str) -> bool {
 // Source-location filtering (partition traversal)
 // ---------------------------------------------------------------------------
 
-fn should_emit(entity: &Entity, traverse_files: &[PathBuf], base_dir: &Path) -> bool {
-    should_emit_by_location(entity, traverse_files, base_dir)
+/// A partition's traverse files, matched against the files clang reports
+/// for each entity.
+///
+/// Both sides are canonicalized, so a header reached through a symlink
+/// (`/usr/include/x86_64-linux-gnu` vs the multiarch real path) still
+/// matches, and compared case-insensitively where the file system usually
+/// is. Counts how many entities each traverse entry matched.
+struct TraverseScope<'a> {
+    /// `(entry, files)` as configured and expanded.
+    entries: Vec<(&'a Path, Vec<PathBuf>)>,
+    /// `(entry index, normalized file)` for every traverse file, as
+    /// resolved and canonicalized.
+    files: Vec<(usize, PathBuf)>,
+    /// Entry matched by each file clang reported, by its path.
+    cache: RefCell<HashMap<PathBuf, Option<usize>>>,
+    hits: RefCell<Vec<usize>>,
+}
+
+impl<'a> TraverseScope<'a> {
+    fn new(entries: Vec<(&'a Path, Vec<PathBuf>)>) -> Self {
+        let mut files = Vec::new();
+        for (i, (_, paths)) in entries.iter().enumerate() {
+            for path in paths {
+                files.push((i, normalize_path(path)));
+                if let Ok(canonical) = std::fs::canonicalize(path) {
+                    files.push((i, normalize_path(&canonical)));
+                }
+            }
+        }
+        let hits = RefCell::new(vec![0; entries.len()]);
+        Self {
+            entries,
+            files,
+            cache: RefCell::default(),
+            hits,
+        }
+    }
+
+    fn contains(&self, entity: &Entity) -> bool {
+        let Some(file) = entity
+            .get_location()
+            .and_then(|loc| loc.get_file_location().file)
+        else {
+            return false;
+        };
+        let path = file.get_path();
+        let entry = *self
+            .cache
+            .borrow_mut()
+            .entry(path.clone())
+            .or_insert_with(|| self.match_file(&path));
+        if let Some(i) = entry {
+            self.hits.borrow_mut()[i] += 1;
+        }
+        entry.is_some()
+    }
+
+    /// The entry `path` belongs to: equal to one of its files, or ending
+    /// with one (for robustness), before or after canonicalizing.
+    fn match_file(&self, path: &Path) -> Option<usize> {
+        let mut candidates = vec![normalize_path(path)];
+        if let Ok(canonical) = std::fs::canonicalize(path) {
+            candidates.push(normalize_path(&canonical));
+        }
+        candidates.iter().find_map(|candidate| {
+            self.files
+                .iter()
+                .find(|(_, tf)| candidate == tf || candidate.ends_with(tf))
+                .map(|(i, _)| *i)
+        })
+    }
+
+    /// Every traverse entry with the number of entities found in it.
+    fn hits(&self) -> Vec<(&'a Path, usize)> {
+        let hits = self.hits.borrow();
+        self.entries
+            .iter()
+            .zip(hits.iter())
+            .map(|((entry, _), hits)| (*entry, *hits))
+            .collect()
+    }
+
+    /// Traverse entries no entity was found in.
+    fn unmatched(&self) -> Vec<&'a Path> {
+        self.hits()
+            .into_iter()
+            .filter(|(_, hits)| *hits == 0)
+            .map(|(entry, _)| entry)
+            .collect()
+    }
+}
+
+/// `path` as compared for traverse matching: lowercased on macOS and
+/// Windows, whose file systems are case-insensitive by default.
+fn normalize_path(path: &Path) -> PathBuf {
+    if cfg!(any(target_os = "macos", target_os = "windows")) {
+        PathBuf::from(path.to_string_lossy().to_lowercase())
+    } else {
+        path.to_path_buf()
+    }
 }
 
 /// Emit a trace log when an entity is skipped because it falls outside the
-/// traverse scope. Helps diagnose missing types when authoring partitions.
-fn trace_out_of_scope(entity: &Entity, kind: &str) {
-    let file = entity
-        .get_location()
-        .and_then(|loc| loc.get_file_location().file)
-        .map(|f| f.get_path().display().to_string())
+/// traverse scope, and record it for the API report. Helps diagnose missing
+/// types when authoring partitions.
+fn skip_out_of_scope(entity: &Entity, kind: &'static str, skipped: &mut Vec<SkippedDecl>) {
+    let location = source_location(entity);
+    let file = location
+        .as_ref()
+        .map(|loc| loc.file.clone())
         .unwrap_or_else(|| "<unknown>".into());
     let name = entity.get_name().unwrap_or_else(|| "<unnamed>".into());
     trace!(kind, name = %name, file = %file, "skipping out-of-scope type");
+    skipped.push(SkippedDecl {
+        name,
+        kind: kind.into(),
+        reason: SkipReason::OutOfScope { file },
+        location,
+    });
 }
 
-fn should_emit_by_location(entity: &Entity, traverse_files: &[PathBuf], _base_dir: &Path) -> bool {
-    let location = match entity.get_location() {
-        Some(loc) => loc,
-        None => return false,
-    };
-    let file_location = location.get_file_location();
-    let file = match file_location.file {
-        Some(f) => f,
-        None => return false,
-    };
-    let file_path = file.get_path();
+/// Record the in-scope Objective-C declarations, which have no winmd
+/// equivalent. The C declarations around them are extracted as usual.
+fn skip_objc(
+    entities: &[Entity],
+    in_scope: &impl Fn(&Entity) -> bool,
+    skipped: &mut Vec<SkippedDecl>,
+) {
+    for entity in entities {
+        let kind = match entity.get_kind() {
+            EntityKind::ObjCInterfaceDecl => "objc interface",
+            EntityKind::ObjCCategoryDecl => "objc category",
+            EntityKind::ObjCProtocolDecl => "objc protocol",
+            EntityKind::ObjCImplementationDecl | EntityKind::ObjCCategoryImplDecl => {
+                "objc implementation"
+            }
+            _ => continue,
+        };
+        if !in_scope(entity) {
+            continue;
+        }
+        let name = entity.get_name().unwrap_or_else(|| "<unnamed>".into());
+        trace!(kind, name = %name, "skipping Objective-C declaration");
+        skipped.push(SkippedDecl {
+            name,
+            kind: kind.into(),
+            reason: SkipReason::ObjC,
+            location: source_location(entity),
+        });
+    }
+}
 
-    // traverse_files are already resolved to absolute paths by the caller,
-    // so we just compare directly (or by suffix for robustness).
-    traverse_files
-        .iter()
-        .any(|tf| file_path == *tf || file_path.ends_with(tf))
+/// Record a declaration whose extraction failed.
+fn skip_unsupported(
+    skipped: &mut Vec<SkippedDecl>,
+    entity: &Entity,
+    name: &str,
+    kind: &'static str,
+    err: &anyhow::Error,
+) {
+    skipped.push(SkippedDecl {
+        name: name.to_string(),
+        kind: kind.into(),
+        reason: SkipReason::Unsupported(format!("{err:#}")),
+        location: source_location(entity),
+    });
+}
+
+/// File and line of an entity's spelling location.
+fn source_location(entity: &Entity) -> Option<SourceLocation> {
+    let loc = entity.get_location()?.get_file_location();
+    Some(SourceLocation {
+        file: loc.file?.get_path().display().to_string(),
+        line: loc.line,
+    })
+}
+
+/// Report skipped declarations as diagnostics. Out-of-scope skips are the
+/// normal result of traverse filtering and are left to the API report.
+fn record_skipped(namespace: &str, skipped: &[SkippedDecl], diagnostics: &mut Diagnostics) {
+    for decl in skipped {
+        let (code, message) = match &decl.reason {
+            SkipReason::OutOfScope { .. } | SkipReason::ObjC => continue,
+            SkipReason::Variadic => ("variadic_function", "skipped variadic function".to_string()),
+            SkipReason::Unsupported(err) => (
+                "unsupported_declaration",
+                format!("skipped {}: {err}", decl.kind),
+            ),
+            SkipReason::UnparsedMacro => (
+                "constant_unparsed",
+                "#define body is not a numeric literal".to_string(),
+            ),
+        };
+        diagnostics.push(
+            Diagnostic::new(code, message)
+                .name(&decl.name)
+                .namespace(namespace)
+                .location(decl.location.as_ref()),
+        );
+    }
 }
 
 /// Build a type registry from all partitions' extracted data.
@@ -1393,7 +3271,7 @@ fn should_emit_by_location(entity: &Entity, traverse_files: &[PathBuf], _base_di
 /// rarely overlap across partitions).
 pub fn build_type_registry(
     partitions: &[Partition],
-    namespace_overrides: &std::collections::HashMap<String, String>,
+    namespace_overrides: &BTreeMap<String, String>,
 ) -> TypeRegistry {
     let mut registry = TypeRegistry::default();
     for partition in partitions {
@@ -1420,6 +3298,13 @@ pub fn build_type_registry(
                 .unwrap_or(&partition.namespace);
             registry.register(&td.name, ns);
         }
+        for i in &partition.interfaces {
+            let ns = namespace_overrides
+                .get(&i.name)
+                .unwrap_or(&partition.namespace);
+            registry.register(&i.name, ns);
+            registry.interfaces.insert(i.name.clone());
+        }
     }
     registry
 }
@@ -0,0 +1,149 @@
+//! `group_by_handle` — move functions operating on one handle type into
+//! their own namespace.
+
+use std::collections::{BTreeMap, HashSet};
+
+use tracing::debug;
+
+use crate::model::*;
+
+/// Functions sharing a first-parameter handle type move out of their
+/// partition's `Apis` class when there are at least this many of them.
+const MIN_GROUP: usize = 2;
+
+/// Move functions whose first parameter is a handle (see [`handle_of`])
+/// into a `<namespace>.<handle>` partition per handle type. Runs after
+/// renames, so namespaces use the final type names.
+pub(crate) fn group_by_handle(partitions: &mut Vec<Partition>) {
+    let enums: HashSet<String> = partitions
+        .iter()
+        .flat_map(|p| p.enums.iter().map(|e| e.name.clone()))
+        .collect();
+
+    let mut moved: Vec<Partition> = Vec::new();
+    for partition in partitions.iter_mut() {
+        let mut groups: BTreeMap<String, Vec<usize>> = BTreeMap::new();
+        for (i, f) in partition.functions.iter().enumerate() {
+            let handle = f.params.first().and_then(|p| handle_of(&p.ty));
+            if let Some(handle) = handle.filter(|h| !enums.contains(*h)) {
+                groups.entry(handle.to_string()).or_default().push(i);
+            }
+        }
+        groups.retain(|_, members| members.len() >= MIN_GROUP);
+        if groups.is_empty() {
+            continue;
+        }
+
+        let type_names: HashSet<&str> = partition
+            .structs
+            .iter()
+            .map(|s| s.name.as_str())
+            .chain(partition.typedefs.iter().map(|t| t.name.as_str()))
+            .chain(partition.enums.iter().map(|e| e.name.as_str()))
+            .chain(partition.interfaces.iter().map(|i| i.name.as_str()))
+            .collect();
+        let mut target_of = vec![None; partition.functions.len()];
+        for (handle, members) in &groups {
+            let namespace = format!("{}.{}", partition.namespace, segment(handle, &type_names));
+            debug!(
+                handle = %handle,
+                namespace = %namespace,
+                functions = members.len(),
+                "grouping functions by handle"
+            );
+            for &i in members {
+                target_of[i] = Some(namespace.clone());
+            }
+        }
+
+        let functions = std::mem::take(&mut partition.functions);
+        for (f, target) in functions.into_iter().zip(target_of) {
+            let Some(namespace) = target else {
+                partition.functions.push(f);
+                continue;
+            };
+            match moved.iter_mut().find(|p| p.namespace == namespace) {
+                Some(group) => group.functions.push(f),
+                None => moved.push(Partition {
+                    namespace,
+                    library: partition.library.clone(),
+                    library_soname: partition.library_soname.clone(),
+                    delay_load: partition.delay_load,
+                    structs: Vec::new(),
+                    enums: Vec::new(),
+                    functions: vec![f],
+                    typedefs: Vec::new(),
+                    constants: Vec::new(),
+                    interfaces: Vec::new(),
+                    skipped: Vec::new(),
+                    apis_split: partition.apis_split,
+                    max_methods: partition.max_methods,
+                    pointer_width: partition.pointer_width,
+                }),
+            }
+        }
+    }
+
+    // A group may land in a namespace that already exists (a partition or
+    // `namespace_from_path` directory of that name): merge into it, keeping
+    // each function's own library.
+    for group in moved {
+        match partitions
+            .iter_mut()
+            .find(|p| p.namespace == group.namespace)
+        {
+            Some(existing) => {
+                for mut f in group.functions {
+                    if existing.library != group.library && f.library.is_none() {
+                        f.library = Some(group.library.clone());
+                    }
+                    existing.functions.push(f);
+                }
+            }
+            None => partitions.push(group),
+        }
+    }
+}
+
+/// The handle type a parameter refers to: a record or opaque type passed
+/// by pointer (`BIGNUM *`), or a typedef that is itself such a pointer
+/// (`typedef struct sqlite3_stmt *stmt_t`).
+fn handle_of(ty: &CType) -> Option<&str> {
+    match ty {
+        CType::Ptr { pointee, .. } => match pointee.as_ref() {
+            CType::Named { name, resolved } if resolved.as_deref().is_none_or(is_opaque) => {
+                Some(name)
+            }
+            _ => None,
+        },
+        CType::Named {
+            name,
+            resolved: Some(resolved),
+        } => match resolved.as_ref() {
+            CType::Ptr { pointee, .. } if is_opaque(pointee) => Some(name),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// A type whose contents callers don't see through: a record, a
+/// forward-declared record or `void`.
+fn is_opaque(ty: &CType) -> bool {
+    matches!(
+        ty,
+        CType::Named { resolved: None, .. } | CType::Forward { .. } | CType::Void
+    )
+}
+
+/// Namespace segment for a handle: its name lowercased, so the Rust module
+/// doesn't clash with the type, with `_api` appended when that spelling is
+/// itself a type in the namespace (`sqlite3`).
+fn segment(handle: &str, type_names: &HashSet<&str>) -> String {
+    let lower = handle.to_ascii_lowercase();
+    if type_names.contains(lower.as_str()) {
+        format!("{lower}_api")
+    } else {
+        lower
+    }
+}
@@ -0,0 +1,381 @@
+//! IDL backend — model types → MIDL3-like text, for reading rather than
+//! compiling.
+//!
+//! A winmd diff in a pull request is a binary blob; the IDL is the same
+//! API surface as reviewable text. Declarations follow MIDL3 spelling
+//! (`Int32`, `struct`, `delegate`, `interface`, a `static runtimeclass
+//! Apis` holding functions and constants), with the parts MIDL3 has no
+//! syntax for — pointers, packing, import libraries, parameter
+//! annotations — written as C pointers and bracketed attributes.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Write;
+use std::path::PathBuf;
+
+use tracing::debug;
+
+use crate::model::*;
+
+/// Render every partition as an IDL file named after its namespace
+/// (`Foo.Bar.idl`); partitions sharing a namespace share the file.
+pub fn emit_idl(partitions: &[Partition], registry: &TypeRegistry) -> Vec<(PathBuf, String)> {
+    let mut bodies: BTreeMap<&str, String> = BTreeMap::new();
+    for partition in partitions.iter().filter(|p| !p.is_empty()) {
+        let file = File {
+            namespace: &partition.namespace,
+            registry,
+        };
+        file.render(bodies.entry(&partition.namespace).or_default(), partition);
+    }
+    bodies
+        .into_iter()
+        .map(|(namespace, body)| {
+            let mut text =
+                format!("// Generated by bnd-winmd. Do not edit.\n\nnamespace {namespace}\n{{");
+            for line in body.trim_start_matches('\n').lines() {
+                if line.is_empty() {
+                    text.push('\n');
+                } else {
+                    let _ = write!(text, "\n    {line}");
+                }
+            }
+            text.push_str("\n}\n");
+            debug!(namespace, bytes = text.len(), "rendered idl");
+            (PathBuf::from(format!("{namespace}.idl")), text)
+        })
+        .collect()
+}
+
+/// Renders the declarations of one namespace.
+struct File<'a> {
+    namespace: &'a str,
+    registry: &'a TypeRegistry,
+}
+
+impl File<'_> {
+    fn render(&self, out: &mut String, p: &Partition) {
+        for en in &p.enums {
+            render_enum(out, en);
+        }
+        for s in &p.structs {
+            self.render_struct(out, s);
+        }
+        for td in &p.typedefs {
+            self.render_typedef(out, td);
+        }
+        for i in &p.interfaces {
+            self.render_interface(out, i);
+        }
+        if !p.constants.is_empty() || !p.functions.is_empty() {
+            self.render_apis(out, p);
+        }
+    }
+
+    fn render_struct(&self, out: &mut String, s: &StructDef) {
+        out.push('\n');
+        if let Some(packing) = s.packing {
+            let _ = writeln!(out, "[packed({packing})]");
+        }
+        let keyword = if s.is_union { "union" } else { "struct" };
+        let _ = writeln!(out, "{keyword} {}\n{{", s.name);
+        if s.fields.is_empty() {
+            let _ = writeln!(out, "    // opaque, {} bytes", s.size);
+        }
+        for field in &s.fields {
+            for bitfield in &field.native_bitfields {
+                let _ = writeln!(
+                    out,
+                    "    // {}: bits {}..{}",
+                    bitfield.name,
+                    bitfield.offset,
+                    bitfield.offset + bitfield.width
+                );
+            }
+            let _ = writeln!(out, "    {};", self.declarator(&field.ty, &field.name));
+        }
+        out.push_str("};\n");
+    }
+
+    fn render_typedef(&self, out: &mut String, td: &TypedefDef) {
+        out.push('\n');
+        if let Some(free) = &td.raii_free {
+            let _ = writeln!(out, "[free_with({free})]");
+        }
+        let fn_ptr = match &td.underlying_type {
+            CType::Ptr { pointee, .. } => pointee.as_ref(),
+            other => other,
+        };
+        if let CType::FnPtr {
+            return_type,
+            params,
+            calling_convention,
+        } = fn_ptr
+        {
+            if let Some(cc) = callconv(*calling_convention) {
+                let _ = writeln!(out, "[{cc}]");
+            }
+            // Prototype names are only used when there is a distinct one
+            // per parameter.
+            let names = &td.param_names;
+            let unique: BTreeSet<&String> = names.iter().filter(|n| !n.is_empty()).collect();
+            let named = names.len() == params.len() && unique.len() == params.len();
+            let params: Vec<String> = params
+                .iter()
+                .enumerate()
+                .map(|(i, ty)| {
+                    let name = if named {
+                        names[i].clone()
+                    } else {
+                        format!("p{i}")
+                    };
+                    self.declarator(ty, &name)
+                })
+                .collect();
+            let _ = writeln!(
+                out,
+                "delegate {} {}({});",
+                self.type_name(return_type),
+                td.name,
+                params.join(", ")
+            );
+        } else {
+            let _ = writeln!(
+                out,
+                "typedef {};",
+                self.declarator(&td.underlying_type, &td.name)
+            );
+        }
+    }
+
+    fn render_interface(&self, out: &mut String, i: &InterfaceDef) {
+        out.push('\n');
+        if let Some(guid) = &i.guid {
+            let _ = writeln!(out, "[uuid({guid})]");
+        }
+        let _ = writeln!(out, "interface {}\n{{", i.name);
+        for m in &i.methods {
+            let _ = writeln!(
+                out,
+                "    {} {}({});",
+                self.type_name(&m.return_type),
+                m.name,
+                self.params(&m.params).join(", ")
+            );
+        }
+        out.push_str("};\n");
+    }
+
+    /// Constants, then functions, in a static class importing from the
+    /// partition's library.
+    fn render_apis(&self, out: &mut String, p: &Partition) {
+        out.push('\n');
+        if !p.functions.is_empty() {
+            let _ = writeln!(out, "[dllimport(\"{}\")]", p.library);
+        }
+        out.push_str("static runtimeclass Apis\n{\n");
+        for c in &p.constants {
+            let _ = writeln!(out, "    {}", constant(c));
+        }
+        for (i, f) in p.functions.iter().enumerate() {
+            if i > 0 || !p.constants.is_empty() {
+                out.push('\n');
+            }
+            let mut attributes = Vec::new();
+            if let Some(library) = &f.library {
+                attributes.push(format!("dllimport(\"{library}\")"));
+            }
+            if f.entry_point != f.name {
+                attributes.push(format!("entrypoint(\"{}\")", f.entry_point));
+            }
+            if let Some(cc) = callconv(f.calling_convention) {
+                attributes.push(cc.to_string());
+            }
+            if let Some(convention) = f.return_convention {
+                attributes.push(format!("return_convention(\"{}\")", convention.as_str()));
+            }
+            if f.returns_string {
+                attributes.push("string".to_string());
+            }
+            if !attributes.is_empty() {
+                let _ = writeln!(out, "    [{}]", attributes.join(", "));
+            }
+            let _ = writeln!(
+                out,
+                "    static {} {}({});",
+                self.type_name(&f.return_type),
+                f.name,
+                self.params(&f.params).join(", ")
+            );
+        }
+        out.push_str("}\n");
+    }
+
+    /// Parameters with their annotations as MIDL attributes; parameter
+    /// indices in size annotations become names.
+    fn params(&self, params: &[ParamDef]) -> Vec<String> {
+        let name_of = |index: usize| {
+            params
+                .get(index)
+                .map_or_else(|| index.to_string(), |p| p.name.clone())
+        };
+        params
+            .iter()
+            .map(|p| {
+                let a = &p.annotations;
+                let mut attributes = Vec::new();
+                match a.direction {
+                    Some(ParamDirection::In) => attributes.push("in".to_string()),
+                    Some(ParamDirection::Out) => attributes.push("out".to_string()),
+                    Some(ParamDirection::InOut) => attributes.extend(["in".into(), "out".into()]),
+                    None => {}
+                }
+                if a.optional {
+                    attributes.push("optional".to_string());
+                }
+                if p.is_string {
+                    attributes.push("string".to_string());
+                }
+                if let Some(index) = a.array_count {
+                    attributes.push(format!("size_is({})", name_of(index)));
+                }
+                if let Some(index) = a.memory_size {
+                    attributes.push(format!("byte_count({})", name_of(index)));
+                }
+                if a.not_null_terminated {
+                    attributes.push("not_null_terminated".to_string());
+                }
+                if let Some(e) = &p.associated_enum {
+                    attributes.push(format!("associated_enum({e})"));
+                }
+                if let Some(c) = &p.associated_constant {
+                    attributes.push(format!("associated_constant({c})"));
+                }
+                let declarator = self.declarator(&p.ty, &p.name);
+                if attributes.is_empty() {
+                    declarator
+                } else {
+                    format!("[{}] {declarator}", attributes.join(", "))
+                }
+            })
+            .collect()
+    }
+
+    /// `ty name`, with array dimensions after the name as in C.
+    fn declarator(&self, ty: &CType, name: &str) -> String {
+        let mut dims = String::new();
+        let mut element = ty;
+        while let CType::Array {
+            element: inner,
+            len,
+        } = element
+        {
+            let _ = write!(dims, "[{len}]");
+            element = inner;
+        }
+        format!("{} {name}{dims}", self.type_name(element))
+    }
+
+    fn type_name(&self, ty: &CType) -> String {
+        match ty {
+            CType::Void | CType::Forward { .. } => "void".to_string(),
+            CType::Bool => "Boolean".to_string(),
+            CType::I8 => "Int8".to_string(),
+            CType::U8 => "UInt8".to_string(),
+            CType::I16 => "Int16".to_string(),
+            CType::U16 => "UInt16".to_string(),
+            CType::I32 => "Int32".to_string(),
+            CType::U32 => "UInt32".to_string(),
+            CType::I64 => "Int64".to_string(),
+            CType::U64 => "UInt64".to_string(),
+            CType::F32 => "Single".to_string(),
+            CType::F64 => "Double".to_string(),
+            CType::WChar { width: 2 } => "Char".to_string(),
+            CType::WChar { .. } => "UInt32".to_string(),
+            CType::ISize => "IntPtr".to_string(),
+            CType::USize => "UIntPtr".to_string(),
+            // A pointer to a function is the function pointer itself.
+            CType::Ptr { pointee, .. } if matches!(pointee.as_ref(), CType::FnPtr { .. }) => {
+                self.type_name(pointee)
+            }
+            CType::Ptr { pointee, is_const } => {
+                let constness = if *is_const { "const " } else { "" };
+                format!("{constness}{}*", self.type_name(pointee))
+            }
+            CType::Array { element, len } => format!("{}[{len}]", self.type_name(element)),
+            CType::Named { name, resolved } => match resolved {
+                // System typedefs no partition extracted fall back to the
+                // type clang resolved.
+                Some(resolved) if !self.registry.contains(name) => self.type_name(resolved),
+                _ => {
+                    let namespace = self.registry.namespace_for(name, self.namespace);
+                    if namespace == self.namespace {
+                        name.clone()
+                    } else {
+                        format!("{namespace}.{name}")
+                    }
+                }
+            },
+            CType::FnPtr {
+                return_type,
+                params,
+                calling_convention,
+            } => {
+                let params: Vec<String> = params.iter().map(|p| self.type_name(p)).collect();
+                let cc = callconv(*calling_convention).map_or(String::new(), |cc| format!("{cc} "));
+                format!(
+                    "{} ({cc}*)({})",
+                    self.type_name(return_type),
+                    params.join(", ")
+                )
+            }
+        }
+    }
+}
+
+fn render_enum(out: &mut String, en: &EnumDef) {
+    let underlying = match en.underlying_type {
+        CType::I8 => "Int8",
+        CType::U8 => "UInt8",
+        CType::I16 => "Int16",
+        CType::U16 => "UInt16",
+        CType::U32 => "UInt32",
+        CType::I64 => "Int64",
+        CType::U64 => "UInt64",
+        _ => "Int32",
+    };
+    let _ = writeln!(out, "\nenum {} : {underlying}\n{{", en.name);
+    for variant in &en.variants {
+        let _ = writeln!(out, "    {} = {},", variant.name, en.value(variant));
+    }
+    out.push_str("};\n");
+}
+
+fn constant(c: &ConstantDef) -> String {
+    let (ty, value) = match &c.value {
+        ConstantValue::Signed(_) | ConstantValue::Unsigned(_) => {
+            let ty = match c.int_type().expect("integer constant") {
+                CType::I8 => "Int8",
+                CType::U8 => "UInt8",
+                CType::I16 => "Int16",
+                CType::U16 => "UInt16",
+                CType::I32 => "Int32",
+                CType::U32 => "UInt32",
+                CType::I64 => "Int64",
+                _ => "UInt64",
+            };
+            (ty, c.int_value().expect("integer constant").to_string())
+        }
+        ConstantValue::Float(v) => ("Double", format!("{v:?}")),
+        ConstantValue::Guid(g) => ("Guid", format!("{{{g}}}")),
+    };
+    format!("const {ty} {} = {value};", c.name)
+}
+
+/// Attribute for a non-default calling convention.
+fn callconv(calling_convention: CallConv) -> Option<&'static str> {
+    match calling_convention {
+        CallConv::Cdecl => None,
+        CallConv::Stdcall => Some("stdcall"),
+        CallConv::Fastcall => Some("fastcall"),
+    }
+}
@@ -0,0 +1,159 @@
+//! Helpers for reading generated winmd files back — shared by the diff and
+//! dump tools.
+
+use anyhow::{Context, Result};
+use windows_metadata::{
+    ParamAttributes, Type, TypeAttributes, Value,
+    reader::{AsRow, Attribute, File, MethodDef, Row, TypeDef, TypeIndex},
+};
+
+use crate::stamp::STAMP_TYPE;
+
+/// Index an in-memory winmd.
+pub(crate) fn index_bytes(bytes: Vec<u8>) -> Result<TypeIndex> {
+    let file = File::new(bytes).context("not a valid winmd file")?;
+    Ok(TypeIndex::new(vec![file]))
+}
+
+/// Returns `true` for the synthetic `<Module>` type and other entries that
+/// carry no API surface.
+pub(crate) fn is_synthetic(td: &TypeDef) -> bool {
+    td.namespace().is_empty() || td.name() == "<Module>"
+}
+
+/// The synthetic `<BndStamp>` type (see [`crate::stamp`]) of a one-file
+/// index with `type_defs` TypeDef rows. [`TypeIndex`] leaves every type in
+/// the empty namespace out, so the rows are walked directly.
+pub(crate) fn stamp_type(index: &TypeIndex, type_defs: usize) -> Option<TypeDef<'_>> {
+    (0..type_defs)
+        .map(|pos| {
+            TypeDef::from_row(Row {
+                index,
+                file: 0,
+                pos,
+            })
+        })
+        .find(|td| td.namespace().is_empty() && td.name() == STAMP_TYPE)
+}
+
+/// Returns `true` for an `Apis` class, including those of `apis_split` /
+/// `max_methods` shard namespaces: the only non-interface types bnd-winmd
+/// emits that extend `Object`.
+pub(crate) fn is_apis(td: &TypeDef) -> bool {
+    !td.flags().contains(TypeAttributes::Interface)
+        && td
+            .extends()
+            .is_some_and(|e| (e.namespace(), e.name()) == ("System", "Object"))
+}
+
+/// Classify a TypeDef the way bnd-winmd emits it.
+pub(crate) fn type_kind(td: &TypeDef) -> &'static str {
+    if is_apis(td) {
+        return "apis";
+    }
+    if td.flags().contains(TypeAttributes::Interface) {
+        return "interface";
+    }
+    let base = td
+        .extends()
+        .map(|e| (e.namespace().to_string(), e.name().to_string()));
+    match base.as_ref().map(|(ns, name)| (ns.as_str(), name.as_str())) {
+        Some(("System", "Enum")) => "enum",
+        Some(("System", "MulticastDelegate")) => "delegate",
+        Some(("System", "ValueType")) => {
+            if td.flags().contains(TypeAttributes::ExplicitLayout) {
+                "union"
+            } else {
+                "struct"
+            }
+        }
+        _ => "class",
+    }
+}
+
+/// Name of a custom attribute, taken from the type its constructor belongs to.
+pub(crate) fn attribute_name(attr: &Attribute) -> String {
+    attr.ctor().parent().name().to_string()
+}
+
+/// A method signature rendered to text.
+pub(crate) struct MethodSig {
+    /// `(name, type)` per parameter; `Out` params are prefixed `[out] `.
+    pub params: Vec<(String, String)>,
+    pub return_type: String,
+}
+
+/// Render the signature of `method`.
+pub(crate) fn method_sig(method: &MethodDef) -> MethodSig {
+    let sig = method.signature(&[]);
+    let mut params: Vec<(String, String)> = sig
+        .types
+        .iter()
+        .enumerate()
+        .map(|(i, ty)| (format!("param{i}"), type_name(ty)))
+        .collect();
+    for p in method.params() {
+        let seq = p.sequence() as usize;
+        if seq == 0 {
+            continue;
+        }
+        if let Some((name, ty)) = params.get_mut(seq - 1) {
+            *name = p.name().to_string();
+            if p.flags().contains(ParamAttributes::Out) {
+                *ty = format!("[out] {ty}");
+            }
+        }
+    }
+    MethodSig {
+        params,
+        return_type: type_name(&sig.return_type),
+    }
+}
+
+/// Render a metadata type as a short, stable string.
+pub(crate) fn type_name(ty: &Type) -> String {
+    match ty {
+        Type::Void => "void".into(),
+        Type::Bool => "bool".into(),
+        Type::Char => "char".into(),
+        Type::I8 => "i8".into(),
+        Type::U8 => "u8".into(),
+        Type::I16 => "i16".into(),
+        Type::U16 => "u16".into(),
+        Type::I32 => "i32".into(),
+        Type::U32 => "u32".into(),
+        Type::I64 => "i64".into(),
+        Type::U64 => "u64".into(),
+        Type::F32 => "f32".into(),
+        Type::F64 => "f64".into(),
+        Type::ISize => "isize".into(),
+        Type::USize => "usize".into(),
+        Type::String => "string".into(),
+        Type::Name(tn) => format!("{}.{}", tn.namespace, tn.name),
+        Type::PtrMut(inner, depth) => format!("{}{}", "*mut ".repeat(*depth), type_name(inner)),
+        Type::PtrConst(inner, depth) => {
+            format!("{}{}", "*const ".repeat(*depth), type_name(inner))
+        }
+        Type::ArrayFixed(inner, len) => format!("[{}; {len}]", type_name(inner)),
+        other => format!("{other:?}"),
+    }
+}
+
+/// Render a constant value as source text.
+pub(crate) fn value_text(value: &Value) -> String {
+    match value {
+        Value::Bool(v) => v.to_string(),
+        Value::U8(v) => v.to_string(),
+        Value::I8(v) => v.to_string(),
+        Value::U16(v) => v.to_string(),
+        Value::I16(v) => v.to_string(),
+        Value::U32(v) => v.to_string(),
+        Value::I32(v) => v.to_string(),
+        Value::U64(v) => v.to_string(),
+        Value::I64(v) => v.to_string(),
+        Value::F32(v) => v.to_string(),
+        Value::F64(v) => v.to_string(),
+        Value::Utf8(v) => format!("{v:?}"),
+        other => format!("{other:?}"),
+    }
+}
@@ -0,0 +1,147 @@
+//! `interface` config — emit COM-style vtable structs as interfaces.
+
+use std::collections::HashSet;
+
+use anyhow::Result;
+use tracing::{debug, warn};
+
+use crate::diagnostics::{Diagnostic, Diagnostics};
+use crate::model::*;
+
+/// Replace the vtable struct `name` with an [`InterfaceDef`] whose methods
+/// are its function-pointer fields, minus the leading object pointer.
+/// Delegates synthesized for those fields are dropped with the struct.
+pub(crate) fn apply_interface(
+    partitions: &mut [Partition],
+    name: &str,
+    diagnostics: &mut Diagnostics,
+) -> Result<()> {
+    let found = partitions.iter().enumerate().find_map(|(pi, p)| {
+        p.structs
+            .iter()
+            .position(|s| s.name == name)
+            .map(|si| (pi, si))
+    });
+    let Some((pi, si)) = found else {
+        warn!(interface = %name, "interface: struct not found, skipping");
+        diagnostics
+            .push(Diagnostic::new("interface_not_found", "interface: struct not found").name(name));
+        return Ok(());
+    };
+
+    let s = &partitions[pi].structs[si];
+    anyhow::ensure!(!s.is_union, "interface `{name}` is a union");
+    anyhow::ensure!(!s.fields.is_empty(), "interface `{name}` has no fields");
+
+    let mut methods = Vec::with_capacity(s.fields.len());
+    let mut synthesized = HashSet::new();
+    for field in &s.fields {
+        let Some((return_type, params, param_names)) = slot_signature(partitions, &field.ty) else {
+            anyhow::bail!(
+                "interface `{name}`: field `{}` is not a function pointer",
+                field.name
+            );
+        };
+        anyhow::ensure!(
+            matches!(params.first(), Some(CType::Ptr { .. })),
+            "interface `{name}`: `{}` does not take the object pointer as its first parameter",
+            field.name
+        );
+        if let CType::Named { name: delegate, .. } = &field.ty
+            && *delegate == format!("{name}_{}", field.name)
+        {
+            synthesized.insert(delegate.clone());
+        }
+
+        // Prototype names are only trusted when there is one per parameter.
+        let names_usable = param_names.len() == params.len();
+        let params = params
+            .iter()
+            .enumerate()
+            .skip(1)
+            .map(|(i, ty)| ParamDef {
+                name: match param_names.get(i) {
+                    Some(n) if names_usable && !n.is_empty() => n.clone(),
+                    _ => format!("param{}", i - 1),
+                },
+                ty: ty.clone(),
+                associated_enum: None,
+                associated_constant: None,
+                is_string: false,
+                annotations: ParamAnnotations::default(),
+            })
+            .collect();
+        methods.push(InterfaceMethod {
+            name: field.name.clone(),
+            return_type,
+            params,
+        });
+    }
+
+    let partition = &mut partitions[pi];
+    partition.structs.remove(si);
+    partition
+        .typedefs
+        .retain(|td| !synthesized.contains(&td.name));
+    debug!(interface = %name, methods = methods.len(), "promoted vtable struct to interface");
+    partition.interfaces.push(InterfaceDef {
+        name: name.to_string(),
+        methods,
+        guid: None,
+    });
+    Ok(())
+}
+
+/// `(return type, params, param names)` of a function-pointer field, either
+/// inline or through a function-pointer typedef.
+fn slot_signature(
+    partitions: &[Partition],
+    ty: &CType,
+) -> Option<(CType, Vec<CType>, Vec<String>)> {
+    match ty {
+        CType::Ptr { pointee, .. } => match pointee.as_ref() {
+            CType::FnPtr {
+                return_type,
+                params,
+                ..
+            } => Some((return_type.as_ref().clone(), params.clone(), Vec::new())),
+            _ => None,
+        },
+        CType::Named { name, resolved } => {
+            let td = partitions
+                .iter()
+                .flat_map(|p| &p.typedefs)
+                .find(|td| &td.name == name);
+            match td {
+                Some(td) => slot_signature(partitions, &td.underlying_type)
+                    .map(|(ret, params, _)| (ret, params, td.param_names.clone())),
+                None => slot_signature(partitions, resolved.as_deref()?),
+            }
+        }
+        _ => None,
+    }
+}
+
+/// Give each interface the IID of a GUID constant named `IID_<name>`, or
+/// `IID_<name without Vtbl>` for vtable structs named `IFooVtbl`.
+pub(crate) fn attach_guids(partitions: &mut [Partition]) {
+    let guids: Vec<(String, Guid)> = partitions
+        .iter()
+        .flat_map(|p| &p.constants)
+        .filter_map(|c| match c.value {
+            ConstantValue::Guid(g) => Some((c.name.clone(), g)),
+            _ => None,
+        })
+        .collect();
+    if guids.is_empty() {
+        return;
+    }
+    for i in partitions.iter_mut().flat_map(|p| p.interfaces.iter_mut()) {
+        let base = i.name.strip_suffix("Vtbl").unwrap_or(&i.name);
+        let iid = format!("IID_{base}");
+        if let Some((_, g)) = guids.iter().find(|(name, _)| *name == iid) {
+            debug!(interface = %i.name, %iid, "attached interface GUID");
+            i.guid = Some(*g);
+        }
+    }
+}
@@ -0,0 +1,97 @@
+//! Serialized model — the extracted IR as JSON.
+//!
+//! An [`Ir`] holds everything emission needs: the resolved partitions, the
+//! type registry (including types imported from other winmds) and the
+//! [`Stamp`] of the run that extracted it. Written by `--emit-ir`, it can be
+//! checked in as a snapshot, diffed in tests, and emitted again on machines
+//! without libclang or the original headers.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::Path;
+
+use anyhow::{Context, Result, ensure};
+use serde::{Deserialize, Serialize};
+
+use crate::assembly::AssemblyMetadata;
+use crate::model::{Partition, TypeRegistry};
+use crate::stamp::Stamp;
+
+/// Version of the IR layout; bumped whenever the model changes in a way
+/// older IR files can't be read as.
+pub const IR_FORMAT: u32 = 1;
+
+/// The resolved model of a whole config.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Ir {
+    /// [`IR_FORMAT`] of the producing bnd-winmd.
+    pub format: u32,
+    /// Assembly name from `[output]`.
+    pub assembly: String,
+    /// Inputs the IR was extracted from.
+    pub stamp: Stamp,
+    /// Version, company and copyright from `[output]`.
+    #[serde(default)]
+    pub metadata: AssemblyMetadata,
+    /// Partitions in emission order.
+    pub partitions: Vec<Partition>,
+    /// Type name → namespace for every type a reference may resolve to.
+    pub types: BTreeMap<String, String>,
+    /// Registered types that are interfaces.
+    pub interfaces: BTreeSet<String>,
+    /// Imported type name → assembly name of the winmd defining it.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub assemblies: BTreeMap<String, String>,
+}
+
+impl Ir {
+    pub(crate) fn new(
+        assembly: &str,
+        stamp: Stamp,
+        metadata: AssemblyMetadata,
+        partitions: Vec<Partition>,
+        registry: &TypeRegistry,
+    ) -> Self {
+        Self {
+            format: IR_FORMAT,
+            assembly: assembly.to_string(),
+            stamp,
+            metadata,
+            partitions,
+            types: registry.types.clone().into_iter().collect(),
+            interfaces: registry.interfaces.clone().into_iter().collect(),
+            assemblies: registry.assemblies.clone().into_iter().collect(),
+        }
+    }
+
+    /// Rebuild the [`TypeRegistry`] emission resolves references with.
+    pub fn registry(&self) -> TypeRegistry {
+        TypeRegistry {
+            types: self.types.clone().into_iter().collect(),
+            interfaces: self.interfaces.clone().into_iter().collect(),
+            assemblies: self.assemblies.clone().into_iter().collect(),
+        }
+    }
+
+    /// Serialize the IR as pretty-printed JSON. Maps are sorted, so the
+    /// same model always serializes to the same text.
+    pub fn to_json(&self) -> Result<String> {
+        serde_json::to_string_pretty(self).context("serializing IR")
+    }
+
+    /// Write the IR as pretty-printed JSON to `path`.
+    pub fn write_json(&self, path: &Path) -> Result<()> {
+        std::fs::write(path, self.to_json()?)
+            .with_context(|| format!("writing IR to {}", path.display()))
+    }
+
+    /// Parse IR JSON, rejecting other [`IR_FORMAT`] versions.
+    pub fn from_json(json: &str) -> Result<Ir> {
+        let ir: Ir = serde_json::from_str(json).context("parsing IR")?;
+        ensure!(
+            ir.format == IR_FORMAT,
+            "IR format {} is not supported (expected {IR_FORMAT}); re-extract it with this bnd-winmd",
+            ir.format
+        );
+        Ok(ir)
+    }
+}
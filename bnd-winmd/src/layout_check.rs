@@ -0,0 +1,187 @@
+//! Read the emitted winmd back and check every struct's field offsets
+//! against the ones clang reported.
+//!
+//! Offsets are recomputed from the metadata alone with the ECMA-335
+//! sequential layout rules: each field is aligned to its natural alignment
+//! capped by `ClassLayout.PackingSize`, and explicit-layout (union) fields
+//! all sit at offset 0. A mismatch means consumers laying the struct out
+//! from the winmd get a different layout than C — typically a flattened
+//! bitfield group that no longer starts where clang put it.
+
+use std::collections::HashMap;
+
+use anyhow::Result;
+use tracing::{debug, warn};
+use windows_metadata::reader::{self, TypeIndex};
+use windows_metadata::{Type, TypeAttributes};
+
+use crate::config::Severity;
+use crate::diagnostics::{Diagnostic, Diagnostics};
+use crate::model::Partition;
+
+/// `(size, align)` of a type; `None` when it cannot be computed from this
+/// winmd alone (types imported from another winmd).
+type Layout = Option<(usize, usize)>;
+
+/// Compare the layout of every struct in `partitions` as emitted in
+/// `winmd` with clang's offsets and size.
+pub(crate) fn check_layouts(
+    winmd: &[u8],
+    partitions: &[&Partition],
+    severity: Severity,
+    diagnostics: &mut Diagnostics,
+) -> Result<()> {
+    if severity == Severity::Allow {
+        return Ok(());
+    }
+    let file = reader::File::new(winmd.to_vec())
+        .ok_or_else(|| anyhow::anyhow!("emitted winmd could not be read back"))?;
+    let index = TypeIndex::new(vec![file]);
+    // Pointer-sized fields take the width clang reported for the target.
+    let ptr = partitions
+        .iter()
+        .find_map(|p| p.pointer_width)
+        .unwrap_or(std::mem::size_of::<usize>());
+    let mut cx = LayoutCx {
+        index: &index,
+        ptr,
+        cache: HashMap::new(),
+    };
+
+    let mut mismatches = Vec::new();
+    for partition in partitions {
+        for s in partition.structs.iter().filter(|s| s.size > 0) {
+            let Some(td) = index.get(&partition.namespace, &s.name).next() else {
+                continue;
+            };
+            let Some((offsets, size, _)) = cx.struct_layout(td) else {
+                debug!(name = %s.name, "layout check skipped: field of imported type");
+                continue;
+            };
+            let mut found = Vec::new();
+            for (field, winmd_offset) in s.fields.iter().zip(&offsets) {
+                match field.offset {
+                    Some(clang) if clang != *winmd_offset => found.push(format!(
+                        "field `{}` at offset {winmd_offset}, clang has {clang}",
+                        field.name
+                    )),
+                    _ => {}
+                }
+            }
+            if size != s.size {
+                found.push(format!("size {size}, clang has {}", s.size));
+            }
+            for message in found {
+                if severity == Severity::Warn {
+                    warn!(name = %s.name, namespace = %partition.namespace, "layout mismatch: {message}");
+                    diagnostics.push(
+                        Diagnostic::new("layout_mismatch", message.clone())
+                            .name(&s.name)
+                            .namespace(&partition.namespace),
+                    );
+                }
+                mismatches.push(format!(
+                    "\n  • `{}` in `{}`: {message}",
+                    s.name, partition.namespace
+                ));
+            }
+        }
+    }
+
+    if severity == Severity::Error && !mismatches.is_empty() {
+        anyhow::bail!(
+            "{} struct layout mismatch(es) between the winmd and clang:{}",
+            mismatches.len(),
+            mismatches.concat()
+        );
+    }
+    Ok(())
+}
+
+struct LayoutCx<'a> {
+    index: &'a TypeIndex,
+    /// Size and alignment of pointers on the target.
+    ptr: usize,
+    cache: HashMap<(String, String), Layout>,
+}
+
+impl LayoutCx<'_> {
+    /// Field offsets, size and alignment of a struct per ECMA-335.
+    fn struct_layout(&mut self, td: reader::TypeDef) -> Option<(Vec<usize>, usize, usize)> {
+        let packing = td
+            .class_layout()
+            .map(|l| usize::from(l.packing_size()))
+            .filter(|&p| p > 0);
+        let class_size = td.class_layout().map_or(0, |l| l.class_size() as usize);
+        let explicit = td.flags().contains(TypeAttributes::ExplicitLayout);
+
+        let mut offsets = Vec::new();
+        let mut cursor = 0;
+        let mut max_align = 1;
+        for field in td.fields() {
+            let (size, align) = self.type_layout(&field.ty())?;
+            let align = packing.map_or(align, |p| align.min(p));
+            max_align = max_align.max(align);
+            if explicit {
+                offsets.push(0);
+                cursor = cursor.max(size);
+            } else {
+                let offset = cursor.next_multiple_of(align);
+                offsets.push(offset);
+                cursor = offset + size;
+            }
+        }
+        let size = cursor.next_multiple_of(max_align).max(class_size);
+        Some((offsets, size, max_align))
+    }
+
+    fn type_layout(&mut self, ty: &Type) -> Layout {
+        let ptr = self.ptr;
+        Some(match ty {
+            Type::Bool | Type::I8 | Type::U8 => (1, 1),
+            Type::Char | Type::I16 | Type::U16 => (2, 2),
+            Type::I32 | Type::U32 | Type::F32 => (4, 4),
+            Type::I64 | Type::U64 | Type::F64 => (8, 8),
+            Type::ISize
+            | Type::USize
+            | Type::String
+            | Type::Object
+            | Type::PtrMut(..)
+            | Type::PtrConst(..)
+            | Type::RefMut(_)
+            | Type::RefConst(_) => (ptr, ptr),
+            Type::ArrayFixed(element, len) => {
+                let (size, align) = self.type_layout(element)?;
+                (size * len, align)
+            }
+            Type::Name(tn) => return self.named_layout(&tn.namespace, &tn.name),
+            _ => return None,
+        })
+    }
+
+    fn named_layout(&mut self, namespace: &str, name: &str) -> Layout {
+        let key = (namespace.to_string(), name.to_string());
+        if let Some(layout) = self.cache.get(&key) {
+            return *layout;
+        }
+        let ptr = self.ptr;
+        let layout = self.index.get(namespace, name).next().and_then(|td| {
+            if td.flags().contains(TypeAttributes::Interface) {
+                return Some((ptr, ptr));
+            }
+            let base = td
+                .extends()
+                .map(|e| (e.namespace().to_string(), e.name().to_string()));
+            match base.as_ref().map(|(ns, name)| (ns.as_str(), name.as_str())) {
+                Some(("System", "MulticastDelegate")) => Some((ptr, ptr)),
+                Some(("System", "Enum")) => {
+                    let value = td.fields().find(|f| f.name() == "value__")?;
+                    self.type_layout(&value.ty())
+                }
+                _ => self.struct_layout(td).map(|(_, size, align)| (size, align)),
+            }
+        });
+        self.cache.insert(key, layout);
+        layout
+    }
+}
@@ -0,0 +1,121 @@
+//! Generated struct layout tests — see [`LayoutTestsConfig`].
+
+use std::collections::HashMap;
+use std::fmt::Write;
+
+use crate::config::LayoutTestsConfig;
+use crate::model::*;
+
+/// Render one `#[test]` per sized struct asserting its Rust size and
+/// alignment equal the ones clang reported. Opaque (zero-sized) structs
+/// are skipped, and so is the alignment of over-aligned structs (see
+/// [`bindings_aligns`]).
+pub(crate) fn layout_tests_source(cfg: &LayoutTestsConfig, partitions: &[Partition]) -> String {
+    let aligns = bindings_aligns(partitions);
+    let mut out = String::new();
+    out.push_str("// Generated by bnd-winmd from clang's struct layouts. Do not edit.\n");
+    for partition in partitions {
+        for s in partition.structs.iter().filter(|s| s.size > 0) {
+            let ty = if cfg.flat {
+                format!("{}::{}", cfg.path, s.name)
+            } else {
+                format!(
+                    "{}::{}::{}",
+                    cfg.path,
+                    partition.namespace.replace('.', "::"),
+                    s.name
+                )
+            };
+            let test_name = format!(
+                "size_of_{}_{}",
+                partition.namespace.replace('.', "_"),
+                s.name
+            );
+            let _ = write!(
+                out,
+                "\n#[test]\n#[allow(non_snake_case)]\nfn {test_name}() {{\n    assert_eq!(core::mem::size_of::<{ty}>(), {}, \"sizeof({})\");\n",
+                s.size, s.name
+            );
+            if aligns.get(s.name.as_str()) == Some(&s.align) {
+                let _ = writeln!(
+                    out,
+                    "    assert_eq!(core::mem::align_of::<{ty}>(), {}, \"alignof({})\");",
+                    s.align, s.name
+                );
+            } else {
+                let _ = writeln!(
+                    out,
+                    "    // alignof({}) = {} is not checked: over-aligned beyond its fields.",
+                    s.name, s.align
+                );
+            }
+            out.push_str("}\n");
+        }
+    }
+    out
+}
+
+/// Alignment each struct gets in bindings generated from the winmd:
+/// clang's, capped at the alignment its fields give. `ClassLayout` can
+/// lower a struct's alignment (`packed(N)`) but never raise it, so a
+/// struct over-aligned with `__attribute__((aligned(N)))` — or embedding
+/// one — comes out less aligned than in C. Pointers and types that are
+/// not extracted structs count as already aligned.
+fn bindings_aligns(partitions: &[Partition]) -> HashMap<&str, usize> {
+    let structs: HashMap<&str, &StructDef> = partitions
+        .iter()
+        .flat_map(|p| &p.structs)
+        .map(|s| (s.name.as_str(), s))
+        .collect();
+    let mut aligns = HashMap::new();
+    for s in structs.values() {
+        bindings_align(s, &structs, &mut aligns);
+    }
+    aligns
+}
+
+fn bindings_align<'a>(
+    s: &'a StructDef,
+    structs: &HashMap<&str, &'a StructDef>,
+    aligns: &mut HashMap<&'a str, usize>,
+) -> usize {
+    if let Some(&align) = aligns.get(s.name.as_str()) {
+        return align;
+    }
+    fn field_align<'a>(
+        ty: &CType,
+        fallback: usize,
+        structs: &HashMap<&str, &'a StructDef>,
+        aligns: &mut HashMap<&'a str, usize>,
+    ) -> usize {
+        match ty {
+            CType::Bool | CType::I8 | CType::U8 => 1,
+            CType::I16 | CType::U16 => 2,
+            CType::I32 | CType::U32 | CType::F32 => 4,
+            CType::I64 | CType::U64 | CType::F64 => 8,
+            CType::WChar { width } => *width,
+            CType::Array { element, .. } => field_align(element, fallback, structs, aligns),
+            CType::Named {
+                resolved: Some(resolved),
+                ..
+            } => field_align(resolved, fallback, structs, aligns),
+            CType::Named { name, .. } => match structs.get(name.as_str()) {
+                Some(inner) => bindings_align(inner, structs, aligns),
+                None => fallback,
+            },
+            _ => fallback,
+        }
+    }
+    // Seed with clang's value so a cycle (only possible through a
+    // malformed model) terminates.
+    aligns.insert(&s.name, s.align);
+    let natural = s
+        .fields
+        .iter()
+        .map(|f| field_align(&f.ty, s.align, structs, aligns))
+        .max()
+        .unwrap_or(1);
+    let align = s.align.min(natural);
+    aligns.insert(&s.name, align);
+    align
+}
@@ -11,7 +11,7 @@
 //! use std::path::Path;
 //!
 //! // Reads config TOML, parses headers, writes the .winmd file.
-//! bnd_winmd::run(Path::new("bnd-winmd.toml"), None).unwrap();
+//! bnd_winmd::run(Path::new("bnd-winmd.toml"), None, None).unwrap();
 //! ```
 //!
 //! Or get the raw bytes without writing to disk:
@@ -21,83 +21,678 @@
 //!
 //! let winmd_bytes = bnd_winmd::generate(Path::new("bnd-winmd.toml")).unwrap();
 //! ```
+//!
+//! Top-level functions return [`Error`], whose variants name the failing
+//! stage (config, parse, unresolved type, ...) so callers can match on it:
+//!
+//! ```no_run
+//! use std::path::Path;
+//!
+//! match bnd_winmd::generate(Path::new("bnd-winmd.toml")) {
+//!     Ok(bytes) => println!("{} bytes", bytes.len()),
+//!     Err(bnd_winmd::Error::UnresolvedType(e)) => eprintln!("missing traverse entry: {e:#}"),
+//!     Err(e) => panic!("{e}"),
+//! }
+//! ```
 
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::path::{Path, PathBuf};
+use std::time::Instant;
 
-use anyhow::{Context, Result};
+use anyhow::Context;
 use tracing::{debug, info, warn};
 
+use diagnostics::{Diagnostic, Diagnostics};
+pub use error::{Error, Result, SkippedDeclsError};
+pub use model::{
+    CType, CallConv, ConstantDef, ConstantValue, EnumDef, EnumVariant, FieldDef, FunctionDef,
+    InterfaceDef, InterfaceMethod, ParamAnnotations, ParamDef, ParamDirection, Partition,
+    ReturnConvention, StructDef, TypedefDef,
+};
+pub use progress::ProgressSink;
+pub use timings::Timings;
+pub use type_map::TypeMapHook;
+
+mod annotate;
+pub mod assembly;
+pub mod build;
 pub mod config;
+pub mod coverage;
+mod csharp;
+pub mod diagnostics;
+pub mod diff;
+pub mod docs;
+pub mod dump;
 pub mod emit;
+mod enums;
+mod error;
 pub mod extract;
+mod group;
+mod idl;
+mod inspect;
+mod interface;
+pub mod ir;
+mod layout_check;
+mod layout_tests;
 pub mod model;
+pub mod pipeline;
+pub mod plan;
+pub mod progress;
+mod raii;
+mod rename;
+pub mod report;
+mod rust_sys;
+mod shim;
+pub mod stamp;
+mod tables;
+pub mod testing;
+pub mod timings;
+pub mod type_map;
+pub mod watch;
+
+/// Name of a `[profile.<name>]` section of the config to apply on top of
+/// the base config (see [`config::ProfileConfig`]).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Profile<'a>(pub &'a str);
 
 /// Run the full pipeline: load config, parse C headers, emit WinMD, and write
 /// the output file.
 ///
 /// `config_path` is the path to a `bnd-winmd.toml` configuration file.  
+/// `profile` optionally selects a `[profile.<name>]` section of it.  
 /// `output` optionally overrides the output file path from the config.
 ///
 /// This is the top-level entry point intended for use in `build.rs` scripts
 /// or other programmatic callers that want the complete generate-and-write
 /// workflow in a single call.
 ///
-/// Returns the path the `.winmd` file was written to.
-pub fn run(config_path: &Path, output: Option<&Path>) -> Result<PathBuf> {
-    let cfg = config::load_config(config_path)
-        .with_context(|| format!("loading config from {}", config_path.display()))?;
-
+/// Returns the path the `[output]` `.winmd` file was written to. Every
+/// `[[assembly]]` winmd is written too: to its `file` under the config
+/// directory, or next to `output` when that is given.
+pub fn run(
+    config_path: &Path,
+    profile: Option<Profile<'_>>,
+    output: Option<&Path>,
+) -> Result<PathBuf> {
+    let cfg = load_config_profile(config_path, profile.map(|p| p.0))?;
     let base_dir = config_path.parent().unwrap_or_else(|| Path::new("."));
+    Ok(write_winmd(&cfg, base_dir, output)?.winmds.remove(0))
+}
 
-    let winmd_bytes = generate_from_config(&cfg, base_dir)?;
+/// Files written by [`write_winmd`] and the files read to produce them.
+struct Written {
+    /// Path of every winmd written, `[output]` first.
+    winmds: Vec<PathBuf>,
+    /// As [`GenerateOutput::inputs`].
+    inputs: Vec<PathBuf>,
+}
+
+/// Generate from a loaded config and write the winmds (plus the shim
+/// source and layout tests, if configured). `output` overrides `[output]
+/// file`.
+fn write_winmd(cfg: &config::Config, base_dir: &Path, output: Option<&Path>) -> Result<Written> {
+    let Model {
+        partitions,
+        registry,
+        origins,
+        mut diagnostics,
+        mut timings,
+        inputs,
+    } = model_from_config(cfg, base_dir)?;
+    let (winmd_bytes, assemblies) = emit_checked(
+        cfg,
+        base_dir,
+        &partitions,
+        &registry,
+        &origins,
+        &mut diagnostics,
+        &mut timings,
+    )?;
+    timings.log();
 
     let output_path = match output {
         Some(p) => p.to_path_buf(),
         None => base_dir.join(&cfg.output.file),
     };
-    std::fs::write(&output_path, &winmd_bytes)
-        .with_context(|| format!("writing output to {}", output_path.display()))?;
+    let mut files = vec![(output_path.clone(), &winmd_bytes)];
+    for assembly in &assemblies {
+        files.push((assembly.path(base_dir, output), &assembly.bytes));
+    }
+    let mut written = Vec::new();
+    for (path, bytes) in files {
+        std::fs::write(&path, bytes)
+            .with_context(|| format!("writing output to {}", path.display()))
+            .map_err(Error::Io)?;
+        info!(path = %path.display(), size = bytes.len(), "wrote winmd");
+        written.push(path);
+    }
 
-    info!(
-        path = %output_path.display(),
-        size = winmd_bytes.len(),
-        "wrote winmd"
-    );
+    // The shim source lands next to the winmd so build scripts can compile
+    // it from OUT_DIR.
+    if let Some(shim) = &cfg.shim {
+        let source = shim::shim_source(cfg, &partitions, base_dir);
+        let shim_path = output_path
+            .parent()
+            .unwrap_or_else(|| Path::new("."))
+            .join(&shim.source);
+        std::fs::write(&shim_path, source)
+            .with_context(|| format!("writing shim source to {}", shim_path.display()))
+            .map_err(Error::Io)?;
+        info!(path = %shim_path.display(), "wrote shim source");
+    }
+
+    if let Some(layout) = &cfg.layout_tests {
+        let source = layout_tests::layout_tests_source(layout, &partitions);
+        let tests_path = output_path
+            .parent()
+            .unwrap_or_else(|| Path::new("."))
+            .join(&layout.file);
+        std::fs::write(&tests_path, source)
+            .with_context(|| format!("writing layout tests to {}", tests_path.display()))
+            .map_err(Error::Io)?;
+        info!(path = %tests_path.display(), "wrote layout tests");
+    }
 
-    Ok(output_path)
+    Ok(Written {
+        winmds: written,
+        inputs,
+    })
 }
 
 /// Parse a `bnd-winmd.toml` config file, extract declarations from the
 /// referenced C headers, and return the generated WinMD bytes without
 /// writing to disk.
 pub fn generate(config_path: &Path) -> Result<Vec<u8>> {
-    let cfg = config::load_config(config_path)
-        .with_context(|| format!("loading config from {}", config_path.display()))?;
-
+    let cfg = load_config(config_path)?;
     let base_dir = config_path.parent().unwrap_or_else(|| Path::new("."));
 
-    generate_from_config(&cfg, base_dir)
+    Ok(generate_from_config(&cfg, base_dir)?.bytes)
 }
 
 /// Validate a config by running extraction, type-reference checks,
 /// and winmd generation without writing the output file. Returns
 /// Ok(()) if all checks pass. Pipeline logs provide partition stats.
 pub fn validate(config_path: &Path) -> Result<()> {
-    let cfg = config::load_config(config_path)
-        .with_context(|| format!("loading config from {}", config_path.display()))?;
-
+    let cfg = load_config(config_path)?;
     let base_dir = config_path.parent().unwrap_or_else(|| Path::new("."));
 
-    let _bytes = generate_from_config(&cfg, base_dir)?;
+    let _output = generate_from_config(&cfg, base_dir)?;
     info!("validation passed");
     Ok(())
 }
 
+/// Parse a `bnd-winmd.toml` config file and return a summary of the
+/// extracted API surface (see [`report::ApiReport`]) without emitting a
+/// winmd.
+pub fn report(config_path: &Path) -> Result<report::ApiReport> {
+    let cfg = load_config(config_path)?;
+    let base_dir = config_path.parent().unwrap_or_else(|| Path::new("."));
+
+    report_from_config(&cfg, base_dir)
+}
+
+/// Parse a `bnd-winmd.toml` config file and return the extracted model:
+/// one [`Partition`] per namespace, after every model pass (injection,
+/// renames, dedup, ...) and validation — exactly what would be emitted,
+/// in emission order. For tools that want the clang-independent IR
+/// instead of winmd bytes.
+///
+/// ```no_run
+/// use std::path::Path;
+///
+/// for partition in bnd_winmd::extract(Path::new("bnd-winmd.toml")).unwrap() {
+///     for f in &partition.functions {
+///         println!("{}::{}", partition.namespace, f.name);
+///     }
+/// }
+/// ```
+pub fn extract(config_path: &Path) -> Result<Vec<Partition>> {
+    let cfg = load_config(config_path)?;
+    let base_dir = config_path.parent().unwrap_or_else(|| Path::new("."));
+
+    extract_from_config(&cfg, base_dir)
+}
+
+/// Extract the model (see [`extract()`]) from an already-loaded
+/// [`config::Config`].
+pub fn extract_from_config(cfg: &config::Config, base_dir: &Path) -> Result<Vec<Partition>> {
+    let model = model_from_config(cfg, base_dir)?;
+    Ok(model.partitions)
+}
+
+/// Parse a `bnd-winmd.toml` config file and return the resolved model as
+/// an [`ir::Ir`], ready to serialize with [`ir::Ir::to_json`].
+pub fn ir(config_path: &Path) -> Result<ir::Ir> {
+    let cfg = load_config(config_path)?;
+    let base_dir = config_path.parent().unwrap_or_else(|| Path::new("."));
+
+    ir_from_config(&cfg, base_dir)
+}
+
+/// Build an [`ir::Ir`] from an already-loaded [`config::Config`].
+pub fn ir_from_config(cfg: &config::Config, base_dir: &Path) -> Result<ir::Ir> {
+    let model = model_from_config(cfg, base_dir)?;
+    let stamp = stamp::Stamp::compute(cfg, base_dir);
+    let metadata = assembly::AssemblyMetadata::of(&cfg.output);
+    Ok(ir::Ir::new(
+        &cfg.output.name,
+        stamp,
+        metadata,
+        model.partitions,
+        &model.registry,
+    ))
+}
+
+/// Emit a winmd from an IR file written by `--emit-ir` (see [`ir::Ir`]),
+/// without libclang or the original headers. The result is byte-identical
+/// to generating from the config the IR was extracted from, stamp
+/// included.
+///
+/// ```no_run
+/// use std::path::Path;
+///
+/// let bytes = bnd_winmd::emit_from_ir(Path::new("ir.json")).unwrap();
+/// std::fs::write("out.winmd", bytes).unwrap();
+/// ```
+pub fn emit_from_ir(ir_path: &Path) -> Result<Vec<u8>> {
+    let ir = std::fs::read_to_string(ir_path)
+        .map_err(anyhow::Error::from)
+        .and_then(|json| ir::Ir::from_json(&json))
+        .with_context(|| format!("reading IR from {}", ir_path.display()))
+        .map_err(Error::Ir)?;
+    emit_ir(&ir)
+}
+
+/// Emit a winmd from an in-memory [`ir::Ir`]; see [`emit_from_ir`].
+pub fn emit_ir(ir: &ir::Ir) -> Result<Vec<u8>> {
+    let partitions: Vec<&model::Partition> = ir.partitions.iter().collect();
+    let bytes = emit::emit_winmd_stamped(
+        &ir.assembly,
+        &partitions,
+        &ir.registry(),
+        &ir.stamp,
+        &ir.metadata,
+    )
+    .map_err(Error::Emit)?;
+    info!(assembly = %ir.assembly, size = bytes.len(), "emitted winmd from IR");
+    Ok(bytes)
+}
+
+/// Parse a `bnd-winmd.toml` config file and render the extracted model
+/// directly as Rust `-sys` bindings (see [`emit::emit_rust_sys`]), skipping
+/// the winmd and windows-bindgen.
+///
+/// ```no_run
+/// use std::path::Path;
+///
+/// let tree = bnd_winmd::rust_sys(Path::new("bnd-winmd.toml")).unwrap();
+/// std::fs::write("bindings.rs", tree.to_single_file()).unwrap();
+/// ```
+pub fn rust_sys(config_path: &Path) -> Result<emit::GeneratedTree> {
+    let cfg = load_config(config_path)?;
+    let base_dir = config_path.parent().unwrap_or_else(|| Path::new("."));
+
+    rust_sys_from_config(&cfg, base_dir)
+}
+
+/// Render Rust `-sys` bindings (see [`rust_sys()`]) from an already-loaded
+/// [`config::Config`].
+pub fn rust_sys_from_config(cfg: &config::Config, base_dir: &Path) -> Result<emit::GeneratedTree> {
+    let model = model_from_config(cfg, base_dir)?;
+    Ok(emit::emit_rust_sys(&model.partitions, &model.registry))
+}
+
+/// Parse a `bnd-winmd.toml` config file and render the extracted model as
+/// C# P/Invoke source (see [`emit::emit_csharp`]): one `(file name,
+/// source)` pair per namespace.
+pub fn csharp(config_path: &Path) -> Result<Vec<(PathBuf, String)>> {
+    let cfg = load_config(config_path)?;
+    let base_dir = config_path.parent().unwrap_or_else(|| Path::new("."));
+
+    csharp_from_config(&cfg, base_dir)
+}
+
+/// Render C# source (see [`csharp()`]) from an already-loaded
+/// [`config::Config`].
+pub fn csharp_from_config(cfg: &config::Config, base_dir: &Path) -> Result<Vec<(PathBuf, String)>> {
+    let model = model_from_config(cfg, base_dir)?;
+    Ok(emit::emit_csharp(&model.partitions, &model.registry))
+}
+
+/// Parse a `bnd-winmd.toml` config file and render the extracted model as
+/// MIDL3-like IDL (see [`emit::emit_idl`]): one `(file name, text)` pair
+/// per namespace, for reviewing the API surface without a binary diff.
+pub fn idl(config_path: &Path) -> Result<Vec<(PathBuf, String)>> {
+    let cfg = load_config(config_path)?;
+    let base_dir = config_path.parent().unwrap_or_else(|| Path::new("."));
+
+    idl_from_config(&cfg, base_dir)
+}
+
+/// Render IDL (see [`idl()`]) from an already-loaded [`config::Config`].
+pub fn idl_from_config(cfg: &config::Config, base_dir: &Path) -> Result<Vec<(PathBuf, String)>> {
+    let model = model_from_config(cfg, base_dir)?;
+    Ok(emit::emit_idl(&model.partitions, &model.registry))
+}
+
+/// Parse a `bnd-winmd.toml` config file and list the namespaces, types,
+/// functions and constants it would emit (see [`plan::Plan`]). Runs
+/// extraction and validation but not emission.
+pub fn plan(config_path: &Path) -> Result<plan::Plan> {
+    let cfg = load_config(config_path)?;
+    let base_dir = config_path.parent().unwrap_or_else(|| Path::new("."));
+
+    plan_from_config(&cfg, base_dir)
+}
+
+/// Build a [`plan::Plan`] from an already-loaded [`config::Config`].
+pub fn plan_from_config(cfg: &config::Config, base_dir: &Path) -> Result<plan::Plan> {
+    let model = model_from_config(cfg, base_dir)?;
+    Ok(plan::Plan::new(&cfg.output.name, &model.partitions))
+}
+
+/// Build an [`report::ApiReport`] from an already-loaded [`config::Config`].
+pub fn report_from_config(cfg: &config::Config, base_dir: &Path) -> Result<report::ApiReport> {
+    let model = model_from_config(cfg, base_dir)?;
+    Ok(report::ApiReport::new(&cfg.output.name, &model.partitions))
+}
+
+/// Parse a `bnd-winmd.toml` config file and compare, per partition, the
+/// declarations found in its traverse scope with those emitted (see
+/// [`coverage::CoverageReport`]).
+pub fn coverage(config_path: &Path) -> Result<coverage::CoverageReport> {
+    let cfg = load_config(config_path)?;
+    let base_dir = config_path.parent().unwrap_or_else(|| Path::new("."));
+
+    coverage_from_config(&cfg, base_dir)
+}
+
+/// Build a [`coverage::CoverageReport`] from an already-loaded
+/// [`config::Config`].
+pub fn coverage_from_config(
+    cfg: &config::Config,
+    base_dir: &Path,
+) -> Result<coverage::CoverageReport> {
+    let model = model_from_config(cfg, base_dir)?;
+    Ok(coverage::CoverageReport::new(
+        &cfg.output.name,
+        &model.partitions,
+    ))
+}
+
+/// Compare two winmd files and classify every metadata difference as
+/// breaking or additive (see [`diff::DiffReport`]).
+pub fn diff(old_winmd: &Path, new_winmd: &Path) -> Result<diff::DiffReport> {
+    diff::diff_files(old_winmd, new_winmd).map_err(Error::Winmd)
+}
+
+/// Render a winmd file as a human-readable listing of its types, fields,
+/// constants, method signatures, imports and attributes.
+pub fn dump(winmd: &Path) -> Result<String> {
+    dump::dump_file(winmd).map_err(Error::Winmd)
+}
+
+/// Generate from `config_path` now and again whenever a file it reads
+/// changes (the config and any config it extends, every header clang
+/// reached, imported winmds), passing each result (with an API diff
+/// against the previous one) to `on_change`. Generation and diff failures
+/// are reported to the callback rather than ending the watch; returns
+/// when the callback breaks.
+///
+/// ```no_run
+/// use std::ops::ControlFlow;
+/// use std::path::Path;
+///
+/// bnd_winmd::watch(Path::new("bnd-winmd.toml"), |event| {
+///     match (&event.output, &event.diff) {
+///         (Ok(_), Some(Ok(diff))) => print!("{diff}"),
+///         (Ok(out), _) => println!("{} bytes", out.bytes.len()),
+///         (Err(e), _) => eprintln!("{e}"),
+///     }
+///     ControlFlow::Continue(())
+/// })
+/// .unwrap();
+/// ```
+pub fn watch(
+    config_path: &Path,
+    on_change: impl FnMut(&watch::Regenerated) -> std::ops::ControlFlow<()>,
+) -> Result<()> {
+    watch::watch(config_path, on_change)
+}
+
+/// Read the [`stamp::Stamp`] recording the bnd-winmd version, config and
+/// header hashes that produced a winmd file.
+pub fn stamp(winmd: &Path) -> Result<stamp::Stamp> {
+    stamp::read(winmd).map_err(Error::Winmd)
+}
+
+/// Load `config_path`, classifying failures as [`Error::Config`].
+fn load_config(config_path: &Path) -> Result<config::Config> {
+    load_config_profile(config_path, None)
+}
+
+/// Load `config_path` with `profile` applied, classifying failures as
+/// [`Error::Config`].
+fn load_config_profile(config_path: &Path, profile: Option<&str>) -> Result<config::Config> {
+    config::load_config_profile(config_path, profile)
+        .with_context(|| format!("loading config from {}", config_path.display()))
+        .map_err(Error::Config)
+}
+
 /// Generate WinMD bytes from an already-loaded [`config::Config`].
 ///
 /// `base_dir` is the directory relative to which header paths in the config
 /// are resolved (typically the parent directory of the TOML file).
-pub fn generate_from_config(cfg: &config::Config, base_dir: &Path) -> Result<Vec<u8>> {
+pub fn generate_from_config(cfg: &config::Config, base_dir: &Path) -> Result<GenerateOutput> {
+    let Model {
+        partitions,
+        registry,
+        origins,
+        mut diagnostics,
+        mut timings,
+        inputs,
+    } = model_from_config(cfg, base_dir)?;
+
+    // Emit winmd
+    let (winmd_bytes, assemblies) = emit_checked(
+        cfg,
+        base_dir,
+        &partitions,
+        &registry,
+        &origins,
+        &mut diagnostics,
+        &mut timings,
+    )?;
+    timings.log();
+
+    info!(
+        size = winmd_bytes.len(),
+        assemblies = assemblies.len(),
+        warnings = diagnostics.len(),
+        "generated winmd"
+    );
+
+    Ok(GenerateOutput {
+        bytes: winmd_bytes,
+        assemblies,
+        diagnostics,
+        timings,
+        inputs,
+    })
+}
+
+/// Emit the stamped winmd of `[output]` and of every `[[assembly]]`, each
+/// from its own partitions, then read them back and check their struct
+/// layouts against clang's (`layout_mismatch`). A partition belongs to the
+/// assembly listing its `[[partition]]` of origin (see [`Model::origins`]).
+fn emit_checked(
+    cfg: &config::Config,
+    base_dir: &Path,
+    partitions: &[model::Partition],
+    registry: &model::TypeRegistry,
+    origins: &BTreeMap<String, String>,
+    diagnostics: &mut Diagnostics,
+    timings: &mut Timings,
+) -> Result<(Vec<u8>, Vec<AssemblyOutput>)> {
+    let stamp = stamp::Stamp::compute(cfg, base_dir);
+    let metadata = assembly::AssemblyMetadata::of(&cfg.output);
+    let mut emit = |name: &str, partitions: Vec<&model::Partition>| -> Result<Vec<u8>> {
+        let start = Instant::now();
+        let bytes = timings::timed(&mut timings.emit, || {
+            emit::emit_winmd_stamped(name, &partitions, registry, &stamp, &metadata)
+        })
+        .map_err(Error::Emit)?;
+        timings::timed(&mut timings.validation, || {
+            layout_check::check_layouts(&bytes, &partitions, cfg.layout_mismatch, diagnostics)
+        })
+        .map_err(Error::LayoutMismatch)?;
+        if let Some(progress) = &cfg.progress {
+            progress.on_emit_done(name, bytes.len(), start.elapsed());
+        }
+        Ok(bytes)
+    };
+
+    fn origin<'a>(origins: &'a BTreeMap<String, String>, p: &'a model::Partition) -> &'a str {
+        origins
+            .get(&p.namespace)
+            .map_or(p.namespace.as_str(), String::as_str)
+    }
+    let own = partitions
+        .iter()
+        .filter(|p| cfg.assembly_of(origin(origins, p)).is_none())
+        .collect();
+    let bytes = emit(&cfg.output.name, own)?;
+    let mut assemblies = Vec::new();
+    for assembly in &cfg.assembly {
+        let own = partitions
+            .iter()
+            .filter(|p| {
+                assembly
+                    .partitions
+                    .iter()
+                    .any(|ns| ns == origin(origins, p))
+            })
+            .collect();
+        assemblies.push(AssemblyOutput {
+            name: assembly.name.clone(),
+            file: assembly.file.clone(),
+            bytes: emit(&assembly.name, own)?,
+        });
+    }
+    Ok((bytes, assemblies))
+}
+
+/// The resolved model of a config: extraction and every model pass, up to
+/// (but not including) emission. Every backend renders from this.
+#[derive(Debug)]
+pub struct Model {
+    /// One partition per namespace, in emission order.
+    pub partitions: Vec<Partition>,
+    /// Where each type name lives.
+    pub registry: model::TypeRegistry,
+    /// The `[[partition]]` namespace each partition was derived from, for
+    /// those whose namespace is not a configured one: C++ namespaces,
+    /// `namespace_from_path` directories, `overrides` and
+    /// `group_by_handle` groups.
+    pub origins: BTreeMap<String, String>,
+    /// Warnings from extraction and the model passes, in the order raised.
+    pub diagnostics: Diagnostics,
+    /// Wall time per pipeline stage so far.
+    pub timings: Timings,
+    /// As [`GenerateOutput::inputs`].
+    pub inputs: Vec<PathBuf>,
+}
+
+/// Result of [`generate_from_config`]: the winmd plus every warning raised
+/// while producing it.
+#[derive(Debug)]
+pub struct GenerateOutput {
+    /// The `[output]` `.winmd` file contents.
+    pub bytes: Vec<u8>,
+    /// One winmd per `[[assembly]]`, in config order.
+    pub assemblies: Vec<AssemblyOutput>,
+    /// Warnings from extraction and the model passes, in the order raised.
+    pub diagnostics: Diagnostics,
+    /// Wall time per pipeline stage.
+    pub timings: Timings,
+    /// Every file generation read, sorted: the config and the files it
+    /// `extends`, each header clang opened (wrappers, `extra_source` and
+    /// headers included from system directories too) and the imported
+    /// winmds. For `cargo:rerun-if-changed` lines or a Makefile's
+    /// dependency list.
+    pub inputs: Vec<PathBuf>,
+}
+
+/// A winmd generated for an `[[assembly]]` section.
+#[derive(Debug)]
+pub struct AssemblyOutput {
+    /// Assembly name written into the winmd.
+    pub name: String,
+    /// `file` from the config, relative to the config directory.
+    pub file: PathBuf,
+    /// The `.winmd` file contents.
+    pub bytes: Vec<u8>,
+}
+
+impl AssemblyOutput {
+    /// Where [`run`] writes this winmd: `file` under `base_dir`, or its
+    /// file name next to `output` when the `[output]` path is overridden.
+    pub fn path(&self, base_dir: &Path, output: Option<&Path>) -> PathBuf {
+        match output {
+            Some(p) => p
+                .parent()
+                .unwrap_or_else(|| Path::new("."))
+                .join(self.file.file_name().unwrap_or(self.file.as_os_str())),
+            None => base_dir.join(&self.file),
+        }
+    }
+}
+
+/// Held for the life of each `Clang` instance: libclang allows only one
+/// per process, so concurrent generations (parallel tests, a build tool
+/// running several configs) take turns instead of failing.
+fn lock_clang() -> std::sync::MutexGuard<'static, ()> {
+    static CLANG: std::sync::Mutex<()> = std::sync::Mutex::new(());
+    CLANG
+        .lock()
+        .unwrap_or_else(std::sync::PoisonError::into_inner)
+}
+
+/// Extract and resolve the model of an already-loaded [`config::Config`]
+/// (see [`Model`]).
+pub fn model_from_config(cfg: &config::Config, base_dir: &Path) -> Result<Model> {
+    let mut diagnostics = Diagnostics::new();
+    let mut timings = Timings::default();
+    let mut inputs = cfg.input_files(base_dir);
+    let (partitions, registry, origins) =
+        extract_and_resolve(cfg, base_dir, &mut diagnostics, &mut timings, &mut inputs)?;
+    inputs.sort();
+    inputs.dedup();
+    Ok(Model {
+        partitions,
+        registry,
+        origins,
+        diagnostics,
+        timings,
+        inputs,
+    })
+}
+
+/// Run extraction and every model pass up to (but not including) emission:
+/// injection, config-driven annotations, registry construction, dedup and
+/// type-reference validation. Every file clang read is added to `read`.
+/// Also returns [`Model::origins`].
+fn extract_and_resolve(
+    cfg: &config::Config,
+    base_dir: &Path,
+    diagnostics: &mut Diagnostics,
+    timings: &mut Timings,
+    read: &mut Vec<PathBuf>,
+) -> Result<(
+    Vec<model::Partition>,
+    model::TypeRegistry,
+    BTreeMap<String, String>,
+)> {
     info!(
         assembly = %cfg.output.name,
         partitions = cfg.partition.len(),
@@ -105,37 +700,124 @@ pub fn generate_from_config(cfg: &config::Config, base_dir: &Path) -> Result<Vec
     );
 
     // Initialize clang
-    let clang =
-        clang::Clang::new().map_err(|e| anyhow::anyhow!("failed to initialize libclang: {e}"))?;
+    let _clang_lock = lock_clang();
+    let clang = clang::Clang::new()
+        .map_err(|e| Error::Parse(anyhow::anyhow!("failed to initialize libclang: {e}")))?;
     let index = clang::Index::new(&clang, false, false);
 
-    // Extract all partitions
+    let type_map = type_map::TypeMap::new(&cfg.type_map, cfg.type_map_hook.as_deref())
+        .map_err(Error::Config)?;
+    let hook = (!type_map.is_empty()).then_some(&type_map as &dyn TypeMapHook);
+
+    // Extract all partitions. Partitions with the same parse input share
+    // one translation unit, dropped after the last of them.
     let mut partitions = Vec::new();
-    for partition_cfg in &cfg.partition {
-        let partition = extract::extract_partition(
-            &index,
+    let progress = cfg.progress.as_deref();
+    let scratch_dir = cfg.resolved_scratch_dir();
+    let mut inputs = parse_inputs(cfg, &scratch_dir, base_dir).map_err(Error::Io)?;
+    if !cfg.pch.is_empty() {
+        let start = Instant::now();
+        let mut built: HashMap<Vec<String>, PathBuf> = HashMap::new();
+        for input in &mut inputs {
+            if !built.contains_key(&input.args) {
+                let name = format!("{}_{}", cfg.output.name, built.len());
+                let pch =
+                    extract::build_pch(&index, &name, cfg, &input.args, base_dir, diagnostics)
+                        .map_err(Error::Parse)?;
+                built.insert(input.args.clone(), pch);
+            }
+            let pch = built[&input.args].display().to_string();
+            input.args.extend(["-include-pch".to_string(), pch]);
+        }
+        timings.precompile = start.elapsed();
+    }
+    let mut tus = HashMap::new();
+    let mut origins = BTreeMap::new();
+    for (i, (partition_cfg, input)) in cfg.partition.iter().zip(&inputs).enumerate() {
+        if let Some(progress) = progress {
+            progress.on_partition_start(i, cfg.partition.len(), &partition_cfg.namespace);
+        }
+        let start = Instant::now();
+        if !tus.contains_key(input) {
+            let tu = input
+                .parse(
+                    &index,
+                    &partition_cfg.namespace,
+                    &scratch_dir,
+                    base_dir,
+                    &cfg.include_paths,
+                )
+                .map_err(Error::Parse)?;
+            extract::check_clang_diagnostics(
+                &tu,
+                &partition_cfg.namespace,
+                cfg.clang_errors,
+                diagnostics,
+            )
+            .map_err(Error::Parse)?;
+            read.extend(extract::files_read(&tu));
+            tus.insert(input, tu);
+        } else {
+            debug!(namespace = %partition_cfg.namespace, "reusing translation unit");
+        }
+        let parse = start.elapsed();
+        let extracted = extract::extract_from_tu(
+            &tus[input],
             partition_cfg,
             base_dir,
             &cfg.include_paths,
-            &cfg.clang_args,
-            &cfg.namespace_overrides,
-        )?;
-        partitions.push(partition);
+            hook,
+            diagnostics,
+        )
+        .map_err(Error::Parse)?;
+        if !inputs[i + 1..].contains(input) {
+            tus.remove(input);
+        }
+        timings.partitions.push(timings::PartitionTiming {
+            namespace: partition_cfg.namespace.clone(),
+            parse,
+            extract: start.elapsed().saturating_sub(parse),
+        });
+        if let Some(progress) = progress {
+            let counts = progress::PartitionCounts::of(&extracted);
+            progress.on_partition_done(&partition_cfg.namespace, &counts, start.elapsed());
+        }
+        for p in &extracted {
+            if cfg.partition.iter().all(|c| c.namespace != p.namespace) {
+                origins
+                    .entry(p.namespace.clone())
+                    .or_insert_with(|| partition_cfg.namespace.clone());
+            }
+        }
+        partitions.extend(extracted);
     }
+    let mut stage = Instant::now();
+
+    for name in type_map.unmatched() {
+        warn!(name = %name, "type_map: no type spelled this way, skipping");
+        diagnostics.push(
+            Diagnostic::new("type_map_unmatched", "type_map: no type spelled this way").name(name),
+        );
+    }
+
+    check_unmatched_traverse(cfg, diagnostics)?;
+    check_skipped(cfg, &partitions)?;
 
     // Feature #1: Warn when a partition extracts nothing — catches
     // misconfigured headers/traverse paths immediately.
     for p in &partitions {
-        if p.structs.is_empty()
-            && p.enums.is_empty()
-            && p.functions.is_empty()
-            && p.typedefs.is_empty()
-            && p.constants.is_empty()
-        {
+        if p.is_empty() {
             warn!(
                 namespace = %p.namespace,
                 "partition extracted 0 types — check headers and traverse paths"
             );
+            diagnostics.push(
+                Diagnostic::new(
+                    "partition_empty",
+                    "partition extracted 0 types — check headers and traverse paths",
+                )
+                .namespace(&p.namespace),
+            );
         }
     }
 
@@ -150,13 +832,94 @@ pub fn generate_from_config(cfg: &config::Config, base_dir: &Path) -> Result<Vec
                 name = %inj.name,
                 "inject_type: no matching partition, skipping"
             );
+            diagnostics.push(
+                Diagnostic::new("inject_no_partition", "inject_type: no matching partition")
+                    .name(&inj.name)
+                    .namespace(&inj.namespace),
+            );
             continue;
         };
-        merge_injected_type(partition, inj)?;
+        merge_injected_type(partition, inj).map_err(Error::Config)?;
+    }
+
+    // A struct one partition only forward-declares may be defined by
+    // another; point the references at that definition.
+    resolve_forward_declarations(&mut partitions);
+
+    // Group `#define` families into enums before parameter associations
+    // so they can reference the synthesized enums.
+    for synth in &cfg.synthesize_enum {
+        enums::synthesize_enum(&mut partitions, synth, diagnostics).map_err(Error::Config)?;
+    }
+
+    enums::apply_enum_types(&mut partitions, &cfg.enum_type, diagnostics).map_err(Error::Config)?;
+    enums::apply_constant_types(&mut partitions, &cfg.constant_type, diagnostics)
+        .map_err(Error::Config)?;
+
+    // Annotate handle typedefs with their free function. Runs after
+    // injection so injected typedefs can be RAII handles too.
+    for pair in &cfg.raii_free {
+        raii::apply_raii_free(&mut partitions, pair, diagnostics);
+    }
+
+    for assoc in &cfg.param_association {
+        annotate::apply_param_association(&mut partitions, assoc, diagnostics)
+            .map_err(Error::Config)?;
+    }
+    for attrs in &cfg.param_attributes {
+        annotate::apply_param_attributes(&mut partitions, attrs, diagnostics)
+            .map_err(Error::Config)?;
+    }
+
+    annotate::apply_strings(&mut partitions, cfg, diagnostics);
+    annotate::apply_return_conventions(&mut partitions, &cfg.return_conventions, diagnostics)
+        .map_err(Error::Config)?;
+
+    for name in &cfg.interface {
+        interface::apply_interface(&mut partitions, name, diagnostics).map_err(Error::Config)?;
+    }
+    interface::attach_guids(&mut partitions);
+
+    if let Some(shim) = &cfg.shim {
+        shim::apply_shims(&mut partitions, shim, diagnostics);
+    }
+
+    // Renames run last among the model passes: config above refers to C
+    // names, while the registry and emission below see the final names.
+    rename::apply_renames(&mut partitions, &cfg.rename).map_err(Error::Config)?;
+    if cfg.group_by_handle {
+        group::group_by_handle(&mut partitions);
+        // A group is `<namespace>.<handle>` of the partition it came from.
+        for p in &partitions {
+            if origins.contains_key(&p.namespace)
+                || cfg.partition.iter().any(|c| c.namespace == p.namespace)
+            {
+                continue;
+            }
+            if let Some((parent, _)) = p.namespace.rsplit_once('.') {
+                let origin = origins.get(parent).map_or(parent, String::as_str);
+                origins.insert(p.namespace.clone(), origin.to_string());
+            }
+        }
     }
+    timings.model_passes += stage.elapsed();
 
     // Build global type registry
+    stage = Instant::now();
     let mut registry = extract::build_type_registry(&partitions, &cfg.namespace_overrides);
+    timings.registry += stage.elapsed();
+
+    timings::timed(&mut timings.validation, || {
+        check_type_conflicts(
+            &partitions,
+            &registry,
+            cfg.allow_duplicate_types,
+            diagnostics,
+        )?;
+        check_delegate_conflicts(&partitions)
+    })
+    .map_err(Error::TypeConflict)?;
+    stage = Instant::now();
 
     let injected_count = cfg.inject_type.len();
 
@@ -166,9 +929,9 @@ pub fn generate_from_config(cfg: &config::Config, base_dir: &Path) -> Result<Vec
     // the registry), but imported types fill in names that are referenced
     // by function signatures but not extracted locally.
     let imported_before = registry.types.len();
-    for ti in &cfg.type_import {
-        let winmd_path = config::resolve_header(&ti.winmd, base_dir, &cfg.include_paths);
-        seed_registry_from_winmd(&mut registry, &winmd_path, &ti.namespace);
+    for (winmd, filter) in cfg.winmd_import_filters() {
+        let winmd_path = config::resolve_header(winmd, base_dir, &cfg.include_paths);
+        seed_registry_from_winmd(&mut registry, &winmd_path, &filter).map_err(Error::Winmd)?;
     }
     let imported_count = registry.types.len() - imported_before;
 
@@ -200,6 +963,14 @@ pub fn generate_from_config(cfg: &config::Config, base_dir: &Path) -> Result<Vec
                     duplicate = partition.namespace,
                     "dropping duplicate typedef (canonical partition wins)"
                 );
+                diagnostics.push(
+                    Diagnostic::new(
+                        "duplicate_dropped",
+                        format!("dropped duplicate typedef; `{canonical_ns}` is canonical"),
+                    )
+                    .name(&td.name)
+                    .namespace(&partition.namespace),
+                );
             }
             !dominated
         });
@@ -214,6 +985,14 @@ pub fn generate_from_config(cfg: &config::Config, base_dir: &Path) -> Result<Vec
                     duplicate = partition.namespace,
                     "dropping duplicate struct (canonical partition wins)"
                 );
+                diagnostics.push(
+                    Diagnostic::new(
+                        "duplicate_dropped",
+                        format!("dropped duplicate struct; `{canonical_ns}` is canonical"),
+                    )
+                    .name(&sd.name)
+                    .namespace(&partition.namespace),
+                );
             }
             !dominated
         });
@@ -225,67 +1004,522 @@ pub fn generate_from_config(cfg: &config::Config, base_dir: &Path) -> Result<Vec
         );
     }
 
+    timings.registry += stage.elapsed();
+
     // Validate that all referenced types are resolvable before emitting.
     // This catches missing traverse headers early with actionable diagnostics
     // instead of a cryptic windows-bindgen "type not found" panic later.
-    validate_type_references(&partitions, &registry)?;
+    timings::timed(&mut timings.validation, || {
+        validate_type_references(&partitions, &registry).map_err(Error::UnresolvedType)?;
+        check_name_collisions(&partitions, &registry, cfg.name_collision, diagnostics)
+            .map_err(Error::NameCollision)
+    })?;
 
-    // Emit winmd
-    let winmd_bytes = emit::emit_winmd(&cfg.output.name, &partitions, &registry)?;
+    // Emission follows the model's order: sort it so the same headers give
+    // byte-identical winmds.
+    for partition in &mut partitions {
+        partition.sort();
+    }
+    partitions.sort_by(|a, b| a.namespace.cmp(&b.namespace));
+
+    Ok((partitions, registry, origins))
+}
+
+/// Replace every [`CType::Forward`](model::CType::Forward) with a
+/// reference to the struct of that name when some partition defines it,
+/// and with `void` otherwise.
+fn resolve_forward_declarations(partitions: &mut [model::Partition]) {
+    fn resolve(ty: &mut model::CType, defined: &HashSet<String>, upgraded: &mut usize) {
+        match ty {
+            model::CType::Forward { name } => {
+                *ty = if defined.contains(name.as_str()) {
+                    debug!(name = %name, "forward declaration resolved to another partition");
+                    *upgraded += 1;
+                    model::CType::Named {
+                        name: std::mem::take(name),
+                        resolved: None,
+                    }
+                } else {
+                    model::CType::Void
+                };
+            }
+            model::CType::Ptr { pointee, .. } => resolve(pointee, defined, upgraded),
+            model::CType::Array { element, .. } => resolve(element, defined, upgraded),
+            model::CType::Named {
+                resolved: Some(resolved),
+                ..
+            } => resolve(resolved, defined, upgraded),
+            model::CType::FnPtr {
+                return_type,
+                params,
+                ..
+            } => {
+                resolve(return_type, defined, upgraded);
+                for p in params {
+                    resolve(p, defined, upgraded);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    let defined: HashSet<String> = partitions
+        .iter()
+        .flat_map(|p| p.structs.iter().map(|s| s.name.clone()))
+        .collect();
+    let mut upgraded = 0;
+    for p in partitions.iter_mut() {
+        for s in &mut p.structs {
+            for field in &mut s.fields {
+                resolve(&mut field.ty, &defined, &mut upgraded);
+            }
+        }
+        for td in &mut p.typedefs {
+            resolve(&mut td.underlying_type, &defined, &mut upgraded);
+        }
+        for f in &mut p.functions {
+            resolve(&mut f.return_type, &defined, &mut upgraded);
+            for param in &mut f.params {
+                resolve(&mut param.ty, &defined, &mut upgraded);
+            }
+        }
+    }
+    if upgraded > 0 {
+        info!(
+            upgraded,
+            "resolved forward-declared struct references across partitions"
+        );
+    }
+}
 
-    info!(size = winmd_bytes.len(), "generated winmd");
+/// The parse input of every partition, in config order. With
+/// `shared_translation_unit`, partitions whose clang arguments (language
+/// and `clang_args` included) match get the union of their headers, so
+/// they parse once.
+fn parse_inputs(
+    cfg: &config::Config,
+    scratch_dir: &Path,
+    base_dir: &Path,
+) -> anyhow::Result<Vec<extract::ParseInput>> {
+    let mut inputs = cfg
+        .partition
+        .iter()
+        .map(|p| {
+            extract::ParseInput::new(
+                p,
+                scratch_dir,
+                base_dir,
+                &cfg.include_paths,
+                &cfg.effective_clang_args(),
+            )
+        })
+        .collect::<anyhow::Result<Vec<_>>>()?;
+    if cfg.shared_translation_unit {
+        let mut headers: HashMap<Vec<String>, Vec<PathBuf>> = HashMap::new();
+        for input in &inputs {
+            let union = headers.entry(input.args.clone()).or_default();
+            for h in &input.headers {
+                if !union.contains(h) {
+                    union.push(h.clone());
+                }
+            }
+        }
+        for input in &mut inputs {
+            input.headers = headers[&input.args].clone();
+        }
+    }
+    Ok(inputs)
+}
+
+/// Fail when several partitions define a function-pointer typedef of the
+/// same name with different signatures. Deduplication keeps one copy, and
+/// unlike a struct layout there is no safe one to pick.
+fn check_delegate_conflicts(partitions: &[model::Partition]) -> anyhow::Result<()> {
+    fn signature(td: &model::TypedefDef) -> Option<&model::CType> {
+        match &td.underlying_type {
+            ty @ model::CType::FnPtr { .. } => Some(ty),
+            model::CType::Ptr { pointee, .. }
+                if matches!(**pointee, model::CType::FnPtr { .. }) =>
+            {
+                Some(pointee)
+            }
+            _ => None,
+        }
+    }
+
+    let mut first: HashMap<&str, (&str, &model::CType)> = HashMap::new();
+    let mut conflicts = Vec::new();
+    for partition in partitions {
+        for td in &partition.typedefs {
+            let Some(sig) = signature(td) else {
+                continue;
+            };
+            let ns = partition.namespace.as_str();
+            match first.get(td.name.as_str()) {
+                None => {
+                    first.insert(&td.name, (ns, sig));
+                }
+                Some(&(first_ns, first_sig)) if first_sig != sig => conflicts.push(format!(
+                    "\n  • `{}` in `{first_ns}` ({}) and `{ns}` ({})",
+                    td.name,
+                    report::c_type_name(first_sig),
+                    report::c_type_name(sig)
+                )),
+                Some(_) => {}
+            }
+        }
+    }
 
-    Ok(winmd_bytes)
+    if !conflicts.is_empty() {
+        anyhow::bail!(
+            "{} delegate(s) defined with different signatures by several partitions:{}\n\
+             Give one a distinct name with [[rename]].",
+            conflicts.len(),
+            conflicts.concat()
+        );
+    }
+    Ok(())
+}
+
+/// Report structs defined with different layouts by several partitions.
+/// Deduplication keeps only the registry's copy, so functions of the other
+/// partitions would silently bind to a layout their headers don't use.
+fn check_type_conflicts(
+    partitions: &[model::Partition],
+    registry: &model::TypeRegistry,
+    allow: bool,
+    diagnostics: &mut Diagnostics,
+) -> anyhow::Result<()> {
+    fn layout(s: &model::StructDef) -> impl PartialEq + '_ {
+        let fields: Vec<_> = s.fields.iter().map(|f| (&f.name, f.offset)).collect();
+        (s.size, s.align, s.packing, s.is_union, fields)
+    }
+    fn describe(s: &model::StructDef) -> String {
+        let fields: Vec<&str> = s.fields.iter().map(|f| f.name.as_str()).collect();
+        format!("{} bytes: {}", s.size, fields.join(", "))
+    }
+
+    // Opaque declarations carry no layout to disagree with.
+    let mut by_name: BTreeMap<&str, Vec<(&str, &model::StructDef)>> = BTreeMap::new();
+    for partition in partitions {
+        for s in partition.structs.iter().filter(|s| s.size > 0) {
+            by_name
+                .entry(&s.name)
+                .or_default()
+                .push((partition.namespace.as_str(), s));
+        }
+    }
+
+    let mut conflicts = Vec::new();
+    for (name, defs) in &by_name {
+        let (first_ns, first) = defs[0];
+        for &(ns, s) in &defs[1..] {
+            if layout(s) == layout(first) {
+                continue;
+            }
+            let kept = registry.namespace_for(name, ns);
+            if allow {
+                warn!(
+                    name = %name,
+                    first = %first_ns,
+                    second = %ns,
+                    kept = %kept,
+                    "struct defined with different layouts; keeping one (allow_duplicate_types)"
+                );
+                diagnostics.push(
+                    Diagnostic::new(
+                        "type_conflict",
+                        format!(
+                            "struct defined with different layouts in `{first_ns}` and `{ns}`; `{kept}` is kept"
+                        ),
+                    )
+                    .name(*name),
+                );
+            }
+            conflicts.push(format!(
+                "\n  • `{name}` in `{first_ns}` ({}) and `{ns}` ({})",
+                describe(first),
+                describe(s)
+            ));
+        }
+    }
+
+    if !allow && !conflicts.is_empty() {
+        anyhow::bail!(
+            "{} struct(s) defined with different layouts by several partitions:{}\n\
+             Give one a distinct name with [[rename]], choose the canonical \
+             namespace with namespace_overrides, or set \
+             allow_duplicate_types = true to keep one definition.",
+            conflicts.len(),
+            conflicts.concat()
+        );
+    }
+    Ok(())
+}
+
+/// Fail with a [`SkippedDeclsError`] if any skipped declaration is of a
+/// kind the config's `strict` / `fail_on` covers.
+fn check_skipped(cfg: &config::Config, partitions: &[model::Partition]) -> Result<()> {
+    let skipped: Vec<(String, model::SkippedDecl)> = partitions
+        .iter()
+        .flat_map(|p| p.skipped.iter().map(move |s| (p, s)))
+        .filter(|(_, s)| {
+            let kind = match s.reason {
+                model::SkipReason::Variadic => config::SkipKind::Variadic,
+                model::SkipReason::Unsupported(_) => config::SkipKind::UnsupportedType,
+                model::SkipReason::UnparsedMacro => config::SkipKind::SkippedMacro,
+                model::SkipReason::OutOfScope { .. } | model::SkipReason::ObjC => return false,
+            };
+            cfg.fails_on(kind)
+        })
+        .map(|(p, s)| (p.namespace.clone(), s.clone()))
+        .collect();
+    if skipped.is_empty() {
+        return Ok(());
+    }
+    Err(SkippedDeclsError { skipped }.into())
+}
+
+/// Fail if a `traverse` entry matched no declaration and the config's
+/// `strict` / `fail_on` covers [`UnmatchedTraverse`](config::SkipKind::UnmatchedTraverse).
+fn check_unmatched_traverse(cfg: &config::Config, diagnostics: &Diagnostics) -> Result<()> {
+    if !cfg.fails_on(config::SkipKind::UnmatchedTraverse) {
+        return Ok(());
+    }
+    let unmatched: Vec<String> = diagnostics
+        .with_code("traverse_unmatched")
+        .map(|d| {
+            format!(
+                "\n  • `{}` in `{}`",
+                d.name.as_deref().unwrap_or_default(),
+                d.namespace.as_deref().unwrap_or_default()
+            )
+        })
+        .collect();
+    if unmatched.is_empty() {
+        return Ok(());
+    }
+    Err(Error::Config(anyhow::anyhow!(
+        "{} traverse path(s) matched no declarations (strict mode):{}",
+        unmatched.len(),
+        unmatched.concat()
+    )))
+}
+
+/// Report functions that share their name with a type in the same
+/// namespace (`struct stat` / `stat()`), suggesting a `[[rename]]` for the
+/// type. Runs after renames, so a configured rename resolves the finding.
+fn check_name_collisions(
+    partitions: &[model::Partition],
+    registry: &model::TypeRegistry,
+    severity: config::Severity,
+    diagnostics: &mut Diagnostics,
+) -> anyhow::Result<()> {
+    if severity == config::Severity::Allow {
+        return Ok(());
+    }
+
+    // What the type is, and the `[[rename]]` kind that renames it:
+    // interfaces are renamed as structs.
+    let type_kind = |p: &model::Partition, name: &str| {
+        if p.structs.iter().any(|s| s.name == name) {
+            Some(("struct", "struct"))
+        } else if p.interfaces.iter().any(|i| i.name == name) {
+            Some(("interface", "struct"))
+        } else if p.enums.iter().any(|e| e.name == name) {
+            Some(("enum", "enum"))
+        } else if p.typedefs.iter().any(|t| t.name == name) {
+            Some(("typedef", "typedef"))
+        } else {
+            None
+        }
+    };
+
+    let mut collisions = Vec::new();
+    for partition in partitions {
+        for f in &partition.functions {
+            if !registry.contains(&f.name)
+                || registry.namespace_for(&f.name, &partition.namespace) != partition.namespace
+            {
+                continue;
+            }
+            // Imported types share the registry but are not ours to rename.
+            let Some((kind, rename_kind)) = type_kind(partition, &f.name) else {
+                continue;
+            };
+            let suggestion = format!(
+                "[[rename]]\nname = \"{0}\"\nkind = \"{rename_kind}\"\nto = \"{0}_t\"",
+                f.name
+            );
+            if severity == config::Severity::Warn {
+                warn!(
+                    name = %f.name,
+                    namespace = %partition.namespace,
+                    kind,
+                    "{kind} and function share a name; consider:\n{suggestion}"
+                );
+                diagnostics.push(
+                    Diagnostic::new(
+                        "name_collision",
+                        format!("{kind} and function share a name; consider:\n{suggestion}"),
+                    )
+                    .name(&f.name)
+                    .namespace(&partition.namespace),
+                );
+            }
+            let article = if matches!(kind, "interface" | "enum") {
+                "an"
+            } else {
+                "a"
+            };
+            collisions.push(format!(
+                "\n  • `{}` is both {article} {kind} and a function in `{}`; add:\n{}",
+                f.name,
+                partition.namespace,
+                suggestion
+                    .lines()
+                    .map(|l| format!("      {l}"))
+                    .collect::<Vec<_>>()
+                    .join("\n")
+            ));
+        }
+    }
+
+    if severity == config::Severity::Error && !collisions.is_empty() {
+        anyhow::bail!(
+            "{} type/function name collision(s) found:{}",
+            collisions.len(),
+            collisions.concat()
+        );
+    }
+    Ok(())
 }
 
 /// Pre-seed the [`TypeRegistry`](model::TypeRegistry) with types from an
-/// external `.winmd` file.  Only types whose namespace starts with
-/// `ns_filter` are imported.
+/// external `.winmd` file.  Types the filter selects are imported, along
+/// with every type their fields and delegate signatures reach in the same
+/// winmd, so an imported struct never refers to a name the registry does
+/// not know.  Excluded names are neither imported nor followed.
 fn seed_registry_from_winmd(
     registry: &mut model::TypeRegistry,
     winmd_path: &Path,
-    ns_filter: &str,
-) {
-    let bytes = std::fs::read(winmd_path).unwrap_or_else(|e| {
-        panic!(
-            "failed to read external winmd {}: {e}\n\
-             Hint: run the upstream gen crate first (e.g. `cargo run -p bnd-posix-gen`)",
+    filter: &config::ImportFilter,
+) -> anyhow::Result<()> {
+    let bytes = std::fs::read(winmd_path).with_context(|| {
+        format!(
+            "reading external winmd {} for `{}` (run the upstream gen crate \
+             first, e.g. `cargo run -p bnd-linux-gen`)",
+            winmd_path.display(),
+            filter.namespace
+        )
+    })?;
+    let assembly = tables::assembly_name(&bytes).with_context(|| {
+        format!(
+            "reading the assembly name of external winmd {}",
             winmd_path.display()
         )
-    });
-    let file = windows_metadata::reader::File::new(bytes)
-        .unwrap_or_else(|| panic!("failed to parse external winmd: {}", winmd_path.display()));
-    let index = windows_metadata::reader::TypeIndex::new(vec![file]);
+    })?;
+    let index = inspect::index_bytes(bytes).with_context(|| {
+        format!(
+            "parsing external winmd {} for `{}` (regenerate it with the upstream gen crate)",
+            winmd_path.display(),
+            filter.namespace
+        )
+    })?;
+    // Skip the synthetic <Module> and Apis classes, and filter by namespace.
+    let mut pending: Vec<(String, String)> = index
+        .types()
+        .filter(|td| {
+            let (ns, name) = (td.namespace(), td.name());
+            !ns.is_empty()
+                && name != "<Module>"
+                && !inspect::is_apis(td)
+                && filter.selects(ns, name)
+        })
+        .map(|td| (td.namespace().to_string(), td.name().to_string()))
+        .collect();
+    for name in filter.types {
+        if !pending.iter().any(|(_, n)| n == name) {
+            warn!(
+                path = %winmd_path.display(),
+                namespace = filter.namespace,
+                name = %name,
+                "type_import names a type the winmd does not define under its namespace"
+            );
+        }
+    }
+    let mut visited = HashSet::new();
     let mut count = 0usize;
-    for td in index.types() {
-        let ns = td.namespace();
-        let name = td.name();
-        // Skip the synthetic <Module> and Apis classes, and filter by namespace.
-        if ns.is_empty() || name == "<Module>" || name == "Apis" {
+    let mut transitive = 0usize;
+    while let Some((ns, name)) = pending.pop() {
+        // Cycles (a struct pointing at itself or at a struct pointing
+        // back) end here.
+        if filter.excludes(&name) || !visited.insert((ns.clone(), name.clone())) {
             continue;
         }
-        if !ns.starts_with(ns_filter) {
+        let Some(td) = index.get(&ns, &name).next() else {
             continue;
+        };
+        for field in td.fields() {
+            referenced_types(&field.ty(), &mut pending);
+        }
+        for method in td.methods() {
+            let sig = method.signature(&[]);
+            referenced_types(&sig.return_type, &mut pending);
+            for ty in &sig.types {
+                referenced_types(ty, &mut pending);
+            }
         }
         // Only insert if not already registered (local types win).
         // When two external namespaces define the same type name (e.g.
         // __sigset_t in posix.signal and posix.pthread), keep the
         // lexicographically smallest namespace for determinism.
-        if !registry.contains(name) {
-            registry.register(name, ns);
+        if !registry.contains(&name) {
+            registry.register(&name, &ns);
+            registry.assemblies.insert(name.clone(), assembly.clone());
             count += 1;
-        } else if registry.namespace_for(name, "").as_str() < ns {
+            if !filter.selects(&ns, &name) {
+                transitive += 1;
+            }
+        } else if registry.namespace_for(&name, "").as_str() < ns.as_str() {
             // Already have a smaller namespace — keep it.
         } else {
-            registry.register(name, ns);
+            registry.register(&name, &ns);
+            registry.assemblies.insert(name.clone(), assembly.clone());
         }
     }
     info!(
         path = %winmd_path.display(),
-        namespace = ns_filter,
+        namespace = filter.namespace,
         imported = count,
+        transitive,
         "pre-seeded type registry from external winmd"
     );
+    Ok(())
+}
+
+/// Push every named type `ty` mentions, through pointers and arrays.
+fn referenced_types(ty: &windows_metadata::Type, out: &mut Vec<(String, String)>) {
+    use windows_metadata::Type;
+    match ty {
+        Type::Name(tn) => {
+            // `System` base types are not part of the external winmd.
+            if tn.namespace != "System" {
+                out.push((tn.namespace.clone(), tn.name.clone()));
+            }
+            for generic in &tn.generics {
+                referenced_types(generic, out);
+            }
+        }
+        Type::Array(inner)
+        | Type::ArrayRef(inner)
+        | Type::ArrayFixed(inner, _)
+        | Type::RefMut(inner)
+        | Type::RefConst(inner)
+        | Type::PtrMut(inner, _)
+        | Type::PtrConst(inner, _) => referenced_types(inner, out),
+        _ => {}
+    }
 }
 
 // ---------------------------------------------------------------------------
@@ -298,7 +1532,7 @@ fn seed_registry_from_winmd(
 fn merge_injected_type(
     partition: &mut model::Partition,
     inj: &config::InjectTypeConfig,
-) -> Result<()> {
+) -> anyhow::Result<()> {
     use config::InjectTypeKind;
 
     match inj.kind {
@@ -307,7 +1541,8 @@ fn merge_injected_type(
                 debug!(name = %inj.name, "inject_type: enum already extracted, skipping");
                 return Ok(());
             }
-            let underlying = parse_underlying(inj.underlying.as_deref(), &inj.name)?;
+            let underlying =
+                enums::parse_underlying(inj.underlying.as_deref(), &inj.name, "inject_type")?;
             let variants = inj
                 .variants
                 .iter()
@@ -329,11 +1564,14 @@ fn merge_injected_type(
                 debug!(name = %inj.name, "inject_type: typedef already extracted, skipping");
                 return Ok(());
             }
-            let underlying = parse_underlying(inj.underlying.as_deref(), &inj.name)?;
+            let underlying =
+                enums::parse_underlying(inj.underlying.as_deref(), &inj.name, "inject_type")?;
             info!(name = %inj.name, "injected typedef into partition {}", partition.namespace);
             partition.typedefs.push(model::TypedefDef {
                 name: inj.name.clone(),
                 underlying_type: underlying,
+                param_names: Vec::new(),
+                raii_free: None,
             });
         }
         InjectTypeKind::Struct => {
@@ -371,12 +1609,16 @@ fn merge_injected_type(
                 },
                 bitfield_width: None,
                 bitfield_offset: None,
+                offset: None,
+                size: Some(size),
+                native_bitfields: Vec::new(),
             }];
             info!(name = %inj.name, size, align, "injected struct into partition {}", partition.namespace);
             partition.structs.push(model::StructDef {
                 name: inj.name.clone(),
                 size,
                 align,
+                packing: None,
                 fields,
                 is_union: false,
             });
@@ -385,27 +1627,6 @@ fn merge_injected_type(
     Ok(())
 }
 
-/// Parse an `underlying` string (e.g. `"u8"`, `"i32"`) into a `CType`.
-fn parse_underlying(underlying: Option<&str>, type_name: &str) -> Result<model::CType> {
-    let s = underlying.ok_or_else(|| {
-        anyhow::anyhow!("inject_type: `{}` requires `underlying` field", type_name)
-    })?;
-    match s {
-        "i8" => Ok(model::CType::I8),
-        "u8" => Ok(model::CType::U8),
-        "i16" => Ok(model::CType::I16),
-        "u16" => Ok(model::CType::U16),
-        "i32" => Ok(model::CType::I32),
-        "u32" => Ok(model::CType::U32),
-        "i64" => Ok(model::CType::I64),
-        "u64" => Ok(model::CType::U64),
-        other => anyhow::bail!(
-            "inject_type: unsupported underlying type `{other}` for `{type_name}` \
-             (expected i8, u8, i16, u16, i32, u32, i64, u64)"
-        ),
-    }
-}
-
 // ---------------------------------------------------------------------------
 // Type-reference validation
 // ---------------------------------------------------------------------------
@@ -426,7 +1647,7 @@ struct UnresolvedRef {
 fn validate_type_references(
     partitions: &[model::Partition],
     registry: &model::TypeRegistry,
-) -> Result<()> {
+) -> anyhow::Result<()> {
     let mut unresolved: Vec<UnresolvedRef> = Vec::new();
 
     for partition in partitions {
@@ -472,6 +1693,27 @@ fn validate_type_references(
                 &mut unresolved,
             );
         }
+
+        for i in &partition.interfaces {
+            for m in &i.methods {
+                collect_unresolved(
+                    &m.return_type,
+                    registry,
+                    ns,
+                    &format!("return type of method `{}::{}`", i.name, m.name),
+                    &mut unresolved,
+                );
+                for param in &m.params {
+                    collect_unresolved(
+                        &param.ty,
+                        registry,
+                        ns,
+                        &format!("param `{}` of method `{}::{}`", param.name, i.name, m.name),
+                        &mut unresolved,
+                    );
+                }
+            }
+        }
     }
 
     if unresolved.is_empty() {
@@ -506,7 +1748,8 @@ fn validate_type_references(
 }
 
 /// Recursively walk a CType and collect any `Named { resolved: None }` that
-/// is not in the registry.
+/// is not in the registry, following unregistered typedefs through their
+/// resolved type.
 fn collect_unresolved(
     ctype: &model::CType,
     registry: &model::TypeRegistry,
@@ -516,12 +1759,20 @@ fn collect_unresolved(
 ) {
     match ctype {
         model::CType::Named { name, resolved } => {
-            if resolved.is_none() && !registry.contains(name) {
-                out.push(UnresolvedRef {
+            if registry.contains(name) {
+                return;
+            }
+            // An unextracted typedef stands for its clang-resolved type,
+            // which must itself resolve.
+            match resolved {
+                Some(resolved) => {
+                    collect_unresolved(resolved, registry, partition_ns, context, out);
+                }
+                None => out.push(UnresolvedRef {
                     type_name: name.clone(),
                     partition: partition_ns.to_string(),
                     context: context.to_string(),
-                });
+                }),
             }
         }
         model::CType::Ptr { pointee, .. } => {
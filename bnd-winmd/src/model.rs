@@ -3,33 +3,133 @@
 //! These types are clang-independent and winmd-independent, making both the extractor
 //! and emitter easier to test in isolation.
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+
+use serde::{Deserialize, Serialize};
 
 /// A fully extracted partition ready for winmd emission.
-#[derive(Debug)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct Partition {
     pub namespace: String,
     pub library: String,
+    /// Versioned file name of `library` for runtime loaders
+    /// (`libssl.so.3`), recorded as a `LibrarySonameAttribute`.
+    #[serde(default)]
+    pub library_soname: Option<String>,
+    /// Load `library` on first call instead of linking it, recorded as a
+    /// `DelayLoadAttribute`.
+    #[serde(default)]
+    pub delay_load: bool,
     pub structs: Vec<StructDef>,
     pub enums: Vec<EnumDef>,
     pub functions: Vec<FunctionDef>,
     pub typedefs: Vec<TypedefDef>,
     pub constants: Vec<ConstantDef>,
+    /// Vtable structs promoted to interfaces by `interface` config.
+    pub interfaces: Vec<InterfaceDef>,
+    /// Declarations seen during extraction but not emitted.
+    pub skipped: Vec<SkippedDecl>,
+    /// How functions and constants are spread over `Apis` classes.
+    #[serde(default)]
+    pub apis_split: ApisSplit,
+    /// Most methods (and constants) per `Apis` class.
+    #[serde(default)]
+    pub max_methods: Option<usize>,
+    /// Pointer size of the clang target in bytes; `None` for the host's.
+    #[serde(default)]
+    pub pointer_width: Option<usize>,
+}
+
+/// How a partition's functions and constants are spread over `Apis`
+/// classes (`apis_split`).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ApisSplit {
+    /// A single `Apis` class.
+    #[default]
+    None,
+    /// One `<namespace>.<prefix>` namespace with its own `Apis` class per
+    /// name prefix: the name up to its last `_`, lowercased
+    /// (`EVP_MD_CTX_new` → `<namespace>.evp_md_ctx`). Names without one
+    /// stay in the partition's `Apis`.
+    ByPrefix,
+}
+
+impl Partition {
+    /// `true` if nothing was extracted into this partition.
+    pub fn is_empty(&self) -> bool {
+        self.structs.is_empty()
+            && self.enums.is_empty()
+            && self.functions.is_empty()
+            && self.typedefs.is_empty()
+            && self.constants.is_empty()
+            && self.interfaces.is_empty()
+    }
+
+    /// Sort every declaration list by name, so emission order doesn't
+    /// depend on clang traversal or hash-map iteration. Struct fields, enum
+    /// variants and interface methods keep their C order, which is part of
+    /// the layout / vtable.
+    pub fn sort(&mut self) {
+        self.structs.sort_by(|a, b| a.name.cmp(&b.name));
+        self.enums.sort_by(|a, b| a.name.cmp(&b.name));
+        self.functions.sort_by(|a, b| a.name.cmp(&b.name));
+        self.typedefs.sort_by(|a, b| a.name.cmp(&b.name));
+        self.constants.sort_by(|a, b| a.name.cmp(&b.name));
+        self.interfaces.sort_by(|a, b| a.name.cmp(&b.name));
+    }
+}
+
+/// A declaration that extraction skipped, with the reason.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SkippedDecl {
+    pub name: String,
+    /// Declaration kind (`struct`, `enum`, `function`, `typedef`, ...).
+    pub kind: String,
+    pub reason: SkipReason,
+    /// Where the declaration appears, when clang knows.
+    pub location: Option<SourceLocation>,
+}
+
+/// A header file and 1-based line.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SourceLocation {
+    pub file: String,
+    pub line: u32,
+}
+
+/// Why a declaration was not emitted.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum SkipReason {
+    /// Declared in a file outside the partition's traverse list.
+    OutOfScope { file: String },
+    /// Variadic function — P/Invoke metadata cannot represent `...`.
+    Variadic,
+    /// Extraction failed (unsupported type, missing layout, ...).
+    Unsupported(String),
+    /// Object-like `#define` whose body is not a numeric literal.
+    UnparsedMacro,
+    /// Objective-C class, category or protocol (`language =
+    /// "objective-c"`). Expected, so never fails generation.
+    ObjC,
 }
 
 /// A C struct or union definition.
-#[derive(Debug)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct StructDef {
     pub name: String,
     pub size: usize,
     pub align: usize,
+    /// Maximum field alignment when the struct is packed below its fields'
+    /// natural alignment (`#pragma pack(n)`, `__attribute__((packed))`).
+    pub packing: Option<usize>,
     pub fields: Vec<FieldDef>,
     /// True if this is a C `union` (all fields at offset 0).
     pub is_union: bool,
 }
 
 /// A single struct field.
-#[derive(Debug)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct FieldDef {
     pub name: String,
     pub ty: CType,
@@ -37,10 +137,30 @@ pub struct FieldDef {
     pub bitfield_width: Option<usize>,
     /// Bit offset of a bitfield within the struct (from clang).
     pub bitfield_offset: Option<usize>,
+    /// Byte offset clang reports for the field, when known. Checked
+    /// against the emitted layout after generation.
+    pub offset: Option<usize>,
+    /// Byte size clang reports for the field (for collapsed bitfields, of
+    /// their storage), when known.
+    #[serde(default)]
+    pub size: Option<usize>,
+    /// Logical bitfields packed into this storage field
+    /// ([`BitfieldMode::Storage`](crate::config::BitfieldMode::Storage)).
+    pub native_bitfields: Vec<NativeBitfield>,
+}
+
+/// One C bitfield inside a synthetic storage field, emitted as a
+/// `NativeBitfieldAttribute`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NativeBitfield {
+    pub name: String,
+    /// Bit offset from the start of the storage field.
+    pub offset: usize,
+    pub width: usize,
 }
 
 /// A C enum definition.
-#[derive(Debug)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct EnumDef {
     pub name: String,
     /// The underlying integer type (e.g. `CType::U32`).
@@ -48,8 +168,31 @@ pub struct EnumDef {
     pub variants: Vec<EnumVariant>,
 }
 
+impl EnumDef {
+    /// Value of `variant` as this enum's underlying type reads it: clang
+    /// reports both a sign- and a zero-extended value, and which one is
+    /// right depends on the type's signedness.
+    pub fn value(&self, variant: &EnumVariant) -> i128 {
+        variant.value(
+            self.underlying_type
+                .int_range()
+                .is_none_or(|(min, _)| min < 0),
+        )
+    }
+
+    /// The earlier variant the one at `index` duplicates (`SIGABRT =
+    /// SIGIOT`), if any. Both are kept as fields; generators use this to
+    /// spell the later one as an alias.
+    pub fn alias_of(&self, index: usize) -> Option<&EnumVariant> {
+        let value = self.value(&self.variants[index]);
+        self.variants[..index]
+            .iter()
+            .find(|v| self.value(v) == value)
+    }
+}
+
 /// A single enum variant.
-#[derive(Debug)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct EnumVariant {
     pub name: String,
     /// Value as (signed, unsigned) pair — from clang.
@@ -57,59 +200,328 @@ pub struct EnumVariant {
     pub unsigned_value: u64,
 }
 
+impl EnumVariant {
+    /// The signed or unsigned value, widened so both compare alike.
+    pub fn value(&self, signed: bool) -> i128 {
+        if signed {
+            i128::from(self.signed_value)
+        } else {
+            i128::from(self.unsigned_value)
+        }
+    }
+}
+
 /// A C function declaration.
-#[derive(Debug)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct FunctionDef {
+    /// Metadata (method) name. Differs from `entry_point` after renaming.
     pub name: String,
+    /// Exported symbol used as the P/Invoke entry point.
+    pub entry_point: String,
     pub return_type: CType,
     pub params: Vec<ParamDef>,
     pub calling_convention: CallConv,
+    /// Library to import from instead of the partition's (shim wrappers).
+    pub library: Option<String>,
+    /// C spelling of an inline definition's prototype. `Some` only for
+    /// `static`/`inline` definitions, which export no symbol of their own.
+    pub inline_prototype: Option<CPrototype>,
+    /// The `char *` return value is a C string (`[strings]`).
+    #[serde(default)]
+    pub returns_string: bool,
+    /// How the return value reports failure (`[return_conventions]`).
+    #[serde(default)]
+    pub return_convention: Option<ReturnConvention>,
+}
+
+/// How a function's return value reports failure, recorded as
+/// `ReturnConventionAttribute` for wrapper generators.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ReturnConvention {
+    /// Nonzero means success.
+    Bool,
+    /// `0` on success, otherwise an errno value (`pthread_mutex_lock`).
+    Errno,
+    /// Non-negative on success, a negated errno value on failure.
+    NegErrno,
+    /// `-1` on failure with the error in `errno` (POSIX system calls).
+    MinusOneErrno,
+    /// `NULL` on failure (pointer returns).
+    Null,
+}
+
+impl ReturnConvention {
+    /// The config spelling, also used as the attribute argument.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ReturnConvention::Bool => "bool",
+            ReturnConvention::Errno => "errno",
+            ReturnConvention::NegErrno => "neg-errno",
+            ReturnConvention::MinusOneErrno => "minus-one-errno",
+            ReturnConvention::Null => "null",
+        }
+    }
+}
+
+/// Return and parameter types of a function as clang spells them.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CPrototype {
+    pub return_type: String,
+    pub param_types: Vec<String>,
 }
 
 /// A function parameter.
-#[derive(Debug)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct ParamDef {
     pub name: String,
     pub ty: CType,
+    /// Enum whose values the parameter accepts (`AssociatedEnumAttribute`).
+    pub associated_enum: Option<String>,
+    /// Constant the parameter is tied to (`AssociatedConstantAttribute`).
+    pub associated_constant: Option<String>,
+    /// The `char *` parameter is a C string (`[strings]`).
+    #[serde(default)]
+    pub is_string: bool,
+    /// Direction, nullability and buffer size, from SAL annotations in the
+    /// header or `[[param_attributes]]`.
+    #[serde(default)]
+    pub annotations: ParamAnnotations,
+}
+
+/// Explicit parameter attributes. Without a `direction`, mutable pointers
+/// are emitted as `Out` and everything else as input.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ParamAnnotations {
+    pub direction: Option<ParamDirection>,
+    /// May be null (`ParamAttributes::Optional`).
+    pub optional: bool,
+    /// Index of the parameter holding this buffer's size in bytes
+    /// (`MemorySizeAttribute`).
+    pub memory_size: Option<usize>,
+    /// Index of the parameter holding this buffer's element count
+    /// (`NativeArrayInfoAttribute`).
+    pub array_count: Option<usize>,
+    /// Text buffer without a terminating NUL (`NotNullTerminatedAttribute`).
+    pub not_null_terminated: bool,
+}
+
+/// Which way data flows through a parameter.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ParamDirection {
+    In,
+    Out,
+    InOut,
+}
+
+/// A COM-style vtable struct emitted as an interface.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct InterfaceDef {
+    pub name: String,
+    pub methods: Vec<InterfaceMethod>,
+    /// IID from a matching `IID_<name>` GUID constant (`GuidAttribute`).
+    pub guid: Option<Guid>,
+}
+
+/// One vtable slot. `params` exclude the leading `this` pointer.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct InterfaceMethod {
+    pub name: String,
+    pub return_type: CType,
+    pub params: Vec<ParamDef>,
 }
 
 /// A C typedef.
-#[derive(Debug)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct TypedefDef {
     pub name: String,
     pub underlying_type: CType,
+    /// Parameter names from a function-pointer prototype, used for the
+    /// delegate's `Invoke` params. Empty (or blank entries) when unknown.
+    pub param_names: Vec<String>,
+    /// Function that releases a handle of this type (`RAIIFreeAttribute`).
+    pub raii_free: Option<String>,
 }
 
 /// A `#define` integer constant.
-#[derive(Debug)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct ConstantDef {
     pub name: String,
     pub value: ConstantValue,
+    /// How an integer value was spelled, which decides its C type.
+    #[serde(default)]
+    pub literal: IntLiteral,
+    /// The `#define` body as written (`0400`, `((unsigned char) ~0)`) when
+    /// it isn't just the decimal value, for reports and docs.
+    #[serde(default)]
+    pub spelling: Option<String>,
+    /// Integer type fixed by the declaration (an anonymous enum's
+    /// underlying type) or `[constant_type]`, replacing the inferred one.
+    #[serde(default)]
+    pub ty: Option<CType>,
+}
+
+impl ConstantDef {
+    /// A constant with the type C gives an unsuffixed decimal literal.
+    pub fn new(name: impl Into<String>, value: ConstantValue) -> Self {
+        Self {
+            name: name.into(),
+            value,
+            literal: IntLiteral::default(),
+            spelling: None,
+            ty: None,
+        }
+    }
+
+    /// The integer value, widened; `None` for floats and GUIDs.
+    pub fn int_value(&self) -> Option<i128> {
+        match self.value {
+            ConstantValue::Signed(v) => Some(v.into()),
+            ConstantValue::Unsigned(v) => Some(v.into()),
+            ConstantValue::Float(_) | ConstantValue::Guid(_) => None,
+        }
+    }
+
+    /// The integer type to emit: the `[constant_type]` override, else the
+    /// literal's C type (see [`IntLiteral::c_type`]). `None` for floats
+    /// and GUIDs.
+    pub fn int_type(&self) -> Option<CType> {
+        let value = self.int_value()?;
+        if let Some(ty) = &self.ty {
+            return Some(ty.clone());
+        }
+        Some(self.literal.c_type(value))
+    }
+}
+
+/// The parts of an integer literal's spelling that decide its C type.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IntLiteral {
+    /// `u`/`U` suffix.
+    pub unsigned: bool,
+    /// `l`/`L`/`ll`/`LL` suffix.
+    pub long: bool,
+    /// Hexadecimal, octal or binary: the value may take an unsigned type
+    /// without a `U` suffix.
+    pub non_decimal: bool,
+}
+
+impl IntLiteral {
+    /// Read the suffix and radix of a literal like `0x10UL`; `None` if
+    /// `token` is not an integer literal.
+    pub fn parse(token: &str) -> Option<Self> {
+        let digits = token.trim_end_matches(['u', 'U', 'l', 'L']);
+        let suffix = &token[digits.len()..];
+        if !digits.starts_with(|c: char| c.is_ascii_digit()) {
+            return None;
+        }
+        Some(Self {
+            unsigned: suffix.contains(['u', 'U']),
+            long: suffix.contains(['l', 'L']),
+            non_decimal: digits.len() > 1 && digits.starts_with('0'),
+        })
+    }
+
+    /// The first of C's candidate types for a literal spelled like this
+    /// (C11 6.4.4.1, with a 64-bit `long`) that holds `value`.
+    pub fn c_type(self, value: i128) -> CType {
+        let IntLiteral {
+            unsigned,
+            long,
+            non_decimal,
+        } = self;
+        let candidates: &[CType] = match (unsigned, long, non_decimal) {
+            (true, true, _) => &[CType::U64],
+            (true, false, _) => &[CType::U32, CType::U64],
+            (false, true, false) => &[CType::I64],
+            (false, true, true) => &[CType::I64, CType::U64],
+            (false, false, false) => &[CType::I32, CType::I64],
+            (false, false, true) => &[CType::I32, CType::U32, CType::I64, CType::U64],
+        };
+        // A negated literal keeps a signed type.
+        let fits = |ty: &&CType| {
+            let (min, max) = ty.int_range().expect("integer candidates");
+            (min..=max).contains(&value)
+        };
+        let ty = candidates
+            .iter()
+            .find(fits)
+            .or_else(|| [CType::I32, CType::I64, CType::U64].iter().find(fits))
+            .expect("every i64/u64 value fits I64 or U64");
+        ty.clone()
+    }
 }
 
 /// Value of a `#define` constant.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum ConstantValue {
     Signed(i64),
     Unsigned(u64),
     Float(f64),
+    /// `DEFINE_GUID(...)` or a `const GUID` initializer.
+    Guid(Guid),
+}
+
+/// A GUID in its C `{ Data1, Data2, Data3, { Data4[8] } }` layout.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Guid {
+    pub data1: u32,
+    pub data2: u16,
+    pub data3: u16,
+    pub data4: [u8; 8],
+}
+
+impl Guid {
+    /// Build from the 11 initializer values, checking each fits its field.
+    pub fn from_values(values: &[u64]) -> Option<Self> {
+        let [d1, d2, d3, rest @ ..] = values else {
+            return None;
+        };
+        let data4: Vec<u8> = rest
+            .iter()
+            .map(|&b| u8::try_from(b).ok())
+            .collect::<Option<_>>()?;
+        Some(Guid {
+            data1: u32::try_from(*d1).ok()?,
+            data2: u16::try_from(*d2).ok()?,
+            data3: u16::try_from(*d3).ok()?,
+            data4: data4.try_into().ok()?,
+        })
+    }
+}
+
+impl std::fmt::Display for Guid {
+    /// Registry format: `8-4-4-4-12` hex digits.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let d = &self.data4;
+        write!(
+            f,
+            "{:08x}-{:04x}-{:04x}-{:02x}{:02x}-{:02x}{:02x}{:02x}{:02x}{:02x}{:02x}",
+            self.data1, self.data2, self.data3, d[0], d[1], d[2], d[3], d[4], d[5], d[6], d[7]
+        )
+    }
 }
 
 /// Calling convention.
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum CallConv {
     /// Platform default (cdecl on most platforms).
+    #[serde(alias = "cdecl")]
     Cdecl,
     /// stdcall (Windows x86).
+    #[serde(alias = "stdcall")]
     Stdcall,
     /// Fastcall.
+    #[serde(alias = "fastcall")]
     Fastcall,
 }
 
 /// A C type — our intermediate representation.
 ///
 /// Maps closely to both clang's `TypeKind` and ECMA-335's `Type` enum.
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum CType {
     Void,
     Bool,
@@ -123,6 +535,11 @@ pub enum CType {
     U64,
     F32,
     F64,
+    /// A wide character — `wchar_t`, `char16_t` or `char32_t` — `width`
+    /// bytes wide (2 or 4).
+    WChar {
+        width: usize,
+    },
     ISize,
     USize,
     /// Pointer to a type. `is_const` indicates `const T*`.
@@ -145,6 +562,12 @@ pub enum CType {
         /// fall back to the primitive when the typedef isn't extracted.
         resolved: Option<Box<CType>>,
     },
+    /// A record only forward-declared where it was seen (`struct foo;`).
+    /// After extraction it becomes `Named` when another partition defines
+    /// the record, and `Void` otherwise.
+    Forward {
+        name: String,
+    },
     /// A function pointer type.
     FnPtr {
         return_type: Box<CType>,
@@ -154,6 +577,12 @@ pub enum CType {
 }
 
 impl CType {
+    /// Returns `true` for `char *` / `const char *` — the types `[strings]`
+    /// emits as `PSTR` / `PCSTR`.
+    pub fn is_char_ptr(&self) -> bool {
+        matches!(self, CType::Ptr { pointee, .. } if **pointee == CType::I8)
+    }
+
     /// Returns `true` if the outermost type is a mutable pointer (`T *`,
     /// i.e. `Ptr { is_const: false }`).
     pub fn is_outer_ptr_mut(&self) -> bool {
@@ -165,6 +594,51 @@ impl CType {
             }
         )
     }
+
+    /// Returns `true` for integer types (and `bool`), looking through
+    /// typedefs.
+    pub fn is_integer(&self) -> bool {
+        match self {
+            CType::Bool | CType::ISize | CType::USize => true,
+            CType::Named {
+                resolved: Some(resolved),
+                ..
+            } => resolved.is_integer(),
+            other => other.int_range().is_some(),
+        }
+    }
+
+    /// Rust spelling of a fixed-width integer type (`"u32"`); `None` for
+    /// everything else.
+    pub fn int_name(&self) -> Option<&'static str> {
+        Some(match self {
+            CType::I8 => "i8",
+            CType::U8 => "u8",
+            CType::I16 => "i16",
+            CType::U16 => "u16",
+            CType::I32 => "i32",
+            CType::U32 => "u32",
+            CType::I64 => "i64",
+            CType::U64 => "u64",
+            _ => return None,
+        })
+    }
+
+    /// `(min, max)` of a fixed-width integer type; `None` for everything
+    /// else.
+    pub fn int_range(&self) -> Option<(i128, i128)> {
+        Some(match self {
+            CType::I8 => (i8::MIN.into(), i8::MAX.into()),
+            CType::U8 => (0, u8::MAX.into()),
+            CType::I16 => (i16::MIN.into(), i16::MAX.into()),
+            CType::U16 => (0, u16::MAX.into()),
+            CType::I32 => (i32::MIN.into(), i32::MAX.into()),
+            CType::U32 => (0, u32::MAX.into()),
+            CType::I64 => (i64::MIN.into(), i64::MAX.into()),
+            CType::U64 => (0, u64::MAX.into()),
+            _ => return None,
+        })
+    }
 }
 
 /// Global type registry — tracks which namespace each named type lives in.
@@ -175,6 +649,10 @@ impl CType {
 pub struct TypeRegistry {
     /// Maps type name → namespace.
     pub types: HashMap<String, String>,
+    /// Registered types that are interfaces (implicitly pointers).
+    pub interfaces: HashSet<String>,
+    /// Imported type name → assembly name of the winmd defining it.
+    pub assemblies: HashMap<String, String>,
 }
 
 impl TypeRegistry {
@@ -188,6 +666,20 @@ impl TypeRegistry {
         self.types.contains_key(name)
     }
 
+    /// Assembly name of the winmd `namespace.name` was imported from;
+    /// `None` for a type this generation defines.
+    pub fn assembly_for(&self, namespace: &str, name: &str) -> Option<&str> {
+        self.assemblies
+            .get(name)
+            .filter(|_| self.types.get(name).is_some_and(|ns| ns == namespace))
+            .map(String::as_str)
+    }
+
+    /// Returns true if the type name is an extracted interface.
+    pub fn is_interface(&self, name: &str) -> bool {
+        self.interfaces.contains(name)
+    }
+
     /// Look up the namespace for a named type. Returns the type's own
     /// namespace if registered, otherwise falls back to `default_namespace`.
     pub fn namespace_for(&self, name: &str, default_namespace: &str) -> String {
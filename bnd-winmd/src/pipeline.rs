@@ -0,0 +1,221 @@
+//! Config → winmd → Rust in one call.
+//!
+//! [`Builder`] runs bnd-winmd on a config and hands the result, plus any
+//! referenced winmds, to `windows_bindgen::bindgen`:
+//!
+//! ```no_run
+//! use bnd_winmd::pipeline::{Builder, Layout, ReferenceStyle};
+//!
+//! Builder::new("openssl.toml", "bnd-openssl")
+//!     .winmd("bnd-openssl/winmd/bnd-openssl.winmd")
+//!     .input("bnd-linux/winmd/bnd-linux.winmd")
+//!     .filter("openssl")
+//!     .reference("bnd_linux", ReferenceStyle::Full, "libc")
+//!     .layout(Layout::Package)
+//!     .no_toml(true)
+//!     .run()
+//!     .unwrap();
+//! ```
+
+use std::path::{Path, PathBuf};
+
+use anyhow::Context;
+use tracing::info;
+
+pub use crate::config::ReferenceStyle;
+use crate::config::{self, ReferenceConfig};
+use crate::{Error, Result};
+
+/// How windows-bindgen lays out the generated Rust.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Layout {
+    /// One file with a module per namespace.
+    #[default]
+    Modules,
+    /// One file, namespaces flattened (`--flat`).
+    Flat,
+    /// A `mod.rs` tree under the output directory with a Cargo feature per
+    /// namespace (`--package`).
+    Package,
+}
+
+/// Generates a winmd from a bnd-winmd config, then Rust bindings from it.
+#[derive(Debug, Clone)]
+pub struct Builder {
+    config: PathBuf,
+    profile: Option<String>,
+    out: PathBuf,
+    winmd: Option<PathBuf>,
+    inputs: Vec<PathBuf>,
+    filters: Vec<String>,
+    references: Vec<String>,
+    layout: Layout,
+    sys: bool,
+    no_toml: bool,
+    build_script: Option<PathBuf>,
+}
+
+impl Builder {
+    /// Generate from the config at `config` into `out` — a file for
+    /// [`Layout::Modules`] and [`Layout::Flat`], the crate directory for
+    /// [`Layout::Package`].
+    pub fn new(config: impl Into<PathBuf>, out: impl Into<PathBuf>) -> Self {
+        Self {
+            config: config.into(),
+            profile: None,
+            out: out.into(),
+            winmd: None,
+            inputs: Vec::new(),
+            filters: Vec::new(),
+            references: Vec::new(),
+            layout: Layout::default(),
+            sys: true,
+            no_toml: false,
+            build_script: None,
+        }
+    }
+
+    /// Apply the config's `[profile.<name>]` (see
+    /// [`ProfileConfig`](crate::config::ProfileConfig)).
+    pub fn profile(mut self, name: impl Into<String>) -> Self {
+        self.profile = Some(name.into());
+        self
+    }
+
+    /// Where to write the generated winmd. Defaults to `[output] file`,
+    /// relative to the config's directory.
+    pub fn winmd(mut self, path: impl Into<PathBuf>) -> Self {
+        self.winmd = Some(path.into());
+        self
+    }
+
+    /// Another winmd to load alongside the generated one, e.g. the winmd of
+    /// a crate named in [`reference`](Self::reference).
+    pub fn input(mut self, winmd: impl Into<PathBuf>) -> Self {
+        self.inputs.push(winmd.into());
+        self
+    }
+
+    /// Namespace (or type) to generate bindings for.
+    pub fn filter(mut self, filter: impl Into<String>) -> Self {
+        self.filters.push(filter.into());
+        self
+    }
+
+    /// Use types under `namespace` from crate `krate` instead of generating
+    /// them. `[[reference]]` entries in the config are added automatically.
+    pub fn reference(mut self, krate: &str, style: ReferenceStyle, namespace: &str) -> Self {
+        self.references
+            .push(format!("{krate},{},{namespace}", style.as_str()));
+        self
+    }
+
+    /// Add a config `[[reference]]`: its winmd as an input and its crate as
+    /// a reference. `winmd` is the reference's resolved winmd path.
+    pub fn reference_config(mut self, reference: &ReferenceConfig, winmd: &Path) -> Self {
+        if !self.inputs.iter().any(|p| p == winmd) {
+            self.inputs.push(winmd.to_path_buf());
+        }
+        let arg = reference.bindgen_arg();
+        if !self.references.contains(&arg) {
+            self.references.push(arg);
+        }
+        self
+    }
+
+    pub fn layout(mut self, layout: Layout) -> Self {
+        self.layout = layout;
+        self
+    }
+
+    /// Emit `extern` declarations only (`--sys`). On by default.
+    pub fn sys(mut self, sys: bool) -> Self {
+        self.sys = sys;
+        self
+    }
+
+    /// Leave `Cargo.toml` alone in [`Layout::Package`] (`--no-toml`).
+    pub fn no_toml(mut self, no_toml: bool) -> Self {
+        self.no_toml = no_toml;
+        self
+    }
+
+    /// Also write a `build.rs` to `path` that links the config's libraries
+    /// (see [`Config::build_script`](crate::config::Config::build_script)).
+    pub fn build_script(mut self, path: impl Into<PathBuf>) -> Self {
+        self.build_script = Some(path.into());
+        self
+    }
+
+    /// Generate the winmd, then the bindings. Returns the winmd path.
+    ///
+    /// windows-bindgen warnings are returned as [`Error::Bindgen`].
+    pub fn run(&self) -> Result<PathBuf> {
+        if let Some(dir) = self.winmd.as_deref().and_then(Path::parent) {
+            std::fs::create_dir_all(dir)
+                .with_context(|| format!("creating {}", dir.display()))
+                .map_err(Error::Io)?;
+        }
+        let cfg = crate::load_config_profile(&self.config, self.profile.as_deref())?;
+        let base_dir = self.config.parent().unwrap_or_else(|| Path::new("."));
+        let mut written = crate::write_winmd(&cfg, base_dir, self.winmd.as_deref())?.winmds;
+        let winmd = written.remove(0);
+
+        // The `[[assembly]]` winmds are inputs of the same generation.
+        let mut builder = self.clone();
+        for path in written {
+            if !builder.inputs.contains(&path) {
+                builder.inputs.push(path);
+            }
+        }
+        let builder = cfg.reference.iter().fold(builder, |b, r| {
+            let path = config::resolve_header(&r.winmd, base_dir, &cfg.include_paths);
+            b.reference_config(r, &path)
+        });
+        let args = builder.bindgen_args(&winmd);
+        info!(args = ?args, "running windows-bindgen");
+        let warnings = windows_bindgen::bindgen(&args);
+        if !warnings.is_empty() {
+            return Err(Error::Bindgen(anyhow::anyhow!("{warnings}")));
+        }
+        if let Some(path) = &self.build_script {
+            std::fs::write(path, cfg.build_script())
+                .with_context(|| format!("writing {}", path.display()))
+                .map_err(Error::Io)?;
+            info!(path = %path.display(), "wrote build script");
+        }
+        Ok(winmd)
+    }
+
+    /// The windows-bindgen arguments for the generated `winmd`. [`run`](Self::run)
+    /// first adds the config's `[[reference]]` entries.
+    pub fn bindgen_args(&self, winmd: &Path) -> Vec<String> {
+        let mut args = Vec::new();
+        for input in std::iter::once(winmd).chain(self.inputs.iter().map(PathBuf::as_path)) {
+            args.push("--in".to_string());
+            args.push(input.display().to_string());
+        }
+        args.push("--out".to_string());
+        args.push(self.out.display().to_string());
+        for filter in &self.filters {
+            args.push("--filter".to_string());
+            args.push(filter.clone());
+        }
+        for reference in &self.references {
+            args.push("--reference".to_string());
+            args.push(reference.clone());
+        }
+        match self.layout {
+            Layout::Modules => {}
+            Layout::Flat => args.push("--flat".to_string()),
+            Layout::Package => args.push("--package".to_string()),
+        }
+        if self.sys {
+            args.push("--sys".to_string());
+        }
+        if self.no_toml {
+            args.push("--no-toml".to_string());
+        }
+        args
+    }
+}
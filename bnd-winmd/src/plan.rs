@@ -0,0 +1,137 @@
+//! Dry-run plan — what a config would emit, without emitting it.
+//!
+//! Built from the model after every pass and validation, so it lists the
+//! same namespaces and declarations as the winmd would, in emission order.
+//! Cheaper to skim than an [`ApiReport`](crate::report::ApiReport) while
+//! curating `traverse` lists: names and counts only.
+
+use std::fmt;
+
+use anyhow::{Context, Result};
+use serde::Serialize;
+
+use crate::model::Partition;
+
+/// Namespaces and their declarations for a whole config.
+#[derive(Debug, Serialize)]
+pub struct Plan {
+    /// Assembly name from `[output]`.
+    pub assembly: String,
+    pub namespaces: Vec<NamespacePlan>,
+}
+
+/// Declarations one namespace would contain.
+#[derive(Debug, Serialize)]
+pub struct NamespacePlan {
+    pub namespace: String,
+    pub library: String,
+    pub types: Vec<PlannedType>,
+    pub functions: Vec<String>,
+    pub constants: Vec<String>,
+    /// Declarations skipped during extraction, out-of-scope ones excluded.
+    pub skipped: usize,
+}
+
+/// A type with its kind (`enum`, `struct`, `union`, `typedef` or
+/// `interface`).
+#[derive(Debug, Serialize)]
+pub struct PlannedType {
+    pub kind: &'static str,
+    pub name: String,
+}
+
+impl Plan {
+    /// Build a plan from resolved partitions.
+    pub fn new(assembly: &str, partitions: &[Partition]) -> Self {
+        Self {
+            assembly: assembly.to_string(),
+            namespaces: partitions.iter().map(NamespacePlan::new).collect(),
+        }
+    }
+
+    /// Total `(types, functions, constants)` across all namespaces.
+    pub fn counts(&self) -> (usize, usize, usize) {
+        self.namespaces.iter().fold((0, 0, 0), |(t, f, c), ns| {
+            (
+                t + ns.types.len(),
+                f + ns.functions.len(),
+                c + ns.constants.len(),
+            )
+        })
+    }
+
+    /// Serialize the plan as pretty-printed JSON.
+    pub fn to_json(&self) -> Result<String> {
+        serde_json::to_string_pretty(self).context("serializing plan")
+    }
+}
+
+impl NamespacePlan {
+    fn new(p: &Partition) -> Self {
+        let types = p
+            .enums
+            .iter()
+            .map(|e| ("enum", &e.name))
+            .chain(
+                p.structs
+                    .iter()
+                    .map(|s| (if s.is_union { "union" } else { "struct" }, &s.name)),
+            )
+            .chain(p.typedefs.iter().map(|t| ("typedef", &t.name)))
+            .chain(p.interfaces.iter().map(|i| ("interface", &i.name)))
+            .map(|(kind, name)| PlannedType {
+                kind,
+                name: name.clone(),
+            })
+            .collect();
+        Self {
+            namespace: p.namespace.clone(),
+            library: p.library.clone(),
+            types,
+            functions: p.functions.iter().map(|f| f.name.clone()).collect(),
+            constants: p.constants.iter().map(|c| c.name.clone()).collect(),
+            skipped: p
+                .skipped
+                .iter()
+                .filter(|s| !matches!(s.reason, crate::model::SkipReason::OutOfScope { .. }))
+                .count(),
+        }
+    }
+}
+
+impl fmt::Display for Plan {
+    /// One header line per namespace with its counts, then its
+    /// declarations indented below.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for ns in &self.namespaces {
+            write!(
+                f,
+                "{} ({} types, {} functions, {} constants",
+                ns.namespace,
+                ns.types.len(),
+                ns.functions.len(),
+                ns.constants.len()
+            )?;
+            if ns.skipped > 0 {
+                write!(f, ", {} skipped", ns.skipped)?;
+            }
+            writeln!(f, ")")?;
+            for ty in &ns.types {
+                writeln!(f, "  {} {}", ty.kind, ty.name)?;
+            }
+            for name in &ns.functions {
+                writeln!(f, "  fn {name}")?;
+            }
+            for name in &ns.constants {
+                writeln!(f, "  const {name}")?;
+            }
+        }
+        let (types, functions, constants) = self.counts();
+        writeln!(
+            f,
+            "{}: {} namespaces, {types} types, {functions} functions, {constants} constants",
+            self.assembly,
+            self.namespaces.len()
+        )
+    }
+}
@@ -0,0 +1,85 @@
+//! Structured progress reporting for long generations.
+//!
+//! A [`ProgressSink`] set on the [`Config`](crate::config::Config) is told
+//! when each partition starts and finishes extracting and when each winmd
+//! has been emitted, with counts and timings, so gen binaries and CI
+//! wrappers can render progress without scraping `tracing` output.
+
+use std::fmt;
+use std::time::Duration;
+
+use crate::model::{Partition, SkipReason};
+
+/// Receives progress events from [`generate_from_config`](crate::generate_from_config)
+/// and the other entry points that extract a config.
+///
+/// Every method has an empty default, so a sink implements only what it
+/// renders:
+///
+/// ```
+/// use std::time::Duration;
+/// use bnd_winmd::progress::{PartitionCounts, ProgressSink};
+///
+/// struct Bar;
+///
+/// impl ProgressSink for Bar {
+///     fn on_partition_done(&self, namespace: &str, counts: &PartitionCounts, elapsed: Duration) {
+///         eprintln!("{namespace}: {} functions in {elapsed:?}", counts.functions);
+///     }
+/// }
+/// ```
+pub trait ProgressSink: Send + Sync {
+    /// Partition `index` (0-based) of `total` starts extracting.
+    fn on_partition_start(&self, index: usize, total: usize, namespace: &str) {
+        let _ = (index, total, namespace);
+    }
+
+    /// A partition finished extracting. With `namespace_from_path`,
+    /// `counts` covers every namespace the partition produced.
+    fn on_partition_done(&self, namespace: &str, counts: &PartitionCounts, elapsed: Duration) {
+        let _ = (namespace, counts, elapsed);
+    }
+
+    /// The winmd of `assembly` (`[output]` or an `[[assembly]]`) was
+    /// emitted and layout-checked.
+    fn on_emit_done(&self, assembly: &str, bytes: usize, elapsed: Duration) {
+        let _ = (assembly, bytes, elapsed);
+    }
+}
+
+impl fmt::Debug for dyn ProgressSink + '_ {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("ProgressSink")
+    }
+}
+
+/// What a partition's extraction produced, before any model pass.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PartitionCounts {
+    pub functions: usize,
+    /// Structs and unions.
+    pub structs: usize,
+    pub enums: usize,
+    pub typedefs: usize,
+    pub constants: usize,
+    /// Declarations skipped in scope (variadic, unsupported, unparsed
+    /// macros).
+    pub skipped: usize,
+}
+
+impl PartitionCounts {
+    pub(crate) fn of(partitions: &[Partition]) -> Self {
+        partitions.iter().fold(Self::default(), |c, p| Self {
+            functions: c.functions + p.functions.len(),
+            structs: c.structs + p.structs.len(),
+            enums: c.enums + p.enums.len(),
+            typedefs: c.typedefs + p.typedefs.len(),
+            constants: c.constants + p.constants.len(),
+            skipped: c.skipped
+                + p.skipped
+                    .iter()
+                    .filter(|s| !matches!(s.reason, SkipReason::OutOfScope { .. }))
+                    .count(),
+        })
+    }
+}
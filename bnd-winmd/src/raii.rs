@@ -0,0 +1,86 @@
+//! `[[raii_free]]` — record the function that frees a handle typedef.
+
+use tracing::{debug, warn};
+
+use crate::config::RaiiFreeConfig;
+use crate::diagnostics::{Diagnostic, Diagnostics};
+use crate::model::*;
+
+/// Resolve the handle type returned by `pair.alloc` and record `pair.free`
+/// on the matching typedef. Unresolvable pairs are warned about and skipped.
+pub(crate) fn apply_raii_free(
+    partitions: &mut [Partition],
+    pair: &RaiiFreeConfig,
+    diagnostics: &mut Diagnostics,
+) {
+    let alloc = partitions
+        .iter()
+        .flat_map(|p| &p.functions)
+        .find(|f| f.name == pair.alloc);
+    let Some(alloc) = alloc else {
+        warn!(alloc = %pair.alloc, "raii_free: alloc function not found, skipping");
+        diagnostics.push(
+            Diagnostic::new(
+                "raii_free_unresolved",
+                "raii_free: alloc function not found",
+            )
+            .name(&pair.alloc),
+        );
+        return;
+    };
+
+    // Peel pointers: `DIR *opendir(...)` → `DIR`.
+    let mut ret = &alloc.return_type;
+    while let CType::Ptr { pointee, .. } = ret {
+        ret = pointee;
+    }
+    let CType::Named { name: handle, .. } = ret else {
+        warn!(
+            alloc = %pair.alloc,
+            ty = ?alloc.return_type,
+            "raii_free: alloc function does not return a named handle type, skipping"
+        );
+        diagnostics.push(
+            Diagnostic::new(
+                "raii_free_unresolved",
+                "raii_free: alloc function does not return a named handle type",
+            )
+            .name(&pair.alloc),
+        );
+        return;
+    };
+    let handle = handle.clone();
+
+    if !partitions
+        .iter()
+        .any(|p| p.functions.iter().any(|f| f.name == pair.free))
+    {
+        // The free function may live in an imported winmd — not fatal.
+        debug!(free = %pair.free, "raii_free: free function not extracted locally");
+    }
+
+    let td = partitions
+        .iter_mut()
+        .flat_map(|p| p.typedefs.iter_mut())
+        .find(|td| td.name == handle);
+    match td {
+        Some(td) => {
+            debug!(handle = %handle, free = %pair.free, "annotated RAII handle");
+            td.raii_free = Some(pair.free.clone());
+        }
+        None => {
+            warn!(
+                alloc = %pair.alloc,
+                handle = %handle,
+                "raii_free: handle type is not an extracted typedef, skipping"
+            );
+            diagnostics.push(
+                Diagnostic::new(
+                    "raii_free_unresolved",
+                    format!("raii_free: handle type `{handle}` is not an extracted typedef"),
+                )
+                .name(&pair.alloc),
+            );
+        }
+    }
+}
@@ -0,0 +1,186 @@
+//! `[[rename]]` rules — rename emitted symbols and every reference to them.
+
+use std::collections::HashMap;
+
+use anyhow::{Context, Result};
+use regex::Regex;
+use tracing::debug;
+
+use crate::config::{RenameConfig, RenameKind};
+use crate::model::*;
+
+enum Matcher {
+    Exact(String),
+    Pattern(Regex),
+}
+
+struct Rule<'a> {
+    matcher: Matcher,
+    to: &'a str,
+    kind: Option<RenameKind>,
+}
+
+/// Compiled rename rules. First matching rule wins.
+struct Rules<'a>(Vec<Rule<'a>>);
+
+impl<'a> Rules<'a> {
+    fn compile(rules: &'a [RenameConfig]) -> Result<Self> {
+        let mut compiled = Vec::with_capacity(rules.len());
+        for r in rules {
+            let matcher = match (&r.name, &r.pattern) {
+                (Some(name), None) => Matcher::Exact(name.clone()),
+                (None, Some(pattern)) => Matcher::Pattern(
+                    Regex::new(pattern)
+                        .with_context(|| format!("rename: invalid pattern `{pattern}`"))?,
+                ),
+                _ => anyhow::bail!(
+                    "rename: rule to `{}` must set exactly one of `name` or `pattern`",
+                    r.to
+                ),
+            };
+            compiled.push(Rule {
+                matcher,
+                to: &r.to,
+                kind: r.kind,
+            });
+        }
+        Ok(Rules(compiled))
+    }
+
+    /// The new name for `name`, if a rule applies to it.
+    fn apply(&self, kind: RenameKind, name: &str) -> Result<Option<String>> {
+        for rule in &self.0 {
+            if rule.kind.is_some_and(|k| k != kind) {
+                continue;
+            }
+            let renamed = match &rule.matcher {
+                Matcher::Exact(exact) if exact == name => rule.to.to_string(),
+                Matcher::Pattern(re) if re.is_match(name) => re.replace(name, rule.to).into_owned(),
+                _ => continue,
+            };
+            anyhow::ensure!(
+                !renamed.is_empty(),
+                "rename: `{name}` renamed to an empty name"
+            );
+            return Ok(Some(renamed));
+        }
+        Ok(None)
+    }
+
+    /// Rename `name` in place, recording `old → new` in `map`.
+    fn rename(
+        &self,
+        kind: RenameKind,
+        name: &mut String,
+        map: &mut HashMap<String, String>,
+    ) -> Result<()> {
+        if let Some(new) = self.apply(kind, name)? {
+            debug!(from = %name, to = %new, ?kind, "renamed");
+            map.insert(name.clone(), new.clone());
+            *name = new;
+        }
+        Ok(())
+    }
+}
+
+/// Apply `rules` to every declaration in `partitions`, then rewrite all
+/// references so the model stays consistent. Function entry points keep
+/// the C symbol.
+pub(crate) fn apply_renames(partitions: &mut [Partition], rules: &[RenameConfig]) -> Result<()> {
+    if rules.is_empty() {
+        return Ok(());
+    }
+    let rules = Rules::compile(rules)?;
+
+    let mut types = HashMap::new();
+    let mut functions = HashMap::new();
+    let mut constants = HashMap::new();
+    for p in partitions.iter_mut() {
+        for s in &mut p.structs {
+            rules.rename(RenameKind::Struct, &mut s.name, &mut types)?;
+        }
+        for e in &mut p.enums {
+            rules.rename(RenameKind::Enum, &mut e.name, &mut types)?;
+        }
+        for td in &mut p.typedefs {
+            rules.rename(RenameKind::Typedef, &mut td.name, &mut types)?;
+        }
+        // Interfaces are promoted structs and match `struct` rules.
+        for i in &mut p.interfaces {
+            rules.rename(RenameKind::Struct, &mut i.name, &mut types)?;
+        }
+        for f in &mut p.functions {
+            rules.rename(RenameKind::Function, &mut f.name, &mut functions)?;
+        }
+        for c in &mut p.constants {
+            rules.rename(RenameKind::Constant, &mut c.name, &mut constants)?;
+        }
+    }
+
+    let renamed = |map: &HashMap<String, String>, name: &mut String| {
+        if let Some(new) = map.get(name.as_str()) {
+            *name = new.clone();
+        }
+    };
+    for p in partitions.iter_mut() {
+        for s in &mut p.structs {
+            for field in &mut s.fields {
+                rename_ctype(&mut field.ty, &types);
+            }
+        }
+        for td in &mut p.typedefs {
+            rename_ctype(&mut td.underlying_type, &types);
+            if let Some(free) = &mut td.raii_free {
+                renamed(&functions, free);
+            }
+        }
+        for i in &mut p.interfaces {
+            for m in &mut i.methods {
+                rename_ctype(&mut m.return_type, &types);
+                for param in &mut m.params {
+                    rename_ctype(&mut param.ty, &types);
+                }
+            }
+        }
+        for f in &mut p.functions {
+            rename_ctype(&mut f.return_type, &types);
+            for param in &mut f.params {
+                rename_ctype(&mut param.ty, &types);
+                if let Some(en) = &mut param.associated_enum {
+                    renamed(&types, en);
+                }
+                if let Some(c) = &mut param.associated_constant {
+                    renamed(&constants, c);
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Rewrite `Named` references inside `ty` according to `types`.
+fn rename_ctype(ty: &mut CType, types: &HashMap<String, String>) {
+    match ty {
+        CType::Ptr { pointee, .. } => rename_ctype(pointee, types),
+        CType::Array { element, .. } => rename_ctype(element, types),
+        CType::Named { name, resolved } => {
+            if let Some(new) = types.get(name.as_str()) {
+                *name = new.clone();
+            }
+            if let Some(resolved) = resolved {
+                rename_ctype(resolved, types);
+            }
+        }
+        CType::FnPtr {
+            return_type,
+            params,
+            ..
+        } => {
+            rename_ctype(return_type, types);
+            for p in params {
+                rename_ctype(p, types);
+            }
+        }
+        _ => {}
+    }
+}
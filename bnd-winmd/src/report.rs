@@ -0,0 +1,299 @@
+//! API surface report — a JSON-serializable summary of what a config
+//! extracts.
+//!
+//! The report mirrors the intermediate model after injection and
+//! cross-partition deduplication, i.e. exactly what is emitted into the
+//! winmd, plus everything extraction skipped and why. Diffing two reports
+//! shows how the API surface changed between header versions.
+
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use serde::Serialize;
+
+use crate::model::{CType, ConstantValue, Partition, SkipReason};
+
+/// Extracted API surface of a whole config.
+#[derive(Debug, Serialize)]
+pub struct ApiReport {
+    /// Assembly name from `[output]`.
+    pub assembly: String,
+    pub partitions: Vec<PartitionReport>,
+}
+
+/// Extracted API surface of a single partition.
+#[derive(Debug, Serialize)]
+pub struct PartitionReport {
+    pub namespace: String,
+    pub library: String,
+    pub functions: Vec<FunctionReport>,
+    pub structs: Vec<StructReport>,
+    pub enums: Vec<EnumReport>,
+    pub typedefs: Vec<TypedefReport>,
+    pub constants: Vec<ConstantReport>,
+    pub skipped: Vec<SkippedReport>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct FunctionReport {
+    pub name: String,
+    /// Exported symbol the binding links against. Differs from `name` for
+    /// renamed or redirected functions.
+    pub entry_point: String,
+    /// C-style signature, e.g. `int add(int a, int b)`.
+    pub signature: String,
+    pub return_type: String,
+    pub params: Vec<ParamReport>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ParamReport {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub ty: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct StructReport {
+    pub name: String,
+    pub size: usize,
+    pub align: usize,
+    pub is_union: bool,
+    pub fields: Vec<FieldReport>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct FieldReport {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub ty: String,
+    /// Byte offset and size clang reports, when known.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub offset: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub size: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bitfield_width: Option<usize>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct EnumReport {
+    pub name: String,
+    pub underlying_type: String,
+    pub variants: Vec<EnumVariantReport>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct EnumVariantReport {
+    pub name: String,
+    /// Value as the enum's underlying type reads it, so unsigned values
+    /// above `i64::MAX` stay positive.
+    pub value: i128,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TypedefReport {
+    pub name: String,
+    pub underlying_type: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ConstantReport {
+    pub name: String,
+    /// Value rendered as C source text (`42`, `-1`, `0.5`).
+    pub value: String,
+    /// The `#define` body as written when it differs (`0400`, `0x1F`).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub spelling: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SkippedReport {
+    pub name: String,
+    pub kind: String,
+    /// `out_of_scope`, `variadic`, `unsupported` or `unparsed_macro`.
+    pub reason: String,
+    /// Source file (out-of-scope) or error message (unsupported).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+}
+
+impl ApiReport {
+    /// Build a report from emitted partitions.
+    pub fn new(assembly: &str, partitions: &[Partition]) -> Self {
+        Self {
+            assembly: assembly.to_string(),
+            partitions: partitions.iter().map(PartitionReport::new).collect(),
+        }
+    }
+
+    /// Serialize the report as pretty-printed JSON.
+    pub fn to_json(&self) -> Result<String> {
+        serde_json::to_string_pretty(self).context("serializing API report")
+    }
+
+    /// Write the report as pretty-printed JSON to `path`.
+    pub fn write_json(&self, path: &Path) -> Result<()> {
+        std::fs::write(path, self.to_json()?)
+            .with_context(|| format!("writing API report to {}", path.display()))
+    }
+}
+
+impl PartitionReport {
+    fn new(p: &Partition) -> Self {
+        Self {
+            namespace: p.namespace.clone(),
+            library: p.library.clone(),
+            functions: p
+                .functions
+                .iter()
+                .map(|f| {
+                    let params: Vec<ParamReport> = f
+                        .params
+                        .iter()
+                        .map(|p| ParamReport {
+                            name: p.name.clone(),
+                            ty: c_type_name(&p.ty),
+                        })
+                        .collect();
+                    let return_type = c_type_name(&f.return_type);
+                    let args: Vec<String> = params
+                        .iter()
+                        .map(|p| format!("{} {}", p.ty, p.name))
+                        .collect();
+                    FunctionReport {
+                        name: f.name.clone(),
+                        entry_point: f.entry_point.clone(),
+                        signature: format!("{return_type} {}({})", f.name, args.join(", ")),
+                        return_type,
+                        params,
+                    }
+                })
+                .collect(),
+            structs: p
+                .structs
+                .iter()
+                .map(|s| StructReport {
+                    name: s.name.clone(),
+                    size: s.size,
+                    align: s.align,
+                    is_union: s.is_union,
+                    fields: s
+                        .fields
+                        .iter()
+                        .map(|f| FieldReport {
+                            name: f.name.clone(),
+                            ty: c_type_name(&f.ty),
+                            offset: f.offset,
+                            size: f.size,
+                            bitfield_width: f.bitfield_width,
+                        })
+                        .collect(),
+                })
+                .collect(),
+            enums: p
+                .enums
+                .iter()
+                .map(|e| EnumReport {
+                    name: e.name.clone(),
+                    underlying_type: c_type_name(&e.underlying_type),
+                    variants: e
+                        .variants
+                        .iter()
+                        .map(|v| EnumVariantReport {
+                            name: v.name.clone(),
+                            value: e.value(v),
+                        })
+                        .collect(),
+                })
+                .collect(),
+            typedefs: p
+                .typedefs
+                .iter()
+                .map(|t| TypedefReport {
+                    name: t.name.clone(),
+                    underlying_type: c_type_name(&t.underlying_type),
+                })
+                .collect(),
+            constants: p
+                .constants
+                .iter()
+                .map(|c| ConstantReport {
+                    name: c.name.clone(),
+                    value: match c.value {
+                        ConstantValue::Signed(v) => v.to_string(),
+                        ConstantValue::Unsigned(v) => v.to_string(),
+                        ConstantValue::Float(v) => v.to_string(),
+                        ConstantValue::Guid(g) => g.to_string(),
+                    },
+                    spelling: c.spelling.clone(),
+                })
+                .collect(),
+            skipped: p
+                .skipped
+                .iter()
+                .map(|s| {
+                    let (reason, detail) = skip_reason(&s.reason);
+                    SkippedReport {
+                        name: s.name.clone(),
+                        kind: s.kind.clone(),
+                        reason: reason.to_string(),
+                        detail,
+                    }
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Machine-readable name and detail (file or error message) of a skip
+/// reason.
+pub(crate) fn skip_reason(reason: &SkipReason) -> (&'static str, Option<String>) {
+    match reason {
+        SkipReason::OutOfScope { file } => ("out_of_scope", Some(file.clone())),
+        SkipReason::Variadic => ("variadic", None),
+        SkipReason::Unsupported(err) => ("unsupported", Some(err.clone())),
+        SkipReason::UnparsedMacro => ("unparsed_macro", None),
+        SkipReason::ObjC => ("objc", None),
+    }
+}
+
+/// Render a [`CType`] using C spelling (`const char *`, `int[4]`, ...).
+pub(crate) fn c_type_name(ty: &CType) -> String {
+    match ty {
+        CType::Void | CType::Forward { .. } => "void".into(),
+        CType::Bool => "bool".into(),
+        CType::I8 => "int8_t".into(),
+        CType::U8 => "uint8_t".into(),
+        CType::I16 => "int16_t".into(),
+        CType::U16 => "uint16_t".into(),
+        CType::I32 => "int32_t".into(),
+        CType::U32 => "uint32_t".into(),
+        CType::I64 => "int64_t".into(),
+        CType::U64 => "uint64_t".into(),
+        CType::F32 => "float".into(),
+        CType::F64 => "double".into(),
+        CType::WChar { width: 2 } => "char16_t".into(),
+        CType::WChar { .. } => "char32_t".into(),
+        CType::ISize => "intptr_t".into(),
+        CType::USize => "uintptr_t".into(),
+        CType::Ptr { pointee, is_const } => {
+            let inner = c_type_name(pointee);
+            if *is_const {
+                format!("const {inner} *")
+            } else {
+                format!("{inner} *")
+            }
+        }
+        CType::Array { element, len } => format!("{}[{len}]", c_type_name(element)),
+        CType::Named { name, .. } => name.clone(),
+        CType::FnPtr {
+            return_type,
+            params,
+            ..
+        } => {
+            let params: Vec<String> = params.iter().map(c_type_name).collect();
+            format!("{} (*)({})", c_type_name(return_type), params.join(", "))
+        }
+    }
+}
@@ -0,0 +1,588 @@
+//! Rust `-sys` backend — model types → Rust source, without a winmd.
+//!
+//! The output follows windows-bindgen's `--sys` style: `#[repr(C)]`
+//! structs and unions, enums as integer type aliases with constants,
+//! `Option<unsafe extern fn>` delegates and one `extern` block per
+//! library. Going straight from the model avoids what the metadata
+//! round trip forces on the bindings: constants are no longer narrowed to
+//! `i32`, pointer constness is kept as declared, and vtable structs stay
+//! structs of function pointers.
+
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fmt::Write;
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use tracing::debug;
+
+use crate::model::*;
+
+/// Lints the generated names (C spellings) and unused items would trip.
+const ALLOW_LINTS: &str = "non_camel_case_types, non_snake_case, non_upper_case_globals, \
+                           dead_code, clippy::all";
+
+/// Rust source for every namespace, as returned by
+/// [`emit_rust_sys`](crate::emit::emit_rust_sys).
+#[derive(Debug, Default)]
+pub struct GeneratedTree {
+    /// Module body per namespace (`Foo.Bar`), without its child modules.
+    pub modules: BTreeMap<String, String>,
+}
+
+impl GeneratedTree {
+    /// Every namespace nested into a single file (`pub mod Foo { pub mod
+    /// Bar { ... } }`), ready to `include!` at a crate's root or in a
+    /// module of its own.
+    pub fn to_single_file(&self) -> String {
+        let mut out = String::from("// Generated by bnd-winmd. Do not edit.\n");
+        for root in self.children("") {
+            let _ = writeln!(out, "\n#[allow({ALLOW_LINTS})]");
+            self.write_nested(&mut out, &root, 0);
+        }
+        out
+    }
+
+    /// Write one `mod.rs` per namespace under `dir` (`Foo/Bar/mod.rs`),
+    /// each declaring its child modules, plus `dir/mod.rs` declaring the
+    /// top-level ones.
+    pub fn write(&self, dir: &Path) -> Result<()> {
+        let mut root =
+            format!("// Generated by bnd-winmd. Do not edit.\n\n#![allow({ALLOW_LINTS})]\n");
+        for child in self.children("") {
+            let _ = write!(root, "\npub mod {child};");
+        }
+        root.push('\n');
+        write_file(&dir.join("mod.rs"), &root)?;
+
+        for namespace in self.namespaces() {
+            let mut text = String::from("// Generated by bnd-winmd. Do not edit.\n");
+            for child in self.children(&namespace) {
+                let _ = write!(text, "\npub mod {};\n", last_segment(&child));
+            }
+            if let Some(body) = self.modules.get(&namespace) {
+                text.push_str(body);
+            }
+            let path = namespace
+                .split('.')
+                .fold(dir.to_path_buf(), |path, segment| path.join(segment));
+            write_file(&path.join("mod.rs"), &text)?;
+        }
+        Ok(())
+    }
+
+    /// Every namespace and its ancestors.
+    fn namespaces(&self) -> BTreeSet<String> {
+        let mut all = BTreeSet::new();
+        for namespace in self.modules.keys() {
+            let mut prefix = String::new();
+            for segment in namespace.split('.') {
+                if !prefix.is_empty() {
+                    prefix.push('.');
+                }
+                prefix.push_str(segment);
+                all.insert(prefix.clone());
+            }
+        }
+        all
+    }
+
+    /// Direct children of `parent` (`""` for the top level), as full
+    /// namespaces.
+    fn children(&self, parent: &str) -> Vec<String> {
+        self.namespaces()
+            .into_iter()
+            .filter(|ns| match ns.rsplit_once('.') {
+                Some((p, _)) => p == parent,
+                None => parent.is_empty(),
+            })
+            .collect()
+    }
+
+    fn write_nested(&self, out: &mut String, namespace: &str, depth: usize) {
+        let indent = "    ".repeat(depth);
+        let _ = writeln!(out, "{indent}pub mod {} {{", last_segment(namespace));
+        let body = self.modules.get(namespace);
+        if let Some(body) = body {
+            for line in body.trim_start_matches('\n').lines() {
+                if line.is_empty() {
+                    out.push('\n');
+                } else {
+                    let _ = writeln!(out, "{indent}    {line}");
+                }
+            }
+        }
+        for (i, child) in self.children(namespace).iter().enumerate() {
+            if i > 0 || body.is_some() {
+                out.push('\n');
+            }
+            self.write_nested(out, child, depth + 1);
+        }
+        let _ = writeln!(out, "{indent}}}");
+    }
+}
+
+fn write_file(path: &Path, text: &str) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)
+            .with_context(|| format!("creating {}", parent.display()))?;
+    }
+    std::fs::write(path, text).with_context(|| format!("writing {}", path.display()))
+}
+
+fn last_segment(namespace: &str) -> &str {
+    namespace.rsplit('.').next().unwrap_or(namespace)
+}
+
+/// Render every partition as a Rust module; see [`GeneratedTree`].
+pub fn emit_rust_sys(partitions: &[Partition], registry: &TypeRegistry) -> GeneratedTree {
+    let aligns: HashMap<&str, usize> = partitions
+        .iter()
+        .flat_map(|p| &p.structs)
+        .map(|s| (s.name.as_str(), s.align))
+        .collect();
+
+    let mut tree = GeneratedTree::default();
+    for partition in partitions.iter().filter(|p| !p.is_empty()) {
+        let module = Module {
+            namespace: &partition.namespace,
+            registry,
+            aligns: &aligns,
+        };
+        let body = module.render(partition);
+        debug!(namespace = %partition.namespace, bytes = body.len(), "rendered rust module");
+        tree.modules
+            .entry(partition.namespace.clone())
+            .or_default()
+            .push_str(&body);
+    }
+    tree
+}
+
+/// Renders the declarations of one namespace.
+struct Module<'a> {
+    namespace: &'a str,
+    registry: &'a TypeRegistry,
+    /// Alignment of every extracted struct, for spotting over-aligned ones.
+    aligns: &'a HashMap<&'a str, usize>,
+}
+
+impl Module<'_> {
+    fn render(&self, p: &Partition) -> String {
+        let mut out = String::new();
+        for en in &p.enums {
+            self.render_enum(&mut out, en);
+        }
+        for s in &p.structs {
+            self.render_struct(&mut out, s);
+        }
+        for td in &p.typedefs {
+            self.render_typedef(&mut out, td);
+        }
+        for i in &p.interfaces {
+            self.render_interface(&mut out, i);
+        }
+        if !p.constants.is_empty() {
+            out.push('\n');
+        }
+        for c in &p.constants {
+            render_constant(&mut out, c);
+        }
+        self.render_functions(&mut out, p);
+        out
+    }
+
+    fn render_enum(&self, out: &mut String, en: &EnumDef) {
+        let name = ident(&en.name);
+        let _ = writeln!(
+            out,
+            "\npub type {name} = {};",
+            self.rust_type(&en.underlying_type)
+        );
+        for (i, variant) in en.variants.iter().enumerate() {
+            let value = match en.alias_of(i) {
+                Some(original) => ident(&original.name),
+                None => en.value(variant).to_string(),
+            };
+            let _ = writeln!(out, "pub const {}: {name} = {value};", ident(&variant.name));
+        }
+    }
+
+    fn render_struct(&self, out: &mut String, s: &StructDef) {
+        let repr = match s.packing {
+            Some(packing) => format!("C, packed({packing})"),
+            None if s.align > self.natural_align(s) => format!("C, align({})", s.align),
+            None => "C".to_string(),
+        };
+        let keyword = if s.is_union { "union" } else { "struct" };
+        let _ = writeln!(
+            out,
+            "\n#[repr({repr})]\n#[derive(Clone, Copy)]\npub {keyword} {} {{",
+            ident(&s.name)
+        );
+        if s.fields.is_empty() {
+            // Opaque, or injected with only a size.
+            let _ = writeln!(out, "    _opaque: [u8; {}],", s.size);
+        }
+        for field in &s.fields {
+            for bitfield in &field.native_bitfields {
+                let _ = writeln!(
+                    out,
+                    "    /// Bitfield `{}`: bits {}..{}.",
+                    bitfield.name,
+                    bitfield.offset,
+                    bitfield.offset + bitfield.width
+                );
+            }
+            let _ = writeln!(
+                out,
+                "    pub {}: {},",
+                ident(&field.name),
+                self.rust_type(&field.ty)
+            );
+        }
+        out.push_str("}\n");
+    }
+
+    /// Alignment the fields alone give a struct. Pointers and unknown
+    /// types count as already aligned, so only alignment clang reports
+    /// beyond every field's (`__attribute__((aligned))`, or an opaque
+    /// struct's) gets `align(N)`.
+    fn natural_align(&self, s: &StructDef) -> usize {
+        fn align(ty: &CType, aligns: &HashMap<&str, usize>, fallback: usize) -> usize {
+            match ty {
+                CType::Bool | CType::I8 | CType::U8 => 1,
+                CType::I16 | CType::U16 => 2,
+                CType::I32 | CType::U32 | CType::F32 => 4,
+                CType::I64 | CType::U64 | CType::F64 => 8,
+                CType::WChar { width } => *width,
+                CType::Array { element, .. } => align(element, aligns, fallback),
+                CType::Named {
+                    resolved: Some(resolved),
+                    ..
+                } => align(resolved, aligns, fallback),
+                CType::Named { name, .. } => aligns.get(name.as_str()).copied().unwrap_or(fallback),
+                _ => fallback,
+            }
+        }
+        s.fields
+            .iter()
+            .map(|f| align(&f.ty, self.aligns, s.align))
+            .max()
+            .unwrap_or(1)
+    }
+
+    fn render_typedef(&self, out: &mut String, td: &TypedefDef) {
+        if let Some(free) = &td.raii_free {
+            let _ = writeln!(out, "\n/// Released with [`{free}`].");
+        } else {
+            out.push('\n');
+        }
+        let ty = match &td.underlying_type {
+            CType::FnPtr {
+                return_type,
+                params,
+                calling_convention,
+            } => self.fn_ptr(return_type, params, *calling_convention, &td.param_names),
+            CType::Ptr { pointee, .. } => match pointee.as_ref() {
+                CType::FnPtr {
+                    return_type,
+                    params,
+                    calling_convention,
+                } => self.fn_ptr(return_type, params, *calling_convention, &td.param_names),
+                _ => self.rust_type(&td.underlying_type),
+            },
+            other => self.rust_type(other),
+        };
+        let _ = writeln!(out, "pub type {} = {ty};", ident(&td.name));
+    }
+
+    /// A vtable struct: one function pointer per slot, taking the object
+    /// pointer first.
+    fn render_interface(&self, out: &mut String, i: &InterfaceDef) {
+        if let Some(guid) = &i.guid {
+            let _ = writeln!(out, "\n/// IID `{{{guid}}}`.");
+        } else {
+            out.push('\n');
+        }
+        let _ = writeln!(
+            out,
+            "#[repr(C)]\n#[derive(Clone, Copy)]\npub struct {} {{",
+            ident(&i.name)
+        );
+        for m in &i.methods {
+            let mut params = vec!["this: *mut core::ffi::c_void".to_string()];
+            params.extend(self.params(&m.params));
+            let _ = writeln!(
+                out,
+                "    pub {}: Option<unsafe extern \"system\" fn({}){}>,",
+                ident(&m.name),
+                params.join(", "),
+                self.returns(&m.return_type)
+            );
+        }
+        out.push_str("}\n");
+    }
+
+    /// One `extern` block per library and calling convention. Functions
+    /// of a `delay_load` partition are declared with
+    /// `bnd_delayload::link!` instead, through a `pub static` `Library`
+    /// for the soname (when one is set) that callers can check with
+    /// `is_available`.
+    fn render_functions(&self, out: &mut String, p: &Partition) {
+        let mut blocks: BTreeMap<(&str, &str), Vec<&FunctionDef>> = BTreeMap::new();
+        let mut delayed = Vec::new();
+        for f in &p.functions {
+            if p.delay_load && f.library.is_none() {
+                delayed.push(f);
+                continue;
+            }
+            let library = f.library.as_deref().unwrap_or(&p.library);
+            blocks
+                .entry((library, abi(f.calling_convention)))
+                .or_default()
+                .push(f);
+        }
+        for ((library, abi), functions) in blocks {
+            let _ = writeln!(
+                out,
+                "\n#[link({})]\nunsafe extern \"{abi}\" {{",
+                link_args(library)
+            );
+            for f in functions {
+                if f.entry_point != f.name {
+                    let _ = writeln!(out, "    #[link_name = \"{}\"]", f.entry_point);
+                }
+                let _ = writeln!(
+                    out,
+                    "    pub fn {}({}){};",
+                    ident(&f.name),
+                    self.params(&f.params).join(", "),
+                    self.returns(&f.return_type)
+                );
+            }
+            out.push_str("}\n");
+        }
+        if delayed.is_empty() {
+            return;
+        }
+        let library = p.library_soname.as_deref().unwrap_or(&p.library);
+        let library_static = library_static(library);
+        let _ = writeln!(
+            out,
+            "\npub static {library_static}: bnd_delayload::Library =\n    bnd_delayload::Library::new(\"{library}\");\n"
+        );
+        for f in delayed {
+            let link_name = if f.entry_point != f.name {
+                format!(" \"{}\"", f.entry_point)
+            } else {
+                String::new()
+            };
+            let _ = writeln!(
+                out,
+                "bnd_delayload::link!({library_static} \"{}\"{link_name} fn {}({}){});",
+                abi(f.calling_convention),
+                ident(&f.name),
+                self.params(&f.params).join(", "),
+                self.returns(&f.return_type)
+            );
+        }
+    }
+
+    fn params(&self, params: &[ParamDef]) -> Vec<String> {
+        params
+            .iter()
+            .map(|p| format!("{}: {}", ident(&p.name), self.rust_type(&p.ty)))
+            .collect()
+    }
+
+    fn returns(&self, ty: &CType) -> String {
+        match ty {
+            CType::Void => String::new(),
+            other => format!(" -> {}", self.rust_type(other)),
+        }
+    }
+
+    fn fn_ptr(
+        &self,
+        return_type: &CType,
+        params: &[CType],
+        calling_convention: CallConv,
+        names: &[String],
+    ) -> String {
+        // Prototype names are only used when there is a distinct one per
+        // parameter.
+        let unique: BTreeSet<&String> = names.iter().filter(|n| !n.is_empty()).collect();
+        let named = names.len() == params.len() && unique.len() == params.len();
+        let params: Vec<String> = params
+            .iter()
+            .enumerate()
+            .map(|(i, ty)| {
+                let ty = self.rust_type(ty);
+                if named {
+                    format!("{}: {ty}", ident(&names[i]))
+                } else {
+                    ty
+                }
+            })
+            .collect();
+        format!(
+            "Option<unsafe extern \"{}\" fn({}){}>",
+            abi(calling_convention),
+            params.join(", "),
+            self.returns(return_type)
+        )
+    }
+
+    fn rust_type(&self, ty: &CType) -> String {
+        match ty {
+            CType::Void | CType::Forward { .. } => "core::ffi::c_void".to_string(),
+            CType::Bool => "bool".to_string(),
+            CType::I8 => "i8".to_string(),
+            CType::U8 => "u8".to_string(),
+            CType::I16 => "i16".to_string(),
+            CType::U16 => "u16".to_string(),
+            CType::I32 => "i32".to_string(),
+            CType::U32 => "u32".to_string(),
+            CType::I64 => "i64".to_string(),
+            CType::U64 => "u64".to_string(),
+            CType::F32 => "f32".to_string(),
+            CType::F64 => "f64".to_string(),
+            CType::WChar { width: 2 } => "u16".to_string(),
+            CType::WChar { .. } => "u32".to_string(),
+            CType::ISize => "isize".to_string(),
+            CType::USize => "usize".to_string(),
+            // A pointer to a function is the function pointer itself.
+            CType::Ptr { pointee, .. } if matches!(pointee.as_ref(), CType::FnPtr { .. }) => {
+                self.rust_type(pointee)
+            }
+            CType::Ptr { pointee, is_const } => {
+                let mutability = if *is_const { "const" } else { "mut" };
+                format!("*{mutability} {}", self.rust_type(pointee))
+            }
+            CType::Array { element, len } => format!("[{}; {len}]", self.rust_type(element)),
+            CType::Named { name, resolved } => match resolved {
+                // System typedefs no partition extracted fall back to the
+                // type clang resolved.
+                Some(resolved) if !self.registry.contains(name) => self.rust_type(resolved),
+                _ => self.type_path(name),
+            },
+            CType::FnPtr {
+                return_type,
+                params,
+                calling_convention,
+            } => self.fn_ptr(return_type, params, *calling_convention, &[]),
+        }
+    }
+
+    /// Path to a named type from this module: `super::` up to the root,
+    /// then down into the type's namespace.
+    fn type_path(&self, name: &str) -> String {
+        let namespace = self.registry.namespace_for(name, self.namespace);
+        if namespace == self.namespace {
+            return ident(name);
+        }
+        let mut path = "super::".repeat(self.namespace.split('.').count());
+        for segment in namespace.split('.') {
+            path.push_str(segment);
+            path.push_str("::");
+        }
+        path.push_str(&ident(name));
+        path
+    }
+}
+
+fn render_constant(out: &mut String, c: &ConstantDef) {
+    let (ty, value) = match &c.value {
+        ConstantValue::Signed(_) | ConstantValue::Unsigned(_) => (
+            c.int_type()
+                .and_then(|t| t.int_name())
+                .expect("integer constant"),
+            c.int_value().expect("integer constant").to_string(),
+        ),
+        ConstantValue::Float(v) if v.is_nan() => ("f64", "f64::NAN".to_string()),
+        ConstantValue::Float(v) if v.is_infinite() => (
+            "f64",
+            if *v > 0.0 {
+                "f64::INFINITY"
+            } else {
+                "f64::NEG_INFINITY"
+            }
+            .to_string(),
+        ),
+        ConstantValue::Float(v) => ("f64", format!("{v:?}")),
+        // Same bits as windows-sys's `GUID::from_u128`.
+        ConstantValue::Guid(g) => {
+            let d = &g.data4;
+            let value = format!(
+                "0x{:08x}_{:04x}_{:04x}_{:02x}{:02x}_{:02x}{:02x}{:02x}{:02x}{:02x}{:02x}",
+                g.data1, g.data2, g.data3, d[0], d[1], d[2], d[3], d[4], d[5], d[6], d[7]
+            );
+            ("u128", value)
+        }
+    };
+    let _ = match &c.spelling {
+        Some(spelling) => writeln!(
+            out,
+            "pub const {}: {ty} = {value}; // {spelling}",
+            ident(&c.name)
+        ),
+        None => writeln!(out, "pub const {}: {ty} = {value};", ident(&c.name)),
+    };
+}
+
+/// `#[link]` arguments for a library: macOS framework libraries
+/// (`CoreFoundation.framework/CoreFoundation`) link with
+/// `kind = "framework"`.
+fn link_args(library: &str) -> String {
+    match library.split_once(".framework/") {
+        Some((name, binary)) if name == binary => {
+            format!("name = \"{name}\", kind = \"framework\"")
+        }
+        _ => format!("name = \"{library}\""),
+    }
+}
+
+/// Name of the `bnd_delayload::Library` static for `library`:
+/// `libnuma.so.1` becomes `LIBNUMA_SO_1`.
+fn library_static(library: &str) -> String {
+    let name: String = library
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() {
+                c.to_ascii_uppercase()
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    if name.starts_with(|c: char| c.is_ascii_digit()) {
+        format!("_{name}")
+    } else {
+        name
+    }
+}
+
+fn abi(calling_convention: CallConv) -> &'static str {
+    match calling_convention {
+        CallConv::Cdecl => "C",
+        CallConv::Stdcall => "stdcall",
+        CallConv::Fastcall => "fastcall",
+    }
+}
+
+/// `name` as a Rust identifier: keywords become raw identifiers, and the
+/// few that can't be raw get a trailing underscore.
+fn ident(name: &str) -> String {
+    const KEYWORDS: &[&str] = &[
+        "as", "async", "await", "box", "break", "const", "continue", "do", "dyn", "else", "enum",
+        "extern", "false", "fn", "for", "gen", "if", "impl", "in", "let", "loop", "macro", "match",
+        "mod", "move", "mut", "priv", "pub", "ref", "return", "static", "struct", "trait", "true",
+        "try", "type", "typeof", "unsafe", "unsized", "use", "virtual", "where", "while", "yield",
+        "abstract", "become", "final", "override",
+    ];
+    match name {
+        "self" | "Self" | "super" | "crate" | "_" => format!("{name}_"),
+        _ if KEYWORDS.contains(&name) => format!("r#{name}"),
+        _ => name.to_string(),
+    }
+}
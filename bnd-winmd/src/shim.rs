@@ -0,0 +1,137 @@
+//! Shim library for `static inline` functions — see [`ShimConfig`].
+
+use std::fmt::Write;
+use std::path::Path;
+
+use tracing::{debug, warn};
+
+use crate::config::{Config, PartitionConfig, ShimConfig};
+use crate::diagnostics::{Diagnostic, Diagnostics};
+use crate::model::*;
+
+/// Point each shimmed function's import at its exported wrapper. Runs on
+/// C names, before renames.
+pub(crate) fn apply_shims(
+    partitions: &mut [Partition],
+    shim: &ShimConfig,
+    diagnostics: &mut Diagnostics,
+) {
+    for name in &shim.functions {
+        let f = partitions
+            .iter_mut()
+            .flat_map(|p| p.functions.iter_mut())
+            .find(|f| &f.name == name);
+        let Some(f) = f else {
+            warn!(function = %name, "shim: function not found, skipping");
+            diagnostics
+                .push(Diagnostic::new("shim_unresolved", "shim: function not found").name(name));
+            continue;
+        };
+        if f.inline_prototype.is_none() {
+            warn!(function = %name, "shim: not an inline definition, skipping");
+            diagnostics.push(
+                Diagnostic::new("shim_unresolved", "shim: not an inline definition").name(name),
+            );
+            continue;
+        }
+        f.entry_point = format!("{}{}", shim.prefix, name);
+        f.library = Some(shim.library.clone());
+        debug!(function = %name, entry_point = %f.entry_point, "shimmed inline function");
+    }
+}
+
+/// Render the C source defining every wrapper that [`apply_shims`] set up.
+pub(crate) fn shim_source(cfg: &Config, partitions: &[Partition], base_dir: &Path) -> String {
+    let Some(shim) = &cfg.shim else {
+        return String::new();
+    };
+
+    let mut includes = Vec::new();
+    let mut wrappers = String::new();
+    for name in &shim.functions {
+        let entry_point = format!("{}{name}", shim.prefix);
+        let found = partitions.iter().find_map(|p| {
+            p.functions
+                .iter()
+                .find(|f| f.entry_point == entry_point)
+                .map(|f| (p, f))
+        });
+        let Some((partition, f)) = found else {
+            continue;
+        };
+        let Some(proto) = &f.inline_prototype else {
+            continue;
+        };
+        let Some(partition_cfg) = partition_config(cfg, &partition.namespace) else {
+            continue;
+        };
+        for h in partition_cfg.expanded_headers(base_dir, &cfg.include_paths) {
+            if !includes.contains(&h) {
+                includes.push(h);
+            }
+        }
+
+        let params: Vec<String> = proto
+            .param_types
+            .iter()
+            .enumerate()
+            .map(|(i, ty)| c_declaration(ty, &format!("p{i}")))
+            .collect();
+        let args: Vec<String> = (0..params.len()).map(|i| format!("p{i}")).collect();
+        let params = if params.is_empty() {
+            "void".to_string()
+        } else {
+            params.join(", ")
+        };
+        let call = format!("{name}({})", args.join(", "));
+        let body = if proto.return_type == "void" {
+            format!("{call};")
+        } else {
+            format!("return {call};")
+        };
+        let _ = writeln!(
+            wrappers,
+            "\nBND_SHIM_EXPORT {} {{\n    {body}\n}}",
+            c_declaration(&proto.return_type, &format!("{entry_point}({params})")),
+        );
+    }
+
+    let mut out =
+        String::from("/* Generated by bnd-winmd: exported wrappers for inline functions. */\n\n");
+    for h in &includes {
+        let _ = writeln!(out, "#include <{}>", h.display());
+    }
+    out.push_str(
+        "\n#if defined(_WIN32)\n\
+         #define BND_SHIM_EXPORT __declspec(dllexport)\n\
+         #else\n\
+         #define BND_SHIM_EXPORT __attribute__((visibility(\"default\")))\n\
+         #endif\n",
+    );
+    out.push_str(&wrappers);
+    out
+}
+
+/// The configured partition an extracted namespace came from: an exact
+/// match, else the longest parent (C++ namespaces add sub-namespaces).
+fn partition_config<'a>(cfg: &'a Config, namespace: &str) -> Option<&'a PartitionConfig> {
+    cfg.partition
+        .iter()
+        .filter(|pc| {
+            namespace == pc.namespace
+                || namespace
+                    .strip_prefix(pc.namespace.as_str())
+                    .is_some_and(|rest| rest.starts_with('.'))
+        })
+        .max_by_key(|pc| pc.namespace.len())
+}
+
+/// `ty name`, going through `__typeof__` for types whose C spelling wraps
+/// around the declarator (function pointers, arrays).
+fn c_declaration(ty: &str, name: &str) -> String {
+    if ty.contains(['(', '[']) {
+        format!("__typeof__({ty}) {name}")
+    } else {
+        format!("{ty} {name}")
+    }
+}
@@ -0,0 +1,180 @@
+//! Provenance stamp: which bnd-winmd version, config and headers produced
+//! a winmd.
+//!
+//! The writer has no assembly-level attribute parent, so the stamp is a
+//! set of custom attributes on a synthetic `<BndStamp>` type in the empty
+//! namespace, next to `<Module>`. Like `<Module>` it carries no API:
+//! windows-bindgen never generates it, and [`diff`](crate::diff),
+//! [`dump`](crate::dump) and [`merge_winmds`](crate::emit::merge_winmds)
+//! skip it. The reader's `TypeIndex` does not index the empty namespace
+//! either, so reading the stamp back walks the TypeDef rows.
+//!
+//! Header paths are recorded relative to the config directory, an include
+//! path or the sysroot, so the stamp of the same inputs is the same on
+//! every machine.
+
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use windows_metadata::Value;
+use windows_metadata::reader::{HasAttributes, TypeIndex};
+
+use crate::config::Config;
+
+/// Name of the synthetic type carrying the stamp attributes.
+pub(crate) const STAMP_TYPE: &str = "<BndStamp>";
+/// `StampAttribute(version, config_hash)`.
+pub(crate) const STAMP_ATTRIBUTE: &str = "StampAttribute";
+/// `SourceHashAttribute(path, hash)`, one per header.
+pub(crate) const SOURCE_HASH_ATTRIBUTE: &str = "SourceHashAttribute";
+/// `DefineAttribute(scope, flag)`, one per `[defines]` entry.
+pub(crate) const DEFINE_ATTRIBUTE: &str = "DefineAttribute";
+
+/// The inputs that produced a winmd; see [`crate::stamp`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Stamp {
+    /// bnd-winmd version that generated the file.
+    pub version: String,
+    /// Hash of the config files as written and the applied profile (see
+    /// [`config_hash`]).
+    pub config_hash: String,
+    /// `(path, content hash)` of every header the partitions parse or
+    /// traverse, sorted by path. Paths are relative to the config
+    /// directory, else to the include path they sit under (`sys/stat.h`),
+    /// else rooted at the sysroot (`/usr/include/zlib.h`).
+    pub headers: Vec<(String, String)>,
+    /// `(scope, flag)` of every `[defines]` entry, e.g.
+    /// `("", "-D_GNU_SOURCE")`. The scope is empty for top-level defines
+    /// and the partition namespace for a partition's.
+    #[serde(default)]
+    pub defines: Vec<(String, String)>,
+}
+
+impl Stamp {
+    /// Stamp for generating `cfg` now. Headers that can't be read (clang
+    /// will report them) are left out.
+    pub(crate) fn compute(cfg: &Config, base_dir: &Path) -> Stamp {
+        let mut headers: Vec<(String, String)> = cfg
+            .input_headers(base_dir)
+            .iter()
+            .filter_map(|path| {
+                let bytes = std::fs::read(path).ok()?;
+                Some((shown_path(path, cfg, base_dir), hash(&bytes)))
+            })
+            .collect();
+        headers.sort();
+        headers.dedup();
+
+        let scopes = std::iter::once(("", &cfg.defines)).chain(
+            cfg.partition
+                .iter()
+                .map(|p| (p.namespace.as_str(), &p.defines)),
+        );
+        let defines = scopes
+            .flat_map(|(scope, defines)| {
+                defines
+                    .iter()
+                    .map(move |(name, value)| (scope.to_string(), value.flag(name)))
+            })
+            .collect();
+
+        Stamp {
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            config_hash: config_hash(cfg),
+            headers,
+            defines,
+        }
+    }
+
+    /// Read the stamp from in-memory winmd bytes; `None` if the bytes are
+    /// not a winmd or carry no stamp.
+    pub fn from_bytes(bytes: Vec<u8>) -> Option<Stamp> {
+        let type_defs = crate::tables::type_def_rows(&bytes).ok()?;
+        Stamp::from_index(&crate::inspect::index_bytes(bytes).ok()?, type_defs)
+    }
+
+    /// Read the stamp from a winmd indexed from bytes with `type_defs`
+    /// TypeDef rows; `None` if it has none.
+    fn from_index(index: &TypeIndex, type_defs: usize) -> Option<Stamp> {
+        let td = crate::inspect::stamp_type(index, type_defs)?;
+        let strings = |value: Vec<(String, Value)>| -> Option<(String, String)> {
+            match <[_; 2]>::try_from(value).ok()? {
+                [(_, Value::Utf8(a)), (_, Value::Utf8(b))] => Some((a, b)),
+                _ => None,
+            }
+        };
+        let (version, config_hash) = strings(td.find_attribute(STAMP_ATTRIBUTE)?.value())?;
+        let headers = td
+            .attributes()
+            .filter(|attr| crate::inspect::attribute_name(attr) == SOURCE_HASH_ATTRIBUTE)
+            .filter_map(|attr| strings(attr.value()))
+            .collect();
+        let defines = td
+            .attributes()
+            .filter(|attr| crate::inspect::attribute_name(attr) == DEFINE_ATTRIBUTE)
+            .filter_map(|attr| strings(attr.value()))
+            .collect();
+        Some(Stamp {
+            version,
+            config_hash,
+            headers,
+            defines,
+        })
+    }
+}
+
+/// Read the stamp of the winmd at `path`.
+pub(crate) fn read(path: &Path) -> Result<Stamp> {
+    let bytes = std::fs::read(path).with_context(|| format!("reading winmd {}", path.display()))?;
+    let type_defs = crate::tables::type_def_rows(&bytes)
+        .with_context(|| format!("parsing winmd {}", path.display()))?;
+    let index = crate::inspect::index_bytes(bytes)
+        .with_context(|| format!("parsing winmd {}", path.display()))?;
+    Stamp::from_index(&index, type_defs)
+        .with_context(|| format!("{} has no bnd-winmd stamp", path.display()))
+}
+
+/// How `path` is recorded in the stamp: relative to `base_dir`, else to
+/// the first include path it sits under, else as its path inside the
+/// sysroot. Other paths are kept as they are.
+fn shown_path(path: &Path, cfg: &Config, base_dir: &Path) -> String {
+    let include_paths = cfg.include_paths.iter().map(|inc| base_dir.join(inc));
+    let relative = std::iter::once(base_dir.to_path_buf())
+        .chain(include_paths)
+        .find_map(|dir| path.strip_prefix(dir).ok().map(Path::to_path_buf));
+    let shown = relative.or_else(|| {
+        let sysroot = cfg.sysroot.as_ref()?;
+        Some(Path::new("/").join(path.strip_prefix(sysroot).ok()?))
+    });
+    shown.as_deref().unwrap_or(path).display().to_string()
+}
+
+/// Hash of the bytes of each loaded config file, in load order, and the
+/// applied profile. Unlike the loaded [`Config`], these hold no resolved
+/// paths or attached hooks, so the hash is the same in any checkout. A
+/// config built in code has no files, and hashes as empty.
+pub fn config_hash(cfg: &Config) -> String {
+    let mut input = Vec::new();
+    for path in &cfg.config_files {
+        // A file that has since gone away was read when loading; hash it
+        // as empty rather than fail the stamp.
+        input.extend(std::fs::read(path).unwrap_or_default());
+        input.push(0);
+    }
+    if let Some(profile) = &cfg.applied_profile {
+        input.extend(profile.as_bytes());
+    }
+    hash(&input)
+}
+
+/// 64-bit FNV-1a, as 16 hex digits. Only needs to tell inputs apart, not
+/// resist tampering.
+pub(crate) fn hash(bytes: &[u8]) -> String {
+    let mut h: u64 = 0xcbf2_9ce4_8422_2325;
+    for &b in bytes {
+        h ^= u64::from(b);
+        h = h.wrapping_mul(0x0000_0100_0000_01b3);
+    }
+    format!("{h:016x}")
+}
@@ -0,0 +1,526 @@
+//! Raw reads of the ECMA-335 metadata tables (§II.24.2.6) that the
+//! windows-metadata reader does not expose: the `Assembly` row, whose
+//! version [`crate::assembly`] patches in place, the number of `TypeDef`
+//! rows, to reach types the reader does not index, the `FieldLayout`
+//! offsets of explicit-layout structs, and the `TypeRef` scopes and
+//! `AssemblyRef` rows [`crate::assembly`] rewrites for imported types.
+
+use std::collections::HashMap;
+
+use anyhow::{Context, Result, bail, ensure};
+
+pub(crate) fn u16(bytes: &[u8], at: usize) -> Result<u16> {
+    let b = bytes.get(at..at + 2).context("winmd is truncated")?;
+    Ok(u16::from_le_bytes([b[0], b[1]]))
+}
+
+fn u32(bytes: &[u8], at: usize) -> Result<u32> {
+    let b = bytes.get(at..at + 4).context("winmd is truncated")?;
+    Ok(u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+}
+
+fn set_u32(bytes: &mut [u8], at: usize, value: u32) {
+    bytes[at..at + 4].copy_from_slice(&value.to_le_bytes());
+}
+
+/// A 2- or 4-byte index at `at`.
+fn index_at(bytes: &[u8], at: usize, width: usize) -> Result<u32> {
+    match width {
+        2 => Ok(u32::from(u16(bytes, at)?)),
+        _ => u32(bytes, at),
+    }
+}
+
+fn set_index(bytes: &mut [u8], at: usize, width: usize, value: u32) {
+    match width {
+        2 => bytes[at..at + 2].copy_from_slice(&(value as u16).to_le_bytes()),
+        _ => set_u32(bytes, at, value),
+    }
+}
+
+/// File offsets of the PE headers that locate (and size) the metadata.
+struct Pe {
+    optional: usize,
+    section: usize,
+    sections: usize,
+    cli: usize,
+    root: usize,
+}
+
+/// File offset of the metadata root: PE headers → CLI header → metadata
+/// directory.
+fn metadata_root(bytes: &[u8]) -> Result<usize> {
+    Ok(pe(bytes)?.root)
+}
+
+fn pe(bytes: &[u8]) -> Result<Pe> {
+    ensure!(bytes.starts_with(b"MZ"), "not a PE file");
+    let pe = u32(bytes, 0x3C)? as usize;
+    ensure!(bytes.get(pe..pe + 4) == Some(b"PE\0\0"), "not a PE file");
+    let sections = u16(bytes, pe + 6)? as usize;
+    let optional = pe + 24;
+    let optional_size = u16(bytes, pe + 20)? as usize;
+    // Data directories start at 96 (PE32) or 112 (PE32+) bytes into the
+    // optional header; the CLI header is entry 14.
+    let directories = match u16(bytes, optional)? {
+        0x10B => optional + 96,
+        0x20B => optional + 112,
+        magic => bail!("unknown PE optional header magic {magic:#x}"),
+    };
+    let to_offset = |rva: u32| -> Result<usize> {
+        let table = optional + optional_size;
+        for i in 0..sections {
+            let section = table + 40 * i;
+            let size = u32(bytes, section + 8)?;
+            let start = u32(bytes, section + 12)?;
+            if (start..start + size).contains(&rva) {
+                return Ok((rva - start + u32(bytes, section + 20)?) as usize);
+            }
+        }
+        bail!("RVA {rva:#x} is outside every section")
+    };
+    let cli = to_offset(u32(bytes, directories + 14 * 8)?)?;
+    let root = to_offset(u32(bytes, cli + 8)?)?;
+    ensure!(u32(bytes, root)? == 0x424A_5342, "no metadata root");
+    Ok(Pe {
+        optional,
+        section: optional + optional_size,
+        sections,
+        cli,
+        root,
+    })
+}
+
+/// A metadata stream: where its header is, and its data's offset from
+/// the metadata root and size.
+struct Stream {
+    header: usize,
+    offset: usize,
+    size: usize,
+    name: Vec<u8>,
+}
+
+fn streams(bytes: &[u8], root: usize) -> Result<Vec<Stream>> {
+    let version_len = u32(bytes, root + 12)? as usize;
+    let count = u16(bytes, root + 16 + version_len + 2)?;
+    let mut header = root + 16 + version_len + 4;
+    let mut streams = Vec::new();
+    for _ in 0..count {
+        let name_start = header + 8;
+        let name_len = bytes
+            .get(name_start..)
+            .and_then(|b| b.iter().position(|&b| b == 0))
+            .context("unterminated stream name")?;
+        streams.push(Stream {
+            header,
+            offset: u32(bytes, header)? as usize,
+            size: u32(bytes, header + 4)? as usize,
+            name: bytes[name_start..name_start + name_len].to_vec(),
+        });
+        header = name_start + (name_len + 4) / 4 * 4;
+    }
+    Ok(streams)
+}
+
+/// Table ids used below (ECMA-335 §II.22).
+const MODULE: usize = 0x00;
+const TYPE_REF: usize = 0x01;
+const TYPE_DEF: usize = 0x02;
+const FIELD: usize = 0x04;
+const METHOD_DEF: usize = 0x06;
+const PARAM: usize = 0x08;
+const INTERFACE_IMPL: usize = 0x09;
+const MEMBER_REF: usize = 0x0A;
+const DECL_SECURITY: usize = 0x0E;
+const FIELD_LAYOUT: usize = 0x10;
+const STAND_ALONE_SIG: usize = 0x11;
+const EVENT: usize = 0x14;
+const PROPERTY: usize = 0x17;
+const MODULE_REF: usize = 0x1A;
+const TYPE_SPEC: usize = 0x1B;
+const ASSEMBLY: usize = 0x20;
+const ASSEMBLY_REF: usize = 0x23;
+const FILE: usize = 0x26;
+const EXPORTED_TYPE: usize = 0x27;
+const MANIFEST_RESOURCE: usize = 0x28;
+const GENERIC_PARAM: usize = 0x2A;
+const METHOD_SPEC: usize = 0x2B;
+const GENERIC_PARAM_CONSTRAINT: usize = 0x2C;
+
+/// Row counts, and where in the file each table up to `AssemblyRef`
+/// starts.
+struct Tables {
+    rows: [u32; 64],
+    /// Bit `n` set when table `n` is present, if empty.
+    valid: u64,
+    start: [usize; ASSEMBLY_REF + 1],
+    /// File offset of the `#~` stream.
+    stream: usize,
+    /// File offset of the `#Strings` heap.
+    strings: usize,
+    /// Width of an index into the `Field` table.
+    field_index: usize,
+    /// Widths of `#Strings` and `#Blob` heap indexes.
+    string_index: usize,
+    blob_index: usize,
+    /// Width of a `ResolutionScope` coded index.
+    resolution_scope: usize,
+}
+
+/// Walk the `#~` stream header: each table starts where the one before it
+/// ends, at its row count times its row size.
+fn tables(bytes: &[u8]) -> Result<Tables> {
+    let root = metadata_root(bytes)?;
+    let streams = streams(bytes, root)?;
+    let stream = |name: &[u8]| {
+        streams
+            .iter()
+            .find(|s| s.name == name)
+            .map(|s| root + s.offset)
+    };
+    let tables = stream(b"#~").context("no #~ stream")?;
+    let strings = stream(b"#Strings").context("no #Strings stream")?;
+
+    let heap_sizes = bytes[tables + 6];
+    let valid = u64::from(u32(bytes, tables + 8)?) | u64::from(u32(bytes, tables + 12)?) << 32;
+    let mut rows = [0u32; 64];
+    let mut at = tables + 24;
+    for (table, count) in rows.iter_mut().enumerate() {
+        if valid & (1 << table) != 0 {
+            *count = u32(bytes, at)?;
+            at += 4;
+        }
+    }
+
+    let heap = |bit: u8| if heap_sizes & bit != 0 { 4 } else { 2 };
+    let (string, guid, blob) = (heap(0x01), heap(0x02), heap(0x04));
+    let index = |table: usize| if rows[table] < 1 << 16 { 2 } else { 4 };
+    let coded = |tag_bits: u32, tables: &[usize]| {
+        let max = tables.iter().map(|&t| rows[t]).max().unwrap_or(0);
+        if max < 1 << (16 - tag_bits) { 2 } else { 4 }
+    };
+    let type_def_or_ref = coded(2, &[TYPE_DEF, TYPE_REF, TYPE_SPEC]);
+    let has_constant = coded(2, &[FIELD, PARAM, PROPERTY]);
+    let has_custom_attribute = coded(
+        5,
+        &[
+            METHOD_DEF,
+            FIELD,
+            TYPE_REF,
+            TYPE_DEF,
+            PARAM,
+            INTERFACE_IMPL,
+            MEMBER_REF,
+            MODULE,
+            DECL_SECURITY,
+            PROPERTY,
+            EVENT,
+            STAND_ALONE_SIG,
+            MODULE_REF,
+            TYPE_SPEC,
+            ASSEMBLY,
+            ASSEMBLY_REF,
+            FILE,
+            EXPORTED_TYPE,
+            MANIFEST_RESOURCE,
+            GENERIC_PARAM,
+            GENERIC_PARAM_CONSTRAINT,
+            METHOD_SPEC,
+        ],
+    );
+    let has_field_marshal = coded(1, &[FIELD, PARAM]);
+    let has_decl_security = coded(2, &[TYPE_DEF, METHOD_DEF, ASSEMBLY]);
+    let member_ref_parent = coded(3, &[TYPE_DEF, TYPE_REF, MODULE_REF, METHOD_DEF, TYPE_SPEC]);
+    let has_semantics = coded(1, &[EVENT, PROPERTY]);
+    let method_def_or_ref = coded(1, &[METHOD_DEF, MEMBER_REF]);
+    let member_forwarded = coded(1, &[FIELD, METHOD_DEF]);
+    let custom_attribute_type = coded(3, &[METHOD_DEF, MEMBER_REF]);
+    let resolution_scope = coded(2, &[MODULE, MODULE_REF, ASSEMBLY_REF, TYPE_REF]);
+
+    // Row size of every table before `Assembly`, in table id order.
+    let row_sizes = [
+        2 + string + 3 * guid,                                               // Module
+        resolution_scope + 2 * string,                                       // TypeRef
+        4 + 2 * string + type_def_or_ref + index(FIELD) + index(METHOD_DEF), // TypeDef
+        index(FIELD),                                                        // FieldPtr
+        2 + string + blob,                                                   // Field
+        index(METHOD_DEF),                                                   // MethodPtr
+        4 + 2 + 2 + string + blob + index(PARAM),                            // MethodDef
+        index(PARAM),                                                        // ParamPtr
+        2 + 2 + string,                                                      // Param
+        index(TYPE_DEF) + type_def_or_ref,                                   // InterfaceImpl
+        member_ref_parent + string + blob,                                   // MemberRef
+        2 + has_constant + blob,                                             // Constant
+        has_custom_attribute + custom_attribute_type + blob,                 // CustomAttribute
+        has_field_marshal + blob,                                            // FieldMarshal
+        2 + has_decl_security + blob,                                        // DeclSecurity
+        2 + 4 + index(TYPE_DEF),                                             // ClassLayout
+        4 + index(FIELD),                                                    // FieldLayout
+        blob,                                                                // StandAloneSig
+        index(TYPE_DEF) + index(EVENT),                                      // EventMap
+        index(EVENT),                                                        // EventPtr
+        2 + string + type_def_or_ref,                                        // Event
+        index(TYPE_DEF) + index(PROPERTY),                                   // PropertyMap
+        index(PROPERTY),                                                     // PropertyPtr
+        2 + string + blob,                                                   // Property
+        2 + index(METHOD_DEF) + has_semantics,                               // MethodSemantics
+        index(TYPE_DEF) + 2 * method_def_or_ref,                             // MethodImpl
+        string,                                                              // ModuleRef
+        blob,                                                                // TypeSpec
+        2 + member_forwarded + string + index(MODULE_REF),                   // ImplMap
+        4 + index(FIELD),                                                    // FieldRVA
+        4 + 4,                                                               // EncLog
+        4,                                                                   // EncMap
+    ];
+    let mut start = [at; ASSEMBLY_REF + 1];
+    for (table, size) in row_sizes.iter().enumerate() {
+        start[table + 1] = start[table] + size * rows[table] as usize;
+    }
+    // Assembly, AssemblyProcessor and AssemblyOS.
+    let assembly_sizes = [4 + 8 + 4 + blob + 2 * string, 4, 4 + 4 + 4];
+    for (i, size) in assembly_sizes.iter().enumerate() {
+        let table = ASSEMBLY + i;
+        start[table + 1] = start[table] + size * rows[table] as usize;
+    }
+    Ok(Tables {
+        rows,
+        valid,
+        start,
+        stream: tables,
+        strings,
+        field_index: index(FIELD),
+        string_index: string,
+        blob_index: blob,
+        resolution_scope,
+    })
+}
+
+impl Tables {
+    /// Size of an `AssemblyRef` row.
+    fn assembly_ref_size(&self) -> usize {
+        8 + 4 + 2 * self.blob_index + 2 * self.string_index
+    }
+
+    /// File offset of the row count of `table`, which must be present
+    /// (see [`valid`](Self::valid)).
+    fn row_count_at(&self, table: usize) -> usize {
+        let before = self.valid & ((1 << table) - 1);
+        self.stream + 24 + 4 * before.count_ones() as usize
+    }
+
+    fn string(&self, bytes: &[u8], at: usize) -> Result<String> {
+        let start = self.strings + index_at(bytes, at, self.string_index)? as usize;
+        let len = bytes
+            .get(start..)
+            .and_then(|b| b.iter().position(|&b| b == 0))
+            .context("unterminated string")?;
+        Ok(String::from_utf8_lossy(&bytes[start..start + len]).into_owned())
+    }
+}
+
+/// File offset of the single `Assembly` row.
+pub(crate) fn assembly_row(bytes: &[u8]) -> Result<usize> {
+    let tables = tables(bytes)?;
+    ensure!(tables.rows[ASSEMBLY] != 0, "winmd has no Assembly row");
+    let row = tables.start[ASSEMBLY];
+    ensure!(row + 12 <= bytes.len(), "winmd is truncated");
+    Ok(row)
+}
+
+/// Name of the single `Assembly` row.
+pub(crate) fn assembly_name(bytes: &[u8]) -> Result<String> {
+    let row = assembly_row(bytes)?;
+    let tables = tables(bytes)?;
+    tables.string(bytes, row + 4 + 8 + 4 + tables.blob_index)
+}
+
+/// A `TypeRef` row.
+pub(crate) struct TypeRef {
+    /// File offset of its `ResolutionScope`.
+    pub scope_at: usize,
+    /// The 1-based `AssemblyRef` row it resolves through, if any.
+    pub assembly_ref: Option<u32>,
+    pub namespace: String,
+    pub name: String,
+}
+
+/// Every `TypeRef` row, in table order.
+pub(crate) fn type_refs(bytes: &[u8]) -> Result<Vec<TypeRef>> {
+    let tables = tables(bytes)?;
+    let scope = tables.resolution_scope;
+    let row_size = scope + 2 * tables.string_index;
+    (0..tables.rows[TYPE_REF] as usize)
+        .map(|i| {
+            let row = tables.start[TYPE_REF] + i * row_size;
+            let code = index_at(bytes, row, scope)?;
+            Ok(TypeRef {
+                scope_at: row,
+                // Tag 2 of ResolutionScope is AssemblyRef.
+                assembly_ref: (code & 3 == 2).then_some(code >> 2),
+                name: tables.string(bytes, row + scope)?,
+                namespace: tables.string(bytes, row + scope + tables.string_index)?,
+            })
+        })
+        .collect()
+}
+
+/// The name of every `AssemblyRef` row, in table order.
+pub(crate) fn assembly_ref_names(bytes: &[u8]) -> Result<Vec<String>> {
+    let tables = tables(bytes)?;
+    let name = 8 + 4 + tables.blob_index;
+    (0..tables.rows[ASSEMBLY_REF] as usize)
+        .map(|i| {
+            let row = tables.start[ASSEMBLY_REF] + i * tables.assembly_ref_size();
+            tables.string(bytes, row + name)
+        })
+        .collect()
+}
+
+/// Replace the `AssemblyRef` table with the rows `keep` (0-based, in that
+/// order) followed by one new row per name in `added`, versioned
+/// `255.255.255.255` like the writer's own, then point each
+/// `(scope_at, row)` `TypeRef` at 1-based `AssemblyRef` `row`.
+///
+/// The new names are appended to `#Strings`; the streams after the edits,
+/// the metadata size and the section and image sizes move with them.
+pub(crate) fn rewrite_assembly_refs(
+    bytes: &mut Vec<u8>,
+    keep: &[usize],
+    added: &[String],
+    scopes: &[(usize, u32)],
+) -> Result<()> {
+    let tables = tables(bytes)?;
+    let pe = pe(bytes)?;
+    ensure!(pe.sections == 1, "expected a single-section winmd");
+    ensure!(
+        tables.valid & 1 << ASSEMBLY_REF != 0,
+        "winmd has no AssemblyRef table"
+    );
+    let rows = keep.len() + added.len();
+    // Below this no coded index widens or narrows (HasCustomAttribute has
+    // the fewest spare bits: 5).
+    ensure!(
+        rows < 1 << 11 && tables.rows[ASSEMBLY_REF] < 1 << 11,
+        "too many AssemblyRef rows"
+    );
+
+    let mut streams = streams(bytes, pe.root)?;
+    streams.sort_by_key(|s| s.offset);
+    let heap = streams
+        .iter()
+        .find(|s| s.name == b"#Strings")
+        .context("no #Strings stream")?;
+    let heap_end = heap.offset + heap.size;
+    let mut strings = Vec::new();
+    let mut names = Vec::new();
+    for name in added {
+        names.push((heap.size + strings.len()) as u32);
+        strings.extend_from_slice(name.as_bytes());
+        strings.push(0);
+    }
+    strings.resize(strings.len().next_multiple_of(4), 0);
+    ensure!(
+        tables.string_index == 4 || heap.size + strings.len() < 1 << 16,
+        "#Strings heap too large for 2-byte indexes"
+    );
+
+    let size = tables.assembly_ref_size();
+    let old_start = tables.start[ASSEMBLY_REF];
+    let mut table = Vec::with_capacity(rows * size);
+    for &i in keep {
+        let row = old_start + i * size;
+        table.extend_from_slice(&bytes[row..row + size]);
+    }
+    for name in names {
+        let mut row = vec![0u8; size];
+        for part in 0..4 {
+            row[2 * part..2 * part + 2].copy_from_slice(&0xFFu16.to_le_bytes());
+        }
+        // AssemblyFlags::WindowsRuntime
+        set_u32(&mut row, 8, 0x200);
+        set_index(&mut row, 12 + tables.blob_index, tables.string_index, name);
+        table.extend_from_slice(&row);
+    }
+    let table_delta = table.len() as isize - (tables.rows[ASSEMBLY_REF] as usize * size) as isize;
+
+    // Fixed-width edits first, while every offset is still the writer's.
+    for &(at, row) in scopes {
+        set_index(bytes, at, tables.resolution_scope, row << 2 | 2);
+    }
+    set_u32(bytes, tables.row_count_at(ASSEMBLY_REF), rows as u32);
+    // Then the splices, the later one first.
+    let strings_at = pe.root + heap_end;
+    ensure!(old_start < strings_at, "#~ must come before #Strings");
+    bytes.splice(strings_at..strings_at, strings.iter().copied());
+    bytes.splice(
+        old_start..old_start + tables.rows[ASSEMBLY_REF] as usize * size,
+        table,
+    );
+
+    let mut moved = 0isize;
+    for stream in &streams {
+        let grow = match stream.name.as_slice() {
+            b"#~" => table_delta,
+            b"#Strings" => strings.len() as isize,
+            _ => 0,
+        };
+        set_u32(
+            bytes,
+            stream.header,
+            (stream.offset as isize + moved) as u32,
+        );
+        set_u32(
+            bytes,
+            stream.header + 4,
+            (stream.size as isize + grow) as u32,
+        );
+        moved += grow;
+    }
+
+    // The metadata runs to the end of the one section, which runs to the
+    // end of the file.
+    let virtual_size = pe.section + 8;
+    let raw_size = pe.section + 16;
+    let raw_start = u32(bytes, pe.section + 20)? as usize;
+    let section_size = bytes.len() - raw_start;
+    let file_alignment = u32(bytes, pe.optional + 36)? as usize;
+    let section_alignment = u32(bytes, pe.optional + 32)? as usize;
+    set_u32(bytes, virtual_size, section_size as u32);
+    set_u32(
+        bytes,
+        raw_size,
+        section_size.next_multiple_of(file_alignment) as u32,
+    );
+    set_u32(
+        bytes,
+        pe.optional + 56,
+        (raw_start + section_size).next_multiple_of(section_alignment) as u32,
+    );
+    let metadata_size = u32(bytes, pe.cli + 12)? as isize + moved;
+    set_u32(bytes, pe.cli + 12, metadata_size as u32);
+    Ok(())
+}
+
+/// Number of `TypeDef` rows, `<Module>` and other empty-namespace types
+/// included.
+pub(crate) fn type_def_rows(bytes: &[u8]) -> Result<usize> {
+    Ok(tables(bytes)?.rows[TYPE_DEF] as usize)
+}
+
+/// The `FieldLayout` offset of every explicitly laid out field, by the
+/// field's 0-based `Field` row (the reader's row position).
+pub(crate) fn field_layouts(bytes: &[u8]) -> Result<HashMap<usize, u32>> {
+    let tables = tables(bytes)?;
+    let row_size = 4 + tables.field_index;
+    (0..tables.rows[FIELD_LAYOUT] as usize)
+        .map(|i| {
+            let row = tables.start[FIELD_LAYOUT] + i * row_size;
+            let field = match tables.field_index {
+                2 => usize::from(u16(bytes, row + 4)?),
+                _ => u32(bytes, row + 4)? as usize,
+            };
+            ensure!(field != 0, "FieldLayout row {i} has no field");
+            Ok((field - 1, u32(bytes, row)?))
+        })
+        .collect()
+}
@@ -0,0 +1,197 @@
+//! Round-trip helpers for tests: C source in, an indexed winmd out.
+//!
+//! ```no_run
+//! use bnd_winmd::testing::{Options, generate_from_header_str};
+//!
+//! let winmd = generate_from_header_str(
+//!     "struct Point { int x; int y; };\nint point_len(struct Point *p);",
+//!     Options::new(),
+//! )
+//! .unwrap();
+//! assert_eq!(winmd.field_names("Point"), ["x", "y"]);
+//! assert!(winmd.function("point_len").impl_map().is_some());
+//! ```
+
+use std::path::Path;
+
+use anyhow::Context;
+use windows_metadata::Value;
+use windows_metadata::reader::{MethodDef, TypeDef, TypeIndex};
+
+use crate::config::{Config, Language, PartitionConfig};
+use crate::diagnostics::Diagnostics;
+use crate::{Error, Result, inspect};
+
+/// Partition settings for [`generate_from_header_str`].
+#[derive(Debug, Clone)]
+pub struct Options {
+    namespace: String,
+    library: String,
+    language: Language,
+    clang_args: Vec<String>,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self {
+            namespace: "Test".to_string(),
+            library: "test".to_string(),
+            language: Language::C,
+            clang_args: Vec::new(),
+        }
+    }
+}
+
+impl Options {
+    /// Namespace `Test`, library `test`, parsed as C.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn namespace(mut self, namespace: impl Into<String>) -> Self {
+        self.namespace = namespace.into();
+        self
+    }
+
+    pub fn library(mut self, library: impl Into<String>) -> Self {
+        self.library = library.into();
+        self
+    }
+
+    pub fn language(mut self, language: Language) -> Self {
+        self.language = language;
+        self
+    }
+
+    pub fn clang_arg(mut self, arg: impl Into<String>) -> Self {
+        self.clang_args.push(arg.into());
+        self
+    }
+}
+
+/// Generate a winmd from `source`, written to a scratch header that is
+/// the partition's only header and traverse entry.
+///
+/// Generation takes the library's libclang lock, so tests calling this
+/// (or any other entry point) in parallel need no ordering of their own.
+pub fn generate_from_header_str(source: &str, options: Options) -> Result<Winmd> {
+    let dir = tempfile::Builder::new()
+        .prefix("bnd_winmd_testing")
+        .tempdir()
+        .context("creating scratch dir")
+        .map_err(Error::Io)?;
+    let header = dir.path().join("test.h");
+    std::fs::write(&header, source)
+        .with_context(|| format!("writing {}", header.display()))
+        .map_err(Error::Io)?;
+    let output = generate_header(&header, &options)?;
+
+    let index = inspect::index_bytes(output.bytes).map_err(Error::Emit)?;
+    Ok(Winmd {
+        index,
+        namespace: options.namespace,
+        diagnostics: output.diagnostics,
+    })
+}
+
+fn generate_header(header: &Path, options: &Options) -> Result<crate::GenerateOutput> {
+    let partition = options.clang_args.iter().fold(
+        PartitionConfig::new(&options.namespace)
+            .library(&options.library)
+            .language(options.language)
+            .header(header),
+        |p, arg| p.clang_arg(arg),
+    );
+    let cfg = Config::builder()
+        .output(&options.namespace)
+        .partition(partition)
+        .build()
+        .map_err(Error::Config)?;
+    let base_dir = header.parent().expect("scratch header has a parent");
+    crate::generate_from_config(&cfg, base_dir)
+}
+
+/// A generated winmd, indexed for lookups in the [`Options`] namespace.
+pub struct Winmd {
+    index: TypeIndex,
+    namespace: String,
+    diagnostics: Diagnostics,
+}
+
+impl Winmd {
+    /// The reader index, for anything the helpers don't cover.
+    pub fn index(&self) -> &TypeIndex {
+        &self.index
+    }
+
+    /// Warnings raised while generating.
+    pub fn diagnostics(&self) -> &Diagnostics {
+        &self.diagnostics
+    }
+
+    /// Names of every type in the namespace, sorted.
+    pub fn type_names(&self) -> Vec<String> {
+        let mut names: Vec<String> = self
+            .index
+            .types()
+            .filter(|td| td.namespace() == self.namespace)
+            .map(|td| td.name().to_string())
+            .collect();
+        names.sort();
+        names
+    }
+
+    pub fn has_type(&self, name: &str) -> bool {
+        self.index.get(&self.namespace, name).next().is_some()
+    }
+
+    /// The type `name`; panics listing the namespace's types if missing.
+    pub fn type_def(&self, name: &str) -> TypeDef<'_> {
+        self.index
+            .get(&self.namespace, name)
+            .next()
+            .unwrap_or_else(|| panic!("type {name} not found in {:?}", self.type_names()))
+    }
+
+    /// Field names of the type `name`, in declaration order.
+    pub fn field_names(&self, name: &str) -> Vec<String> {
+        self.type_def(name)
+            .fields()
+            .map(|f| f.name().to_string())
+            .collect()
+    }
+
+    /// Functions on the namespace's `Apis` class(es).
+    pub fn functions(&self) -> Vec<MethodDef<'_>> {
+        self.apis().flat_map(|td| td.methods()).collect()
+    }
+
+    /// The function `name`; panics listing the functions if missing.
+    pub fn function(&self, name: &str) -> MethodDef<'_> {
+        self.apis()
+            .flat_map(|td| td.methods())
+            .find(|m| m.name() == name)
+            .unwrap_or_else(|| {
+                let names: Vec<&str> = self.functions().iter().map(|m| m.name()).collect();
+                panic!("function {name} not found in {names:?}")
+            })
+    }
+
+    /// Value of the constant `name` on the `Apis` class(es), or of the enum
+    /// member `name` of any enum in the namespace.
+    pub fn constant(&self, name: &str) -> Option<Value> {
+        self.index
+            .types()
+            .filter(|td| td.namespace() == self.namespace)
+            .flat_map(|td| td.fields())
+            .find(|f| f.name() == name)
+            .and_then(|f| f.constant())
+            .map(|c| c.value())
+    }
+
+    fn apis(&self) -> impl Iterator<Item = TypeDef<'_>> {
+        self.index
+            .types()
+            .filter(|td| td.namespace() == self.namespace && inspect::is_apis(td))
+    }
+}
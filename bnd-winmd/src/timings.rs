@@ -0,0 +1,113 @@
+//! Wall-clock time spent in each pipeline stage.
+//!
+//! [`generate_from_config`](crate::generate_from_config) returns the
+//! timings of its run in [`GenerateOutput::timings`](crate::GenerateOutput::timings)
+//! and logs them at `info`, so the cost of clang parsing can be weighed
+//! against extraction and emission before optimizing either.
+
+use std::fmt;
+use std::time::{Duration, Instant};
+
+use tracing::info;
+
+/// Time per stage of one generation.
+#[derive(Debug, Clone, Default)]
+pub struct Timings {
+    /// Building the `pch` precompiled headers.
+    pub precompile: Duration,
+    /// One entry per `[[partition]]`, in config order.
+    pub partitions: Vec<PartitionTiming>,
+    /// Injection, synthesized enums, annotations, interfaces, shims and
+    /// renames.
+    pub model_passes: Duration,
+    /// Building the type registry, seeding it from external winmds and
+    /// dropping duplicate definitions.
+    pub registry: Duration,
+    /// Layout conflicts, unresolved references, name collisions and the
+    /// emitted layout check.
+    pub validation: Duration,
+    /// Writing every winmd.
+    pub emit: Duration,
+}
+
+/// Time spent on one partition.
+#[derive(Debug, Clone)]
+pub struct PartitionTiming {
+    pub namespace: String,
+    /// libclang parsing the partition's headers.
+    pub parse: Duration,
+    /// Walking the translation unit into the model.
+    pub extract: Duration,
+}
+
+impl Timings {
+    /// Parse time over every partition.
+    pub fn parse(&self) -> Duration {
+        self.partitions.iter().map(|p| p.parse).sum()
+    }
+
+    /// Extraction time over every partition, parsing excluded.
+    pub fn extract(&self) -> Duration {
+        self.partitions.iter().map(|p| p.extract).sum()
+    }
+
+    /// Time over every stage.
+    pub fn total(&self) -> Duration {
+        self.precompile
+            + self.parse()
+            + self.extract()
+            + self.model_passes
+            + self.registry
+            + self.validation
+            + self.emit
+    }
+
+    /// Log the stage totals at `info`.
+    pub(crate) fn log(&self) {
+        info!(
+            precompile = ?self.precompile,
+            parse = ?self.parse(),
+            extract = ?self.extract(),
+            model_passes = ?self.model_passes,
+            registry = ?self.registry,
+            validation = ?self.validation,
+            emit = ?self.emit,
+            total = ?self.total(),
+            "stage timings"
+        );
+    }
+}
+
+/// Run `f`, adding its wall time to `stage`.
+pub(crate) fn timed<T>(stage: &mut Duration, f: impl FnOnce() -> T) -> T {
+    let start = Instant::now();
+    let value = f();
+    *stage += start.elapsed();
+    value
+}
+
+impl fmt::Display for Timings {
+    /// A stage per line, with each partition's share indented under parse
+    /// and extract.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let row = |f: &mut fmt::Formatter<'_>, label: &str, d: Duration| {
+            writeln!(f, "{label:<40} {:>10.1} ms", d.as_secs_f64() * 1000.0)
+        };
+        if !self.precompile.is_zero() {
+            row(f, "precompile", self.precompile)?;
+        }
+        row(f, "parse", self.parse())?;
+        for p in &self.partitions {
+            row(f, &format!("  {}", p.namespace), p.parse)?;
+        }
+        row(f, "extract", self.extract())?;
+        for p in &self.partitions {
+            row(f, &format!("  {}", p.namespace), p.extract)?;
+        }
+        row(f, "model passes", self.model_passes)?;
+        row(f, "registry", self.registry)?;
+        row(f, "validation", self.validation)?;
+        row(f, "emit", self.emit)?;
+        row(f, "total", self.total())
+    }
+}
@@ -0,0 +1,126 @@
+//! Overrides for the C → [`CType`] mapping.
+//!
+//! A [`TypeMapHook`] set on the [`Config`](crate::config::Config) sees
+//! every type extraction maps — field, parameter, return and typedef
+//! types, and the pointees and elements inside them — together with the
+//! mapping bnd-winmd would use, and may replace it. The `[type_map]`
+//! table is applied the same way, before the hook.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use std::sync::Mutex;
+
+use anyhow::Result;
+
+use crate::model::CType;
+
+/// Overrides how C types map to [`CType`].
+///
+/// `clang_spelling` is the type as written, without `const`/`volatile`
+/// (`time_t`, `struct stat`, `char *`); `default` is what bnd-winmd would
+/// map it to. Return `None` to keep the default. Types bnd-winmd can't
+/// map at all (`__int128`, ...) never reach the hook.
+///
+/// Closures implement the trait:
+///
+/// ```
+/// use bnd_winmd::{CType, TypeMapHook};
+///
+/// let hook = |spelling: &str, _default: CType| match spelling {
+///     "time_t" => Some(CType::I64),
+///     "gboolean" => Some(CType::Bool),
+///     _ => None,
+/// };
+/// assert_eq!(hook.map("time_t", CType::I32), Some(CType::I64));
+/// ```
+pub trait TypeMapHook: Send + Sync {
+    fn map(&self, clang_spelling: &str, default: CType) -> Option<CType>;
+}
+
+impl<F> TypeMapHook for F
+where
+    F: Fn(&str, CType) -> Option<CType> + Send + Sync,
+{
+    fn map(&self, clang_spelling: &str, default: CType) -> Option<CType> {
+        self(clang_spelling, default)
+    }
+}
+
+impl fmt::Debug for dyn TypeMapHook + '_ {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("TypeMapHook")
+    }
+}
+
+/// `[type_map]` entries followed by the config's hook, recording which
+/// entries matched.
+pub(crate) struct TypeMap<'a> {
+    table: BTreeMap<String, CType>,
+    hook: Option<&'a dyn TypeMapHook>,
+    used: Mutex<BTreeSet<String>>,
+}
+
+impl<'a> TypeMap<'a> {
+    pub(crate) fn new(
+        table: &BTreeMap<String, String>,
+        hook: Option<&'a dyn TypeMapHook>,
+    ) -> Result<Self> {
+        let table = table
+            .iter()
+            .map(|(name, ty)| Ok((name.clone(), primitive(name, ty)?)))
+            .collect::<Result<_>>()?;
+        Ok(Self {
+            table,
+            hook,
+            used: Mutex::default(),
+        })
+    }
+
+    /// `true` if neither a table nor a hook is configured.
+    pub(crate) fn is_empty(&self) -> bool {
+        self.table.is_empty() && self.hook.is_none()
+    }
+
+    /// Table entries no extracted type was spelled as.
+    pub(crate) fn unmatched(&self) -> Vec<&str> {
+        let used = self.used.lock().unwrap();
+        self.table
+            .keys()
+            .filter(|name| !used.contains(*name))
+            .map(String::as_str)
+            .collect()
+    }
+}
+
+impl TypeMapHook for TypeMap<'_> {
+    fn map(&self, clang_spelling: &str, default: CType) -> Option<CType> {
+        if let Some(ty) = self.table.get(clang_spelling) {
+            self.used.lock().unwrap().insert(clang_spelling.to_string());
+            return Some(ty.clone());
+        }
+        self.hook?.map(clang_spelling, default)
+    }
+}
+
+/// Parse a `[type_map]` value.
+fn primitive(name: &str, ty: &str) -> Result<CType> {
+    Ok(match ty {
+        "bool" => CType::Bool,
+        "i8" => CType::I8,
+        "u8" => CType::U8,
+        "i16" => CType::I16,
+        "u16" => CType::U16,
+        "i32" => CType::I32,
+        "u32" => CType::U32,
+        "i64" => CType::I64,
+        "u64" => CType::U64,
+        "isize" => CType::ISize,
+        "usize" => CType::USize,
+        "f32" => CType::F32,
+        "f64" => CType::F64,
+        other => anyhow::bail!(
+            "type_map: unsupported type `{other}` for `{name}` \
+             (expected bool, i8 ... u64, isize, usize, f32 or f64)"
+        ),
+    })
+}
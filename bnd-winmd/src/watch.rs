@@ -0,0 +1,115 @@
+//! Watch mode — regenerate whenever the config or a file it reads changes.
+//!
+//! Files are polled for modification time and size rather than watched
+//! through OS notifications, which keeps the crate free of platform
+//! dependencies; at authoring scale (a config plus a few hundred headers)
+//! a poll is cheap.
+
+use std::collections::BTreeMap;
+use std::ops::ControlFlow;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+use tracing::{debug, info};
+
+use crate::diff::DiffReport;
+use crate::{Error, GenerateOutput, Result};
+
+/// How often watched files are checked for changes.
+const POLL_INTERVAL: Duration = Duration::from_millis(300);
+
+/// One regeneration, passed to the [`crate::watch`] callback.
+#[derive(Debug)]
+pub struct Regenerated {
+    /// Files whose change triggered this run; empty for the first run.
+    pub changed: Vec<PathBuf>,
+    /// The generated winmd, or why generation failed.
+    pub output: Result<GenerateOutput>,
+    /// API changes since the last successful generation, or why they
+    /// could not be computed; `None` for the first one, or when this run
+    /// failed.
+    pub diff: Option<Result<DiffReport>>,
+}
+
+/// Modification time and size of every watched file; `None` for files
+/// that don't exist (yet).
+type Snapshot = BTreeMap<PathBuf, Option<(SystemTime, u64)>>;
+
+pub(crate) fn watch(
+    config_path: &Path,
+    mut on_change: impl FnMut(&Regenerated) -> ControlFlow<()>,
+) -> Result<()> {
+    let base_dir = config_path.parent().unwrap_or_else(|| Path::new("."));
+    let mut previous: Option<Vec<u8>> = None;
+    // Everything the last successful run read, `#include`d headers and
+    // extended configs included.
+    let mut last_inputs: Vec<PathBuf> = Vec::new();
+    let mut changed = Vec::new();
+    loop {
+        let cfg = crate::load_config(config_path);
+        let mut files = vec![config_path.to_path_buf()];
+        if let Ok(cfg) = &cfg {
+            files.extend(cfg.input_files(base_dir));
+        }
+        // A config that fails to load may be broken in a file it extends.
+        files.extend(last_inputs.iter().cloned());
+        files.sort();
+        files.dedup();
+        // Taken before generating, so edits made while it runs are seen.
+        let mut before = snapshot(&files);
+
+        let output = cfg.and_then(|cfg| crate::generate_from_config(&cfg, base_dir));
+        let diff = match (&output, &previous) {
+            (Ok(out), Some(prev)) => {
+                Some(crate::diff::diff_bytes(prev.clone(), out.bytes.clone()).map_err(Error::Winmd))
+            }
+            _ => None,
+        };
+        if let Ok(out) = &output {
+            previous = Some(out.bytes.clone());
+            last_inputs = out.inputs.clone();
+            let new: Vec<PathBuf> = last_inputs
+                .iter()
+                .filter(|path| !before.contains_key(*path))
+                .cloned()
+                .collect();
+            before.extend(snapshot(&new));
+        }
+        let event = Regenerated {
+            changed: std::mem::take(&mut changed),
+            output,
+            diff,
+        };
+        if on_change(&event).is_break() {
+            return Ok(());
+        }
+
+        let files: Vec<PathBuf> = before.keys().cloned().collect();
+        info!(files = files.len(), "watching for changes");
+        changed = loop {
+            let after = snapshot(&files);
+            let diff: Vec<PathBuf> = after
+                .iter()
+                .filter(|(path, state)| before.get(*path) != Some(state))
+                .map(|(path, _)| path.clone())
+                .collect();
+            if !diff.is_empty() {
+                break diff;
+            }
+            std::thread::sleep(POLL_INTERVAL);
+        };
+        debug!(changed = ?changed, "regenerating");
+    }
+}
+
+fn snapshot(files: &[PathBuf]) -> Snapshot {
+    files
+        .iter()
+        .map(|path| {
+            let state = std::fs::metadata(path)
+                .ok()
+                .and_then(|m| Some((m.modified().ok()?, m.len())));
+            (path.clone(), state)
+        })
+        .collect()
+}
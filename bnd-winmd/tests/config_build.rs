@@ -0,0 +1,62 @@
+//! `bnd_winmd::build`: rerun directives for build scripts. Only loads the
+//! config — no clang parsing.
+
+use std::path::{Path, PathBuf};
+
+fn write(path: &Path, text: &str) {
+    std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+    std::fs::write(path, text).expect("write file");
+}
+
+#[test]
+fn rerun_directives_cover_configs_headers_and_winmds() {
+    let root = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("build_rerun");
+    write(
+        &root.join("common.toml"),
+        r#"
+[output]
+name = "Common"
+
+[[type_import]]
+winmd = "winmd/Other.winmd"
+namespace = "Other"
+"#,
+    );
+    write(
+        &root.join("lib.toml"),
+        r#"
+extends = "common.toml"
+
+[[partition]]
+namespace = "Lib"
+library = "lib"
+headers = ["lib.h", "lib_extra.h"]
+"#,
+    );
+    write(&root.join("lib.h"), "int lib_init(void);\n");
+    write(&root.join("lib_extra.h"), "int lib_extra(void);\n");
+
+    let cfg = bnd_winmd::config::load_config(&root.join("lib.toml")).expect("load config");
+    let directives = bnd_winmd::build::rerun_directives(&cfg.input_files(&root));
+    let expected: Vec<String> = [
+        "lib.toml",
+        "common.toml",
+        "lib.h",
+        "lib_extra.h",
+        "winmd/Other.winmd",
+    ]
+    .iter()
+    .map(|file| format!("cargo:rerun-if-changed={}", root.join(file).display()))
+    .collect();
+    assert_eq!(directives, expected);
+}
+
+#[test]
+fn generate_needs_out_dir() {
+    // Integration tests run without `OUT_DIR` (bnd-winmd has no build
+    // script), as any caller outside a build script would.
+    assert!(std::env::var_os("OUT_DIR").is_none());
+    let err = bnd_winmd::build::generate_config(Path::new("missing.toml"), None).unwrap_err();
+    assert!(matches!(err, bnd_winmd::Error::Config(_)), "{err}");
+    assert!(err.to_string().contains("OUT_DIR is not set"), "{err}");
+}
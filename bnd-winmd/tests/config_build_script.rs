@@ -0,0 +1,74 @@
+//! `build.rs` linker snippet from the config's libraries. Only loads the
+//! config — no clang parsing.
+
+use std::path::PathBuf;
+
+#[test]
+fn build_script_links_partition_libraries() {
+    let path = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("build_script.toml");
+    std::fs::write(
+        &path,
+        r#"
+library_paths = ["/opt/foo/lib"]
+
+[output]
+name = "Foo"
+
+[[partition]]
+namespace = "Foo"
+library = "foo"
+headers = ["foo.h"]
+
+[[partition]]
+namespace = "Foo.Extra"
+library = "foo"
+headers = ["foo_extra.h"]
+
+[[partition]]
+namespace = "Foo.Numa"
+library = "numa"
+delay_load = true
+headers = ["numa.h"]
+"#,
+    )
+    .expect("write config");
+
+    let cfg = bnd_winmd::config::load_config(&path).expect("load config");
+    assert_eq!(
+        cfg.link_directives(),
+        [
+            "cargo:rustc-link-search=native=/opt/foo/lib",
+            "cargo:rustc-link-lib=foo",
+        ]
+    );
+    let script = cfg.build_script();
+    assert!(
+        script.contains(
+            "fn main() {\n    println!(\"cargo:rustc-link-search=native=/opt/foo/lib\");\n    \
+             println!(\"cargo:rustc-link-lib=foo\");\n}\n"
+        ),
+        "{script}"
+    );
+}
+
+#[test]
+fn library_paths_follow_sysroot() {
+    let cfg = bnd_winmd::config::Config::builder()
+        .output("Cross")
+        .sysroot("/opt/ndk/sysroot")
+        .library_path("/usr/lib")
+        .partition(
+            bnd_winmd::config::PartitionConfig::new("Cross")
+                .library("c")
+                .header("stdio.h"),
+        )
+        .build()
+        .expect("build config");
+    assert_eq!(
+        cfg.link_directives(),
+        [
+            "cargo:rustc-link-search=native=/opt/ndk/sysroot/usr/lib",
+            "cargo:rustc-link-lib=c",
+        ]
+    );
+}
@@ -0,0 +1,127 @@
+//! `[defines]` expansion and validation. Only loads the config — no clang
+//! parsing.
+
+use std::path::PathBuf;
+
+fn load(name: &str, toml: &str) -> anyhow::Result<bnd_winmd::config::Config> {
+    let path = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join(format!("{name}.toml"));
+    std::fs::write(&path, toml).expect("write config");
+    bnd_winmd::config::load_config(&path)
+}
+
+#[test]
+fn defines_expand_to_clang_flags_per_scope() {
+    let cfg = load(
+        "defines",
+        r#"
+clang_args = ["-Wno-pragma-once-outside-header", "-D_GNU_SOURCE"]
+
+[defines]
+_GNU_SOURCE = true
+OPENSSL_API_COMPAT = 30000
+NDEBUG = false
+
+[output]
+name = "Defines"
+
+[[partition]]
+namespace = "Defines"
+library = "c"
+headers = ["stdio.h"]
+defines = { _FILE_OFFSET_BITS = "64" }
+"#,
+    )
+    .expect("load config");
+
+    assert_eq!(
+        cfg.effective_clang_args(),
+        [
+            "-Wno-pragma-once-outside-header",
+            "-D_GNU_SOURCE",
+            "-UNDEBUG",
+            "-DOPENSSL_API_COMPAT=30000",
+            "-D_GNU_SOURCE",
+        ]
+    );
+    assert_eq!(
+        cfg.partition[0].effective_clang_args(),
+        ["-D_FILE_OFFSET_BITS=64"]
+    );
+    // The flags are derived, not stored: `clang_args` keeps what the
+    // config spelled.
+    assert_eq!(
+        cfg.clang_args,
+        ["-Wno-pragma-once-outside-header", "-D_GNU_SOURCE"]
+    );
+    assert!(cfg.partition[0].clang_args.is_empty());
+}
+
+#[test]
+fn conflicting_defines_are_rejected() {
+    let err = load(
+        "defines_conflict",
+        r#"
+[defines]
+OPENSSL_API_COMPAT = 30000
+
+[output]
+name = "Defines"
+
+[[partition]]
+namespace = "Defines"
+library = "c"
+headers = ["stdio.h"]
+defines = { OPENSSL_API_COMPAT = 10100 }
+"#,
+    )
+    .expect_err("conflicting values");
+    let msg = format!("{err:#}");
+    assert!(
+        msg.contains(
+            "`OPENSSL_API_COMPAT` is set by both [defines] (`-DOPENSSL_API_COMPAT=30000`) \
+             and partition Defines defines (`-DOPENSSL_API_COMPAT=10100`)"
+        ),
+        "{msg}"
+    );
+
+    let err = load(
+        "defines_clang_args_conflict",
+        r#"
+clang_args = ["-D", "FOO=1"]
+
+[defines]
+FOO = 2
+
+[output]
+name = "Defines"
+
+[[partition]]
+namespace = "Defines"
+library = "c"
+headers = ["stdio.h"]
+"#,
+    )
+    .expect_err("conflicting with clang_args");
+    assert!(
+        format!("{err:#}").contains("clang_args (`-DFOO=1`)"),
+        "{err:#}"
+    );
+
+    let err = load(
+        "defines_bad_name",
+        r#"
+[defines]
+"1BAD" = true
+
+[output]
+name = "Defines"
+
+[[partition]]
+namespace = "Defines"
+library = "c"
+headers = ["stdio.h"]
+"#,
+    )
+    .expect_err("invalid macro name");
+    assert!(format!("{err:#}").contains("`1BAD` is not a valid macro name"));
+}
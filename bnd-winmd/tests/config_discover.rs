@@ -0,0 +1,58 @@
+//! `[discover]` pkg-config integration. Only loads the config — no clang
+//! parsing — so it runs independently of the round-trip tests.
+
+use std::path::PathBuf;
+
+fn write_config(name: &str, toml: &str) -> PathBuf {
+    let path = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join(name);
+    std::fs::write(&path, toml).expect("write config");
+    path
+}
+
+#[test]
+fn pkg_config_supplies_default_library() {
+    let path = write_config(
+        "discover_zlib.toml",
+        r#"
+[output]
+name = "Zlib"
+
+[discover]
+pkg_config = "zlib"
+
+[[partition]]
+namespace = "Zlib"
+headers = ["zlib.h"]
+
+[[partition]]
+namespace = "Zlib.Explicit"
+library = "custom"
+headers = ["zlib.h"]
+"#,
+    );
+
+    let cfg = bnd_winmd::config::load_config(&path).expect("load config");
+    assert_eq!(
+        cfg.partition[0].library, "z",
+        "pkg-config -lz fills in library"
+    );
+    assert_eq!(cfg.partition[1].library, "custom", "explicit library wins");
+}
+
+#[test]
+fn missing_library_without_discovery_is_an_error() {
+    let path = write_config(
+        "discover_none.toml",
+        r#"
+[output]
+name = "NoLib"
+
+[[partition]]
+namespace = "NoLib"
+headers = ["nolib.h"]
+"#,
+    );
+
+    let err = bnd_winmd::config::load_config(&path).unwrap_err();
+    assert!(err.to_string().contains("has no library"), "{err}");
+}
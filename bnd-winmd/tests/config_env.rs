@@ -0,0 +1,98 @@
+//! Environment variable substitution in config paths. Only loads the
+//! config — no clang parsing.
+
+use std::path::{Path, PathBuf};
+
+fn load(name: &str, toml: &str) -> anyhow::Result<bnd_winmd::config::Config> {
+    let path = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join(format!("{name}.toml"));
+    std::fs::write(&path, toml).expect("write config");
+    bnd_winmd::config::load_config(&path)
+}
+
+// Cargo sets `CARGO_MANIFEST_DIR` and `CARGO_PKG_NAME` for the test run.
+#[test]
+fn env_vars_are_substituted_in_paths() {
+    let cfg = load(
+        "env",
+        r#"
+include_paths = ["${CARGO_MANIFEST_DIR}/include"]
+library_paths = ["${CARGO_MANIFEST_DIR}/lib"]
+framework_paths = ["${CARGO_MANIFEST_DIR}/Frameworks"]
+
+[output]
+name = "Env"
+file = "${CARGO_PKG_NAME}.winmd"
+
+[[partition]]
+namespace = "Env"
+library = "c"
+headers = ["$ENV{CARGO_PKG_NAME}/api.h"]
+traverse = ["${CARGO_PKG_NAME}", "price$$.h"]
+
+[[type_import]]
+winmd = "${CARGO_MANIFEST_DIR}/Other.winmd"
+namespace = "Other"
+"#,
+    )
+    .expect("load config");
+
+    let manifest = Path::new(env!("CARGO_MANIFEST_DIR"));
+    assert_eq!(cfg.include_paths, [manifest.join("include")]);
+    assert_eq!(cfg.library_paths, [manifest.join("lib")]);
+    assert_eq!(cfg.framework_paths, [manifest.join("Frameworks")]);
+    assert_eq!(cfg.output.file, Path::new("bnd-winmd.winmd"));
+    let p = &cfg.partition[0];
+    assert_eq!(p.headers, [Path::new("bnd-winmd/api.h")]);
+    assert_eq!(p.traverse, [Path::new("bnd-winmd"), Path::new("price$.h")]);
+    assert_eq!(cfg.type_import[0].winmd, manifest.join("Other.winmd"));
+}
+
+#[test]
+fn sysroot_is_expanded_before_rerooting() {
+    let cfg = load(
+        "env_sysroot",
+        r#"
+sysroot = "${CARGO_MANIFEST_DIR}/sysroot"
+include_paths = ["/usr/include"]
+
+[output]
+name = "Env"
+
+[[partition]]
+namespace = "Env"
+library = "c"
+headers = ["api.h"]
+"#,
+    )
+    .expect("load config");
+
+    let sysroot = Path::new(env!("CARGO_MANIFEST_DIR")).join("sysroot");
+    assert_eq!(cfg.sysroot.as_deref(), Some(sysroot.as_path()));
+    assert_eq!(cfg.include_paths, [sysroot.join("usr/include")]);
+}
+
+#[test]
+fn unset_env_var_is_an_error() {
+    let err = load(
+        "env_unset",
+        r#"
+[output]
+name = "Env"
+
+[[partition]]
+namespace = "Env"
+library = "c"
+headers = ["${BND_WINMD_SURELY_UNSET_VAR}/api.h"]
+"#,
+    )
+    .expect_err("unset variable");
+    let msg = format!("{err:#}");
+    assert!(msg.starts_with("partition Env headers: "), "{msg}");
+    assert!(
+        msg.contains(
+            "environment variable `BND_WINMD_SURELY_UNSET_VAR` used in \
+             `${BND_WINMD_SURELY_UNSET_VAR}/api.h` is not set"
+        ),
+        "{msg}"
+    );
+}
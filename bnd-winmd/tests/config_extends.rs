@@ -0,0 +1,206 @@
+//! `extends` config inheritance. Only loads the config — no clang parsing.
+
+use std::path::{Path, PathBuf};
+
+fn write(path: &Path, toml: &str) {
+    std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+    std::fs::write(path, toml).expect("write config");
+}
+
+#[test]
+fn extends_merges_tables_and_concatenates_arrays() {
+    let root = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("extends");
+    write(
+        &root.join("common.toml"),
+        r#"
+include_paths = ["include", "/usr/include"]
+clang_args = ["-D_GNU_SOURCE"]
+strict = true
+
+[type_map]
+size_t = "usize"
+off_t = "i64"
+
+[output]
+name = "Common"
+file = "common.winmd"
+
+[[partition]]
+namespace = "Common"
+library = "c"
+headers = ["common.h"]
+
+[[type_import]]
+winmd = "winmd/Other.winmd"
+namespace = "Other"
+"#,
+    );
+    write(
+        &root.join("linux/linux.toml"),
+        r#"
+extends = "../common.toml"
+clang_args = ["-DLINUX"]
+strict = false
+
+[type_map]
+off_t = "i32"
+
+[output]
+name = "Linux"
+
+[[partition]]
+namespace = "Linux"
+library = "c"
+headers = ["linux.h"]
+"#,
+    );
+
+    let cfg = bnd_winmd::config::load_config(&root.join("linux/linux.toml")).expect("load config");
+    assert_eq!(
+        cfg.include_paths,
+        [Path::new("../include"), Path::new("/usr/include")]
+    );
+    assert_eq!(cfg.clang_args, ["-D_GNU_SOURCE", "-DLINUX"]);
+    assert!(!cfg.strict);
+    assert_eq!(cfg.type_map["size_t"], "usize");
+    assert_eq!(cfg.type_map["off_t"], "i32");
+    assert_eq!(cfg.output.name, "Linux");
+    assert_eq!(cfg.output.file, Path::new("common.winmd"));
+    let namespaces: Vec<_> = cfg.partition.iter().map(|p| p.namespace.as_str()).collect();
+    assert_eq!(namespaces, ["Common", "Linux"]);
+    assert_eq!(cfg.type_import[0].winmd, Path::new("../winmd/Other.winmd"));
+}
+
+#[test]
+fn extends_cycle_is_an_error() {
+    let root = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("extends_cycle");
+    write(&root.join("a.toml"), "extends = \"b.toml\"\n");
+    write(&root.join("b.toml"), "extends = [\"a.toml\"]\n");
+
+    let err = bnd_winmd::config::load_config(&root.join("a.toml")).expect_err("cycle");
+    assert!(
+        format!("{err:#}").contains("a.toml extends itself"),
+        "{err:#}"
+    );
+}
+
+#[test]
+fn extends_rebases_paths_of_a_sibling_config() {
+    let root = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("extends_sibling");
+    write(
+        &root.join("base/base.toml"),
+        r#"
+include_paths = ["include"]
+framework_paths = ["Frameworks", "/Library/Frameworks"]
+library_paths = ["lib"]
+pch = ["prelude.h", "stdio.h"]
+scratch_dir = "scratch"
+sysroot = "sdk"
+
+[profile.debug]
+include_paths = ["debug/include"]
+remove_include_paths = ["include"]
+
+[[reference]]
+winmd = "winmd/Ref.winmd"
+namespace = "Ref"
+crate = "ref_sys"
+"#,
+    );
+    write(&root.join("base/prelude.h"), "typedef int prelude_t;\n");
+    write(
+        &root.join("app/app.toml"),
+        r#"
+extends = "../base/base.toml"
+
+[output]
+name = "App"
+
+[[partition]]
+namespace = "App"
+library = "app"
+headers = ["app.h"]
+"#,
+    );
+
+    let cfg = bnd_winmd::config::load_config(&root.join("app/app.toml")).expect("load config");
+    let sdk = Path::new("../base/sdk");
+    assert_eq!(cfg.sysroot.as_deref(), Some(sdk));
+    // Relative directories are not re-rooted under the sysroot.
+    assert_eq!(cfg.include_paths, [Path::new("../base/include")]);
+    assert_eq!(
+        cfg.framework_paths,
+        [
+            Path::new("../base/Frameworks").to_path_buf(),
+            sdk.join("Library/Frameworks")
+        ]
+    );
+    assert_eq!(cfg.library_paths, [Path::new("../base/lib")]);
+    // `prelude.h` sits next to the base config; `stdio.h` is found
+    // through the include paths.
+    assert_eq!(
+        cfg.pch,
+        [Path::new("../base/prelude.h"), Path::new("stdio.h")]
+    );
+    assert_eq!(
+        cfg.scratch_dir.as_deref(),
+        Some(Path::new("../base/scratch"))
+    );
+    assert_eq!(cfg.reference[0].winmd, Path::new("../base/winmd/Ref.winmd"));
+
+    let debug = bnd_winmd::config::load_config_profile(&root.join("app/app.toml"), Some("debug"))
+        .expect("load debug profile");
+    assert_eq!(debug.include_paths, [Path::new("../base/debug/include")]);
+}
+
+#[test]
+fn extends_rebases_partition_headers_of_a_sibling_config() {
+    let root = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("extends_partition");
+    write(
+        &root.join("base/base.toml"),
+        r#"
+[[partition]]
+namespace = "Base"
+library = "base"
+headers = ["base.h", "stdio.h", "gen/*.h"]
+traverse = ["base.h", "gen"]
+overrides = [{ file = "gen/extra.h", namespace = "Base.Extra" }]
+"#,
+    );
+    write(&root.join("base/base.h"), "typedef int base_t;\n");
+    write(&root.join("base/gen/extra.h"), "typedef int extra_t;\n");
+    write(
+        &root.join("app/app.toml"),
+        r#"
+extends = "../base/base.toml"
+
+[output]
+name = "App"
+
+[[partition]]
+namespace = "App"
+library = "app"
+headers = ["app.h"]
+"#,
+    );
+
+    let cfg = bnd_winmd::config::load_config(&root.join("app/app.toml")).expect("load config");
+    let base = &cfg.partition[0];
+    // `stdio.h` is not next to the base config, so it stays an
+    // include-path lookup.
+    assert_eq!(
+        base.headers,
+        [
+            Path::new("../base/base.h"),
+            Path::new("stdio.h"),
+            Path::new("../base/gen/*.h")
+        ]
+    );
+    assert_eq!(
+        base.traverse,
+        [Path::new("../base/base.h"), Path::new("../base/gen")]
+    );
+    assert_eq!(base.overrides[0].file, Path::new("../base/gen/extra.h"));
+    // The extending config's own partitions are left alone.
+    assert_eq!(cfg.partition[1].headers, [Path::new("app.h")]);
+}
@@ -0,0 +1,138 @@
+//! Partition `frameworks` resolution. Only loads the config — no clang
+//! parsing.
+
+use std::path::{Path, PathBuf};
+
+/// A fake macOS SDK with `Widget.framework` and `Extra.framework`.
+fn sdk(name: &str) -> PathBuf {
+    let sdk = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join(name);
+    for framework in ["Widget", "Extra"] {
+        let headers = sdk.join(format!(
+            "System/Library/Frameworks/{framework}.framework/Headers"
+        ));
+        std::fs::create_dir_all(&headers).expect("create framework");
+        std::fs::write(headers.join(format!("{framework}.h")), "#pragma once\n")
+            .expect("write umbrella header");
+    }
+    sdk
+}
+
+fn load(name: &str, toml: &str) -> anyhow::Result<bnd_winmd::config::Config> {
+    let path = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join(format!("{name}.toml"));
+    std::fs::write(&path, toml).expect("write config");
+    bnd_winmd::config::load_config(&path)
+}
+
+#[test]
+fn frameworks_fill_headers_traverse_and_library() {
+    let sdk = sdk("frameworks_sdk");
+    let cfg = load(
+        "frameworks",
+        &format!(
+            r#"
+sysroot = "{}"
+
+[output]
+name = "Mac"
+
+[[partition]]
+namespace = "Mac.Widget"
+frameworks = ["Widget", "Extra"]
+"#,
+            sdk.display()
+        ),
+    )
+    .expect("load config");
+
+    let frameworks = sdk.join("System/Library/Frameworks");
+    let p = &cfg.partition[0];
+    assert_eq!(p.library, "Widget.framework/Widget");
+    assert_eq!(
+        p.headers,
+        [
+            frameworks.join("Widget.framework/Headers/Widget.h"),
+            frameworks.join("Extra.framework/Headers/Extra.h"),
+        ]
+    );
+    assert_eq!(
+        p.traverse,
+        [
+            frameworks.join("Widget.framework/Headers"),
+            frameworks.join("Extra.framework/Headers"),
+        ]
+    );
+    assert_eq!(p.clang_args, [format!("-F{}", frameworks.display())]);
+}
+
+#[test]
+fn explicit_settings_are_kept() {
+    let sdk = sdk("frameworks_explicit_sdk");
+    let cfg = load(
+        "frameworks_explicit",
+        &format!(
+            r#"
+framework_paths = ["{}"]
+
+[output]
+name = "Mac"
+
+[[partition]]
+namespace = "Mac.Widget"
+library = "widget"
+headers = ["Widget/Widget.h"]
+traverse = ["Widget/Widget.h"]
+frameworks = ["Widget"]
+"#,
+            sdk.join("System/Library/Frameworks").display()
+        ),
+    )
+    .expect("load config");
+
+    let p = &cfg.partition[0];
+    assert_eq!(p.library, "widget");
+    assert_eq!(p.headers, [Path::new("Widget/Widget.h")]);
+    assert_eq!(p.traverse, [Path::new("Widget/Widget.h")]);
+}
+
+#[test]
+fn missing_framework_is_an_error() {
+    let sdk = sdk("frameworks_missing_sdk");
+    let err = load(
+        "frameworks_missing",
+        &format!(
+            r#"
+sysroot = "{}"
+
+[output]
+name = "Mac"
+
+[[partition]]
+namespace = "Mac.Gadget"
+frameworks = ["Gadget"]
+"#,
+            sdk.display()
+        ),
+    )
+    .expect_err("Gadget.framework does not exist");
+    assert!(
+        err.to_string().contains("framework Gadget not found"),
+        "{err}"
+    );
+}
+
+#[test]
+fn missing_headers_without_frameworks_is_an_error() {
+    let err = load(
+        "frameworks_no_headers",
+        r#"
+[output]
+name = "Mac"
+
+[[partition]]
+namespace = "Mac.Widget"
+library = "widget"
+"#,
+    )
+    .expect_err("no headers and no frameworks");
+    assert!(err.to_string().contains("has no headers"), "{err}");
+}
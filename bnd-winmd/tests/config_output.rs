@@ -0,0 +1,73 @@
+//! `[output]` assembly version, company and copyright. Only loads the
+//! config — no clang parsing.
+
+use std::path::PathBuf;
+
+use bnd_winmd::assembly::AssemblyVersion;
+
+fn load(name: &str, output: &str) -> anyhow::Result<bnd_winmd::config::Config> {
+    let path = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join(format!("{name}.toml"));
+    let toml = format!(
+        r#"
+[output]
+name = "Out"
+{output}
+
+[[partition]]
+namespace = "Out"
+library = "c"
+headers = ["out.h"]
+"#
+    );
+    std::fs::write(&path, toml).expect("write config");
+    bnd_winmd::config::load_config(&path)
+}
+
+#[test]
+fn version_parts_default_to_zero() {
+    let cfg = load(
+        "output_version",
+        r#"version = "1.2"
+company = "Acme"
+copyright = "(c) Acme""#,
+    )
+    .expect("load config");
+    assert_eq!(cfg.output.version, Some(AssemblyVersion([1, 2, 0, 0])));
+    assert_eq!(cfg.output.company.as_deref(), Some("Acme"));
+    assert_eq!(cfg.output.copyright.as_deref(), Some("(c) Acme"));
+    assert_eq!(cfg.output.version.unwrap().to_string(), "1.2.0.0");
+
+    let cfg = load("output_no_version", "").expect("load config");
+    assert_eq!(cfg.output.version, None);
+}
+
+#[test]
+fn malformed_versions_are_rejected() {
+    for (version, message) in [
+        ("1.2.3.4.5", "must have one to four parts"),
+        ("1.x", "`x` is not a number"),
+        ("1.70000", "`70000` is not a number"),
+    ] {
+        let err =
+            load("output_bad_version", &format!("version = \"{version}\"")).expect_err(version);
+        assert!(format!("{err:#}").contains(message), "{version}: {err:#}");
+    }
+}
+
+#[test]
+fn builder_sets_assembly_metadata() {
+    let cfg = bnd_winmd::config::Config::builder()
+        .output("Out")
+        .version("4.5.6.7".parse().unwrap())
+        .company("Acme")
+        .copyright("(c) Acme")
+        .partition(
+            bnd_winmd::config::PartitionConfig::new("Out")
+                .header("out.h")
+                .library("c"),
+        )
+        .build()
+        .expect("build config");
+    assert_eq!(cfg.output.version, Some(AssemblyVersion([4, 5, 6, 7])));
+    assert_eq!(cfg.output.company.as_deref(), Some("Acme"));
+}
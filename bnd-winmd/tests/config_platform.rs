@@ -0,0 +1,68 @@
+//! Per-platform `library` tables. Only loads the config — no clang
+//! parsing.
+
+use std::path::PathBuf;
+
+fn load(
+    name: &str,
+    toml: &str,
+    profile: Option<&str>,
+) -> anyhow::Result<bnd_winmd::config::Config> {
+    let path = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join(format!("{name}.toml"));
+    std::fs::write(&path, toml).expect("write config");
+    bnd_winmd::config::load_config_profile(&path, profile)
+}
+
+const LIBC: &str = r#"
+[output]
+name = "libc"
+
+[[partition]]
+namespace = "libc.stdio"
+library = { linux = "c", macos = "System", windows = "ucrtbase" }
+headers = ["stdio.h"]
+
+[[partition]]
+namespace = "libc.z"
+library = "z"
+headers = ["zlib.h"]
+
+[[profile.extra.partition]]
+namespace = "libc.math"
+library = { linux = "m", macos = "System", windows = "ucrtbase" }
+headers = ["math.h"]
+"#;
+
+#[test]
+fn platform_selects_library_names() {
+    for (platform, stdio, math) in [
+        ("linux", "c", "m"),
+        ("macos", "System", "System"),
+        ("windows", "ucrtbase", "ucrtbase"),
+    ] {
+        let toml = format!("platform = \"{platform}\"\n{LIBC}");
+        let cfg = load(&format!("platform_{platform}"), &toml, Some("extra")).expect("load config");
+        let library = |ns: &str| {
+            cfg.partition
+                .iter()
+                .find(|p| p.namespace == ns)
+                .unwrap()
+                .library
+                .clone()
+        };
+        assert_eq!(library("libc.stdio"), stdio, "{platform}");
+        assert_eq!(library("libc.z"), "z", "plain names are kept");
+        assert_eq!(library("libc.math"), math, "{platform}: profile partition");
+    }
+}
+
+#[test]
+fn missing_platform_entry_is_an_error() {
+    let toml = format!("platform = \"freebsd\"\n{LIBC}");
+    let err = load("platform_missing", &toml, None).expect_err("no freebsd entry");
+    assert!(
+        err.to_string()
+            .contains("partition libc.stdio: `library` has no entry for platform `freebsd`"),
+        "{err}"
+    );
+}
@@ -0,0 +1,92 @@
+//! `[profile.<name>]` config variants. Only loads the config — no clang
+//! parsing.
+
+use std::path::{Path, PathBuf};
+
+const POSIX: &str = r#"
+include_paths = ["/usr/include/x86_64-linux-gnu", "/usr/include"]
+
+[defines]
+_GNU_SOURCE = true
+_FILE_OFFSET_BITS = 64
+
+[output]
+name = "posix"
+
+[[partition]]
+namespace = "posix.stdio"
+library = "c"
+headers = ["stdio.h"]
+
+[[partition]]
+namespace = "posix.gnu"
+library = "c"
+headers = ["gnu/libc-version.h"]
+
+[profile.musl]
+include_paths = ["/usr/include/x86_64-linux-musl"]
+remove_include_paths = ["/usr/include/x86_64-linux-gnu"]
+defines = { _FILE_OFFSET_BITS = 32 }
+remove_defines = ["_GNU_SOURCE"]
+remove_partitions = ["posix.gnu"]
+
+[[profile.musl.partition]]
+namespace = "posix.musl"
+library = "c"
+headers = ["musl.h"]
+
+[profile.stale]
+remove_partitions = ["posix.renamed"]
+"#;
+
+fn config_path() -> PathBuf {
+    let path = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("profile.toml");
+    std::fs::write(&path, POSIX).expect("write config");
+    path
+}
+
+#[test]
+fn profile_adds_and_removes_entries() {
+    let path = config_path();
+    let glibc = bnd_winmd::config::load_config(&path).expect("load config");
+    assert_eq!(glibc.partition.len(), 2);
+    assert_eq!(
+        glibc.effective_clang_args(),
+        ["-D_FILE_OFFSET_BITS=64", "-D_GNU_SOURCE"]
+    );
+
+    let musl =
+        bnd_winmd::config::load_config_profile(&path, Some("musl")).expect("load musl profile");
+    let namespaces: Vec<_> = musl
+        .partition
+        .iter()
+        .map(|p| p.namespace.as_str())
+        .collect();
+    assert_eq!(namespaces, ["posix.stdio", "posix.musl"]);
+    assert_eq!(
+        musl.include_paths,
+        [
+            Path::new("/usr/include"),
+            Path::new("/usr/include/x86_64-linux-musl")
+        ]
+    );
+    assert_eq!(musl.effective_clang_args(), ["-D_FILE_OFFSET_BITS=32"]);
+}
+
+#[test]
+fn unknown_or_stale_profile_is_an_error() {
+    let path = config_path();
+    let err =
+        bnd_winmd::config::load_config_profile(&path, Some("uclibc")).expect_err("unknown profile");
+    assert!(
+        format!("{err:#}").contains("no profile `uclibc` (defined: musl, stale)"),
+        "{err:#}"
+    );
+
+    let err =
+        bnd_winmd::config::load_config_profile(&path, Some("stale")).expect_err("stale profile");
+    assert!(
+        format!("{err:#}").contains("profile stale: no partition `posix.renamed` to remove"),
+        "{err:#}"
+    );
+}
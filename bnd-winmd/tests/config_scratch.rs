@@ -0,0 +1,70 @@
+//! Wrapper files in a caller-provided scratch dir. Only writes wrappers —
+//! no clang parsing.
+
+use std::path::{Path, PathBuf};
+
+use bnd_winmd::config::PartitionConfig;
+
+fn scratch(name: &str) -> PathBuf {
+    let dir = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join(name);
+    let _ = std::fs::remove_dir_all(&dir);
+    dir
+}
+
+#[test]
+fn wrapper_names_carry_a_content_hash() {
+    let dir = scratch("scratch_hash");
+    let base = Path::new("/nonexistent");
+    let a = PartitionConfig::new("Lib.Core").header("a.h").header("b.h");
+    let b = PartitionConfig::new("Lib.Core").header("a.h").header("c.h");
+
+    let path_a = a.wrapper_header(&dir, base, &[]).expect("write wrapper a");
+    let path_b = b.wrapper_header(&dir, base, &[]).expect("write wrapper b");
+    assert_ne!(path_a, path_b, "same namespace, different headers");
+    assert!(path_a.starts_with(&dir), "{}", path_a.display());
+    let name = path_a.file_name().unwrap().to_str().unwrap();
+    assert!(
+        name.starts_with("Lib_Core_") && name.ends_with("_wrapper.c"),
+        "{name}"
+    );
+    assert_eq!(
+        std::fs::read_to_string(&path_a).unwrap(),
+        "#include <a.h>\n#include <b.h>\n"
+    );
+    assert_eq!(
+        std::fs::read_to_string(&path_b).unwrap(),
+        "#include <a.h>\n#include <c.h>\n"
+    );
+
+    // Same content, same file.
+    let again = a
+        .wrapper_header(&dir, base, &[])
+        .expect("rewrite wrapper a");
+    assert_eq!(again, path_a);
+}
+
+#[test]
+fn single_header_needs_no_wrapper() {
+    let dir = scratch("scratch_single");
+    let path = PartitionConfig::new("Lib")
+        .header("/usr/include/stdio.h")
+        .wrapper_header(&dir, Path::new("."), &[])
+        .expect("resolve header");
+    assert_eq!(path, Path::new("/usr/include/stdio.h"));
+    assert!(!dir.exists());
+}
+
+#[test]
+fn unwritable_scratch_dir_is_an_error() {
+    let file = scratch("scratch_file");
+    std::fs::write(&file, "not a directory").unwrap();
+    let err = PartitionConfig::new("Lib")
+        .header("a.h")
+        .header("b.h")
+        .wrapper_header(&file, Path::new("."), &[])
+        .unwrap_err();
+    assert!(
+        format!("{err:#}").contains("creating scratch dir"),
+        "{err:#}"
+    );
+}
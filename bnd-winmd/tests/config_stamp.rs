@@ -0,0 +1,89 @@
+//! The stamp's config hash, and reading a stamp back. Only loads configs
+//! and emits from IR — no clang parsing.
+
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use bnd_winmd::stamp::config_hash;
+
+const BASE: &str = r#"
+include_paths = ["include"]
+scratch_dir = "scratch"
+"#;
+
+const APP: &str = r#"
+extends = "base.toml"
+
+[output]
+name = "App"
+
+[[partition]]
+namespace = "App"
+library = "app"
+headers = ["app.h"]
+
+[profile.debug]
+defines = { APP_DEBUG = true }
+"#;
+
+/// Write the configs to a fresh `<tmp>/<name>` checkout.
+fn checkout(name: &str, app: &str) -> PathBuf {
+    let dir = Path::new(env!("CARGO_TARGET_TMPDIR"))
+        .join("config_stamp")
+        .join(name);
+    std::fs::create_dir_all(&dir).expect("create checkout");
+    std::fs::write(dir.join("base.toml"), BASE).expect("write base config");
+    std::fs::write(dir.join("app.toml"), app).expect("write config");
+    dir.join("app.toml")
+}
+
+#[test]
+fn config_hash_ignores_checkout_dir_and_hooks() {
+    let a = bnd_winmd::config::load_config(&checkout("a", APP)).expect("load a");
+    let mut b = bnd_winmd::config::load_config(&checkout("b", APP)).expect("load b");
+    b.type_map_hook = Some(Arc::new(|_: &str, _: bnd_winmd::CType| None));
+    assert_ne!(a.config_files, b.config_files);
+    assert_eq!(config_hash(&a), config_hash(&b));
+}
+
+#[test]
+fn config_hash_follows_content_and_profile() {
+    let path = checkout("edited", APP);
+    let plain = bnd_winmd::config::load_config(&path).expect("load config");
+    let debug = bnd_winmd::config::load_config_profile(&path, Some("debug")).expect("load debug");
+    assert_ne!(config_hash(&plain), config_hash(&debug));
+
+    let edited = checkout(
+        "edited_library",
+        &APP.replace("library = \"app\"", "library = \"app2\""),
+    );
+    let edited = bnd_winmd::config::load_config(&edited).expect("load edited");
+    assert_ne!(config_hash(&plain), config_hash(&edited));
+}
+
+#[test]
+fn stamp_reads_back_from_emitted_winmd() {
+    let ir = bnd_winmd::ir::Ir::from_json(
+        r#"{
+  "format": 1,
+  "assembly": "Stamped",
+  "stamp": {
+    "version": "0.0.0",
+    "config_hash": "0123456789abcdef",
+    "headers": [["stamped.h", "fedcba9876543210"]],
+    "defines": [["", "-DSTAMPED=1"]]
+  },
+  "partitions": [],
+  "types": {},
+  "interfaces": []
+}"#,
+    )
+    .expect("parse IR");
+    let bytes = bnd_winmd::emit_ir(&ir).expect("emit IR");
+    let stamp = bnd_winmd::stamp::Stamp::from_bytes(bytes.clone()).expect("stamped");
+    assert_eq!(stamp, ir.stamp);
+
+    let path = Path::new(env!("CARGO_TARGET_TMPDIR")).join("config_stamp_read.winmd");
+    std::fs::write(&path, bytes).expect("write winmd");
+    assert_eq!(bnd_winmd::stamp(&path).expect("read stamp"), ir.stamp);
+}
@@ -0,0 +1,54 @@
+//! `sysroot` path re-rooting. Only loads the config — no clang parsing.
+
+use std::path::{Path, PathBuf};
+
+#[test]
+fn sysroot_reroots_absolute_paths() {
+    let path = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("sysroot.toml");
+    std::fs::write(
+        &path,
+        r#"
+sysroot = "/opt/ndk/sysroot"
+include_paths = ["/usr/include", "local/include"]
+clang_args = ["-DFOO"]
+
+[output]
+name = "Cross"
+
+[[partition]]
+namespace = "Cross"
+library = "c"
+headers = ["/usr/include/stdio.h"]
+traverse = ["stdio.h", "/opt/ndk/sysroot/usr/include/bits"]
+"#,
+    )
+    .expect("write config");
+
+    let cfg = bnd_winmd::config::load_config(&path).expect("load config");
+    assert_eq!(
+        cfg.include_paths,
+        [
+            Path::new("/opt/ndk/sysroot/usr/include"),
+            Path::new("local/include")
+        ]
+    );
+    assert_eq!(
+        cfg.effective_clang_args(),
+        ["--sysroot=/opt/ndk/sysroot", "-DFOO"]
+    );
+    assert_eq!(cfg.clang_args, ["-DFOO"]);
+
+    let p = &cfg.partition[0];
+    assert_eq!(
+        p.headers,
+        [Path::new("/opt/ndk/sysroot/usr/include/stdio.h")]
+    );
+    assert_eq!(
+        p.traverse,
+        [
+            Path::new("stdio.h"),
+            Path::new("/opt/ndk/sysroot/usr/include/bits"),
+        ],
+        "relative and already-rooted entries are left alone"
+    );
+}
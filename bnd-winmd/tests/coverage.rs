@@ -0,0 +1,58 @@
+//! Coverage report: declared vs emitted per partition, with omissions.
+
+use std::path::{Path, PathBuf};
+
+fn fixture(name: &str) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("../tests/fixtures")
+        .join(name)
+        .join(format!("{name}.toml"))
+}
+
+#[test]
+fn omissions_are_counted_with_their_reason() {
+    let report = bnd_winmd::coverage(&fixture("features")).expect("coverage");
+    let root = report
+        .partitions
+        .iter()
+        .find(|p| p.namespace == "FeaturesTest")
+        .expect("FeaturesTest partition");
+
+    let functions = &root.functions;
+    let log = functions
+        .omitted
+        .iter()
+        .find(|o| o.name == "feature_log")
+        .expect("variadic feature_log omitted");
+    assert_eq!(log.reason, "variadic");
+    assert!(log.location.as_ref().unwrap().file.ends_with("features.h"));
+    assert_eq!(
+        functions.declared,
+        functions.emitted + functions.omitted.len()
+    );
+    assert!(functions.emitted > 0 && functions.percent() < 100.0);
+
+    // Every kind balances, and nothing out of scope is counted.
+    for p in &report.partitions {
+        for (kind, c) in p.kinds() {
+            assert_eq!(c.declared, c.emitted + c.omitted.len(), "{kind}");
+            assert!(c.omitted.iter().all(|o| o.reason != "out_of_scope"));
+        }
+    }
+
+    let summary = report.to_string();
+    assert!(
+        summary.contains("FeaturesTest (features)\n  functions"),
+        "{summary}"
+    );
+    assert!(summary.contains("    feature_log: variadic\n"), "{summary}");
+    let (declared, emitted) = report.totals();
+    assert!(summary.ends_with(&format!(
+        "FeaturesTest: {emitted}/{declared} declarations emitted ({:.1}%)\n",
+        emitted as f64 * 100.0 / declared as f64
+    )));
+
+    let json: serde_json::Value = serde_json::from_str(&report.to_json().unwrap()).unwrap();
+    assert_eq!(json["assembly"], "FeaturesTest");
+    assert!(json["partitions"][0]["functions"]["omitted"].is_array());
+}
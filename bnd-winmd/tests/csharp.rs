@@ -0,0 +1,83 @@
+//! C# backend: render fixtures straight from the model and check the
+//! P/Invoke declarations.
+
+use std::path::{Path, PathBuf};
+use std::sync::LazyLock;
+
+fn fixture(name: &str) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("../tests/fixtures")
+        .join(name)
+        .join(format!("{name}.toml"))
+}
+
+fn file<'a>(files: &'a [(PathBuf, String)], name: &str) -> &'a str {
+    files
+        .iter()
+        .find(|(path, _)| path == Path::new(name))
+        .map(|(_, text)| text.as_str())
+        .unwrap_or_else(|| {
+            panic!(
+                "no {name} in {:?}",
+                files.iter().map(|f| &f.0).collect::<Vec<_>>()
+            )
+        })
+}
+
+static SIMPLE: LazyLock<Vec<(PathBuf, String)>> =
+    LazyLock::new(|| bnd_winmd::csharp(&fixture("simple")).expect("render simple"));
+
+static MULTI: LazyLock<Vec<(PathBuf, String)>> =
+    LazyLock::new(|| bnd_winmd::csharp(&fixture("multi")).expect("render multi"));
+
+#[test]
+fn simple_renders_pinvoke_declarations() {
+    let source = file(&SIMPLE, "SimpleTest.cs");
+    for expected in [
+        "namespace SimpleTest;",
+        "public enum Color : uint\n{",
+        "    COLOR_BLUE = 2,",
+        "[StructLayout(LayoutKind.Explicit, Size = 64)]\npublic unsafe struct CacheAligned",
+        "    public fixed int values[4];",
+        "public unsafe struct CompareFunc\n{\n    public delegate* unmanaged[Cdecl]<void*, void*, int> Value;",
+        "public static unsafe partial class Apis",
+        "    public const int MAX_WIDGETS = 256;",
+        "    [DllImport(\"simple\", EntryPoint = \"create_widget\", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]\n    public static extern int create_widget(sbyte* name, Rect bounds, Widget* @out);",
+        "    public static extern void destroy_widget(Widget* w);",
+    ] {
+        assert!(
+            source.contains(expected),
+            "missing `{expected}` in:\n{source}"
+        );
+    }
+    // Every field offset is known, so unions overlay their members.
+    let value = source
+        .split("public unsafe struct Value\n")
+        .nth(1)
+        .expect("Value union");
+    assert!(
+        value.starts_with("{\n    [FieldOffset(0)]\n    public int i;"),
+        "{value}"
+    );
+}
+
+#[test]
+fn cross_namespace_references_are_qualified() {
+    let widgets = file(&MULTI, "MultiTest.Widgets.cs");
+    assert!(
+        widgets.contains("namespace MultiTest.Widgets;"),
+        "{widgets}"
+    );
+    assert!(
+        widgets.contains("public global::MultiTest.Types.Color color;"),
+        "{widgets}"
+    );
+    assert!(
+        widgets.contains("global::MultiTest.Types.Rect bounds"),
+        "{widgets}"
+    );
+    // Types and functions stay in the file of their own namespace.
+    let types = file(&MULTI, "MultiTest.Types.cs");
+    assert!(types.contains("public unsafe struct Rect"), "{types}");
+    assert!(!widgets.contains("public unsafe struct Rect"), "{widgets}");
+}
@@ -0,0 +1,68 @@
+//! Markdown API docs: render fixture models and check the pages.
+
+use std::path::{Path, PathBuf};
+use std::sync::LazyLock;
+
+fn fixture(name: &str) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("../tests/fixtures")
+        .join(name)
+        .join(format!("{name}.toml"))
+}
+
+fn page<'a>(pages: &'a [(PathBuf, String)], name: &str) -> &'a str {
+    pages
+        .iter()
+        .find(|(path, _)| path == Path::new(name))
+        .map(|(_, text)| text.as_str())
+        .unwrap_or_else(|| panic!("no {name}"))
+}
+
+static SIMPLE: LazyLock<Vec<(PathBuf, String)>> = LazyLock::new(|| {
+    let ir = bnd_winmd::ir(&fixture("simple")).expect("extract simple");
+    bnd_winmd::docs::render(&ir.partitions)
+});
+
+static MULTI: LazyLock<Vec<(PathBuf, String)>> = LazyLock::new(|| {
+    let ir = bnd_winmd::ir(&fixture("multi")).expect("extract multi");
+    bnd_winmd::docs::render(&ir.partitions)
+});
+
+#[test]
+fn simple_page_lists_signatures_layouts_and_constants() {
+    let md = page(&SIMPLE, "SimpleTest.md");
+    for expected in [
+        "# SimpleTest\n\nLibrary: `simple`",
+        "### `create_widget`\n\n```rust\nfn create_widget(name: *const i8, bounds: Rect, out: *mut Widget) -> i32\n```",
+        "| `bounds` | [`Rect`](#rect) |",
+        "### `Rect`\n\nStruct, 16 bytes, aligned to 4.",
+        "| 4 | `y` | `i32` |",
+        "| 8 | `values` | `[i32; 4]` |",
+        "| 24 | `color` | [`Color`](#color) |",
+        "### `CacheAligned`\n\nStruct, 64 bytes, aligned to 64.",
+        "### `Value`\n\nUnion, 4 bytes, aligned to 4.",
+        "| `COLOR_GREEN` | `1` |",
+        "| `SIGNAL_ABRT` | `6` (alias of `SIGNAL_IOT`) |",
+        "### `CompareFunc`\n\nAlias of `fn(*const c_void, *const c_void) -> i32`.",
+        "| `MAX_WIDGETS` | `i32` | `256` |",
+    ] {
+        assert!(md.contains(expected), "missing `{expected}` in:\n{md}");
+    }
+    assert!(page(&SIMPLE, "index.md").contains("- [SimpleTest](SimpleTest.md)"));
+}
+
+#[test]
+fn references_link_across_namespace_pages() {
+    let widgets = page(&MULTI, "MultiTest.Widgets.md");
+    assert!(
+        widgets.contains("[`Color`](MultiTest.Types.md#color)"),
+        "{widgets}"
+    );
+    let index = page(&MULTI, "index.md");
+    assert!(
+        index.contains(
+            "- [MultiTest.Types](MultiTest.Types.md)\n- [MultiTest.Widgets](MultiTest.Widgets.md)"
+        ),
+        "{index}"
+    );
+}
@@ -0,0 +1,70 @@
+//! IDL export: render fixtures straight from the model and check the
+//! listing.
+
+use std::path::{Path, PathBuf};
+use std::sync::LazyLock;
+
+fn fixture(name: &str) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("../tests/fixtures")
+        .join(name)
+        .join(format!("{name}.toml"))
+}
+
+fn file<'a>(files: &'a [(PathBuf, String)], name: &str) -> &'a str {
+    files
+        .iter()
+        .find(|(path, _)| path == Path::new(name))
+        .map(|(_, text)| text.as_str())
+        .unwrap_or_else(|| panic!("no {name}"))
+}
+
+static SIMPLE: LazyLock<Vec<(PathBuf, String)>> =
+    LazyLock::new(|| bnd_winmd::idl(&fixture("simple")).expect("render simple"));
+
+static FEATURES: LazyLock<Vec<(PathBuf, String)>> =
+    LazyLock::new(|| bnd_winmd::idl(&fixture("features")).expect("render features"));
+
+fn assert_contains(text: &str, expected: &[&str]) {
+    for expected in expected {
+        assert!(text.contains(expected), "missing `{expected}` in:\n{text}");
+    }
+}
+
+#[test]
+fn simple_renders_declarations() {
+    let idl = file(&SIMPLE, "SimpleTest.idl");
+    assert!(idl.contains("\nnamespace SimpleTest\n{\n    enum Color : UInt32\n    {\n"));
+    assert_contains(
+        idl,
+        &[
+            "        COLOR_BLUE = 2,\n    };",
+            "    struct Widget\n    {\n        const Int8* name;\n        Int32 values[4];\n        Color color;\n    };",
+            "    union Value\n    {",
+            "    delegate Int32 CompareFunc(const void* a, const void* b);",
+            "    [dllimport(\"simple\")]\n    static runtimeclass Apis\n    {",
+            "        const Int32 MAX_WIDGETS = 256;",
+            "        static Int32 create_widget(const Int8* name, Rect bounds, Widget* out);",
+            "        static void destroy_widget(Widget* w);",
+        ],
+    );
+    assert!(idl.ends_with("    }\n}\n"), "{idl}");
+}
+
+#[test]
+fn annotations_render_as_attributes() {
+    let idl = file(&FEATURES, "FeaturesTest.idl");
+    assert_contains(
+        idl,
+        &[
+            "[free_with(feature_close)]",
+            "[in] void* source, [out, byte_count(len)] void* buf",
+            "[byte_count(n), not_null_terminated] void* dst",
+            "[in, size_is(count)] const Int32* values",
+            "[size_is(n)] Int32* values",
+            "[associated_enum(FeatureMode)] Int32 mode",
+            "        [return_convention(\"errno\")]\n        static Int32 feature_set_mode(",
+            "        [entrypoint(\"feature_lock64\")]\n        static Int32 feature_lock(Int32 fd);",
+        ],
+    );
+}
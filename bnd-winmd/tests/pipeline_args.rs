@@ -0,0 +1,116 @@
+//! Tests for the windows-bindgen arguments assembled by
+//! `pipeline::Builder`.
+
+use std::path::Path;
+
+use bnd_winmd::pipeline::{Builder, Layout, ReferenceStyle};
+
+#[test]
+fn package_with_reference() {
+    let args = Builder::new("openssl.toml", "out")
+        .input("linux.winmd")
+        .filter("openssl")
+        .reference("bnd_linux", ReferenceStyle::Full, "libc")
+        .layout(Layout::Package)
+        .no_toml(true)
+        .bindgen_args(Path::new("openssl.winmd"));
+
+    assert_eq!(
+        args,
+        [
+            "--in",
+            "openssl.winmd",
+            "--in",
+            "linux.winmd",
+            "--out",
+            "out",
+            "--filter",
+            "openssl",
+            "--reference",
+            "bnd_linux,full,libc",
+            "--package",
+            "--sys",
+            "--no-toml",
+        ]
+    );
+}
+
+#[test]
+fn flat_without_sys() {
+    let args = Builder::new("simple.toml", "src/bindings.rs")
+        .filter("SimpleTest")
+        .layout(Layout::Flat)
+        .sys(false)
+        .bindgen_args(Path::new("simple.winmd"));
+
+    assert_eq!(
+        args,
+        [
+            "--in",
+            "simple.winmd",
+            "--out",
+            "src/bindings.rs",
+            "--filter",
+            "SimpleTest",
+            "--flat",
+        ]
+    );
+}
+
+#[test]
+fn reference_section_feeds_imports_and_bindgen() {
+    let path = std::path::PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("reference.toml");
+    std::fs::write(
+        &path,
+        r#"
+[output]
+name = "openssl"
+
+[[type_import]]
+winmd = "extra.winmd"
+namespace = "extra"
+
+[[reference]]
+crate = "bnd_linux"
+namespace = "libc"
+winmd = "../bnd-linux/winmd/bnd-linux.winmd"
+
+[[reference]]
+crate = "windows_sys"
+namespace = "Windows"
+winmd = "windows.winmd"
+style = "skip-root"
+"#,
+    )
+    .expect("write config");
+    let cfg = bnd_winmd::config::load_config(&path).expect("load config");
+
+    let imports: Vec<_> = cfg.winmd_imports().map(|(_, ns)| ns).collect();
+    assert_eq!(imports, ["extra", "libc", "Windows"]);
+
+    let builder = cfg
+        .reference
+        .iter()
+        .fold(Builder::new(&path, "out"), |b, r| {
+            b.reference_config(r, &r.winmd)
+        });
+    let args = builder.bindgen_args(Path::new("openssl.winmd"));
+    assert_eq!(
+        args,
+        [
+            "--in",
+            "openssl.winmd",
+            "--in",
+            "../bnd-linux/winmd/bnd-linux.winmd",
+            "--in",
+            "windows.winmd",
+            "--out",
+            "out",
+            "--reference",
+            "bnd_linux,full,libc",
+            "--reference",
+            "windows_sys,skip-root,Windows",
+            "--sys",
+        ]
+    );
+}
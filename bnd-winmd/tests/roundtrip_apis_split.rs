@@ -0,0 +1,132 @@
+//! `apis_split` / `max_methods`: functions and constants sharded over the
+//! `Apis` classes of several sub-namespaces, all of which windows-bindgen
+//! turns into bindings.
+
+use std::path::{Path, PathBuf};
+use std::sync::LazyLock;
+
+use bnd_winmd::pipeline::{Builder, Layout};
+
+fn fixture(name: &str) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("../tests/fixtures/apis_split")
+        .join(name)
+}
+
+/// The Rust windows-bindgen generates from the split fixture, one module
+/// per namespace.
+static BINDINGS: LazyLock<String> = LazyLock::new(|| {
+    let dir = tempfile::tempdir().expect("tempdir");
+    let out = dir.path().join("bindings.rs");
+    Builder::new(fixture("split.toml"), &out)
+        .winmd(dir.path().join("split_test.winmd"))
+        .filter("SplitTest")
+        .layout(Layout::Modules)
+        .run()
+        .expect("generate split bindings");
+    std::fs::read_to_string(&out).expect("read bindings")
+});
+
+/// The body of `pub mod <path>` in [`BINDINGS`], up to the next module.
+fn module(path: &[&str]) -> &'static str {
+    let mut rest = BINDINGS.as_str();
+    for name in path {
+        let start = rest
+            .find(&format!("pub mod {name} {{"))
+            .unwrap_or_else(|| panic!("module {name} missing:\n{}", *BINDINGS));
+        rest = &rest[start + name.len() + 10..];
+    }
+    match rest.find("pub mod ") {
+        Some(end) => &rest[..end],
+        None => rest,
+    }
+}
+
+fn assert_members(path: &[&str], functions: &[&str], constants: &[&str]) {
+    let body = module(path);
+    for f in functions {
+        assert!(
+            body.contains(&format!("fn {f}(")),
+            "{f} missing from {path:?}:\n{body}"
+        );
+    }
+    for c in constants {
+        assert!(
+            body.contains(&format!("const {c}:")),
+            "{c} missing from {path:?}:\n{body}"
+        );
+    }
+}
+
+#[test]
+fn split_by_prefix() {
+    assert_members(&["SplitTest", "Prefix"], &["splitversion"], &[]);
+    assert_members(&["SplitTest", "Prefix", "split"], &[], &["SPLIT_VERSION"]);
+    assert_members(
+        &["SplitTest", "Prefix", "split_cipher"],
+        &["split_cipher_init", "split_cipher_update"],
+        &[],
+    );
+    assert_members(
+        &["SplitTest", "Prefix", "split_md"],
+        &["split_md_final", "split_md_init", "split_md_update"],
+        &["SPLIT_MD_BLOCK", "SPLIT_MD_SIZE"],
+    );
+}
+
+#[test]
+fn split_by_max_methods() {
+    assert_members(
+        &["SplitTest", "Count"],
+        &["split_cipher_init", "split_cipher_update"],
+        &["SPLIT_MD_BLOCK", "SPLIT_MD_SIZE"],
+    );
+    assert_members(
+        &["SplitTest", "Count", "apis1"],
+        &["split_md_final", "split_md_init"],
+        &["SPLIT_VERSION"],
+    );
+    assert_members(
+        &["SplitTest", "Count", "apis2"],
+        &["split_md_update", "splitversion"],
+        &[],
+    );
+}
+
+#[test]
+fn shard_colliding_with_prefix_is_an_error() {
+    let err = bnd_winmd::generate(&fixture("collide.toml"))
+        .expect_err("apis1 prefix and first shard share a namespace");
+    let msg = format!("{err:#}");
+    assert!(
+        msg.contains("CollideTest.apis1") && msg.contains("collides"),
+        "{msg}"
+    );
+}
+
+#[test]
+fn emit_rejects_zero_max_methods() {
+    // IR files skip the extract-time check, so emission validates too.
+    let partition = bnd_winmd::Partition {
+        namespace: "SplitTest.Zero".to_string(),
+        library: "split".to_string(),
+        library_soname: None,
+        delay_load: false,
+        structs: Vec::new(),
+        enums: Vec::new(),
+        functions: Vec::new(),
+        typedefs: Vec::new(),
+        constants: Vec::new(),
+        interfaces: Vec::new(),
+        skipped: Vec::new(),
+        apis_split: bnd_winmd::model::ApisSplit::None,
+        max_methods: Some(0),
+        pointer_width: None,
+    };
+    let err = bnd_winmd::emit::emit_winmd("Zero", &[partition], &Default::default())
+        .expect_err("max_methods = 0 must be rejected");
+    assert!(
+        err.to_string().contains("max_methods must be at least 1"),
+        "{err}"
+    );
+}
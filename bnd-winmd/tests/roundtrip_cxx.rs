@@ -0,0 +1,64 @@
+//! `language = "c++"`: extern "C" blocks and namespaces.
+
+use std::path::Path;
+use std::sync::LazyLock;
+
+static CXX_WINMD: LazyLock<Vec<u8>> = LazyLock::new(|| {
+    let config = Path::new(env!("CARGO_MANIFEST_DIR")).join("../tests/fixtures/cxx/cxx.toml");
+    bnd_winmd::generate(&config).expect("generate cxx winmd")
+});
+
+fn open_index() -> windows_metadata::reader::TypeIndex {
+    let file = windows_metadata::reader::File::new(CXX_WINMD.clone()).expect("parse winmd");
+    windows_metadata::reader::TypeIndex::new(vec![file])
+}
+
+/// Import name of `<namespace>.Apis::<function>`.
+fn import_name(namespace: &str, function: &str) -> String {
+    let index = open_index();
+    let method = index
+        .expect(namespace, "Apis")
+        .methods()
+        .find(|m| m.name() == function)
+        .unwrap_or_else(|| panic!("{namespace}.{function} not found"));
+    method
+        .impl_map()
+        .unwrap_or_else(|| panic!("{function} has no ImplMap"))
+        .import_name()
+        .to_string()
+}
+
+#[test]
+fn extern_c_block_is_extracted() {
+    let index = open_index();
+    assert!(index.contains("CxxTest", "CxxPoint"));
+    assert_eq!(import_name("CxxTest", "cxx_point_sum"), "cxx_point_sum");
+}
+
+#[test]
+fn namespaces_become_sub_namespaces() {
+    let index = open_index();
+    assert!(index.contains("CxxTest.geo", "Size"));
+    assert!(!index.contains("CxxTest", "Size"));
+    assert_eq!(
+        import_name("CxxTest.geo.detail", "geo_detail_version"),
+        "geo_detail_version"
+    );
+}
+
+#[test]
+fn cxx_linkage_functions_use_mangled_names() {
+    let import = import_name("CxxTest.geo", "area");
+    assert!(import.starts_with("_ZN3geo4area"), "{import}");
+}
+
+#[test]
+fn anonymous_namespace_is_skipped() {
+    let index = open_index();
+    assert!(
+        !index
+            .expect("CxxTest", "Apis")
+            .methods()
+            .any(|m| m.name() == "cxx_hidden")
+    );
+}
@@ -0,0 +1,57 @@
+//! `delay_load = true`: the partition is tagged for loaders, and the Rust
+//! `-sys` backend resolves its functions with `bnd_delayload::link!`
+//! through one `Library` static.
+
+use std::path::{Path, PathBuf};
+use std::sync::LazyLock;
+
+use windows_metadata::reader::HasAttributes;
+
+fn fixture() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("../tests/fixtures/delayload/delayload.toml")
+}
+
+static WINMD: LazyLock<Vec<u8>> =
+    LazyLock::new(|| bnd_winmd::generate(&fixture()).expect("generate delayload winmd"));
+
+static RUST_SYS: LazyLock<bnd_winmd::emit::GeneratedTree> =
+    LazyLock::new(|| bnd_winmd::rust_sys(&fixture()).expect("render delayload"));
+
+#[test]
+fn apis_carries_delay_load_attribute() {
+    let file = windows_metadata::reader::File::new(WINMD.clone()).expect("parse winmd");
+    let index = windows_metadata::reader::TypeIndex::new(vec![file]);
+    let apis = index.expect("DelayLoadTest", "Apis");
+
+    let attr = apis
+        .find_attribute("DelayLoadAttribute")
+        .expect("Apis should carry DelayLoadAttribute");
+    let values: Vec<_> = attr.value().into_iter().map(|(_, v)| v).collect();
+    assert_eq!(
+        values,
+        [windows_metadata::Value::Utf8("libm.so.6".to_string())]
+    );
+
+    // The ImplMap keeps the link name for linking consumers.
+    let cos = apis
+        .methods()
+        .find(|m| m.name() == "cos")
+        .expect("cos should be on Apis");
+    assert_eq!(
+        cos.impl_map().expect("cos ImplMap").import_scope().name(),
+        "m"
+    );
+}
+
+#[test]
+fn rust_sys_declares_delay_loaded_functions() {
+    let module = &RUST_SYS.modules["DelayLoadTest"];
+    for expected in [
+        "pub static LIBM_SO_6: bnd_delayload::Library =\n    bnd_delayload::Library::new(\"libm.so.6\");",
+        "bnd_delayload::link!(LIBM_SO_6 \"C\" fn cos(x: f64) -> f64);",
+        "bnd_delayload::link!(LIBM_SO_6 \"C\" fn sqrt(x: f64) -> f64);",
+    ] {
+        assert!(module.contains(expected), "missing {expected:?}:\n{module}");
+    }
+    assert!(!module.contains("#[link("), "{module}");
+}
@@ -0,0 +1,52 @@
+//! `[[partition.extra_source]]`: inline C appended to the translation unit
+//! is extracted with the partition's headers.
+
+use std::path::Path;
+use std::sync::LazyLock;
+
+use bnd_winmd::model::{CType, Partition};
+
+static PARTITION: LazyLock<Partition> = LazyLock::new(|| {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("../tests/fixtures/extrasource/extrasource.toml");
+    bnd_winmd::extract(&path)
+        .expect("extract extrasource")
+        .remove(0)
+});
+
+#[test]
+fn extra_source_typedef_extracted() {
+    let td = PARTITION
+        .typedefs
+        .iter()
+        .find(|t| t.name == "my_id_t")
+        .expect("my_id_t from extra_source");
+    assert_eq!(td.underlying_type, CType::U32);
+}
+
+#[test]
+fn extra_source_completes_forward_declaration() {
+    let session = PARTITION
+        .structs
+        .iter()
+        .find(|s| s.name == "Session")
+        .expect("Session struct");
+    let fields: Vec<_> = session.fields.iter().map(|f| f.name.as_str()).collect();
+    assert_eq!(fields, ["id", "flags"]);
+    assert_eq!(session.size, 8);
+}
+
+#[test]
+fn extra_source_define_extracted() {
+    assert!(
+        PARTITION.constants.iter().any(|c| c.name == "SESSION_MAX"),
+        "SESSION_MAX missing"
+    );
+    // The header's own declarations are still there.
+    let names: Vec<_> = PARTITION
+        .functions
+        .iter()
+        .map(|f| f.name.as_str())
+        .collect();
+    assert_eq!(names, ["session_open", "session_close"]);
+}
@@ -0,0 +1,984 @@
+//! Round-trip integration test for config-driven metadata features:
+//! parse features.h with features.toml → emit winmd → verify attributes.
+
+use std::path::Path;
+use std::sync::LazyLock;
+
+use windows_metadata::reader::HasAttributes;
+
+fn features_toml() -> std::path::PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("../tests/fixtures/features/features.toml")
+}
+
+static FEATURES_OUTPUT: LazyLock<bnd_winmd::GenerateOutput> = LazyLock::new(|| {
+    let path = features_toml();
+    let cfg = bnd_winmd::config::load_config(&path).expect("load features config");
+    bnd_winmd::generate_from_config(&cfg, path.parent().unwrap()).expect("generate features winmd")
+});
+
+static FEATURES_REPORT: LazyLock<bnd_winmd::report::ApiReport> =
+    LazyLock::new(|| bnd_winmd::report(&features_toml()).expect("build API report"));
+
+static FEATURES_PLAN: LazyLock<bnd_winmd::plan::Plan> =
+    LazyLock::new(|| bnd_winmd::plan(&features_toml()).expect("build plan"));
+
+fn open_index() -> windows_metadata::reader::TypeIndex {
+    let file =
+        windows_metadata::reader::File::new(FEATURES_OUTPUT.bytes.clone()).expect("parse winmd");
+    windows_metadata::reader::TypeIndex::new(vec![file])
+}
+
+#[test]
+fn raii_free_attribute_on_handle() {
+    let index = open_index();
+
+    let handle = index.expect("FeaturesTest", "FeatureHandle");
+    let attr = handle
+        .find_attribute("RAIIFreeAttribute")
+        .expect("FeatureHandle should carry RAIIFreeAttribute");
+    let args = attr.value();
+    assert_eq!(args.len(), 1, "RAIIFree takes one argument, got: {args:?}");
+    match &args[0].1 {
+        windows_metadata::Value::Utf8(free) => assert_eq!(free, "feature_close"),
+        other => panic!("unexpected RAIIFree argument: {other:?}"),
+    }
+}
+
+/// Return the single string argument of `attr_name` on the named parameter.
+fn param_attribute_arg(function: &str, param: &str, attr_name: &str) -> String {
+    let index = open_index();
+    let apis = index.expect("FeaturesTest", "Apis");
+    let method = apis
+        .methods()
+        .find(|m| m.name() == function)
+        .unwrap_or_else(|| panic!("{function} not found"));
+    let p = method
+        .params()
+        .find(|p| p.name() == param)
+        .unwrap_or_else(|| panic!("{function} has no param {param}"));
+    let attr = p
+        .find_attribute(attr_name)
+        .unwrap_or_else(|| panic!("{function}({param}) should carry {attr_name}"));
+    match attr.value().into_iter().next() {
+        Some((_, windows_metadata::Value::Utf8(s))) => s,
+        other => panic!("unexpected {attr_name} argument: {other:?}"),
+    }
+}
+
+#[test]
+fn associated_enum_on_param() {
+    assert_eq!(
+        param_attribute_arg("feature_set_mode", "mode", "AssociatedEnumAttribute"),
+        "FeatureMode"
+    );
+}
+
+#[test]
+fn associated_constant_on_param() {
+    assert_eq!(
+        param_attribute_arg("feature_set_flags", "flags", "AssociatedConstantAttribute"),
+        "FEATURE_FLAGS_DEFAULT"
+    );
+}
+
+#[test]
+fn glob_headers_and_directory_traverse() {
+    let index = open_index();
+
+    // alpha.h matched by `globbed/*.h`; nested/beta.h only reached via the
+    // `globbed` traverse directory.
+    for name in ["GlobAlpha", "GlobBeta"] {
+        assert!(
+            index.contains("FeaturesTest.Globbed", name),
+            "{name} should be extracted from the globbed partition"
+        );
+    }
+}
+
+#[test]
+fn delegate_calling_convention() {
+    let index = open_index();
+
+    // System.Runtime.InteropServices.CallingConvention values.
+    for (name, expected) in [
+        ("CdeclCallback", 2),
+        ("StdcallCallback", 3),
+        ("FastcallCallback", 5),
+    ] {
+        let delegate = index.expect("FeaturesTest.Callbacks", name);
+        let attr = delegate
+            .find_attribute("UnmanagedFunctionPointerAttribute")
+            .unwrap_or_else(|| panic!("{name} should carry UnmanagedFunctionPointerAttribute"));
+        match attr.value().into_iter().next() {
+            Some((_, windows_metadata::Value::I32(cc))) => assert_eq!(cc, expected, "{name}"),
+            other => panic!("unexpected UnmanagedFunctionPointer argument: {other:?}"),
+        }
+    }
+}
+
+#[test]
+fn partition_calling_convention() {
+    let index = open_index();
+    let apis = index.expect("FeaturesTest.Callbacks", "Apis");
+    let import = apis
+        .methods()
+        .find(|m| m.name() == "callconv_default")
+        .and_then(|m| m.impl_map())
+        .expect("callconv_default ImplMap");
+
+    // The partition's `calling_convention = "stdcall"` applies where clang
+    // reports the default; `CdeclCallback` above shows delegates keep theirs.
+    assert_eq!(
+        import.flags(),
+        windows_metadata::PInvokeAttributes::CallConvPlatformapi
+    );
+}
+
+#[test]
+fn fnptr_fields_reference_synthesized_delegates() {
+    let index = open_index();
+
+    let allocator = index.expect("FeaturesTest", "FeatureAllocator");
+    let fields: Vec<_> = allocator.fields().map(|f| (f.name(), f.ty())).collect();
+    assert_eq!(
+        fields[..2],
+        [
+            (
+                "alloc",
+                windows_metadata::Type::named("FeaturesTest", "FeatureAllocator_alloc")
+            ),
+            (
+                "release",
+                windows_metadata::Type::named("FeaturesTest", "FeatureAllocator_release")
+            ),
+        ]
+    );
+
+    for name in ["FeatureAllocator_alloc", "FeatureAllocator_release"] {
+        let delegate = index.expect("FeaturesTest", name);
+        assert_eq!(
+            delegate.extends().map(|e| e.name().to_string()).as_deref(),
+            Some("MulticastDelegate"),
+            "{name} should be a delegate"
+        );
+    }
+}
+
+#[test]
+fn fnptr_params_reference_delegates() {
+    use windows_metadata::{ParamAttributes, Type};
+
+    let index = open_index();
+
+    let apis = index.expect("FeaturesTest", "Apis");
+    let on_exit = apis
+        .methods()
+        .find(|m| m.name() == "feature_on_exit")
+        .expect("feature_on_exit function");
+    let sig = on_exit.signature(&[]);
+    assert_eq!(
+        sig.types,
+        [
+            Type::named("FeaturesTest", "feature_on_exit_destructor"),
+            Type::named("FeaturesTest", "FeatureNotify"),
+        ]
+    );
+    // A callback is an input, not an out-pointer.
+    assert!(
+        on_exit
+            .params()
+            .all(|p| !p.flags().contains(ParamAttributes::Out)),
+        "callback parameters must not be marked [Out]"
+    );
+
+    let delegate = index.expect("FeaturesTest", "feature_on_exit_destructor");
+    assert_eq!(
+        delegate.extends().map(|e| e.name().to_string()).as_deref(),
+        Some("MulticastDelegate")
+    );
+}
+
+#[test]
+fn delegate_param_names_from_prototype() {
+    let index = open_index();
+
+    for (name, expected) in [
+        ("FeatureCompare", ["lhs", "rhs"]),
+        ("FeatureNotify", ["param0", "user"]),
+    ] {
+        let invoke = index
+            .expect("FeaturesTest", name)
+            .methods()
+            .find(|m| m.name() == "Invoke")
+            .unwrap_or_else(|| panic!("{name} has no Invoke"));
+        let params: Vec<String> = invoke.params().map(|p| p.name().to_string()).collect();
+        assert_eq!(params, expected, "{name}");
+    }
+}
+
+#[test]
+fn synthesized_enum_from_define_prefix() {
+    let index = open_index();
+
+    let level = index.expect("FeaturesTest", "FeatureLevel");
+    let variants: Vec<_> = level
+        .fields()
+        .filter_map(|f| {
+            let value = match f.constant()?.value() {
+                windows_metadata::Value::U8(v) => v,
+                other => panic!("{} should be a u8 variant, got {other:?}", f.name()),
+            };
+            Some((f.name(), value))
+        })
+        .collect();
+    assert_eq!(
+        variants,
+        [("FEATURE_LEVEL_LOW", 1), ("FEATURE_LEVEL_HIGH", 2)]
+    );
+
+    // The loose constants are folded into the enum.
+    let apis = index.expect("FeaturesTest", "Apis");
+    assert!(
+        !apis
+            .fields()
+            .any(|f| f.name().starts_with("FEATURE_LEVEL_")),
+        "FEATURE_LEVEL_* should no longer be Apis constants"
+    );
+}
+
+/// `(name, value)` of every variant of `name`, as the winmd stores them.
+fn enum_variants(name: &str) -> Vec<(String, windows_metadata::Value)> {
+    let index = open_index();
+    let ty = index.expect("FeaturesTest", name);
+    ty.fields()
+        .filter_map(|f| Some((f.name().to_string(), f.constant()?.value())))
+        .collect()
+}
+
+/// Type and value of the `Apis` constant `name`.
+fn constant(name: &str) -> (windows_metadata::Type, Option<windows_metadata::Value>) {
+    let index = open_index();
+    let field = index
+        .expect("FeaturesTest", "Apis")
+        .fields()
+        .find(|f| f.name() == name)
+        .unwrap_or_else(|| panic!("{name} not found"));
+    (field.ty(), field.constant().map(|c| c.value()))
+}
+
+#[test]
+fn constant_types_follow_literal() {
+    use windows_metadata::{Type, Value};
+
+    assert_eq!(
+        constant("FEATURE_TIMEOUT"),
+        (Type::U64, Some(Value::U64(100)))
+    );
+    assert_eq!(
+        constant("FEATURE_RETRIES"),
+        (Type::U32, Some(Value::U32(3)))
+    );
+    // Hex literals take `unsigned int` when they don't fit `int`.
+    assert_eq!(
+        constant("FEATURE_ALL_BITS"),
+        (Type::U32, Some(Value::U32(u32::MAX)))
+    );
+    assert_eq!(
+        constant("FEATURE_FLAGS_DEFAULT"),
+        (Type::I32, Some(Value::I32(0x10)))
+    );
+    // Decimal literals widen to `long`, never to unsigned.
+    assert_eq!(
+        constant("FEATURE_BIG"),
+        (Type::I64, Some(Value::I64(5_000_000_000)))
+    );
+    assert_eq!(
+        constant("FEATURE_OFFSET"),
+        (Type::I64, Some(Value::I64(-1)))
+    );
+    assert_eq!(
+        constant("FEATURE_PORT"),
+        (Type::U16, Some(Value::U16(8080)))
+    );
+}
+
+#[test]
+fn const_variables_become_constants() {
+    use windows_metadata::{Type, Value};
+
+    assert_eq!(
+        constant("FEATURE_MAX_ITEMS"),
+        (Type::I32, Some(Value::I32(16)))
+    );
+    // The type goes through the typedef.
+    assert_eq!(
+        constant("FEATURE_DEFAULT_PORT"),
+        (Type::U16, Some(Value::U16(443)))
+    );
+    assert_eq!(
+        constant("FEATURE_RATIO"),
+        (Type::F64, Some(Value::F64(0.5)))
+    );
+
+    let index = open_index();
+    assert!(
+        !index
+            .expect("FeaturesTest", "Apis")
+            .fields()
+            .any(|f| f.name() == "feature_runtime_limit"),
+        "an extern const without initializer is not a constant"
+    );
+}
+
+#[test]
+fn char_literal_and_cast_constants() {
+    use windows_metadata::{Type, Value};
+
+    assert_eq!(
+        constant("FEATURE_PATH_SEP"),
+        (Type::I32, Some(Value::I32(':' as i32)))
+    );
+    assert_eq!(
+        constant("FEATURE_NEWLINE"),
+        (Type::I32, Some(Value::I32(10)))
+    );
+    // A cast to a non-integer type keeps the value.
+    assert_eq!(constant("FEATURE_ERR"), (Type::I32, Some(Value::I32(-1))));
+    // A cast to an integer type wraps and fixes the type.
+    assert_eq!(
+        constant("FEATURE_BYTE_MAX"),
+        (Type::U8, Some(Value::U8(255)))
+    );
+    // An unsigned literal keeps its type under `~` and `-`, and wraps.
+    assert_eq!(
+        constant("FEATURE_UINT_MAX"),
+        (Type::U32, Some(Value::U32(u32::MAX)))
+    );
+    assert_eq!(
+        constant("FEATURE_ULONG_MAX"),
+        (Type::U64, Some(Value::U64(u64::MAX)))
+    );
+    assert_eq!(
+        constant("FEATURE_MINUS_ONE_U"),
+        (Type::U32, Some(Value::U32(u32::MAX)))
+    );
+}
+
+#[test]
+fn enum_underlying_type_fits_variants() {
+    use windows_metadata::Value;
+
+    assert_eq!(
+        enum_variants("FeatureStatus"),
+        [
+            ("FEATURE_STATUS_EIO".to_string(), Value::I32(-5)),
+            ("FEATURE_STATUS_OK".to_string(), Value::I32(0)),
+        ]
+    );
+    assert_eq!(
+        enum_variants("FeatureWideMask"),
+        [
+            ("FEATURE_WIDE_LOW".to_string(), Value::U64(1)),
+            ("FEATURE_WIDE_HIGH".to_string(), Value::U64(0x1_0000_0000)),
+        ]
+    );
+    // `enum : feature_u16_t` resolves through the typedef.
+    assert_eq!(
+        enum_variants("FeatureFixed"),
+        [("FEATURE_FIXED_MAX".to_string(), Value::U16(0xffff))]
+    );
+}
+
+#[test]
+fn enum_type_override() {
+    use windows_metadata::Value;
+
+    assert_eq!(
+        enum_variants("FeatureSmall"),
+        [
+            ("FEATURE_SMALL_A".to_string(), Value::U8(1)),
+            ("FEATURE_SMALL_B".to_string(), Value::U8(200)),
+        ]
+    );
+}
+
+#[test]
+fn type_map_pins_typedefs_to_primitives() {
+    use windows_metadata::Type;
+
+    let index = open_index();
+    let apis = index.expect("FeaturesTest", "Apis");
+    let buffer_len = apis
+        .methods()
+        .find(|m| m.name() == "feature_buffer_len")
+        .expect("feature_buffer_len function");
+    let sig = buffer_len.signature(&[]);
+    assert_eq!(sig.return_type, Type::USize);
+    assert_eq!(sig.types[1], Type::USize);
+
+    let unmatched: Vec<_> = FEATURES_OUTPUT
+        .diagnostics
+        .with_code("type_map_unmatched")
+        .map(|d| d.name.as_deref())
+        .collect();
+    assert_eq!(unmatched, [Some("feature_missing_t")]);
+}
+
+#[test]
+fn string_functions_use_pstr() {
+    use windows_metadata::Type;
+
+    let index = open_index();
+    let apis = index.expect("FeaturesTest", "Apis");
+    let describe = apis
+        .methods()
+        .find(|m| m.name() == "feature_describe")
+        .expect("feature_describe function");
+    let sig = describe.signature(&[]);
+    let pcstr = Type::named("Windows.Win32.Foundation", "PCSTR");
+    let pstr = Type::named("Windows.Win32.Foundation", "PSTR");
+    assert_eq!(sig.return_type, pcstr);
+    assert_eq!(sig.types, [pcstr, pstr, Type::I32]);
+
+    for param in ["", "name", "buf"] {
+        assert_eq!(
+            param_attribute_arg("feature_describe", param, "NativeEncodingAttribute"),
+            "ansi"
+        );
+    }
+    // Functions not listed keep raw pointers.
+    let open = apis.methods().find(|m| m.name() == "feature_open").unwrap();
+    let sig = open.signature(&[]);
+    assert_eq!(sig.types, [Type::PtrMut(Box::new(Type::I8), 1)]);
+
+    assert!(
+        FEATURES_OUTPUT
+            .diagnostics
+            .with_code("strings_unmatched")
+            .any(|d| d.name.as_deref() == Some("feature_missing"))
+    );
+}
+
+#[test]
+fn return_conventions_are_recorded() {
+    let index = open_index();
+    let apis = index.expect("FeaturesTest", "Apis");
+    let convention = |function: &str| {
+        let method = apis.methods().find(|m| m.name() == function).unwrap();
+        let attr = method.find_attribute("ReturnConventionAttribute")?;
+        match attr.value().as_slice() {
+            [(_, windows_metadata::Value::Utf8(s))] => Some(s.clone()),
+            other => panic!("unexpected ReturnConventionAttribute arguments: {other:?}"),
+        }
+    };
+    assert_eq!(convention("feature_set_mode").as_deref(), Some("errno"));
+    assert_eq!(convention("feature_open").as_deref(), Some("null"));
+    assert_eq!(convention("feature_set_flags"), None);
+}
+
+#[test]
+fn param_annotations_from_sal_and_config() {
+    use windows_metadata::ParamAttributes;
+
+    let index = open_index();
+    let apis = index.expect("FeaturesTest", "Apis");
+    let flags = |function: &str, param: &str| {
+        let method = apis.methods().find(|m| m.name() == function).unwrap();
+        method.params().find(|p| p.name() == param).unwrap().flags()
+    };
+    let memory_size = |function: &str, param: &str| {
+        let method = apis.methods().find(|m| m.name() == function).unwrap();
+        let p = method.params().find(|p| p.name() == param).unwrap();
+        let attr = p.find_attribute("MemorySizeAttribute")?;
+        match attr.value().as_slice() {
+            [(name, windows_metadata::Value::I16(i))] if name == "BytesParamIndex" => Some(*i),
+            other => panic!("unexpected MemorySizeAttribute arguments: {other:?}"),
+        }
+    };
+
+    // SAL: `_In_` wins over the mutable-pointer default of Out.
+    assert_eq!(flags("feature_read", "source"), ParamAttributes::In);
+    assert_eq!(flags("feature_read", "buf"), ParamAttributes::Out);
+    assert_eq!(memory_size("feature_read", "buf"), Some(2));
+    assert_eq!(
+        flags("feature_read", "tag"),
+        ParamAttributes::In | ParamAttributes::Optional
+    );
+
+    // `__in` / `__out` after the type are parameter names.
+    assert_eq!(flags("feature_route", "__in"), ParamAttributes::default());
+    assert_eq!(flags("feature_route", "__out"), ParamAttributes::default());
+
+    // nonnull(1, 2): the unlisted pointer may be null.
+    assert_eq!(flags("feature_copy", "dst"), ParamAttributes::Out);
+    assert_eq!(flags("feature_copy", "src"), ParamAttributes::default());
+    assert_eq!(
+        flags("feature_copy", "written"),
+        ParamAttributes::Out | ParamAttributes::Optional
+    );
+    // Separate nonnull(1) and nonnull(2) add up.
+    assert_eq!(flags("feature_move", "dst"), ParamAttributes::Out);
+    assert_eq!(flags("feature_move", "src"), ParamAttributes::default());
+    assert_eq!(
+        flags("feature_move", "moved"),
+        ParamAttributes::Out | ParamAttributes::Optional
+    );
+
+    // [[param_attributes]]
+    assert_eq!(memory_size("feature_copy", "dst"), Some(2));
+    assert_eq!(memory_size("feature_copy", "src"), None);
+    let copy = apis.methods().find(|m| m.name() == "feature_copy").unwrap();
+    let dst = copy.params().find(|p| p.name() == "dst").unwrap();
+    assert!(dst.find_attribute("NotNullTerminatedAttribute").is_some());
+}
+
+#[test]
+fn array_counts_from_sal_and_config() {
+    let index = open_index();
+    let apis = index.expect("FeaturesTest", "Apis");
+    let count = |function: &str, param: &str| {
+        let method = apis.methods().find(|m| m.name() == function).unwrap();
+        let p = method.params().find(|p| p.name() == param).unwrap();
+        let attr = p.find_attribute("NativeArrayInfoAttribute")?;
+        match attr.value().as_slice() {
+            [(name, windows_metadata::Value::I16(i))] if name == "CountParamIndex" => Some(*i),
+            other => panic!("unexpected NativeArrayInfoAttribute arguments: {other:?}"),
+        }
+    };
+    assert_eq!(count("feature_sum", "values"), Some(1));
+    assert_eq!(count("feature_fill", "values"), Some(1));
+    assert_eq!(count("feature_fill", "n"), None);
+    // Byte sizes are a different attribute.
+    assert_eq!(count("feature_read", "buf"), None);
+}
+
+#[test]
+fn enum_type_override_must_fit_variants() {
+    let path = features_toml();
+    let mut cfg = bnd_winmd::config::load_config(&path).expect("load features config");
+    cfg.enum_type
+        .insert("FeatureStatus".to_string(), "u8".to_string());
+    let err = bnd_winmd::generate_from_config(&cfg, path.parent().unwrap())
+        .expect_err("-5 does not fit u8");
+    assert!(
+        err.to_string()
+            .contains("`FEATURE_STATUS_EIO` = -5 does not fit `u8`"),
+        "{err}"
+    );
+}
+
+#[test]
+fn declarations_are_emitted_in_sorted_order() {
+    let index = open_index();
+
+    // Skip `<Module>` and the stamp, which live in the empty namespace.
+    let namespaces: Vec<_> = index
+        .types()
+        .map(|td| td.namespace().to_string())
+        .filter(|ns| !ns.is_empty())
+        .collect();
+    let mut seen = namespaces.clone();
+    seen.dedup();
+    assert!(seen.is_sorted(), "namespaces out of order: {seen:?}");
+
+    let enums: Vec<_> = index
+        .types()
+        .filter(|td| td.namespace() == "FeaturesTest")
+        .filter(|td| td.extends().is_some_and(|e| e.name() == "Enum"))
+        .map(|td| td.name().to_string())
+        .collect();
+    assert!(enums.len() > 1 && enums.is_sorted(), "{enums:?}");
+
+    let apis = index.expect("FeaturesTest", "Apis");
+    let methods: Vec<_> = apis.methods().map(|m| m.name().to_string()).collect();
+    assert!(methods.is_sorted(), "{methods:?}");
+    let constants: Vec<_> = apis.fields().map(|f| f.name().to_string()).collect();
+    assert!(constants.is_sorted(), "{constants:?}");
+}
+
+#[test]
+fn stamp_records_version_config_and_headers() {
+    let stamp = bnd_winmd::stamp::Stamp::from_bytes(FEATURES_OUTPUT.bytes.clone())
+        .expect("generated winmd should be stamped");
+    assert_eq!(stamp.version, env!("CARGO_PKG_VERSION"));
+    assert_eq!(stamp.config_hash.len(), 16, "{stamp:?}");
+
+    let paths: Vec<&str> = stamp.headers.iter().map(|(p, _)| p.as_str()).collect();
+    assert!(paths.contains(&"features.h"), "{paths:?}");
+    assert!(paths.contains(&"callconv.h"), "{paths:?}");
+    assert!(paths.is_sorted(), "{paths:?}");
+    assert_eq!(
+        stamp.defines,
+        [
+            (String::new(), "-DFEATURES_FIXTURE=1".to_string()),
+            (
+                "FeaturesTest.Callbacks".to_string(),
+                "-DFEATURES_CALLBACKS".to_string()
+            ),
+        ]
+    );
+
+    // Round-trips through a file, and is invisible to the dump.
+    let path = Path::new(env!("CARGO_TARGET_TMPDIR")).join("features_stamp.winmd");
+    std::fs::write(&path, &FEATURES_OUTPUT.bytes).unwrap();
+    assert_eq!(bnd_winmd::stamp(&path).expect("read stamp"), stamp);
+    let text = bnd_winmd::dump::dump_bytes(FEATURES_OUTPUT.bytes.clone()).expect("dump winmd");
+    assert!(!text.contains("BndStamp"), "dump:\n{text}");
+}
+
+#[test]
+fn stamp_header_paths_do_not_depend_on_the_machine() {
+    let dir = Path::new(env!("CARGO_TARGET_TMPDIR")).join("stamp_paths");
+    let _ = std::fs::remove_dir_all(&dir);
+    let files = [
+        ("cfg/local.h", "int local_fn(void);\n"),
+        ("sysroot/usr/local/include/sub/dep.h", "int dep_fn(void);\n"),
+        ("sysroot/usr/include/sys.h", "int sys_fn(void);\n"),
+    ];
+    for (path, text) in files {
+        let path = dir.join(path);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, text).unwrap();
+    }
+    let config = dir.join("cfg/stamp.toml");
+    std::fs::write(
+        &config,
+        format!(
+            r#"
+include_paths = ["/usr/local/include"]
+sysroot = "{}"
+
+[output]
+name = "StampPaths"
+
+[[partition]]
+namespace = "StampPaths"
+library = "stamp_paths"
+headers = ["local.h", "sub/dep.h", "/usr/include/sys.h"]
+"#,
+            dir.join("sysroot").display()
+        ),
+    )
+    .unwrap();
+
+    let bytes = bnd_winmd::generate(&config).expect("generate stamp_paths winmd");
+    let stamp = bnd_winmd::stamp::Stamp::from_bytes(bytes).expect("stamped");
+    let paths: Vec<&str> = stamp.headers.iter().map(|(p, _)| p.as_str()).collect();
+    assert_eq!(paths, ["/usr/include/sys.h", "local.h", "sub/dep.h"]);
+}
+
+#[test]
+fn assembly_version_company_and_copyright() {
+    use bnd_winmd::assembly::{AssemblyMetadata, AssemblyVersion};
+
+    let metadata = AssemblyMetadata::from_bytes(&FEATURES_OUTPUT.bytes).expect("read metadata");
+    assert_eq!(
+        metadata,
+        AssemblyMetadata {
+            version: Some(AssemblyVersion([0, 3, 0, 0])),
+            company: Some("bnd".to_string()),
+            copyright: Some("Copyright (c) bnd contributors".to_string()),
+        }
+    );
+
+    // The patched winmd still parses.
+    let index = open_index();
+    assert!(index.get("FeaturesTest", "Apis").next().is_some());
+}
+
+#[test]
+fn rename_struct_and_references() {
+    let index = open_index();
+
+    assert!(index.contains("FeaturesTest", "feature_stat_t"));
+    assert!(!index.contains("FeaturesTest", "feature_stat"));
+
+    // The function of the same name is untouched and points at the renamed struct.
+    let apis = index.expect("FeaturesTest", "Apis");
+    let stat = apis
+        .methods()
+        .find(|m| m.name() == "feature_stat")
+        .expect("feature_stat function");
+    let sig = stat.signature(&[]);
+    assert_eq!(
+        sig.types[1],
+        windows_metadata::Type::PtrMut(
+            Box::new(windows_metadata::Type::named(
+                "FeaturesTest",
+                "feature_stat_t"
+            )),
+            1
+        )
+    );
+}
+
+#[test]
+fn rename_function_keeps_entry_point() {
+    let index = open_index();
+    let apis = index.expect("FeaturesTest", "Apis");
+
+    assert!(!apis.methods().any(|m| m.name() == "feature_legacy_reset"));
+    let reset = apis
+        .methods()
+        .find(|m| m.name() == "feature_reset")
+        .expect("renamed feature_reset");
+    let import = reset.impl_map().expect("feature_reset ImplMap");
+    assert_eq!(import.import_name(), "feature_legacy_reset");
+}
+
+#[test]
+fn asm_label_redirect_sets_entry_point() {
+    let index = open_index();
+    let lock = index
+        .expect("FeaturesTest", "Apis")
+        .methods()
+        .find(|m| m.name() == "feature_lock")
+        .expect("feature_lock");
+    let import = lock.impl_map().expect("feature_lock ImplMap");
+    assert_eq!(import.import_name(), "feature_lock64");
+}
+
+#[test]
+fn vtable_struct_becomes_interface() {
+    let index = open_index();
+
+    let vtbl = index.expect("FeaturesTest", "FeatureStreamVtbl");
+    assert!(
+        vtbl.flags()
+            .contains(windows_metadata::TypeAttributes::Interface)
+    );
+    assert_eq!(vtbl.fields().count(), 0);
+
+    // Slots become methods without the leading object pointer.
+    let methods: Vec<_> = vtbl
+        .methods()
+        .map(|m| {
+            let params: Vec<String> = m.params().map(|p| p.name().to_string()).collect();
+            (m.name(), params)
+        })
+        .collect();
+    assert_eq!(
+        methods,
+        [
+            ("read", vec!["param0".to_string(), "param1".to_string()]),
+            ("close", vec![]),
+        ]
+    );
+
+    // The per-slot delegates are gone, and the vtable pointer field is
+    // the interface itself.
+    assert!(!index.contains("FeaturesTest", "FeatureStreamVtbl_read"));
+    let stream = index.expect("FeaturesTest", "FeatureStream");
+    assert_eq!(
+        stream.fields().next().map(|f| f.ty()),
+        Some(windows_metadata::Type::named(
+            "FeaturesTest",
+            "FeatureStreamVtbl"
+        ))
+    );
+}
+
+/// `GuidAttribute` arguments rendered as a registry-format GUID.
+fn guid_text(attr: windows_metadata::reader::Attribute) -> String {
+    let values: Vec<u64> = attr
+        .value()
+        .into_iter()
+        .map(|(_, v)| match v {
+            windows_metadata::Value::U32(v) => v as u64,
+            windows_metadata::Value::U16(v) => v as u64,
+            windows_metadata::Value::U8(v) => v as u64,
+            other => panic!("unexpected GuidAttribute argument: {other:?}"),
+        })
+        .collect();
+    assert_eq!(values.len(), 11, "{values:?}");
+    format!(
+        "{:08x}-{:04x}-{:04x}-{}-{}",
+        values[0],
+        values[1],
+        values[2],
+        values[3..5]
+            .iter()
+            .map(|b| format!("{b:02x}"))
+            .collect::<String>(),
+        values[5..]
+            .iter()
+            .map(|b| format!("{b:02x}"))
+            .collect::<String>(),
+    )
+}
+
+#[test]
+fn guid_constants() {
+    let index = open_index();
+    let apis = index.expect("FeaturesTest", "Apis");
+
+    for (name, expected) in [
+        ("IID_FeatureStream", "6b29fc40-ca47-1067-b31d-00dd010662da"),
+        ("CLSID_FeatureThing", "12345678-9abc-def0-0123-456789abcdef"),
+    ] {
+        let field = apis
+            .fields()
+            .find(|f| f.name() == name)
+            .unwrap_or_else(|| panic!("{name} not found"));
+        assert_eq!(field.ty(), windows_metadata::Type::named("System", "Guid"));
+        let attr = field
+            .find_attribute("GuidAttribute")
+            .unwrap_or_else(|| panic!("{name} should carry GuidAttribute"));
+        assert_eq!(guid_text(attr), expected, "{name}");
+    }
+
+    // IID_FeatureStream names the FeatureStreamVtbl interface.
+    let attr = index
+        .expect("FeaturesTest", "FeatureStreamVtbl")
+        .find_attribute("GuidAttribute")
+        .expect("interface should carry GuidAttribute");
+    assert_eq!(guid_text(attr), "6b29fc40-ca47-1067-b31d-00dd010662da");
+}
+
+#[test]
+fn api_report_lists_surface_and_skips() {
+    let report = &*FEATURES_REPORT;
+    assert_eq!(report.assembly, "FeaturesTest");
+
+    let part = report
+        .partitions
+        .iter()
+        .find(|p| p.namespace == "FeaturesTest")
+        .expect("FeaturesTest partition");
+
+    let open = part
+        .functions
+        .iter()
+        .find(|f| f.name == "feature_open")
+        .expect("feature_open in report");
+    assert_eq!(
+        open.signature,
+        "FeatureHandle * feature_open(const int8_t * name)"
+    );
+
+    let reset = part
+        .functions
+        .iter()
+        .find(|f| f.name == "feature_reset")
+        .expect("renamed feature_reset in report");
+    assert_eq!(reset.entry_point, "feature_legacy_reset");
+
+    let flags = part
+        .constants
+        .iter()
+        .find(|c| c.name == "FEATURE_FLAGS_DEFAULT")
+        .expect("FEATURE_FLAGS_DEFAULT in report");
+    assert_eq!(flags.value, "16");
+
+    // Read as the enum's u16 type, not sign-extended to -1.
+    let fixed = part
+        .enums
+        .iter()
+        .find(|e| e.name == "FeatureFixed")
+        .expect("FeatureFixed in report");
+    assert_eq!(fixed.variants[0].name, "FEATURE_FIXED_MAX");
+    assert_eq!(fixed.variants[0].value, 0xffff);
+
+    let allocator = part
+        .structs
+        .iter()
+        .find(|s| s.name == "FeatureAllocator")
+        .expect("FeatureAllocator in report");
+    let layout: Vec<_> = allocator
+        .fields
+        .iter()
+        .map(|f| (f.name.as_str(), f.offset, f.size))
+        .collect();
+    assert_eq!(
+        layout,
+        [("alloc", Some(0), Some(8)), ("release", Some(8), Some(8))]
+    );
+
+    let log = part
+        .skipped
+        .iter()
+        .find(|s| s.name == "feature_log")
+        .expect("variadic feature_log should be reported as skipped");
+    assert_eq!(log.reason, "variadic");
+
+    let json = report.to_json().expect("serialize report");
+    assert!(json.contains("\"feature_set_mode\""));
+}
+
+#[test]
+fn diagnostics_record_skips_with_location() {
+    let diagnostics = &FEATURES_OUTPUT.diagnostics;
+
+    let log = diagnostics
+        .with_code("variadic_function")
+        .find(|d| d.name.as_deref() == Some("feature_log"))
+        .expect("variadic feature_log should be a diagnostic");
+    assert_eq!(log.namespace.as_deref(), Some("FeaturesTest"));
+    assert!(
+        log.file
+            .as_deref()
+            .is_some_and(|f| f.ends_with("features.h")),
+        "{log:?}"
+    );
+    assert_eq!(log.line, Some(29));
+
+    let json = diagnostics.to_json().expect("serialize diagnostics");
+    assert!(json.contains("\"code\": \"variadic_function\""), "{json}");
+}
+
+#[test]
+fn dump_lists_types_imports_and_attributes() {
+    let text = bnd_winmd::dump::dump_bytes(FEATURES_OUTPUT.bytes.clone()).expect("dump winmd");
+
+    assert!(
+        text.contains("[RAIIFreeAttribute(\"feature_close\")]\nstruct FeaturesTest.FeatureHandle"),
+        "dump:\n{text}"
+    );
+    assert!(
+        text.contains("// import features!feature_open"),
+        "dump:\n{text}"
+    );
+    assert!(
+        text.contains("const FEATURE_FLAGS_DEFAULT: i32 = 16"),
+        "dump:\n{text}"
+    );
+    assert!(
+        text.contains("enum FeaturesTest.FeatureMode"),
+        "dump:\n{text}"
+    );
+}
+
+#[test]
+fn plan_lists_what_would_be_emitted() {
+    let plan = &*FEATURES_PLAN;
+    assert_eq!(plan.assembly, "FeaturesTest");
+
+    let ns = plan
+        .namespaces
+        .iter()
+        .find(|ns| ns.namespace == "FeaturesTest")
+        .expect("FeaturesTest in plan");
+    assert!(
+        ns.types
+            .iter()
+            .any(|t| t.kind == "enum" && t.name == "FeatureMode")
+    );
+    assert!(
+        ns.types
+            .iter()
+            .any(|t| t.kind == "interface" && t.name == "FeatureStreamVtbl")
+    );
+    assert!(ns.functions.iter().any(|f| f == "feature_open"));
+    assert!(ns.constants.iter().any(|c| c == "FEATURE_FLAGS_DEFAULT"));
+    assert!(ns.skipped >= 1, "variadic feature_log should be counted");
+
+    // The plan matches the emitted winmd.
+    let index = open_index();
+    let apis = index.expect("FeaturesTest", "Apis");
+    let methods: Vec<_> = apis.methods().map(|m| m.name().to_string()).collect();
+    assert_eq!(ns.functions, methods);
+
+    let text = plan.to_string();
+    assert!(text.contains("\n  fn feature_open\n"), "{text}");
+    assert!(text.contains("FeaturesTest.Globbed ("), "{text}");
+}
@@ -0,0 +1,69 @@
+//! `group_by_handle`: functions sharing a first-parameter handle type move
+//! into a `<namespace>.<handle>` namespace.
+
+use std::path::{Path, PathBuf};
+use std::sync::LazyLock;
+
+fn fixture(name: &str) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("../tests/fixtures/group")
+        .join(name)
+}
+
+static GROUP_WINMD: LazyLock<Vec<u8>> =
+    LazyLock::new(|| bnd_winmd::generate(&fixture("group.toml")).expect("generate group winmd"));
+
+fn open_index() -> windows_metadata::reader::TypeIndex {
+    let file = windows_metadata::reader::File::new(GROUP_WINMD.clone()).expect("parse winmd");
+    windows_metadata::reader::TypeIndex::new(vec![file])
+}
+
+fn functions(index: &windows_metadata::reader::TypeIndex, namespace: &str) -> Vec<String> {
+    let mut names: Vec<_> = index
+        .expect(namespace, "Apis")
+        .methods()
+        .map(|m| m.name().to_string())
+        .collect();
+    names.sort();
+    names
+}
+
+#[test]
+fn functions_grouped_by_handle() {
+    let index = open_index();
+
+    assert_eq!(
+        functions(&index, "GroupTest.counter"),
+        ["counter_free", "counter_increment", "counter_value"]
+    );
+    assert_eq!(
+        functions(&index, "GroupTest.session_t_api"),
+        ["session_close", "session_send"]
+    );
+    // Constructors, single-function handles and handle-less functions stay.
+    assert_eq!(
+        functions(&index, "GroupTest"),
+        [
+            "counter_new",
+            "group_version",
+            "point_length",
+            "session_open"
+        ]
+    );
+}
+
+#[test]
+fn grouped_functions_reference_parent_types() {
+    let index = open_index();
+
+    let send = index
+        .expect("GroupTest.session_t_api", "Apis")
+        .methods()
+        .find(|m| m.name() == "session_send")
+        .expect("session_send function");
+    let sig = send.signature(&[]);
+    assert_eq!(
+        sig.types[0],
+        windows_metadata::Type::named("GroupTest", "session_t")
+    );
+}
@@ -0,0 +1,242 @@
+//! Struct packing and alignment: `#pragma pack`, `__attribute__((packed))`
+//! and `__attribute__((aligned(N)))` → ClassLayout.
+
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::LazyLock;
+
+use bnd_winmd::pipeline::{Builder, Layout};
+use windows_metadata::reader::HasAttributes;
+use windows_metadata::{Type, TypeAttributes, Value};
+
+fn fixture(name: &str) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("../tests/fixtures/layout")
+        .join(name)
+}
+
+static LAYOUT_OUTPUT: LazyLock<bnd_winmd::GenerateOutput> = LazyLock::new(|| {
+    let path = fixture("layout.toml");
+    let cfg = bnd_winmd::config::load_config(&path).expect("load layout config");
+    bnd_winmd::generate_from_config(&cfg, path.parent().unwrap()).expect("generate layout winmd")
+});
+
+static OVERALIGNED_RESULT: LazyLock<Result<Vec<u8>, String>> = LazyLock::new(|| {
+    bnd_winmd::generate(&fixture("overaligned.toml")).map_err(|e| format!("{e:#}"))
+});
+
+/// `(packing_size, class_size)` of `LayoutTest.<name>`.
+fn class_layout(name: &str) -> (u16, u32) {
+    let file =
+        windows_metadata::reader::File::new(LAYOUT_OUTPUT.bytes.clone()).expect("parse winmd");
+    let index = windows_metadata::reader::TypeIndex::new(vec![file]);
+    let layout = index
+        .expect("LayoutTest", name)
+        .class_layout()
+        .unwrap_or_else(|| panic!("{name} has no ClassLayout"));
+    (layout.packing_size(), layout.class_size())
+}
+
+#[test]
+fn pragma_pack_1() {
+    assert_eq!(class_layout("PackedHeader"), (1, 7));
+}
+
+#[test]
+fn attribute_packed() {
+    assert_eq!(class_layout("AttrPacked"), (1, 9));
+}
+
+#[test]
+fn pragma_pack_2() {
+    assert_eq!(class_layout("Pack2"), (2, 6));
+}
+
+#[test]
+fn aligned_64() {
+    assert_eq!(class_layout("CacheLine"), (64, 64));
+}
+
+#[test]
+fn unrepresentable_alignment_is_an_error() {
+    let err = OVERALIGNED_RESULT
+        .as_ref()
+        .expect_err("aligned(4096) should be rejected");
+    assert!(err.contains("PageAligned"), "{err}");
+    assert!(err.contains("4096"), "{err}");
+}
+
+#[test]
+fn layout_check_reports_bitfield_overlap() {
+    let mismatches: Vec<_> = LAYOUT_OUTPUT
+        .diagnostics
+        .with_code("layout_mismatch")
+        .collect();
+    assert!(
+        mismatches
+            .iter()
+            .all(|d| d.name.as_deref() == Some("BitfieldOverlap")),
+        "{mismatches:?}"
+    );
+    let messages: Vec<_> = mismatches.iter().map(|d| d.message.as_str()).collect();
+    assert_eq!(
+        messages,
+        [
+            "field `flag` at offset 4, clang has 3",
+            "size 8, clang has 4"
+        ]
+    );
+}
+
+#[test]
+fn misaligned_bitfield_storage_stays_sequential() {
+    let file =
+        windows_metadata::reader::File::new(LAYOUT_OUTPUT.bytes.clone()).expect("parse winmd");
+    let index = windows_metadata::reader::TypeIndex::new(vec![file]);
+    for name in ["BitfieldMidUnit", "BitfieldStraddle"] {
+        let flags = index.expect("LayoutTest", name).flags();
+        assert!(flags.contains(TypeAttributes::SequentialLayout), "{name}");
+        assert!(!flags.contains(TypeAttributes::ExplicitLayout), "{name}");
+    }
+
+    // The u16 `value` cannot sit at byte 1, so its bytes are kept as an
+    // array there, padded out to clang's size.
+    assert_eq!(class_layout("BitfieldMidUnit"), (4, 4));
+    let fields: Vec<_> = index
+        .expect("LayoutTest", "BitfieldMidUnit")
+        .fields()
+        .map(|f| (f.name().to_string(), f.ty()))
+        .collect();
+    assert_eq!(
+        fields,
+        [
+            ("tag".to_string(), Type::I8),
+            ("value".to_string(), Type::ArrayFixed(Box::new(Type::U8), 2)),
+            (
+                "_padding".to_string(),
+                Type::ArrayFixed(Box::new(Type::U8), 1)
+            ),
+        ]
+    );
+}
+
+#[test]
+fn misaligned_bitfield_storage_bindings_match_clang() {
+    let dir = tempfile::tempdir().expect("tempdir");
+    let out = dir.path().join("bindings.rs");
+    Builder::new(fixture("layout.toml"), &out)
+        .winmd(dir.path().join("layout_test.winmd"))
+        .filter("LayoutTest")
+        .filter("LayoutTest.Bitfields")
+        .layout(Layout::Flat)
+        .sys(true)
+        .run()
+        .expect("generate layout bindings");
+    let bindings = std::fs::read_to_string(&out).expect("read bindings");
+    for name in ["BitfieldMidUnit", "BitfieldStraddle", "StorageStraddle"] {
+        assert!(
+            bindings.contains(&format!("pub struct {name} {{")),
+            "{name} is not a struct:\n{bindings}"
+        );
+    }
+
+    // Offsets and sizes clang reports for the same structs.
+    let checks = "
+const _: () = assert!(core::mem::offset_of!(BitfieldMidUnit, value) == 1);
+const _: () = assert!(core::mem::size_of::<BitfieldMidUnit>() == 4);
+const _: () = assert!(core::mem::offset_of!(BitfieldStraddle, value) == 1);
+const _: () = assert!(core::mem::size_of::<BitfieldStraddle>() == 4);
+const _: () = assert!(core::mem::offset_of!(StorageStraddle, _bitfield0) == 1);
+const _: () = assert!(core::mem::size_of::<StorageStraddle>() == 4);
+";
+    assert_compiles(dir.path(), &format!("{bindings}{checks}"));
+}
+
+/// Type-check `source` as a library crate with the rustc running the tests.
+fn assert_compiles(dir: &Path, source: &str) {
+    let path = dir.join("layout_checks.rs");
+    std::fs::write(&path, source).unwrap();
+    let output = Command::new(std::env::var("RUSTC").unwrap_or_else(|_| "rustc".into()))
+        .args([
+            "--edition",
+            "2021",
+            "--crate-type",
+            "lib",
+            "--emit",
+            "metadata",
+        ])
+        .arg("--out-dir")
+        .arg(dir)
+        .arg(&path)
+        .output()
+        .expect("run rustc");
+    assert!(
+        output.status.success(),
+        "{}\n{source}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+}
+
+/// `(field name, field type, [(bitfield, offset, width)])`.
+type StorageField = (String, Type, Vec<(String, i64, i64)>);
+
+/// Fields of `LayoutTest.Bitfields.<name>`.
+fn storage_fields(name: &str) -> Vec<StorageField> {
+    let file =
+        windows_metadata::reader::File::new(LAYOUT_OUTPUT.bytes.clone()).expect("parse winmd");
+    let index = windows_metadata::reader::TypeIndex::new(vec![file]);
+    index
+        .expect("LayoutTest.Bitfields", name)
+        .fields()
+        .map(|f| {
+            let bitfields = f
+                .attributes()
+                .filter(|a| a.ctor().parent().name() == "NativeBitfieldAttribute")
+                .map(|a| match a.value().as_slice() {
+                    [(_, Value::Utf8(n)), (_, Value::I64(o)), (_, Value::I64(w))] => {
+                        (n.clone(), *o, *w)
+                    }
+                    other => panic!("unexpected NativeBitfieldAttribute arguments: {other:?}"),
+                })
+                .collect();
+            (f.name().to_string(), f.ty(), bitfields)
+        })
+        .collect()
+}
+
+#[test]
+fn storage_mode_groups_bitfields_by_unit() {
+    let bf = |n: &str, o, w| (n.to_string(), o, w);
+    assert_eq!(
+        storage_fields("StorageFlags"),
+        [
+            (
+                "_bitfield0".to_string(),
+                Type::U32,
+                vec![bf("a", 0, 3), bf("b", 3, 5), bf("c", 8, 24)]
+            ),
+            ("_bitfield1".to_string(), Type::U16, vec![bf("d", 0, 4)]),
+        ]
+    );
+}
+
+#[test]
+fn storage_mode_straddling_unit_covers_bytes() {
+    let fields = storage_fields("StorageStraddle");
+    assert_eq!(fields[0].0, "tag");
+    assert_eq!(
+        fields[1],
+        (
+            "_bitfield0".to_string(),
+            Type::ArrayFixed(Box::new(Type::U8), 3),
+            vec![("value".to_string(), 0, 20)]
+        )
+    );
+}
+
+#[test]
+fn layout_check_uses_target_pointer_width() {
+    // `Ptr32Node` is 12 bytes on i686; checking it with the host's 8-byte
+    // pointers fails generation under `layout_mismatch = "error"`.
+    bnd_winmd::generate(&fixture("ptr32.toml")).expect("generate ptr32 winmd");
+}
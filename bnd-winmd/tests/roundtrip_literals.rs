@@ -0,0 +1,83 @@
+//! Integer `#define`s in octal, hex, binary and with digit separators:
+//! correct values, and the original spelling kept for reports and docs.
+
+use std::path::Path;
+use std::sync::LazyLock;
+
+use bnd_winmd::model::{CType, ConstantDef, Partition};
+
+static PARTITIONS: LazyLock<Vec<Partition>> = LazyLock::new(|| {
+    let path =
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("../tests/fixtures/literals/literals.toml");
+    bnd_winmd::extract(&path).expect("extract literals")
+});
+
+fn constant(namespace: &str, name: &str) -> &'static ConstantDef {
+    PARTITIONS
+        .iter()
+        .filter(|p| p.namespace == namespace)
+        .flat_map(|p| &p.constants)
+        .find(|c| c.name == name)
+        .unwrap_or_else(|| panic!("{namespace}.{name} not found"))
+}
+
+/// `(value, type, spelling)` of a constant.
+fn literal(namespace: &str, name: &str) -> (i128, CType, Option<&'static str>) {
+    let c = constant(namespace, name);
+    (
+        c.int_value().expect("integer constant"),
+        c.int_type().expect("integer constant"),
+        c.spelling.as_deref(),
+    )
+}
+
+#[test]
+fn octal_literals() {
+    assert_eq!(
+        literal("LiteralTest", "LIT_MODE_OWNER_READ"),
+        (0o400, CType::I32, Some("0400"))
+    );
+    assert_eq!(
+        literal("LiteralTest", "LIT_MODE_DEFAULT"),
+        (0o644, CType::I32, Some("0644"))
+    );
+    // `O_CREAT`-style: not decimal 100.
+    assert_eq!(
+        literal("LiteralTest", "LIT_OPEN_CREATE"),
+        (0o100, CType::I32, Some("0100"))
+    );
+}
+
+#[test]
+fn hex_binary_and_decimal_literals() {
+    assert_eq!(
+        literal("LiteralTest", "LIT_HEX_MASK"),
+        (0x1f, CType::I32, Some("0x1F"))
+    );
+    assert_eq!(
+        literal("LiteralTest", "LIT_BINARY_FLAGS"),
+        (0b1010, CType::I32, Some("0b1010"))
+    );
+    // A plain decimal spelling adds nothing.
+    assert_eq!(
+        literal("LiteralTest", "LIT_DECIMAL"),
+        (42, CType::I32, None)
+    );
+}
+
+#[test]
+fn digit_separators() {
+    let ns = "LiteralTest.Separators";
+    assert_eq!(
+        literal(ns, "LIT_MILLION"),
+        (1_000_000, CType::I32, Some("1'000'000"))
+    );
+    assert_eq!(
+        literal(ns, "LIT_BINARY_BYTE"),
+        (0b1010_0101, CType::I32, Some("0b1010'0101"))
+    );
+    assert_eq!(
+        literal(ns, "LIT_HEX_WORD"),
+        (0xffff_0000, CType::U32, Some("0xFFFF'0000"))
+    );
+}
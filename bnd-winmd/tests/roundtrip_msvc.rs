@@ -0,0 +1,85 @@
+//! `flavor = "msvc"`: MSVC-style headers (`__declspec`, `__stdcall`,
+//! `__int64`) parse for a Windows target, with LLP64 types and the
+//! target's calling conventions.
+
+use std::path::Path;
+use std::sync::LazyLock;
+
+use bnd_winmd::model::{CType, CallConv, Partition};
+
+static PARTITIONS: LazyLock<Vec<Partition>> = LazyLock::new(|| {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("../tests/fixtures/msvc/msvc.toml");
+    bnd_winmd::extract(&path).expect("extract msvc")
+});
+
+fn partition(namespace: &str) -> &'static Partition {
+    PARTITIONS
+        .iter()
+        .find(|p| p.namespace == namespace)
+        .unwrap_or_else(|| panic!("partition {namespace} not found"))
+}
+
+fn calling_convention(namespace: &str, name: &str) -> CallConv {
+    partition(namespace)
+        .functions
+        .iter()
+        .find(|f| f.name == name)
+        .unwrap_or_else(|| panic!("{namespace}.{name} not found"))
+        .calling_convention
+}
+
+#[test]
+fn declspec_functions_extracted() {
+    let names: Vec<_> = partition("MsvcTest")
+        .functions
+        .iter()
+        .map(|f| f.name.as_str())
+        .collect();
+    for name in ["MsvcGetVersion", "MsvcGetInfo", "MsvcAbort"] {
+        assert!(names.contains(&name), "{name} missing: {names:?}");
+    }
+}
+
+#[test]
+fn windows_type_sizes() {
+    let p = partition("MsvcTest");
+
+    // `long` is 32-bit on Windows (LLP64).
+    let point = p.structs.iter().find(|s| s.name == "MSVC_POINT").unwrap();
+    assert_eq!(point.size, 8);
+    assert!(point.fields.iter().all(|f| f.ty == CType::I32));
+
+    let dword = p.typedefs.iter().find(|t| t.name == "DWORD").unwrap();
+    assert_eq!(dword.underlying_type, CType::U32);
+
+    // `unsigned __int64` and a 16-bit `wchar_t`.
+    let info = p.structs.iter().find(|s| s.name == "MSVC_INFO").unwrap();
+    assert_eq!(info.size, 32);
+    assert_eq!(info.fields[1].offset, Some(8));
+}
+
+#[test]
+fn x86_target_keeps_conventions() {
+    let ns = "MsvcTest.X86";
+    assert_eq!(calling_convention(ns, "MsvcEnumPoints"), CallConv::Stdcall);
+    assert_eq!(calling_convention(ns, "MsvcFormat"), CallConv::Cdecl);
+    assert_eq!(calling_convention(ns, "MsvcFast"), CallConv::Fastcall);
+
+    let proc = partition(ns)
+        .typedefs
+        .iter()
+        .find(|t| t.name == "MSVC_ENUMPROC")
+        .unwrap();
+    let CType::Ptr { pointee, .. } = &proc.underlying_type else {
+        panic!(
+            "MSVC_ENUMPROC should be a pointer: {:?}",
+            proc.underlying_type
+        );
+    };
+    match pointee.as_ref() {
+        CType::FnPtr {
+            calling_convention, ..
+        } => assert_eq!(*calling_convention, CallConv::Stdcall),
+        other => panic!("MSVC_ENUMPROC should point to a function, got {other:?}"),
+    }
+}
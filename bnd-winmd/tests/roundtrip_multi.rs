@@ -1,6 +1,6 @@
 //! Round-trip integration test: parse multi-partition config → emit winmd → read back and verify.
 
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::sync::LazyLock;
 
 static MULTI_WINMD: LazyLock<Vec<u8>> = LazyLock::new(|| {
@@ -139,3 +139,338 @@ fn multi_constants_in_types_namespace() {
         "DEFAULT_HEIGHT should be in MultiTest.Types.Apis. Fields: {fields:?}"
     );
 }
+
+static SPLIT: LazyLock<bnd_winmd::GenerateOutput> = LazyLock::new(|| {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("../tests/fixtures/multi/multi.toml");
+    let mut cfg = bnd_winmd::config::load_config(&path).expect("load multi config");
+    cfg.assembly.push(
+        bnd_winmd::config::AssemblyConfig::new("MultiWidgets", "multi_widgets.winmd")
+            .partition("MultiTest.Widgets"),
+    );
+    bnd_winmd::generate_from_config(&cfg, path.parent().unwrap()).expect("generate split winmds")
+});
+
+#[test]
+fn assembly_sections_split_partitions_across_winmds() {
+    let index = |bytes: &[u8]| {
+        let file = windows_metadata::reader::File::new(bytes.to_vec()).expect("parse winmd");
+        windows_metadata::reader::TypeIndex::new(vec![file])
+    };
+    let primary = index(&SPLIT.bytes);
+    assert!(primary.get("MultiTest.Types", "Color").next().is_some());
+    assert!(primary.get("MultiTest.Widgets", "Widget").next().is_none());
+
+    let [widgets] = &SPLIT.assemblies[..] else {
+        panic!("expected one [[assembly]] output");
+    };
+    assert_eq!(widgets.name, "MultiWidgets");
+    assert_eq!(widgets.file, Path::new("multi_widgets.winmd"));
+    let split = index(&widgets.bytes);
+    assert!(split.get("MultiTest.Widgets", "Widget").next().is_some());
+    assert!(
+        split.get("MultiTest.Types", "Color").next().is_none(),
+        "shared registry: Color stays a reference into the primary winmd"
+    );
+
+    // Loaded together, the reference resolves.
+    let both = windows_metadata::reader::TypeIndex::new(vec![
+        windows_metadata::reader::File::new(SPLIT.bytes.clone()).unwrap(),
+        windows_metadata::reader::File::new(widgets.bytes.clone()).unwrap(),
+    ]);
+    assert!(both.get("MultiTest.Types", "Color").next().is_some());
+    assert!(both.get("MultiTest.Widgets", "Widget").next().is_some());
+}
+
+#[test]
+fn generate_reports_stage_timings() {
+    let timings = &SPLIT.timings;
+    let namespaces: Vec<_> = timings
+        .partitions
+        .iter()
+        .map(|p| p.namespace.as_str())
+        .collect();
+    assert_eq!(namespaces, ["MultiTest.Types", "MultiTest.Widgets"]);
+    assert!(timings.parse() > std::time::Duration::ZERO);
+    assert!(timings.total() >= timings.parse() + timings.emit);
+
+    let table = timings.to_string();
+    assert!(table.starts_with("parse "), "{table}");
+    assert!(table.contains("\n  MultiTest.Widgets "), "{table}");
+    assert!(
+        table.lines().last().unwrap().starts_with("total "),
+        "{table}"
+    );
+}
+
+#[test]
+fn assembly_sections_must_name_existing_partitions() {
+    let dir = PathBuf::from(env!("CARGO_TARGET_TMPDIR"));
+    let write = |name: &str, assemblies: &str| {
+        let path = dir.join(name);
+        let toml = format!(
+            "[output]\nname = \"Core\"\n\n\
+             [[partition]]\nnamespace = \"Core.A\"\nlibrary = \"a\"\nheaders = [\"a.h\"]\n\n\
+             {assemblies}"
+        );
+        std::fs::write(&path, toml).unwrap();
+        bnd_winmd::config::load_config(&path)
+    };
+
+    let cfg = write(
+        "assembly_ok.toml",
+        "[[assembly]]\nname = \"Ext\"\nfile = \"ext.winmd\"\npartitions = [\"Core.A\"]\n",
+    )
+    .expect("valid [[assembly]]");
+    assert_eq!(cfg.assembly_of("Core.A").unwrap().name, "Ext");
+
+    let err = write(
+        "assembly_missing.toml",
+        "[[assembly]]\nname = \"Ext\"\nfile = \"ext.winmd\"\npartitions = [\"Core.B\"]\n",
+    )
+    .unwrap_err();
+    assert!(err.to_string().contains("no partition `Core.B`"), "{err}");
+
+    let err = write(
+        "assembly_twice.toml",
+        "[[assembly]]\nname = \"X\"\nfile = \"x.winmd\"\npartitions = [\"Core.A\"]\n\n\
+         [[assembly]]\nname = \"Y\"\nfile = \"y.winmd\"\npartitions = [\"Core.A\"]\n",
+    )
+    .unwrap_err();
+    assert!(err.to_string().contains("more than one assembly"), "{err}");
+}
+
+/// Extract a one-header `App` config whose `[[type_import]]` has the
+/// given keys, next to `multi_test.winmd` (a copy of the multi winmd).
+fn import_multi(dir: &str, import: &str) -> bnd_winmd::Result<bnd_winmd::ir::Ir> {
+    import_winmd(dir, &MULTI_WINMD, import)
+}
+
+/// [`import_multi`] with `winmd` as `multi_test.winmd`.
+fn import_winmd(dir: &str, winmd: &[u8], import: &str) -> bnd_winmd::Result<bnd_winmd::ir::Ir> {
+    let dir = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join(dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join("multi_test.winmd"), winmd).unwrap();
+    // `widget.h` is included but not traversed, so `Widget` resolves to
+    // the import.
+    let widget = Path::new(env!("CARGO_MANIFEST_DIR")).join("../tests/fixtures/multi/widget.h");
+    std::fs::write(
+        dir.join("app.h"),
+        format!(
+            "#include \"{}\"\ntypedef int app_id_t;\nint app_run(app_id_t id);\n\
+             void app_show(const Widget* widget);\n",
+            widget.display()
+        ),
+    )
+    .unwrap();
+    let path = dir.join("app.toml");
+    std::fs::write(
+        &path,
+        format!(
+            "[output]\nname = \"App\"\n\n\
+             [[type_import]]\n{import}\n\
+             [[partition]]\nnamespace = \"App\"\nlibrary = \"app\"\nheaders = [\"app.h\"]\n"
+        ),
+    )
+    .unwrap();
+    bnd_winmd::ir(&path)
+}
+
+static IMPORTED: LazyLock<bnd_winmd::ir::Ir> = LazyLock::new(|| {
+    import_multi(
+        "transitive_import",
+        "winmd = \"multi_test.winmd\"\nnamespace = \"MultiTest.Widgets\"\n",
+    )
+    .expect("extract with transitive import")
+});
+
+static SELECTED: LazyLock<bnd_winmd::ir::Ir> = LazyLock::new(|| {
+    import_multi(
+        "selective_import",
+        "winmd = \"multi_test.winmd\"\nnamespace = \"MultiTest\"\n\
+         types = [\"Widget\", \"Rect\"]\nexclude = [\"Color\"]\n",
+    )
+    .expect("extract with selective import")
+});
+
+static MISSING: LazyLock<bnd_winmd::Error> = LazyLock::new(|| {
+    import_multi(
+        "missing_import",
+        "winmd = \"not_generated_yet.winmd\"\nnamespace = \"MultiTest\"\n",
+    )
+    .expect_err("missing external winmd")
+});
+
+#[test]
+fn type_import_follows_field_types_out_of_the_filter() {
+    let types = &IMPORTED.types;
+    assert_eq!(types["Widget"], "MultiTest.Widgets");
+    // `Widget.color` pulls `Color` in, though its namespace is filtered out.
+    assert_eq!(types["Color"], "MultiTest.Types");
+    // Only fields and delegate signatures are followed, not `Apis`.
+    assert!(!types.contains_key("Rect"), "{types:?}");
+    assert!(!types.contains_key("CompareFunc"), "{types:?}");
+}
+
+#[test]
+fn type_import_types_and_exclude_narrow_the_import() {
+    let types = &SELECTED.types;
+    assert_eq!(types["Widget"], "MultiTest.Widgets");
+    assert_eq!(types["Rect"], "MultiTest.Types");
+    // Excluded even though `Widget.color` needs it.
+    assert!(!types.contains_key("Color"), "{types:?}");
+    assert!(!types.contains_key("CompareFunc"), "{types:?}");
+}
+
+#[test]
+fn imported_types_resolve_through_the_upstream_assembly() {
+    // The multi winmd again, under an assembly name that is not the root
+    // of its namespaces.
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("../tests/fixtures/multi/multi.toml");
+    let mut cfg = bnd_winmd::config::load_config(&path).expect("load multi config");
+    cfg.output.name = "Gadgets".to_string();
+    let upstream = bnd_winmd::generate_from_config(&cfg, path.parent().unwrap())
+        .expect("generate renamed multi winmd");
+    let ir = import_winmd(
+        "assembly_ref_import",
+        &upstream.bytes,
+        "winmd = \"multi_test.winmd\"\nnamespace = \"MultiTest.Widgets\"\n",
+    )
+    .expect("extract with renamed import");
+    assert_eq!(ir.assemblies["Widget"], "Gadgets");
+
+    let bytes = bnd_winmd::emit_ir(&ir).expect("emit imported IR");
+    let refs = bnd_winmd::assembly::type_ref_assemblies(&bytes).expect("read TypeRefs");
+    assert_eq!(refs["MultiTest.Widgets.Widget"], "Gadgets", "{refs:?}");
+    // No row is left named after the imported root namespace.
+    assert!(!refs.values().any(|a| a == "MultiTest"), "{refs:?}");
+}
+
+#[test]
+fn missing_type_import_winmd_is_an_error_not_a_panic() {
+    assert!(
+        matches!(*MISSING, bnd_winmd::Error::Winmd(_)),
+        "{:?}",
+        *MISSING
+    );
+    let msg = MISSING.to_string();
+    assert!(msg.contains("not_generated_yet.winmd"), "{msg}");
+    assert!(msg.contains("run the upstream gen crate first"), "{msg}");
+}
+
+static SHARED: LazyLock<bnd_winmd::GenerateOutput> = LazyLock::new(|| {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("../tests/fixtures/multi/multi.toml");
+    let mut cfg = bnd_winmd::config::load_config(&path).expect("load multi config");
+    cfg.shared_translation_unit = true;
+    bnd_winmd::generate_from_config(&cfg, path.parent().unwrap()).expect("generate shared TU")
+});
+
+static PRECOMPILED: LazyLock<bnd_winmd::GenerateOutput> = LazyLock::new(|| {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("../tests/fixtures/multi/multi.toml");
+    let mut cfg = bnd_winmd::config::load_config(&path).expect("load multi config");
+    cfg.pch.push("types.h".into());
+    bnd_winmd::generate_from_config(&cfg, path.parent().unwrap()).expect("generate with pch")
+});
+
+static MULTI_MODEL: LazyLock<Vec<bnd_winmd::Partition>> = LazyLock::new(|| {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("../tests/fixtures/multi/multi.toml");
+    bnd_winmd::extract(&path).expect("extract multi model")
+});
+
+#[test]
+fn shared_translation_unit_is_sliced_by_traverse() {
+    // Both partitions parse `types.h` + `widget.h` once; traverse still
+    // keeps each declaration in its own partition.
+    let dump = |bytes: &[u8]| bnd_winmd::dump::dump_bytes(bytes.to_vec()).expect("dump winmd");
+    assert_eq!(dump(&SHARED.bytes), dump(&MULTI_WINMD));
+
+    let [types, widgets] = &SHARED.timings.partitions[..] else {
+        panic!("expected two partitions");
+    };
+    assert!(widgets.parse < types.parse, "{}", SHARED.timings);
+}
+
+#[test]
+fn inputs_list_every_file_read() {
+    let names: Vec<&str> = SHARED
+        .inputs
+        .iter()
+        .filter_map(|p| p.file_name()?.to_str())
+        .collect();
+    for expected in ["multi.toml", "types.h", "widget.h"] {
+        assert!(names.contains(&expected), "{expected} missing: {names:?}");
+    }
+    // The shared parse goes through a generated wrapper.
+    assert!(
+        names.iter().any(|n| n.ends_with("_wrapper.c")),
+        "wrapper missing: {names:?}"
+    );
+    assert!(SHARED.inputs.is_sorted());
+}
+
+#[test]
+fn precompiled_headers_keep_their_declarations() {
+    // `types.h` comes from the PCH in both partitions, and its types,
+    // delegate and `#define`s are still extracted.
+    let dump = |bytes: &[u8]| bnd_winmd::dump::dump_bytes(bytes.to_vec()).expect("dump winmd");
+    assert_eq!(dump(&PRECOMPILED.bytes), dump(&MULTI_WINMD));
+    assert!(PRECOMPILED.timings.precompile > std::time::Duration::ZERO);
+}
+
+#[test]
+fn forward_declaration_resolves_to_other_partition() {
+    use bnd_winmd::model::CType;
+
+    let types = MULTI_MODEL
+        .iter()
+        .find(|p| p.namespace == "MultiTest.Types")
+        .expect("MultiTest.Types partition");
+    let param = |function: &str| {
+        let f = types
+            .functions
+            .iter()
+            .find(|f| f.name == function)
+            .unwrap_or_else(|| panic!("{function} not extracted"));
+        f.params[0].ty.clone()
+    };
+    assert_eq!(
+        param("gadget_id"),
+        CType::Ptr {
+            pointee: Box::new(CType::Named {
+                name: "Gadget".to_string(),
+                resolved: None,
+            }),
+            is_const: true,
+        }
+    );
+    assert_eq!(
+        param("hidden_free"),
+        CType::Ptr {
+            pointee: Box::new(CType::Void),
+            is_const: false,
+        }
+    );
+
+    // The winmd references the definition in the other namespace.
+    let index = open_multi_index();
+    assert!(index.contains("MultiTest.Widgets", "Gadget"));
+    assert!(!index.contains("MultiTest.Types", "Gadget"));
+}
+
+#[test]
+fn typedef_of_other_partition_struct_is_an_alias() {
+    let index = open_multi_index();
+    let alias = index.expect("MultiTest.Types", "GadgetAlias");
+    let value = alias
+        .fields()
+        .find(|f| f.name() == "Value")
+        .expect("alias has a Value field");
+    match value.ty() {
+        windows_metadata::Type::Name(tn) => {
+            assert_eq!(
+                (tn.namespace.as_str(), tn.name.as_str()),
+                ("MultiTest.Widgets", "Gadget")
+            );
+        }
+        other => panic!("GadgetAlias.Value should reference Gadget, got {other:?}"),
+    }
+}
@@ -0,0 +1,130 @@
+//! Round-trip integration test for per-file namespaces: derived from the
+//! header directory layout (`namespace_from_path`) and set per header
+//! (`overrides`).
+
+use std::path::Path;
+use std::sync::LazyLock;
+
+static NESTED_OUTPUT: LazyLock<bnd_winmd::GenerateOutput> = LazyLock::new(|| {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("../tests/fixtures/nested/nested.toml");
+    let cfg = bnd_winmd::config::load_config(&path).expect("load nested config");
+    bnd_winmd::generate_from_config(&cfg, path.parent().unwrap()).expect("generate nested winmd")
+});
+
+fn open_index() -> windows_metadata::reader::TypeIndex {
+    let file =
+        windows_metadata::reader::File::new(NESTED_OUTPUT.bytes.clone()).expect("parse winmd");
+    windows_metadata::reader::TypeIndex::new(vec![file])
+}
+
+#[test]
+fn subdirectories_become_sub_namespaces() {
+    let index = open_index();
+
+    index.expect("NestedTest", "nested_header");
+    index.expect("NestedTest", "nested_u32");
+    index.expect("NestedTest.net", "nlmsg");
+    // `can-bus` is not a valid namespace segment.
+    index.expect("NestedTest.can_bus", "can_state");
+    index.expect("NestedTest.can_bus", "can_frame");
+    assert!(
+        index.get("NestedTest", "nlmsg").next().is_none(),
+        "nlmsg should only be in NestedTest.net"
+    );
+}
+
+#[test]
+fn sub_namespace_apis_and_cross_references() {
+    let index = open_index();
+
+    let apis = index.expect("NestedTest.net", "Apis");
+    assert!(apis.methods().any(|m| m.name() == "nlmsg_send"));
+    assert!(apis.fields().any(|f| f.name() == "NLMSG_ALIGNTO"));
+    assert!(index.get("NestedTest", "Apis").next().is_none());
+
+    // A field typed with a struct from the parent directory references it
+    // in its own namespace.
+    let nlmsg = index.expect("NestedTest.net", "nlmsg");
+    let hdr = nlmsg
+        .fields()
+        .find(|f| f.name() == "hdr")
+        .expect("hdr field");
+    match hdr.ty() {
+        windows_metadata::Type::Name(tn) => {
+            assert_eq!(
+                (tn.namespace.as_str(), tn.name.as_str()),
+                ("NestedTest", "nested_header")
+            );
+        }
+        other => panic!("hdr should reference nested_header, got {other:?}"),
+    }
+}
+
+#[test]
+fn file_override_wins_over_directory() {
+    let index = open_index();
+
+    index.expect("NestedTest.attr", "nlattr");
+    assert!(
+        index.get("NestedTest.net.bits", "nlattr").next().is_none(),
+        "nlattr should follow its override, not the directory"
+    );
+
+    let nlmsg = index.expect("NestedTest.net", "nlmsg");
+    let attr = nlmsg
+        .fields()
+        .find(|f| f.name() == "attr")
+        .expect("attr field");
+    match attr.ty() {
+        windows_metadata::Type::Name(tn) => assert_eq!(tn.namespace, "NestedTest.attr"),
+        other => panic!("attr should reference nlattr, got {other:?}"),
+    }
+}
+
+#[test]
+fn unmatched_file_override_is_reported() {
+    let missing = NESTED_OUTPUT
+        .diagnostics
+        .with_code("namespace_override_unmatched")
+        .next()
+        .expect("override for uapi/missing.h should be reported");
+    assert_eq!(missing.namespace.as_deref(), Some("NestedTest.missing"));
+    assert!(missing.message.contains("uapi/missing.h"), "{missing:?}");
+}
+
+#[test]
+fn derived_namespaces_follow_their_partition_into_its_assembly() {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("../tests/fixtures/nested/nested.toml");
+    let mut cfg = bnd_winmd::config::load_config(&path).expect("load nested config");
+    cfg.assembly.push(
+        bnd_winmd::config::AssemblyConfig::new("NestedExt", "nested_ext.winmd")
+            .partition("NestedTest"),
+    );
+    let output =
+        bnd_winmd::generate_from_config(&cfg, path.parent().unwrap()).expect("generate winmds");
+    let index = |bytes: &[u8]| {
+        let file = windows_metadata::reader::File::new(bytes.to_vec()).expect("parse winmd");
+        windows_metadata::reader::TypeIndex::new(vec![file])
+    };
+
+    let [ext] = &output.assemblies[..] else {
+        panic!("expected one [[assembly]] output");
+    };
+    let ext = index(&ext.bytes);
+    let primary = index(&output.bytes);
+    for (namespace, name) in [
+        ("NestedTest", "nested_header"),
+        ("NestedTest.net", "nlmsg"),
+        ("NestedTest.can_bus", "can_frame"),
+        ("NestedTest.attr", "nlattr"),
+    ] {
+        assert!(
+            ext.get(namespace, name).next().is_some(),
+            "{namespace}.{name} should be in NestedExt"
+        );
+        assert!(
+            primary.get(namespace, name).next().is_none(),
+            "{namespace}.{name} should not be in [output]"
+        );
+    }
+}
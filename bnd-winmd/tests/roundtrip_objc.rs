@@ -0,0 +1,70 @@
+//! `language = "objective-c"`: C declarations in a header mixing C and
+//! Objective-C are extracted; classes, categories and protocols are
+//! skipped without failing `strict`.
+
+use std::path::Path;
+use std::sync::LazyLock;
+
+use bnd_winmd::model::{CType, Partition, SkipReason};
+
+static PARTITION: LazyLock<Partition> = LazyLock::new(|| {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("../tests/fixtures/objc/objc.toml");
+    let mut partitions = bnd_winmd::extract(&path).expect("extract objc");
+    assert_eq!(partitions.len(), 1);
+    partitions.remove(0)
+});
+
+fn void_ptr() -> CType {
+    CType::Ptr {
+        pointee: Box::new(CType::Void),
+        is_const: false,
+    }
+}
+
+#[test]
+fn c_declarations_extracted() {
+    let p = &*PARTITION;
+    for name in ["ObjcPoint", "ObjcHolder"] {
+        assert!(p.structs.iter().any(|s| s.name == name), "{name} missing");
+    }
+    assert!(p.enums.iter().any(|e| e.name == "ObjcMode"));
+    assert!(p.constants.iter().any(|c| c.name == "OBJC_LIMIT"));
+
+    let names: Vec<_> = p.functions.iter().map(|f| f.name.as_str()).collect();
+    for name in [
+        "objc_point_count",
+        "objc_widget_create",
+        "objc_widget_release",
+        "objc_selector",
+        "objc_run",
+    ] {
+        assert!(names.contains(&name), "{name} missing: {names:?}");
+    }
+}
+
+#[test]
+fn objc_types_are_opaque_pointers() {
+    let p = &*PARTITION;
+    let function = |name: &str| p.functions.iter().find(|f| f.name == name).unwrap();
+
+    assert_eq!(function("objc_widget_create").return_type, void_ptr());
+    assert_eq!(function("objc_widget_release").params[0].ty, void_ptr());
+    assert_eq!(function("objc_run").params[0].ty, void_ptr());
+
+    let holder = p.structs.iter().find(|s| s.name == "ObjcHolder").unwrap();
+    assert_eq!(holder.size, 24);
+    assert_eq!(holder.fields[0].ty, void_ptr());
+}
+
+#[test]
+fn objc_declarations_skipped() {
+    let skipped = |name: &str, kind: &str| {
+        PARTITION
+            .skipped
+            .iter()
+            .any(|s| s.name == name && s.kind == kind && matches!(s.reason, SkipReason::ObjC))
+    };
+    assert!(skipped("ObjcWidget", "objc interface"));
+    assert!(skipped("Extras", "objc category"));
+    assert!(skipped("ObjcDrawing", "objc protocol"));
+}
@@ -0,0 +1,71 @@
+//! `[shim]`: static inline functions are imported from exported wrappers
+//! in a generated C source.
+
+use std::path::{Path, PathBuf};
+use std::sync::LazyLock;
+
+/// Run the pipeline into a scratch directory; returns the winmd path.
+static SHIM_WINMD: LazyLock<PathBuf> = LazyLock::new(|| {
+    let config = Path::new(env!("CARGO_MANIFEST_DIR")).join("../tests/fixtures/shim/shim.toml");
+    let out = Path::new(env!("CARGO_TARGET_TMPDIR")).join("shim_test.winmd");
+    bnd_winmd::run(&config, None, Some(&out)).expect("run shim pipeline")
+});
+
+/// `(import scope, import name)` of `ShimTest.Apis::<function>`.
+fn import_of(function: &str) -> (String, String) {
+    let bytes = std::fs::read(&*SHIM_WINMD).expect("read winmd");
+    let file = windows_metadata::reader::File::new(bytes).expect("parse winmd");
+    let index = windows_metadata::reader::TypeIndex::new(vec![file]);
+    let method = index
+        .expect("ShimTest", "Apis")
+        .methods()
+        .find(|m| m.name() == function)
+        .unwrap_or_else(|| panic!("{function} not found"));
+    let import = method
+        .impl_map()
+        .unwrap_or_else(|| panic!("{function} has no ImplMap"));
+    (
+        import.import_scope().name().to_string(),
+        import.import_name().to_string(),
+    )
+}
+
+fn shim_source() -> String {
+    let path = SHIM_WINMD.with_file_name("shim_test_shims.c");
+    std::fs::read_to_string(&path).expect("read shim source")
+}
+
+#[test]
+fn shimmed_functions_import_wrappers() {
+    for name in ["shim_swap32", "shim_clear", "shim_zero"] {
+        assert_eq!(
+            import_of(name),
+            ("shimtest_shims".to_string(), format!("bnd_shim_{name}"))
+        );
+    }
+}
+
+#[test]
+fn exported_functions_are_untouched() {
+    assert_eq!(
+        import_of("shim_exported"),
+        ("shimtest".to_string(), "shim_exported".to_string())
+    );
+}
+
+#[test]
+fn shim_source_defines_wrappers() {
+    let source = shim_source();
+    assert!(source.contains("#include <shim.h>"), "{source}");
+    for expected in [
+        "BND_SHIM_EXPORT uint32_t bnd_shim_shim_swap32(uint32_t p0) {\n    return shim_swap32(p0);\n}",
+        "BND_SHIM_EXPORT void bnd_shim_shim_clear(int * p0) {\n    shim_clear(p0);\n}",
+        "BND_SHIM_EXPORT int bnd_shim_shim_zero(void) {\n    return shim_zero();\n}",
+    ] {
+        assert!(
+            source.contains(expected),
+            "missing {expected:?} in:\n{source}"
+        );
+    }
+    assert!(!source.contains("shim_exported"), "{source}");
+}
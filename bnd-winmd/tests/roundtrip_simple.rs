@@ -8,6 +8,76 @@ static SIMPLE_WINMD: LazyLock<Vec<u8>> = LazyLock::new(|| {
     bnd_winmd::generate(&path).expect("generate simple winmd")
 });
 
+static SIMPLE_LAYOUT_TESTS: LazyLock<String> = LazyLock::new(|| {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("../tests/fixtures/simple/simple.toml");
+    let out = Path::new(env!("CARGO_TARGET_TMPDIR")).join("simple_layout/simple_test.winmd");
+    std::fs::create_dir_all(out.parent().unwrap()).expect("create output dir");
+    bnd_winmd::run(&path, None, Some(&out)).expect("run simple pipeline");
+    std::fs::read_to_string(out.with_file_name("layout_tests.rs")).expect("read layout tests")
+});
+
+static SIMPLE_MODEL: LazyLock<Vec<bnd_winmd::Partition>> = LazyLock::new(|| {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("../tests/fixtures/simple/simple.toml");
+    bnd_winmd::extract(&path).expect("extract simple model")
+});
+
+static SIMPLE_IR: LazyLock<bnd_winmd::ir::Ir> = LazyLock::new(|| {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("../tests/fixtures/simple/simple.toml");
+    bnd_winmd::ir(&path).expect("extract simple IR")
+});
+
+static SIMPLE_HOOKED: LazyLock<Vec<bnd_winmd::Partition>> = LazyLock::new(|| {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("../tests/fixtures/simple/simple.toml");
+    let mut cfg = bnd_winmd::config::load_config(&path).expect("load simple config");
+    cfg.type_map_hook = Some(std::sync::Arc::new(
+        |spelling: &str, default: bnd_winmd::CType| match spelling {
+            // Flatten the enum to its integer.
+            "Color" => Some(bnd_winmd::CType::U32),
+            "Rect" => {
+                assert!(matches!(&default, bnd_winmd::CType::Named { name, .. } if name == "Rect"));
+                None
+            }
+            _ => None,
+        },
+    ));
+    bnd_winmd::extract_from_config(&cfg, path.parent().unwrap()).expect("extract with hook")
+});
+
+/// Records every progress event as a line.
+#[derive(Default)]
+struct Recorder(std::sync::Mutex<Vec<String>>);
+
+impl bnd_winmd::ProgressSink for Recorder {
+    fn on_partition_start(&self, index: usize, total: usize, namespace: &str) {
+        let event = format!("start {index}/{total} {namespace}");
+        self.0.lock().unwrap().push(event);
+    }
+
+    fn on_partition_done(
+        &self,
+        namespace: &str,
+        counts: &bnd_winmd::progress::PartitionCounts,
+        _elapsed: std::time::Duration,
+    ) {
+        let event = format!("done {namespace} {} functions", counts.functions);
+        self.0.lock().unwrap().push(event);
+    }
+
+    fn on_emit_done(&self, assembly: &str, bytes: usize, _elapsed: std::time::Duration) {
+        let event = format!("emit {assembly} {}", bytes > 0);
+        self.0.lock().unwrap().push(event);
+    }
+}
+
+static SIMPLE_PROGRESS: LazyLock<Vec<String>> = LazyLock::new(|| {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("../tests/fixtures/simple/simple.toml");
+    let mut cfg = bnd_winmd::config::load_config(&path).expect("load simple config");
+    let recorder = std::sync::Arc::new(Recorder::default());
+    cfg.progress = Some(recorder.clone());
+    bnd_winmd::generate_from_config(&cfg, path.parent().unwrap()).expect("generate with progress");
+    recorder.0.lock().unwrap().clone()
+});
+
 fn open_index() -> windows_metadata::reader::TypeIndex {
     let file = windows_metadata::reader::File::new(SIMPLE_WINMD.clone()).expect("parse winmd");
     windows_metadata::reader::TypeIndex::new(vec![file])
@@ -76,6 +146,25 @@ fn roundtrip_enum_variants() {
     );
 }
 
+#[test]
+fn roundtrip_enum_aliases() {
+    let index = open_index();
+
+    // Variants sharing a value are all kept, each with its own literal.
+    let signal = index.expect("SimpleTest", "Signal");
+    let value = |name: &str| {
+        signal
+            .fields()
+            .find(|f| f.name() == name)
+            .unwrap_or_else(|| panic!("missing {name}"))
+            .constant()
+            .map(|c| c.value())
+    };
+    assert_eq!(value("SIGNAL_ABRT"), value("SIGNAL_IOT"));
+    assert_eq!(value("SIGNAL_DEFAULT"), value("SIGNAL_HUP"));
+    assert_eq!(signal.fields().count(), 5);
+}
+
 #[test]
 fn roundtrip_struct_fields() {
     let index = open_index();
@@ -183,6 +272,33 @@ fn roundtrip_anonymous_nested_type() {
     );
 }
 
+#[test]
+fn roundtrip_anonymous_enum_field() {
+    let index = open_index();
+
+    let mode = index.expect("SimpleTest", "WithAnonEnum_mode");
+    let extends = mode.extends().expect("enum must extend something");
+    assert_eq!(extends.name(), "Enum");
+    let variants: Vec<String> = mode
+        .fields()
+        .map(|f| f.name().to_string())
+        .filter(|n| n != "value__")
+        .collect();
+    assert_eq!(variants, ["ANON_MODE_OFF", "ANON_MODE_ON"]);
+
+    let fields: Vec<_> = index
+        .expect("SimpleTest", "WithAnonEnum")
+        .fields()
+        .map(|f| (f.name().to_string(), f.ty()))
+        .collect();
+    assert_eq!(fields[0].0, "mode");
+    assert!(
+        matches!(&fields[0].1, windows_metadata::Type::Name(tn) if tn.name == "WithAnonEnum_mode"),
+        "{fields:?}"
+    );
+    assert_eq!(fields[1].0, "flags");
+}
+
 #[test]
 fn roundtrip_functions() {
     let index = open_index();
@@ -350,3 +466,150 @@ fn roundtrip_pinvoke() {
         "DLL name should be 'simple'"
     );
 }
+
+#[test]
+fn layout_tests_assert_clang_sizes_and_alignments() {
+    let tests = &*SIMPLE_LAYOUT_TESTS;
+    assert!(
+        tests.contains(
+            "fn size_of_SimpleTest_Rect() {\n    \
+             assert_eq!(core::mem::size_of::<crate::bindings::Rect>(), 16, \"sizeof(Rect)\");\n    \
+             assert_eq!(core::mem::align_of::<crate::bindings::Rect>(), 4, \"alignof(Rect)\");\n}"
+        ),
+        "{tests}"
+    );
+    // `aligned(64)` can't be expressed through the winmd, in the struct
+    // itself or in one embedding it.
+    for name in ["AlignedInner", "EmbeddingAligned"] {
+        assert!(
+            tests.contains(&format!(
+                "// alignof({name}) = 64 is not checked: over-aligned beyond its fields."
+            )),
+            "{tests}"
+        );
+    }
+    assert!(
+        tests.contains("size_of::<crate::bindings::EmbeddingAligned>(), 192"),
+        "{tests}"
+    );
+    // Injected opaque structs carry their declared size.
+    assert!(
+        tests.contains("size_of::<crate::bindings::OpaqueCtx>(), 32"),
+        "{tests}"
+    );
+}
+
+#[test]
+fn extract_returns_resolved_model() {
+    let [partition] = SIMPLE_MODEL.as_slice() else {
+        panic!("expected one partition: {:?}", *SIMPLE_MODEL);
+    };
+    assert_eq!(partition.namespace, "SimpleTest");
+    assert_eq!(partition.library, "simple");
+
+    let create = partition
+        .functions
+        .iter()
+        .find(|f| f.name == "create_widget")
+        .expect("create_widget");
+    assert_eq!(create.params.len(), 3);
+    assert!(matches!(create.return_type, bnd_winmd::CType::I32));
+
+    // Injected types are merged in; the extracted `Color` wins over the
+    // injected one.
+    assert!(partition.enums.iter().any(|e| e.name == "Priority"));
+    let color = partition.enums.iter().find(|e| e.name == "Color").unwrap();
+    assert!(color.variants.iter().all(|v| v.name != "COLOR_INJECTED"));
+
+    // Same order as the winmd.
+    assert!(partition.functions.is_sorted_by(|a, b| a.name <= b.name));
+}
+
+#[test]
+fn ir_json_roundtrips() {
+    let json = SIMPLE_IR.to_json().expect("serialize IR");
+    let back = bnd_winmd::ir::Ir::from_json(&json).expect("parse IR");
+    assert_eq!(back.to_json().unwrap(), json);
+
+    assert_eq!(back.assembly, "SimpleTest");
+    assert_eq!(back.format, bnd_winmd::ir::IR_FORMAT);
+    assert_eq!(
+        back.types.get("Rect").map(String::as_str),
+        Some("SimpleTest")
+    );
+    let names = |p: &bnd_winmd::Partition| {
+        p.functions
+            .iter()
+            .map(|f| f.name.clone())
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(names(&back.partitions[0]), names(&SIMPLE_MODEL[0]));
+    assert!(
+        back.stamp
+            .headers
+            .iter()
+            .any(|(path, _)| path == "simple.h")
+    );
+
+    // IR from another format version is rejected rather than misread.
+    let future = json.replacen(
+        &format!("\"format\": {}", bnd_winmd::ir::IR_FORMAT),
+        "\"format\": 999",
+        1,
+    );
+    let err = bnd_winmd::ir::Ir::from_json(&future).unwrap_err();
+    assert!(format!("{err:#}").contains("IR format 999"), "{err:#}");
+}
+
+#[test]
+fn emit_from_ir_matches_generate() {
+    let path = Path::new(env!("CARGO_TARGET_TMPDIR")).join("simple_ir/ir.json");
+    std::fs::create_dir_all(path.parent().unwrap()).expect("create output dir");
+    SIMPLE_IR.write_json(&path).expect("write IR");
+
+    let bytes = bnd_winmd::emit_from_ir(&path).expect("emit from IR");
+    assert!(
+        bytes == *SIMPLE_WINMD,
+        "IR emission differs from generate()"
+    );
+
+    let missing = bnd_winmd::emit_from_ir(&path.with_file_name("missing.json")).unwrap_err();
+    assert!(matches!(missing, bnd_winmd::Error::Ir(_)), "{missing}");
+}
+
+#[test]
+fn type_map_hook_overrides_mapping() {
+    let partition = &SIMPLE_HOOKED[0];
+    let widget = partition
+        .structs
+        .iter()
+        .find(|s| s.name == "Widget")
+        .unwrap();
+    let color = widget.fields.iter().find(|f| f.name == "color").unwrap();
+    assert_eq!(color.ty, bnd_winmd::CType::U32);
+
+    // Everything the hook declines keeps its default mapping.
+    let create = partition
+        .functions
+        .iter()
+        .find(|f| f.name == "create_widget")
+        .unwrap();
+    assert!(matches!(&create.params[1].ty, bnd_winmd::CType::Named { name, .. } if name == "Rect"));
+    assert!(matches!(
+        &create.params[0].ty,
+        bnd_winmd::CType::Ptr { is_const: true, .. }
+    ));
+}
+
+#[test]
+fn progress_sink_sees_partitions_and_emit() {
+    let functions = SIMPLE_MODEL[0].functions.len();
+    assert_eq!(
+        *SIMPLE_PROGRESS,
+        [
+            "start 0/1 SimpleTest".to_string(),
+            format!("done SimpleTest {functions} functions"),
+            "emit SimpleTest true".to_string(),
+        ]
+    );
+}
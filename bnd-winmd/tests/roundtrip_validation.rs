@@ -12,6 +12,32 @@ static UNRESOLVED_RESULT: LazyLock<Result<Vec<u8>, String>> = LazyLock::new(|| {
     bnd_winmd::generate(&path).map_err(|e| format!("{e:#}"))
 });
 
+static COLLISION_RESULT: LazyLock<Result<Vec<u8>, String>> = LazyLock::new(|| {
+    let path =
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("../tests/fixtures/collision/collision.toml");
+    bnd_winmd::generate(&path).map_err(|e| format!("{e:#}"))
+});
+
+static STRICT_RESULT: LazyLock<Result<Vec<u8>, bnd_winmd::Error>> = LazyLock::new(|| {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("../tests/fixtures/strict/strict.toml");
+    bnd_winmd::generate(&path)
+});
+
+fn conflict_toml() -> std::path::PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("../tests/fixtures/conflict/conflict.toml")
+}
+
+static CONFLICT_RESULT: LazyLock<Result<Vec<u8>, bnd_winmd::Error>> =
+    LazyLock::new(|| bnd_winmd::generate(&conflict_toml()));
+
+static CONFLICT_ALLOWED: LazyLock<bnd_winmd::GenerateOutput> = LazyLock::new(|| {
+    let path = conflict_toml();
+    let mut cfg = bnd_winmd::config::load_config(&path).expect("load conflict config");
+    cfg.allow_duplicate_types = true;
+    bnd_winmd::generate_from_config(&cfg, path.parent().unwrap())
+        .expect("allow_duplicate_types should keep one definition")
+});
+
 #[test]
 fn unresolved_type_reference_is_caught() {
     let err = UNRESOLVED_RESULT
@@ -37,6 +63,18 @@ fn unresolved_type_reference_is_caught() {
     );
 }
 
+#[test]
+fn errors_are_classified_by_stage() {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("../tests/fixtures/missing/missing.toml");
+    let err = bnd_winmd::generate(&path).expect_err("config file does not exist");
+    assert!(matches!(err, bnd_winmd::Error::Config(_)), "{err:?}");
+    assert!(
+        err.to_string()
+            .starts_with("config error: loading config from"),
+        "{err}"
+    );
+}
+
 #[test]
 fn unresolved_does_not_report_known_types() {
     let err = UNRESOLVED_RESULT
@@ -49,3 +87,279 @@ fn unresolved_does_not_report_known_types() {
         "error should NOT mention 'KnownStruct' (it's properly traversed), got:\n{err}"
     );
 }
+
+#[test]
+fn struct_function_collision_is_an_error() {
+    let err = COLLISION_RESULT
+        .as_ref()
+        .expect_err("name_collision = \"error\" should fail generation");
+
+    assert!(err.contains("2 type/function name collision"), "{err}");
+    assert!(
+        err.contains("`collide` is both a struct and a function in `test.collision`"),
+        "{err}"
+    );
+    assert!(
+        err.contains(
+            "`collide_stream_vtbl` is both an interface and a function in `test.collision`"
+        ),
+        "{err}"
+    );
+    // Interfaces are renamed with `kind = "struct"`.
+    assert!(!err.contains("kind = \"interface\""), "{err}");
+    // The suggested fix is a ready-to-paste rename rule.
+    assert!(
+        err.contains("name = \"collide\"") && err.contains("to = \"collide_t\""),
+        "{err}"
+    );
+    assert!(!err.contains("apart"), "{err}");
+}
+
+#[test]
+fn fail_on_collects_skipped_declarations() {
+    let err = STRICT_RESULT
+        .as_ref()
+        .expect_err("fail_on should turn skips into an error");
+    let bnd_winmd::Error::Skipped(e) = err else {
+        panic!("expected Error::Skipped, got: {err}");
+    };
+    let skipped = &e.skipped;
+
+    let mut names: Vec<_> = skipped
+        .iter()
+        .map(|(ns, d)| (ns.as_str(), d.kind.as_str(), d.name.as_str()))
+        .collect();
+    names.sort();
+    assert_eq!(
+        names,
+        [
+            ("test.strict", "function", "strict_log"),
+            ("test.strict", "macro", "STRICT_NAME"),
+        ]
+    );
+}
+
+#[test]
+fn struct_layout_conflict_across_partitions_is_an_error() {
+    let err = CONFLICT_RESULT
+        .as_ref()
+        .expect_err("differing layouts should fail generation");
+    assert!(matches!(err, bnd_winmd::Error::TypeConflict(_)), "{err:?}");
+
+    let err = err.to_string();
+    assert!(
+        err.contains("1 struct(s) defined with different layouts"),
+        "{err}"
+    );
+    assert!(
+        err.contains(
+            "`conflict_cfg` in `test.conflict.a` (4 bytes: flags) and `test.conflict.b` (16 bytes: flags, version)"
+        ),
+        "{err}"
+    );
+    assert!(err.contains("allow_duplicate_types"), "{err}");
+    // Identical definitions are plain duplicates.
+    assert!(!err.contains("conflict_point"), "{err}");
+}
+
+#[test]
+fn allow_duplicate_types_keeps_one_definition() {
+    let output = &*CONFLICT_ALLOWED;
+    let conflict = output
+        .diagnostics
+        .with_code("type_conflict")
+        .next()
+        .expect("conflict should still be reported");
+    assert_eq!(conflict.name.as_deref(), Some("conflict_cfg"));
+
+    let file = windows_metadata::reader::File::new(output.bytes.clone()).expect("parse winmd");
+    let index = windows_metadata::reader::TypeIndex::new(vec![file]);
+    let count = ["test.conflict.a", "test.conflict.b"]
+        .iter()
+        .filter(|ns| index.get(ns, "conflict_cfg").next().is_some())
+        .count();
+    assert_eq!(count, 1, "only one conflict_cfg should be emitted");
+}
+
+/// `headers` reaches `api.h` through a symlinked directory while
+/// `traverse` names the real path, plus one misspelled traverse entry.
+static SYMLINKED: LazyLock<bnd_winmd::GenerateOutput> = LazyLock::new(|| {
+    let dir = std::path::PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("symlinked_traverse");
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(dir.join("real")).unwrap();
+    std::fs::write(dir.join("real/api.h"), "int api_call(int x);\n").unwrap();
+    std::os::unix::fs::symlink(dir.join("real"), dir.join("link")).unwrap();
+    let path = dir.join("symlinked.toml");
+    std::fs::write(
+        &path,
+        "[output]\nname = \"Symlinked\"\n\n\
+         [[partition]]\nnamespace = \"Symlinked\"\nlibrary = \"api\"\n\
+         headers = [\"link/api.h\"]\ntraverse = [\"real/api.h\", \"real/apj.h\"]\n",
+    )
+    .unwrap();
+    let cfg = bnd_winmd::config::load_config(&path).expect("load symlinked config");
+    bnd_winmd::generate_from_config(&cfg, &dir).expect("generate through symlink")
+});
+
+#[test]
+fn traverse_matches_through_symlinks_and_reports_typos() {
+    let file = windows_metadata::reader::File::new(SYMLINKED.bytes.clone()).unwrap();
+    let index = windows_metadata::reader::TypeIndex::new(vec![file]);
+    let apis = index.expect("Symlinked", "Apis");
+    assert!(apis.methods().any(|m| m.name() == "api_call"));
+
+    let unmatched: Vec<_> = SYMLINKED
+        .diagnostics
+        .with_code("traverse_unmatched")
+        .map(|d| d.message.as_str())
+        .collect();
+    assert_eq!(
+        unmatched,
+        ["traverse entry `real/apj.h` matched no declarations"]
+    );
+}
+
+/// The symlinked config from [`SYMLINKED`] with `fail_on` covering
+/// unmatched traverse entries.
+static UNMATCHED_STRICT: LazyLock<bnd_winmd::Error> = LazyLock::new(|| {
+    LazyLock::force(&SYMLINKED);
+    let dir = std::path::PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("symlinked_traverse");
+    let mut cfg =
+        bnd_winmd::config::load_config(&dir.join("symlinked.toml")).expect("load symlinked config");
+    cfg.fail_on = vec![bnd_winmd::config::SkipKind::UnmatchedTraverse];
+    bnd_winmd::generate_from_config(&cfg, &dir).expect_err("typo'd traverse entry must fail")
+});
+
+#[test]
+fn unmatched_traverse_fails_when_covered_by_fail_on() {
+    assert!(matches!(*UNMATCHED_STRICT, bnd_winmd::Error::Config(_)));
+    let msg = UNMATCHED_STRICT.to_string();
+    assert!(
+        msg.contains("1 traverse path(s) matched no declarations"),
+        "{msg}"
+    );
+    assert!(msg.contains("`real/apj.h` in `Symlinked`"), "{msg}");
+    assert!(!msg.contains("real/api.h"), "{msg}");
+}
+
+/// A header that warns and errors when compiled without `RIGHT_FLAG`.
+fn broken_config() -> (bnd_winmd::config::Config, std::path::PathBuf) {
+    let dir = std::path::PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("clang_errors");
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(
+        dir.join("broken.h"),
+        "int ok_call(int x);\n\
+         #warning \"deprecated header\"\n\
+         #ifndef RIGHT_FLAG\n\
+         #error \"RIGHT_FLAG must be defined\"\n\
+         #endif\n",
+    )
+    .unwrap();
+    let path = dir.join("broken.toml");
+    std::fs::write(
+        &path,
+        "[output]\nname = \"Broken\"\n\n\
+         [[partition]]\nnamespace = \"Broken\"\nlibrary = \"broken\"\n\
+         headers = [\"broken.h\"]\ntraverse = [\"broken.h\"]\n",
+    )
+    .unwrap();
+    let cfg = bnd_winmd::config::load_config(&path).expect("load broken config");
+    (cfg, dir)
+}
+
+static CLANG_ERROR: LazyLock<bnd_winmd::Error> = LazyLock::new(|| {
+    let (mut cfg, dir) = broken_config();
+    cfg.clang_errors = bnd_winmd::config::Severity::Error;
+    bnd_winmd::generate_from_config(&cfg, &dir).expect_err("clang_errors = error should fail")
+});
+
+static CLANG_ERROR_WARNED: LazyLock<bnd_winmd::GenerateOutput> = LazyLock::new(|| {
+    let (cfg, dir) = broken_config();
+    bnd_winmd::generate_from_config(&cfg, &dir).expect("clang errors only warn by default")
+});
+
+#[test]
+fn clang_errors_fail_with_clang_messages() {
+    assert!(matches!(*CLANG_ERROR, bnd_winmd::Error::Parse(_)));
+    let msg = CLANG_ERROR.to_string();
+    assert!(
+        msg.contains("clang reported 1 error(s) parsing `Broken`"),
+        "{msg}"
+    );
+    assert!(msg.contains("broken.h:4"), "{msg}");
+    assert!(msg.contains("RIGHT_FLAG must be defined"), "{msg}");
+    assert!(!msg.contains("deprecated header"), "{msg}");
+}
+
+#[test]
+fn clang_warnings_and_lowered_errors_become_diagnostics() {
+    let diagnostics = &CLANG_ERROR_WARNED.diagnostics;
+    let error: Vec<_> = diagnostics.with_code("clang_error").collect();
+    assert_eq!(error.len(), 1);
+    assert!(error[0].message.contains("RIGHT_FLAG must be defined"));
+    assert!(error[0].file.as_ref().unwrap().ends_with("broken.h"));
+    assert_eq!(error[0].line, Some(4));
+    assert_eq!(error[0].namespace.as_deref(), Some("Broken"));
+
+    assert!(
+        diagnostics
+            .with_code("clang_warning")
+            .any(|d| d.message.contains("deprecated header"))
+    );
+}
+
+static DELEGATE_CONFLICT: LazyLock<bnd_winmd::Error> = LazyLock::new(|| {
+    let dir = std::path::PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("delegate_conflict");
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(
+        dir.join("a.h"),
+        "typedef void (*handler_t)(int sig);\nvoid a_install(handler_t h);\n",
+    )
+    .unwrap();
+    std::fs::write(
+        dir.join("b.h"),
+        "typedef int (*handler_t)(int sig, void *ctx);\nvoid b_install(handler_t h);\n",
+    )
+    .unwrap();
+    let path = dir.join("delegates.toml");
+    std::fs::write(
+        &path,
+        "[output]\nname = \"Delegates\"\n\n\
+         [[partition]]\nnamespace = \"Delegates.A\"\nlibrary = \"d\"\nheaders = [\"a.h\"]\n\n\
+         [[partition]]\nnamespace = \"Delegates.B\"\nlibrary = \"d\"\nheaders = [\"b.h\"]\n",
+    )
+    .unwrap();
+    bnd_winmd::generate(&path).expect_err("differing delegate signatures")
+});
+
+#[test]
+fn identical_delegates_are_deduplicated() {
+    let output = &*CONFLICT_ALLOWED;
+    let file = windows_metadata::reader::File::new(output.bytes.clone()).expect("parse winmd");
+    let index = windows_metadata::reader::TypeIndex::new(vec![file]);
+    let count = ["test.conflict.a", "test.conflict.b"]
+        .iter()
+        .filter(|ns| index.get(ns, "conflict_visit_fn").next().is_some())
+        .count();
+    assert_eq!(count, 1);
+}
+
+#[test]
+fn delegates_with_different_signatures_fail() {
+    assert!(
+        matches!(*DELEGATE_CONFLICT, bnd_winmd::Error::TypeConflict(_)),
+        "{:?}",
+        *DELEGATE_CONFLICT
+    );
+    let msg = DELEGATE_CONFLICT.to_string();
+    assert!(
+        msg.contains("1 delegate(s) defined with different signatures"),
+        "{msg}"
+    );
+    assert!(
+        msg.contains(
+            "`handler_t` in `Delegates.A` (void (*)(int32_t)) and `Delegates.B` (int32_t (*)(int32_t, void *))"
+        ),
+        "{msg}"
+    );
+}
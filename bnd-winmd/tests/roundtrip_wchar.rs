@@ -0,0 +1,107 @@
+//! `wchar_t`, `char16_t` and `char32_t`: width from the target, UTF-32
+//! text tagged with `NativeEncodingAttribute`.
+
+use std::path::{Path, PathBuf};
+use std::sync::LazyLock;
+
+use windows_metadata::reader::HasAttributes;
+use windows_metadata::{Type, Value};
+
+fn fixture(name: &str) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("../tests/fixtures/wchar")
+        .join(name)
+}
+
+static WCHAR_WINMD: LazyLock<Vec<u8>> =
+    LazyLock::new(|| bnd_winmd::generate(&fixture("wchar.toml")).expect("generate wchar winmd"));
+
+fn open_index() -> windows_metadata::reader::TypeIndex {
+    let file = windows_metadata::reader::File::new(WCHAR_WINMD.clone()).expect("parse winmd");
+    windows_metadata::reader::TypeIndex::new(vec![file])
+}
+
+/// The `NativeEncodingAttribute` argument on a field or param, if any.
+fn encoding<'a>(item: &impl HasAttributes<'a>) -> Option<String> {
+    let attr = item.find_attribute("NativeEncodingAttribute")?;
+    match attr.value().as_slice() {
+        [(_, Value::Utf8(s))] => Some(s.clone()),
+        other => panic!("unexpected NativeEncodingAttribute arguments: {other:?}"),
+    }
+}
+
+#[test]
+fn linux_wchar_is_utf32() {
+    let index = open_index();
+    let fields: Vec<_> = index
+        .expect("WCharTest", "WideName")
+        .fields()
+        .map(|f| (f.name().to_string(), f.ty(), encoding(&f)))
+        .collect();
+    let utf32 = Some("utf-32".to_string());
+    assert_eq!(
+        fields,
+        [
+            (
+                "name".to_string(),
+                Type::ArrayFixed(Box::new(Type::U32), 8),
+                utf32.clone()
+            ),
+            ("u16".to_string(), Type::Char, None),
+            ("u32".to_string(), Type::U32, utf32),
+        ]
+    );
+}
+
+#[test]
+fn wchar_pointer_param_is_tagged() {
+    let index = open_index();
+    let wide_len = index
+        .expect("WCharTest", "Apis")
+        .methods()
+        .find(|m| m.name() == "wide_len")
+        .expect("wide_len");
+    let sig = wide_len.signature(&[]);
+    assert_eq!(sig.types, [Type::PtrMut(Box::new(Type::U32), 1)]);
+    let param = wide_len
+        .params()
+        .find(|p| p.sequence() == 1)
+        .expect("param s");
+    assert_eq!(encoding(&param).as_deref(), Some("utf-32"));
+}
+
+#[test]
+fn short_wchar_is_char() {
+    let index = open_index();
+    let c = index
+        .expect("WCharTest.Short", "ShortName")
+        .fields()
+        .next()
+        .expect("field c");
+    assert_eq!(c.ty(), Type::Char);
+    assert_eq!(encoding(&c), None);
+}
+
+#[test]
+fn wchar_width_must_be_two_or_four() {
+    let path = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("wchar_width_3.toml");
+    std::fs::write(
+        &path,
+        r#"
+[output]
+name = "WCharTest"
+
+[[partition]]
+namespace = "WCharTest"
+library = "wchar"
+headers = ["wchar.h"]
+wchar_width = 3
+"#,
+    )
+    .expect("write config");
+    let err = bnd_winmd::config::load_config(&path).expect_err("wchar_width = 3 must fail");
+    assert!(
+        format!("{err:#}").contains("wchar_width must be 2 or 4"),
+        "{err:#}"
+    );
+}
@@ -0,0 +1,95 @@
+//! `__int128`, `long double` and `_Complex` mapped per the partition's
+//! `int128` / `long_double` settings; by-value `_Complex` is skipped.
+
+use std::path::Path;
+use std::sync::LazyLock;
+
+use windows_metadata::Type;
+
+static WIDE_OUTPUT: LazyLock<bnd_winmd::GenerateOutput> = LazyLock::new(|| {
+    let path =
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("../tests/fixtures/widetypes/widetypes.toml");
+    let cfg = bnd_winmd::config::load_config(&path).expect("load widetypes config");
+    bnd_winmd::generate_from_config(&cfg, path.parent().unwrap()).expect("generate widetypes winmd")
+});
+
+fn open_index() -> windows_metadata::reader::TypeIndex {
+    let file = windows_metadata::reader::File::new(WIDE_OUTPUT.bytes.clone()).expect("parse winmd");
+    windows_metadata::reader::TypeIndex::new(vec![file])
+}
+
+fn array(element: Type, len: usize) -> Type {
+    Type::ArrayFixed(Box::new(element), len)
+}
+
+#[test]
+fn struct_fields_use_lossy_mappings() {
+    let index = open_index();
+    let fields: Vec<(String, Type)> = index
+        .expect("WideTypes", "WideValues")
+        .fields()
+        .map(|f| (f.name().to_string(), f.ty()))
+        .collect();
+    assert_eq!(
+        fields,
+        [
+            ("big".to_string(), array(Type::U64, 2)),
+            ("ubig".to_string(), array(Type::U64, 2)),
+            ("ld".to_string(), array(Type::U8, 16)),
+            ("z".to_string(), array(Type::F64, 2)),
+            ("zf".to_string(), array(Type::F32, 2)),
+        ]
+    );
+    assert_eq!(
+        WIDE_OUTPUT.diagnostics.with_code("layout_mismatch").count(),
+        0
+    );
+}
+
+#[test]
+fn int128_typedef_is_extracted() {
+    let index = open_index();
+    let value = index
+        .expect("WideTypes", "wide_s128")
+        .fields()
+        .next()
+        .expect("Value field");
+    assert_eq!(value.ty(), array(Type::U64, 2));
+}
+
+#[test]
+fn complex_by_value_is_skipped() {
+    // `[f64; 2]` has the layout of `double _Complex` but not its calling
+    // convention, so functions taking or returning one are skipped.
+    let path =
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("../tests/fixtures/widetypes/widetypes.toml");
+    let partitions = bnd_winmd::extract(&path).expect("extract widetypes");
+    let wide = partitions
+        .iter()
+        .find(|p| p.namespace == "WideTypes")
+        .expect("WideTypes partition");
+    assert!(wide.functions.iter().all(|f| f.name != "wide_cabs"));
+    let cabs = wide
+        .skipped
+        .iter()
+        .find(|s| s.name == "wide_cabs")
+        .expect("wide_cabs reported as skipped");
+    assert!(
+        matches!(&cabs.reason, bnd_winmd::model::SkipReason::Unsupported(r) if r.contains("_Complex")),
+        "{:?}",
+        cabs.reason
+    );
+}
+
+#[test]
+fn long_double_as_f64() {
+    let index = open_index();
+    let sqrtl = index
+        .expect("WideTypes.F64", "Apis")
+        .methods()
+        .find(|m| m.name() == "wide_sqrtl")
+        .expect("wide_sqrtl");
+    let sig = sqrtl.signature(&[]);
+    assert_eq!(sig.return_type, Type::F64);
+    assert_eq!(sig.types, [Type::F64]);
+}
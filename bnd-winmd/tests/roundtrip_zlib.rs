@@ -3,6 +3,8 @@
 use std::path::Path;
 use std::sync::LazyLock;
 
+use windows_metadata::reader::HasAttributes;
+
 static ZLIB_WINMD: LazyLock<Vec<u8>> = LazyLock::new(|| {
     let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("../tests/fixtures/zlib/zlib.toml");
     bnd_winmd::generate(&path).expect("generate zlib winmd")
@@ -198,3 +200,22 @@ fn zlib_pinvoke() {
         "library name should be 'z'"
     );
 }
+
+#[test]
+fn zlib_library_soname() {
+    let index = open_index();
+
+    // The ImplMap keeps the link name; the soname rides on the class.
+    let attr = index
+        .expect("Zlib", "Apis")
+        .find_attribute("LibrarySonameAttribute")
+        .expect("Apis should carry LibrarySonameAttribute");
+    let values: Vec<_> = attr.value().into_iter().map(|(_, v)| v).collect();
+    assert_eq!(
+        values,
+        [
+            windows_metadata::Value::Utf8("z".to_string()),
+            windows_metadata::Value::Utf8("libz.so.1".to_string()),
+        ]
+    );
+}
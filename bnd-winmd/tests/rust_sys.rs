@@ -0,0 +1,126 @@
+//! Rust `-sys` backend: render fixtures straight from the model and check
+//! the source compiles.
+
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::LazyLock;
+
+fn fixture(name: &str) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("../tests/fixtures")
+        .join(name)
+        .join(format!("{name}.toml"))
+}
+
+static SIMPLE: LazyLock<bnd_winmd::emit::GeneratedTree> =
+    LazyLock::new(|| bnd_winmd::rust_sys(&fixture("simple")).expect("render simple"));
+
+static MULTI: LazyLock<bnd_winmd::emit::GeneratedTree> =
+    LazyLock::new(|| bnd_winmd::rust_sys(&fixture("multi")).expect("render multi"));
+
+static LAYOUT: LazyLock<bnd_winmd::emit::GeneratedTree> =
+    LazyLock::new(|| bnd_winmd::rust_sys(&fixture("layout")).expect("render layout"));
+
+/// Type-check `source` as a library crate with the rustc running the tests.
+fn assert_compiles(name: &str, source: &str) {
+    let dir = Path::new(env!("CARGO_TARGET_TMPDIR")).join("rust_sys");
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join(format!("{name}.rs"));
+    std::fs::write(&path, source).unwrap();
+    let output = Command::new(std::env::var("RUSTC").unwrap_or_else(|_| "rustc".into()))
+        .args([
+            "--edition",
+            "2021",
+            "--crate-type",
+            "lib",
+            "--emit",
+            "metadata",
+        ])
+        .arg("--out-dir")
+        .arg(&dir)
+        .arg(&path)
+        .output()
+        .expect("run rustc");
+    assert!(
+        output.status.success(),
+        "{}\n{source}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+}
+
+#[test]
+fn simple_renders_and_compiles() {
+    let module = &SIMPLE.modules["SimpleTest"];
+    for expected in [
+        "pub type Color = u32;",
+        "pub const COLOR_BLUE: Color = 2;",
+        "pub const SIGNAL_IOT: Signal = 6;",
+        "pub const SIGNAL_ABRT: Signal = SIGNAL_IOT;",
+        "#[repr(C)]\n#[derive(Clone, Copy)]\npub union Value {",
+        "#[repr(C, align(64))]\n#[derive(Clone, Copy)]\npub struct CacheAligned {",
+        "pub type CompareFunc = Option<unsafe extern \"C\" fn(a: *const core::ffi::c_void, b: *const core::ffi::c_void) -> i32>;",
+        "#[link(name = \"simple\")]\nunsafe extern \"C\" {",
+        "    pub fn create_widget(name: *const i8, bounds: Rect, out: *mut Widget) -> i32;",
+        "    pub fn destroy_widget(w: *mut Widget);",
+        "pub const MAX_WIDGETS: i32 = 256;",
+    ] {
+        assert!(
+            module.contains(expected),
+            "missing `{expected}` in:\n{module}"
+        );
+    }
+    // Fields alone give Rect its alignment.
+    assert!(module.contains("#[repr(C)]\n#[derive(Clone, Copy)]\npub struct Rect {"));
+
+    assert_compiles("simple", &SIMPLE.to_single_file());
+}
+
+#[test]
+fn cross_namespace_references_use_relative_paths() {
+    let widgets = &MULTI.modules["MultiTest.Widgets"];
+    assert!(
+        widgets.contains("pub color: super::super::MultiTest::Types::Color,"),
+        "{widgets}"
+    );
+    assert!(
+        widgets.contains("bounds: super::super::MultiTest::Types::Rect"),
+        "{widgets}"
+    );
+    assert_compiles("multi", &MULTI.to_single_file());
+
+    // The directory layout declares every level of the namespace tree.
+    let dir = Path::new(env!("CARGO_TARGET_TMPDIR")).join("rust_sys_tree");
+    let _ = std::fs::remove_dir_all(&dir);
+    MULTI.write(&dir).expect("write tree");
+    let root = std::fs::read_to_string(dir.join("mod.rs")).unwrap();
+    assert!(root.contains("pub mod MultiTest;"), "{root}");
+    let multi = std::fs::read_to_string(dir.join("MultiTest/mod.rs")).unwrap();
+    assert!(multi.contains("pub mod Types;") && multi.contains("pub mod Widgets;"));
+    assert!(dir.join("MultiTest/Widgets/mod.rs").exists());
+}
+
+#[test]
+fn misaligned_bitfields_keep_clang_offsets() {
+    let module = &LAYOUT.modules["LayoutTest"];
+    assert!(
+        module.contains(
+            "#[repr(C, align(4))]\n#[derive(Clone, Copy)]\npub struct BitfieldMidUnit {\n    \
+             pub tag: i8,\n    pub value: [u8; 2],\n    pub _padding: [u8; 1],\n}"
+        ),
+        "{module}"
+    );
+
+    // Offsets and sizes clang reports for the same structs.
+    let checks = "
+use LayoutTest::{BitfieldMidUnit, BitfieldStraddle};
+use LayoutTest::Bitfields::StorageStraddle;
+const _: () = assert!(core::mem::offset_of!(BitfieldMidUnit, value) == 1);
+const _: () = assert!(core::mem::size_of::<BitfieldMidUnit>() == 4);
+const _: () = assert!(core::mem::align_of::<BitfieldMidUnit>() == 4);
+const _: () = assert!(core::mem::offset_of!(BitfieldStraddle, value) == 1);
+const _: () = assert!(core::mem::size_of::<BitfieldStraddle>() == 4);
+const _: () = assert!(core::mem::offset_of!(StorageStraddle, _bitfield0) == 1);
+const _: () = assert!(core::mem::size_of::<StorageStraddle>() == 4);
+";
+    assert_compiles("layout", &format!("{}{checks}", LAYOUT.to_single_file()));
+}
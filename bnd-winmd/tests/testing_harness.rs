@@ -0,0 +1,35 @@
+//! `bnd_winmd::testing`: round trips from an inline header.
+
+use bnd_winmd::testing::{Options, generate_from_header_str};
+use windows_metadata::Value;
+
+#[test]
+fn header_str_round_trip() {
+    let winmd = generate_from_header_str(
+        r#"
+#define POINT_MAX 100
+enum Shape { SHAPE_DOT = 1, SHAPE_LINE = 2 };
+struct Point { int x; int y; };
+int point_len(const struct Point *p);
+"#,
+        Options::new().namespace("Harness").library("harness"),
+    )
+    .expect("generate");
+
+    assert!(winmd.has_type("Point"));
+    assert!(!winmd.has_type("Missing"));
+    assert_eq!(winmd.field_names("Point"), ["x", "y"]);
+    assert_eq!(winmd.constant("POINT_MAX"), Some(Value::I32(100)));
+    assert!(winmd.constant("SHAPE_LINE").is_some());
+
+    let point_len = winmd.function("point_len");
+    assert_eq!(
+        point_len
+            .impl_map()
+            .expect("point_len ImplMap")
+            .import_scope()
+            .name(),
+        "harness"
+    );
+    assert_eq!(winmd.functions().len(), 1);
+}
@@ -0,0 +1,61 @@
+//! Watch mode: editing a header regenerates and reports the API diff.
+
+use std::ops::ControlFlow;
+
+const CONFIG: &str = r#"
+[output]
+name = "watched"
+file = "watched.winmd"
+
+[[partition]]
+namespace = "WatchTest"
+library = "watched"
+headers = ["watched.h"]
+"#;
+
+#[test]
+fn header_edit_regenerates_with_diff() {
+    let dir = std::path::Path::new(env!("CARGO_TARGET_TMPDIR")).join("watch");
+    std::fs::create_dir_all(&dir).unwrap();
+    let header = dir.join("watched.h");
+    std::fs::write(&header, "int watched_first(int x);\n").unwrap();
+    let config = dir.join("watched.toml");
+    std::fs::write(&config, CONFIG).unwrap();
+
+    let mut events = 0;
+    bnd_winmd::watch(&config, |event| {
+        events += 1;
+        let out = event.output.as_ref().expect("generate watched winmd");
+        if events == 1 {
+            assert!(event.changed.is_empty(), "{:?}", event.changed);
+            assert!(event.diff.is_none());
+            let mut text = std::fs::read_to_string(&header).unwrap();
+            text.push_str("int watched_second(int x);\n");
+            std::fs::write(&header, text).unwrap();
+            return ControlFlow::Continue(());
+        }
+
+        assert!(!out.bytes.is_empty());
+        assert!(
+            event.changed.iter().any(|p| p.ends_with("watched.h")),
+            "{:?}",
+            event.changed
+        );
+        let diff = event
+            .diff
+            .as_ref()
+            .expect("diff against first run")
+            .as_ref()
+            .expect("diff winmds");
+        assert!(!diff.is_breaking(), "{diff}");
+        assert!(
+            diff.changes
+                .iter()
+                .any(|c| c.item.ends_with("watched_second")),
+            "{diff}"
+        );
+        ControlFlow::Break(())
+    })
+    .expect("watch");
+    assert_eq!(events, 2);
+}
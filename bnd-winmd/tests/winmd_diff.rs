@@ -0,0 +1,177 @@
+//! Tests for the winmd diff subsystem. Builds small winmds directly with the
+//! `windows-metadata` writer so no clang parsing is needed.
+
+use bnd_winmd::diff::{Severity, diff_bytes};
+use windows_metadata::{
+    FieldAttributes, MethodAttributes, MethodCallAttributes, MethodImplAttributes,
+    PInvokeAttributes, Signature, Type, TypeAttributes, Value,
+    writer::{File, HasConstant, TypeDefOrRef},
+};
+
+/// Shape of the test API: `Apis.compute(<param>) -> i32`, an optional
+/// `Apis.extra()`, a `LIMIT` constant, a `Point { x, y }` struct and an
+/// explicit-layout `Pair { a @ 0, b @ <pair_b> }`.
+struct Api {
+    param: Type,
+    extra: bool,
+    limit: u32,
+    point_y: Type,
+    pair_b: u32,
+}
+
+impl Default for Api {
+    fn default() -> Self {
+        Self {
+            param: Type::I32,
+            extra: false,
+            limit: 16,
+            point_y: Type::I32,
+            pair_b: 4,
+        }
+    }
+}
+
+fn build(api: Api) -> Vec<u8> {
+    let mut file = File::new("DiffTest");
+
+    let valuetype_ref = file.TypeRef("System", "ValueType");
+    let point = file.TypeDef(
+        "DiffTest",
+        "Point",
+        TypeDefOrRef::TypeRef(valuetype_ref),
+        TypeAttributes::Public | TypeAttributes::SequentialLayout,
+    );
+    file.ClassLayout(point, 4, 8);
+    file.Field("x", &Type::I32, FieldAttributes::Public);
+    file.Field("y", &api.point_y, FieldAttributes::Public);
+
+    let pair = file.TypeDef(
+        "DiffTest",
+        "Pair",
+        TypeDefOrRef::TypeRef(valuetype_ref),
+        TypeAttributes::Public | TypeAttributes::ExplicitLayout,
+    );
+    file.ClassLayout(pair, 4, 8);
+    let a = file.Field("a", &Type::I32, FieldAttributes::Public);
+    file.FieldLayout(a, 0);
+    let b = file.Field("b", &Type::I32, FieldAttributes::Public);
+    file.FieldLayout(b, api.pair_b);
+
+    let object_ref = file.TypeRef("System", "Object");
+    file.TypeDef(
+        "DiffTest",
+        "Apis",
+        TypeDefOrRef::TypeRef(object_ref),
+        TypeAttributes::Public | TypeAttributes::Abstract | TypeAttributes::Sealed,
+    );
+
+    let mut functions = vec![("compute", vec![api.param])];
+    if api.extra {
+        functions.push(("extra", vec![]));
+    }
+    for (name, types) in functions {
+        let sig = Signature {
+            flags: MethodCallAttributes::default(),
+            return_type: Type::I32,
+            types,
+        };
+        let method = file.MethodDef(
+            name,
+            &sig,
+            MethodAttributes::Public | MethodAttributes::HideBySig,
+            MethodImplAttributes::PreserveSig,
+        );
+        file.ImplMap(method, PInvokeAttributes::CallConvCdecl, name, "difftest");
+    }
+
+    let field = file.Field(
+        "LIMIT",
+        &Type::U32,
+        FieldAttributes::Public
+            | FieldAttributes::Static
+            | FieldAttributes::Literal
+            | FieldAttributes::HasDefault,
+    );
+    file.Constant(HasConstant::Field(field), &Value::U32(api.limit));
+
+    file.into_stream()
+}
+
+#[test]
+fn identical_winmds_have_no_changes() {
+    let report = diff_bytes(build(Api::default()), build(Api::default())).unwrap();
+    assert!(report.is_empty(), "unexpected changes:\n{report}");
+}
+
+#[test]
+fn added_function_is_additive() {
+    let new = Api {
+        extra: true,
+        ..Api::default()
+    };
+    let report = diff_bytes(build(Api::default()), build(new)).unwrap();
+    assert!(!report.is_breaking(), "unexpected breaking:\n{report}");
+    let added: Vec<_> = report.additive().map(|c| c.item.as_str()).collect();
+    assert_eq!(added, ["DiffTest.Apis.extra"]);
+}
+
+#[test]
+fn removed_function_is_breaking() {
+    let old = Api {
+        extra: true,
+        ..Api::default()
+    };
+    let report = diff_bytes(build(old), build(Api::default())).unwrap();
+    let breaking: Vec<_> = report.breaking().map(|c| c.item.as_str()).collect();
+    assert_eq!(breaking, ["DiffTest.Apis.extra"]);
+}
+
+#[test]
+fn signature_layout_and_value_changes_are_breaking() {
+    let new = Api {
+        param: Type::I64,
+        limit: 32,
+        point_y: Type::I64,
+        ..Api::default()
+    };
+    let report = diff_bytes(build(Api::default()), build(new)).unwrap();
+
+    let mut breaking: Vec<_> = report.breaking().map(|c| c.item.as_str()).collect();
+    breaking.sort();
+    assert_eq!(
+        breaking,
+        [
+            "DiffTest.Apis.LIMIT",
+            "DiffTest.Apis.compute",
+            "DiffTest.Point"
+        ]
+    );
+    assert!(
+        report
+            .changes
+            .iter()
+            .all(|c| c.severity == Severity::Breaking)
+    );
+}
+
+#[test]
+fn moved_explicit_field_is_breaking() {
+    let new = Api {
+        pair_b: 0,
+        ..Api::default()
+    };
+    let report = diff_bytes(build(Api::default()), build(new)).unwrap();
+    let changes: Vec<_> = report
+        .changes
+        .iter()
+        .map(|c| (c.severity, c.item.as_str(), c.description.as_str()))
+        .collect();
+    assert_eq!(
+        changes,
+        [(
+            Severity::Breaking,
+            "DiffTest.Pair.b",
+            "layout changed: offset 4 to 0"
+        )]
+    );
+}
@@ -0,0 +1,237 @@
+//! Tests for `emit::merge_winmds`. Inputs are built directly with the
+//! `windows-metadata` writer, or emitted from an IR written out here.
+
+use std::path::PathBuf;
+
+use bnd_winmd::diff::diff_bytes;
+use bnd_winmd::emit::merge_winmds;
+use windows_metadata::reader::HasAttributes;
+use windows_metadata::{
+    FieldAttributes, MethodAttributes, MethodCallAttributes, MethodImplAttributes,
+    PInvokeAttributes, Signature, Type, TypeAttributes,
+    writer::{File, TypeDefOrRef},
+};
+
+/// A winmd with a `<namespace>.Point { x: <x_ty> }` struct and an `Apis`
+/// class holding one function named `<function>`.
+fn build(assembly: &str, namespace: &str, x_ty: Type, function: &str) -> Vec<u8> {
+    let mut file = File::new(assembly);
+
+    let valuetype_ref = file.TypeRef("System", "ValueType");
+    let point = file.TypeDef(
+        namespace,
+        "Point",
+        TypeDefOrRef::TypeRef(valuetype_ref),
+        TypeAttributes::Public | TypeAttributes::SequentialLayout,
+    );
+    file.ClassLayout(point, 8, 8);
+    file.Field("x", &x_ty, FieldAttributes::Public);
+
+    let object_ref = file.TypeRef("System", "Object");
+    file.TypeDef(
+        namespace,
+        "Apis",
+        TypeDefOrRef::TypeRef(object_ref),
+        TypeAttributes::Public | TypeAttributes::Abstract | TypeAttributes::Sealed,
+    );
+    let sig = Signature {
+        flags: MethodCallAttributes::default(),
+        return_type: Type::Void,
+        types: vec![Type::named(namespace, "Point")],
+    };
+    let method = file.MethodDef(
+        function,
+        &sig,
+        MethodAttributes::Public | MethodAttributes::HideBySig,
+        MethodImplAttributes::PreserveSig,
+    );
+    file.ImplMap(method, PInvokeAttributes::CallConvCdecl, function, "merge");
+    file.Param("p", 1, Default::default());
+
+    file.into_stream()
+}
+
+fn write_input(name: &str, bytes: Vec<u8>) -> PathBuf {
+    let path = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join(name);
+    std::fs::write(&path, bytes).expect("write merge input");
+    path
+}
+
+#[test]
+fn merge_of_single_input_is_lossless() {
+    let original = build("One", "One", Type::I32, "one_fn");
+    let input = write_input("merge_single.winmd", original.clone());
+
+    let merged = merge_winmds(&[input], "One").expect("merge");
+    let report = diff_bytes(original, merged).unwrap();
+    assert!(report.is_empty(), "merge changed metadata:\n{report}");
+}
+
+#[test]
+fn merge_combines_inputs_first_wins() {
+    let first = write_input(
+        "merge_first.winmd",
+        build("First", "First", Type::I32, "first_fn"),
+    );
+    let second = write_input(
+        "merge_second.winmd",
+        build("Second", "Second", Type::I32, "second_fn"),
+    );
+    // Re-declares First.Point with a different layout — must be dropped,
+    // while its function joins First.Apis.
+    let dup = write_input(
+        "merge_dup.winmd",
+        build("Dup", "First", Type::I64, "dup_fn"),
+    );
+
+    let merged = merge_winmds(&[first, second, dup], "Combined").expect("merge");
+    let file = windows_metadata::reader::File::new(merged).expect("parse merged winmd");
+    let index = windows_metadata::reader::TypeIndex::new(vec![file]);
+
+    assert!(index.contains("First", "Point"));
+    assert!(index.contains("Second", "Point"));
+
+    let point = index.expect("First", "Point");
+    let x = point.fields().next().expect("Point.x");
+    assert_eq!(x.ty(), Type::I32, "first definition of First.Point wins");
+
+    let apis = index.expect("First", "Apis");
+    let names: Vec<_> = apis.methods().map(|m| m.name()).collect();
+    assert_eq!(names, ["first_fn", "dup_fn"]);
+
+    let second_apis = index.expect("Second", "Apis");
+    let import = second_apis
+        .methods()
+        .next()
+        .and_then(|m| m.impl_map())
+        .expect("second_fn ImplMap");
+    assert_eq!(import.import_name(), "second_fn");
+    assert_eq!(import.import_scope().name(), "merge");
+}
+
+#[test]
+fn merge_joins_apis_of_shared_namespace() {
+    let left = write_input(
+        "merge_shared_left.winmd",
+        build("Left", "Shared", Type::I32, "left_fn"),
+    );
+    let right = write_input(
+        "merge_shared_right.winmd",
+        build("Right", "Shared", Type::I32, "right_fn"),
+    );
+    // Declares left_fn again — the first input's copy is kept.
+    let again = write_input(
+        "merge_shared_again.winmd",
+        build("Again", "Shared", Type::I64, "left_fn"),
+    );
+
+    let merged = merge_winmds(&[left, right, again], "Shared").expect("merge");
+    let file = windows_metadata::reader::File::new(merged).expect("parse merged winmd");
+    let index = windows_metadata::reader::TypeIndex::new(vec![file]);
+
+    let apis: Vec<_> = index
+        .types()
+        .filter(|t| t.namespace() == "Shared" && t.name() == "Apis")
+        .collect();
+    assert_eq!(apis.len(), 1, "one Apis class per namespace");
+    let methods: Vec<_> = apis[0].methods().collect();
+    let names: Vec<_> = methods.iter().map(|m| m.name()).collect();
+    assert_eq!(names, ["left_fn", "right_fn"]);
+    for method in &methods {
+        let import = method.impl_map().expect("ImplMap");
+        assert_eq!(import.import_name(), method.name());
+        let params: Vec<_> = method.params().map(|p| p.name().to_string()).collect();
+        assert_eq!(params, ["p"], "{}", method.name());
+    }
+}
+
+/// A partition carrying every bnd-specific piece of metadata: a soname and
+/// delay-load on `Apis`, an explicit-layout union, and a stamp with a
+/// define, version, company and copyright.
+const BND_IR: &str = r#"{
+  "format": 1,
+  "assembly": "Bnd",
+  "stamp": {
+    "version": "0.0.0",
+    "config_hash": "0123456789abcdef",
+    "headers": [["bnd.h", "fedcba9876543210"]],
+    "defines": [["", "-DBND_FEATURE=1"]]
+  },
+  "metadata": { "version": "1.2.3.4", "company": "Bnd Co", "copyright": "(c) Bnd" },
+  "partitions": [{
+    "namespace": "Bnd",
+    "library": "bnd",
+    "library_soname": "libbnd.so.1",
+    "delay_load": true,
+    "structs": [{
+      "name": "Gap",
+      "size": 4,
+      "align": 4,
+      "packing": null,
+      "fields": [
+        { "name": "a", "ty": "U8", "bitfield_width": null, "bitfield_offset": null,
+          "offset": 0, "native_bitfields": [] },
+        { "name": "b", "ty": "U32", "bitfield_width": null, "bitfield_offset": null,
+          "offset": 0, "native_bitfields": [] }
+      ],
+      "is_union": true
+    }],
+    "enums": [],
+    "functions": [{
+      "name": "bnd_gap",
+      "entry_point": "bnd_gap",
+      "return_type": "Void",
+      "params": [],
+      "calling_convention": "Cdecl",
+      "library": null,
+      "inline_prototype": null
+    }],
+    "typedefs": [],
+    "constants": [],
+    "interfaces": [],
+    "skipped": []
+  }],
+  "types": { "Gap": "Bnd" },
+  "interfaces": []
+}"#;
+
+#[test]
+fn merge_keeps_bnd_metadata() {
+    let ir = bnd_winmd::ir::Ir::from_json(BND_IR).expect("parse IR");
+    let original = bnd_winmd::emit_ir(&ir).expect("emit IR");
+    let input = write_input("merge_bnd.winmd", original.clone());
+    let other = write_input(
+        "merge_bnd_other.winmd",
+        build("Other", "Other", Type::I32, "other_fn"),
+    );
+
+    let merged = merge_winmds(&[input, other], "Bnd").expect("merge");
+    let merged_path = write_input("merge_bnd_merged.winmd", merged.clone());
+
+    // Attributes, layouts and field offsets of the copied types.
+    let dump = |bytes: &[u8]| bnd_winmd::dump::dump_bytes(bytes.to_vec()).expect("dump winmd");
+    let (before, after) = (dump(&original), dump(&merged));
+    assert!(before.contains("b: u32 @ 0"), "{before}");
+    for block in before.split("\n\n").filter(|b| !b.trim().is_empty()) {
+        assert!(after.contains(block), "{block}\n---\n{after}");
+    }
+
+    let index = windows_metadata::reader::TypeIndex::new(vec![
+        windows_metadata::reader::File::new(merged.clone()).expect("parse merged winmd"),
+    ]);
+    let apis = index.expect("Bnd", "Apis");
+    for name in ["LibrarySonameAttribute", "DelayLoadAttribute"] {
+        let attr = apis.find_attribute(name).expect(name);
+        assert_eq!(attr.ctor().parent().namespace(), "Bnd.Metadata", "{name}");
+    }
+
+    // The stamp, with its defines, company and copyright, and the version.
+    assert_eq!(
+        bnd_winmd::stamp(&merged_path).expect("merged stamp"),
+        ir.stamp
+    );
+    assert_eq!(
+        bnd_winmd::assembly::AssemblyMetadata::from_bytes(&merged).expect("merged metadata"),
+        ir.metadata
+    );
+}
@@ -5,7 +5,8 @@ fn main() {
 
     // Step 1: Generate winmd from the multi-partition config
     let winmd_path = out_dir.join("multi_test.winmd");
-    bnd_winmd::run(&fixtures.join("multi.toml"), Some(&winmd_path)).expect("bnd-winmd failed");
+    bnd_winmd::run(&fixtures.join("multi.toml"), None, Some(&winmd_path))
+        .expect("bnd-winmd failed");
 
     // Step 2: Generate Rust bindings with namespace modules (no --flat)
     let bindings_path = manifest_dir.join("src/bindings.rs");
@@ -3,6 +3,12 @@ pub use bindings::*;
 
 extern crate bnd_macros as windows_link;
 
+// `size_of` / `align_of` checks for every struct, generated from clang's layouts.
+#[cfg(test)]
+mod layout_tests {
+    include!(concat!(env!("OUT_DIR"), "/layout_tests.rs"));
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
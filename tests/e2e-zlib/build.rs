@@ -5,7 +5,7 @@ fn main() {
 
     // Step 1: Generate winmd from the zlib config
     let winmd_path = out_dir.join("zlib.winmd");
-    bnd_winmd::run(&fixtures.join("zlib.toml"), Some(&winmd_path)).expect("bnd-winmd failed");
+    bnd_winmd::run(&fixtures.join("zlib.toml"), None, Some(&winmd_path)).expect("bnd-winmd failed");
 
     // Step 2: Generate Rust bindings (flat + sys for single partition)
     let bindings_path = manifest_dir.join("src/bindings.rs");
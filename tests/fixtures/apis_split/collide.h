@@ -0,0 +1,5 @@
+// A prefix whose namespace is also the first max_methods shard's.
+
+int apis1_open(void);
+int alpha(void);
+int beta(void);
@@ -0,0 +1,12 @@
+// Functions and constants spread over several Apis classes.
+
+#define SPLIT_MD_SIZE 64
+#define SPLIT_MD_BLOCK 128
+#define SPLIT_VERSION 3
+
+int split_md_init(void);
+int split_md_update(const void *data, unsigned long len);
+int split_md_final(unsigned char *out);
+int split_cipher_init(void);
+int split_cipher_update(const void *data, unsigned long len);
+int splitversion(void);
@@ -0,0 +1,24 @@
+#ifndef COLLISION_H
+#define COLLISION_H
+
+/* Struct and function share a name, like `struct stat` / `stat()`. */
+struct collide {
+    int value;
+};
+int collide(struct collide *out);
+
+/* A vtable promoted to an interface, sharing its name with a function:
+   reported as an interface, renamed as a struct. */
+struct collide_stream;
+struct collide_stream_vtbl {
+    void (*close)(struct collide_stream *self);
+};
+int collide_stream_vtbl(void);
+
+/* Distinct names: no finding. */
+struct apart_info {
+    int value;
+};
+int apart(struct apart_info *out);
+
+#endif /* COLLISION_H */
@@ -0,0 +1,13 @@
+#ifndef CONFLICT_COMMON_H
+#define CONFLICT_COMMON_H
+
+/* Same definition in both partitions: deduplicated, no finding. */
+struct conflict_point {
+    int x;
+    int y;
+};
+
+/* Same signature in both partitions: one delegate, no finding. */
+typedef int (*conflict_visit_fn)(struct conflict_point *at, void *ctx);
+
+#endif /* CONFLICT_COMMON_H */
@@ -0,0 +1,13 @@
+#ifndef CONFLICT_A_H
+#define CONFLICT_A_H
+
+#include "common.h"
+
+/* Defined differently by conflict_b.h. */
+struct conflict_cfg {
+    int flags;
+};
+
+int conflict_a_apply(struct conflict_cfg *cfg, struct conflict_point *at);
+
+#endif /* CONFLICT_A_H */
@@ -0,0 +1,13 @@
+#ifndef CONFLICT_B_H
+#define CONFLICT_B_H
+
+#include "common.h"
+
+struct conflict_cfg {
+    long long flags;
+    int version;
+};
+
+int conflict_b_apply(struct conflict_cfg *cfg, struct conflict_point *at);
+
+#endif /* CONFLICT_B_H */
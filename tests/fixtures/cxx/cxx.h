@@ -0,0 +1,37 @@
+#ifndef CXX_H
+#define CXX_H
+
+#include <stdint.h>
+
+extern "C" {
+
+typedef struct CxxPoint {
+    int32_t x;
+    int32_t y;
+} CxxPoint;
+
+int32_t cxx_point_sum(const CxxPoint *p);
+
+}
+
+namespace geo {
+
+struct Size {
+    int32_t w;
+    int32_t h;
+};
+
+/* C++ linkage: imported by mangled name. */
+int32_t area(const Size *s);
+
+namespace detail {
+extern "C" int32_t geo_detail_version(void);
+}
+
+} // namespace geo
+
+namespace {
+int32_t cxx_hidden(void);
+}
+
+#endif /* CXX_H */
@@ -0,0 +1,5 @@
+/* Functions of an optional library, resolved on first call. */
+#pragma once
+
+double cos(double x);
+double sqrt(double x);
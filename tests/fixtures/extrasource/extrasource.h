@@ -0,0 +1,7 @@
+/* The struct is only forward-declared; the config completes it. */
+#pragma once
+
+typedef struct Session Session;
+
+Session *session_open(const char *name);
+void session_close(Session *session);
@@ -0,0 +1,12 @@
+#pragma once
+
+// Callback typedefs with explicit calling conventions. Parsed for an i686
+// target (see features.toml) — x86_64 clang ignores `stdcall`.
+
+typedef int (__attribute__((stdcall)) *StdcallCallback)(int value);
+typedef int (__attribute__((fastcall)) *FastcallCallback)(int value);
+typedef int (*CdeclCallback)(int value);
+
+// Functions without a convention in the header take the partition's
+// `calling_convention` (stdcall); an explicit one is kept.
+int callconv_default(int value);
@@ -0,0 +1,153 @@
+#pragma once
+
+// Fixture for config-driven metadata features that don't fit the simple
+// fixture (which also drives e2e-simple and its checked-in bindings).
+
+// Opaque handle with an allocate/free pair — annotated via [[raii_free]].
+typedef struct feature_handle_st FeatureHandle;
+
+FeatureHandle *feature_open(const char *name);
+void feature_close(FeatureHandle *handle);
+
+// Parameter associations — `mode` accepts FeatureMode values and `flags`
+// is tied to FEATURE_FLAGS_DEFAULT via [[param_association]].
+enum FeatureMode {
+    FEATURE_MODE_READ = 0,
+    FEATURE_MODE_WRITE = 1,
+};
+
+#define FEATURE_FLAGS_DEFAULT 0x10
+
+// Constant types follow the literal's suffix and radix like C's, except
+// FEATURE_PORT, pinned by [constant_type].
+#define FEATURE_TIMEOUT 100UL
+#define FEATURE_RETRIES 3U
+#define FEATURE_ALL_BITS 0xFFFFFFFF
+#define FEATURE_BIG 5000000000
+#define FEATURE_OFFSET -1L
+#define FEATURE_PORT 8080
+
+// Character literals, parentheses, unary operators and casts.
+#define FEATURE_PATH_SEP ':'
+#define FEATURE_NEWLINE '\n'
+#define FEATURE_ERR ((FeatureNotify) -1)
+#define FEATURE_BYTE_MAX ((unsigned char) ~0)
+#define FEATURE_UINT_MAX (~0U)
+#define FEATURE_ULONG_MAX (~0UL)
+#define FEATURE_MINUS_ONE_U (-1U)
+
+// `#define` family grouped into the FeatureLevel enum via [[synthesize_enum]].
+#define FEATURE_LEVEL_LOW 1
+#define FEATURE_LEVEL_HIGH 2
+
+int feature_set_mode(FeatureHandle *handle, int mode);
+int feature_set_flags(FeatureHandle *handle, unsigned int flags);
+
+// Variadic — skipped by extraction, listed in the API report.
+void feature_log(const char *fmt, ...);
+
+// Inline function-pointer fields — each gets a synthesized delegate
+// (`FeatureAllocator_alloc`, `FeatureAllocator_release`).
+typedef struct FeatureAllocator {
+    void *(*alloc)(void *opaque, unsigned int size);
+    void (*release)(void *opaque, void *ptr);
+    void *opaque;
+} FeatureAllocator;
+
+// Delegate parameter names come from the prototype; unnamed ones fall
+// back to `paramN`.
+typedef int (*FeatureCompare)(const void *lhs, const void *rhs);
+typedef void (*FeatureNotify)(int, void *user);
+
+// Callback parameters: an inline function pointer gets a synthesized
+// delegate (`feature_on_exit_destructor`); a typedef'd one keeps its name.
+int feature_on_exit(void (*destructor)(void *), FeatureNotify notify);
+
+// Struct and function share a name — [[rename]] gives the struct `_t`.
+struct feature_stat {
+    int size;
+};
+int feature_stat(const char *path, struct feature_stat *out);
+
+// Prefix rewritten by a pattern rule; the entry point keeps the C symbol.
+int feature_legacy_reset(void);
+
+// glibc __REDIRECT-style alias: the binding must import `feature_lock64`.
+int feature_lock(int fd) __asm__("feature_lock64");
+
+// COM-style vtable, emitted as an interface via `interface = [...]`.
+typedef struct FeatureStream FeatureStream;
+typedef struct FeatureStreamVtbl {
+    int (*read)(FeatureStream *self, void *buf, int len);
+    void (*close)(FeatureStream *self);
+} FeatureStreamVtbl;
+struct FeatureStream {
+    const FeatureStreamVtbl *vtbl;
+};
+
+// GUIDs: DEFINE_GUID (attached to the FeatureStreamVtbl interface by its
+// IID_ name) and a `static const GUID` initializer.
+typedef struct _GUID {
+    unsigned int Data1;
+    unsigned short Data2;
+    unsigned short Data3;
+    unsigned char Data4[8];
+} GUID;
+#define DEFINE_GUID(name, l, w1, w2, b1, b2, b3, b4, b5, b6, b7, b8) \
+    extern const GUID name
+DEFINE_GUID(IID_FeatureStream, 0x6b29fc40, 0xca47, 0x1067, 0xb3, 0x1d, 0x00,
+            0xdd, 0x01, 0x06, 0x62, 0xda);
+static const GUID CLSID_FeatureThing = {
+    0x12345678, 0x9abc, 0xdef0, {0x01, 0x23, 0x45, 0x67, 0x89, 0xab, 0xcd, 0xef}};
+
+// Enum underlying types: negative values, values past 32 bits, a fixed
+// type spelled through a typedef, and an [enum_type] override.
+enum FeatureStatus {
+    FEATURE_STATUS_EIO = -5,
+    FEATURE_STATUS_OK = 0,
+};
+enum FeatureWideMask {
+    FEATURE_WIDE_LOW = 1,
+    FEATURE_WIDE_HIGH = 0x100000000ULL,
+};
+typedef unsigned short feature_u16_t;
+enum FeatureFixed : feature_u16_t {
+    FEATURE_FIXED_MAX = 0xffff,
+};
+enum FeatureSmall {
+    FEATURE_SMALL_A = 1,
+    FEATURE_SMALL_B = 200,
+};
+
+// `const` variables with constant initializers are constants of the
+// variable's type; one without an initializer is not.
+static const int FEATURE_MAX_ITEMS = 4 * 4;
+static const feature_u16_t FEATURE_DEFAULT_PORT = 443;
+static const double FEATURE_RATIO = 0.5;
+extern const int feature_runtime_limit;
+
+// Platform typedef pinned to a primitive by [type_map].
+typedef unsigned long feature_size_t;
+feature_size_t feature_buffer_len(const void *buf, feature_size_t cap);
+
+// C strings, emitted as PCSTR / PSTR by [strings].
+const char *feature_describe(const char *name, char *buf, int len);
+
+// Parameter annotations: SAL macros (empty here, as outside MSVC), a GCC
+// nonnull list, and [[param_attributes]] overrides.
+#define _In_
+#define _In_opt_
+#define _Out_writes_bytes_(size)
+int feature_read(_In_ void *source, _Out_writes_bytes_(len) void *buf, int len,
+                 _In_opt_ const char *tag);
+int feature_copy(void *dst, const void *src, int n, int *written)
+    __attribute__((nonnull(1, 2)));
+int feature_move(void *dst, const void *src, int *moved)
+    __attribute__((nonnull(1))) __attribute__((nonnull(2)));
+// Parameters named like legacy SAL macros are names, not annotations.
+int feature_route(const int *__in, const int *__out);
+
+// Element counts: `_In_reads_` in the header, and [[param_attributes]].
+#define _In_reads_(count)
+int feature_sum(_In_reads_(count) const int *values, int count);
+void feature_fill(int *values, int n, int value);
@@ -0,0 +1,8 @@
+// Picked up by the `globbed/*.h` header pattern.
+#pragma once
+
+#include "nested/beta.h"
+
+typedef struct {
+    int a;
+} GlobAlpha;
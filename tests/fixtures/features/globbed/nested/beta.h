@@ -0,0 +1,7 @@
+// Not matched by `globbed/*.h`, but covered by the `globbed` traverse
+// directory because alpha.h includes it.
+#pragma once
+
+typedef struct {
+    int b;
+} GlobBeta;
@@ -0,0 +1,25 @@
+// Functions grouped by the handle type of their first parameter.
+
+// Opaque record handle, grouped into `GroupTest.counter`. `counter_new`
+// takes no handle and stays in `GroupTest`.
+typedef struct Counter Counter;
+Counter *counter_new(int start);
+void counter_increment(Counter *counter);
+int counter_value(const Counter *counter);
+void counter_free(Counter *counter);
+
+// Pointer typedef handle. Its lowercase spelling is the typedef itself,
+// so the namespace gets an `_api` suffix: `GroupTest.session_t_api`.
+typedef struct session *session_t;
+session_t session_open(const char *name);
+int session_send(session_t session, const void *buf, unsigned long len);
+void session_close(session_t session);
+
+// A single function per handle is not worth a namespace.
+struct point {
+    int x;
+    int y;
+};
+int point_length(const struct point *p);
+
+int group_version(void);
@@ -0,0 +1,14 @@
+/* Bitfields emitted as win32metadata-style storage units. */
+
+typedef struct StorageFlags {
+    unsigned int a : 3;
+    unsigned int b : 5;
+    unsigned int c : 24;
+    unsigned short d : 4;
+} StorageFlags;
+
+/* The u32 unit of `value` overlaps `tag`; storage covers bytes 1..4. */
+typedef struct StorageStraddle {
+    char tag;
+    unsigned int value : 20;
+} StorageStraddle;
@@ -0,0 +1,48 @@
+#pragma once
+
+// Packing and over-alignment fixtures.
+
+#pragma pack(push, 1)
+typedef struct PackedHeader {
+    unsigned char tag;
+    unsigned int length;
+    unsigned short flags;
+} PackedHeader;
+#pragma pack(pop)
+
+typedef struct AttrPacked {
+    char c;
+    long long value;
+} __attribute__((packed)) AttrPacked;
+
+#pragma pack(push, 2)
+typedef struct Pack2 {
+    char c;
+    int value;
+} Pack2;
+#pragma pack(pop)
+
+typedef struct CacheLine {
+    int value;
+} __attribute__((aligned(64))) CacheLine;
+
+/* clang starts `value` at byte 1, where no u32 can sit; the bytes it
+ * covers become a `[u8; 3]`. */
+typedef struct BitfieldStraddle {
+    char tag;
+    unsigned int value : 20;
+} BitfieldStraddle;
+
+/* `value` would flatten to a u16 at byte 1; it becomes a `[u8; 2]`, with
+ * trailing padding for the struct's 4-byte size. */
+typedef struct BitfieldMidUnit {
+    char tag;
+    unsigned int value : 12;
+} BitfieldMidUnit;
+
+/* 17 bits flatten to a u32, but clang packs `flag` into that unit's last
+ * byte, which the layout check reports. */
+typedef struct BitfieldOverlap {
+    unsigned int value : 17;
+    char flag;
+} BitfieldOverlap;
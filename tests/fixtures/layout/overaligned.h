@@ -0,0 +1,6 @@
+#pragma once
+
+// Alignment above ClassLayout's 128-byte packing limit — must be rejected.
+typedef struct PageAligned {
+    int value;
+} __attribute__((aligned(4096))) PageAligned;
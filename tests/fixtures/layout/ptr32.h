@@ -0,0 +1,7 @@
+/* Pointer-sized fields on a 32-bit target. */
+
+typedef struct Ptr32Node {
+    struct Ptr32Node *next;
+    int value;
+    void *data;
+} Ptr32Node;
@@ -0,0 +1,10 @@
+// Integer literals in every base, kept with their original spelling.
+
+// Octal file modes (sonar alone would read these as decimal).
+#define LIT_MODE_OWNER_READ 0400
+#define LIT_MODE_DEFAULT 0644
+#define LIT_OPEN_CREATE 0100
+
+#define LIT_HEX_MASK 0x1F
+#define LIT_BINARY_FLAGS 0b1010
+#define LIT_DECIMAL 42
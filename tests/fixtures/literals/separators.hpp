@@ -0,0 +1,4 @@
+// C++14 digit separators.
+#define LIT_MILLION 1'000'000
+#define LIT_BINARY_BYTE 0b1010'0101
+#define LIT_HEX_WORD 0xFFFF'0000
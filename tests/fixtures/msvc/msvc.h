@@ -0,0 +1,27 @@
+#pragma once
+
+// MSVC / Windows SDK style declarations, parsed with `flavor = "msvc"`.
+
+#include <stddef.h>
+
+#define WINAPI __stdcall
+#define CALLBACK __stdcall
+#define MSVC_API __declspec(dllimport)
+
+typedef unsigned long DWORD;
+typedef unsigned __int64 ULONG64;
+
+typedef struct MSVC_POINT {
+    long x;
+    long y;
+} MSVC_POINT;
+
+typedef struct MSVC_INFO {
+    DWORD size;
+    ULONG64 total;
+    wchar_t name[8];
+} MSVC_INFO;
+
+MSVC_API DWORD WINAPI MsvcGetVersion(void);
+MSVC_API int WINAPI MsvcGetInfo(MSVC_INFO *info);
+__declspec(noreturn) void MsvcAbort(void);
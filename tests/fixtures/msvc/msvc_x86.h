@@ -0,0 +1,12 @@
+#pragma once
+
+// Parsed for i686-pc-windows-msvc, where `__stdcall` is a distinct
+// convention.
+
+#include "msvc.h"
+
+typedef int (CALLBACK *MSVC_ENUMPROC)(MSVC_POINT *point, long param);
+
+MSVC_API int WINAPI MsvcEnumPoints(MSVC_ENUMPROC proc, long param);
+MSVC_API int __cdecl MsvcFormat(char *buffer, DWORD value);
+MSVC_API int __fastcall MsvcFast(int value);
@@ -22,3 +22,15 @@ typedef int (*CompareFunc)(const void* a, const void* b);
 #define MAX_WIDGETS 256
 #define DEFAULT_WIDTH 800
 #define DEFAULT_HEIGHT 600
+
+// Only forward-declared here; widget.h defines Gadget, so the reference
+// resolves to MultiTest.Widgets.Gadget. Hidden is defined nowhere and
+// stays `void *`.
+struct Gadget;
+struct Hidden;
+int gadget_id(const struct Gadget* gadget);
+
+// A typedef of the struct MultiTest.Widgets owns: emitted as an alias
+// whose Value references MultiTest.Widgets.Gadget.
+typedef struct Gadget GadgetAlias;
+void hidden_free(struct Hidden* hidden);
@@ -8,6 +8,12 @@ typedef struct {
     Color color;
 } Widget;
 
+// Defines the struct types.h only forward-declares.
+struct Gadget {
+    int id;
+    Widget* owner;
+};
+
 // Functions referencing types from types.h
 int create_widget(const char* name, Rect bounds, Widget* out);
 void destroy_widget(Widget* w);
@@ -0,0 +1,9 @@
+#pragma once
+
+// Directly inside the traverse directory — stays in NestedTest.
+typedef unsigned int nested_u32;
+
+struct nested_header {
+    nested_u32 len;
+    nested_u32 type;
+};
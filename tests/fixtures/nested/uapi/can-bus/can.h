@@ -0,0 +1,14 @@
+#pragma once
+
+#include <uapi/base.h>
+
+// uapi/can-bus/ → NestedTest.can_bus
+enum can_state {
+    CAN_STATE_ACTIVE = 0,
+    CAN_STATE_OFF = 1,
+};
+
+struct can_frame {
+    nested_u32 can_id;
+    unsigned char data[8];
+};
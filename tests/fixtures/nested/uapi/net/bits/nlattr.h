@@ -0,0 +1,8 @@
+#pragma once
+
+// Moved to NestedTest.attr by the partition's `overrides`, which win over
+// the uapi/net/bits/ → NestedTest.net.bits directory rule.
+struct nlattr {
+    unsigned short nla_len;
+    unsigned short nla_type;
+};
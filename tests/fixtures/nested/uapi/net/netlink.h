@@ -0,0 +1,15 @@
+#pragma once
+
+#include <uapi/base.h>
+#include <uapi/net/bits/nlattr.h>
+
+// uapi/net/ → NestedTest.net
+#define NLMSG_ALIGNTO 4
+
+struct nlmsg {
+    struct nested_header hdr;
+    unsigned short flags;
+    struct nlattr attr;
+};
+
+int nlmsg_send(struct nlmsg *msg);
@@ -0,0 +1,49 @@
+#pragma once
+
+// A macOS-framework style header: C declarations mixed with Objective-C
+// classes, parsed with `language = "objective-c"`. The C parts are
+// extracted; the Objective-C ones are skipped without failing `strict`.
+
+@class ObjcWidget;
+
+typedef struct ObjcPoint {
+    double x;
+    double y;
+} ObjcPoint;
+
+typedef enum ObjcMode {
+    OBJC_MODE_IDLE,
+    OBJC_MODE_ACTIVE,
+} ObjcMode;
+
+#define OBJC_LIMIT 64
+
+@protocol ObjcDrawing
+- (void)drawAt:(ObjcPoint)point;
+@end
+
+@interface ObjcWidget <ObjcDrawing> {
+    int _count;
+}
+@property int count;
+- (instancetype)initWithOrigin:(ObjcPoint)origin;
++ (ObjcWidget *)widget;
+@end
+
+@interface ObjcWidget (Extras)
+- (void)reset;
+@end
+
+// Object, `id`, `SEL` and block types in C declarations are opaque
+// pointers.
+typedef struct ObjcHolder {
+    ObjcWidget *widget;
+    id delegate;
+    ObjcMode mode;
+} ObjcHolder;
+
+int objc_point_count(const ObjcPoint *points, int n);
+ObjcWidget *objc_widget_create(ObjcPoint origin);
+void objc_widget_release(id widget);
+SEL objc_selector(const char *name);
+void objc_run(void (^block)(int value));
@@ -0,0 +1,19 @@
+#ifndef SHIM_H
+#define SHIM_H
+
+#include <stdint.h>
+
+/* Exported — imported directly. */
+int shim_exported(int x);
+
+/* Prototype followed by its inline definition. */
+static inline uint32_t shim_swap32(uint32_t v);
+static inline uint32_t shim_swap32(uint32_t v) {
+    return (v >> 24) | ((v >> 8) & 0xff00) | ((v << 8) & 0xff0000) | (v << 24);
+}
+
+static inline void shim_clear(int *p) { *p = 0; }
+
+static inline int shim_zero(void) { return 0; }
+
+#endif /* SHIM_H */
@@ -98,6 +98,15 @@ enum BitfieldKind {
     BF_KIND_VALUE = 2,
 };
 
+// Enum with duplicate values — both spellings of 6 must survive, and
+// generators spell the later one as an alias of the first.
+enum Signal {
+    SIGNAL_HUP  = 1,
+    SIGNAL_IOT  = 6,
+    SIGNAL_ABRT = SIGNAL_IOT,
+    SIGNAL_DEFAULT = 1,
+};
+
 struct WithBitfield {
     const char *name;
     enum BitfieldKind kind:8;
@@ -139,3 +148,10 @@ struct EmbeddingAligned {
     struct AlignedInner aligned_member;
     int after;
 };
+
+// Anonymous enum field — extracted as the synthetic enum
+// `WithAnonEnum_mode` instead of skipping the struct.
+struct WithAnonEnum {
+    enum { ANON_MODE_OFF, ANON_MODE_ON = 4 } mode;
+    int flags;
+};
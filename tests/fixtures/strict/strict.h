@@ -0,0 +1,10 @@
+#ifndef STRICT_H
+#define STRICT_H
+
+#define STRICT_VERSION 3
+#define STRICT_NAME "strict"
+
+int strict_open(const char *path);
+int strict_log(const char *fmt, ...);
+
+#endif /* STRICT_H */
@@ -0,0 +1,6 @@
+/* Parsed with -fshort-wchar: wchar_t is a UTF-16 code unit. */
+#include <stddef.h>
+
+typedef struct ShortName {
+    wchar_t c;
+} ShortName;
@@ -0,0 +1,11 @@
+/* Wide characters at the target's width (4 bytes on Linux). */
+#include <stddef.h>
+#include <uchar.h>
+
+typedef struct WideName {
+    wchar_t name[8];
+    char16_t u16;
+    char32_t u32;
+} WideName;
+
+size_t wide_len(const wchar_t *s);
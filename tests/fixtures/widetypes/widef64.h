@@ -0,0 +1,3 @@
+/* `long double` lowered to f64. */
+
+long double wide_sqrtl(long double x);
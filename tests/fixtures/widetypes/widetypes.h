@@ -0,0 +1,13 @@
+/* Types with no WinMD equivalent, mapped lossily on request. */
+
+typedef struct WideValues {
+    __int128 big;
+    unsigned __int128 ubig;
+    long double ld;
+    double _Complex z;
+    float _Complex zf;
+} WideValues;
+
+typedef __int128 wide_s128;
+
+double wide_cabs(double _Complex z);